@@ -0,0 +1,42 @@
+//! 大きなクレームを持つJWTのデコード性能を比較するベンチマーク
+//!
+//! `cargo bench --bench jwt_decode` で実行します。
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mobile::{decode_jwt, decode_jwt_bytes};
+
+/// 指定したおおよそのサイズ（バイト）のペイロードを持つJWTを生成します
+fn make_large_jwt(payload_size: usize) -> String {
+    let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"HS256","typ":"JWT"}"#);
+    let mut claims = String::from(r#"{"sub":"1234567890","roles":["#);
+    let mut i = 0;
+    while claims.len() < payload_size {
+        if i > 0 {
+            claims.push(',');
+        }
+        claims.push_str(&format!(r#"{{"id":{},"name":"role-{}","enabled":true}}"#, i, i));
+        i += 1;
+    }
+    claims.push_str("]}");
+    let payload = URL_SAFE_NO_PAD.encode(claims);
+    format!("{}.{}.signature", header, payload)
+}
+
+fn bench_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("jwt_decode");
+    for size in [1_024, 10 * 1_024, 100 * 1_024] {
+        let jwt = make_large_jwt(size);
+        group.throughput(Throughput::Bytes(jwt.len() as u64));
+        group.bench_with_input(BenchmarkId::new("decode_jwt", size), &jwt, |b, jwt| {
+            b.iter(|| decode_jwt(black_box(jwt)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("decode_jwt_bytes", size), &jwt, |b, jwt| {
+            b.iter(|| decode_jwt_bytes(black_box(jwt)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_decode);
+criterion_main!(benches);
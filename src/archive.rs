@@ -0,0 +1,440 @@
+//! ZIPアーカイブモジュール
+//!
+//! このモジュールは、ZIPアーカイブのエントリの一覧・展開と、
+//! ファイルの一覧からのアーカイブ作成を行う`ZipArchive`をエクスポートします。
+//! データのエクスポート・バックアップ機能で使用します。
+//!
+//! 展開時は、エントリ名に含まれる`..`や絶対パスによって展開先のディレクトリの外に
+//! 書き込まれる攻撃（Zip Slip）を防ぐため、そのようなエントリとシンボリックリンクを
+//! 拒否します。また、展開後のサイズの上限を指定して圧縮爆弾を防ぎます。
+
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek, Write};
+use std::path::{Component, Path};
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+use zip::result::ZipError;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+use crate::file_io::write_atomic_with;
+
+/// アーカイブの読み込み元（ファイルまたはメモリ上のデータ）
+trait ArchiveSource: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ArchiveSource for T {}
+
+/// ZIPアーカイブの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum ArchiveError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access file: {0}")]
+    IoError(String),
+    /// ZIPアーカイブとして不正、または未対応の形式（暗号化など）の場合
+    #[error("Invalid archive: {0}")]
+    InvalidArchive(String),
+    /// 指定した名前のエントリが存在しない場合
+    #[error("Entry not found: {0}")]
+    EntryNotFound(String),
+    /// エントリ名が展開先の外を指す、またはシンボリックリンクの場合
+    #[error("Unsafe entry path: {0}")]
+    UnsafePath(String),
+    /// 展開後のサイズが上限を超える場合
+    #[error("Entry size exceeds the limit of {0} bytes")]
+    SizeLimitExceeded(u64),
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(error: io::Error) -> Self {
+        ArchiveError::IoError(error.to_string())
+    }
+}
+
+impl From<ZipError> for ArchiveError {
+    fn from(error: ZipError) -> Self {
+        match error {
+            ZipError::Io(e) => ArchiveError::IoError(e.to_string()),
+            other => ArchiveError::InvalidArchive(other.to_string()),
+        }
+    }
+}
+
+/// アーカイブ内のエントリの情報
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ZipEntry {
+    /// アーカイブ内のパス（区切り文字は`/`）
+    pub name: String,
+    /// 展開後のサイズ（バイト）
+    pub size: u64,
+    /// 圧縮後のサイズ（バイト）
+    pub compressed_size: u64,
+    /// ディレクトリかどうか
+    pub is_dir: bool,
+    /// 暗号化されているかどうか（暗号化されたエントリは展開できません）
+    pub encrypted: bool,
+    /// 展開後のデータのCRC-32
+    pub crc32: u32,
+}
+
+/// アーカイブに追加するファイル
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ZipInputFile {
+    /// アーカイブ内のパス（例: `photos/2026/a.jpg`）
+    pub name: String,
+    /// 追加するファイルのパス
+    pub path: String,
+}
+
+/// エントリ名が展開先のディレクトリ内に収まる相対パスか確認します
+fn check_entry_name(name: &str) -> Result<(), ArchiveError> {
+    let path = Path::new(name);
+    let safe = !name.is_empty()
+        && !name.contains('\\')
+        && path.components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+    if !safe {
+        return Err(ArchiveError::UnsafePath(name.to_string()));
+    }
+    Ok(())
+}
+
+/// ZIPアーカイブ
+///
+/// エントリの一覧と個別の展開、およびファイルの一覧からの作成に対応します。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let archive = ZipArchive::open(backup_path)?;
+/// for entry in archive.entries()? {
+///     // entry.name, entry.size
+/// }
+/// let json = archive.extract_entry("data/settings.json".to_string(), 1 << 20)?;
+/// archive.extract_entry_to_dir("photos/a.jpg".to_string(), restore_dir, 50 << 20)?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct ZipArchive {
+    archive: Mutex<zip::ZipArchive<Box<dyn ArchiveSource>>>,
+}
+
+impl ZipArchive {
+    /// 読み込み元からアーカイブを開きます
+    fn with_source(source: Box<dyn ArchiveSource>) -> Result<Arc<Self>, ArchiveError> {
+        Ok(Arc::new(Self {
+            archive: Mutex::new(zip::ZipArchive::new(source)?),
+        }))
+    }
+
+    /// 指定したエントリを上限付きで`writer`に書き出し、書き出したバイト数を返します
+    fn copy_entry<W: Write>(
+        &self,
+        name: &str,
+        max_size: u64,
+        writer: &mut W,
+    ) -> Result<u64, ArchiveError> {
+        let mut archive = self.archive.lock()
+            .map_err(|_| ArchiveError::MutexPoisoned)?;
+        let mut entry = match archive.by_name(name) {
+            Ok(entry) => entry,
+            Err(ZipError::FileNotFound) => {
+                return Err(ArchiveError::EntryNotFound(name.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        if entry.is_dir() || entry.is_symlink() {
+            return Err(ArchiveError::UnsafePath(name.to_string()));
+        }
+        let copied = io::copy(&mut (&mut entry).take(max_size.saturating_add(1)), writer)?;
+        if copied > max_size {
+            return Err(ArchiveError::SizeLimitExceeded(max_size));
+        }
+        Ok(copied)
+    }
+}
+
+#[uniffi::export]
+impl ZipArchive {
+    /// ファイルからアーカイブを開きます
+    ///
+    /// # Arguments
+    /// * `path` - ZIPファイルのパス
+    ///
+    /// # Errors
+    /// * `ArchiveError::IoError` - ファイルを開けない場合
+    /// * `ArchiveError::InvalidArchive` - ZIPアーカイブとして不正な場合
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, ArchiveError> {
+        Self::with_source(Box::new(File::open(path)?))
+    }
+
+    /// メモリ上のデータからアーカイブを開きます
+    ///
+    /// # Arguments
+    /// * `data` - ZIPアーカイブのデータ
+    ///
+    /// # Errors
+    /// * `ArchiveError::InvalidArchive` - ZIPアーカイブとして不正な場合
+    #[uniffi::constructor]
+    pub fn from_bytes(data: Vec<u8>) -> Result<Arc<Self>, ArchiveError> {
+        Self::with_source(Box::new(Cursor::new(data)))
+    }
+
+    /// ファイルの一覧からアーカイブを作成し、作成したアーカイブを開きます
+    ///
+    /// 各ファイルはdeflateで圧縮されます。同じディレクトリの一時ファイルに書き出した後に
+    /// 出力先のファイルを置き換えるため、作成に失敗した場合も既存のファイルは変更されません。
+    ///
+    /// # Arguments
+    /// * `output_path` - 作成するZIPファイルのパス
+    /// * `files` - 追加するファイルの一覧（アーカイブ内のパスは重複不可）
+    ///
+    /// # Errors
+    /// * `ArchiveError::UnsafePath` - アーカイブ内のパスが空、絶対パス、または`..`を含む場合
+    /// * `ArchiveError::IoError` - ファイルの読み書きに失敗した場合
+    /// * `ArchiveError::InvalidArchive` - アーカイブ内のパスが重複している場合
+    #[uniffi::constructor]
+    pub fn create(
+        output_path: String,
+        files: Vec<ZipInputFile>,
+    ) -> Result<Arc<Self>, ArchiveError> {
+        for file in &files {
+            check_entry_name(&file.name)?;
+        }
+        write_atomic_with::<ArchiveError>(Path::new(&output_path), |output| {
+            let mut writer = zip::ZipWriter::new(output);
+            let options =
+                SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+            for file in &files {
+                let mut input = File::open(&file.path)?;
+                let options = options.large_file(input.metadata()?.len() >= u32::MAX as u64);
+                writer.start_file(file.name.as_str(), options)?;
+                io::copy(&mut input, &mut writer)?;
+            }
+            writer.finish()?;
+            Ok(())
+        })?;
+        Self::open(output_path)
+    }
+
+    /// すべてのエントリの情報をアーカイブ内の順序で返します
+    ///
+    /// # Errors
+    /// * `ArchiveError::InvalidArchive` - エントリのヘッダーが不正な場合
+    /// * `ArchiveError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn entries(&self) -> Result<Vec<ZipEntry>, ArchiveError> {
+        let mut archive = self.archive.lock()
+            .map_err(|_| ArchiveError::MutexPoisoned)?;
+        (0..archive.len())
+            .map(|index| {
+                let entry = archive.by_index_raw(index)?;
+                Ok(ZipEntry {
+                    name: entry.name().to_string(),
+                    size: entry.size(),
+                    compressed_size: entry.compressed_size(),
+                    is_dir: entry.is_dir(),
+                    encrypted: entry.encrypted(),
+                    crc32: entry.crc32(),
+                })
+            })
+            .collect()
+    }
+
+    /// エントリを展開してバイト列で返します
+    ///
+    /// # Arguments
+    /// * `name` - エントリ名
+    /// * `max_size` - 展開後のサイズの上限（バイト）
+    ///
+    /// # Errors
+    /// * `ArchiveError::EntryNotFound` - エントリが存在しない場合
+    /// * `ArchiveError::UnsafePath` - ディレクトリまたはシンボリックリンクの場合
+    /// * `ArchiveError::SizeLimitExceeded` - 展開後のサイズが上限を超える場合
+    /// * `ArchiveError::InvalidArchive` - 暗号化されている、またはデータが破損している場合
+    /// * `ArchiveError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn extract_entry(&self, name: String, max_size: u64) -> Result<Vec<u8>, ArchiveError> {
+        let mut data = Vec::new();
+        self.copy_entry(&name, max_size, &mut data)?;
+        Ok(data)
+    }
+
+    /// エントリを展開先のディレクトリにファイルとして書き出します
+    ///
+    /// エントリ名のディレクトリ構造を保ったまま書き出し、必要なディレクトリは作成されます。
+    /// 同じパスのファイルは、一時ファイルへの展開が成功した後に置き換えられます。
+    /// 展開に失敗した場合、既存のファイルは変更されません。
+    ///
+    /// # Arguments
+    /// * `name` - エントリ名
+    /// * `destination_dir` - 展開先のディレクトリ
+    /// * `max_size` - 展開後のサイズの上限（バイト）
+    ///
+    /// # Returns
+    /// 書き出したファイルのパス
+    ///
+    /// # Errors
+    /// * `ArchiveError::UnsafePath` - エントリ名が展開先の外を指す、
+    ///   またはディレクトリ・シンボリックリンクの場合
+    /// * その他`extract_entry`と同じエラー、およびファイルの書き込みに失敗した場合の
+    ///   `ArchiveError::IoError`
+    pub fn extract_entry_to_dir(
+        &self,
+        name: String,
+        destination_dir: String,
+        max_size: u64,
+    ) -> Result<String, ArchiveError> {
+        check_entry_name(&name)?;
+        let target = Path::new(&destination_dir).join(&name);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_atomic_with(&target, |file| self.copy_entry(&name, max_size, file).map(|_| ()))?;
+        Ok(target.to_string_lossy().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("mobile_archive_{}_{}", name, std::process::id()))
+    }
+
+    /// 任意のエントリ名を持つアーカイブをメモリ上に作成します
+    fn build_zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, data) in entries {
+            writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+            writer.write_all(data).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_zip_entries_and_extract() {
+        let data = build_zip(&[("a.txt", b"hello"), ("dir/b.json", b"{}")]);
+        let archive = ZipArchive::from_bytes(data).unwrap();
+        let entries = archive.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "a.txt");
+        assert_eq!(entries[0].size, 5);
+        assert_eq!(entries[0].crc32, crc32fast::hash(b"hello"));
+        assert!(!entries[1].is_dir);
+        assert_eq!(archive.extract_entry("dir/b.json".to_string(), 100).unwrap(), b"{}");
+    }
+
+    #[test]
+    fn test_zip_create_and_extract_to_dir() {
+        let source = temp_path("source.txt");
+        let output = temp_path("created.zip");
+        let restore = temp_path("restore");
+        std::fs::write(&source, "バックアップ".repeat(100)).unwrap();
+
+        let files = vec![ZipInputFile {
+            name: "notes/backup.txt".to_string(),
+            path: source.to_string_lossy().to_string(),
+        }];
+        let archive = ZipArchive::create(output.to_string_lossy().to_string(), files).unwrap();
+        let entry = &archive.entries().unwrap()[0];
+        assert!(entry.compressed_size < entry.size);
+
+        let written = archive
+            .extract_entry_to_dir(
+                "notes/backup.txt".to_string(),
+                restore.to_string_lossy().to_string(),
+                1 << 20,
+            )
+            .unwrap();
+        assert_eq!(std::fs::read(&written).unwrap(), std::fs::read(&source).unwrap());
+
+        std::fs::remove_file(&source).unwrap();
+        std::fs::remove_file(&output).unwrap();
+        std::fs::remove_dir_all(&restore).unwrap();
+    }
+
+    #[test]
+    fn test_zip_failures_keep_existing_files() {
+        let restore = temp_path("keep");
+        std::fs::create_dir_all(&restore).unwrap();
+        let existing = restore.join("notes.txt");
+        std::fs::write(&existing, b"user data").unwrap();
+        let archive = ZipArchive::from_bytes(build_zip(&[("notes.txt", &[b'x'; 100])])).unwrap();
+        let dir = restore.to_string_lossy().to_string();
+
+        // 上限を超えるエントリや存在しないエントリの展開は既存のファイルを変更しない
+        match archive.extract_entry_to_dir("notes.txt".to_string(), dir.clone(), 10) {
+            Err(ArchiveError::SizeLimitExceeded(10)) => (),
+            other => panic!("Expected SizeLimitExceeded error, got {:?}", other),
+        }
+        std::fs::write(restore.join("other.txt"), b"other data").unwrap();
+        match archive.extract_entry_to_dir("other.txt".to_string(), dir.clone(), 1 << 20) {
+            Err(ArchiveError::EntryNotFound(_)) => (),
+            other => panic!("Expected EntryNotFound error, got {:?}", other),
+        }
+        assert_eq!(std::fs::read(&existing).unwrap(), b"user data");
+        assert_eq!(std::fs::read(restore.join("other.txt")).unwrap(), b"other data");
+
+        // 入力ファイルがない場合も既存のアーカイブを変更しない
+        let files = vec![ZipInputFile {
+            name: "missing.txt".to_string(),
+            path: restore.join("missing.txt").to_string_lossy().to_string(),
+        }];
+        match ZipArchive::create(existing.to_string_lossy().to_string(), files) {
+            Err(ArchiveError::IoError(_)) => (),
+            other => panic!("Expected IoError error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(std::fs::read(&existing).unwrap(), b"user data");
+        let mut names: Vec<_> = std::fs::read_dir(&restore)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["notes.txt", "other.txt"]);
+        std::fs::remove_dir_all(&restore).unwrap();
+    }
+
+    #[test]
+    fn test_zip_slip_is_rejected() {
+        let data = build_zip(&[("../evil.txt", b"x"), ("/abs.txt", b"y")]);
+        let archive = ZipArchive::from_bytes(data).unwrap();
+        let restore = temp_path("slip").to_string_lossy().to_string();
+        for name in ["../evil.txt", "/abs.txt"] {
+            match archive.extract_entry_to_dir(name.to_string(), restore.clone(), 100) {
+                Err(ArchiveError::UnsafePath(_)) => (),
+                other => panic!("Expected UnsafePath error for {}, got {:?}", name, other),
+            }
+        }
+        assert!(!temp_path("slip").exists());
+
+        let files = vec![ZipInputFile {
+            name: "a/../../b".to_string(),
+            path: "/dev/null".to_string(),
+        }];
+        match ZipArchive::create(temp_path("slip.zip").to_string_lossy().to_string(), files) {
+            Err(ArchiveError::UnsafePath(_)) => (),
+            other => panic!("Expected UnsafePath error, got {:?}", other.map(|_| ())),
+        }
+    }
+
+    #[test]
+    fn test_zip_errors() {
+        let data = build_zip(&[("big.bin", &[0u8; 4096])]);
+        let archive = ZipArchive::from_bytes(data).unwrap();
+        match archive.extract_entry("big.bin".to_string(), 1024) {
+            Err(ArchiveError::SizeLimitExceeded(1024)) => (),
+            other => panic!("Expected SizeLimitExceeded error, got {:?}", other.map(|d| d.len())),
+        }
+        match archive.extract_entry("missing".to_string(), 1024) {
+            Err(ArchiveError::EntryNotFound(_)) => (),
+            other => panic!("Expected EntryNotFound error, got {:?}", other.map(|d| d.len())),
+        }
+        match ZipArchive::from_bytes(b"not a zip".to_vec()) {
+            Err(ArchiveError::InvalidArchive(_)) => (),
+            other => panic!("Expected InvalidArchive error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
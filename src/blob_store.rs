@@ -0,0 +1,483 @@
+//! コンテンツアドレス型ブロブストアモジュール
+//!
+//! このモジュールは、データをBLAKE3ハッシュをキーとして保存する`BlobStore`をエクスポートします。
+//! 同じ内容のデータは1つのファイルにまとめられ、参照カウントが0になったデータは
+//! ガベージコレクションで削除されます。ダウンロードした画像や添付ファイルのキャッシュに使用します。
+//!
+//! # ディレクトリ構成
+//! ```text
+//! <directory>/index.sqlite          ハッシュ・サイズ・参照カウントの索引
+//! <directory>/objects/ab/cdef...    データ（ハッシュの先頭2文字をサブディレクトリ名に使用）
+//! <directory>/tmp/                  書き込み途中のファイル
+//! ```
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rand_core::{OsRng, RngCore};
+use rusqlite::{params, Connection, OptionalExtension};
+use thiserror::Error;
+
+/// 索引を保存するテーブルの作成SQL
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS blobs (
+    hash TEXT PRIMARY KEY,
+    size INTEGER NOT NULL,
+    ref_count INTEGER NOT NULL
+) WITHOUT ROWID";
+
+/// ブロブストアの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum BlobStoreError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access blob file: {0}")]
+    IoError(String),
+    /// ハッシュが64文字の小文字の16進数でない場合
+    #[error("Invalid blob hash: {0}")]
+    InvalidHash(String),
+    /// 指定したハッシュのデータが存在しない場合
+    #[error("Blob not found: {0}")]
+    BlobNotFound(String),
+    /// 保存されたデータのハッシュが一致しない場合
+    #[error("Blob is corrupted: {0}")]
+    CorruptedBlob(String),
+    /// 索引の操作に失敗した場合
+    #[error("Blob index error: {0}")]
+    StorageError(String),
+}
+
+impl From<io::Error> for BlobStoreError {
+    fn from(error: io::Error) -> Self {
+        BlobStoreError::IoError(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for BlobStoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        BlobStoreError::StorageError(error.to_string())
+    }
+}
+
+/// ガベージコレクションの結果
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct BlobGcReport {
+    /// 削除したデータの数（索引にないファイルを含む）
+    pub removed_blobs: u64,
+    /// 解放したバイト数
+    pub freed_bytes: u64,
+    /// 残っているデータの数
+    pub remaining_blobs: u64,
+    /// 残っているデータの合計バイト数
+    pub remaining_bytes: u64,
+}
+
+/// ハッシュが64文字の小文字の16進数か確認します
+fn check_hash(hash: &str) -> Result<(), BlobStoreError> {
+    if hash.len() != 64 || !hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) {
+        return Err(BlobStoreError::InvalidHash(hash.to_string()));
+    }
+    Ok(())
+}
+
+/// コンテンツアドレス型のブロブストア
+///
+/// `put`するたびに参照カウントが1増え、`release`で1減ります。
+/// 参照カウントが0になったデータは`get`で引き続き読み出せますが、
+/// `collect_garbage`を呼んだ時点で削除されます。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let store = BlobStore::open(cache_dir)?;
+/// let hash = store.put(image_bytes)?;
+/// let path = store.path(hash.clone())?;  // 画像の読み込みに直接使用できる
+/// store.release(hash)?;
+/// let report = store.collect_garbage()?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct BlobStore {
+    directory: PathBuf,
+    connection: Mutex<Connection>,
+}
+
+impl BlobStore {
+    /// データのファイルのパス
+    fn object_path(&self, hash: &str) -> PathBuf {
+        self.directory.join("objects").join(&hash[..2]).join(&hash[2..])
+    }
+
+    /// 書き込み途中のファイルを置くディレクトリ
+    fn temp_dir(&self) -> PathBuf {
+        self.directory.join("tmp")
+    }
+
+    /// 一時ファイルを作成します
+    fn create_temp_file(&self) -> Result<(PathBuf, File), BlobStoreError> {
+        let mut suffix = [0u8; 8];
+        OsRng.fill_bytes(&mut suffix);
+        let path = self.temp_dir().join(hex::encode(suffix));
+        let file = File::create(&path)?;
+        Ok((path, file))
+    }
+
+    /// 一時ファイルの内容を登録し、参照カウントを1増やします
+    ///
+    /// 同じ内容のデータが既にある場合、一時ファイルは削除されます。
+    fn commit_temp_file(
+        &self,
+        temp_path: &Path,
+        hash: &str,
+        size: u64,
+    ) -> Result<(), BlobStoreError> {
+        let connection = self.connection.lock()
+            .map_err(|_| BlobStoreError::MutexPoisoned)?;
+        let object_path = self.object_path(hash);
+        if object_path.exists() {
+            fs::remove_file(temp_path)?;
+        } else {
+            if let Some(parent) = object_path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(temp_path, &object_path)?;
+        }
+        connection.execute(
+            "INSERT INTO blobs (hash, size, ref_count) VALUES (?1, ?2, 1)
+             ON CONFLICT (hash) DO UPDATE SET ref_count = ref_count + 1",
+            params![hash, size],
+        )?;
+        Ok(())
+    }
+
+    /// 読み込みを一時ファイルに書き出しながらハッシュを計算し、登録します
+    fn store_from<R: Read>(&self, mut reader: R) -> Result<String, BlobStoreError> {
+        let (temp_path, mut file) = self.create_temp_file()?;
+        let result = (|| {
+            let mut hasher = blake3::Hasher::new();
+            let mut buf = [0u8; 64 * 1024];
+            let mut size = 0u64;
+            loop {
+                let n = reader.read(&mut buf)?;
+                if n == 0 {
+                    break;
+                }
+                hasher.update(&buf[..n]);
+                file.write_all(&buf[..n])?;
+                size += n as u64;
+            }
+            file.sync_all()?;
+            let hash = hasher.finalize().to_hex().to_string();
+            self.commit_temp_file(&temp_path, &hash, size)?;
+            Ok(hash)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+
+    /// 参照カウントを変更し、変更後の値を返します
+    fn add_ref(&self, hash: &str, delta: i64) -> Result<u64, BlobStoreError> {
+        check_hash(hash)?;
+        let connection = self.connection.lock()
+            .map_err(|_| BlobStoreError::MutexPoisoned)?;
+        connection
+            .query_row(
+                "UPDATE blobs SET ref_count = MAX(ref_count + ?2, 0) WHERE hash = ?1
+                 RETURNING ref_count",
+                params![hash, delta],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| BlobStoreError::BlobNotFound(hash.to_string()))
+    }
+}
+
+#[uniffi::export]
+impl BlobStore {
+    /// ブロブストアのディレクトリを開きます（存在しない場合は作成されます）
+    ///
+    /// 前回の書き込み途中で残った一時ファイルは削除されます。
+    ///
+    /// # Arguments
+    /// * `directory` - データを保存するディレクトリ
+    ///
+    /// # Errors
+    /// * `BlobStoreError::IoError` - ディレクトリを作成できない場合
+    /// * `BlobStoreError::StorageError` - 索引を開けない場合
+    #[uniffi::constructor]
+    pub fn open(directory: String) -> Result<Arc<Self>, BlobStoreError> {
+        let directory = PathBuf::from(directory);
+        fs::create_dir_all(directory.join("objects"))?;
+        let temp_dir = directory.join("tmp");
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir)?;
+        let connection = Connection::open(directory.join("index.sqlite"))?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(CREATE_TABLE)?;
+        Ok(Arc::new(Self {
+            directory,
+            connection: Mutex::new(connection),
+        }))
+    }
+
+    /// データを保存し、BLAKE3ハッシュ（64文字の16進数）を返します
+    ///
+    /// 同じ内容のデータが既にある場合は新たに書き込まず、参照カウントだけを1増やします。
+    ///
+    /// # Errors
+    /// * `BlobStoreError::IoError` - ファイルの書き込みに失敗した場合
+    /// * `BlobStoreError::StorageError` - 索引の更新に失敗した場合
+    /// * `BlobStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn put(&self, data: Vec<u8>) -> Result<String, BlobStoreError> {
+        self.store_from(data.as_slice())
+    }
+
+    /// ファイルの内容を保存し、BLAKE3ハッシュを返します
+    ///
+    /// ダウンロード済みのファイルなど、大きなデータをメモリに読み込まずに保存します。
+    /// 元のファイルは変更されません。
+    ///
+    /// # Errors
+    /// * `put`と同じエラー、およびファイルを開けない場合の`BlobStoreError::IoError`
+    pub fn put_file(&self, path: String) -> Result<String, BlobStoreError> {
+        self.store_from(File::open(path)?)
+    }
+
+    /// データを読み出します（存在しない場合は`None`）
+    ///
+    /// 読み出したデータのハッシュを検証します。
+    ///
+    /// # Errors
+    /// * `BlobStoreError::InvalidHash` - ハッシュの形式が不正な場合
+    /// * `BlobStoreError::CorruptedBlob` - データのハッシュが一致しない場合
+    /// * `BlobStoreError::IoError` - ファイルの読み込みに失敗した場合
+    pub fn get(&self, hash: String) -> Result<Option<Vec<u8>>, BlobStoreError> {
+        check_hash(&hash)?;
+        let data = match fs::read(self.object_path(&hash)) {
+            Ok(data) => data,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if blake3::hash(&data).to_hex().as_str() != hash {
+            return Err(BlobStoreError::CorruptedBlob(hash));
+        }
+        Ok(Some(data))
+    }
+
+    /// データのファイルのパスを返します（存在しない場合は`None`）
+    ///
+    /// 返したファイルは読み取り専用として扱い、変更・削除しないでください。
+    ///
+    /// # Errors
+    /// * `BlobStoreError::InvalidHash` - ハッシュの形式が不正な場合
+    pub fn path(&self, hash: String) -> Result<Option<String>, BlobStoreError> {
+        check_hash(&hash)?;
+        let path = self.object_path(&hash);
+        Ok(path.exists().then(|| path.to_string_lossy().into_owned()))
+    }
+
+    /// 参照カウントを返します（登録されていない場合は`None`）
+    ///
+    /// # Errors
+    /// * `BlobStoreError::InvalidHash` - ハッシュの形式が不正な場合
+    /// * `BlobStoreError::StorageError` - 索引の読み込みに失敗した場合
+    /// * `BlobStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn ref_count(&self, hash: String) -> Result<Option<u64>, BlobStoreError> {
+        check_hash(&hash)?;
+        let connection = self.connection.lock()
+            .map_err(|_| BlobStoreError::MutexPoisoned)?;
+        Ok(connection
+            .query_row("SELECT ref_count FROM blobs WHERE hash = ?1", params![hash], |row| {
+                row.get(0)
+            })
+            .optional()?)
+    }
+
+    /// 参照カウントを1増やし、変更後の値を返します
+    ///
+    /// # Errors
+    /// * `BlobStoreError::InvalidHash` - ハッシュの形式が不正な場合
+    /// * `BlobStoreError::BlobNotFound` - データが登録されていない場合
+    /// * `BlobStoreError::StorageError` - 索引の更新に失敗した場合
+    /// * `BlobStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn retain(&self, hash: String) -> Result<u64, BlobStoreError> {
+        self.add_ref(&hash, 1)
+    }
+
+    /// 参照カウントを1減らし、変更後の値を返します（0未満にはなりません）
+    ///
+    /// # Errors
+    /// * `retain`と同じエラー
+    pub fn release(&self, hash: String) -> Result<u64, BlobStoreError> {
+        self.add_ref(&hash, -1)
+    }
+
+    /// 参照カウントが0のデータと、索引に登録されていないファイルを削除します
+    ///
+    /// # Errors
+    /// * `BlobStoreError::IoError` - ファイルの削除に失敗した場合
+    /// * `BlobStoreError::StorageError` - 索引の更新に失敗した場合
+    /// * `BlobStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn collect_garbage(&self) -> Result<BlobGcReport, BlobStoreError> {
+        let mut connection = self.connection.lock()
+            .map_err(|_| BlobStoreError::MutexPoisoned)?;
+        let mut removed_blobs = 0;
+        let mut freed_bytes = 0;
+
+        let transaction = connection.transaction()?;
+        let unreferenced: Vec<(String, u64)> = {
+            let mut statement =
+                transaction.prepare("SELECT hash, size FROM blobs WHERE ref_count = 0")?;
+            let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+            rows.collect::<Result<_, _>>()?
+        };
+        for (hash, size) in &unreferenced {
+            match fs::remove_file(self.object_path(hash)) {
+                Ok(()) => freed_bytes += size,
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+            transaction.execute("DELETE FROM blobs WHERE hash = ?1", params![hash])?;
+            removed_blobs += 1;
+        }
+        transaction.commit()?;
+
+        // 索引の更新前に終了した場合などに残る、登録されていないファイルを削除する
+        for shard in fs::read_dir(self.directory.join("objects"))? {
+            let shard = shard?;
+            if !shard.file_type()?.is_dir() {
+                continue;
+            }
+            for entry in fs::read_dir(shard.path())? {
+                let entry = entry?;
+                let hash = format!(
+                    "{}{}",
+                    shard.file_name().to_string_lossy(),
+                    entry.file_name().to_string_lossy()
+                );
+                let registered = check_hash(&hash).is_ok()
+                    && connection
+                        .query_row("SELECT 1 FROM blobs WHERE hash = ?1", params![hash], |_| Ok(()))
+                        .optional()?
+                        .is_some();
+                if !registered {
+                    freed_bytes += entry.metadata()?.len();
+                    fs::remove_file(entry.path())?;
+                    removed_blobs += 1;
+                }
+            }
+        }
+
+        let (remaining_blobs, remaining_bytes) = connection.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(size), 0) FROM blobs",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?;
+        Ok(BlobGcReport {
+            removed_blobs,
+            freed_bytes,
+            remaining_blobs,
+            remaining_bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_blob_store_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_blob_store_put_get_dedup() {
+        let dir = temp_dir("dedup");
+        let store = BlobStore::open(dir.clone()).unwrap();
+        let hash = store.put(b"image".to_vec()).unwrap();
+        assert_eq!(hash, blake3::hash(b"image").to_hex().to_string());
+        assert_eq!(store.put(b"image".to_vec()).unwrap(), hash);
+        assert_eq!(store.ref_count(hash.clone()).unwrap(), Some(2));
+        assert_eq!(store.get(hash.clone()).unwrap(), Some(b"image".to_vec()));
+        let path = store.path(hash.clone()).unwrap().unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"image");
+
+        let missing = blake3::hash(b"missing").to_hex().to_string();
+        assert_eq!(store.get(missing.clone()).unwrap(), None);
+        assert_eq!(store.path(missing).unwrap(), None);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_blob_store_put_file() {
+        let dir = temp_dir("file");
+        let store = BlobStore::open(dir.clone()).unwrap();
+        let source = std::env::temp_dir()
+            .join(format!("mobile_blob_store_source_{}.bin", std::process::id()));
+        let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+        fs::write(&source, &data).unwrap();
+        let hash = store.put_file(source.to_string_lossy().into_owned()).unwrap();
+        assert_eq!(hash, blake3::hash(&data).to_hex().to_string());
+        assert_eq!(store.get(hash).unwrap(), Some(data));
+        assert!(source.exists());
+        let _ = fs::remove_file(source);
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_blob_store_ref_counting_and_gc() {
+        let dir = temp_dir("gc");
+        let store = BlobStore::open(dir.clone()).unwrap();
+        let kept = store.put(b"kept".to_vec()).unwrap();
+        let dropped = store.put(b"dropped!".to_vec()).unwrap();
+        assert_eq!(store.retain(kept.clone()).unwrap(), 2);
+        assert_eq!(store.release(dropped.clone()).unwrap(), 0);
+        assert_eq!(store.release(dropped.clone()).unwrap(), 0);
+
+        let orphan = store.object_path(&blake3::hash(b"orphan").to_hex());
+        fs::create_dir_all(orphan.parent().unwrap()).unwrap();
+        fs::write(&orphan, b"orphan").unwrap();
+
+        let report = store.collect_garbage().unwrap();
+        assert_eq!(report.removed_blobs, 2);
+        assert_eq!(report.freed_bytes, 8 + 6);
+        assert_eq!(report.remaining_blobs, 1);
+        assert_eq!(report.remaining_bytes, 4);
+        assert_eq!(store.get(dropped.clone()).unwrap(), None);
+        assert_eq!(store.ref_count(dropped).unwrap(), None);
+        assert_eq!(store.get(kept).unwrap(), Some(b"kept".to_vec()));
+        assert!(!orphan.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_blob_store_errors() {
+        let dir = temp_dir("errors");
+        let store = BlobStore::open(dir.clone()).unwrap();
+        match store.get("../../etc/passwd".to_string()) {
+            Err(BlobStoreError::InvalidHash(_)) => (),
+            other => panic!("Expected InvalidHash error, got {:?}", other),
+        }
+        let unknown = blake3::hash(b"unknown").to_hex().to_string();
+        match store.retain(unknown) {
+            Err(BlobStoreError::BlobNotFound(_)) => (),
+            other => panic!("Expected BlobNotFound error, got {:?}", other),
+        }
+        let hash = store.put(b"original".to_vec()).unwrap();
+        fs::write(store.object_path(&hash), b"tampered").unwrap();
+        match store.get(hash) {
+            Err(BlobStoreError::CorruptedBlob(_)) => (),
+            other => panic!("Expected CorruptedBlob error, got {:?}", other),
+        }
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,333 @@
+//! LRUキャッシュモジュール
+//!
+//! このモジュールは、容量と有効期限付きのメモリ上のキャッシュ`LruCache`をエクスポートします。
+//! デコード済みのJWTやAPIレスポンスなど、再計算・再取得のコストが高いデータの保持に使用します。
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+/// キャッシュの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CacheError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// 容量が0の場合
+    #[error("Cache capacity must be at least 1")]
+    InvalidCapacity,
+}
+
+/// キャッシュの統計情報
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct CacheStats {
+    /// `get`で値が見つかった回数
+    pub hits: u64,
+    /// `get`で値が見つからなかった（期限切れを含む）回数
+    pub misses: u64,
+    /// 容量を超えたために追い出されたエントリ数
+    pub evictions: u64,
+    /// 有効期限が切れて取り除かれたエントリ数
+    pub expirations: u64,
+    /// 現在のエントリ数（期限切れで未削除のエントリを含む）
+    pub size: u64,
+    /// 最大エントリ数
+    pub capacity: u64,
+}
+
+/// キャッシュのエントリ
+struct CacheEntry {
+    value: Vec<u8>,
+    expires_at: Option<Instant>,
+    /// 最後に使用された順序（`CacheState::order`のキー）
+    tick: u64,
+}
+
+/// キャッシュの内部状態
+struct CacheState {
+    entries: HashMap<String, CacheEntry>,
+    /// 使用順序からキーへの対応（先頭が最も古い）
+    order: BTreeMap<u64, String>,
+    next_tick: u64,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
+    expirations: u64,
+}
+
+impl CacheState {
+    /// 新しい使用順序を発行します
+    fn tick(&mut self) -> u64 {
+        self.next_tick += 1;
+        self.next_tick
+    }
+
+    /// エントリを取り除きます
+    fn remove(&mut self, key: &str) -> Option<CacheEntry> {
+        let entry = self.entries.remove(key)?;
+        self.order.remove(&entry.tick);
+        Some(entry)
+    }
+}
+
+/// 容量と有効期限付きのLRUキャッシュ
+///
+/// 容量を超えると最も長く使用されていないエントリから追い出されます。
+/// 有効期限はエントリごとに指定でき、省略した場合は作成時の既定値が使われます。
+/// 期限切れのエントリは次に参照されたとき、または追い出しの対象になったときに取り除かれます。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let cache = LruCache::new(256, Some(60_000))?;
+/// cache.put("jwt:abc".to_string(), claims_json, None)?;
+/// if let Some(claims) = cache.get("jwt:abc".to_string())? {
+///     // キャッシュされた値を使用
+/// }
+/// ```
+#[derive(uniffi::Object)]
+pub struct LruCache {
+    capacity: u64,
+    default_ttl: Option<Duration>,
+    state: Mutex<CacheState>,
+}
+
+#[uniffi::export]
+impl LruCache {
+    /// キャッシュを作成します
+    ///
+    /// # Arguments
+    /// * `capacity` - 最大エントリ数（1以上）
+    /// * `default_ttl_ms` - エントリの既定の有効期限（ミリ秒、`None`の場合は無期限）
+    ///
+    /// # Errors
+    /// * `CacheError::InvalidCapacity` - 容量が0の場合
+    #[uniffi::constructor(default(default_ttl_ms = None))]
+    pub fn new(capacity: u64, default_ttl_ms: Option<u64>) -> Result<Arc<Self>, CacheError> {
+        if capacity == 0 {
+            return Err(CacheError::InvalidCapacity);
+        }
+        Ok(Arc::new(Self {
+            capacity,
+            default_ttl: default_ttl_ms.map(Duration::from_millis),
+            state: Mutex::new(CacheState {
+                entries: HashMap::new(),
+                order: BTreeMap::new(),
+                next_tick: 0,
+                hits: 0,
+                misses: 0,
+                evictions: 0,
+                expirations: 0,
+            }),
+        }))
+    }
+
+    /// 値を保存します（同じキーの値は上書きされます）
+    ///
+    /// # Arguments
+    /// * `key` - エントリのキー
+    /// * `value` - 保存するデータ
+    /// * `ttl_ms` - このエントリの有効期限（ミリ秒、`None`の場合は既定値）
+    ///
+    /// # Errors
+    /// * `CacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    #[uniffi::method(default(ttl_ms = None))]
+    pub fn put(&self, key: String, value: Vec<u8>, ttl_ms: Option<u64>) -> Result<(), CacheError> {
+        let mut state = self.state.lock()
+            .map_err(|_| CacheError::MutexPoisoned)?;
+        let now = Instant::now();
+        let ttl = ttl_ms.map(Duration::from_millis).or(self.default_ttl);
+        state.remove(&key);
+        while state.entries.len() as u64 >= self.capacity {
+            let Some((_, oldest)) = state.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = state.entries.remove(&oldest) {
+                if entry.expires_at.is_some_and(|at| at <= now) {
+                    state.expirations += 1;
+                } else {
+                    state.evictions += 1;
+                }
+            }
+        }
+        let tick = state.tick();
+        state.order.insert(tick, key.clone());
+        state.entries.insert(
+            key,
+            CacheEntry {
+                value,
+                expires_at: ttl.map(|ttl| now + ttl),
+                tick,
+            },
+        );
+        Ok(())
+    }
+
+    /// 値を取得し、エントリを最近使用したものとして扱います
+    ///
+    /// 存在しない場合や有効期限が切れている場合は`None`を返します。
+    ///
+    /// # Errors
+    /// * `CacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn get(&self, key: String) -> Result<Option<Vec<u8>>, CacheError> {
+        let mut state = self.state.lock()
+            .map_err(|_| CacheError::MutexPoisoned)?;
+        let state = &mut *state;
+        let expired = match state.entries.get(&key) {
+            None => {
+                state.misses += 1;
+                return Ok(None);
+            }
+            Some(entry) => entry.expires_at.is_some_and(|at| at <= Instant::now()),
+        };
+        if expired {
+            state.remove(&key);
+            state.expirations += 1;
+            state.misses += 1;
+            return Ok(None);
+        }
+        let tick = state.tick();
+        let Some(entry) = state.entries.get_mut(&key) else {
+            return Ok(None);
+        };
+        state.order.remove(&entry.tick);
+        state.order.insert(tick, key);
+        entry.tick = tick;
+        state.hits += 1;
+        Ok(Some(entry.value.clone()))
+    }
+
+    /// 値を削除します
+    ///
+    /// # Returns
+    /// * `true` - エントリが存在し削除された場合
+    /// * `false` - エントリが存在しなかった場合
+    ///
+    /// # Errors
+    /// * `CacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn remove(&self, key: String) -> Result<bool, CacheError> {
+        let mut state = self.state.lock()
+            .map_err(|_| CacheError::MutexPoisoned)?;
+        Ok(state.remove(&key).is_some())
+    }
+
+    /// すべてのエントリを削除します（統計情報は保持されます）
+    ///
+    /// # Errors
+    /// * `CacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn clear(&self) -> Result<(), CacheError> {
+        let mut state = self.state.lock()
+            .map_err(|_| CacheError::MutexPoisoned)?;
+        state.entries.clear();
+        state.order.clear();
+        Ok(())
+    }
+
+    /// 統計情報を返します
+    ///
+    /// # Errors
+    /// * `CacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn stats(&self) -> Result<CacheStats, CacheError> {
+        let state = self.state.lock()
+            .map_err(|_| CacheError::MutexPoisoned)?;
+        Ok(CacheStats {
+            hits: state.hits,
+            misses: state.misses,
+            evictions: state.evictions,
+            expirations: state.expirations,
+            size: state.entries.len() as u64,
+            capacity: self.capacity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn put(cache: &LruCache, key: &str) {
+        cache.put(key.to_string(), key.as_bytes().to_vec(), None).unwrap();
+    }
+
+    #[test]
+    fn test_lru_cache_get_and_evict() {
+        let cache = LruCache::new(2, None).unwrap();
+        put(&cache, "a");
+        put(&cache, "b");
+        assert_eq!(cache.get("a".to_string()).unwrap(), Some(b"a".to_vec()));
+        put(&cache, "c");
+        assert_eq!(cache.get("b".to_string()).unwrap(), None);
+        assert_eq!(cache.get("a".to_string()).unwrap(), Some(b"a".to_vec()));
+        assert_eq!(cache.get("c".to_string()).unwrap(), Some(b"c".to_vec()));
+
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.hits, 3);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.size, 2);
+        assert_eq!(stats.capacity, 2);
+    }
+
+    #[test]
+    fn test_lru_cache_overwrite_and_remove() {
+        let cache = LruCache::new(2, None).unwrap();
+        put(&cache, "a");
+        cache.put("a".to_string(), b"new".to_vec(), None).unwrap();
+        put(&cache, "b");
+        assert_eq!(cache.stats().unwrap().evictions, 0);
+        assert_eq!(cache.get("a".to_string()).unwrap(), Some(b"new".to_vec()));
+        assert!(cache.remove("a".to_string()).unwrap());
+        assert!(!cache.remove("a".to_string()).unwrap());
+        cache.clear().unwrap();
+        assert_eq!(cache.stats().unwrap().size, 0);
+    }
+
+    #[test]
+    fn test_lru_cache_ttl() {
+        let cache = LruCache::new(10, Some(20)).unwrap();
+        put(&cache, "short");
+        cache.put("long".to_string(), b"l".to_vec(), Some(60_000)).unwrap();
+        thread::sleep(Duration::from_millis(40));
+        assert_eq!(cache.get("short".to_string()).unwrap(), None);
+        assert_eq!(cache.get("long".to_string()).unwrap(), Some(b"l".to_vec()));
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.expirations, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.size, 1);
+    }
+
+    #[test]
+    fn test_lru_cache_concurrent_access() {
+        let cache = LruCache::new(50, None).unwrap();
+        let handles: Vec<_> = (0..4)
+            .map(|t| {
+                let cache = cache.clone();
+                thread::spawn(move || {
+                    for i in 0..100 {
+                        let key = format!("{}-{}", t, i % 20);
+                        cache.put(key.clone(), vec![t as u8], None).unwrap();
+                        cache.get(key).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let stats = cache.stats().unwrap();
+        assert_eq!(stats.size, 50);
+        assert_eq!(stats.hits + stats.misses, 400);
+    }
+
+    #[test]
+    fn test_lru_cache_invalid_capacity() {
+        match LruCache::new(0, None) {
+            Err(CacheError::InvalidCapacity) => (),
+            other => panic!("Expected InvalidCapacity error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
@@ -0,0 +1,333 @@
+//! 暦計算モジュール
+//!
+//! このモジュールは、日付（年月日）に対する月の加算・営業日の加算・日数の差・週の始まりの計算を
+//! エクスポートします。月末の扱いは`MonthEndPolicy`で明示的に選ぶため、
+//! プラットフォームごとに結果が食い違うことがありません。
+
+use std::collections::HashSet;
+
+use chrono::{Datelike, Months, NaiveDate, TimeDelta, Weekday};
+use thiserror::Error;
+
+/// 暦計算で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CalendarError {
+    /// 存在しない日付が指定された場合
+    #[error("Invalid date: {0}")]
+    InvalidDate(String),
+    /// 計算結果が扱える範囲外の場合
+    #[error("Date out of range: {0}")]
+    OutOfRange(String),
+}
+
+/// 時刻とタイムゾーンを持たない日付（グレゴリオ暦）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, uniffi::Record)]
+pub struct CalendarDate {
+    /// 年
+    pub year: i32,
+    /// 月（1〜12）
+    pub month: u32,
+    /// 日（1〜31）
+    pub day: u32,
+}
+
+impl CalendarDate {
+    /// chronoの日付に変換します
+    fn to_naive(self) -> Result<NaiveDate, CalendarError> {
+        NaiveDate::from_ymd_opt(self.year, self.month, self.day).ok_or_else(|| {
+            CalendarError::InvalidDate(format!(
+                "{:04}-{:02}-{:02}",
+                self.year, self.month, self.day
+            ))
+        })
+    }
+
+    /// chronoの日付から変換します
+    fn from_naive(date: NaiveDate) -> Self {
+        CalendarDate { year: date.year(), month: date.month(), day: date.day() }
+    }
+}
+
+/// 月を加算した結果の日が、加算先の月に存在しない場合などの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum MonthEndPolicy {
+    /// 加算先の月の末日に丸めます（1月31日 + 1か月 = 2月29日）。
+    /// FoundationのCalendarと同じ動作です
+    Clamp,
+    /// あふれた日数を翌月に繰り越します（1月31日 + 1か月 = 3月2日）
+    Overflow,
+    /// 元の日付が月末の場合は加算先の月末にします（2月29日 + 1か月 = 3月31日）。
+    /// それ以外は`Clamp`と同じです
+    PreserveMonthEnd,
+}
+
+/// 範囲外エラーを作成します
+fn out_of_range(date: CalendarDate) -> CalendarError {
+    CalendarError::OutOfRange(format!("{:04}-{:02}-{:02}", date.year, date.month, date.day))
+}
+
+/// 月の末日を返します
+fn last_day_of_month(year: i32, month: u32) -> Option<NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    first.checked_add_months(Months::new(1))?.pred_opt()
+}
+
+/// 日付に月数を加算します（負の値で減算）
+///
+/// # Arguments
+/// * `date` - 基準の日付
+/// * `months` - 加算する月数
+/// * `policy` - 加算先の月に同じ日が存在しない場合などの扱い（省略時は`Clamp`）
+///
+/// # Errors
+/// * `CalendarError::InvalidDate` - 存在しない日付が指定された場合
+/// * `CalendarError::OutOfRange` - 結果が扱える範囲外の場合
+///
+/// # Example
+/// ```
+/// let date = CalendarDate { year: 2024, month: 1, day: 31 };
+/// let clamped = add_months(date, 1, None)?;
+/// assert_eq!(clamped, CalendarDate { year: 2024, month: 2, day: 29 });
+/// let overflowed = add_months(date, 1, Some(MonthEndPolicy::Overflow))?;
+/// assert_eq!(overflowed, CalendarDate { year: 2024, month: 3, day: 2 });
+/// ```
+#[uniffi::export(default(policy = None))]
+pub fn add_months(
+    date: CalendarDate,
+    months: i32,
+    policy: Option<MonthEndPolicy>,
+) -> Result<CalendarDate, CalendarError> {
+    let naive = date.to_naive()?;
+    let total = i64::from(date.year) * 12 + i64::from(date.month - 1) + i64::from(months);
+    let year = i32::try_from(total.div_euclid(12)).map_err(|_| out_of_range(date))?;
+    let month = total.rem_euclid(12) as u32 + 1;
+    let last_day = last_day_of_month(year, month).ok_or_else(|| out_of_range(date))?;
+    let is_month_end = naive.succ_opt().is_some_and(|next| next.month() != naive.month());
+    let result = match policy.unwrap_or(MonthEndPolicy::Clamp) {
+        MonthEndPolicy::PreserveMonthEnd if is_month_end => last_day,
+        MonthEndPolicy::Clamp | MonthEndPolicy::PreserveMonthEnd => {
+            last_day.with_day(date.day.min(last_day.day())).unwrap_or(last_day)
+        }
+        MonthEndPolicy::Overflow => {
+            let overflow = date.day.saturating_sub(last_day.day());
+            last_day
+                .with_day(date.day.min(last_day.day()))
+                .and_then(|day| day.checked_add_signed(TimeDelta::days(overflow.into())))
+                .ok_or_else(|| out_of_range(date))?
+        }
+    };
+    Ok(CalendarDate::from_naive(result))
+}
+
+/// 日付に営業日数を加算します（負の値で減算）
+///
+/// 土曜日・日曜日と`holidays`に含まれる日を除いて1日ずつ数えます。
+/// 基準の日付自体は数えないため、金曜日に1営業日を加算すると翌週の月曜日になります。
+/// `days`が0の場合は基準の日付をそのまま返します。
+///
+/// # Arguments
+/// * `date` - 基準の日付
+/// * `days` - 加算する営業日数
+/// * `holidays` - 休業日の一覧
+///
+/// # Errors
+/// * `CalendarError::InvalidDate` - 存在しない日付が指定された場合
+/// * `CalendarError::OutOfRange` - 結果が扱える範囲外の場合
+///
+/// # Example
+/// ```
+/// // 2024-12-31（火）から2営業日後。1月1日は休業日
+/// let date = CalendarDate { year: 2024, month: 12, day: 31 };
+/// let holidays = vec![CalendarDate { year: 2025, month: 1, day: 1 }];
+/// let result = add_business_days(date, 2, holidays)?;
+/// assert_eq!(result, CalendarDate { year: 2025, month: 1, day: 3 });
+/// ```
+#[uniffi::export]
+pub fn add_business_days(
+    date: CalendarDate,
+    days: i32,
+    holidays: Vec<CalendarDate>,
+) -> Result<CalendarDate, CalendarError> {
+    let mut current = date.to_naive()?;
+    let holidays =
+        holidays.into_iter().map(CalendarDate::to_naive).collect::<Result<HashSet<_>, _>>()?;
+    let step = TimeDelta::days(if days < 0 { -1 } else { 1 });
+    let mut remaining = days.unsigned_abs();
+    while remaining > 0 {
+        current = current.checked_add_signed(step).ok_or_else(|| out_of_range(date))?;
+        let is_weekend = matches!(current.weekday(), Weekday::Sat | Weekday::Sun);
+        if !is_weekend && !holidays.contains(&current) {
+            remaining -= 1;
+        }
+    }
+    Ok(CalendarDate::from_naive(current))
+}
+
+/// 2つの日付の間の日数を返します
+///
+/// `end`が`start`より前の場合は負の値になります。
+///
+/// # Arguments
+/// * `start` - 開始日
+/// * `end` - 終了日
+///
+/// # Errors
+/// * `CalendarError::InvalidDate` - 存在しない日付が指定された場合
+#[uniffi::export]
+pub fn days_between(start: CalendarDate, end: CalendarDate) -> Result<i64, CalendarError> {
+    Ok((end.to_naive()? - start.to_naive()?).num_days())
+}
+
+/// 日曜日始まりの地域（CLDRの`firstDay`）
+const SUNDAY_FIRST_REGIONS: &[&str] = &[
+    "AG", "AS", "BD", "BR", "BS", "BT", "BW", "BZ", "CA", "CO", "DM", "DO", "ET", "GT", "GU", "HK",
+    "HN", "ID", "IL", "IN", "JM", "JP", "KE", "KH", "KR", "LA", "MH", "MM", "MO", "MT", "MX", "MZ",
+    "NI", "NP", "PA", "PE", "PH", "PK", "PR", "PT", "PY", "SA", "SG", "SV", "TH", "TT", "TW", "UM",
+    "US", "VE", "VI", "WS", "YE", "ZA", "ZW",
+];
+
+/// 土曜日始まりの地域（CLDRの`firstDay`）
+const SATURDAY_FIRST_REGIONS: &[&str] =
+    &["AE", "AF", "BH", "DJ", "DZ", "EG", "IQ", "IR", "JO", "KW", "LY", "OM", "QA", "SD", "SY"];
+
+/// 地域の指定がない場合に言語から推定する地域
+const DEFAULT_REGIONS: &[(&str, &str)] =
+    &[("en", "US"), ("ja", "JP"), ("es", "ES"), ("fr", "FR"), ("de", "DE"), ("zh", "CN")];
+
+/// ロケール識別子から週の最初の曜日を選択します（不明な場合は月曜日）
+fn first_weekday(locale: &str) -> Weekday {
+    let mut subtags = locale.trim().split(['.', '@']).next().unwrap_or_default().split(['-', '_']);
+    let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+    // 言語の後の2文字の英字サブタグを地域とみなす（`zh-Hant-TW`の`Hant`は読み飛ばす）
+    let region = subtags
+        .find(|subtag| subtag.len() == 2 && subtag.chars().all(|c| c.is_ascii_alphabetic()))
+        .map(|subtag| subtag.to_ascii_uppercase())
+        .or_else(|| {
+            DEFAULT_REGIONS
+                .iter()
+                .find(|(default_language, _)| *default_language == language)
+                .map(|(_, region)| region.to_string())
+        })
+        .unwrap_or_default();
+    if SUNDAY_FIRST_REGIONS.contains(&region.as_str()) {
+        Weekday::Sun
+    } else if SATURDAY_FIRST_REGIONS.contains(&region.as_str()) {
+        Weekday::Sat
+    } else {
+        Weekday::Mon
+    }
+}
+
+/// 日付を含む週の最初の日を返します
+///
+/// 週の最初の曜日はロケールの地域で決まります（CLDRの`firstDay`）。
+/// 米国・日本などは日曜日、中東の一部は土曜日、それ以外は月曜日です。
+/// 地域を含まないロケール（`ja`など）は言語の代表的な地域（`JP`）とみなします。
+///
+/// # Arguments
+/// * `date` - 基準の日付
+/// * `locale` - ロケール識別子（例: `"en-US"`, `"de_DE"`）
+///
+/// # Errors
+/// * `CalendarError::InvalidDate` - 存在しない日付が指定された場合
+/// * `CalendarError::OutOfRange` - 結果が扱える範囲外の場合
+///
+/// # Example
+/// ```
+/// // 2024-01-10は水曜日
+/// let date = CalendarDate { year: 2024, month: 1, day: 10 };
+/// assert_eq!(start_of_week(date, "en-US".to_string())?.day, 7);
+/// assert_eq!(start_of_week(date, "de-DE".to_string())?.day, 8);
+/// ```
+#[uniffi::export]
+pub fn start_of_week(date: CalendarDate, locale: String) -> Result<CalendarDate, CalendarError> {
+    let naive = date.to_naive()?;
+    let first = first_weekday(&locale);
+    let elapsed = (naive.weekday().num_days_from_monday() + 7 - first.num_days_from_monday()) % 7;
+    naive
+        .checked_sub_signed(TimeDelta::days(elapsed.into()))
+        .map(CalendarDate::from_naive)
+        .ok_or_else(|| out_of_range(date))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> CalendarDate {
+        CalendarDate { year, month, day }
+    }
+
+    #[test]
+    fn test_add_months_policies() {
+        let january_31 = date(2024, 1, 31);
+        assert_eq!(add_months(january_31, 1, None).unwrap(), date(2024, 2, 29));
+        assert_eq!(
+            add_months(january_31, 1, Some(MonthEndPolicy::Overflow)).unwrap(),
+            date(2024, 3, 2)
+        );
+        assert_eq!(add_months(january_31, 13, None).unwrap(), date(2025, 2, 28));
+        assert_eq!(add_months(date(2024, 3, 31), -1, None).unwrap(), date(2024, 2, 29));
+        assert_eq!(add_months(date(2024, 1, 15), -13, None).unwrap(), date(2022, 12, 15));
+
+        let preserve = Some(MonthEndPolicy::PreserveMonthEnd);
+        assert_eq!(add_months(date(2024, 2, 29), 1, preserve).unwrap(), date(2024, 3, 31));
+        assert_eq!(add_months(date(2024, 2, 29), 1, None).unwrap(), date(2024, 3, 29));
+        assert_eq!(add_months(date(2024, 4, 30), 1, preserve).unwrap(), date(2024, 5, 31));
+        assert_eq!(add_months(date(2024, 1, 30), 1, preserve).unwrap(), date(2024, 2, 29));
+        assert_eq!(add_months(date(2024, 5, 30), 1, preserve).unwrap(), date(2024, 6, 30));
+    }
+
+    #[test]
+    fn test_add_business_days() {
+        let holidays = vec![date(2025, 1, 1)];
+        // 2024-12-31は火曜日
+        assert_eq!(add_business_days(date(2024, 12, 31), 2, holidays).unwrap(), date(2025, 1, 3));
+        // 2024-01-05は金曜日
+        assert_eq!(add_business_days(date(2024, 1, 5), 1, vec![]).unwrap(), date(2024, 1, 8));
+        assert_eq!(add_business_days(date(2024, 1, 8), -1, vec![]).unwrap(), date(2024, 1, 5));
+        assert_eq!(add_business_days(date(2024, 1, 6), 0, vec![]).unwrap(), date(2024, 1, 6));
+        assert_eq!(add_business_days(date(2024, 1, 1), 10, vec![]).unwrap(), date(2024, 1, 15));
+    }
+
+    #[test]
+    fn test_days_between() {
+        assert_eq!(days_between(date(2024, 1, 1), date(2024, 3, 1)).unwrap(), 60);
+        assert_eq!(days_between(date(2024, 3, 1), date(2024, 1, 1)).unwrap(), -60);
+        assert_eq!(days_between(date(2023, 1, 1), date(2024, 1, 1)).unwrap(), 365);
+    }
+
+    #[test]
+    fn test_start_of_week() {
+        // 2024-01-10は水曜日
+        let wednesday = date(2024, 1, 10);
+        let start = |locale: &str| start_of_week(wednesday, locale.to_string()).unwrap();
+        assert_eq!(start("en-US"), date(2024, 1, 7));
+        assert_eq!(start("ja"), date(2024, 1, 7));
+        assert_eq!(start("en-GB"), date(2024, 1, 8));
+        assert_eq!(start("de_DE.UTF-8"), date(2024, 1, 8));
+        assert_eq!(start("zh-Hant-TW"), date(2024, 1, 7));
+        assert_eq!(start("ar-EG"), date(2024, 1, 6));
+        assert_eq!(start(""), date(2024, 1, 8));
+        // 週の最初の曜日自体はその日を返す
+        let sunday = start_of_week(date(2024, 1, 7), "en-US".to_string()).unwrap();
+        assert_eq!(sunday, date(2024, 1, 7));
+    }
+
+    #[test]
+    fn test_invalid_date() {
+        match add_months(date(2023, 2, 29), 1, None) {
+            Err(CalendarError::InvalidDate(_)) => (),
+            other => panic!("Expected InvalidDate error, got {:?}", other),
+        }
+        match add_business_days(date(2024, 1, 1), 1, vec![date(2024, 13, 1)]) {
+            Err(CalendarError::InvalidDate(_)) => (),
+            other => panic!("Expected InvalidDate error, got {:?}", other),
+        }
+        match add_months(date(2024, 1, 1), i32::MAX, None) {
+            Err(CalendarError::OutOfRange(_)) => (),
+            other => panic!("Expected OutOfRange error, got {:?}", other),
+        }
+    }
+}
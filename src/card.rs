@@ -0,0 +1,311 @@
+//! 決済カード番号の検証モジュール
+//!
+//! このモジュールは、カード番号（PAN）をLuhnチェックディジット・ブランド・桁数で検証する
+//! `validate_card_number`と、下4桁以外を伏せて表示用に整形する`format_card_display`を
+//! エクスポートします。カード入力画面の検証ロジックを1か所にまとめ、監査しやすくするために
+//! 使用します。
+//!
+//! カード番号はログやエラーメッセージに含めません。
+
+use thiserror::Error;
+
+/// 表示で伏せた桁に使う文字
+const MASK: char = '•';
+
+/// 表示で伏せずに残す末尾の桁数
+const VISIBLE_DIGITS: usize = 4;
+
+/// カード番号の処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CardError {
+    /// 数字・空白・ハイフン以外の文字を含む場合
+    #[error("Card number contains an invalid character")]
+    InvalidCharacter,
+}
+
+/// カードのブランド
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum CardBrand {
+    /// Visa
+    Visa,
+    /// Mastercard
+    Mastercard,
+    /// American Express
+    AmericanExpress,
+    /// Discover
+    Discover,
+    /// JCB
+    Jcb,
+    /// Diners Club
+    DinersClub,
+    /// UnionPay（銀聯）
+    UnionPay,
+    /// Maestro
+    Maestro,
+    /// 番号の先頭からブランドを判別できない場合
+    Unknown,
+}
+
+/// カード番号の検証結果
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct CardValidation {
+    /// 番号の先頭（IIN）から判別したブランド
+    pub brand: CardBrand,
+    /// Luhnチェックディジットが正しい場合は`true`
+    pub luhn_valid: bool,
+    /// 桁数がブランドの規則に合う場合は`true`
+    pub length_valid: bool,
+    /// ブランドが判別でき、桁数とチェックディジットが正しい場合は`true`
+    pub is_valid: bool,
+    /// ブランドで使われる最大の桁数（入力欄の文字数制限用）
+    pub max_length: u32,
+}
+
+/// ブランドの判別規則
+struct BrandRule {
+    brand: CardBrand,
+    /// 先頭の桁の範囲（`(桁数, 最小, 最大)`）
+    prefixes: &'static [(usize, u32, u32)],
+    /// 有効な桁数
+    lengths: &'static [usize],
+    /// 表示時の桁のまとまり
+    groups: &'static [usize],
+}
+
+/// ブランドの判別規則（先頭から順に照合します）
+const BRAND_RULES: &[BrandRule] = &[
+    BrandRule {
+        brand: CardBrand::AmericanExpress,
+        prefixes: &[(2, 34, 34), (2, 37, 37)],
+        lengths: &[15],
+        groups: &[4, 6, 5],
+    },
+    BrandRule {
+        brand: CardBrand::DinersClub,
+        prefixes: &[(3, 300, 305), (2, 36, 36), (2, 38, 39)],
+        lengths: &[14, 16, 17, 18, 19],
+        groups: &[4, 6, 4],
+    },
+    BrandRule {
+        brand: CardBrand::Jcb,
+        prefixes: &[(4, 3528, 3589)],
+        lengths: &[16, 17, 18, 19],
+        groups: &[4, 4, 4, 4],
+    },
+    BrandRule {
+        brand: CardBrand::Visa,
+        prefixes: &[(1, 4, 4)],
+        lengths: &[13, 16, 19],
+        groups: &[4, 4, 4, 4],
+    },
+    BrandRule {
+        brand: CardBrand::Mastercard,
+        prefixes: &[(2, 51, 55), (4, 2221, 2720)],
+        lengths: &[16],
+        groups: &[4, 4, 4, 4],
+    },
+    BrandRule {
+        brand: CardBrand::Discover,
+        prefixes: &[(4, 6011, 6011), (3, 644, 649), (2, 65, 65)],
+        lengths: &[16, 17, 18, 19],
+        groups: &[4, 4, 4, 4],
+    },
+    BrandRule {
+        brand: CardBrand::UnionPay,
+        prefixes: &[(2, 62, 62), (2, 81, 81)],
+        lengths: &[16, 17, 18, 19],
+        groups: &[4, 4, 4, 4],
+    },
+    BrandRule {
+        brand: CardBrand::Maestro,
+        prefixes: &[(2, 50, 50), (2, 56, 58), (1, 6, 6)],
+        lengths: &[12, 13, 14, 15, 16, 17, 18, 19],
+        groups: &[4, 4, 4, 4],
+    },
+];
+
+/// 空白とハイフンを除いた数字の並びを返します
+fn digits(pan: &str) -> Result<Vec<u8>, CardError> {
+    pan.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_digit(10).map(|digit| digit as u8).ok_or(CardError::InvalidCharacter))
+        .collect()
+}
+
+/// 番号の先頭に一致するブランドの規則を返します
+fn brand_rule(digits: &[u8]) -> Option<&'static BrandRule> {
+    BRAND_RULES.iter().find(|rule| {
+        rule.prefixes.iter().any(|&(length, min, max)| {
+            digits.len() >= length
+                && (min..=max).contains(
+                    &digits[..length].iter().fold(0, |value, &digit| value * 10 + u32::from(digit)),
+                )
+        })
+    })
+}
+
+/// Luhnチェックディジットが正しいかどうかを返します
+fn luhn_valid(digits: &[u8]) -> bool {
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            let digit = u32::from(digit);
+            match i % 2 {
+                0 => digit,
+                _ if digit * 2 > 9 => digit * 2 - 9,
+                _ => digit * 2,
+            }
+        })
+        .sum();
+    !digits.is_empty() && sum.is_multiple_of(10)
+}
+
+/// カード番号を検証します
+///
+/// 数字の間の空白とハイフンは無視します。UnionPayにはLuhnチェックディジットを
+/// 持たない番号があるため、UnionPayの`is_valid`はチェックディジットを条件にしません。
+///
+/// # Arguments
+/// * `pan` - カード番号
+///
+/// # Errors
+/// * `CardError::InvalidCharacter` - 数字・空白・ハイフン以外の文字を含む場合
+///
+/// # Example
+/// ```
+/// let result = validate_card_number("4242 4242 4242 4242".to_string())?;
+/// assert_eq!(result.brand, CardBrand::Visa);
+/// assert!(result.is_valid);
+/// ```
+#[uniffi::export]
+pub fn validate_card_number(pan: String) -> Result<CardValidation, CardError> {
+    let digits = digits(&pan)?;
+    let luhn_valid = luhn_valid(&digits);
+    let Some(rule) = brand_rule(&digits) else {
+        return Ok(CardValidation {
+            brand: CardBrand::Unknown,
+            luhn_valid,
+            length_valid: false,
+            is_valid: false,
+            max_length: 19,
+        });
+    };
+    let length_valid = rule.lengths.contains(&digits.len());
+    let checksum_valid = luhn_valid || rule.brand == CardBrand::UnionPay;
+    Ok(CardValidation {
+        brand: rule.brand,
+        luhn_valid,
+        length_valid,
+        is_valid: length_valid && checksum_valid,
+        max_length: rule.lengths.iter().copied().max().unwrap_or_default() as u32,
+    })
+}
+
+/// カード番号を下4桁以外を伏せた表示用の文字列に整形します
+///
+/// ブランドの桁のまとまり（American Expressは4-6-5、それ以外は主に4桁ずつ）で
+/// 空白を入れ、下4桁以外を`•`に置き換えます。
+///
+/// # Arguments
+/// * `pan` - カード番号（空白とハイフンは無視します）
+///
+/// # Errors
+/// * `CardError::InvalidCharacter` - 数字・空白・ハイフン以外の文字を含む場合
+///
+/// # Example
+/// ```
+/// assert_eq!(format_card_display("4242424242424242".to_string())?, "•••• •••• •••• 4242");
+/// assert_eq!(format_card_display("378282246310005".to_string())?, "•••• •••••• •0005");
+/// ```
+#[uniffi::export]
+pub fn format_card_display(pan: String) -> Result<String, CardError> {
+    let digits = digits(&pan)?;
+    let groups = brand_rule(&digits).map_or(&[4, 4, 4, 4][..], |rule| rule.groups);
+    let visible_from = digits.len().saturating_sub(VISIBLE_DIGITS);
+    let mut output = String::new();
+    let mut group_sizes = groups.iter().copied().chain(std::iter::repeat(4));
+    let mut remaining_in_group = group_sizes.next().unwrap_or(4);
+    for (i, &digit) in digits.iter().enumerate() {
+        if remaining_in_group == 0 {
+            output.push(' ');
+            remaining_in_group = group_sizes.next().unwrap_or(4);
+        }
+        output.push(if i < visible_from { MASK } else { char::from(b'0' + digit) });
+        remaining_in_group -= 1;
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brand(pan: &str) -> CardBrand {
+        validate_card_number(pan.to_string()).unwrap().brand
+    }
+
+    #[test]
+    fn test_validate_card_number() {
+        for (pan, expected) in [
+            ("4242 4242 4242 4242", CardBrand::Visa),
+            ("4222222222222", CardBrand::Visa),
+            ("5555-5555-5555-4444", CardBrand::Mastercard),
+            ("2223003122003222", CardBrand::Mastercard),
+            ("378282246310005", CardBrand::AmericanExpress),
+            ("6011111111111117", CardBrand::Discover),
+            ("3566002020360505", CardBrand::Jcb),
+            ("30569309025904", CardBrand::DinersClub),
+            ("6200000000000005", CardBrand::UnionPay),
+            ("6759649826438453", CardBrand::Maestro),
+        ] {
+            let result = validate_card_number(pan.to_string()).unwrap();
+            assert_eq!(result.brand, expected, "{}", pan);
+            assert!(result.is_valid, "{}", pan);
+        }
+
+        let wrong_check_digit = validate_card_number("4242424242424241".to_string()).unwrap();
+        assert!(!wrong_check_digit.luhn_valid);
+        assert!(wrong_check_digit.length_valid);
+        assert!(!wrong_check_digit.is_valid);
+
+        let incomplete = validate_card_number("3782 822463".to_string()).unwrap();
+        assert_eq!(incomplete.brand, CardBrand::AmericanExpress);
+        assert_eq!(incomplete.max_length, 15);
+        assert!(!incomplete.length_valid);
+
+        let unknown = validate_card_number("9999999999999995".to_string()).unwrap();
+        assert_eq!(unknown.brand, CardBrand::Unknown);
+        assert!(unknown.luhn_valid);
+        assert!(!unknown.is_valid);
+        assert!(!validate_card_number(String::new()).unwrap().is_valid);
+    }
+
+    #[test]
+    fn test_brand_detection_while_typing() {
+        assert_eq!(brand("4"), CardBrand::Visa);
+        assert_eq!(brand("37"), CardBrand::AmericanExpress);
+        assert_eq!(brand("3"), CardBrand::Unknown);
+        assert_eq!(brand("2720"), CardBrand::Mastercard);
+        assert_eq!(brand("2721"), CardBrand::Unknown);
+        assert_eq!(brand("6011"), CardBrand::Discover);
+        assert_eq!(brand("62"), CardBrand::UnionPay);
+    }
+
+    #[test]
+    fn test_format_card_display() {
+        let display = |pan: &str| format_card_display(pan.to_string()).unwrap();
+        assert_eq!(display("4242424242424242"), "•••• •••• •••• 4242");
+        assert_eq!(display("4242-4242-4242-4242-123"), "•••• •••• •••• •••2 123");
+        assert_eq!(display("378282246310005"), "•••• •••••• •0005");
+        assert_eq!(display("30569309025904"), "•••• •••••• 5904");
+        assert_eq!(display("424"), "424");
+        assert_eq!(display(""), "");
+        match format_card_display("4242 4242 4242 424x".to_string()) {
+            Err(CardError::InvalidCharacter) => (),
+            other => panic!("Expected InvalidCharacter error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,615 @@
+//! HTTP通信の記録・再生モジュール
+//!
+//! このモジュールは、HTTPリクエストとレスポンスの組をカセットファイル（JSON）に記録し、
+//! 後からネットワークを使わずに再生する`HttpCassette`をエクスポートします。
+//! SwiftのUIテストを、Rustコアの通信結果が決まった状態で実行するために使用します。
+//!
+//! `HttpCassette`は`HttpTransport`を実装しているため、`as_transport`で得たトランスポートを
+//! `set_http_transport`で登録すると、クレート自身の通信（`fetch_greeting`など）も
+//! 記録・再生の対象になります。
+//!
+//! 記録時はリクエストヘッダーを保存しません（認証トークンなどをファイルに残さないため）。
+//! 再生時はメソッド・URL・ボディが一致する記録を、記録された順に返します。
+//!
+//! # カセットファイルの形式
+//! ```text
+//! {
+//!   "version": 1,
+//!   "interactions": [
+//!     {
+//!       "request": { "method": "GET", "url": "https://...", "body": "" },
+//!       "response": { "status": 200, "headers": { "content-type": "..." }, "body": "..." }
+//!     }
+//!   ]
+//! }
+//! ```
+//! ボディがUTF-8でない場合は`body`の代わりに`body_base64`（標準Base64）を使用します。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::file_io::write_atomic;
+use crate::sigv4::HttpRequestParts;
+use crate::transport::{send_builtin, HttpResponseParts, HttpTransport, TransportError};
+
+/// カセットファイルの形式のバージョン
+const CASSETTE_VERSION: u64 = 1;
+
+/// 記録するレスポンスボディの最大サイズ（16 MiB）
+const MAX_BODY_LEN: u64 = 16 * 1024 * 1024;
+
+/// カセットの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CassetteError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// カセットファイルの読み書きに失敗した場合
+    #[error("Failed to access cassette file: {0}")]
+    IoError(String),
+    /// カセットファイルの形式が正しくない場合
+    #[error("Invalid cassette: {0}")]
+    InvalidCassette(String),
+    /// URLがHTTP(S)でない場合
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    /// 記録中の通信に失敗した場合
+    #[error("Network error: {0}")]
+    Network(String),
+    /// 記録中のレスポンスボディが上限（16 MiB）を超えた場合
+    #[error("Response body is too large")]
+    ResponseTooLarge,
+    /// 再生時に一致する記録がない場合
+    #[error("No recorded interaction for {method} {url}")]
+    NoMatchingInteraction { method: String, url: String },
+}
+
+impl From<std::io::Error> for CassetteError {
+    fn from(error: std::io::Error) -> Self {
+        CassetteError::IoError(error.to_string())
+    }
+}
+
+impl From<TransportError> for CassetteError {
+    fn from(error: TransportError) -> Self {
+        match error {
+            TransportError::InvalidUrl { url } => CassetteError::InvalidUrl(url),
+            TransportError::Network { message } => CassetteError::Network(message),
+        }
+    }
+}
+
+impl From<CassetteError> for TransportError {
+    fn from(error: CassetteError) -> Self {
+        match error {
+            CassetteError::InvalidUrl(url) => TransportError::InvalidUrl { url },
+            other => TransportError::Network { message: other.to_string() },
+        }
+    }
+}
+
+/// カセットの動作モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum CassetteMode {
+    /// 実際に通信し、結果をカセットファイルに記録します（既存のファイルは置き換えます）
+    Record,
+    /// 通信せず、カセットファイルの記録を返します
+    Replay,
+}
+
+/// 記録されたレスポンス
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct RecordedResponse {
+    /// ステータスコード
+    pub status: u16,
+    /// ヘッダー（名前は小文字）
+    pub headers: HashMap<String, String>,
+    /// ボディ
+    pub body: Vec<u8>,
+}
+
+/// 記録された1回の通信
+#[derive(Debug, Clone)]
+struct Interaction {
+    method: String,
+    url: String,
+    body: Vec<u8>,
+    response: RecordedResponse,
+    /// 再生済みか
+    used: bool,
+}
+
+impl Interaction {
+    fn matches(&self, request: &HttpRequestParts) -> bool {
+        self.method.eq_ignore_ascii_case(&request.method)
+            && self.url == request.url
+            && self.body == request.body
+    }
+}
+
+/// ボディをJSONオブジェクトに書き込みます
+fn body_to_json(object: &mut Map<String, Value>, body: &[u8]) {
+    match std::str::from_utf8(body) {
+        Ok(text) => object.insert("body".to_string(), json!(text)),
+        Err(_) => object.insert("body_base64".to_string(), json!(STANDARD.encode(body))),
+    };
+}
+
+/// JSONオブジェクトからボディを読み出します
+fn body_from_json(object: &Map<String, Value>) -> Result<Vec<u8>, CassetteError> {
+    match (object.get("body"), object.get("body_base64")) {
+        (Some(Value::String(text)), _) => Ok(text.as_bytes().to_vec()),
+        (_, Some(Value::String(encoded))) => STANDARD
+            .decode(encoded)
+            .map_err(|e| CassetteError::InvalidCassette(format!("invalid body_base64: {}", e))),
+        (None, None) => Ok(Vec::new()),
+        _ => Err(CassetteError::InvalidCassette("body must be a string".to_string())),
+    }
+}
+
+fn interaction_to_json(interaction: &Interaction) -> Value {
+    let mut request = Map::new();
+    request.insert("method".to_string(), json!(interaction.method));
+    request.insert("url".to_string(), json!(interaction.url));
+    body_to_json(&mut request, &interaction.body);
+
+    let mut headers: Vec<_> = interaction.response.headers.iter().collect();
+    headers.sort();
+    let mut response = Map::new();
+    response.insert("status".to_string(), json!(interaction.response.status));
+    response.insert(
+        "headers".to_string(),
+        Value::Object(headers.into_iter().map(|(k, v)| (k.clone(), json!(v))).collect()),
+    );
+    body_to_json(&mut response, &interaction.response.body);
+
+    json!({ "request": request, "response": response })
+}
+
+fn interaction_from_json(value: &Value) -> Result<Interaction, CassetteError> {
+    let invalid = |message: &str| CassetteError::InvalidCassette(message.to_string());
+    let request = value
+        .get("request")
+        .and_then(Value::as_object)
+        .ok_or_else(|| invalid("interaction must have a request object"))?;
+    let response = value
+        .get("response")
+        .and_then(Value::as_object)
+        .ok_or_else(|| invalid("interaction must have a response object"))?;
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("request.method must be a string"))?;
+    let url = request
+        .get("url")
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("request.url must be a string"))?;
+    let status = response
+        .get("status")
+        .and_then(Value::as_u64)
+        .and_then(|status| u16::try_from(status).ok())
+        .ok_or_else(|| invalid("response.status must be a status code"))?;
+    let mut headers = HashMap::new();
+    if let Some(values) = response.get("headers") {
+        let values = values
+            .as_object()
+            .ok_or_else(|| invalid("response.headers must be an object"))?;
+        for (name, value) in values {
+            let value = value
+                .as_str()
+                .ok_or_else(|| invalid("response header values must be strings"))?;
+            headers.insert(name.to_ascii_lowercase(), value.to_string());
+        }
+    }
+    Ok(Interaction {
+        method: method.to_string(),
+        url: url.to_string(),
+        body: body_from_json(request)?,
+        response: RecordedResponse {
+            status,
+            headers,
+            body: body_from_json(response)?,
+        },
+        used: false,
+    })
+}
+
+/// カセットファイルを読み込みます
+fn load_cassette(path: &Path) -> Result<Vec<Interaction>, CassetteError> {
+    let text = std::fs::read_to_string(path)?;
+    let root: Value = serde_json::from_str(&text)
+        .map_err(|e| CassetteError::InvalidCassette(e.to_string()))?;
+    match root.get("version").and_then(Value::as_u64) {
+        Some(CASSETTE_VERSION) => {}
+        other => {
+            return Err(CassetteError::InvalidCassette(format!(
+                "unsupported version: {:?}",
+                other
+            )))
+        }
+    }
+    root.get("interactions")
+        .and_then(Value::as_array)
+        .ok_or_else(|| {
+            CassetteError::InvalidCassette("interactions must be an array".to_string())
+        })?
+        .iter()
+        .map(interaction_from_json)
+        .collect()
+}
+
+/// HTTP通信を記録・再生するカセット
+///
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// // 記録（開発時に一度だけ実行）
+/// let cassette = HttpCassette::open(path, CassetteMode::Record)?;
+/// let response = cassette.send(request).await?;
+///
+/// // 再生（UIテスト）
+/// let cassette = HttpCassette::open(path, CassetteMode::Replay)?;
+/// let response = cassette.send(request).await?; // 通信しない
+///
+/// // クレート自身の通信も記録・再生する
+/// set_http_transport(cassette.as_transport());
+/// ```
+#[derive(uniffi::Object)]
+pub struct HttpCassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Mutex<Vec<Interaction>>,
+}
+
+impl HttpCassette {
+    /// 実際に通信してレスポンスを返します
+    ///
+    /// カセット自身がトランスポートとして登録されている場合があるため、
+    /// 登録されたトランスポートではなく組み込みのクライアントで通信します。
+    fn perform(&self, request: &HttpRequestParts) -> Result<RecordedResponse, CassetteError> {
+        let lower = request.url.to_ascii_lowercase();
+        if !lower.starts_with("https://") && !lower.starts_with("http://") {
+            return Err(CassetteError::InvalidUrl(request.url.clone()));
+        }
+        let response = send_builtin(request, MAX_BODY_LEN)?;
+        if response.body.len() as u64 > MAX_BODY_LEN {
+            return Err(CassetteError::ResponseTooLarge);
+        }
+        Ok(RecordedResponse {
+            status: response.status,
+            headers: response.headers,
+            body: response.body,
+        })
+    }
+
+    /// 記録をカセットファイルに書き出します
+    fn save(&self, interactions: &[Interaction]) -> Result<(), CassetteError> {
+        let root = json!({
+            "version": CASSETTE_VERSION,
+            "interactions": interactions.iter().map(interaction_to_json).collect::<Vec<_>>(),
+        });
+        let text = serde_json::to_string_pretty(&root)
+            .map_err(|e| CassetteError::InvalidCassette(e.to_string()))?;
+        write_atomic(&self.path, &[text.as_bytes()])?;
+        Ok(())
+    }
+
+    /// リクエストを記録または再生します（同期版）
+    fn send_blocking(&self, request: HttpRequestParts) -> Result<RecordedResponse, CassetteError> {
+        match self.mode {
+            CassetteMode::Record => {
+                let response = self.perform(&request)?;
+                let mut interactions = self.interactions.lock()
+                    .map_err(|_| CassetteError::MutexPoisoned)?;
+                interactions.push(Interaction {
+                    method: request.method.to_ascii_uppercase(),
+                    url: request.url,
+                    body: request.body,
+                    response: response.clone(),
+                    used: true,
+                });
+                self.save(&interactions)?;
+                Ok(response)
+            }
+            CassetteMode::Replay => {
+                let mut interactions = self.interactions.lock()
+                    .map_err(|_| CassetteError::MutexPoisoned)?;
+                let index = interactions
+                    .iter()
+                    .position(|i| !i.used && i.matches(&request))
+                    .or_else(|| interactions.iter().rposition(|i| i.matches(&request)))
+                    .ok_or_else(|| CassetteError::NoMatchingInteraction {
+                        method: request.method.clone(),
+                        url: request.url.clone(),
+                    })?;
+                interactions[index].used = true;
+                Ok(interactions[index].response.clone())
+            }
+        }
+    }
+}
+
+#[uniffi::export]
+impl HttpCassette {
+    /// カセットファイルを開きます
+    ///
+    /// `Record`モードでは空のカセットから記録を始め、最初の`send`でファイルを置き換えます。
+    /// `Replay`モードではファイルを読み込みます。
+    ///
+    /// # Arguments
+    /// * `path` - カセットファイルのパス
+    /// * `mode` - 記録または再生
+    ///
+    /// # Errors
+    /// * `CassetteError::IoError` - `Replay`モードでファイルを読み込めない場合
+    /// * `CassetteError::InvalidCassette` - ファイルの形式が正しくない場合
+    #[uniffi::constructor]
+    pub fn open(path: String, mode: CassetteMode) -> Result<Arc<Self>, CassetteError> {
+        let path = PathBuf::from(path);
+        let interactions = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::Replay => load_cassette(&path)?,
+        };
+        Ok(Arc::new(Self { path, mode, interactions: Mutex::new(interactions) }))
+    }
+
+    /// リクエストを送信し（`Record`）、または記録されたレスポンスを返します（`Replay`）
+    ///
+    /// 200以外のステータスもエラーではなくレスポンスとして記録・再生します。
+    /// 再生時は、メソッド・URL・ボディが一致する未使用の記録を記録された順に返します。
+    /// 一致する記録をすべて使い切った場合は、最後に一致した記録を繰り返し返します。
+    ///
+    /// # Arguments
+    /// * `request` - リクエスト（`payload_hash`は使用しません）
+    ///
+    /// # Errors
+    /// * `CassetteError::NoMatchingInteraction` - 再生時に一致する記録がない場合
+    /// * `CassetteError::InvalidUrl` - 記録時にURLがHTTP(S)でない場合
+    /// * `CassetteError::Network` - 記録時に通信に失敗した場合
+    /// * `CassetteError::ResponseTooLarge` - 記録時にレスポンスボディが16 MiBを超える場合
+    /// * `CassetteError::IoError` - 記録時にファイルを書き込めない場合
+    /// * `CassetteError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub async fn send(
+        self: Arc<Self>,
+        request: HttpRequestParts,
+    ) -> Result<RecordedResponse, CassetteError> {
+        blocking::unblock(move || self.send_blocking(request)).await
+    }
+
+    /// `set_http_transport`に登録できるトランスポートとしてカセットを返します
+    ///
+    /// 登録すると、クレートが行うHTTP通信がこのカセットで記録・再生されます。
+    /// カセットのエラーは`TransportError::Network`（URLが不正な場合は`InvalidUrl`）に変換されます。
+    pub fn as_transport(self: Arc<Self>) -> Arc<dyn HttpTransport> {
+        self
+    }
+
+    /// 動作モードを返します
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    /// カセットに含まれる記録の数を返します
+    ///
+    /// # Errors
+    /// * `CassetteError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn interaction_count(&self) -> Result<u32, CassetteError> {
+        let interactions = self.interactions.lock()
+            .map_err(|_| CassetteError::MutexPoisoned)?;
+        Ok(interactions.len() as u32)
+    }
+}
+
+impl HttpTransport for HttpCassette {
+    fn send(&self, request: HttpRequestParts) -> Result<HttpResponseParts, TransportError> {
+        let response = self.send_blocking(request)?;
+        Ok(HttpResponseParts {
+            status: response.status,
+            headers: response.headers,
+            body: response.body,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_cassette_{}_{}.json", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn request(method: &str, url: &str, body: &[u8]) -> HttpRequestParts {
+        HttpRequestParts {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: HashMap::from([("Authorization".to_string(), "Bearer secret".to_string())]),
+            body: body.to_vec(),
+            payload_hash: None,
+        }
+    }
+
+    /// 指定した数のレスポンスを順に返すサーバーを起動します
+    fn serve(responses: Vec<&'static str>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut data = Vec::new();
+                let mut buf = [0u8; 1024];
+                while let Ok(len) = stream.read(&mut buf) {
+                    if len == 0 {
+                        break;
+                    }
+                    data.extend_from_slice(&buf[..len]);
+                    let text = String::from_utf8_lossy(&data).to_lowercase();
+                    if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                        let length = head
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length: ")?.parse().ok())
+                            .unwrap_or(0);
+                        if body.len() >= length {
+                            break;
+                        }
+                    }
+                }
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}", addr)
+    }
+
+    #[test]
+    fn test_record_then_replay() {
+        let base = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 11\r\n\
+             Connection: close\r\n\r\n{\"id\": 42}\n",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 201 Created\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+        ]);
+        let path = temp_path("record_replay");
+        let recorder = HttpCassette::open(path.clone(), CassetteMode::Record).unwrap();
+        let user = format!("{}/users/42", base);
+        let missing = format!("{}/users/0", base);
+        let upload = format!("{}/upload", base);
+
+        let first =
+            pollster::block_on(Arc::clone(&recorder).send(request("GET", &user, b""))).unwrap();
+        assert_eq!(first.status, 200);
+        assert_eq!(first.body, b"{\"id\": 42}\n");
+        let second =
+            pollster::block_on(Arc::clone(&recorder).send(request("get", &missing, b"")))
+                .unwrap();
+        assert_eq!(second.status, 404);
+        let third = pollster::block_on(Arc::clone(&recorder).send(request(
+            "POST",
+            &upload,
+            &[0, 159, 146, 150],
+        )))
+        .unwrap();
+        assert_eq!(third.status, 201);
+        assert_eq!(recorder.interaction_count().unwrap(), 3);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(!text.contains("secret"));
+        assert!(text.contains("body_base64"));
+
+        let player = HttpCassette::open(path.clone(), CassetteMode::Replay).unwrap();
+        assert_eq!(player.mode(), CassetteMode::Replay);
+        let binary = request("POST", &upload, &[0, 159, 146, 150]);
+        let replayed = pollster::block_on(Arc::clone(&player).send(binary)).unwrap();
+        assert_eq!(replayed, third);
+        let replayed =
+            pollster::block_on(Arc::clone(&player).send(request("GET", &user, b""))).unwrap();
+        assert_eq!(replayed, first);
+        let replayed =
+            pollster::block_on(Arc::clone(&player).send(request("GET", &missing, b""))).unwrap();
+        assert_eq!(replayed, second);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_in_recorded_order() {
+        let path = temp_path("order");
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "interactions": [
+                {"request": {"method": "GET", "url": "https://example.com/poll"},
+                 "response": {"status": 200, "headers": {"Content-Type": "text/plain"},
+                              "body": "pending"}},
+                {"request": {"method": "GET", "url": "https://example.com/poll"},
+                 "response": {"status": 200, "body": "done"}}
+            ]}"#,
+        )
+        .unwrap();
+        let player = HttpCassette::open(path.clone(), CassetteMode::Replay).unwrap();
+        let poll = request("GET", "https://example.com/poll", b"");
+        let bodies: Vec<_> = (0..3)
+            .map(|_| pollster::block_on(Arc::clone(&player).send(poll.clone())).unwrap().body)
+            .collect();
+        assert_eq!(bodies, vec![b"pending".to_vec(), b"done".to_vec(), b"done".to_vec()]);
+
+        match pollster::block_on(player.send(request("POST", "https://example.com/poll", b"")))
+        {
+            Err(CassetteError::NoMatchingInteraction { method, .. }) => assert_eq!(method, "POST"),
+            other => panic!("Expected NoMatchingInteraction error, got {:?}", other),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_cassette_as_transport() {
+        let path = temp_path("transport");
+        std::fs::write(
+            &path,
+            r#"{"version": 1, "interactions": [
+                {"request": {"method": "GET", "url": "https://example.com/banner"},
+                 "response": {"status": 200, "headers": {"content-type": "text/plain"},
+                              "body": "Hello"}}
+            ]}"#,
+        )
+        .unwrap();
+        let transport = HttpCassette::open(path.clone(), CassetteMode::Replay)
+            .unwrap()
+            .as_transport();
+        let response = transport.send(request("GET", "https://example.com/banner", b"")).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.header("Content-Type"), Some("text/plain"));
+        assert_eq!(response.body, b"Hello");
+        match transport.send(request("GET", "https://example.com/other", b"")) {
+            Err(TransportError::Network { message }) => assert!(message.contains("/other")),
+            other => panic!("Expected Network error, got {:?}", other),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_rejects_oversized_body() {
+        let body = "x".repeat(MAX_BODY_LEN as usize + 1);
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let base = serve(vec![Box::leak(response.into_boxed_str())]);
+        let path = temp_path("oversized");
+        let recorder = HttpCassette::open(path.clone(), CassetteMode::Record).unwrap();
+        let large = request("GET", &format!("{}/large", base), b"");
+        match pollster::block_on(Arc::clone(&recorder).send(large)) {
+            Err(CassetteError::ResponseTooLarge) => (),
+            other => panic!("Expected ResponseTooLarge error, got {:?}", other),
+        }
+        assert_eq!(recorder.interaction_count().unwrap(), 0);
+        assert!(!Path::new(&path).exists());
+    }
+
+    #[test]
+    fn test_open_invalid_cassette() {
+        let path = temp_path("invalid");
+        match HttpCassette::open(path.clone(), CassetteMode::Replay) {
+            Err(CassetteError::IoError(_)) => (),
+            other => panic!("Expected IoError error, got {:?}", other.map(|_| ())),
+        }
+        std::fs::write(&path, r#"{"version": 2, "interactions": []}"#).unwrap();
+        match HttpCassette::open(path.clone(), CassetteMode::Replay) {
+            Err(CassetteError::InvalidCassette(_)) => (),
+            other => panic!("Expected InvalidCassette error, got {:?}", other.map(|_| ())),
+        }
+        std::fs::remove_file(&path).unwrap();
+    }
+}
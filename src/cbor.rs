@@ -0,0 +1,202 @@
+//! CBOR変換モジュール
+//!
+//! このモジュールは、CBOR（RFC 8949）とJSONを相互に変換する関数をエクスポートします。
+//! WebAuthnのアテステーションオブジェクトや、デバイスのテレメトリを
+//! コンパクトに送る用途を想定しています。
+//!
+//! CBORにはJSONにない型があるため、JSONへの変換時は次のように対応付けます。
+//!
+//! | CBOR | JSON |
+//! |------|------|
+//! | バイト列 | Base64URL（パディングなし）の文字列 |
+//! | 文字列以外のマップのキー | キーをJSONで表した文字列（例: `-2`、`true`） |
+//! | タグ付きの値 | タグを除いた中身の値 |
+//! | undefined・NaN・無限大 | `null` |
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ciborium::value::Value as CborValue;
+use serde_json::{Map, Number, Value as JsonValue};
+use thiserror::Error;
+
+/// CBOR変換で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CborError {
+    /// CBORとして不正なバイト列の場合
+    #[error("Invalid CBOR: {0}")]
+    InvalidCbor(String),
+    /// JSONとして不正な文字列の場合
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+    /// JSONで表現できない値を含む場合（範囲外の整数など）
+    #[error("Unsupported value: {0}")]
+    UnsupportedValue(String),
+}
+
+/// マップのキーをJSONオブジェクトのキーに変換します
+fn key_to_string(key: CborValue) -> Result<String, CborError> {
+    match key {
+        CborValue::Text(text) => Ok(text),
+        other => Ok(cbor_value_to_json(other)?.to_string()),
+    }
+}
+
+/// CBORの値をJSONの値に変換します
+fn cbor_value_to_json(value: CborValue) -> Result<JsonValue, CborError> {
+    Ok(match value {
+        CborValue::Null => JsonValue::Null,
+        CborValue::Bool(b) => JsonValue::Bool(b),
+        CborValue::Integer(integer) => {
+            let integer = i128::from(integer);
+            if let Ok(n) = u64::try_from(integer) {
+                JsonValue::from(n)
+            } else if let Ok(n) = i64::try_from(integer) {
+                JsonValue::from(n)
+            } else {
+                return Err(CborError::UnsupportedValue(format!(
+                    "integer out of range: {}",
+                    integer
+                )));
+            }
+        }
+        CborValue::Float(f) => Number::from_f64(f).map_or(JsonValue::Null, JsonValue::Number),
+        CborValue::Text(text) => JsonValue::String(text),
+        CborValue::Bytes(bytes) => JsonValue::String(URL_SAFE_NO_PAD.encode(bytes)),
+        CborValue::Array(items) => JsonValue::Array(
+            items
+                .into_iter()
+                .map(cbor_value_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        CborValue::Map(entries) => {
+            let mut map = Map::with_capacity(entries.len());
+            for (key, value) in entries {
+                map.insert(key_to_string(key)?, cbor_value_to_json(value)?);
+            }
+            JsonValue::Object(map)
+        }
+        CborValue::Tag(_, inner) => cbor_value_to_json(*inner)?,
+        _ => JsonValue::Null,
+    })
+}
+
+/// CBORのバイト列をJSON文字列に変換します
+///
+/// 変換規則はモジュールのドキュメントを参照してください。
+/// 入力は1つのCBORデータ項目である必要があり、後続のデータは許可されません。
+///
+/// # Arguments
+/// * `bytes` - CBORでエンコードされたデータ
+///
+/// # Errors
+/// * `CborError::InvalidCbor` - CBORとして不正な場合、または後続のデータがある場合
+/// * `CborError::UnsupportedValue` - JSONで表現できない整数を含む場合
+///
+/// # Example
+/// ```
+/// // {1: "a", "b": h'0102'}
+/// let json = cbor_to_json(vec![0xa2, 0x01, 0x61, 0x61, 0x61, 0x62, 0x42, 0x01, 0x02])?;
+/// assert_eq!(json, r#"{"1":"a","b":"AQI"}"#);
+/// ```
+#[uniffi::export]
+pub fn cbor_to_json(bytes: Vec<u8>) -> Result<String, CborError> {
+    let mut reader = bytes.as_slice();
+    let value: CborValue = ciborium::de::from_reader(&mut reader)
+        .map_err(|e| CborError::InvalidCbor(e.to_string()))?;
+    if !reader.is_empty() {
+        return Err(CborError::InvalidCbor(format!(
+            "{} trailing bytes after data item",
+            reader.len()
+        )));
+    }
+    Ok(cbor_value_to_json(value)?.to_string())
+}
+
+/// JSON文字列をCBORのバイト列に変換します
+///
+/// 整数は最短の整数表現に、小数は浮動小数点数に変換されます。
+/// JSONの文字列はすべてCBORのテキスト文字列になります（バイト列には戻りません）。
+///
+/// # Arguments
+/// * `text` - 変換するJSON文字列
+///
+/// # Errors
+/// * `CborError::InvalidJson` - JSONとして不正な場合
+#[uniffi::export]
+pub fn json_to_cbor(text: String) -> Result<Vec<u8>, CborError> {
+    let value: JsonValue =
+        serde_json::from_str(&text).map_err(|e| CborError::InvalidJson(e.to_string()))?;
+    let mut output = Vec::new();
+    ciborium::ser::into_writer(&value, &mut output)
+        .map_err(|e| CborError::UnsupportedValue(e.to_string()))?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_to_cbor_rfc8949_examples() {
+        assert_eq!(json_to_cbor("0".to_string()).unwrap(), vec![0x00]);
+        assert_eq!(json_to_cbor("-1".to_string()).unwrap(), vec![0x20]);
+        assert_eq!(json_to_cbor("1000".to_string()).unwrap(), vec![0x19, 0x03, 0xe8]);
+        assert_eq!(json_to_cbor("\"IETF\"".to_string()).unwrap(), b"\x64IETF".to_vec());
+        assert_eq!(
+            json_to_cbor("[1,[2,3]]".to_string()).unwrap(),
+            vec![0x82, 0x01, 0x82, 0x02, 0x03]
+        );
+        assert_eq!(
+            json_to_cbor(r#"{"a":1}"#.to_string()).unwrap(),
+            vec![0xa1, 0x61, 0x61, 0x01]
+        );
+    }
+
+    #[test]
+    fn test_cbor_json_roundtrip() {
+        let json = r#"{"id":42,"name":"sensor","nested":{"k":"v"},"values":[1.5,-2,null,true]}"#;
+        let cbor = json_to_cbor(json.to_string()).unwrap();
+        assert_eq!(cbor_to_json(cbor).unwrap(), json);
+    }
+
+    #[test]
+    fn test_cbor_to_json_cose_key() {
+        // COSE_Key: {1: 2, 3: -7, -1: 1, -2: h'0102'}
+        let cose = vec![0xa4, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x42, 0x01, 0x02];
+        assert_eq!(
+            cbor_to_json(cose).unwrap(),
+            r#"{"-1":1,"-2":"AQI","1":2,"3":-7}"#
+        );
+    }
+
+    #[test]
+    fn test_cbor_to_json_tags_and_undefined() {
+        // 1(1363896240), undefined
+        assert_eq!(
+            cbor_to_json(vec![0xc1, 0x1a, 0x51, 0x4b, 0x67, 0xb0]).unwrap(),
+            "1363896240"
+        );
+        assert_eq!(cbor_to_json(vec![0xf7]).unwrap(), "null");
+    }
+
+    #[test]
+    fn test_cbor_errors() {
+        match cbor_to_json(vec![0x82, 0x01]) {
+            Err(CborError::InvalidCbor(_)) => (),
+            other => panic!("Expected InvalidCbor error, got {:?}", other),
+        }
+        match cbor_to_json(vec![0x01, 0x02]) {
+            Err(CborError::InvalidCbor(_)) => (),
+            other => panic!("Expected InvalidCbor error, got {:?}", other),
+        }
+        match json_to_cbor("{".to_string()) {
+            Err(CborError::InvalidJson(_)) => (),
+            other => panic!("Expected InvalidJson error, got {:?}", other),
+        }
+        // 負の整数 -2^64
+        match cbor_to_json(vec![0x3b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]) {
+            Err(CborError::UnsupportedValue(_)) => (),
+            other => panic!("Expected UnsupportedValue error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! チェックサムモジュール
+//!
+//! このモジュールは、ダウンロードしたチャンクの検証などに使用する高速な
+//! チェックサム関数（CRC-32、CRC-32C、Adler-32）をエクスポートします。
+//! 改ざん検知が必要な場合は`hash`モジュールや`mac`モジュールを使用してください。
+
+/// Adler-32の法（65521以下の最大の素数）
+const ADLER_MOD: u32 = 65521;
+
+/// Adler-32でオーバーフローせずに処理できる最大のブロック長
+const ADLER_NMAX: usize = 5552;
+
+/// CRC-32（IEEE 802.3、zlib/gzip/PNGと同じ多項式）を計算します
+///
+/// # Arguments
+/// * `data` - チェックサムを計算するデータ
+///
+/// # Example
+/// ```
+/// assert_eq!(crc32(b"123456789".to_vec()), 0xCBF43926);
+/// ```
+#[uniffi::export]
+pub fn crc32(data: Vec<u8>) -> u32 {
+    crc32fast::hash(&data)
+}
+
+/// CRC-32C（Castagnoli多項式、iSCSI/ext4などで使用）を計算します
+///
+/// 対応するCPUではハードウェア命令で高速に計算されます。
+///
+/// # Arguments
+/// * `data` - チェックサムを計算するデータ
+#[uniffi::export]
+pub fn crc32c(data: Vec<u8>) -> u32 {
+    crc32c::crc32c(&data)
+}
+
+/// Adler-32（zlibストリームの末尾で使用）を計算します
+///
+/// # Arguments
+/// * `data` - チェックサムを計算するデータ
+#[uniffi::export]
+pub fn adler32(data: Vec<u8>) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for chunk in data.chunks(ADLER_NMAX) {
+        for byte in chunk {
+            a += u32::from(*byte);
+            b += a;
+        }
+        a %= ADLER_MOD;
+        b %= ADLER_MOD;
+    }
+    (b << 16) | a
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789".to_vec()), 0xCBF43926);
+        assert_eq!(crc32(Vec::new()), 0);
+    }
+
+    #[test]
+    fn test_crc32c_check_value() {
+        assert_eq!(crc32c(b"123456789".to_vec()), 0xE3069283);
+    }
+
+    #[test]
+    fn test_adler32() {
+        assert_eq!(adler32(b"Wikipedia".to_vec()), 0x11E60398);
+        assert_eq!(adler32(Vec::new()), 1);
+    }
+
+    #[test]
+    fn test_adler32_large_input() {
+        // ブロック境界をまたぐ入力でもオーバーフローしないこと
+        let data = vec![0xFFu8; 100_000];
+        let mut a: u64 = 1;
+        let mut b: u64 = 0;
+        for byte in &data {
+            a = (a + u64::from(*byte)) % 65521;
+            b = (b + a) % 65521;
+        }
+        assert_eq!(adler32(data), ((b << 16) | a) as u32);
+    }
+}
@@ -0,0 +1,376 @@
+//! 圧縮モジュール
+//!
+//! このモジュールは、gzip（RFC 1952）、deflate（RFC 1951、ヘッダーなし）、
+//! Zstandard（RFC 8878）によるデータの圧縮・展開を行う関数をエクスポートします。
+//! アナリティクスのイベントをまとめてアップロードする前の圧縮などに使用します。
+//!
+//! 似た構造の小さなJSONレコードを大量に扱うオフライン同期では、
+//! `train_dictionary`で学習した辞書をZstandardに指定すると圧縮率が大きく向上します。
+//! 辞書は送信側と受信側で同じものを使用する必要があります。
+//!
+//! 展開時は展開後のサイズの上限を必ず指定します。小さな入力が巨大なデータに
+//! 展開される圧縮爆弾を受け取っても、上限を超えた時点で処理を打ち切ります。
+
+use std::io::{Read, Write};
+
+use flate2::read::{DeflateDecoder, MultiGzDecoder};
+use flate2::write::{DeflateEncoder, GzEncoder};
+use flate2::Compression;
+use thiserror::Error;
+
+/// gzip・deflateの最大の圧縮レベル
+const MAX_LEVEL: u32 = 9;
+
+/// Zstandardの最大の圧縮レベル
+const MAX_ZSTD_LEVEL: u32 = 22;
+
+/// 展開時に受け付けるZstandardのウィンドウサイズの上限（2^27 = 128 MiB）
+const MAX_ZSTD_WINDOW_LOG: u32 = 27;
+
+/// 圧縮・展開で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CompressionError {
+    /// 圧縮レベルが範囲外の場合
+    #[error("Invalid compression level: {0}")]
+    InvalidLevel(u32),
+    /// 圧縮データとして不正な場合
+    #[error("Invalid compressed data: {0}")]
+    InvalidData(String),
+    /// 展開後のサイズが上限を超える場合
+    #[error("Decompressed size exceeds the limit of {0} bytes")]
+    SizeLimitExceeded(u64),
+    /// 辞書が不正な場合、または辞書の学習に失敗した場合（サンプルが少なすぎるなど）
+    #[error("Invalid dictionary: {0}")]
+    InvalidDictionary(String),
+}
+
+/// 圧縮レベルを確認してflate2の設定に変換します
+fn flate_level(level: u32) -> Result<Compression, CompressionError> {
+    if level > MAX_LEVEL {
+        return Err(CompressionError::InvalidLevel(level));
+    }
+    Ok(Compression::new(level))
+}
+
+/// デコーダーから上限までのデータを読み出します
+fn read_limited<R: Read>(decoder: R, max_size: u64) -> Result<Vec<u8>, CompressionError> {
+    let mut output = Vec::new();
+    decoder
+        .take(max_size.saturating_add(1))
+        .read_to_end(&mut output)
+        .map_err(|e| CompressionError::InvalidData(e.to_string()))?;
+    if output.len() as u64 > max_size {
+        return Err(CompressionError::SizeLimitExceeded(max_size));
+    }
+    Ok(output)
+}
+
+/// データをgzip形式で圧縮します
+///
+/// # Arguments
+/// * `data` - 圧縮するデータ
+/// * `level` - 圧縮レベル（0〜9、0は無圧縮、9は最大圧縮、既定値は6）
+///
+/// # Errors
+/// * `CompressionError::InvalidLevel` - 圧縮レベルが範囲外の場合
+///
+/// # Example
+/// ```
+/// let compressed = gzip_compress(batch_json.into_bytes(), 6)?;
+/// // request.setValue("gzip", forHTTPHeaderField: "Content-Encoding")
+/// ```
+#[uniffi::export(default(level = 6))]
+pub fn gzip_compress(data: Vec<u8>, level: u32) -> Result<Vec<u8>, CompressionError> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate_level(level)?);
+    encoder
+        .write_all(&data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| CompressionError::InvalidData(e.to_string()))
+}
+
+/// gzip形式のデータを展開します
+///
+/// 複数のメンバーを連結したgzipデータは、すべてのメンバーを展開して連結します。
+///
+/// # Arguments
+/// * `data` - gzip形式のデータ
+/// * `max_size` - 展開後のサイズの上限（バイト）
+///
+/// # Errors
+/// * `CompressionError::InvalidData` - gzip形式として不正、またはチェックサムが一致しない場合
+/// * `CompressionError::SizeLimitExceeded` - 展開後のサイズが上限を超える場合
+#[uniffi::export]
+pub fn gzip_decompress(data: Vec<u8>, max_size: u64) -> Result<Vec<u8>, CompressionError> {
+    read_limited(MultiGzDecoder::new(data.as_slice()), max_size)
+}
+
+/// データをdeflate形式（ヘッダーなし）で圧縮します
+///
+/// # Arguments
+/// * `data` - 圧縮するデータ
+/// * `level` - 圧縮レベル（0〜9、既定値は6）
+///
+/// # Errors
+/// * `CompressionError::InvalidLevel` - 圧縮レベルが範囲外の場合
+#[uniffi::export(default(level = 6))]
+pub fn deflate_compress(data: Vec<u8>, level: u32) -> Result<Vec<u8>, CompressionError> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), flate_level(level)?);
+    encoder
+        .write_all(&data)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| CompressionError::InvalidData(e.to_string()))
+}
+
+/// deflate形式（ヘッダーなし）のデータを展開します
+///
+/// # Arguments
+/// * `data` - deflate形式のデータ
+/// * `max_size` - 展開後のサイズの上限（バイト）
+///
+/// # Errors
+/// * `CompressionError::InvalidData` - deflate形式として不正な場合
+/// * `CompressionError::SizeLimitExceeded` - 展開後のサイズが上限を超える場合
+#[uniffi::export]
+pub fn deflate_decompress(data: Vec<u8>, max_size: u64) -> Result<Vec<u8>, CompressionError> {
+    read_limited(DeflateDecoder::new(data.as_slice()), max_size)
+}
+
+/// データをZstandard形式で圧縮します
+///
+/// # Arguments
+/// * `data` - 圧縮するデータ
+/// * `level` - 圧縮レベル（1〜22、既定値は3）
+/// * `dictionary` - `train_dictionary`で学習した辞書（`None`の場合は辞書なし）
+///
+/// # Errors
+/// * `CompressionError::InvalidLevel` - 圧縮レベルが範囲外の場合
+/// * `CompressionError::InvalidDictionary` - 辞書を読み込めない場合
+///
+/// # Example
+/// ```
+/// let dictionary = train_dictionary(sample_records, 16384)?;
+/// let compressed = zstd_compress(record, 3, Some(dictionary.clone()))?;
+/// let restored = zstd_decompress(compressed, 1 << 20, Some(dictionary))?;
+/// ```
+#[uniffi::export(default(level = 3, dictionary = None))]
+pub fn zstd_compress(
+    data: Vec<u8>,
+    level: u32,
+    dictionary: Option<Vec<u8>>,
+) -> Result<Vec<u8>, CompressionError> {
+    if !(1..=MAX_ZSTD_LEVEL).contains(&level) {
+        return Err(CompressionError::InvalidLevel(level));
+    }
+    let mut compressor = match &dictionary {
+        Some(dictionary) => zstd::bulk::Compressor::with_dictionary(level as i32, dictionary)
+            .map_err(|e| CompressionError::InvalidDictionary(e.to_string()))?,
+        None => zstd::bulk::Compressor::new(level as i32)
+            .map_err(|e| CompressionError::InvalidData(e.to_string()))?,
+    };
+    compressor
+        .compress(&data)
+        .map_err(|e| CompressionError::InvalidData(e.to_string()))
+}
+
+/// Zstandard形式のデータを展開します
+///
+/// 複数のフレームを連結したデータは、すべてのフレームを展開して連結します。
+///
+/// # Arguments
+/// * `data` - Zstandard形式のデータ
+/// * `max_size` - 展開後のサイズの上限（バイト）
+/// * `dictionary` - 圧縮時に使用した辞書（`None`の場合は辞書なし）
+///
+/// # Errors
+/// * `CompressionError::InvalidData` - Zstandard形式として不正、辞書が一致しない、
+///   またはフレームのウィンドウサイズが128 MiBを超える場合
+/// * `CompressionError::InvalidDictionary` - 辞書を読み込めない場合
+/// * `CompressionError::SizeLimitExceeded` - 展開後のサイズが上限を超える場合
+#[uniffi::export(default(dictionary = None))]
+pub fn zstd_decompress(
+    data: Vec<u8>,
+    max_size: u64,
+    dictionary: Option<Vec<u8>>,
+) -> Result<Vec<u8>, CompressionError> {
+    let mut decoder = zstd::stream::read::Decoder::with_dictionary(
+        data.as_slice(),
+        dictionary.as_deref().unwrap_or_default(),
+    )
+    .map_err(|e| CompressionError::InvalidDictionary(e.to_string()))?;
+    // 巨大なウィンドウを要求するフレームでメモリを使い果たさないように制限する
+    decoder
+        .window_log_max(MAX_ZSTD_WINDOW_LOG)
+        .map_err(|e| CompressionError::InvalidData(e.to_string()))?;
+    read_limited(decoder, max_size)
+}
+
+/// サンプルからZstandardの辞書を学習します
+///
+/// サンプルには実際に圧縮するデータと同じ構造のレコードを、数百件以上与えてください。
+/// サンプルが少なすぎる場合や多様性が足りない場合は学習に失敗します。
+///
+/// # Arguments
+/// * `samples` - 学習に使用するサンプルの一覧
+/// * `max_size` - 辞書の最大サイズ（バイト、既定値はzstdコマンドと同じ112640）
+///
+/// # Errors
+/// * `CompressionError::InvalidDictionary` - 学習に失敗した場合
+#[uniffi::export(default(max_size = 112640))]
+pub fn train_dictionary(
+    samples: Vec<Vec<u8>>,
+    max_size: u32,
+) -> Result<Vec<u8>, CompressionError> {
+    if samples.is_empty() || max_size == 0 {
+        return Err(CompressionError::InvalidDictionary(
+            "samples and max_size must not be empty".to_string(),
+        ));
+    }
+    zstd::dict::from_samples(&samples, max_size as usize)
+        .map_err(|e| CompressionError::InvalidDictionary(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<u8> {
+        r#"{"event":"screen_view","screen":"home"}"#.repeat(100).into_bytes()
+    }
+
+    #[test]
+    fn test_gzip_roundtrip() {
+        let compressed = gzip_compress(sample(), 6).unwrap();
+        assert_eq!(&compressed[..2], &[0x1f, 0x8b]);
+        assert!(compressed.len() < sample().len() / 10);
+        assert_eq!(gzip_decompress(compressed, 1 << 20).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_gzip_decompress_concatenated_members() {
+        let mut data = gzip_compress(b"hello ".to_vec(), 0).unwrap();
+        data.extend(gzip_compress(b"world".to_vec(), 9).unwrap());
+        assert_eq!(gzip_decompress(data, 100).unwrap(), b"hello world");
+    }
+
+    #[test]
+    fn test_deflate_roundtrip() {
+        let compressed = deflate_compress(sample(), 9).unwrap();
+        assert_eq!(deflate_decompress(compressed, sample().len() as u64).unwrap(), sample());
+        let empty = deflate_compress(Vec::new(), 6).unwrap();
+        assert!(deflate_decompress(empty, 0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_decompress_size_limit() {
+        let bomb = gzip_compress(vec![0u8; 10 * 1024 * 1024], 9).unwrap();
+        assert!(bomb.len() < 64 * 1024);
+        match gzip_decompress(bomb, 1024 * 1024) {
+            Err(CompressionError::SizeLimitExceeded(1048576)) => (),
+            other => panic!("Expected SizeLimitExceeded error, got {:?}", other.map(|d| d.len())),
+        }
+        let compressed = deflate_compress(sample(), 6).unwrap();
+        match deflate_decompress(compressed, sample().len() as u64 - 1) {
+            Err(CompressionError::SizeLimitExceeded(_)) => (),
+            other => panic!("Expected SizeLimitExceeded error, got {:?}", other.map(|d| d.len())),
+        }
+    }
+
+    #[test]
+    fn test_compression_errors() {
+        match gzip_compress(Vec::new(), 10) {
+            Err(CompressionError::InvalidLevel(10)) => (),
+            other => panic!("Expected InvalidLevel error, got {:?}", other),
+        }
+        match gzip_decompress(b"not gzip".to_vec(), 100) {
+            Err(CompressionError::InvalidData(_)) => (),
+            other => panic!("Expected InvalidData error, got {:?}", other),
+        }
+        let mut corrupted = gzip_compress(sample(), 6).unwrap();
+        let len = corrupted.len();
+        corrupted[len - 5] ^= 0xff;
+        match gzip_decompress(corrupted, 1 << 20) {
+            Err(CompressionError::InvalidData(_)) => (),
+            other => panic!("Expected InvalidData error, got {:?}", other.map(|d| d.len())),
+        }
+    }
+
+    fn sample_records() -> Vec<Vec<u8>> {
+        (0..1000)
+            .map(|i| {
+                format!(
+                    concat!(
+                        r#"{{"id":{},"type":"todo","title":"Task number {}","done":{},"#,
+                        r#""tags":["work","sync"],"updated_at":"2026-10-{:02}T09:{:02}:00Z"}}"#
+                    ),
+                    i,
+                    i * 7,
+                    i % 3 == 0,
+                    i % 28 + 1,
+                    i % 60
+                )
+                .into_bytes()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_zstd_roundtrip() {
+        let compressed = zstd_compress(sample(), 3, None).unwrap();
+        assert_eq!(&compressed[..4], &[0x28, 0xb5, 0x2f, 0xfd]);
+        assert_eq!(zstd_decompress(compressed, 1 << 20, None).unwrap(), sample());
+        let max = zstd_compress(sample(), 22, None).unwrap();
+        assert_eq!(zstd_decompress(max, 1 << 20, None).unwrap(), sample());
+    }
+
+    #[test]
+    fn test_zstd_dictionary() {
+        let samples = sample_records();
+        let dictionary = train_dictionary(samples.clone(), 4096).unwrap();
+        assert!(!dictionary.is_empty() && dictionary.len() <= 4096);
+
+        let record = concat!(
+            r#"{"id":5000,"type":"todo","title":"Task number 35000","done":false,"#,
+            r#""tags":["work","sync"],"updated_at":"2026-10-16T09:30:00Z"}"#
+        )
+        .as_bytes()
+        .to_vec();
+        let plain = zstd_compress(record.clone(), 3, None).unwrap();
+        let with_dictionary = zstd_compress(record.clone(), 3, Some(dictionary.clone())).unwrap();
+        assert!(with_dictionary.len() < plain.len() / 2);
+        assert_eq!(
+            zstd_decompress(with_dictionary.clone(), 1024, Some(dictionary)).unwrap(),
+            record
+        );
+        match zstd_decompress(with_dictionary, 1024, None) {
+            Err(CompressionError::InvalidData(_)) => (),
+            other => panic!("Expected InvalidData error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_zstd_errors() {
+        match zstd_compress(sample(), 23, None) {
+            Err(CompressionError::InvalidLevel(23)) => (),
+            other => panic!("Expected InvalidLevel error, got {:?}", other),
+        }
+        let compressed = zstd_compress(sample(), 3, None).unwrap();
+        match zstd_decompress(compressed, 100, None) {
+            Err(CompressionError::SizeLimitExceeded(100)) => (),
+            other => panic!("Expected SizeLimitExceeded error, got {:?}", other.map(|d| d.len())),
+        }
+        // ウィンドウサイズが上限（2^27）を超えるフレーム
+        let mut encoder = zstd::stream::write::Encoder::new(Vec::new(), 3).unwrap();
+        encoder.set_parameter(zstd::stream::raw::CParameter::WindowLog(28)).unwrap();
+        encoder.write_all(&sample()).unwrap();
+        let large_window = encoder.finish().unwrap();
+        match zstd_decompress(large_window, 1 << 20, None) {
+            Err(CompressionError::InvalidData(_)) => (),
+            other => panic!("Expected InvalidData error, got {:?}", other.map(|d| d.len())),
+        }
+        match train_dictionary(vec![b"a".to_vec()], 1024) {
+            Err(CompressionError::InvalidDictionary(_)) => (),
+            other => panic!("Expected InvalidDictionary error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//! 設定ファイル変換モジュール
+//!
+//! このモジュールは、TOMLとYAMLの設定ファイルをJSON文字列に正規化する関数を
+//! エクスポートします。リモート配信やアプリ同梱の設定ファイルを、
+//! 形式によらず共通コアでJSONとして扱うために使用します。
+//! テーブル・マッピングのキーは名前順に並べ替えて出力します。
+//!
+//! JSONにない型は次のように対応付けます。
+//!
+//! | 入力 | JSON |
+//! |------|------|
+//! | TOMLの日時 | RFC 3339形式の文字列（例: `"1979-05-27T07:32:00Z"`） |
+//! | YAMLの文字列以外のマッピングのキー | キーをJSONで表した文字列（例: `1`、`true`） |
+//! | YAMLのタグ付きの値（`!tag`） | タグを除いた中身の値 |
+//! | NaN・無限大 | `null` |
+//!
+//! YAMLのアンカー・エイリアスは展開され、マージキー（`<<`）も適用されます。
+
+use serde_json::{Map, Number, Value as JsonValue};
+use thiserror::Error;
+
+/// 設定ファイルの変換で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum ConfigError {
+    /// TOMLとして不正な文字列の場合（位置は1始まりの行・列）
+    #[error("Invalid TOML at line {line}, column {column}: {message}")]
+    InvalidToml { line: u64, column: u64, message: String },
+    /// YAMLとして不正な文字列の場合（位置は1始まりの行・列、不明な場合は0）
+    #[error("Invalid YAML at line {line}, column {column}: {message}")]
+    InvalidYaml { line: u64, column: u64, message: String },
+}
+
+/// 浮動小数点数をJSONの値に変換します（NaN・無限大は`null`）
+fn float_to_json(value: f64) -> JsonValue {
+    Number::from_f64(value).map_or(JsonValue::Null, JsonValue::Number)
+}
+
+/// TOMLの値をJSONの値に変換します
+fn toml_value_to_json(value: toml::Value) -> JsonValue {
+    match value {
+        toml::Value::String(text) => JsonValue::String(text),
+        toml::Value::Integer(n) => JsonValue::from(n),
+        toml::Value::Float(f) => float_to_json(f),
+        toml::Value::Boolean(b) => JsonValue::Bool(b),
+        toml::Value::Datetime(datetime) => JsonValue::String(datetime.to_string()),
+        toml::Value::Array(items) => {
+            JsonValue::Array(items.into_iter().map(toml_value_to_json).collect())
+        }
+        toml::Value::Table(table) => JsonValue::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| (key, toml_value_to_json(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// YAMLの値をJSONの値に変換します
+fn yaml_value_to_json(value: serde_yaml_ng::Value) -> JsonValue {
+    use serde_yaml_ng::Value as YamlValue;
+
+    match value {
+        YamlValue::Null => JsonValue::Null,
+        YamlValue::Bool(b) => JsonValue::Bool(b),
+        YamlValue::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                JsonValue::from(u)
+            } else if let Some(i) = n.as_i64() {
+                JsonValue::from(i)
+            } else {
+                n.as_f64().map_or(JsonValue::Null, float_to_json)
+            }
+        }
+        YamlValue::String(text) => JsonValue::String(text),
+        YamlValue::Sequence(items) => {
+            JsonValue::Array(items.into_iter().map(yaml_value_to_json).collect())
+        }
+        YamlValue::Mapping(mapping) => {
+            let mut map = Map::with_capacity(mapping.len());
+            for (key, value) in mapping {
+                let key = match yaml_value_to_json(key) {
+                    JsonValue::String(text) => text,
+                    other => other.to_string(),
+                };
+                map.insert(key, yaml_value_to_json(value));
+            }
+            JsonValue::Object(map)
+        }
+        YamlValue::Tagged(tagged) => yaml_value_to_json(tagged.value),
+    }
+}
+
+/// バイト位置を1始まりの(行, 列)に変換します
+fn line_and_column(text: &str, offset: usize) -> (u64, u64) {
+    let before = &text[..text.floor_char_boundary(offset.min(text.len()))];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |s| s.chars().count()) + 1;
+    (line as u64, column as u64)
+}
+
+/// TOML文字列をJSON文字列に変換します
+///
+/// # Arguments
+/// * `text` - TOML形式の設定ファイルの内容
+///
+/// # Errors
+/// * `ConfigError::InvalidToml` - TOMLとして不正な場合
+///
+/// # Example
+/// ```
+/// let json = parse_toml_to_json("[server]\nport = 8080".to_string())?;
+/// assert_eq!(json, r#"{"server":{"port":8080}}"#);
+/// ```
+#[uniffi::export]
+pub fn parse_toml_to_json(text: String) -> Result<String, ConfigError> {
+    let table: toml::Table = toml::from_str(&text).map_err(|e| {
+        let (line, column) = e
+            .span()
+            .map_or((0, 0), |span| line_and_column(&text, span.start));
+        ConfigError::InvalidToml {
+            line,
+            column,
+            message: e.message().to_string(),
+        }
+    })?;
+    Ok(toml_value_to_json(toml::Value::Table(table)).to_string())
+}
+
+/// YAML文字列をJSON文字列に変換します
+///
+/// 入力は1つの文書である必要があります。空の文書は`null`になります。
+///
+/// # Arguments
+/// * `text` - YAML形式の設定ファイルの内容
+///
+/// # Errors
+/// * `ConfigError::InvalidYaml` - YAMLとして不正な場合、または複数の文書を含む場合
+///
+/// # Example
+/// ```
+/// let json = parse_yaml_to_json("server:\n  port: 8080".to_string())?;
+/// assert_eq!(json, r#"{"server":{"port":8080}}"#);
+/// ```
+#[uniffi::export]
+pub fn parse_yaml_to_json(text: String) -> Result<String, ConfigError> {
+    let yaml_error = |e: serde_yaml_ng::Error| {
+        let (line, column) = e
+            .location()
+            .map_or((0, 0), |location| (location.line() as u64, location.column() as u64));
+        ConfigError::InvalidYaml {
+            line,
+            column,
+            message: e.to_string(),
+        }
+    };
+    let mut value: serde_yaml_ng::Value = serde_yaml_ng::from_str(&text).map_err(yaml_error)?;
+    value.apply_merge().map_err(yaml_error)?;
+    Ok(yaml_value_to_json(value).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_toml_to_json() {
+        let toml = r#"
+title = "設定"
+ratio = 0.5
+released = 1979-05-27T07:32:00Z
+
+[server]
+port = 8080
+hosts = ["a", "b"]
+
+[[features]]
+name = "beta"
+enabled = true
+"#;
+        assert_eq!(
+            parse_toml_to_json(toml.to_string()).unwrap(),
+            concat!(
+                r#"{"features":[{"enabled":true,"name":"beta"}],"ratio":0.5,"#,
+                r#""released":"1979-05-27T07:32:00Z","server":{"hosts":["a","b"],"port":8080},"#,
+                r#""title":"設定"}"#
+            )
+        );
+        assert_eq!(parse_toml_to_json("nan = nan".to_string()).unwrap(), r#"{"nan":null}"#);
+    }
+
+    #[test]
+    fn test_parse_yaml_to_json() {
+        let yaml = r#"
+defaults: &defaults
+  timeout: 30
+  retry: true
+production:
+  <<: *defaults
+  host: api.example.com
+ports: [80, 443]
+1: one
+ratio: -1.5
+empty:
+"#;
+        assert_eq!(
+            parse_yaml_to_json(yaml.to_string()).unwrap(),
+            concat!(
+                r#"{"1":"one","defaults":{"retry":true,"timeout":30},"empty":null,"#,
+                r#""ports":[80,443],"#,
+                r#""production":{"host":"api.example.com","retry":true,"timeout":30},"#,
+                r#""ratio":-1.5}"#
+            )
+        );
+        assert_eq!(parse_yaml_to_json(String::new()).unwrap(), "null");
+        assert_eq!(parse_yaml_to_json("!secret abc".to_string()).unwrap(), r#""abc""#);
+    }
+
+    #[test]
+    fn test_config_errors() {
+        match parse_toml_to_json("a = 1\nb = ".to_string()) {
+            Err(ConfigError::InvalidToml { line: 2, .. }) => (),
+            other => panic!("Expected InvalidToml error, got {:?}", other),
+        }
+        match parse_yaml_to_json("a: 1\n b: [".to_string()) {
+            Err(ConfigError::InvalidYaml { .. }) => (),
+            other => panic!("Expected InvalidYaml error, got {:?}", other),
+        }
+        match parse_yaml_to_json("a: 1\n---\nb: 2\n".to_string()) {
+            Err(ConfigError::InvalidYaml { .. }) => (),
+            other => panic!("Expected InvalidYaml error, got {:?}", other),
+        }
+    }
+}
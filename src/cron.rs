@@ -0,0 +1,427 @@
+//! cron式モジュール
+//!
+//! このモジュールは、cron式を検証する`parse_cron`と、次の実行時刻を計算する
+//! `next_occurrences`をエクスポートします。端末でスケジュールするリマインダーの時刻を
+//! サーバーのスケジューラー（Vixie cron互換）と一致させるために使用します。
+//!
+//! # 書式
+//! ```text
+//! ┌───────── 分（0-59）
+//! │ ┌─────── 時（0-23）
+//! │ │ ┌───── 日（1-31）
+//! │ │ │ ┌─── 月（1-12、JAN-DEC）
+//! │ │ │ │ ┌─ 曜日（0-7、SUN-SAT。0と7は日曜）
+//! * * * * *
+//! ```
+//! 各フィールドは`*`・値・範囲（`1-5`）・ステップ（`*/15`・`10-30/5`）とそのリスト（`1,15`）を
+//! 受け付けます。`@yearly`・`@annually`・`@monthly`・`@weekly`・`@daily`・`@midnight`・
+//! `@hourly`も使用できます。日と曜日の両方が`*`以外で始まる場合は、
+//! どちらかに一致すれば実行されます（Vixie cronと同じ）。
+
+use chrono::{Datelike, NaiveDate, NaiveTime};
+use thiserror::Error;
+
+use crate::datetime::{DateTimeError, Timestamp};
+use crate::timezone::Zone;
+
+/// 一度に計算できる実行時刻の最大数
+const MAX_OCCURRENCES: u32 = 1000;
+
+/// 実行時刻を探す最大の日数（2月29日のみの式でも見つかるように28年分）
+const MAX_SEARCH_DAYS: i64 = 28 * 366;
+
+/// 月の名前
+const MONTH_NAMES: [&str; 12] =
+    ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+
+/// 曜日の名前（日曜始まり）
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// cron式の処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CronError {
+    /// cron式が正しくない場合
+    #[error("Invalid cron expression: {0}")]
+    InvalidExpression(String),
+    /// タイムゾーンの指定が正しくない場合
+    #[error("Invalid time zone: {0}")]
+    InvalidTimeZone(String),
+    /// 計算する実行時刻の数が上限を超える場合
+    #[error("Too many occurrences requested: {0} (max 1000)")]
+    TooManyOccurrences(u32),
+}
+
+/// 解析済みのcron式（各フィールドが一致する値の昇順のリスト）
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct CronSchedule {
+    /// 分（0-59）
+    pub minutes: Vec<u8>,
+    /// 時（0-23）
+    pub hours: Vec<u8>,
+    /// 日（1-31）
+    pub days_of_month: Vec<u8>,
+    /// 月（1-12）
+    pub months: Vec<u8>,
+    /// 曜日（0-6、0が日曜）
+    pub days_of_week: Vec<u8>,
+}
+
+/// 実行時刻の計算に使うcron式（日付のフィールドはビットマスク）
+struct CronMatcher {
+    schedule: CronSchedule,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// 日のフィールドが`*`で始まるか
+    day_of_month_star: bool,
+    /// 曜日のフィールドが`*`で始まるか
+    day_of_week_star: bool,
+}
+
+impl CronMatcher {
+    /// 日付が日・月・曜日のフィールドに一致するかを返します
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        if self.months & (1 << date.month()) == 0 {
+            return false;
+        }
+        let day_of_month = self.days_of_month & (1 << date.day()) != 0;
+        let day_of_week =
+            self.days_of_week & (1 << date.weekday().num_days_from_sunday()) != 0;
+        if self.day_of_month_star || self.day_of_week_star {
+            day_of_month && day_of_week
+        } else {
+            day_of_month || day_of_week
+        }
+    }
+}
+
+/// 値（数値または名前）を解析します
+fn parse_value(
+    text: &str,
+    names: &[&str],
+    first_name_value: u32,
+    expression: &str,
+) -> Result<u32, CronError> {
+    if let Some(index) = names.iter().position(|name| name.eq_ignore_ascii_case(text)) {
+        return Ok(index as u32 + first_name_value);
+    }
+    let invalid =
+        || CronError::InvalidExpression(format!("{}: invalid value '{}'", expression, text));
+    if text.is_empty() || text.len() > 2 || !text.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    text.parse().map_err(|_| invalid())
+}
+
+/// 1つのフィールドを解析してビットマスクを返します
+fn parse_field(
+    field: &str,
+    min: u32,
+    max: u32,
+    names: &[&str],
+    first_name_value: u32,
+    expression: &str,
+) -> Result<u64, CronError> {
+    let invalid =
+        |message: String| CronError::InvalidExpression(format!("{}: {}", expression, message));
+    let mut mask = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .ok()
+                    .filter(|step| *step > 0 && *step <= max)
+                    .ok_or_else(|| invalid(format!("invalid step in '{}'", item)))?;
+                (range, Some(step))
+            }
+            None => (item, None),
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, names, first_name_value, expression)?,
+                parse_value(end, names, first_name_value, expression)?,
+            )
+        } else {
+            let value = parse_value(range, names, first_name_value, expression)?;
+            // `5/15`は5から最大値までのステップ
+            (value, if step.is_some() { max } else { value })
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid(format!("'{}' is out of range {}-{}", item, min, max)));
+        }
+        for value in (start..=end).step_by(step.unwrap_or(1) as usize) {
+            mask |= 1 << value;
+        }
+    }
+    Ok(mask)
+}
+
+/// ビットマスクを値のリストに変換します
+fn mask_values(mask: u64, min: u32, max: u32) -> Vec<u8> {
+    (min..=max).filter(|value| mask & (1 << value) != 0).map(|value| value as u8).collect()
+}
+
+/// cron式を解析します
+fn parse_matcher(expression: &str) -> Result<CronMatcher, CronError> {
+    let trimmed = expression.trim();
+    let expanded = match trimmed.to_ascii_lowercase().as_str() {
+        "@yearly" | "@annually" => "0 0 1 1 *",
+        "@monthly" => "0 0 1 * *",
+        "@weekly" => "0 0 * * 0",
+        "@daily" | "@midnight" => "0 0 * * *",
+        "@hourly" => "0 * * * *",
+        other if other.starts_with('@') => {
+            return Err(CronError::InvalidExpression(format!("unknown macro: {}", trimmed)))
+        }
+        _ => trimmed,
+    };
+    let fields: Vec<&str> = expanded.split_whitespace().collect();
+    let [minute, hour, day_of_month, month, day_of_week] = fields[..] else {
+        return Err(CronError::InvalidExpression(format!(
+            "{}: expected 5 fields, got {}",
+            trimmed,
+            fields.len()
+        )));
+    };
+    let minutes = parse_field(minute, 0, 59, &[], 0, trimmed)?;
+    let hours = parse_field(hour, 0, 23, &[], 0, trimmed)?;
+    let days_of_month = parse_field(day_of_month, 1, 31, &[], 0, trimmed)?;
+    let months = parse_field(month, 1, 12, &MONTH_NAMES, 1, trimmed)?;
+    let mut days_of_week = parse_field(day_of_week, 0, 7, &WEEKDAY_NAMES, 0, trimmed)?;
+    // 7は日曜
+    if days_of_week & (1 << 7) != 0 {
+        days_of_week = (days_of_week | 1) & !(1 << 7);
+    }
+    let schedule = CronSchedule {
+        minutes: mask_values(minutes, 0, 59),
+        hours: mask_values(hours, 0, 23),
+        days_of_month: mask_values(days_of_month, 1, 31),
+        months: mask_values(months, 1, 12),
+        days_of_week: mask_values(days_of_week, 0, 6),
+    };
+    Ok(CronMatcher {
+        schedule,
+        days_of_month,
+        months,
+        days_of_week,
+        day_of_month_star: day_of_month.starts_with('*'),
+        day_of_week_star: day_of_week.starts_with('*'),
+    })
+}
+
+/// cron式を検証し、各フィールドが一致する値を返します
+///
+/// # Arguments
+/// * `expr` - 5フィールドのcron式、または`@daily`などのマクロ
+///
+/// # Errors
+/// * `CronError::InvalidExpression` - フィールドの数・値・範囲・ステップが正しくない場合
+///
+/// # Example
+/// ```
+/// let schedule = parse_cron("*/15 9-17 * * MON-FRI".to_string())?;
+/// assert_eq!(schedule.minutes, vec![0, 15, 30, 45]);
+/// assert_eq!(schedule.days_of_week, vec![1, 2, 3, 4, 5]);
+/// ```
+#[uniffi::export]
+pub fn parse_cron(expr: String) -> Result<CronSchedule, CronError> {
+    Ok(parse_matcher(&expr)?.schedule)
+}
+
+/// 指定した時点より後の実行時刻を計算します
+///
+/// 時刻は`timezone`のローカル時刻で評価します。夏時間の開始で存在しない時刻は
+/// 切り替え量だけ後ろにずらし（02:30は03:30）、夏時間の終了で2回現れる時刻は
+/// 早い方で1回だけ実行します。28年以内に実行時刻がない式（`0 0 30 2 *`など）では、
+/// 見つかった分だけを返します。
+///
+/// # Arguments
+/// * `expr` - cron式
+/// * `after` - この時点より後（等しい時刻は含まない）の実行時刻を返します
+/// * `count` - 計算する実行時刻の数（最大1000）
+/// * `timezone` - 評価に使うタイムゾーン（省略時は`UTC`）
+///
+/// # Returns
+/// 昇順の実行時刻（`utc_offset_seconds`はその時点での`timezone`のオフセット）
+///
+/// # Errors
+/// * `CronError::InvalidExpression` - cron式が正しくない場合
+/// * `CronError::InvalidTimeZone` - タイムゾーンの指定が正しくない場合
+/// * `CronError::TooManyOccurrences` - `count`が1000を超える場合
+///
+/// # Example
+/// ```
+/// let next = next_occurrences(
+///     "0 9 * * MON-FRI".to_string(),
+///     now,
+///     5,
+///     Some("Asia/Tokyo".to_string()),
+/// )?;
+/// ```
+#[uniffi::export(default(timezone = None))]
+pub fn next_occurrences(
+    expr: String,
+    after: Timestamp,
+    count: u32,
+    timezone: Option<String>,
+) -> Result<Vec<Timestamp>, CronError> {
+    if count > MAX_OCCURRENCES {
+        return Err(CronError::TooManyOccurrences(count));
+    }
+    let matcher = parse_matcher(&expr)?;
+    let timezone = timezone.unwrap_or_else(|| "UTC".to_string());
+    let zone = Zone::parse(&timezone).map_err(|_| CronError::InvalidTimeZone(timezone.clone()))?;
+    let out_of_range = |error: DateTimeError| {
+        CronError::InvalidExpression(format!("{}: {}", expr, error))
+    };
+
+    // 基準時刻のローカル日時から探し始める（夏時間の切り替えに備えて1日前から）
+    let offset = zone.offset_at(after.unix_seconds).map_err(out_of_range)?;
+    let start = Timestamp { nanoseconds: 0, utc_offset_seconds: offset, ..after }
+        .local_datetime()
+        .map_err(out_of_range)?
+        .date()
+        .pred_opt()
+        .ok_or_else(|| CronError::InvalidExpression(format!("{}: time is out of range", expr)))?;
+
+    let mut occurrences: Vec<Timestamp> = Vec::new();
+    let mut last = after.unix_seconds;
+    for date in start.iter_days().take(MAX_SEARCH_DAYS as usize) {
+        if occurrences.len() >= count as usize {
+            break;
+        }
+        if !matcher.matches_date(date) {
+            continue;
+        }
+        for hour in &matcher.schedule.hours {
+            for minute in &matcher.schedule.minutes {
+                if occurrences.len() >= count as usize {
+                    break;
+                }
+                let time = NaiveTime::from_hms_opt(u32::from(*hour), u32::from(*minute), 0)
+                    .expect("cron fields are validated");
+                let unix_seconds = zone.to_unix(&date.and_time(time)).map_err(out_of_range)?;
+                // 夏時間の切り替えで同じ時刻・逆順になる場合を除く
+                if unix_seconds <= last {
+                    continue;
+                }
+                last = unix_seconds;
+                occurrences.push(Timestamp {
+                    unix_seconds,
+                    nanoseconds: 0,
+                    utc_offset_seconds: zone.offset_at(unix_seconds).map_err(out_of_range)?,
+                });
+            }
+        }
+    }
+    Ok(occurrences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::{format_datetime, parse_datetime};
+
+    fn at(text: &str) -> Timestamp {
+        parse_datetime(text.to_string(), None).unwrap()
+    }
+
+    fn next(expr: &str, after: &str, count: u32, timezone: Option<&str>) -> Vec<String> {
+        next_occurrences(expr.to_string(), at(after), count, timezone.map(str::to_string))
+            .unwrap()
+            .into_iter()
+            .map(|t| format_datetime(t, "yyyy-MM-dd'T'HH:mmXXX".to_string(), None).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_cron() {
+        let schedule = parse_cron("*/15 9-17 * * MON-FRI".to_string()).unwrap();
+        assert_eq!(schedule.minutes, vec![0, 15, 30, 45]);
+        assert_eq!(schedule.hours, (9..=17).collect::<Vec<u8>>());
+        assert_eq!(schedule.days_of_month.len(), 31);
+        assert_eq!(schedule.days_of_week, vec![1, 2, 3, 4, 5]);
+
+        let schedule = parse_cron("5,10-20/5 0 1 jan,Jul 7".to_string()).unwrap();
+        assert_eq!(schedule.minutes, vec![5, 10, 15, 20]);
+        assert_eq!(schedule.months, vec![1, 7]);
+        assert_eq!(schedule.days_of_week, vec![0]);
+        assert_eq!(parse_cron("@weekly".to_string()).unwrap().days_of_week, vec![0]);
+        assert_eq!(parse_cron("30/10 * * * *".to_string()).unwrap().minutes, vec![30, 40, 50]);
+
+        for expr in [
+            "* * * *", "60 * * * *", "* 24 * * *", "* * 0 * *", "* * * 13 *", "* * * * 8",
+            "*/0 * * * *", "5-1 * * * *", "a * * * *", "@reboot", "* * * FOO *", "1,,2 * * * *",
+        ] {
+            match parse_cron(expr.to_string()) {
+                Err(CronError::InvalidExpression(_)) => (),
+                other => panic!("Expected InvalidExpression error for {}, got {:?}", expr, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_next_occurrences() {
+        assert_eq!(
+            next("*/15 9-17 * * MON-FRI", "2024-01-05T17:40:00Z", 3, None),
+            vec!["2024-01-05T17:45Z", "2024-01-08T09:00Z", "2024-01-08T09:15Z"]
+        );
+        // 基準時刻と等しい時刻は含まない
+        assert_eq!(next("0 * * * *", "2024-01-01T10:00:00Z", 1, None), vec!["2024-01-01T11:00Z"]);
+        assert_eq!(
+            next("0 9 * * *", "2023-12-31T12:00:00Z", 2, Some("Asia/Tokyo")),
+            vec!["2024-01-01T09:00+09:00", "2024-01-02T09:00+09:00"]
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00Z", 2, None),
+            vec!["2028-02-29T00:00Z", "2032-02-29T00:00Z"]
+        );
+        assert!(next("0 0 30 2 *", "2024-01-01T00:00:00Z", 1, None).is_empty());
+    }
+
+    #[test]
+    fn test_next_occurrences_day_of_month_or_day_of_week() {
+        // 日と曜日の両方を指定した場合はどちらかに一致すればよい
+        assert_eq!(
+            next("0 0 13 * FRI", "2024-09-01T00:00:00Z", 3, None),
+            vec!["2024-09-06T00:00Z", "2024-09-13T00:00Z", "2024-09-20T00:00Z"]
+        );
+        // 曜日が`*`の場合は日のみ
+        assert_eq!(
+            next("0 0 13 * *", "2024-09-01T00:00:00Z", 2, None),
+            vec!["2024-09-13T00:00Z", "2024-10-13T00:00Z"]
+        );
+    }
+
+    #[test]
+    fn test_next_occurrences_across_dst() {
+        let new_york = Some("America/New_York");
+        // 存在しない02:30は03:30（EDT）
+        assert_eq!(
+            next("30 2 * * *", "2024-03-09T12:00:00Z", 2, new_york),
+            vec!["2024-03-10T03:30-04:00", "2024-03-11T02:30-04:00"]
+        );
+        // 2回現れる01:30は1回だけ
+        assert_eq!(
+            next("30 1 * * *", "2024-11-02T12:00:00Z", 3, new_york),
+            vec!["2024-11-03T01:30-04:00", "2024-11-04T01:30-05:00", "2024-11-05T01:30-05:00"]
+        );
+    }
+
+    #[test]
+    fn test_next_occurrences_errors() {
+        match next_occurrences("* * * * *".to_string(), at("2024-01-01T00:00:00Z"), 1001, None) {
+            Err(CronError::TooManyOccurrences(1001)) => (),
+            other => panic!("Expected TooManyOccurrences error, got {:?}", other),
+        }
+        let timezone = Some("Mars/Olympus_Mons".to_string());
+        match next_occurrences("* * * * *".to_string(), at("2024-01-01T00:00:00Z"), 1, timezone) {
+            Err(CronError::InvalidTimeZone(_)) => (),
+            other => panic!("Expected InvalidTimeZone error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! CSV読み書きモジュール
+//!
+//! このモジュールは、CSVを1行ずつ読み出す`CsvReader`と、
+//! 行の一覧をCSV文字列に書き出す`write_csv`をエクスポートします。
+//! データのインポート・エクスポート機能で、プラットフォーム間で
+//! 同じ解釈規則（RFC 4180準拠のクォート・改行の扱い）を共有するために使用します。
+
+use std::fs::File;
+use std::io::{Cursor, Read};
+use std::sync::{Arc, Mutex};
+
+use thiserror::Error;
+
+/// CSV処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum CsvError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ファイルの読み込みに失敗した場合
+    #[error("Failed to access CSV file: {0}")]
+    IoError(String),
+    /// 区切り文字・クォート文字の指定が不正な場合
+    #[error("Invalid CSV options: {0}")]
+    InvalidOptions(String),
+    /// CSVの内容が不正な場合（列数の不一致、UTF-8として不正など）
+    #[error("Invalid CSV at line {line}: {message}")]
+    InvalidCsv { line: u64, message: String },
+}
+
+impl From<csv::Error> for CsvError {
+    fn from(error: csv::Error) -> Self {
+        if error.is_io_error() {
+            return CsvError::IoError(error.to_string());
+        }
+        CsvError::InvalidCsv {
+            line: error.position().map_or(0, |position| position.line()),
+            message: error.to_string(),
+        }
+    }
+}
+
+/// CSVの読み書きの設定
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct CsvOptions {
+    /// フィールドの区切り文字（ASCII 1文字）
+    #[uniffi(default = ",")]
+    pub delimiter: String,
+    /// クォート文字（ASCII 1文字）
+    #[uniffi(default = "\"")]
+    pub quote: String,
+    /// 読み込み時に先頭行をヘッダーとして扱うかどうか
+    #[uniffi(default = true)]
+    pub has_headers: bool,
+    /// 読み込み時に行ごとの列数の違いを許可するかどうか
+    #[uniffi(default = false)]
+    pub flexible: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ",".to_string(),
+            quote: "\"".to_string(),
+            has_headers: true,
+            flexible: false,
+        }
+    }
+}
+
+impl CsvOptions {
+    /// 区切り文字・クォート文字をバイトに変換します
+    fn delimiter_and_quote(&self) -> Result<(u8, u8), CsvError> {
+        let single_ascii = |name: &str, value: &str| match value.as_bytes() {
+            [byte] if byte.is_ascii() && *byte != b'\n' && *byte != b'\r' => Ok(*byte),
+            _ => Err(CsvError::InvalidOptions(format!(
+                "{} must be a single ASCII character other than a line break",
+                name
+            ))),
+        };
+        let delimiter = single_ascii("delimiter", &self.delimiter)?;
+        let quote = single_ascii("quote", &self.quote)?;
+        if delimiter == quote {
+            return Err(CsvError::InvalidOptions(
+                "delimiter and quote must be different".to_string(),
+            ));
+        }
+        Ok((delimiter, quote))
+    }
+}
+
+/// CSVの1行
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct CsvRow {
+    /// 行が始まる行番号（1始まり）
+    ///
+    /// クォート内の改行を含む行は先頭の行番号です。直前に空行がある場合は
+    /// 空行の位置を指すため、エラー表示などの目安として使用してください。
+    pub line: u64,
+    /// 各フィールドの値
+    pub fields: Vec<String>,
+}
+
+/// CSVを1行ずつ読み出すリーダー
+///
+/// 文字列またはファイルから作成し、`next_row`で`None`が返るまで行を読み出します。
+/// ファイルは全体を読み込まずに少しずつ読み進めるため、大きなファイルにも使用できます。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let reader = CsvReader::open(path, None)?;
+/// let headers = reader.headers()?;
+/// while let Some(row) = reader.next_row()? {
+///     // row.fields
+/// }
+/// ```
+#[derive(uniffi::Object)]
+pub struct CsvReader {
+    headers: Option<Vec<String>>,
+    reader: Mutex<csv::Reader<Box<dyn Read + Send>>>,
+}
+
+impl CsvReader {
+    /// 入力元と設定からリーダーを作成し、ヘッダー行を読み込みます
+    fn with_source(
+        source: Box<dyn Read + Send>,
+        options: Option<CsvOptions>,
+    ) -> Result<Arc<Self>, CsvError> {
+        let options = options.unwrap_or_default();
+        let (delimiter, quote) = options.delimiter_and_quote()?;
+        let mut reader = csv::ReaderBuilder::new()
+            .delimiter(delimiter)
+            .quote(quote)
+            .has_headers(options.has_headers)
+            .flexible(options.flexible)
+            .from_reader(source);
+        let headers = if options.has_headers {
+            Some(reader.headers()?.iter().map(str::to_string).collect())
+        } else {
+            None
+        };
+        Ok(Arc::new(Self {
+            headers,
+            reader: Mutex::new(reader),
+        }))
+    }
+}
+
+#[uniffi::export]
+impl CsvReader {
+    /// 文字列からリーダーを作成します
+    ///
+    /// # Arguments
+    /// * `text` - CSV文字列
+    /// * `options` - 読み込みの設定（`None`の場合はカンマ区切り・ヘッダーあり）
+    ///
+    /// # Errors
+    /// * `CsvError::InvalidOptions` - 設定が不正な場合
+    /// * `CsvError::InvalidCsv` - ヘッダー行が不正な場合
+    #[uniffi::constructor(default(options = None))]
+    pub fn from_string(text: String, options: Option<CsvOptions>) -> Result<Arc<Self>, CsvError> {
+        Self::with_source(Box::new(Cursor::new(text.into_bytes())), options)
+    }
+
+    /// ファイルからリーダーを作成します
+    ///
+    /// # Arguments
+    /// * `path` - CSVファイルのパス
+    /// * `options` - 読み込みの設定（`None`の場合はカンマ区切り・ヘッダーあり）
+    ///
+    /// # Errors
+    /// * `CsvError::IoError` - ファイルを開けない場合
+    /// * `CsvError::InvalidOptions` - 設定が不正な場合
+    /// * `CsvError::InvalidCsv` - ヘッダー行が不正な場合
+    #[uniffi::constructor(default(options = None))]
+    pub fn open(path: String, options: Option<CsvOptions>) -> Result<Arc<Self>, CsvError> {
+        let file = File::open(&path).map_err(|e| CsvError::IoError(e.to_string()))?;
+        Self::with_source(Box::new(file), options)
+    }
+
+    /// ヘッダー行のフィールドを返します（`has_headers`が`false`の場合は`None`）
+    pub fn headers(&self) -> Option<Vec<String>> {
+        self.headers.clone()
+    }
+
+    /// 次の行を読み出します
+    ///
+    /// 空行は読み飛ばされます。
+    ///
+    /// # Returns
+    /// 次の行。最後まで読み終えた場合は`None`
+    ///
+    /// # Errors
+    /// * `CsvError::InvalidCsv` - 行の列数が異なる場合（`flexible`でない場合）やUTF-8として不正な場合
+    /// * `CsvError::IoError` - ファイルの読み込みに失敗した場合
+    /// * `CsvError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn next_row(&self) -> Result<Option<CsvRow>, CsvError> {
+        let mut reader = self.reader.lock()
+            .map_err(|_| CsvError::MutexPoisoned)?;
+        let mut record = csv::StringRecord::new();
+        if !reader.read_record(&mut record)? {
+            return Ok(None);
+        }
+        Ok(Some(CsvRow {
+            line: record.position().map_or(0, |position| position.line()),
+            fields: record.iter().map(str::to_string).collect(),
+        }))
+    }
+}
+
+/// 行の一覧をCSV文字列に書き出します
+///
+/// 区切り文字・クォート文字・改行を含むフィールドは自動的にクォートされます。
+/// ヘッダー行が必要な場合は`rows`の先頭に含めてください
+/// （`has_headers`・`flexible`は書き出しには影響しません）。
+/// 各行は`\n`で終わります。
+///
+/// # Arguments
+/// * `rows` - 書き出す行の一覧
+/// * `options` - 区切り文字・クォート文字の設定（`None`の場合はカンマ区切り）
+///
+/// # Errors
+/// * `CsvError::InvalidOptions` - 設定が不正な場合
+///
+/// # Example
+/// ```
+/// let csv = write_csv(vec![vec!["name".into(), "note".into()],
+///                          vec!["Alice".into(), "a, b".into()]], None)?;
+/// assert_eq!(csv, "name,note\nAlice,\"a, b\"\n");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn write_csv(rows: Vec<Vec<String>>, options: Option<CsvOptions>) -> Result<String, CsvError> {
+    let (delimiter, quote) = options.unwrap_or_default().delimiter_and_quote()?;
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .flexible(true)
+        .from_writer(Vec::new());
+    for row in &rows {
+        writer.write_record(row)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| CsvError::IoError(e.to_string()))?;
+    String::from_utf8(bytes).map_err(|e| CsvError::IoError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(reader: &CsvReader) -> Vec<CsvRow> {
+        let mut rows = Vec::new();
+        while let Some(row) = reader.next_row().unwrap() {
+            rows.push(row);
+        }
+        rows
+    }
+
+    #[test]
+    fn test_csv_reader_from_string() {
+        let text = "name,note\nAlice,\"hello, world\"\nBob,\"multi\nline \"\"quoted\"\"\"\n";
+        let reader = CsvReader::from_string(text.to_string(), None).unwrap();
+        assert_eq!(reader.headers(), Some(vec!["name".to_string(), "note".to_string()]));
+        let rows = read_all(&reader);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].line, 2);
+        assert_eq!(rows[0].fields, vec!["Alice", "hello, world"]);
+        assert_eq!(rows[1].line, 3);
+        assert_eq!(rows[1].fields, vec!["Bob", "multi\nline \"quoted\""]);
+        assert_eq!(reader.next_row().unwrap(), None);
+    }
+
+    #[test]
+    fn test_csv_reader_options() {
+        let options = CsvOptions {
+            delimiter: ";".to_string(),
+            quote: "'".to_string(),
+            has_headers: false,
+            flexible: true,
+        };
+        let reader = CsvReader::from_string("a;'b;c'\nd\n".to_string(), Some(options)).unwrap();
+        assert_eq!(reader.headers(), None);
+        let rows = read_all(&reader);
+        assert_eq!(rows[0].fields, vec!["a", "b;c"]);
+        assert_eq!(rows[1].fields, vec!["d"]);
+    }
+
+    #[test]
+    fn test_csv_reader_open_file() {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_csv_{}_{}.csv", "open", std::process::id()));
+        std::fs::write(&path, "id,value\r\n1,x\r\n2,y\r\n").unwrap();
+        let reader = CsvReader::open(path.to_string_lossy().to_string(), None).unwrap();
+        let rows = read_all(&reader);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].fields, vec!["2", "y"]);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let rows = vec![
+            vec!["name".to_string(), "note".to_string()],
+            vec!["Alice".to_string(), "a, \"b\"\nc".to_string()],
+        ];
+        let csv = write_csv(rows.clone(), None).unwrap();
+        assert_eq!(csv, "name,note\nAlice,\"a, \"\"b\"\"\nc\"\n");
+
+        let reader = CsvReader::from_string(csv, None).unwrap();
+        assert_eq!(read_all(&reader)[0].fields, rows[1]);
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let reader = CsvReader::from_string("a,b\n1,2\n3\n".to_string(), None).unwrap();
+        reader.next_row().unwrap();
+        match reader.next_row() {
+            Err(CsvError::InvalidCsv { line: 3, .. }) => (),
+            other => panic!("Expected InvalidCsv error, got {:?}", other),
+        }
+        let options = CsvOptions {
+            delimiter: "::".to_string(),
+            ..CsvOptions::default()
+        };
+        match write_csv(Vec::new(), Some(options)) {
+            Err(CsvError::InvalidOptions(_)) => (),
+            other => panic!("Expected InvalidOptions error, got {:?}", other),
+        }
+        match CsvReader::open("/nonexistent/mobile.csv".to_string(), None) {
+            Err(CsvError::IoError(_)) => (),
+            other => panic!("Expected IoError error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
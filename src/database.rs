@@ -0,0 +1,494 @@
+//! SQLiteデータベースモジュール
+//!
+//! このモジュールは、SQLiteのデータベースファイルを操作する`Database`をエクスポートします。
+//! SQLの実行・問い合わせ・トランザクションをRustコアに集約し、
+//! iOS・Androidで同じデータ層を共有するために使用します。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use rusqlite::types::{ToSqlOutput, Value, ValueRef};
+use rusqlite::{params_from_iter, Connection, ErrorCode, ToSql};
+use thiserror::Error;
+
+/// 既定のビジータイムアウト（ミリ秒）
+const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5000;
+
+/// データベースの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DatabaseError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ビジータイムアウトまで待ってもデータベースのロックを取得できなかった場合
+    #[error("Database is busy: {0}")]
+    Busy(String),
+    /// 一意制約・外部キー制約などに違反した場合
+    #[error("Constraint violation: {0}")]
+    ConstraintViolation(String),
+    /// トランザクションの開始・確定・取り消しが現在の状態で行えない場合
+    #[error("Invalid transaction state: {0}")]
+    InvalidTransactionState(String),
+    /// SQLの構文誤り、パラメータ数の不一致など、その他のSQLiteのエラー
+    #[error("SQLite error: {0}")]
+    SqliteError(String),
+}
+
+impl From<rusqlite::Error> for DatabaseError {
+    fn from(error: rusqlite::Error) -> Self {
+        match error.sqlite_error_code() {
+            Some(ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) => {
+                DatabaseError::Busy(error.to_string())
+            }
+            Some(ErrorCode::ConstraintViolation) => {
+                DatabaseError::ConstraintViolation(error.to_string())
+            }
+            _ => DatabaseError::SqliteError(error.to_string()),
+        }
+    }
+}
+
+/// SQLiteの値
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum SqlValue {
+    /// NULL
+    Null,
+    /// 64ビット整数
+    Integer { value: i64 },
+    /// 浮動小数点数
+    Real { value: f64 },
+    /// 文字列
+    Text { value: String },
+    /// バイナリデータ
+    Blob { value: Vec<u8> },
+}
+
+impl ToSql for SqlValue {
+    fn to_sql(&self) -> rusqlite::Result<ToSqlOutput<'_>> {
+        Ok(match self {
+            SqlValue::Null => ToSqlOutput::Owned(Value::Null),
+            SqlValue::Integer { value } => ToSqlOutput::Owned(Value::Integer(*value)),
+            SqlValue::Real { value } => ToSqlOutput::Owned(Value::Real(*value)),
+            SqlValue::Text { value } => ToSqlOutput::Borrowed(ValueRef::Text(value.as_bytes())),
+            SqlValue::Blob { value } => ToSqlOutput::Borrowed(ValueRef::Blob(value)),
+        })
+    }
+}
+
+impl From<ValueRef<'_>> for SqlValue {
+    fn from(value: ValueRef<'_>) -> Self {
+        match value {
+            ValueRef::Null => SqlValue::Null,
+            ValueRef::Integer(value) => SqlValue::Integer { value },
+            ValueRef::Real(value) => SqlValue::Real { value },
+            ValueRef::Text(text) => SqlValue::Text {
+                value: String::from_utf8_lossy(text).into_owned(),
+            },
+            ValueRef::Blob(blob) => SqlValue::Blob {
+                value: blob.to_vec(),
+            },
+        }
+    }
+}
+
+/// 問い合わせ結果の1行
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SqlRow {
+    /// 列名（SELECT句の順序）
+    pub columns: Vec<String>,
+    /// 列名から値への対応（同名の列は後の列が優先されます）
+    pub values: HashMap<String, SqlValue>,
+}
+
+/// SQLiteデータベース
+///
+/// 1つの接続を保持し、複数のスレッドからの呼び出しは内部で直列化されます。
+/// 他の接続（App Extensionなど）がロックを保持している場合は、
+/// ビジータイムアウトまで待ってから`DatabaseError::Busy`を返します。
+///
+/// # Example
+/// ```
+/// let db = Database::open(path, 5000)?;
+/// db.execute_batch("CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT)".to_string())?;
+/// db.begin_transaction()?;
+/// let body = SqlValue::Text { value: "hello".to_string() };
+/// db.execute("INSERT INTO notes (body) VALUES (?)".to_string(), vec![body])?;
+/// db.commit()?;
+/// let rows = db.query("SELECT id, body FROM notes".to_string(), Vec::new())?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct Database {
+    connection: Mutex<Connection>,
+}
+
+impl Database {
+    /// 接続を設定してオブジェクトを作成します
+    fn with_connection(
+        connection: Connection,
+        busy_timeout_ms: u64,
+    ) -> Result<Arc<Self>, DatabaseError> {
+        connection.busy_timeout(Duration::from_millis(busy_timeout_ms))?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        Ok(Arc::new(Self {
+            connection: Mutex::new(connection),
+        }))
+    }
+
+    /// 接続のロックを取得します（他のモジュールから直接SQLiteを操作するため）
+    pub(crate) fn lock_connection(&self) -> Result<MutexGuard<'_, Connection>, DatabaseError> {
+        self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)
+    }
+
+    /// トランザクションを制御するSQLを実行します
+    fn run_transaction_statement(
+        &self,
+        sql: &str,
+        expect_active: bool,
+    ) -> Result<(), DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        if connection.is_autocommit() == expect_active {
+            return Err(DatabaseError::InvalidTransactionState(if expect_active {
+                "no transaction is active".to_string()
+            } else {
+                "a transaction is already active".to_string()
+            }));
+        }
+        connection.execute_batch(sql)?;
+        Ok(())
+    }
+}
+
+#[uniffi::export]
+impl Database {
+    /// データベースファイルを開きます（存在しない場合は作成されます）
+    ///
+    /// 外部キー制約は有効な状態で開かれます。
+    ///
+    /// # Arguments
+    /// * `path` - データベースファイルのパス
+    /// * `busy_timeout_ms` - ロックの解放を待つ最大時間（ミリ秒、既定値は5000）
+    ///
+    /// # Errors
+    /// * `DatabaseError::SqliteError` - ファイルを開けない、またはデータベースではない場合
+    #[uniffi::constructor(default(busy_timeout_ms = 5000))]
+    pub fn open(path: String, busy_timeout_ms: u64) -> Result<Arc<Self>, DatabaseError> {
+        Self::with_connection(Connection::open(path)?, busy_timeout_ms)
+    }
+
+    /// メモリ上の一時的なデータベースを開きます
+    ///
+    /// # Errors
+    /// * `DatabaseError::SqliteError` - データベースを作成できない場合
+    #[uniffi::constructor]
+    pub fn open_in_memory() -> Result<Arc<Self>, DatabaseError> {
+        Self::with_connection(Connection::open_in_memory()?, DEFAULT_BUSY_TIMEOUT_MS)
+    }
+
+    /// ビジータイムアウトを変更します
+    ///
+    /// # Errors
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn set_busy_timeout(&self, busy_timeout_ms: u64) -> Result<(), DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        connection.busy_timeout(Duration::from_millis(busy_timeout_ms))?;
+        Ok(())
+    }
+
+    /// 結果を返さない1つのSQL文を実行します
+    ///
+    /// # Arguments
+    /// * `sql` - 実行するSQL文（パラメータは`?`または`?NNN`で指定）
+    /// * `params` - パラメータの値
+    ///
+    /// # Returns
+    /// 変更された行数
+    ///
+    /// # Errors
+    /// * `DatabaseError::Busy` - ロックを取得できなかった場合
+    /// * `DatabaseError::ConstraintViolation` - 制約に違反した場合
+    /// * `DatabaseError::SqliteError` - SQLが不正、またはパラメータ数が一致しない場合
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn execute(&self, sql: String, params: Vec<SqlValue>) -> Result<u64, DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        let changed = connection.execute(&sql, params_from_iter(params.iter()))?;
+        Ok(changed as u64)
+    }
+
+    /// セミコロンで区切られた複数のSQL文をパラメータなしで実行します
+    ///
+    /// スキーマの作成などに使用します。
+    ///
+    /// # Errors
+    /// * `execute`と同じエラー
+    pub fn execute_batch(&self, sql: String) -> Result<(), DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        connection.execute_batch(&sql)?;
+        Ok(())
+    }
+
+    /// 問い合わせを実行し、すべての行を返します
+    ///
+    /// # Arguments
+    /// * `sql` - SELECT文など結果を返すSQL文
+    /// * `params` - パラメータの値
+    ///
+    /// # Errors
+    /// * `execute`と同じエラー
+    pub fn query(&self, sql: String, params: Vec<SqlValue>) -> Result<Vec<SqlRow>, DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        let mut statement = connection.prepare(&sql)?;
+        let columns: Vec<String> = statement.column_names().iter().map(|c| c.to_string()).collect();
+        let mut rows = statement.query(params_from_iter(params.iter()))?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let mut values = HashMap::with_capacity(columns.len());
+            for (index, column) in columns.iter().enumerate() {
+                values.insert(column.clone(), SqlValue::from(row.get_ref(index)?));
+            }
+            result.push(SqlRow {
+                columns: columns.clone(),
+                values,
+            });
+        }
+        Ok(result)
+    }
+
+    /// 最後に挿入された行のROWIDを返します
+    ///
+    /// # Errors
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn last_insert_rowid(&self) -> Result<i64, DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    /// トランザクションを開始します
+    ///
+    /// 書き込みロックを開始時に取得する`BEGIN IMMEDIATE`を使用するため、
+    /// 他の接続と競合した場合はこの呼び出しでビジータイムアウトまで待ちます。
+    ///
+    /// # Errors
+    /// * `DatabaseError::InvalidTransactionState` - 既にトランザクションが開始されている場合
+    /// * `DatabaseError::Busy` - ロックを取得できなかった場合
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn begin_transaction(&self) -> Result<(), DatabaseError> {
+        self.run_transaction_statement("BEGIN IMMEDIATE", false)
+    }
+
+    /// トランザクションを確定します
+    ///
+    /// # Errors
+    /// * `DatabaseError::InvalidTransactionState` - トランザクションが開始されていない場合
+    /// * `DatabaseError::Busy` - 確定時にロックを取得できなかった場合
+    /// * `DatabaseError::ConstraintViolation` - 遅延された制約に違反した場合
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn commit(&self) -> Result<(), DatabaseError> {
+        self.run_transaction_statement("COMMIT", true)
+    }
+
+    /// トランザクションを取り消します
+    ///
+    /// # Errors
+    /// * `DatabaseError::InvalidTransactionState` - トランザクションが開始されていない場合
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn rollback(&self) -> Result<(), DatabaseError> {
+        self.run_transaction_statement("ROLLBACK", true)
+    }
+
+    /// トランザクションが開始されているかを返します
+    ///
+    /// # Errors
+    /// * `DatabaseError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn in_transaction(&self) -> Result<bool, DatabaseError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DatabaseError::MutexPoisoned)?;
+        Ok(!connection.is_autocommit())
+    }
+}
+
+/// 非同期版のAPI
+///
+/// 各メソッドは同名の同期メソッドと同じ処理をRust側のスレッドプールで実行するため、
+/// 大きな問い合わせでも呼び出し元のスレッドをブロックしません。
+#[uniffi::export]
+impl Database {
+    /// `execute`の非同期版です
+    ///
+    /// # Errors
+    /// * `execute`と同じエラー
+    pub async fn execute_async(
+        self: Arc<Self>,
+        sql: String,
+        params: Vec<SqlValue>,
+    ) -> Result<u64, DatabaseError> {
+        blocking::unblock(move || self.execute(sql, params)).await
+    }
+
+    /// `execute_batch`の非同期版です
+    ///
+    /// # Errors
+    /// * `execute`と同じエラー
+    pub async fn execute_batch_async(self: Arc<Self>, sql: String) -> Result<(), DatabaseError> {
+        blocking::unblock(move || self.execute_batch(sql)).await
+    }
+
+    /// `query`の非同期版です
+    ///
+    /// # Errors
+    /// * `execute`と同じエラー
+    pub async fn query_async(
+        self: Arc<Self>,
+        sql: String,
+        params: Vec<SqlValue>,
+    ) -> Result<Vec<SqlRow>, DatabaseError> {
+        blocking::unblock(move || self.query(sql, params)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> SqlValue {
+        SqlValue::Text {
+            value: value.to_string(),
+        }
+    }
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_database_{}_{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn notes_db() -> Arc<Database> {
+        let db = Database::open_in_memory().unwrap();
+        db.execute_batch(
+            "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT UNIQUE, score REAL, data BLOB);"
+                .to_string(),
+        )
+        .unwrap();
+        db
+    }
+
+    #[test]
+    fn test_database_execute_and_query() {
+        let db = notes_db();
+        let params = vec![
+            text("hello"),
+            SqlValue::Real { value: 1.5 },
+            SqlValue::Blob { value: vec![1, 2] },
+        ];
+        let sql = "INSERT INTO notes (body, score, data) VALUES (?, ?, ?)";
+        assert_eq!(db.execute(sql.to_string(), params).unwrap(), 1);
+        assert_eq!(db.last_insert_rowid().unwrap(), 1);
+        db.execute(sql.to_string(), vec![text("world"), SqlValue::Null, SqlValue::Null]).unwrap();
+
+        let rows = db
+            .query("SELECT id, body, score, data FROM notes ORDER BY id".to_string(), Vec::new())
+            .unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].columns, vec!["id", "body", "score", "data"]);
+        assert_eq!(rows[0].values["id"], SqlValue::Integer { value: 1 });
+        assert_eq!(rows[0].values["body"], text("hello"));
+        assert_eq!(rows[0].values["score"], SqlValue::Real { value: 1.5 });
+        assert_eq!(rows[0].values["data"], SqlValue::Blob { value: vec![1, 2] });
+        assert_eq!(rows[1].values["score"], SqlValue::Null);
+
+        let id = SqlValue::Integer { value: 2 };
+        let rows = db.query("SELECT body FROM notes WHERE id = ?".to_string(), vec![id]).unwrap();
+        assert_eq!(rows[0].values["body"], text("world"));
+    }
+
+    #[test]
+    fn test_database_transactions() {
+        let db = notes_db();
+        let insert = "INSERT INTO notes (body) VALUES (?)".to_string();
+        db.begin_transaction().unwrap();
+        assert!(db.in_transaction().unwrap());
+        db.execute(insert.clone(), vec![text("discarded")]).unwrap();
+        db.rollback().unwrap();
+        db.begin_transaction().unwrap();
+        db.execute(insert.clone(), vec![text("kept")]).unwrap();
+        db.commit().unwrap();
+        assert!(!db.in_transaction().unwrap());
+
+        let rows = db.query("SELECT body FROM notes".to_string(), Vec::new()).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].values["body"], text("kept"));
+
+        match db.commit() {
+            Err(DatabaseError::InvalidTransactionState(_)) => (),
+            other => panic!("Expected InvalidTransactionState error, got {:?}", other),
+        }
+        db.begin_transaction().unwrap();
+        match db.begin_transaction() {
+            Err(DatabaseError::InvalidTransactionState(_)) => (),
+            other => panic!("Expected InvalidTransactionState error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_database_busy_timeout() {
+        let path = temp_path("busy");
+        let writer = Database::open(path.clone(), 1000).unwrap();
+        writer.execute_batch("CREATE TABLE t (x INTEGER);".to_string()).unwrap();
+        let other = Database::open(path.clone(), 50).unwrap();
+
+        writer.begin_transaction().unwrap();
+        match other.execute("INSERT INTO t VALUES (1)".to_string(), Vec::new()) {
+            Err(DatabaseError::Busy(_)) => (),
+            other => panic!("Expected Busy error, got {:?}", other),
+        }
+        writer.commit().unwrap();
+        other.execute("INSERT INTO t VALUES (1)".to_string(), Vec::new()).unwrap();
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_database_errors() {
+        let db = notes_db();
+        let insert = "INSERT INTO notes (body) VALUES (?)".to_string();
+        db.execute(insert.clone(), vec![text("dup")]).unwrap();
+        match db.execute(insert.clone(), vec![text("dup")]) {
+            Err(DatabaseError::ConstraintViolation(_)) => (),
+            other => panic!("Expected ConstraintViolation error, got {:?}", other),
+        }
+        match db.execute(insert, Vec::new()) {
+            Err(DatabaseError::SqliteError(_)) => (),
+            other => panic!("Expected SqliteError error, got {:?}", other),
+        }
+        match db.query("SELEC 1".to_string(), Vec::new()) {
+            Err(DatabaseError::SqliteError(_)) => (),
+            other => panic!("Expected SqliteError error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_database_async() {
+        let db = Database::open_in_memory().unwrap();
+        pollster::block_on(
+            db.clone().execute_batch_async("CREATE TABLE t (v INTEGER);".to_string()),
+        )
+        .unwrap();
+        let changed = pollster::block_on(db.clone().execute_async(
+            "INSERT INTO t (v) VALUES (?1)".to_string(),
+            vec![SqlValue::Integer { value: 42 }],
+        ))
+        .unwrap();
+        assert_eq!(changed, 1);
+        let rows = pollster::block_on(db.query_async("SELECT v FROM t".to_string(), vec![]))
+            .unwrap();
+        assert_eq!(rows[0].values["v"], SqlValue::Integer { value: 42 });
+    }
+}
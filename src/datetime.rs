@@ -0,0 +1,728 @@
+//! 日時の解析・書式化モジュール
+//!
+//! このモジュールは、ISO 8601 / RFC 3339形式の日時文字列を解析する`parse_datetime`と、
+//! Unicode（LDML）の日付パターンで書式化する`format_datetime`をエクスポートします。
+//! SwiftのDateFormatterと同じパターン文字列を使えるため、Rust側とSwift側で
+//! 日時の扱いが食い違うことを防げます。
+//!
+//! 書式化は`en_US_POSIX`ロケール相当（英語の月名・曜日名、グレゴリオ暦）で行います。
+
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta, Timelike};
+use thiserror::Error;
+
+use crate::timezone::resolve_offset;
+
+/// 英語の月名
+const MONTH_NAMES: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+
+/// 英語の曜日名（月曜始まり）
+const WEEKDAY_NAMES: [&str; 7] =
+    ["Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday", "Sunday"];
+
+/// UTCオフセットの最大値（±23:59）
+const MAX_OFFSET_SECONDS: i32 = 24 * 3600 - 60;
+
+/// 日時の処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DateTimeError {
+    /// 日時文字列の形式が正しくない場合
+    #[error("Invalid date-time format: {0}")]
+    InvalidFormat(String),
+    /// 存在しない日付・時刻、または扱える範囲外の場合
+    #[error("Date-time out of range: {0}")]
+    OutOfRange(String),
+    /// 書式化パターンが正しくない、またはサポートしない文字を含む場合
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+    /// タイムゾーンの指定が正しくない場合
+    #[error("Invalid time zone: {0}")]
+    InvalidTimeZone(String),
+}
+
+/// 日時の解析モード
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum DateParseMode {
+    /// RFC 3339の形式（`2024-01-02T03:04:05.678+09:00`）のみを受け付けます。
+    /// 区切りの`T`と`Z`は大文字、秒とUTCオフセットは必須です
+    Strict,
+    /// ISO 8601のよく使われる変種も受け付けます。
+    /// 小文字の`t`・`z`や空白の区切り、日付のみ（0時）、秒の省略、小数点の`,`、
+    /// 基本形式（`20240102T030405Z`）、`+0900`・`+09`形式のオフセット、
+    /// オフセットの省略（UTCとみなす）、前後の空白
+    Lenient,
+}
+
+/// 時点と、その時点を表記したときのUTCオフセット
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct Timestamp {
+    /// UNIX時刻（1970-01-01T00:00:00Zからの秒数）
+    pub unix_seconds: i64,
+    /// 秒未満の部分（ナノ秒、0〜999,999,999）
+    pub nanoseconds: u32,
+    /// UTCオフセット（秒）。解析時は文字列に書かれていたオフセットが入ります
+    pub utc_offset_seconds: i32,
+}
+
+impl Timestamp {
+    /// オフセットを適用したローカル日時を返します
+    pub(crate) fn local_datetime(&self) -> Result<NaiveDateTime, DateTimeError> {
+        if self.nanoseconds >= 1_000_000_000 {
+            return Err(DateTimeError::OutOfRange(format!(
+                "nanoseconds must be less than 1000000000: {}",
+                self.nanoseconds
+            )));
+        }
+        if self.utc_offset_seconds.abs() > MAX_OFFSET_SECONDS {
+            return Err(DateTimeError::OutOfRange(format!(
+                "UTC offset is out of range: {}",
+                self.utc_offset_seconds
+            )));
+        }
+        DateTime::from_timestamp(self.unix_seconds, self.nanoseconds)
+            .map(|utc| utc.naive_utc())
+            .and_then(|utc| {
+                utc.checked_add_signed(TimeDelta::seconds(self.utc_offset_seconds.into()))
+            })
+            .ok_or_else(|| {
+                DateTimeError::OutOfRange(format!(
+                    "timestamp is out of range: {}",
+                    self.unix_seconds
+                ))
+            })
+    }
+}
+
+/// 文字列を先頭から読み進めるカーソル
+struct Cursor<'a> {
+    text: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text: text.as_bytes(), position: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.get(self.position).copied()
+    }
+
+    fn is_end(&self) -> bool {
+        self.position == self.text.len()
+    }
+
+    /// 指定した文字のいずれかであれば読み進めて`true`を返します
+    fn eat(&mut self, candidates: &[u8]) -> bool {
+        match self.peek() {
+            Some(c) if candidates.contains(&c) => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 続く数字の個数を返します
+    fn digit_run(&self) -> usize {
+        self.text[self.position..].iter().take_while(|c| c.is_ascii_digit()).count()
+    }
+
+    /// ちょうど`count`桁の数字を読みます
+    fn digits(&mut self, count: usize, what: &str) -> Result<u32, DateTimeError> {
+        if self.digit_run() < count {
+            return Err(DateTimeError::InvalidFormat(format!(
+                "expected {} digits for {} at position {}",
+                count, what, self.position
+            )));
+        }
+        let value = self.text[self.position..self.position + count]
+            .iter()
+            .fold(0u32, |acc, c| acc * 10 + u32::from(c - b'0'));
+        self.position += count;
+        Ok(value)
+    }
+
+    /// 指定した文字を読みます
+    fn expect(&mut self, expected: u8) -> Result<(), DateTimeError> {
+        if self.eat(&[expected]) {
+            Ok(())
+        } else {
+            Err(DateTimeError::InvalidFormat(format!(
+                "expected '{}' at position {}",
+                expected as char, self.position
+            )))
+        }
+    }
+}
+
+/// 解析した日時の各要素
+struct Parsed {
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanosecond: u32,
+    offset_seconds: i32,
+}
+
+/// 日付部分（`YYYY-MM-DD`、寛容モードでは`YYYYMMDD`も）を読み、基本形式かを返します
+fn parse_date(
+    cursor: &mut Cursor,
+    parsed: &mut Parsed,
+    mode: DateParseMode,
+) -> Result<bool, DateTimeError> {
+    parsed.year = cursor.digits(4, "year")? as i32;
+    let basic = mode == DateParseMode::Lenient && cursor.peek() != Some(b'-');
+    if !basic {
+        cursor.expect(b'-')?;
+    }
+    parsed.month = cursor.digits(2, "month")?;
+    if !basic {
+        cursor.expect(b'-')?;
+    }
+    parsed.day = cursor.digits(2, "day")?;
+    Ok(basic)
+}
+
+/// 時刻部分（`HH:MM:SS(.fraction)`）を読みます
+fn parse_time(
+    cursor: &mut Cursor,
+    parsed: &mut Parsed,
+    mode: DateParseMode,
+    basic: bool,
+) -> Result<(), DateTimeError> {
+    let lenient = mode == DateParseMode::Lenient;
+    parsed.hour = cursor.digits(2, "hour")?;
+    if !basic {
+        cursor.expect(b':')?;
+    }
+    parsed.minute = cursor.digits(2, "minute")?;
+    let has_seconds = if basic { cursor.digit_run() >= 2 } else { cursor.eat(b":") };
+    if !has_seconds {
+        if lenient {
+            return Ok(());
+        }
+        return Err(DateTimeError::InvalidFormat("seconds are required".to_string()));
+    }
+    parsed.second = cursor.digits(2, "second")?;
+    let separators: &[u8] = if lenient { b".," } else { b"." };
+    if cursor.eat(separators) {
+        let count = cursor.digit_run();
+        if count == 0 {
+            return Err(DateTimeError::InvalidFormat(
+                "fraction of second must have at least one digit".to_string(),
+            ));
+        }
+        // ナノ秒より細かい桁は切り捨てる
+        let start = cursor.position;
+        let mut nanosecond = 0;
+        for index in 0..9 {
+            let digit = cursor.text.get(start + index).filter(|_| index < count);
+            nanosecond = nanosecond * 10 + digit.map_or(0, |c| u32::from(c - b'0'));
+        }
+        cursor.position += count;
+        parsed.nanosecond = nanosecond;
+    }
+    Ok(())
+}
+
+/// UTCオフセット（`Z`・`+HH:MM`、寛容モードでは`+HHMM`・`+HH`も）を読みます
+fn parse_offset(
+    cursor: &mut Cursor,
+    mode: DateParseMode,
+    basic: bool,
+) -> Result<i32, DateTimeError> {
+    let lenient = mode == DateParseMode::Lenient;
+    let zulu: &[u8] = if lenient { b"Zz" } else { b"Z" };
+    if cursor.eat(zulu) {
+        return Ok(0);
+    }
+    if cursor.is_end() && lenient {
+        return Ok(0);
+    }
+    let sign = match cursor.peek() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        _ => {
+            return Err(DateTimeError::InvalidFormat(format!(
+                "expected UTC offset at position {}",
+                cursor.position
+            )))
+        }
+    };
+    cursor.position += 1;
+    let hours = cursor.digits(2, "offset hour")?;
+    let minutes = if lenient && (cursor.is_end() || basic && cursor.digit_run() == 0) {
+        0
+    } else {
+        if !lenient || cursor.peek() == Some(b':') {
+            cursor.expect(b':')?;
+        }
+        cursor.digits(2, "offset minute")?
+    };
+    if hours > 23 || minutes > 59 {
+        return Err(DateTimeError::OutOfRange(format!(
+            "UTC offset is out of range: {:02}:{:02}",
+            hours, minutes
+        )));
+    }
+    Ok(sign * (hours * 3600 + minutes * 60) as i32)
+}
+
+/// 日時文字列をUNIX時刻に変換します
+///
+/// 秒が60（うるう秒）の場合は、次の秒の0として扱います。
+///
+/// # Arguments
+/// * `text` - ISO 8601 / RFC 3339形式の日時文字列
+/// * `mode` - 解析モード（省略時は`Strict`）
+///
+/// # Returns
+/// UNIX時刻と、文字列に書かれていたUTCオフセット
+///
+/// # Errors
+/// * `DateTimeError::InvalidFormat` - 形式が正しくない場合
+/// * `DateTimeError::OutOfRange` - 存在しない日付・時刻（2月30日や25時など）の場合
+///
+/// # Example
+/// ```
+/// let timestamp = parse_datetime("2024-01-02T03:04:05+09:00".to_string(), None)?;
+/// assert_eq!(timestamp.unix_seconds, 1704132245);
+/// assert_eq!(timestamp.utc_offset_seconds, 32400);
+/// ```
+#[uniffi::export(default(mode = None))]
+pub fn parse_datetime(
+    text: String,
+    mode: Option<DateParseMode>,
+) -> Result<Timestamp, DateTimeError> {
+    let mode = mode.unwrap_or(DateParseMode::Strict);
+    let text = match mode {
+        DateParseMode::Strict => text.as_str(),
+        DateParseMode::Lenient => text.trim(),
+    };
+    let mut cursor = Cursor::new(text);
+    let mut parsed = Parsed {
+        year: 0,
+        month: 0,
+        day: 0,
+        hour: 0,
+        minute: 0,
+        second: 0,
+        nanosecond: 0,
+        offset_seconds: 0,
+    };
+    let basic = parse_date(&mut cursor, &mut parsed, mode)?;
+    let separators: &[u8] = match mode {
+        DateParseMode::Strict => b"T",
+        DateParseMode::Lenient => b"Tt ",
+    };
+    if cursor.eat(separators) {
+        parse_time(&mut cursor, &mut parsed, mode, basic)?;
+        parsed.offset_seconds = parse_offset(&mut cursor, mode, basic)?;
+    } else if mode == DateParseMode::Strict || !cursor.is_end() {
+        return Err(DateTimeError::InvalidFormat(format!(
+            "expected time at position {}",
+            cursor.position
+        )));
+    }
+    if !cursor.is_end() {
+        return Err(DateTimeError::InvalidFormat(format!(
+            "unexpected trailing characters at position {}",
+            cursor.position
+        )));
+    }
+
+    let leap_second = parsed.second == 60;
+    let second = if leap_second { 59 } else { parsed.second };
+    let local = NaiveDate::from_ymd_opt(parsed.year, parsed.month, parsed.day)
+        .and_then(|date| {
+            date.and_hms_nano_opt(parsed.hour, parsed.minute, second, parsed.nanosecond)
+        })
+        .ok_or_else(|| DateTimeError::OutOfRange(format!("no such date-time: {}", text)))?;
+    let unix_seconds =
+        local.and_utc().timestamp() - i64::from(parsed.offset_seconds) + i64::from(leap_second);
+    Ok(Timestamp {
+        unix_seconds,
+        nanoseconds: parsed.nanosecond,
+        utc_offset_seconds: parsed.offset_seconds,
+    })
+}
+
+/// タイムゾーンの指定をUTCオフセット（秒）に変換します
+///
+/// `UTC`・`GMT`・`Z`と、`+09:00`・`+0900`・`+09`・`GMT+9`形式の固定オフセットを受け付けます。
+pub(crate) fn parse_fixed_offset(timezone: &str) -> Result<i32, DateTimeError> {
+    let invalid = || DateTimeError::InvalidTimeZone(timezone.to_string());
+    let upper = timezone.trim().to_ascii_uppercase();
+    let rest = upper.strip_prefix("UTC").or_else(|| upper.strip_prefix("GMT")).unwrap_or(&upper);
+    if rest.is_empty() || rest == "Z" {
+        return Ok(0);
+    }
+    let (sign, digits) = match rest.as_bytes()[0] {
+        b'+' => (1, &rest[1..]),
+        b'-' => (-1, &rest[1..]),
+        _ => return Err(invalid()),
+    };
+    // 数字と`:`以外を含む場合は、バイト位置での分割の前に拒否する
+    if !digits.bytes().all(|c| c.is_ascii_digit() || c == b':') {
+        return Err(invalid());
+    }
+    let (hours, minutes) = match digits.split_once(':') {
+        Some((hours, minutes)) if minutes.len() == 2 => (hours, minutes),
+        Some(_) => return Err(invalid()),
+        None if digits.len() == 4 => digits.split_at(2),
+        None if digits.len() <= 2 => (digits, "0"),
+        None => return Err(invalid()),
+    };
+    if hours.is_empty() || hours.len() > 2 {
+        return Err(invalid());
+    }
+    let hours: i32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(sign * (hours * 3600 + minutes * 60))
+}
+
+/// UTCオフセットを書式化します
+///
+/// `z_for_zero`が`true`の場合、オフセット0を`Z`と表記します。
+fn format_offset(
+    output: &mut String,
+    offset_seconds: i32,
+    colon: bool,
+    minutes_optional: bool,
+    z_for_zero: bool,
+) {
+    if offset_seconds == 0 && z_for_zero {
+        output.push('Z');
+        return;
+    }
+    let sign = if offset_seconds < 0 { '-' } else { '+' };
+    let total_minutes = offset_seconds.abs() / 60;
+    let (hours, minutes) = (total_minutes / 60, total_minutes % 60);
+    output.push_str(&format!("{}{:02}", sign, hours));
+    if minutes_optional && minutes == 0 {
+        return;
+    }
+    if colon {
+        output.push(':');
+    }
+    output.push_str(&format!("{:02}", minutes));
+}
+
+/// パターン文字1つ分（同じ文字の連続）を書式化します
+fn format_field(
+    output: &mut String,
+    letter: char,
+    count: usize,
+    local: &NaiveDateTime,
+    offset_seconds: i32,
+) -> Result<(), DateTimeError> {
+    let number = |output: &mut String, value: i64, width: usize| {
+        output.push_str(&format!("{:0width$}", value, width = width));
+    };
+    let month = local.month0() as usize;
+    let weekday = local.weekday().num_days_from_monday() as usize;
+    match (letter, count) {
+        ('G', 1..=3) => output.push_str(if local.year() > 0 { "AD" } else { "BC" }),
+        ('G', 4) => output.push_str(if local.year() > 0 { "Anno Domini" } else { "Before Christ" }),
+        ('y', 2) => number(output, i64::from(local.year().rem_euclid(100)), 2),
+        ('y', _) => number(output, i64::from(local.year()), count),
+        ('M', 1..=2) => number(output, local.month() as i64, count),
+        ('M', 3) => output.push_str(&MONTH_NAMES[month][..3]),
+        ('M', 4) => output.push_str(MONTH_NAMES[month]),
+        ('M', 5) => output.push_str(&MONTH_NAMES[month][..1]),
+        ('d', 1..=2) => number(output, local.day() as i64, count),
+        ('D', 1..=3) => number(output, local.ordinal() as i64, count),
+        ('E', 1..=3) => output.push_str(&WEEKDAY_NAMES[weekday][..3]),
+        ('E', 4) => output.push_str(WEEKDAY_NAMES[weekday]),
+        ('E', 5) => output.push_str(&WEEKDAY_NAMES[weekday][..1]),
+        ('E', 6) => output.push_str(&WEEKDAY_NAMES[weekday][..2]),
+        ('a', 1..=3) => output.push_str(if local.hour() < 12 { "AM" } else { "PM" }),
+        ('h', 1..=2) => number(output, ((local.hour() + 11) % 12 + 1) as i64, count),
+        ('H', 1..=2) => number(output, local.hour() as i64, count),
+        ('K', 1..=2) => number(output, (local.hour() % 12) as i64, count),
+        ('k', 1..=2) => number(output, (local.hour() + 23) as i64 % 24 + 1, count),
+        ('m', 1..=2) => number(output, local.minute() as i64, count),
+        ('s', 1..=2) => number(output, local.second() as i64, count),
+        ('S', 1..=9) => {
+            let fraction = format!("{:09}", local.nanosecond() % 1_000_000_000);
+            output.push_str(&fraction[..count]);
+        }
+        ('Z', 1..=3) => format_offset(output, offset_seconds, false, false, false),
+        ('Z', 4) => {
+            output.push_str("GMT");
+            if offset_seconds != 0 {
+                format_offset(output, offset_seconds, true, false, false);
+            }
+        }
+        ('Z', 5) => format_offset(output, offset_seconds, true, false, true),
+        ('X', 1) => format_offset(output, offset_seconds, false, true, true),
+        ('X', 2 | 4) => format_offset(output, offset_seconds, false, false, true),
+        ('X', 3 | 5) => format_offset(output, offset_seconds, true, false, true),
+        ('x', 1) => format_offset(output, offset_seconds, false, true, false),
+        ('x', 2 | 4) => format_offset(output, offset_seconds, false, false, false),
+        ('x', 3 | 5) => format_offset(output, offset_seconds, true, false, false),
+        ('Y', _) => {
+            return Err(DateTimeError::InvalidPattern(
+                "'Y' is the week-based year; use 'y' for the calendar year".to_string(),
+            ))
+        }
+        _ => {
+            return Err(DateTimeError::InvalidPattern(format!(
+                "unsupported field: {}",
+                letter.to_string().repeat(count)
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// 時点を日付パターンで書式化します
+///
+/// パターンはUnicode（LDML）の日付フォーマットパターンのうち、次の文字をサポートします。
+/// `'...'`で囲んだ部分はそのまま出力され（`''`は`'`）、英字以外の文字もそのまま出力されます。
+///
+/// | 文字 | 意味 | 例 |
+/// |------|------|----|
+/// | `G` | 紀元 | `AD` |
+/// | `y` | 年（`yy`は下2桁） | `2024`・`24` |
+/// | `M` | 月 | `1`・`01`・`Jan`・`January` |
+/// | `d`・`D` | 日・年内の通日 | `2`・`02`・`002` |
+/// | `E` | 曜日 | `Tue`・`Tuesday` |
+/// | `a` | 午前・午後 | `AM` |
+/// | `H`・`k`・`h`・`K` | 時（0-23・1-24・1-12・0-11） | `03` |
+/// | `m`・`s` | 分・秒 | `04`・`05` |
+/// | `S` | 秒未満（桁数分、切り捨て） | `678` |
+/// | `Z`・`X`・`x` | UTCオフセット | `+0900`・`+09:00`・`Z` |
+///
+/// 週基準の年`Y`は、暦年と取り違えやすいためエラーになります。
+///
+/// # Arguments
+/// * `timestamp` - 書式化する時点
+/// * `pattern` - 日付パターン（例: `yyyy-MM-dd'T'HH:mm:ss.SSSXXX`）
+/// * `timezone` - 表示に使うタイムゾーン（`Asia/Tokyo`などのIANAタイムゾーン名、
+///   または`UTC`・`+09:00`などの固定オフセット）。省略時は`timestamp.utc_offset_seconds`を使用します
+///
+/// # Errors
+/// * `DateTimeError::InvalidPattern` - パターンが正しくない場合
+/// * `DateTimeError::InvalidTimeZone` - タイムゾーンの指定が正しくない場合
+/// * `DateTimeError::OutOfRange` - 時点が扱える範囲外の場合
+///
+/// # Example
+/// ```
+/// let pattern = "EEE, d MMM yyyy HH:mm".to_string();
+/// let text = format_datetime(timestamp, pattern, Some("UTC".to_string()))?;
+/// assert_eq!(text, "Mon, 1 Jan 2024 18:04");
+/// ```
+#[uniffi::export(default(timezone = None))]
+pub fn format_datetime(
+    timestamp: Timestamp,
+    pattern: String,
+    timezone: Option<String>,
+) -> Result<String, DateTimeError> {
+    let offset_seconds = match timezone {
+        Some(timezone) => resolve_offset(&timezone, timestamp.unix_seconds)?,
+        None => timestamp.utc_offset_seconds,
+    };
+    let local = Timestamp { utc_offset_seconds: offset_seconds, ..timestamp }.local_datetime()?;
+    format_local(&local, offset_seconds, &pattern)
+}
+
+/// ローカル日時をパターンで書式化します
+pub(crate) fn format_local(
+    local: &NaiveDateTime,
+    offset_seconds: i32,
+    pattern: &str,
+) -> Result<String, DateTimeError> {
+    let mut output = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\'' {
+            if chars.peek() == Some(&'\'') {
+                chars.next();
+                output.push('\'');
+                continue;
+            }
+            loop {
+                match chars.next() {
+                    Some('\'') if chars.peek() == Some(&'\'') => {
+                        chars.next();
+                        output.push('\'');
+                    }
+                    Some('\'') => break,
+                    Some(c) => output.push(c),
+                    None => {
+                        return Err(DateTimeError::InvalidPattern(
+                            "unterminated quoted text".to_string(),
+                        ))
+                    }
+                }
+            }
+        } else if c.is_ascii_alphabetic() {
+            let mut count = 1;
+            while chars.peek() == Some(&c) {
+                chars.next();
+                count += 1;
+            }
+            format_field(&mut output, c, count, local, offset_seconds)?;
+        } else {
+            output.push(c);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict(text: &str) -> Result<Timestamp, DateTimeError> {
+        parse_datetime(text.to_string(), None)
+    }
+
+    fn lenient(text: &str) -> Result<Timestamp, DateTimeError> {
+        parse_datetime(text.to_string(), Some(DateParseMode::Lenient))
+    }
+
+    #[test]
+    fn test_parse_strict_rfc3339() {
+        let timestamp = strict("2024-01-02T03:04:05+09:00").unwrap();
+        assert_eq!(timestamp.unix_seconds, 1704132245);
+        assert_eq!(timestamp.nanoseconds, 0);
+        assert_eq!(timestamp.utc_offset_seconds, 9 * 3600);
+
+        let timestamp = strict("1985-04-12T23:20:50.52Z").unwrap();
+        assert_eq!(timestamp.unix_seconds, 482196050);
+        assert_eq!(timestamp.nanoseconds, 520_000_000);
+
+        let timestamp = strict("1996-12-19T16:39:57-08:00").unwrap();
+        assert_eq!(timestamp.unix_seconds, 851042397);
+        assert_eq!(timestamp.utc_offset_seconds, -8 * 3600);
+
+        // うるう秒は次の秒として扱う
+        let timestamp = strict("1990-12-31T23:59:60Z").unwrap();
+        assert_eq!(timestamp.unix_seconds, 662688000);
+        assert_eq!(strict("1970-01-01T00:00:00.1234567891Z").unwrap().nanoseconds, 123456789);
+    }
+
+    #[test]
+    fn test_parse_strict_rejects_variants() {
+        for text in [
+            "2024-01-02 03:04:05Z",
+            "2024-01-02t03:04:05Z",
+            "2024-01-02T03:04Z",
+            "2024-01-02T03:04:05",
+            "2024-01-02T03:04:05+0900",
+            "2024-01-02",
+            "20240102T030405Z",
+            " 2024-01-02T03:04:05Z",
+            "2024-01-02T03:04:05.Z",
+            "2024-01-02T03:04:05Zjunk",
+        ] {
+            match strict(text) {
+                Err(DateTimeError::InvalidFormat(_)) => (),
+                other => panic!("Expected InvalidFormat error for {}, got {:?}", text, other),
+            }
+        }
+        for text in ["2023-02-29T00:00:00Z", "2024-01-02T24:00:00Z", "2024-01-02T00:00:00+24:00"] {
+            match strict(text) {
+                Err(DateTimeError::OutOfRange(_)) => (),
+                other => panic!("Expected OutOfRange error for {}, got {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_lenient() {
+        let expected = 1704164645;
+        for text in [
+            "2024-01-02T03:04:05Z",
+            "2024-01-02t03:04:05z",
+            "2024-01-02 03:04:05",
+            "  2024-01-02T03:04:05+00:00\n",
+            "20240102T030405Z",
+            "2024-01-02T12:04:05+0900",
+            "2024-01-02T12:04:05+09",
+            "20240102T120405+0900",
+        ] {
+            assert_eq!(lenient(text).unwrap().unix_seconds, expected, "{}", text);
+        }
+        assert_eq!(lenient("2024-01-02").unwrap().unix_seconds, 1704153600);
+        assert_eq!(lenient("2024-01-02T03:04").unwrap().unix_seconds, expected - 5);
+        assert_eq!(lenient("2024-01-02T03:04:05,5Z").unwrap().nanoseconds, 500_000_000);
+        match lenient("2024/01/02") {
+            Err(DateTimeError::InvalidFormat(_)) => (),
+            other => panic!("Expected InvalidFormat error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_format_datetime_patterns() {
+        let timestamp = strict("2024-01-02T03:04:05.678+09:00").unwrap();
+        let format = |pattern: &str, timezone: Option<&str>| {
+            format_datetime(timestamp, pattern.to_string(), timezone.map(str::to_string)).unwrap()
+        };
+        assert_eq!(format("yyyy-MM-dd'T'HH:mm:ss.SSSXXX", None), "2024-01-02T03:04:05.678+09:00");
+        assert_eq!(format("yyyy-MM-dd'T'HH:mm:ssXXX", Some("UTC")), "2024-01-01T18:04:05Z");
+        assert_eq!(
+            format("EEE, d MMM yyyy HH:mm:ss Z", Some("GMT")),
+            "Mon, 1 Jan 2024 18:04:05 +0000"
+        );
+        assert_eq!(
+            format("EEEE, MMMM d, y 'at' h:mm a", None),
+            "Tuesday, January 2, 2024 at 3:04 AM"
+        );
+        assert_eq!(format("yy/M/d k:mm K:mm D", Some("-05:30")), "24/1/1 12:34 0:34 1");
+        assert_eq!(format("ZZZZ x xx xxx X", Some("+0530")), "GMT+05:30 +0530 +0530 +05:30 +0530");
+        assert_eq!(format("ZZZZ ZZZZZ X", Some("Z")), "GMT Z Z");
+        assert_eq!(format("'It''s' hh 'o''clock'", None), "It's 03 o'clock");
+        assert_eq!(format("S SS SSSSSS", None), "6 67 678000");
+        assert_eq!(format("G MMMMM EEEEE EEEEEE", None), "AD J T Tu");
+    }
+
+    #[test]
+    fn test_format_datetime_errors() {
+        let timestamp = Timestamp { unix_seconds: 0, nanoseconds: 0, utc_offset_seconds: 0 };
+        for pattern in ["YYYY-MM-dd", "yyyy-MM-dd 'T", "qqq", "MMMMMM"] {
+            match format_datetime(timestamp, pattern.to_string(), None) {
+                Err(DateTimeError::InvalidPattern(_)) => (),
+                other => panic!("Expected InvalidPattern error for {}, got {:?}", pattern, other),
+            }
+        }
+        // 非ASCIIの文字を含む場合もパニックせずにエラーになる
+        for timezone in ["Mars/Olympus_Mons", "+25:00", "+9:0", "UTC+", "+123", "+1é1", "+09:+5"] {
+            match format_datetime(timestamp, "HH".to_string(), Some(timezone.to_string())) {
+                Err(DateTimeError::InvalidTimeZone(_)) => (),
+                other => panic!("Expected InvalidTimeZone error for {}, got {:?}", timezone, other),
+            }
+        }
+        assert_eq!(
+            format_datetime(timestamp, "HH".to_string(), Some("GMT+9".to_string())).unwrap(),
+            "09"
+        );
+        assert_eq!(
+            format_datetime(timestamp, "HH:mm".to_string(), Some("Asia/Kolkata".to_string()))
+                .unwrap(),
+            "05:30"
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! トークン失効（デナイリスト）管理モジュール
+//!
+//! このモジュールは、失効させたJWTの`jti`クレームを有効期限付きで保持し、
+//! 指定されたJWTが失効済みかどうかを判定する`TokenDenyList`をエクスポートします。
+//! エントリはコンストラクタで指定されたファイルにJSONとして永続化されます。
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::file_io::write_atomic;
+use crate::jwt::decode_jwt;
+
+/// デナイリスト操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DenyListError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access deny list file: {0}")]
+    IoError(String),
+    /// 永続化ファイルの内容が不正な場合
+    #[error("Deny list file is corrupted: {0}")]
+    CorruptedFile(String),
+    /// JWTのデコードに失敗した場合
+    #[error("Failed to decode JWT: {0}")]
+    InvalidJwt(String),
+    /// JWTに`jti`クレームが含まれていない場合
+    #[error("JWT does not contain a jti claim")]
+    MissingJti,
+}
+
+/// 現在のUNIX時刻（秒）を返します
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// 失効済みトークンのデナイリスト
+///
+/// `jti`と有効期限（UNIX秒）の組を保持し、期限切れのエントリは
+/// 自動的に削除されます。複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let list = TokenDenyList::new("/tmp/deny_list.json".to_string())?;
+/// list.add("token-id".to_string(), 1_900_000_000)?;
+/// assert!(list.is_revoked(jwt)?);
+/// ```
+#[derive(uniffi::Object)]
+pub struct TokenDenyList {
+    path: PathBuf,
+    entries: Mutex<HashMap<String, u64>>,
+}
+
+impl TokenDenyList {
+    /// 指定時刻の時点で期限切れのエントリを削除し、変更があればファイルに保存します
+    fn prune_at(&self, now: u64) -> Result<(), DenyListError> {
+        let mut entries = self.entries.lock()
+            .map_err(|_| DenyListError::MutexPoisoned)?;
+        let before = entries.len();
+        entries.retain(|_, expiry| *expiry > now);
+        if entries.len() != before {
+            self.save(&entries)?;
+        }
+        Ok(())
+    }
+
+    /// エントリをJSONとしてファイルに書き出します
+    fn save(&self, entries: &HashMap<String, u64>) -> Result<(), DenyListError> {
+        let json = serde_json::to_string(entries)
+            .map_err(|e| DenyListError::IoError(e.to_string()))?;
+        write_atomic(&self.path, &[json.as_bytes()])
+            .map_err(|e| DenyListError::IoError(e.to_string()))
+    }
+
+    /// ファイルからエントリを読み込みます（ファイルが存在しない場合は空）
+    fn load(path: &PathBuf) -> Result<HashMap<String, u64>, DenyListError> {
+        if !path.exists() {
+            return Ok(HashMap::new());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| DenyListError::IoError(e.to_string()))?;
+        if content.trim().is_empty() {
+            return Ok(HashMap::new());
+        }
+        serde_json::from_str(&content)
+            .map_err(|e| DenyListError::CorruptedFile(e.to_string()))
+    }
+}
+
+#[uniffi::export]
+impl TokenDenyList {
+    /// 指定されたファイルパスに永続化されるデナイリストを作成します
+    ///
+    /// ファイルが既に存在する場合はその内容を読み込み、期限切れの
+    /// エントリを削除します。
+    ///
+    /// # Arguments
+    /// * `path` - エントリを保存するファイルのパス
+    ///
+    /// # Errors
+    /// * `DenyListError::IoError` - ファイルの読み書きに失敗した場合
+    /// * `DenyListError::CorruptedFile` - ファイルの内容が不正な場合
+    #[uniffi::constructor]
+    pub fn new(path: String) -> Result<Arc<Self>, DenyListError> {
+        let path = PathBuf::from(path);
+        let entries = Self::load(&path)?;
+        let list = Self {
+            path,
+            entries: Mutex::new(entries),
+        };
+        list.prune_at(now_unix())?;
+        Ok(Arc::new(list))
+    }
+
+    /// トークンIDを有効期限付きでデナイリストに追加します
+    ///
+    /// # Arguments
+    /// * `jti` - 失効させるトークンの`jti`クレーム
+    /// * `expiry` - エントリの有効期限（UNIX秒）。通常はトークンの`exp`と同じ値
+    ///
+    /// # Errors
+    /// * `DenyListError::IoError` - ファイルへの保存に失敗した場合
+    /// * `DenyListError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn add(&self, jti: String, expiry: u64) -> Result<(), DenyListError> {
+        let now = now_unix();
+        let mut entries = self.entries.lock()
+            .map_err(|_| DenyListError::MutexPoisoned)?;
+        entries.retain(|_, exp| *exp > now);
+        if expiry > now {
+            entries.insert(jti, expiry);
+        }
+        self.save(&entries)
+    }
+
+    /// JWTが失効済みかどうかを判定します
+    ///
+    /// JWTのペイロードから`jti`クレームを取り出し、デナイリストに
+    /// 期限内のエントリが存在するかを確認します。署名の検証は行いません。
+    ///
+    /// # Arguments
+    /// * `jwt` - 判定するJWT文字列
+    ///
+    /// # Errors
+    /// * `DenyListError::InvalidJwt` - JWTのデコードに失敗した場合
+    /// * `DenyListError::MissingJti` - `jti`クレームが存在しない場合
+    /// * `DenyListError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn is_revoked(&self, jwt: &str) -> Result<bool, DenyListError> {
+        let parts = decode_jwt(jwt)
+            .map_err(|e| DenyListError::InvalidJwt(e.to_string()))?;
+        let payload: Value = serde_json::from_str(&parts.payload)
+            .map_err(|e| DenyListError::InvalidJwt(e.to_string()))?;
+        let jti = payload.get("jti")
+            .and_then(Value::as_str)
+            .ok_or(DenyListError::MissingJti)?;
+
+        let entries = self.entries.lock()
+            .map_err(|_| DenyListError::MutexPoisoned)?;
+        Ok(entries.get(jti).is_some_and(|expiry| *expiry > now_unix()))
+    }
+
+    /// 期限切れのエントリを削除します
+    ///
+    /// # Errors
+    /// * `DenyListError::IoError` - ファイルへの保存に失敗した場合
+    /// * `DenyListError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn prune(&self) -> Result<(), DenyListError> {
+        self.prune_at(now_unix())
+    }
+
+    /// 現在保持しているエントリ数を返します
+    ///
+    /// # Errors
+    /// * `DenyListError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn count(&self) -> Result<u64, DenyListError> {
+        let entries = self.entries.lock()
+            .map_err(|_| DenyListError::MutexPoisoned)?;
+        Ok(entries.len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_deny_list_{}_{}.json", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn make_jwt(payload: &str) -> String {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"none","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(payload);
+        format!("{}.{}.sig", header, payload)
+    }
+
+    #[test]
+    fn test_deny_list_add_and_revoke() {
+        let path = temp_path("revoke");
+        let list = TokenDenyList::new(path.clone()).unwrap();
+        list.add("abc".to_string(), now_unix() + 3600).unwrap();
+
+        assert!(list.is_revoked(&make_jwt(r#"{"jti":"abc"}"#)).unwrap());
+        assert!(!list.is_revoked(&make_jwt(r#"{"jti":"xyz"}"#)).unwrap());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_deny_list_persistence() {
+        let path = temp_path("persist");
+        {
+            let list = TokenDenyList::new(path.clone()).unwrap();
+            list.add("persisted".to_string(), now_unix() + 3600).unwrap();
+        }
+        let reopened = TokenDenyList::new(path.clone()).unwrap();
+        assert!(reopened.is_revoked(&make_jwt(r#"{"jti":"persisted"}"#)).unwrap());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_deny_list_prunes_expired_entries() {
+        let path = temp_path("prune");
+        let list = TokenDenyList::new(path.clone()).unwrap();
+        list.add("short".to_string(), now_unix() + 10).unwrap();
+        list.add("long".to_string(), now_unix() + 3600).unwrap();
+        assert_eq!(list.count().unwrap(), 2);
+
+        list.prune_at(now_unix() + 100).unwrap();
+        assert_eq!(list.count().unwrap(), 1);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_deny_list_missing_jti() {
+        let path = temp_path("missing");
+        let list = TokenDenyList::new(path.clone()).unwrap();
+        match list.is_revoked(&make_jwt(r#"{"sub":"user"}"#)) {
+            Err(DenyListError::MissingJti) => (),
+            _ => panic!("Expected MissingJti error"),
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_deny_list_corrupted_file() {
+        let path = temp_path("corrupted");
+        fs::write(&path, "not json").unwrap();
+        match TokenDenyList::new(path.clone()) {
+            Err(DenyListError::CorruptedFile(_)) => (),
+            _ => panic!("Expected CorruptedFile error"),
+        }
+        let _ = fs::remove_file(path);
+    }
+}
@@ -0,0 +1,685 @@
+//! JSONドキュメントストアモジュール
+//!
+//! このモジュールは、コレクションごとにJSONドキュメントを保存・検索する
+//! `DocumentStore`をエクスポートします。SQLiteのJSON関数を使用し、
+//! よく検索するフィールドには式インデックスを作成できます。
+//! メモ機能などのオフライン用の軽量なデータベースとして使用します。
+//!
+//! # 検索条件
+//! `find`の条件はフィールドのパス（`.`区切り）から値または演算子への対応です。
+//! ```text
+//! {"done": false, "meta.priority": {"$gte": 2}, "tag": {"$in": ["work", "home"]}}
+//! ```
+//! 演算子は`$eq`・`$ne`・`$gt`・`$gte`・`$lt`・`$lte`・`$in`に対応し、
+//! 複数の条件はすべて満たすもの（AND）が返されます。
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params, params_from_iter, Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+use crate::snapshot::{
+    read_snapshot, write_snapshot, MergeStrategy, SnapshotError, SnapshotImportReport,
+    SnapshotKind,
+};
+
+/// ドキュメントを保存するテーブルの作成SQL
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS documents (
+    collection TEXT NOT NULL,
+    id TEXT NOT NULL,
+    body TEXT NOT NULL,
+    PRIMARY KEY (collection, id)
+) WITHOUT ROWID";
+
+/// ドキュメントストアの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DocumentStoreError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ドキュメントが不正なJSON、またはオブジェクトでない場合
+    #[error("Invalid document: {0}")]
+    InvalidDocument(String),
+    /// 検索条件が不正な場合
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+    /// フィールドのパスが不正な場合
+    #[error("Invalid field path: {0}")]
+    InvalidField(String),
+    /// データベースの操作に失敗した場合
+    #[error("Document storage error: {0}")]
+    StorageError(String),
+}
+
+impl From<rusqlite::Error> for DocumentStoreError {
+    fn from(error: rusqlite::Error) -> Self {
+        DocumentStoreError::StorageError(error.to_string())
+    }
+}
+
+/// 保存されたドキュメント
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct StoredDocument {
+    /// ドキュメントのID
+    pub id: String,
+    /// ドキュメントのJSON
+    pub json: String,
+}
+
+/// フィールドのパス（例: `meta.priority`）をSQLiteのJSONパス（`$.meta.priority`）に変換します
+///
+/// SQLに埋め込むため、各要素は英数字と`_`のみに制限します。
+fn json_path(field: &str) -> Result<String, DocumentStoreError> {
+    let valid = field.split('.').all(|segment| {
+        !segment.is_empty() && segment.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    });
+    if !valid {
+        return Err(DocumentStoreError::InvalidField(field.to_string()));
+    }
+    Ok(format!("$.{}", field))
+}
+
+/// 検索条件のJSON値をSQLiteの値に変換します（真偽値は`json_extract`と同じ0/1）
+fn to_sql_value(value: &Value) -> Result<SqlValue, DocumentStoreError> {
+    Ok(match value {
+        Value::Null => SqlValue::Null,
+        Value::Bool(b) => SqlValue::Integer(i64::from(*b)),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => SqlValue::Integer(i),
+            None => SqlValue::Real(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Value::String(s) => SqlValue::Text(s.clone()),
+        Value::Array(_) | Value::Object(_) => {
+            return Err(DocumentStoreError::InvalidFilter(
+                "only scalar values can be compared".to_string(),
+            ))
+        }
+    })
+}
+
+/// 1つのフィールドの条件をSQLの式に変換します
+fn field_condition(
+    field: &str,
+    condition: &Value,
+    sql: &mut Vec<String>,
+    params: &mut Vec<SqlValue>,
+) -> Result<(), DocumentStoreError> {
+    let column = format!("json_extract(body, '{}')", json_path(field)?);
+    let operators = match condition {
+        Value::Object(map) if map.keys().any(|k| k.starts_with('$')) => map.clone(),
+        other => Map::from_iter([("$eq".to_string(), other.clone())]),
+    };
+    for (operator, operand) in &operators {
+        if operator == "$in" {
+            let Value::Array(values) = operand else {
+                return Err(DocumentStoreError::InvalidFilter("$in requires an array".to_string()));
+            };
+            if values.is_empty() {
+                sql.push("0".to_string());
+                continue;
+            }
+            for value in values {
+                params.push(to_sql_value(value)?);
+            }
+            sql.push(format!("{} IN ({})", column, vec!["?"; values.len()].join(", ")));
+            continue;
+        }
+        let operand = to_sql_value(operand)?;
+        let expression = match (operator.as_str(), &operand) {
+            ("$eq", SqlValue::Null) => format!("{} IS NULL", column),
+            ("$ne", SqlValue::Null) => format!("{} IS NOT NULL", column),
+            ("$eq", _) => format!("{} = ?", column),
+            ("$ne", _) => format!("{} IS NOT ?", column),
+            ("$gt", _) => format!("{} > ?", column),
+            ("$gte", _) => format!("{} >= ?", column),
+            ("$lt", _) => format!("{} < ?", column),
+            ("$lte", _) => format!("{} <= ?", column),
+            _ => {
+                return Err(DocumentStoreError::InvalidFilter(format!(
+                    "unknown operator: {}",
+                    operator
+                )))
+            }
+        };
+        if operand != SqlValue::Null || !matches!(operator.as_str(), "$eq" | "$ne") {
+            params.push(operand);
+        }
+        sql.push(expression);
+    }
+    Ok(())
+}
+
+/// JSONドキュメントストア
+///
+/// ドキュメントはコレクションとIDの組で識別され、同じ組への`put`は上書きになります。
+/// `create_index`でフィールドにインデックスを作成すると、そのフィールドを条件に含む
+/// `find`が高速になります。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let store = DocumentStore::open(path)?;
+/// store.create_index("tag".to_string())?;
+/// store.put("notes".to_string(), "n1".to_string(), r#"{"tag":"work","body":"..."}"#.to_string())?;
+/// let work_notes = store.find("notes".to_string(), r#"{"tag":"work"}"#.to_string())?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct DocumentStore {
+    connection: Mutex<Connection>,
+}
+
+#[uniffi::export]
+impl DocumentStore {
+    /// ストアのファイルを開きます（存在しない場合は作成されます）
+    ///
+    /// # Arguments
+    /// * `path` - SQLiteファイルのパス
+    ///
+    /// # Errors
+    /// * `DocumentStoreError::StorageError` - ファイルを開けない、またはテーブルを作成できない場合
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, DocumentStoreError> {
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(CREATE_TABLE)?;
+        Ok(Arc::new(Self {
+            connection: Mutex::new(connection),
+        }))
+    }
+
+    /// フィールドにインデックスを作成します（既に存在する場合は何もしません）
+    ///
+    /// インデックスはすべてのコレクションに共通で、コレクションとフィールドの値の組で作成されます。
+    ///
+    /// # Arguments
+    /// * `field` - フィールドのパス（英数字と`_`を`.`で区切ったもの）
+    ///
+    /// # Errors
+    /// * `DocumentStoreError::InvalidField` - パスが不正な場合
+    /// * `DocumentStoreError::StorageError` - インデックスを作成できない場合
+    /// * `DocumentStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn create_index(&self, field: String) -> Result<(), DocumentStoreError> {
+        let path = json_path(&field)?;
+        let connection = self.connection.lock()
+            .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+        connection.execute_batch(&format!(
+            "CREATE INDEX IF NOT EXISTS \"idx_documents_{}\"
+             ON documents (collection, json_extract(body, '{}'))",
+            field.replace('.', "__"),
+            path
+        ))?;
+        Ok(())
+    }
+
+    /// ドキュメントを保存します（同じIDのドキュメントは上書きされます）
+    ///
+    /// # Arguments
+    /// * `collection` - コレクション名
+    /// * `id` - ドキュメントのID
+    /// * `json` - JSONオブジェクト
+    ///
+    /// # Errors
+    /// * `DocumentStoreError::InvalidDocument` - 不正なJSON、またはオブジェクトでない場合
+    /// * `DocumentStoreError::StorageError` - 書き込みに失敗した場合
+    /// * `DocumentStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn put(
+        &self,
+        collection: String,
+        id: String,
+        json: String,
+    ) -> Result<(), DocumentStoreError> {
+        let document: Value = serde_json::from_str(&json)
+            .map_err(|e| DocumentStoreError::InvalidDocument(e.to_string()))?;
+        if !document.is_object() {
+            return Err(DocumentStoreError::InvalidDocument(
+                "document must be a JSON object".to_string(),
+            ));
+        }
+        let connection = self.connection.lock()
+            .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+        connection.execute(
+            "INSERT OR REPLACE INTO documents (collection, id, body) VALUES (?1, ?2, ?3)",
+            params![collection, id, document.to_string()],
+        )?;
+        Ok(())
+    }
+
+    /// ドキュメントを取得します（存在しない場合は`None`）
+    ///
+    /// # Errors
+    /// * `DocumentStoreError::StorageError` - 読み込みに失敗した場合
+    /// * `DocumentStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn get(
+        &self,
+        collection: String,
+        id: String,
+    ) -> Result<Option<String>, DocumentStoreError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+        Ok(connection
+            .query_row(
+                "SELECT body FROM documents WHERE collection = ?1 AND id = ?2",
+                params![collection, id],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
+
+    /// ドキュメントを削除します
+    ///
+    /// # Returns
+    /// * `true` - ドキュメントが存在し削除された場合
+    /// * `false` - ドキュメントが存在しなかった場合
+    ///
+    /// # Errors
+    /// * `DocumentStoreError::StorageError` - 書き込みに失敗した場合
+    /// * `DocumentStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn delete(&self, collection: String, id: String) -> Result<bool, DocumentStoreError> {
+        let connection = self.connection.lock()
+            .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+        let deleted = connection.execute(
+            "DELETE FROM documents WHERE collection = ?1 AND id = ?2",
+            params![collection, id],
+        )?;
+        Ok(deleted > 0)
+    }
+
+    /// 条件に一致するドキュメントをIDの昇順で返します
+    ///
+    /// # Arguments
+    /// * `collection` - コレクション名
+    /// * `filter_json` - 検索条件のJSONオブジェクト（`{}`の場合はすべてのドキュメント）
+    ///
+    /// # Errors
+    /// * `DocumentStoreError::InvalidFilter` - 条件が不正なJSON、未対応の演算子、
+    ///   またはオブジェクト・配列との比較を含む場合
+    /// * `DocumentStoreError::InvalidField` - フィールドのパスが不正な場合
+    /// * `DocumentStoreError::StorageError` - 読み込みに失敗した場合
+    /// * `DocumentStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn find(
+        &self,
+        collection: String,
+        filter_json: String,
+    ) -> Result<Vec<StoredDocument>, DocumentStoreError> {
+        let filter: Value = serde_json::from_str(&filter_json)
+            .map_err(|e| DocumentStoreError::InvalidFilter(e.to_string()))?;
+        let Value::Object(filter) = filter else {
+            return Err(DocumentStoreError::InvalidFilter(
+                "filter must be a JSON object".to_string(),
+            ));
+        };
+        let mut conditions = vec!["collection = ?".to_string()];
+        let mut values = vec![SqlValue::Text(collection)];
+        for (field, condition) in &filter {
+            field_condition(field, condition, &mut conditions, &mut values)?;
+        }
+        let sql = format!(
+            "SELECT id, body FROM documents WHERE {} ORDER BY id",
+            conditions.join(" AND ")
+        );
+
+        let connection = self.connection.lock()
+            .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+        let mut statement = connection.prepare(&sql)?;
+        let rows = statement.query_map(params_from_iter(values), |row| {
+            Ok(StoredDocument {
+                id: row.get(0)?,
+                json: row.get(1)?,
+            })
+        })?;
+        Ok(rows.collect::<Result<_, _>>()?)
+    }
+
+    /// すべてのドキュメントをパスフレーズで暗号化したスナップショットファイルに書き出します
+    ///
+    /// ユーザーのバックアップや機種変更時の移行に使用します。インデックスは含まれないため、
+    /// 読み込み先のストアで`create_index`を呼び出してください。
+    ///
+    /// # Arguments
+    /// * `path` - 書き出すファイルのパス（既存のファイルはアトミックに置き換えられます）
+    /// * `passphrase` - スナップショットを保護するパスフレーズ
+    ///
+    /// # Errors
+    /// * `SnapshotError::IoError` - ファイルの書き込みに失敗した場合
+    /// * `SnapshotError::StoreError` - ドキュメントの読み込みに失敗した場合
+    pub fn export_snapshot(&self, path: String, passphrase: String) -> Result<(), SnapshotError> {
+        let documents = {
+            let connection = self.connection.lock()
+                .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+            let mut statement = connection
+                .prepare("SELECT collection, id, body FROM documents ORDER BY collection, id")?;
+            let rows = statement.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?))
+            })?;
+            let mut documents = Vec::new();
+            for row in rows {
+                let (collection, id, body) = row?;
+                let body: Value = serde_json::from_str(&body)
+                    .map_err(|e| SnapshotError::StoreError(e.to_string()))?;
+                let mut document = Map::new();
+                document.insert("collection".to_string(), collection.into());
+                document.insert("id".to_string(), id.into());
+                document.insert("body".to_string(), body);
+                documents.push(Value::Object(document));
+            }
+            documents
+        };
+        let plaintext = Value::Array(documents).to_string();
+        write_snapshot(&path, &passphrase, SnapshotKind::Documents, plaintext.as_bytes())
+    }
+
+    /// `export_snapshot`で書き出したスナップショットを読み込みます
+    ///
+    /// 1つのトランザクションで書き込むため、途中で失敗した場合は何も変更されません。
+    ///
+    /// # Arguments
+    /// * `path` - スナップショットファイルのパス
+    /// * `passphrase` - 書き出し時に指定したパスフレーズ
+    /// * `merge_strategy` - 同じコレクション・IDの既存のドキュメントとの統合方法
+    ///
+    /// # Errors
+    /// * `SnapshotError::InvalidPassphrase` - パスフレーズが誤っている、または改ざんされている場合
+    /// * `SnapshotError::KindMismatch` - `SecureStore`のスナップショットの場合
+    /// * `SnapshotError::CorruptedSnapshot` - ファイルの内容が不正な場合、
+    ///   または鍵導出パラメータが既定値の4倍を超える場合
+    /// * `SnapshotError::IoError` - ファイルの読み込みに失敗した場合
+    /// * `SnapshotError::StoreError` - ストアへの書き込みに失敗した場合
+    pub fn import_snapshot(
+        &self,
+        path: String,
+        passphrase: String,
+        merge_strategy: MergeStrategy,
+    ) -> Result<SnapshotImportReport, SnapshotError> {
+        let plaintext = read_snapshot(&path, &passphrase, SnapshotKind::Documents)?;
+        let corrupted = |message: &str| SnapshotError::CorruptedSnapshot(message.to_string());
+        let Value::Array(documents) = serde_json::from_slice(&plaintext)
+            .map_err(|e| SnapshotError::CorruptedSnapshot(e.to_string()))?
+        else {
+            return Err(corrupted("documents must be a JSON array"));
+        };
+
+        let mut connection = self.connection.lock()
+            .map_err(|_| DocumentStoreError::MutexPoisoned)?;
+        let transaction = connection.transaction()?;
+        let mut report = SnapshotImportReport {
+            imported: 0,
+            skipped: 0,
+            removed: 0,
+        };
+        if merge_strategy == MergeStrategy::Replace {
+            report.removed = transaction.execute("DELETE FROM documents", [])? as u64;
+        }
+        let sql = match merge_strategy {
+            MergeStrategy::KeepExisting => {
+                "INSERT OR IGNORE INTO documents (collection, id, body) VALUES (?1, ?2, ?3)"
+            }
+            MergeStrategy::Replace | MergeStrategy::Overwrite => {
+                "INSERT OR REPLACE INTO documents (collection, id, body) VALUES (?1, ?2, ?3)"
+            }
+        };
+        for document in &documents {
+            let collection = document.get("collection").and_then(Value::as_str);
+            let id = document.get("id").and_then(Value::as_str);
+            let body = document.get("body").filter(|body| body.is_object());
+            let (Some(collection), Some(id), Some(body)) = (collection, id, body) else {
+                return Err(corrupted("invalid document entry"));
+            };
+            if transaction.execute(sql, params![collection, id, body.to_string()])? > 0 {
+                report.imported += 1;
+            } else {
+                report.skipped += 1;
+            }
+        }
+        transaction.commit()?;
+        Ok(report)
+    }
+}
+
+/// 非同期版のAPI
+///
+/// 各メソッドは同名の同期メソッドと同じ処理をRust側のスレッドプールで実行するため、
+/// 呼び出し元のスレッドをブロックしません。
+#[uniffi::export]
+impl DocumentStore {
+    /// `create_index`の非同期版です
+    ///
+    /// # Errors
+    /// * `create_index`と同じエラー
+    pub async fn create_index_async(
+        self: Arc<Self>,
+        field: String,
+    ) -> Result<(), DocumentStoreError> {
+        blocking::unblock(move || self.create_index(field)).await
+    }
+
+    /// `put`の非同期版です
+    ///
+    /// # Errors
+    /// * `put`と同じエラー
+    pub async fn put_async(
+        self: Arc<Self>,
+        collection: String,
+        id: String,
+        json: String,
+    ) -> Result<(), DocumentStoreError> {
+        blocking::unblock(move || self.put(collection, id, json)).await
+    }
+
+    /// `get`の非同期版です
+    ///
+    /// # Errors
+    /// * `get`と同じエラー
+    pub async fn get_async(
+        self: Arc<Self>,
+        collection: String,
+        id: String,
+    ) -> Result<Option<String>, DocumentStoreError> {
+        blocking::unblock(move || self.get(collection, id)).await
+    }
+
+    /// `delete`の非同期版です
+    ///
+    /// # Errors
+    /// * `delete`と同じエラー
+    pub async fn delete_async(
+        self: Arc<Self>,
+        collection: String,
+        id: String,
+    ) -> Result<bool, DocumentStoreError> {
+        blocking::unblock(move || self.delete(collection, id)).await
+    }
+
+    /// `find`の非同期版です
+    ///
+    /// # Errors
+    /// * `find`と同じエラー
+    pub async fn find_async(
+        self: Arc<Self>,
+        collection: String,
+        filter_json: String,
+    ) -> Result<Vec<StoredDocument>, DocumentStoreError> {
+        blocking::unblock(move || self.find(collection, filter_json)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_document_store_{}_{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn notes_store(name: &str) -> (Arc<DocumentStore>, String) {
+        let path = temp_path(name);
+        let store = DocumentStore::open(path.clone()).unwrap();
+        let notes = [
+            ("n1", r#"{"tag":"work","done":false,"meta":{"priority":1}}"#),
+            ("n2", r#"{"tag":"home","done":true,"meta":{"priority":3}}"#),
+            ("n3", r#"{"tag":"work","done":true,"meta":{"priority":2}}"#),
+            ("n4", r#"{"done":false}"#),
+        ];
+        for (id, json) in notes {
+            store.put("notes".to_string(), id.to_string(), json.to_string()).unwrap();
+        }
+        store.put("other".to_string(), "n1".to_string(), r#"{"tag":"work"}"#.to_string()).unwrap();
+        (store, path)
+    }
+
+    fn find_ids(store: &DocumentStore, filter: &str) -> Vec<String> {
+        store
+            .find("notes".to_string(), filter.to_string())
+            .unwrap()
+            .into_iter()
+            .map(|document| document.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_document_store_put_get_delete() {
+        let (store, path) = notes_store("basic");
+        let json = store.get("notes".to_string(), "n1".to_string()).unwrap().unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["meta"]["priority"], 1);
+        assert_eq!(store.get("notes".to_string(), "missing".to_string()).unwrap(), None);
+
+        store.put("notes".to_string(), "n1".to_string(), r#"{"tag":"x"}"#.to_string()).unwrap();
+        assert_eq!(
+            store.get("notes".to_string(), "n1".to_string()).unwrap(),
+            Some(r#"{"tag":"x"}"#.to_string())
+        );
+        assert!(store.delete("notes".to_string(), "n1".to_string()).unwrap());
+        assert!(!store.delete("notes".to_string(), "n1".to_string()).unwrap());
+        assert!(store.get("other".to_string(), "n1".to_string()).unwrap().is_some());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_document_store_find() {
+        let (store, path) = notes_store("find");
+        store.create_index("tag".to_string()).unwrap();
+        store.create_index("meta.priority".to_string()).unwrap();
+        assert_eq!(find_ids(&store, "{}"), vec!["n1", "n2", "n3", "n4"]);
+        assert_eq!(find_ids(&store, r#"{"tag":"work"}"#), vec!["n1", "n3"]);
+        assert_eq!(find_ids(&store, r#"{"tag":"work","done":true}"#), vec!["n3"]);
+        assert_eq!(find_ids(&store, r#"{"meta.priority":{"$gte":2}}"#), vec!["n2", "n3"]);
+        assert_eq!(find_ids(&store, r#"{"meta.priority":{"$gt":1,"$lt":3}}"#), vec!["n3"]);
+        assert_eq!(find_ids(&store, r#"{"tag":{"$in":["home","none"]}}"#), vec!["n2"]);
+        assert_eq!(find_ids(&store, r#"{"tag":null}"#), vec!["n4"]);
+        assert_eq!(find_ids(&store, r#"{"tag":{"$ne":"work"}}"#), vec!["n2", "n4"]);
+        assert!(find_ids(&store, r#"{"tag":{"$in":[]}}"#).is_empty());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_document_store_index_is_used() {
+        let (store, path) = notes_store("index");
+        store.create_index("tag".to_string()).unwrap();
+        let connection = store.connection.lock().unwrap();
+        let plan: String = connection
+            .query_row(
+                "EXPLAIN QUERY PLAN SELECT id FROM documents
+                 WHERE collection = ?1 AND json_extract(body, '$.tag') = ?2",
+                params!["notes", "work"],
+                |row| row.get(3),
+            )
+            .unwrap();
+        assert!(plan.contains("idx_documents_tag"), "plan: {}", plan);
+        drop(connection);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_document_store_errors() {
+        let (store, path) = notes_store("errors");
+        match store.put("notes".to_string(), "x".to_string(), "[1]".to_string()) {
+            Err(DocumentStoreError::InvalidDocument(_)) => (),
+            other => panic!("Expected InvalidDocument error, got {:?}", other),
+        }
+        for filter in ["[]", r#"{"tag":{"$regex":"w"}}"#, r#"{"tag":{"$in":"work"}}"#] {
+            match store.find("notes".to_string(), filter.to_string()) {
+                Err(DocumentStoreError::InvalidFilter(_)) => (),
+                other => panic!("Expected InvalidFilter error for {}, got {:?}", filter, other),
+            }
+        }
+        match store.find("notes".to_string(), r#"{"a')--":1}"#.to_string()) {
+            Err(DocumentStoreError::InvalidField(_)) => (),
+            other => panic!("Expected InvalidField error, got {:?}", other),
+        }
+        match store.create_index("meta..x".to_string()) {
+            Err(DocumentStoreError::InvalidField(_)) => (),
+            other => panic!("Expected InvalidField error, got {:?}", other),
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_document_store_async() {
+        let path = temp_path("async");
+        let store = DocumentStore::open(path.clone()).unwrap();
+        pollster::block_on(store.clone().create_index_async("age".to_string())).unwrap();
+        pollster::block_on(store.clone().put_async(
+            "users".to_string(),
+            "u1".to_string(),
+            r#"{"age":30}"#.to_string(),
+        ))
+        .unwrap();
+        let found = pollster::block_on(
+            store.clone().find_async("users".to_string(), r#"{"age":{"$gte":18}}"#.to_string()),
+        )
+        .unwrap();
+        assert_eq!(found.len(), 1);
+        let json = pollster::block_on(
+            store.clone().get_async("users".to_string(), "u1".to_string()),
+        )
+        .unwrap();
+        assert_eq!(json.as_deref(), Some(r#"{"age":30}"#));
+        assert!(pollster::block_on(store.delete_async("users".to_string(), "u1".to_string()))
+            .unwrap());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_document_store_snapshot_export_import() {
+        let source_path = temp_path("snapshot_source");
+        let target_path = temp_path("snapshot_target");
+        let snapshot = temp_path("snapshot_file");
+        let source = DocumentStore::open(source_path.clone()).unwrap();
+        source.put("notes".to_string(), "1".to_string(), r#"{"v":"new"}"#.to_string()).unwrap();
+        source.put("todos".to_string(), "1".to_string(), r#"{"v":2}"#.to_string()).unwrap();
+        source.export_snapshot(snapshot.clone(), "pass".to_string()).unwrap();
+
+        let target = DocumentStore::open(target_path.clone()).unwrap();
+        target.put("notes".to_string(), "1".to_string(), r#"{"v":"old"}"#.to_string()).unwrap();
+        target.put("notes".to_string(), "2".to_string(), r#"{"v":"x"}"#.to_string()).unwrap();
+        let report = target
+            .import_snapshot(snapshot.clone(), "pass".to_string(), MergeStrategy::KeepExisting)
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.removed), (1, 1, 0));
+        let note = target.get("notes".to_string(), "1".to_string()).unwrap();
+        assert_eq!(note.as_deref(), Some(r#"{"v":"old"}"#));
+
+        let report = target
+            .import_snapshot(snapshot.clone(), "pass".to_string(), MergeStrategy::Overwrite)
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.removed), (2, 0, 0));
+        let note = target.get("notes".to_string(), "1".to_string()).unwrap();
+        assert_eq!(note.as_deref(), Some(r#"{"v":"new"}"#));
+
+        let report = target
+            .import_snapshot(snapshot.clone(), "pass".to_string(), MergeStrategy::Replace)
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.removed), (2, 0, 3));
+        assert!(target.get("notes".to_string(), "2".to_string()).unwrap().is_none());
+        let _ = std::fs::remove_file(source_path);
+        let _ = std::fs::remove_file(target_path);
+        let _ = std::fs::remove_file(snapshot);
+    }
+}
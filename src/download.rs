@@ -0,0 +1,730 @@
+//! ダウンロード管理モジュール
+//!
+//! このモジュールは、HTTP(S)のリソースをファイルにダウンロードする`Downloader`と、
+//! 進捗・完了・失敗を受け取る`DownloadObserver`をエクスポートします。
+//! ダウンロードはRust側のスレッドで実行され、`DownloadTask`から取り消せます。
+//!
+//! # 再開
+//! 受信中のデータは`<destination>.part`に書き込み、完了するとリネームで
+//! `destination`に置き換えます。失敗・取り消しの後に同じ保存先で再度開始すると、
+//! `Range`ヘッダーで続きから受信します。最初のレスポンスの`ETag`
+//! （なければ`Last-Modified`）を`<destination>.part.validator`に保存して
+//! `If-Range`で送るため、サーバー側でリソースが変わっていた場合は最初から受信し直します。
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::file_io::sync_parent_directory;
+use crate::retry::RetryPolicy;
+
+/// 接続・読み込みのタイムアウト
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 1回に読み込む最大バイト数
+const MAX_CHUNK_LEN: usize = 16 * 1024;
+
+/// 帯域制限時に1回に読み込む最小バイト数
+const MIN_CHUNK_LEN: usize = 1024;
+
+/// 進捗を通知する最小間隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 帯域制限の待機中に取り消しを確認する間隔
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// ダウンロードで発生する可能性のあるエラー
+#[derive(Debug, Clone, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DownloadError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// URLが不正、またはHTTP(S)以外のスキームの場合
+    #[error("Invalid download URL: {0}")]
+    InvalidUrl(String),
+    /// 通信に失敗した、または受信が途中で終了した場合
+    #[error("Network error: {0}")]
+    Network(String),
+    /// サーバーが成功以外のステータスを返した場合
+    #[error("Unexpected HTTP status: {0}")]
+    HttpStatus(u16),
+    /// ファイルの書き込みに失敗した場合
+    #[error("Failed to write download file: {0}")]
+    IoError(String),
+    /// ダウンロードが取り消された場合
+    #[error("Download was cancelled")]
+    Cancelled,
+}
+
+impl From<io::Error> for DownloadError {
+    fn from(error: io::Error) -> Self {
+        DownloadError::IoError(error.to_string())
+    }
+}
+
+/// ダウンロードの進捗・完了・失敗を受け取るオブザーバー
+///
+/// メソッドはダウンロードを実行しているRust側のスレッドから呼ばれます。
+/// UIを更新する場合はメインスレッドに切り替えてください。
+#[uniffi::export(with_foreign)]
+pub trait DownloadObserver: Send + Sync {
+    /// 受信が進んだときに呼ばれます（最大で100ミリ秒に1回）
+    ///
+    /// # Arguments
+    /// * `downloaded_bytes` - 再開前の分を含む受信済みのバイト数
+    /// * `total_bytes` - 全体のバイト数（サーバーが示さない場合は`None`）
+    fn on_progress(&self, downloaded_bytes: u64, total_bytes: Option<u64>);
+
+    /// ダウンロードが完了し、保存先のファイルが作成されたときに呼ばれます
+    fn on_completed(&self, path: String, total_bytes: u64);
+
+    /// ダウンロードが失敗または取り消されたときに呼ばれます
+    ///
+    /// 受信済みのデータは残るため、同じ保存先で再度開始すると続きから再開します。
+    fn on_failed(&self, error: DownloadError);
+}
+
+/// 実行中または終了したダウンロード
+///
+/// `Downloader::start`が返します。`cancel`で取り消し、`wait`で終了を待てます。
+#[derive(uniffi::Object)]
+pub struct DownloadTask {
+    cancelled: AtomicBool,
+    result: Mutex<Option<Result<u64, DownloadError>>>,
+    finished: Condvar,
+}
+
+impl DownloadTask {
+    /// 結果を記録し、待機中の呼び出しを再開します
+    fn finish(&self, result: Result<u64, DownloadError>) {
+        if let Ok(mut slot) = self.result.lock() {
+            *slot = Some(result);
+        }
+        self.finished.notify_all();
+    }
+
+    /// 終了するまで現在のスレッドで待ちます
+    fn wait_blocking(&self) -> Result<u64, DownloadError> {
+        let mut slot = self.result.lock()
+            .map_err(|_| DownloadError::MutexPoisoned)?;
+        loop {
+            if let Some(result) = slot.as_ref() {
+                return result.clone();
+            }
+            slot = self.finished.wait(slot)
+                .map_err(|_| DownloadError::MutexPoisoned)?;
+        }
+    }
+}
+
+#[uniffi::export]
+impl DownloadTask {
+    /// ダウンロードを取り消します
+    ///
+    /// 次の読み込みの区切りで停止し、オブザーバーの`on_failed`に
+    /// `DownloadError::Cancelled`が通知されます。終了後に呼んでも何も起きません。
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// 取り消しが要求されたかを返します
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// ダウンロードが終了したかを返します
+    pub fn is_finished(&self) -> bool {
+        self.result.lock().map(|slot| slot.is_some()).unwrap_or(true)
+    }
+
+    /// ダウンロードの終了を待ち、受信した全体のバイト数を返します
+    ///
+    /// # Errors
+    /// * オブザーバーの`on_failed`に通知されたものと同じエラー
+    pub async fn wait(self: Arc<Self>) -> Result<u64, DownloadError> {
+        blocking::unblock(move || self.wait_blocking()).await
+    }
+}
+
+/// 帯域制限と取り消しを考慮した読み込みの進行状況
+struct Transfer<'a> {
+    task: &'a DownloadTask,
+    limit: &'a AtomicU64,
+    /// 現在の制限値（バイト/秒、0は無制限）
+    current_limit: u64,
+    window_start: Instant,
+    window_bytes: u64,
+}
+
+impl Transfer<'_> {
+    /// 次に読み込むバイト数を返します
+    fn chunk_len(&mut self) -> usize {
+        let limit = self.limit.load(Ordering::Relaxed);
+        if limit != self.current_limit {
+            self.current_limit = limit;
+            self.window_start = Instant::now();
+            self.window_bytes = 0;
+        }
+        if limit == 0 {
+            MAX_CHUNK_LEN
+        } else {
+            ((limit / 10) as usize).clamp(MIN_CHUNK_LEN, MAX_CHUNK_LEN)
+        }
+    }
+
+    /// 読み込んだバイト数を記録し、制限を超えている分だけ待ちます
+    fn record(&mut self, bytes: usize) -> Result<(), DownloadError> {
+        self.window_bytes += bytes as u64;
+        if self.current_limit > 0 {
+            let expected =
+                Duration::from_secs_f64(self.window_bytes as f64 / self.current_limit as f64);
+            while self.window_start.elapsed() < expected {
+                self.check_cancelled()?;
+                let remaining = expected - self.window_start.elapsed();
+                thread::sleep(remaining.min(CANCEL_POLL_INTERVAL));
+            }
+        }
+        self.check_cancelled()
+    }
+
+    fn check_cancelled(&self) -> Result<(), DownloadError> {
+        if self.task.is_cancelled() {
+            return Err(DownloadError::Cancelled);
+        }
+        Ok(())
+    }
+}
+
+/// 保存先にサフィックスを付けたパス
+fn sibling_path(destination: &Path, suffix: &str) -> PathBuf {
+    let mut name = destination.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// `Content-Range: bytes <start>-<end>/<total>`から(開始位置, 全体)を読み取ります
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let (range, total) = value.trim().strip_prefix("bytes ")?.split_once('/')?;
+    let start = range.split_once('-')?.0.trim().parse().ok()?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().ok()?),
+    };
+    Some((start, total))
+}
+
+/// ファイルにダウンロードするダウンローダー
+///
+/// 同時に複数のダウンロードを開始でき、帯域制限はそれぞれのダウンロードに適用されます。
+///
+/// # Example
+/// ```
+/// let downloader = Downloader::new(Some(512 * 1024), Some(default_retry_policy()));
+/// let task = downloader.start(url, destination, observer)?;
+/// // 画面を閉じたときなど
+/// task.cancel();
+/// ```
+#[derive(uniffi::Object)]
+pub struct Downloader {
+    agent: ureq::Agent,
+    /// 1ダウンロードあたりの帯域の上限（バイト/秒、0は無制限）
+    max_bytes_per_second: AtomicU64,
+    /// 通信エラー時の再試行ポリシー（`None`の場合は再試行しない）
+    retry_policy: Option<RetryPolicy>,
+}
+
+impl Downloader {
+    /// 再試行ポリシーに従ってダウンロードを繰り返し、受信した全体のバイト数を返します
+    ///
+    /// 再試行は受信済みのデータの続きから行います。
+    fn run_with_retry(
+        &self,
+        task: &DownloadTask,
+        url: &str,
+        destination: &Path,
+        observer: &dyn DownloadObserver,
+    ) -> Result<u64, DownloadError> {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let error = match self.run(task, url, destination, observer) {
+                Ok(total) => return Ok(total),
+                Err(e) => e,
+            };
+            let Some(policy) = &self.retry_policy else {
+                return Err(error);
+            };
+            let retryable = match &error {
+                DownloadError::Network(_) => true,
+                DownloadError::HttpStatus(status) => policy.retries_status(*status),
+                _ => false,
+            };
+            let delay = match policy.delay(attempt) {
+                Some(delay) if retryable => Duration::from_millis(delay),
+                _ => return Err(error),
+            };
+            let deadline = Instant::now() + delay;
+            while Instant::now() < deadline {
+                if task.is_cancelled() {
+                    return Err(DownloadError::Cancelled);
+                }
+                thread::sleep(CANCEL_POLL_INTERVAL.min(deadline - Instant::now()));
+            }
+        }
+    }
+
+    /// ダウンロードを1回実行し、受信した全体のバイト数を返します
+    fn run(
+        &self,
+        task: &DownloadTask,
+        url: &str,
+        destination: &Path,
+        observer: &dyn DownloadObserver,
+    ) -> Result<u64, DownloadError> {
+        let lower = url.to_ascii_lowercase();
+        if !lower.starts_with("https://") && !lower.starts_with("http://") {
+            return Err(DownloadError::InvalidUrl(url.to_string()));
+        }
+        let part_path = sibling_path(destination, ".part");
+        let validator_path = sibling_path(destination, ".part.validator");
+
+        // 保存済みの範囲がサーバー側で無効になっていた場合は最初からやり直す
+        let mut restarted = false;
+        let (response, file, offset, total) = loop {
+            let offset = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+            let mut request = self.agent.get(url);
+            if offset > 0 {
+                request = request.set("Range", &format!("bytes={}-", offset));
+                if let Ok(validator) = fs::read_to_string(&validator_path) {
+                    request = request.set("If-Range", validator.trim());
+                }
+            }
+            let response = match request.call() {
+                Ok(response) => response,
+                Err(ureq::Error::Status(416, _)) if offset > 0 && !restarted => {
+                    let _ = fs::remove_file(&part_path);
+                    let _ = fs::remove_file(&validator_path);
+                    restarted = true;
+                    continue;
+                }
+                Err(ureq::Error::Status(code, _)) => return Err(DownloadError::HttpStatus(code)),
+                Err(ureq::Error::Transport(e)) => {
+                    return Err(match e.kind() {
+                        ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+                            DownloadError::InvalidUrl(url.to_string())
+                        }
+                        _ => DownloadError::Network(e.to_string()),
+                    });
+                }
+            };
+
+            if response.status() == 206 && offset > 0 {
+                let (start, total) = response
+                    .header("Content-Range")
+                    .and_then(parse_content_range)
+                    .ok_or_else(|| DownloadError::Network("invalid Content-Range".to_string()))?;
+                if start != offset {
+                    return Err(DownloadError::Network(format!(
+                        "server resumed at byte {} instead of {}",
+                        start, offset
+                    )));
+                }
+                let file = OpenOptions::new().append(true).open(&part_path)?;
+                break (response, file, offset, total);
+            }
+            if !(200..300).contains(&response.status()) {
+                return Err(DownloadError::HttpStatus(response.status()));
+            }
+            let total = response.header("Content-Length").and_then(|v| v.trim().parse().ok());
+            let validator = response.header("ETag").or_else(|| response.header("Last-Modified"));
+            match validator {
+                Some(validator) => fs::write(&validator_path, validator)?,
+                None => {
+                    let _ = fs::remove_file(&validator_path);
+                }
+            }
+            let file = File::create(&part_path)?;
+            break (response, file, 0, total);
+        };
+
+        let downloaded = self.receive(task, response, file, offset, total, observer)?;
+        if let Some(total) = total {
+            if downloaded != total {
+                return Err(DownloadError::Network(format!(
+                    "connection closed after {} of {} bytes",
+                    downloaded, total
+                )));
+            }
+        }
+        fs::rename(&part_path, destination)?;
+        let _ = fs::remove_file(&validator_path);
+        sync_parent_directory(destination);
+        Ok(downloaded)
+    }
+
+    /// レスポンスボディをファイルに書き込み、受信済みの全体のバイト数を返します
+    fn receive(
+        &self,
+        task: &DownloadTask,
+        response: ureq::Response,
+        mut file: File,
+        offset: u64,
+        total: Option<u64>,
+        observer: &dyn DownloadObserver,
+    ) -> Result<u64, DownloadError> {
+        let mut transfer = Transfer {
+            task,
+            limit: &self.max_bytes_per_second,
+            current_limit: 0,
+            window_start: Instant::now(),
+            window_bytes: 0,
+        };
+        let mut reader = response.into_reader();
+        let mut buf = vec![0u8; MAX_CHUNK_LEN];
+        let mut downloaded = offset;
+        observer.on_progress(downloaded, total);
+        let mut last_report = Instant::now();
+        loop {
+            transfer.check_cancelled()?;
+            let len = transfer.chunk_len();
+            let n = reader
+                .read(&mut buf[..len])
+                .map_err(|e| DownloadError::Network(e.to_string()))?;
+            if n == 0 {
+                break;
+            }
+            file.write_all(&buf[..n])?;
+            downloaded += n as u64;
+            if last_report.elapsed() >= PROGRESS_INTERVAL {
+                observer.on_progress(downloaded, total);
+                last_report = Instant::now();
+            }
+            transfer.record(n)?;
+        }
+        file.sync_all()?;
+        observer.on_progress(downloaded, total);
+        Ok(downloaded)
+    }
+}
+
+#[uniffi::export]
+impl Downloader {
+    /// ダウンローダーを作成します
+    ///
+    /// # Arguments
+    /// * `max_bytes_per_second` - 1ダウンロードあたりの帯域の上限（`None`の場合は無制限）
+    /// * `retry_policy` - 通信エラーや再試行対象のステータスで失敗した場合の再試行ポリシー
+    ///   （`None`の場合は再試行しない）
+    #[uniffi::constructor(default(max_bytes_per_second = None, retry_policy = None))]
+    pub fn new(
+        max_bytes_per_second: Option<u64>,
+        retry_policy: Option<RetryPolicy>,
+    ) -> Arc<Self> {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(NETWORK_TIMEOUT)
+            .timeout_read(NETWORK_TIMEOUT)
+            .build();
+        Arc::new(Self {
+            agent,
+            max_bytes_per_second: AtomicU64::new(max_bytes_per_second.unwrap_or(0)),
+            retry_policy,
+        })
+    }
+
+    /// 帯域の上限を変更します（実行中のダウンロードにも適用されます）
+    ///
+    /// # Arguments
+    /// * `max_bytes_per_second` - 1ダウンロードあたりの帯域の上限（`None`の場合は無制限）
+    pub fn set_bandwidth_limit(&self, max_bytes_per_second: Option<u64>) {
+        self.max_bytes_per_second
+            .store(max_bytes_per_second.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// ダウンロードをバックグラウンドで開始します
+    ///
+    /// 保存先に`<destination>.part`が残っている場合は続きから再開します。
+    /// 結果はオブザーバーの`on_completed`または`on_failed`のどちらか一方に1回だけ通知されます。
+    ///
+    /// # Arguments
+    /// * `url` - ダウンロードするHTTP(S)のURL
+    /// * `destination` - 保存先のファイルのパス（既存のファイルは完了時に置き換えられます）
+    /// * `observer` - 進捗・完了・失敗を受け取るオブザーバー
+    ///
+    /// # Errors
+    /// * `DownloadError::IoError` - ダウンロード用のスレッドを開始できない場合
+    pub fn start(
+        self: Arc<Self>,
+        url: String,
+        destination: String,
+        observer: Arc<dyn DownloadObserver>,
+    ) -> Result<Arc<DownloadTask>, DownloadError> {
+        let task = Arc::new(DownloadTask {
+            cancelled: AtomicBool::new(false),
+            result: Mutex::new(None),
+            finished: Condvar::new(),
+        });
+        let running = Arc::clone(&task);
+        thread::Builder::new()
+            .name("mobile-download".to_string())
+            .spawn(move || {
+                let destination = PathBuf::from(&destination);
+                let result = self.run_with_retry(&running, &url, &destination, observer.as_ref());
+                match &result {
+                    Ok(total) => {
+                        observer.on_completed(destination.to_string_lossy().into_owned(), *total)
+                    }
+                    Err(e) => observer.on_failed(e.clone()),
+                }
+                running.finish(result);
+            })?;
+        Ok(task)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// 記録したオブザーバーへの通知
+    #[derive(Default)]
+    struct RecordingObserver {
+        progress: Mutex<Vec<(u64, Option<u64>)>>,
+        completed: Mutex<Option<(String, u64)>>,
+        failed: Mutex<Option<String>>,
+    }
+
+    impl DownloadObserver for RecordingObserver {
+        fn on_progress(&self, downloaded_bytes: u64, total_bytes: Option<u64>) {
+            self.progress.lock().unwrap().push((downloaded_bytes, total_bytes));
+        }
+
+        fn on_completed(&self, path: String, total_bytes: u64) {
+            *self.completed.lock().unwrap() = Some((path, total_bytes));
+        }
+
+        fn on_failed(&self, error: DownloadError) {
+            *self.failed.lock().unwrap() = Some(error.to_string());
+        }
+    }
+
+    fn body() -> Vec<u8> {
+        (0..40_000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// `Range`に対応したHTTPサーバーを起動し、URLと受信したリクエストヘッダーを返します
+    fn serve(body: Vec<u8>, connections: usize) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let Ok(mut stream) = stream else { continue };
+                let mut request = Vec::new();
+                let mut byte = [0u8; 1];
+                while !request.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+                    request.push(byte[0]);
+                }
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let start = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("range: bytes="))
+                    .and_then(|range| range.trim_end_matches('-').parse::<usize>().ok());
+                recorded.lock().unwrap().push(request);
+                let response = match start {
+                    Some(start) => {
+                        let mut head = format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\n\
+                             Content-Range: bytes {}-{}/{}\r\nConnection: close\r\n\r\n",
+                            body.len() - start,
+                            start,
+                            body.len() - 1,
+                            body.len()
+                        )
+                        .into_bytes();
+                        head.extend_from_slice(&body[start..]);
+                        head
+                    }
+                    None => {
+                        let mut head = format!(
+                            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nETag: \"v1\"\r\n\
+                             Connection: close\r\n\r\n",
+                            body.len()
+                        )
+                        .into_bytes();
+                        head.extend_from_slice(&body);
+                        head
+                    }
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+        (format!("http://{}/file.bin", addr), requests)
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_download_{}_{}.bin", name, std::process::id()));
+        for suffix in ["", ".part", ".part.validator"] {
+            let _ = fs::remove_file(sibling_path(&path, suffix));
+        }
+        path
+    }
+
+    #[test]
+    fn test_download_completes() {
+        let (url, _) = serve(body(), 1);
+        let destination = temp_path("complete");
+        let observer = Arc::new(RecordingObserver::default());
+        let task = Downloader::new(None, None)
+            .start(url, destination.to_string_lossy().into_owned(), observer.clone())
+            .unwrap();
+        assert_eq!(pollster::block_on(task.clone().wait()).unwrap(), 40_000);
+        assert!(task.is_finished());
+        assert_eq!(fs::read(&destination).unwrap(), body());
+        assert!(!sibling_path(&destination, ".part").exists());
+        let progress = observer.progress.lock().unwrap();
+        assert_eq!(progress.last(), Some(&(40_000, Some(40_000))));
+        let completed = observer.completed.lock().unwrap().clone().unwrap();
+        assert_eq!(completed.1, 40_000);
+        let _ = fs::remove_file(destination);
+    }
+
+    #[test]
+    fn test_download_resumes_partial_file() {
+        let (url, requests) = serve(body(), 1);
+        let destination = temp_path("resume");
+        fs::write(sibling_path(&destination, ".part"), &body()[..10_000]).unwrap();
+        fs::write(sibling_path(&destination, ".part.validator"), "\"v1\"").unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        let task = Downloader::new(None, None)
+            .start(url, destination.to_string_lossy().into_owned(), observer.clone())
+            .unwrap();
+        assert_eq!(pollster::block_on(task.wait()).unwrap(), 40_000);
+        assert_eq!(fs::read(&destination).unwrap(), body());
+        let request = requests.lock().unwrap()[0].clone();
+        assert!(request.contains("range: bytes=10000-"));
+        assert!(request.contains("if-range: \"v1\""));
+        assert_eq!(observer.progress.lock().unwrap()[0].0, 10_000);
+        let _ = fs::remove_file(destination);
+    }
+
+    #[test]
+    fn test_download_cancel_keeps_partial_file() {
+        let (url, _) = serve(body(), 1);
+        let destination = temp_path("cancel");
+        let observer = Arc::new(RecordingObserver::default());
+        let task = Downloader::new(Some(4_000), None)
+            .start(url, destination.to_string_lossy().into_owned(), observer.clone())
+            .unwrap();
+        thread::sleep(Duration::from_millis(300));
+        task.cancel();
+        match pollster::block_on(task.wait()) {
+            Err(DownloadError::Cancelled) => (),
+            other => panic!("Expected Cancelled error, got {:?}", other),
+        }
+        assert!(!destination.exists());
+        let part = fs::metadata(sibling_path(&destination, ".part")).unwrap().len();
+        assert!(part > 0 && part < 40_000);
+        assert!(observer.failed.lock().unwrap().is_some());
+        assert!(observer.completed.lock().unwrap().is_none());
+        for suffix in [".part", ".part.validator"] {
+            let _ = fs::remove_file(sibling_path(&destination, suffix));
+        }
+    }
+
+    #[test]
+    fn test_download_retries_with_policy() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            for (index, stream) in listener.incoming().take(2).enumerate() {
+                let Ok(mut stream) = stream else { continue };
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let response = if index == 0 {
+                    b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\n\
+                      Connection: close\r\n\r\n"
+                        .to_vec()
+                } else {
+                    let mut head = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        body().len()
+                    )
+                    .into_bytes();
+                    head.extend_from_slice(&body());
+                    head
+                };
+                let _ = stream.write_all(&response);
+            }
+        });
+        let url = format!("http://{}/file.bin", addr);
+        let policy = RetryPolicy { base_delay_ms: 10, jitter: 0.0, ..Default::default() };
+        let destination = temp_path("retry");
+        let observer = Arc::new(RecordingObserver::default());
+        let task = Downloader::new(None, Some(policy))
+            .start(url.clone(), destination.to_string_lossy().into_owned(), observer.clone())
+            .unwrap();
+        assert_eq!(pollster::block_on(task.wait()).unwrap(), 40_000);
+        assert_eq!(fs::read(&destination).unwrap(), body());
+        assert!(observer.failed.lock().unwrap().is_none());
+        let _ = fs::remove_file(&destination);
+
+        // ポリシーがない場合は再試行しない
+        let task = Downloader::new(None, None)
+            .start(url, destination.to_string_lossy().into_owned(), observer.clone())
+            .unwrap();
+        match pollster::block_on(task.wait()) {
+            Err(DownloadError::Network(_)) => (),
+            other => panic!("Expected Network error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_download_errors() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                );
+            }
+        });
+        let downloader = Downloader::new(None, None);
+        let destination = temp_path("errors").to_string_lossy().into_owned();
+        let observer = Arc::new(RecordingObserver::default());
+        let task = downloader
+            .clone()
+            .start(format!("http://{}/missing", addr), destination.clone(), observer.clone())
+            .unwrap();
+        match pollster::block_on(task.wait()) {
+            Err(DownloadError::HttpStatus(404)) => (),
+            other => panic!("Expected HttpStatus error, got {:?}", other),
+        }
+        let task = downloader
+            .start("ftp://example.com/file".to_string(), destination, observer)
+            .unwrap();
+        match pollster::block_on(task.wait()) {
+            Err(DownloadError::InvalidUrl(_)) => (),
+            other => panic!("Expected InvalidUrl error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 100-199/200"), Some((100, Some(200))));
+        assert_eq!(parse_content_range("bytes 0-9/*"), Some((0, None)));
+        assert_eq!(parse_content_range("items 0-9/10"), None);
+    }
+}
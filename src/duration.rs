@@ -0,0 +1,400 @@
+//! 時間の長さの解析・表示モジュール
+//!
+//! このモジュールは、「1h30m」「PT90M」「90:00」のような時間の長さの文字列を秒数に変換する
+//! `parse_duration`と、秒数を「1 hour, 30 minutes」「1時間30分」のような
+//! 読みやすい表記に変換する`humanize_duration`をエクスポートします。
+
+use thiserror::Error;
+
+/// 単位ごとの秒数（日・時・分・秒）
+const UNIT_SECONDS: [f64; 4] = [86_400.0, 3_600.0, 60.0, 1.0];
+
+/// 扱える最大の秒数（約3万年。f64で1ミリ秒の精度を保てる範囲）
+const MAX_SECONDS: f64 = 1e12;
+
+/// 時間の長さの処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum DurationError {
+    /// 形式が正しくない場合
+    #[error("Invalid duration: {0}")]
+    InvalidFormat(String),
+    /// 値が大きすぎる、または負の場合
+    #[error("Duration out of range: {0}")]
+    OutOfRange(String),
+}
+
+/// 言語ごとの時間の長さの表記
+///
+/// 単位の表記は`[単数, 複数]`の順で、`{n}`が数値に置換されます。
+struct DurationTemplates {
+    /// 言語コード（ISO 639-1）
+    language: &'static str,
+    /// 日・時・分・秒
+    units: [[&'static str; 2]; 4],
+    /// 単位の区切り
+    separator: &'static str,
+    /// 最後の単位の前の区切り
+    last_separator: &'static str,
+}
+
+/// 対応言語の表記（先頭がフォールバック言語）
+const DURATION_TEMPLATES: &[DurationTemplates] = &[
+    DurationTemplates {
+        language: "en",
+        units: [
+            ["{n} day", "{n} days"],
+            ["{n} hour", "{n} hours"],
+            ["{n} minute", "{n} minutes"],
+            ["{n} second", "{n} seconds"],
+        ],
+        separator: ", ",
+        last_separator: ", ",
+    },
+    DurationTemplates {
+        language: "ja",
+        units: [["{n}日", "{n}日"], ["{n}時間", "{n}時間"], ["{n}分", "{n}分"], ["{n}秒", "{n}秒"]],
+        separator: "",
+        last_separator: "",
+    },
+    DurationTemplates {
+        language: "es",
+        units: [
+            ["{n} día", "{n} días"],
+            ["{n} hora", "{n} horas"],
+            ["{n} minuto", "{n} minutos"],
+            ["{n} segundo", "{n} segundos"],
+        ],
+        separator: ", ",
+        last_separator: " y ",
+    },
+    DurationTemplates {
+        language: "fr",
+        units: [
+            ["{n} jour", "{n} jours"],
+            ["{n} heure", "{n} heures"],
+            ["{n} minute", "{n} minutes"],
+            ["{n} seconde", "{n} secondes"],
+        ],
+        separator: ", ",
+        last_separator: " et ",
+    },
+    DurationTemplates {
+        language: "de",
+        units: [
+            ["{n} Tag", "{n} Tage"],
+            ["{n} Stunde", "{n} Stunden"],
+            ["{n} Minute", "{n} Minuten"],
+            ["{n} Sekunde", "{n} Sekunden"],
+        ],
+        separator: ", ",
+        last_separator: " und ",
+    },
+    DurationTemplates {
+        language: "zh",
+        units: [["{n}天", "{n}天"], ["{n}小时", "{n}小时"], ["{n}分钟", "{n}分钟"], ["{n}秒", "{n}秒"]],
+        separator: "",
+        last_separator: "",
+    },
+];
+
+/// ロケール識別子から表記を選択します（未対応の場合は英語）
+fn resolve_templates(locale: &str) -> &'static DurationTemplates {
+    let language = locale
+        .trim()
+        .split(['-', '_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    DURATION_TEMPLATES
+        .iter()
+        .find(|templates| templates.language == language)
+        .unwrap_or(&DURATION_TEMPLATES[0])
+}
+
+/// 数値（小数を含む）を解析します
+fn parse_number(text: &str, original: &str) -> Result<f64, DurationError> {
+    let valid = !text.is_empty()
+        && !text.starts_with('.')
+        && !text.ends_with('.')
+        && text.bytes().all(|c| c.is_ascii_digit() || c == b'.')
+        && text.bytes().filter(|c| *c == b'.').count() <= 1;
+    if !valid {
+        return Err(DurationError::InvalidFormat(original.to_string()));
+    }
+    text.parse().map_err(|_| DurationError::InvalidFormat(original.to_string()))
+}
+
+/// ISO 8601の期間（`PT1H30M`・`P1DT12H`・`P2W`）を解析します
+///
+/// 長さが一定でない年（`Y`）と月（`M`、日付部分）は扱いません。
+fn parse_iso8601(text: &str) -> Result<f64, DurationError> {
+    let invalid = || DurationError::InvalidFormat(text.to_string());
+    let body = &text[1..];
+    let (date, time) = match body.split_once(['T', 't']) {
+        Some((_, "")) => return Err(invalid()),
+        Some((date, time)) => (date, Some(time)),
+        None => (body, None),
+    };
+    if date.is_empty() && time.is_none() {
+        return Err(invalid());
+    }
+    let mut total = 0.0;
+    for (part, units) in [(date, "WD"), (time.unwrap_or_default(), "HMS")] {
+        let mut rest = part;
+        let mut last_unit = None;
+        while !rest.is_empty() {
+            let end = rest.find(|c: char| c.is_ascii_alphabetic()).ok_or_else(invalid)?;
+            let unit = rest.as_bytes()[end].to_ascii_uppercase();
+            if unit == b'Y' || (unit == b'M' && units == "WD") {
+                return Err(DurationError::InvalidFormat(format!(
+                    "years and months have no fixed length: {}",
+                    text
+                )));
+            }
+            // 単位は大きい順に1回ずつ
+            let index = units.bytes().position(|u| u == unit).ok_or_else(invalid)?;
+            if last_unit.is_some_and(|last| index <= last) {
+                return Err(invalid());
+            }
+            last_unit = Some(index);
+            let value = parse_number(&rest[..end].replace(',', "."), text)?;
+            total += value
+                * match unit {
+                    b'W' => 7.0 * UNIT_SECONDS[0],
+                    b'D' => UNIT_SECONDS[0],
+                    b'H' => UNIT_SECONDS[1],
+                    b'M' => UNIT_SECONDS[2],
+                    _ => UNIT_SECONDS[3],
+                };
+            rest = &rest[end + 1..];
+        }
+    }
+    Ok(total)
+}
+
+/// 時計形式（`mm:ss`・`h:mm:ss`、秒は小数可）を解析します
+fn parse_clock(text: &str) -> Result<f64, DurationError> {
+    let invalid = || DurationError::InvalidFormat(text.to_string());
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 {
+        return Err(invalid());
+    }
+    let mut total = 0.0;
+    for (index, part) in parts.iter().enumerate() {
+        let last = index == parts.len() - 1;
+        if index > 0 {
+            // 先頭以外は2桁（秒は小数部を許可）
+            let integer = part.split('.').next().unwrap_or_default();
+            if integer.len() != 2 {
+                return Err(invalid());
+            }
+        }
+        if !last && part.contains('.') {
+            return Err(invalid());
+        }
+        let value = parse_number(part, text)?;
+        if index > 0 && value >= 60.0 {
+            return Err(DurationError::OutOfRange(text.to_string()));
+        }
+        total = total * 60.0 + value;
+    }
+    Ok(total)
+}
+
+/// 単位付きの表記（`1h30m`・`2d 4h`・`1.5h`・`250ms`）を解析します
+fn parse_units(text: &str) -> Result<f64, DurationError> {
+    let invalid = || DurationError::InvalidFormat(text.to_string());
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    let mut rest = compact.as_str();
+    let mut seen = Vec::new();
+    let mut total = 0.0;
+    while !rest.is_empty() {
+        let number_end =
+            rest.find(|c: char| !c.is_ascii_digit() && c != '.').ok_or_else(invalid)?;
+        let value = parse_number(&rest[..number_end], text)?;
+        rest = &rest[number_end..];
+        let unit_end = rest.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rest.len());
+        let unit = rest[..unit_end].to_ascii_lowercase();
+        let seconds = match unit.as_str() {
+            "w" => 7.0 * UNIT_SECONDS[0],
+            "d" => UNIT_SECONDS[0],
+            "h" => UNIT_SECONDS[1],
+            "m" => UNIT_SECONDS[2],
+            "s" => UNIT_SECONDS[3],
+            "ms" => 0.001,
+            _ => return Err(invalid()),
+        };
+        if seen.contains(&unit) {
+            return Err(invalid());
+        }
+        seen.push(unit);
+        total += value * seconds;
+        rest = &rest[unit_end..];
+    }
+    Ok(total)
+}
+
+/// 時間の長さの文字列を秒数に変換します
+///
+/// 次の3つの形式を受け付けます。
+/// * 単位付き: `1h30m`・`2d 4h`・`1.5h`・`45s`・`250ms`（単位は`w`・`d`・`h`・`m`・`s`・`ms`、
+///   大文字・小文字を区別せず、同じ単位は1回まで）
+/// * ISO 8601: `PT90M`・`P1DT2H`・`P2W`（年`Y`と月`M`は長さが一定でないためエラー）
+/// * 時計形式: `90:00`（分:秒）・`1:30:00`（時:分:秒）・`0:05.5`
+///
+/// # Arguments
+/// * `text` - 時間の長さの文字列
+///
+/// # Returns
+/// 秒数
+///
+/// # Errors
+/// * `DurationError::InvalidFormat` - 形式が正しくない場合
+/// * `DurationError::OutOfRange` - 時計形式の分・秒が60以上の場合、または値が大きすぎる場合
+///
+/// # Example
+/// ```
+/// assert_eq!(parse_duration("1h30m".to_string())?, 5400.0);
+/// assert_eq!(parse_duration("PT90M".to_string())?, 5400.0);
+/// assert_eq!(parse_duration("90:00".to_string())?, 5400.0);
+/// ```
+#[uniffi::export]
+pub fn parse_duration(text: String) -> Result<f64, DurationError> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        return Err(DurationError::InvalidFormat(text));
+    }
+    let seconds = if trimmed.starts_with(['P', 'p']) {
+        parse_iso8601(trimmed)?
+    } else if trimmed.contains(':') {
+        parse_clock(trimmed)?
+    } else {
+        parse_units(trimmed)?
+    };
+    if !seconds.is_finite() || seconds > MAX_SECONDS {
+        return Err(DurationError::OutOfRange(text));
+    }
+    Ok(seconds)
+}
+
+/// 秒数を読みやすい表記に変換します
+///
+/// 日・時間・分・秒のうち、0でない最大の単位から`precision`個の単位までを表示し、
+/// 最後の単位で四捨五入します（0になった単位は省略）。負の値は絶対値で表示します。
+///
+/// 対応言語は英語・日本語・スペイン語・フランス語・ドイツ語・中国語です。
+/// ロケールは言語部分（`ja-JP`なら`ja`）で照合され、未対応の場合は英語にフォールバックします。
+///
+/// # Arguments
+/// * `seconds` - 秒数
+/// * `locale` - ロケール識別子（例: `"ja"`, `"en-US"`）
+/// * `precision` - 表示する単位の最大数（1〜4、省略時は2）
+///
+/// # Example
+/// ```
+/// assert_eq!(humanize_duration(5400.0, "en".to_string(), None), "1 hour, 30 minutes");
+/// assert_eq!(humanize_duration(5400.0, "ja".to_string(), None), "1時間30分");
+/// assert_eq!(humanize_duration(5400.0, "en".to_string(), Some(1)), "2 hours");
+/// ```
+#[uniffi::export(default(precision = None))]
+pub fn humanize_duration(seconds: f64, locale: String, precision: Option<u8>) -> String {
+    let templates = resolve_templates(&locale);
+    let precision = usize::from(precision.unwrap_or(2).clamp(1, 4));
+    let seconds = if seconds.is_finite() { seconds.abs().min(MAX_SECONDS) } else { 0.0 };
+
+    // 最大の単位を決め、表示する最小の単位で丸める
+    let first = UNIT_SECONDS.iter().position(|unit| seconds >= *unit).unwrap_or(3);
+    let last = (first + precision - 1).min(3);
+    let mut remaining = (seconds / UNIT_SECONDS[last]).round() as u64;
+    let mut counts = [0u64; 4];
+    for index in (0..=last).rev() {
+        if index == 0 {
+            counts[0] = remaining;
+        } else {
+            let per_larger = (UNIT_SECONDS[index - 1] / UNIT_SECONDS[index]) as u64;
+            counts[index] = remaining % per_larger;
+            remaining /= per_larger;
+        }
+    }
+
+    let parts: Vec<String> = counts
+        .iter()
+        .enumerate()
+        .filter(|(_, count)| **count > 0)
+        .map(|(index, count)| {
+            templates.units[index][usize::from(*count != 1)].replace("{n}", &count.to_string())
+        })
+        .collect();
+    match parts.as_slice() {
+        [] => templates.units[last][1].replace("{n}", "0"),
+        [only] => only.clone(),
+        [init @ .., tail] => {
+            format!("{}{}{}", init.join(templates.separator), templates.last_separator, tail)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> f64 {
+        parse_duration(text.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_duration_formats() {
+        assert_eq!(parse("1h30m"), 5400.0);
+        assert_eq!(parse(" 1H 30M 15s "), 5415.0);
+        assert_eq!(parse("1.5h"), 5400.0);
+        assert_eq!(parse("2d4h"), 187_200.0);
+        assert_eq!(parse("1w"), 604_800.0);
+        assert_eq!(parse("250ms"), 0.25);
+        assert_eq!(parse("PT90M"), 5400.0);
+        assert_eq!(parse("P1DT2H"), 93_600.0);
+        assert_eq!(parse("P2W"), 1_209_600.0);
+        assert_eq!(parse("PT0.5S"), 0.5);
+        assert_eq!(parse("pt1h30m"), 5400.0);
+        assert_eq!(parse("90:00"), 5400.0);
+        assert_eq!(parse("1:30:00"), 5400.0);
+        assert_eq!(parse("0:05.5"), 5.5);
+    }
+
+    #[test]
+    fn test_parse_duration_errors() {
+        for text in [
+            "", "90", "1x", "1h1h", "h", "1..5h", "P", "PT", "P1Y", "P1M", "PT1S1H", "1:2",
+            "1:30:00:00", "1.5:00",
+        ] {
+            match parse_duration(text.to_string()) {
+                Err(DurationError::InvalidFormat(_)) => (),
+                other => panic!("Expected InvalidFormat error for {:?}, got {:?}", text, other),
+            }
+        }
+        match parse_duration("1:60".to_string()) {
+            Err(DurationError::OutOfRange(_)) => (),
+            other => panic!("Expected OutOfRange error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_humanize_duration() {
+        let humanize = |seconds: f64, locale: &str, precision: Option<u8>| {
+            humanize_duration(seconds, locale.to_string(), precision)
+        };
+        assert_eq!(humanize(5400.0, "en", None), "1 hour, 30 minutes");
+        assert_eq!(humanize(5415.0, "en", Some(3)), "1 hour, 30 minutes, 15 seconds");
+        assert_eq!(humanize(5400.0, "en", Some(1)), "2 hours");
+        assert_eq!(humanize(86_399.0, "en", Some(1)), "1 day");
+        assert_eq!(humanize(3605.0, "en", None), "1 hour");
+        assert_eq!(humanize(90_061.0, "en-GB", Some(4)), "1 day, 1 hour, 1 minute, 1 second");
+        assert_eq!(humanize(0.0, "en", None), "0 seconds");
+        assert_eq!(humanize(-45.0, "en", None), "45 seconds");
+        assert_eq!(humanize(5400.0, "ja", None), "1時間30分");
+        assert_eq!(humanize(5400.0, "es", None), "1 hora y 30 minutos");
+        assert_eq!(humanize(90_000.0, "de", None), "1 Tag und 1 Stunde");
+        assert_eq!(humanize(172_800.0, "fr", None), "2 jours");
+        assert_eq!(humanize(3690.0, "zh-CN", Some(3)), "1小时1分钟30秒");
+    }
+}
@@ -0,0 +1,461 @@
+//! バイナリ・テキスト変換モジュール
+//!
+//! このモジュールは、バイト列とテキスト表現を相互に変換する関数をエクスポートします。
+//! Base64は標準・URLセーフの各アルファベットについて、パディングの有無を選択できます。
+//! 16進数は指紋や鍵の受け渡し向けに、空白を含む入力も受け付けます。
+//! Base32（RFC 4648）はTOTPの共有秘密、Base58（Bitcoinのアルファベット）は
+//! ウォレットアドレスの表示に使用します。
+//! URLのパーセントエンコーディングとクエリ文字列の組み立ても提供します。
+
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::engine::GeneralPurpose;
+use base64::Engine;
+use data_encoding::{BASE32, BASE32_NOPAD};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use thiserror::Error;
+
+/// RFC 3986の非予約文字（英数字と`-`・`.`・`_`・`~`）以外をすべてエンコードする文字集合
+const COMPONENT_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// エンコード・デコードで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum EncodingError {
+    /// Base64として不正な文字列の場合
+    #[error("Invalid base64: {0}")]
+    InvalidBase64(String),
+    /// 16進数として不正な文字列の場合
+    #[error("Invalid hex: {0}")]
+    InvalidHex(String),
+    /// Base32として不正な文字列の場合
+    #[error("Invalid base32: {0}")]
+    InvalidBase32(String),
+    /// Base58として不正な文字列の場合
+    #[error("Invalid base58: {0}")]
+    InvalidBase58(String),
+    /// `%`の後に2桁の16進数が続かない場合
+    #[error("Invalid percent-encoding at byte {0}")]
+    InvalidPercentEncoding(u64),
+    /// デコード結果がUTF-8として不正な場合
+    #[error("Decoded text is not valid UTF-8")]
+    InvalidUtf8,
+}
+
+/// クエリ文字列のパラメータ
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct QueryParam {
+    /// パラメータ名
+    pub name: String,
+    /// 値
+    pub value: String,
+}
+
+/// Base64の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum Base64Variant {
+    /// 標準アルファベット（`+`・`/`）、パディングあり
+    Standard,
+    /// 標準アルファベット（`+`・`/`）、パディングなし
+    StandardNoPad,
+    /// URLセーフなアルファベット（`-`・`_`）、パディングあり
+    UrlSafe,
+    /// URLセーフなアルファベット（`-`・`_`）、パディングなし（JWTなどで使用）
+    UrlSafeNoPad,
+}
+
+impl Base64Variant {
+    /// 対応するbase64クレートのエンジンを返します
+    fn engine(self) -> &'static GeneralPurpose {
+        match self {
+            Self::Standard => &STANDARD,
+            Self::StandardNoPad => &STANDARD_NO_PAD,
+            Self::UrlSafe => &URL_SAFE,
+            Self::UrlSafeNoPad => &URL_SAFE_NO_PAD,
+        }
+    }
+}
+
+/// バイト列をBase64文字列にエンコードします
+///
+/// # Arguments
+/// * `data` - エンコードするデータ
+/// * `variant` - Base64の種類
+///
+/// # Example
+/// ```
+/// assert_eq!(base64_encode(vec![0xfb, 0xff], Base64Variant::Standard), "+/8=");
+/// assert_eq!(base64_encode(vec![0xfb, 0xff], Base64Variant::UrlSafeNoPad), "-_8");
+/// ```
+#[uniffi::export]
+pub fn base64_encode(data: Vec<u8>, variant: Base64Variant) -> String {
+    variant.engine().encode(data)
+}
+
+/// Base64文字列をバイト列にデコードします
+///
+/// パディングの有無は`variant`の指定と一致している必要があります。
+///
+/// # Arguments
+/// * `text` - デコードするBase64文字列
+/// * `variant` - Base64の種類
+///
+/// # Errors
+/// * `EncodingError::InvalidBase64` - 不正な文字、長さ、パディングを含む場合
+#[uniffi::export]
+pub fn base64_decode(text: String, variant: Base64Variant) -> Result<Vec<u8>, EncodingError> {
+    variant
+        .engine()
+        .decode(text)
+        .map_err(|e| EncodingError::InvalidBase64(e.to_string()))
+}
+
+/// バイト列を16進数文字列にエンコードします
+///
+/// # Arguments
+/// * `bytes` - エンコードするデータ
+/// * `uppercase` - `true`の場合は大文字（`A`〜`F`）を使用
+///
+/// # Example
+/// ```
+/// assert_eq!(hex_encode(vec![0xde, 0xad], false), "dead");
+/// assert_eq!(hex_encode(vec![0xde, 0xad], true), "DEAD");
+/// ```
+#[uniffi::export(default(uppercase = false))]
+pub fn hex_encode(bytes: Vec<u8>, uppercase: bool) -> String {
+    if uppercase {
+        hex::encode_upper(bytes)
+    } else {
+        hex::encode(bytes)
+    }
+}
+
+/// 16進数文字列をバイト列にデコードします
+///
+/// 大文字・小文字のどちらも受け付け、空白・タブ・改行は無視します
+/// （`"DE AD BE EF"`のような区切りや、複数行にわたる指紋をそのまま渡せます）。
+///
+/// # Arguments
+/// * `text` - デコードする16進数文字列
+///
+/// # Errors
+/// * `EncodingError::InvalidHex` - 16進数以外の文字を含む、または桁数が奇数の場合
+#[uniffi::export]
+pub fn hex_decode(text: String) -> Result<Vec<u8>, EncodingError> {
+    let digits: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    hex::decode(digits).map_err(|e| EncodingError::InvalidHex(e.to_string()))
+}
+
+/// バイト列をBase32文字列（RFC 4648、大文字）にエンコードします
+///
+/// # Arguments
+/// * `data` - エンコードするデータ
+/// * `padding` - `true`の場合は長さが8の倍数になるよう`=`を付加
+///
+/// # Example
+/// ```
+/// assert_eq!(base32_encode(b"foo".to_vec(), true), "MZXW6===");
+/// assert_eq!(base32_encode(b"foo".to_vec(), false), "MZXW6");
+/// ```
+#[uniffi::export(default(padding = true))]
+pub fn base32_encode(data: Vec<u8>, padding: bool) -> String {
+    if padding {
+        BASE32.encode(&data)
+    } else {
+        BASE32_NOPAD.encode(&data)
+    }
+}
+
+/// Base32文字列（RFC 4648）をバイト列にデコードします
+///
+/// 認証アプリの設定画面などで手入力された共有秘密を扱えるよう、
+/// 大文字・小文字を区別せず、空白・ハイフン・末尾のパディングの有無も問いません
+/// （`"jbsw y3dp-ehpk 3pxp"`を受け付けます）。
+///
+/// # Arguments
+/// * `text` - デコードするBase32文字列
+///
+/// # Errors
+/// * `EncodingError::InvalidBase32` - アルファベット以外の文字を含む、または長さが不正な場合
+#[uniffi::export]
+pub fn base32_decode(text: String) -> Result<Vec<u8>, EncodingError> {
+    let normalized: String = text
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    BASE32_NOPAD
+        .decode(normalized.trim_end_matches('=').as_bytes())
+        .map_err(|e| EncodingError::InvalidBase32(e.to_string()))
+}
+
+/// バイト列をBase58文字列（Bitcoinのアルファベット）にエンコードします
+///
+/// 先頭のゼロバイトは`1`として表されます。チェックサム（Base58Check）は付加しません。
+///
+/// # Arguments
+/// * `data` - エンコードするデータ
+///
+/// # Example
+/// ```
+/// assert_eq!(base58_encode(b"hello world".to_vec()), "StV1DL6CwTryKyV");
+/// assert_eq!(base58_encode(vec![0, 0, 1]), "112");
+/// ```
+#[uniffi::export]
+pub fn base58_encode(data: Vec<u8>) -> String {
+    bs58::encode(data).into_string()
+}
+
+/// Base58文字列（Bitcoinのアルファベット）をバイト列にデコードします
+///
+/// 紛らわしい`0`・`O`・`I`・`l`はアルファベットに含まれないため、エラーになります。
+///
+/// # Arguments
+/// * `text` - デコードするBase58文字列（前後の空白は無視されます）
+///
+/// # Errors
+/// * `EncodingError::InvalidBase58` - アルファベット以外の文字を含む場合
+#[uniffi::export]
+pub fn base58_decode(text: String) -> Result<Vec<u8>, EncodingError> {
+    bs58::decode(text.trim())
+        .into_vec()
+        .map_err(|e| EncodingError::InvalidBase58(e.to_string()))
+}
+
+/// URLの構成要素（パスのセグメントやクエリの値）をパーセントエンコードします
+///
+/// RFC 3986の非予約文字（`A-Z a-z 0-9 - . _ ~`）以外はすべてUTF-8のバイト単位で
+/// `%XX`にエンコードされます。`/`・`?`・`&`・`=`などの予約文字もエンコードされるため、
+/// 値の中に含まれていても区切り文字と誤認されません。空白は`%20`になります。
+///
+/// # Arguments
+/// * `text` - エンコードする文字列
+///
+/// # Example
+/// ```
+/// assert_eq!(url_encode_component("a b&c/d".to_string()), "a%20b%26c%2Fd");
+/// assert_eq!(url_encode_component("日本".to_string()), "%E6%97%A5%E6%9C%AC");
+/// ```
+#[uniffi::export]
+pub fn url_encode_component(text: String) -> String {
+    utf8_percent_encode(&text, COMPONENT_ENCODE_SET).to_string()
+}
+
+/// パーセントエンコードされたURLの構成要素をデコードします
+///
+/// `+`は空白に変換しません（`application/x-www-form-urlencoded`ではなく
+/// RFC 3986の規則に従います）。
+///
+/// # Arguments
+/// * `text` - デコードする文字列
+///
+/// # Errors
+/// * `EncodingError::InvalidPercentEncoding` - `%`の後に2桁の16進数が続かない場合
+/// * `EncodingError::InvalidUtf8` - デコード結果がUTF-8として不正な場合
+#[uniffi::export]
+pub fn url_decode_component(text: String) -> Result<String, EncodingError> {
+    let bytes = text.as_bytes();
+    for (pos, _) in text.match_indices('%') {
+        let valid = bytes
+            .get(pos + 1..pos + 3)
+            .is_some_and(|digits| digits.iter().all(u8::is_ascii_hexdigit));
+        if !valid {
+            return Err(EncodingError::InvalidPercentEncoding(pos as u64));
+        }
+    }
+    percent_decode_str(&text)
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| EncodingError::InvalidUtf8)
+}
+
+/// パラメータの一覧からクエリ文字列（先頭の`?`を含まない）を組み立てます
+///
+/// 名前と値はそれぞれ`url_encode_component`でエンコードされ、
+/// 指定された順序のまま`&`で連結されます。同じ名前のパラメータを複数指定できます。
+///
+/// # Arguments
+/// * `params` - クエリパラメータの一覧
+///
+/// # Example
+/// ```
+/// let query = build_query_string(vec![
+///     QueryParam { name: "q".to_string(), value: "rust & swift".to_string() },
+///     QueryParam { name: "page".to_string(), value: "2".to_string() },
+/// ]);
+/// assert_eq!(query, "q=rust%20%26%20swift&page=2");
+/// ```
+#[uniffi::export]
+pub fn build_query_string(params: Vec<QueryParam>) -> String {
+    params
+        .into_iter()
+        .map(|param| {
+            format!(
+                "{}={}",
+                url_encode_component(param.name),
+                url_encode_component(param.value)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_variants() {
+        let data = vec![0xfb, 0xff];
+        assert_eq!(base64_encode(data.clone(), Base64Variant::Standard), "+/8=");
+        assert_eq!(base64_encode(data.clone(), Base64Variant::StandardNoPad), "+/8");
+        assert_eq!(base64_encode(data.clone(), Base64Variant::UrlSafe), "-_8=");
+        assert_eq!(base64_encode(data, Base64Variant::UrlSafeNoPad), "-_8");
+    }
+
+    #[test]
+    fn test_base64_roundtrip() {
+        let data = b"any carnal pleasure.".to_vec();
+        for variant in [
+            Base64Variant::Standard,
+            Base64Variant::StandardNoPad,
+            Base64Variant::UrlSafe,
+            Base64Variant::UrlSafeNoPad,
+        ] {
+            let encoded = base64_encode(data.clone(), variant);
+            assert_eq!(base64_decode(encoded, variant).unwrap(), data);
+        }
+    }
+
+    #[test]
+    fn test_base64_decode_errors() {
+        match base64_decode("+/8".to_string(), Base64Variant::UrlSafeNoPad) {
+            Err(EncodingError::InvalidBase64(_)) => (),
+            _ => panic!("Expected InvalidBase64 error"),
+        }
+        match base64_decode("-_8".to_string(), Base64Variant::UrlSafe) {
+            Err(EncodingError::InvalidBase64(_)) => (),
+            _ => panic!("Expected InvalidBase64 error"),
+        }
+    }
+
+    #[test]
+    fn test_hex_encode() {
+        assert_eq!(hex_encode(vec![0x00, 0xab, 0xff], false), "00abff");
+        assert_eq!(hex_encode(vec![0x00, 0xab, 0xff], true), "00ABFF");
+        assert_eq!(hex_encode(Vec::new(), false), "");
+    }
+
+    #[test]
+    fn test_hex_decode_lenient_whitespace() {
+        assert_eq!(hex_decode("DEadBEef".to_string()).unwrap(), vec![0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(
+            hex_decode(" de ad\n\tbe ef \r\n".to_string()).unwrap(),
+            vec![0xde, 0xad, 0xbe, 0xef]
+        );
+    }
+
+    #[test]
+    fn test_hex_decode_errors() {
+        match hex_decode("abc".to_string()) {
+            Err(EncodingError::InvalidHex(_)) => (),
+            _ => panic!("Expected InvalidHex error"),
+        }
+        match hex_decode("zz".to_string()) {
+            Err(EncodingError::InvalidHex(_)) => (),
+            _ => panic!("Expected InvalidHex error"),
+        }
+    }
+
+    #[test]
+    fn test_base32_rfc4648_vectors() {
+        let vectors = [
+            ("", ""),
+            ("f", "MY======"),
+            ("fo", "MZXQ===="),
+            ("foo", "MZXW6==="),
+            ("foob", "MZXW6YQ="),
+            ("fooba", "MZXW6YTB"),
+            ("foobar", "MZXW6YTBOI======"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(base32_encode(plain.as_bytes().to_vec(), true), encoded);
+            assert_eq!(base32_decode(encoded.to_string()).unwrap(), plain.as_bytes());
+        }
+        assert_eq!(base32_encode(b"foobar".to_vec(), false), "MZXW6YTBOI");
+    }
+
+    #[test]
+    fn test_base32_decode_lenient() {
+        assert_eq!(
+            base32_decode("jbsw y3dp-ehpk 3pxp".to_string()).unwrap(),
+            b"Hello!\xde\xad\xbe\xef"
+        );
+        assert_eq!(base32_decode("MZXW6".to_string()).unwrap(), b"foo");
+        match base32_decode("MZXW1".to_string()) {
+            Err(EncodingError::InvalidBase32(_)) => (),
+            other => panic!("Expected InvalidBase32 error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_base58() {
+        assert_eq!(base58_encode(b"hello world".to_vec()), "StV1DL6CwTryKyV");
+        assert_eq!(base58_encode(vec![0, 0, 0x28, 0x7f, 0xb4, 0xcd]), "11233QC4");
+        assert_eq!(base58_encode(Vec::new()), "");
+        assert_eq!(
+            base58_decode(" 11233QC4 ".to_string()).unwrap(),
+            vec![0, 0, 0x28, 0x7f, 0xb4, 0xcd]
+        );
+        match base58_decode("0OIl".to_string()) {
+            Err(EncodingError::InvalidBase58(_)) => (),
+            other => panic!("Expected InvalidBase58 error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_url_encode_component() {
+        assert_eq!(url_encode_component("AZaz09-._~".to_string()), "AZaz09-._~");
+        assert_eq!(
+            url_encode_component("a b+c=d&e/f?g#h".to_string()),
+            "a%20b%2Bc%3Dd%26e%2Ff%3Fg%23h"
+        );
+        assert_eq!(url_encode_component("日本".to_string()), "%E6%97%A5%E6%9C%AC");
+    }
+
+    #[test]
+    fn test_url_decode_component() {
+        assert_eq!(url_decode_component("a%20b+c".to_string()).unwrap(), "a b+c");
+        assert_eq!(url_decode_component("%e6%97%a5%E6%9C%AC".to_string()).unwrap(), "日本");
+        match url_decode_component("100%".to_string()) {
+            Err(EncodingError::InvalidPercentEncoding(3)) => (),
+            _ => panic!("Expected InvalidPercentEncoding error"),
+        }
+        match url_decode_component("%zz".to_string()) {
+            Err(EncodingError::InvalidPercentEncoding(0)) => (),
+            _ => panic!("Expected InvalidPercentEncoding error"),
+        }
+        match url_decode_component("%ff".to_string()) {
+            Err(EncodingError::InvalidUtf8) => (),
+            _ => panic!("Expected InvalidUtf8 error"),
+        }
+    }
+
+    #[test]
+    fn test_build_query_string() {
+        let params = vec![
+            QueryParam { name: "q".to_string(), value: "rust & swift".to_string() },
+            QueryParam { name: "tag".to_string(), value: "a".to_string() },
+            QueryParam { name: "tag".to_string(), value: "b=c".to_string() },
+            QueryParam { name: "名前".to_string(), value: String::new() },
+        ];
+        assert_eq!(
+            build_query_string(params),
+            "q=rust%20%26%20swift&tag=a&tag=b%3Dc&%E5%90%8D%E5%89%8D="
+        );
+        assert_eq!(build_query_string(Vec::new()), "");
+    }
+}
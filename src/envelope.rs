@@ -0,0 +1,245 @@
+//! ハイブリッド暗号（デジタルエンベロープ）モジュール
+//!
+//! このモジュールは、受信者のX25519公開鍵に向けてデータを暗号化する`seal`と、
+//! 受信者の秘密鍵で復号する`open`をエクスポートします。
+//! 送信ごとに使い捨ての鍵ペアを生成してX25519で鍵共有を行い、
+//! HKDF-SHA256で導出した鍵とChaCha20-Poly1305で本文を暗号化します。
+//!
+//! # エンベロープ形式
+//! ```text
+//! バージョン(1) | 使い捨て公開鍵(32) | ノンス(12) | 暗号文（タグ16を含む）
+//! ```
+//! バージョンと使い捨て公開鍵は追加認証データとして認証されます。
+
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit, Nonce};
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::key_agreement::{x25519_derive_key, x25519_generate_keypair, KeyAgreementError};
+
+/// 現在のエンベロープ形式のバージョン
+const ENVELOPE_VERSION: u8 = 1;
+
+/// HKDFのコンテキスト情報
+const HKDF_INFO: &[u8] = b"mobile-envelope-v1";
+
+/// X25519の鍵の長さ
+const KEY_LEN: usize = 32;
+
+/// ChaCha20-Poly1305のノンスの長さ
+const NONCE_LEN: usize = 12;
+
+/// 認証タグの長さ
+const TAG_LEN: usize = 16;
+
+/// ヘッダー（バージョン＋使い捨て公開鍵＋ノンス）の長さ
+const HEADER_LEN: usize = 1 + KEY_LEN + NONCE_LEN;
+
+/// エンベロープの暗号化・復号で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum EnvelopeError {
+    /// 鍵の長さや値が不正な場合
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    /// 未対応のバージョンのエンベロープの場合
+    #[error("Unsupported envelope version: {0}")]
+    UnsupportedVersion(u8),
+    /// エンベロープが短すぎるなど、形式が不正な場合
+    #[error("Malformed envelope")]
+    MalformedEnvelope,
+    /// 復号に失敗した場合（鍵の誤りまたはデータの改ざん）
+    #[error("Decryption failed")]
+    DecryptionFailed,
+}
+
+/// 鍵共有のエラーを`EnvelopeError`に変換します
+fn map_key_error(error: KeyAgreementError) -> EnvelopeError {
+    match error {
+        KeyAgreementError::InvalidKey(message) => EnvelopeError::InvalidKey(message),
+        KeyAgreementError::WeakSharedSecret => {
+            EnvelopeError::InvalidKey("low-order public key".to_string())
+        }
+        KeyAgreementError::DerivationFailed(message) => EnvelopeError::InvalidKey(message),
+    }
+}
+
+/// 共有秘密からエンベロープ用の鍵を導出します
+///
+/// ソルトに両者の公開鍵を含めることで、鍵を特定のやり取りに結び付けます。
+fn derive_cipher(
+    private_key: Vec<u8>,
+    peer_public_key: Vec<u8>,
+    ephemeral_public: &[u8],
+    recipient_public: &[u8],
+) -> Result<ChaCha20Poly1305, KeyAgreementError> {
+    let salt = [ephemeral_public, recipient_public].concat();
+    let key = x25519_derive_key(private_key, peer_public_key, salt, HKDF_INFO.to_vec(), 32)?;
+    Ok(ChaCha20Poly1305::new(key.as_slice().into()))
+}
+
+/// 受信者の公開鍵に向けてデータを暗号化します
+///
+/// 呼び出しごとに使い捨ての鍵ペアとノンスを生成するため、同じ平文でも
+/// 毎回異なるエンベロープになります。
+///
+/// # Arguments
+/// * `recipient_public_key` - 受信者の32バイトのX25519公開鍵
+/// * `plaintext` - 暗号化するデータ
+///
+/// # Returns
+/// * バージョン付きのエンベロープ（平文より61バイト長い）
+///
+/// # Errors
+/// * `EnvelopeError::InvalidKey` - 公開鍵の長さが不正、または低位数の点の場合
+///
+/// # Example
+/// ```
+/// let sealed = seal(server_public_key, b"payload".to_vec())?;
+/// let opened = open(server_private_key, sealed)?;
+/// ```
+#[uniffi::export]
+pub fn seal(recipient_public_key: Vec<u8>, plaintext: Vec<u8>) -> Result<Vec<u8>, EnvelopeError> {
+    let ephemeral = x25519_generate_keypair();
+    let cipher = derive_cipher(
+        ephemeral.private_key,
+        recipient_public_key.clone(),
+        &ephemeral.public_key,
+        &recipient_public_key,
+    )
+    .map_err(map_key_error)?;
+
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let mut envelope = Vec::with_capacity(HEADER_LEN + plaintext.len() + TAG_LEN);
+    envelope.push(ENVELOPE_VERSION);
+    envelope.extend_from_slice(&ephemeral.public_key);
+    let payload = Payload {
+        msg: &plaintext,
+        aad: &envelope,
+    };
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), payload)
+        .map_err(|_| EnvelopeError::InvalidKey("encryption failed".to_string()))?;
+    envelope.extend_from_slice(&nonce);
+    envelope.extend_from_slice(&ciphertext);
+    Ok(envelope)
+}
+
+/// `seal`で作成したエンベロープを受信者の秘密鍵で復号します
+///
+/// # Arguments
+/// * `private_key` - 受信者の32バイトのX25519秘密鍵
+/// * `sealed` - `seal`が返したエンベロープ
+///
+/// # Errors
+/// * `EnvelopeError::InvalidKey` - 秘密鍵の長さが不正な場合
+/// * `EnvelopeError::UnsupportedVersion` - 未対応のバージョンの場合
+/// * `EnvelopeError::MalformedEnvelope` - エンベロープが短すぎる場合
+/// * `EnvelopeError::DecryptionFailed` - 鍵の誤りまたはデータが改ざんされている場合
+#[uniffi::export]
+pub fn open(private_key: Vec<u8>, sealed: Vec<u8>) -> Result<Vec<u8>, EnvelopeError> {
+    let secret: [u8; KEY_LEN] = private_key.as_slice().try_into().map_err(|_| {
+        EnvelopeError::InvalidKey(format!(
+            "expected 32-byte private key, got {} bytes",
+            private_key.len()
+        ))
+    })?;
+    let version = *sealed.first().ok_or(EnvelopeError::MalformedEnvelope)?;
+    if version != ENVELOPE_VERSION {
+        return Err(EnvelopeError::UnsupportedVersion(version));
+    }
+    if sealed.len() < HEADER_LEN + TAG_LEN {
+        return Err(EnvelopeError::MalformedEnvelope);
+    }
+
+    let (aad, rest) = sealed.split_at(1 + KEY_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let ephemeral_public = &aad[1..];
+    let recipient_public = PublicKey::from(&StaticSecret::from(secret));
+    let cipher = derive_cipher(
+        private_key,
+        ephemeral_public.to_vec(),
+        ephemeral_public,
+        recipient_public.as_bytes(),
+    )
+    .map_err(|_| EnvelopeError::DecryptionFailed)?;
+    let payload = Payload {
+        msg: ciphertext,
+        aad,
+    };
+    cipher
+        .decrypt(Nonce::from_slice(nonce), payload)
+        .map_err(|_| EnvelopeError::DecryptionFailed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let recipient = x25519_generate_keypair();
+        let sealed = seal(recipient.public_key.clone(), b"hello server".to_vec()).unwrap();
+        assert_eq!(sealed.len(), HEADER_LEN + 12 + TAG_LEN);
+        assert_eq!(sealed[0], ENVELOPE_VERSION);
+        assert_eq!(open(recipient.private_key, sealed).unwrap(), b"hello server");
+    }
+
+    #[test]
+    fn test_seal_is_randomized() {
+        let recipient = x25519_generate_keypair();
+        let a = seal(recipient.public_key.clone(), b"same".to_vec()).unwrap();
+        let b = seal(recipient.public_key, b"same".to_vec()).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_open_with_wrong_key() {
+        let recipient = x25519_generate_keypair();
+        let other = x25519_generate_keypair();
+        let sealed = seal(recipient.public_key, b"secret".to_vec()).unwrap();
+        match open(other.private_key, sealed) {
+            Err(EnvelopeError::DecryptionFailed) => (),
+            _ => panic!("Expected DecryptionFailed error"),
+        }
+    }
+
+    #[test]
+    fn test_open_detects_tampering() {
+        let recipient = x25519_generate_keypair();
+        let mut sealed = seal(recipient.public_key, b"secret".to_vec()).unwrap();
+        sealed[5] ^= 0x01;
+        match open(recipient.private_key, sealed) {
+            Err(EnvelopeError::DecryptionFailed) => (),
+            _ => panic!("Expected DecryptionFailed error"),
+        }
+    }
+
+    #[test]
+    fn test_open_malformed_envelope() {
+        let recipient = x25519_generate_keypair();
+        match open(recipient.private_key.clone(), vec![2, 0, 0]) {
+            Err(EnvelopeError::UnsupportedVersion(2)) => (),
+            _ => panic!("Expected UnsupportedVersion error"),
+        }
+        match open(recipient.private_key, vec![ENVELOPE_VERSION; 20]) {
+            Err(EnvelopeError::MalformedEnvelope) => (),
+            _ => panic!("Expected MalformedEnvelope error"),
+        }
+    }
+
+    #[test]
+    fn test_seal_invalid_public_key() {
+        match seal(vec![0u8; 31], b"x".to_vec()) {
+            Err(EnvelopeError::InvalidKey(_)) => (),
+            _ => panic!("Expected InvalidKey error"),
+        }
+        match seal(vec![0u8; 32], b"x".to_vec()) {
+            Err(EnvelopeError::InvalidKey(_)) => (),
+            _ => panic!("Expected InvalidKey error"),
+        }
+    }
+}
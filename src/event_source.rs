@@ -0,0 +1,1050 @@
+//! Server-Sent Eventsクライアントモジュール
+//!
+//! このモジュールは、Server-Sent Events（HTML Living Standard）のエンドポイントに
+//! 接続してイベントを受信する`EventSource`と、イベントや接続状態の変化を受け取る
+//! `EventSourceListener`をエクスポートします。
+//! 接続はRust側のスレッドで維持され、切断された場合は`RetryPolicy`に従って
+//! `Last-Event-ID`ヘッダー付きで自動的に再接続します。`http://`と`https://`（rustls）に
+//! 対応しています。リダイレクトには追従しません。
+//!
+//! 受信したイベントはリスナーに通知されるほか、`next_event`で非同期に順番に取り出せます。
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{IpAddr, Shutdown, TcpStream};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use thiserror::Error;
+use url::{Host, Position, Url};
+
+use crate::retry::RetryPolicy;
+
+/// 接続のタイムアウト
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// この時間データを受信しない場合は切断されたとみなして再接続する
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// 再接続の待機中に`close`を確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// `next_event`のために保持するイベントの最大数（超えた場合は古いものから破棄）
+const MAX_BUFFERED_EVENTS: usize = 1024;
+
+/// 1回に読み込む最大バイト数
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+/// レスポンスヘッダーの最大サイズ
+const MAX_HEAD_LEN: usize = 64 * 1024;
+
+/// 受信中の1つのイベント（未完成の行とデータの合計）の最大サイズ
+const MAX_EVENT_LEN: usize = 1024 * 1024;
+
+/// UTF-8のBOM
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// EventSourceの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum EventSourceError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// URLが不正、またはHTTP(S)以外のスキームの場合
+    #[error("Invalid event source URL: {0}")]
+    InvalidUrl(String),
+    /// ヘッダーの名前または値が不正な場合
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    /// 既に接続中、または接続済みの場合
+    #[error("Event source is already connected")]
+    AlreadyConnected,
+}
+
+/// 受信したイベント
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ServerSentEvent {
+    /// イベントの種類（`event`フィールド、指定がない場合は`message`）
+    pub event_type: String,
+    /// データ（複数の`data`フィールドは改行で連結されます）
+    pub data: String,
+    /// 最後に受信したイベントID（`id`フィールド、受信していない場合は空文字列）
+    pub last_event_id: String,
+}
+
+/// 接続の状態
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum EventSourceState {
+    /// 接続中
+    Connecting,
+    /// 接続済みでイベントを受信できる状態
+    Open,
+    /// 切断されたため、待機した後に再接続する状態
+    Reconnecting {
+        /// 再接続の試行回数（1始まり）
+        attempt: u32,
+        /// 再接続までの待ち時間（ミリ秒）
+        delay_ms: u64,
+        /// 切断の理由
+        reason: String,
+    },
+    /// 切断され、再接続しない状態
+    Closed {
+        /// 切断の理由
+        reason: String,
+    },
+}
+
+/// 受信したイベントと接続状態の変化を受け取るリスナー
+///
+/// メソッドは接続を維持しているRust側のスレッドから呼ばれます。
+/// UIを更新する場合はメインスレッドに切り替えてください。
+#[uniffi::export(with_foreign)]
+pub trait EventSourceListener: Send + Sync {
+    /// 接続の状態が変化したときに呼ばれます
+    fn on_state_changed(&self, state: EventSourceState);
+
+    /// イベントを受信したときに呼ばれます
+    fn on_event(&self, event: ServerSentEvent);
+}
+
+/// イベントストリームの解析器
+///
+/// 改行はCRLF・LF・CRのいずれも受け付けます。
+#[derive(Default)]
+struct EventParser {
+    line: Vec<u8>,
+    /// 直前のバイトがCRだったかどうか（CRLFのLFを読み飛ばすため）
+    after_cr: bool,
+    /// 先頭のBOMを確認済みかどうか
+    started: bool,
+    event_type: String,
+    data: String,
+    has_data: bool,
+    last_event_id: String,
+    /// サーバーが`retry`フィールドで指定した再接続の待ち時間（ミリ秒）
+    retry_ms: Option<u64>,
+}
+
+impl EventParser {
+    /// 受信したバイト列を解析し、完成したイベントを`events`に追加します
+    ///
+    /// 受信中のイベントが`MAX_EVENT_LEN`を超えた場合はエラーの理由を返します。
+    fn feed(&mut self, mut bytes: &[u8], events: &mut Vec<ServerSentEvent>) -> Result<(), String> {
+        if !self.started {
+            // 先頭のBOMは途中で分割されて届く場合があるため、3バイト揃うまで待つ
+            self.line.extend_from_slice(bytes);
+            if self.line.len() < BOM.len() && BOM.starts_with(&self.line) {
+                return Ok(());
+            }
+            self.started = true;
+            let buffered = std::mem::take(&mut self.line);
+            return self.feed(buffered.strip_prefix(BOM).unwrap_or(&buffered), events);
+        }
+        while let Some(&byte) = bytes.first() {
+            bytes = &bytes[1..];
+            let after_cr = std::mem::replace(&mut self.after_cr, byte == b'\r');
+            match byte {
+                b'\n' if after_cr => {}
+                b'\r' | b'\n' => {
+                    let line = std::mem::take(&mut self.line);
+                    if let Some(event) = self.process_line(&String::from_utf8_lossy(&line)) {
+                        events.push(event);
+                    }
+                }
+                _ => {
+                    if self.line.len() + self.data.len() >= MAX_EVENT_LEN {
+                        return Err(format!("event exceeds {} bytes", MAX_EVENT_LEN));
+                    }
+                    self.line.push(byte);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 1行を処理し、空行で完成したイベントを返します
+    fn process_line(&mut self, line: &str) -> Option<ServerSentEvent> {
+        if line.is_empty() {
+            let event_type = std::mem::take(&mut self.event_type);
+            let data = std::mem::take(&mut self.data);
+            if !std::mem::take(&mut self.has_data) {
+                return None;
+            }
+            return Some(ServerSentEvent {
+                event_type: if event_type.is_empty() { "message".to_string() } else { event_type },
+                data: data.strip_suffix('\n').unwrap_or(&data).to_string(),
+                last_event_id: self.last_event_id.clone(),
+            });
+        }
+        if line.starts_with(':') {
+            return None;
+        }
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+        match field {
+            "event" => self.event_type = value.to_string(),
+            "data" => {
+                self.data.push_str(value);
+                self.data.push('\n');
+                self.has_data = true;
+            }
+            "id" if !value.contains('\0') => self.last_event_id = value.to_string(),
+            "retry" if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) => {
+                self.retry_ms = value.parse().ok();
+            }
+            _ => {}
+        }
+        None
+    }
+
+    /// 接続が切れたときに、完成していないイベントを破棄します
+    fn reset_pending(&mut self) {
+        self.line.clear();
+        self.after_cr = false;
+        self.started = false;
+        self.event_type.clear();
+        self.data.clear();
+        self.has_data = false;
+    }
+}
+
+/// 接続が終了した理由
+enum Disconnect {
+    /// 再接続する（通信エラーやサーバーによる切断）
+    Retry(String),
+    /// 再接続しない（成功以外のステータスなど）
+    Fatal(String),
+}
+
+/// エンドポイントとの通信路（`https`の場合はTLS）
+enum Connection {
+    Plain(TcpStream),
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Read for Connection {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.read(buf),
+            Connection::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Connection {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Connection::Plain(stream) => stream.write(buf),
+            Connection::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Connection::Plain(stream) => stream.flush(),
+            Connection::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// 受信したレスポンス
+struct Response {
+    status: u16,
+    /// ヘッダー（名前は小文字）
+    headers: HashMap<String, String>,
+    body: Box<dyn Read + Send>,
+}
+
+/// `Transfer-Encoding: chunked`の本文を読み込む読み込み元
+struct ChunkedReader<R> {
+    inner: R,
+    /// 読み込み中のチャンクの残りのバイト数
+    remaining: u64,
+    done: bool,
+}
+
+impl<R: BufRead> Read for ChunkedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.done || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.remaining == 0 {
+            let line = read_line(&mut self.inner, MAX_HEAD_LEN)?;
+            let size = line.split(';').next().unwrap_or_default().trim();
+            self.remaining = u64::from_str_radix(size, 16)
+                .map_err(|_| io::Error::new(ErrorKind::InvalidData, "invalid chunk size"))?;
+            if self.remaining == 0 {
+                self.done = true;
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.remaining.try_into().unwrap_or(usize::MAX));
+        let read = self.inner.read(&mut buf[..len])?;
+        if read == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= read as u64;
+        if self.remaining == 0 && !read_line(&mut self.inner, 0)?.is_empty() {
+            return Err(io::Error::new(ErrorKind::InvalidData, "missing chunk terminator"));
+        }
+        Ok(read)
+    }
+}
+
+/// 改行（CRLFまたはLF）までの1行を、改行を除いて読みます
+fn read_line(reader: &mut impl BufRead, max_len: usize) -> io::Result<String> {
+    let mut line = Vec::new();
+    // 改行を含めて最大`max_len + 2`バイトまで読む
+    reader.take(max_len as u64 + 2).read_until(b'\n', &mut line)?;
+    if line.pop() != Some(b'\n') {
+        return Err(io::Error::new(ErrorKind::InvalidData, "line is too long or truncated"));
+    }
+    if line.last() == Some(&b'\r') {
+        line.pop();
+    }
+    String::from_utf8(line).map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+}
+
+/// ルート証明書にMozillaの証明書を使うTLSの設定
+fn tls_config() -> Result<Arc<ClientConfig>, String> {
+    static CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+    if let Some(config) = CONFIG.get() {
+        return Ok(Arc::clone(config));
+    }
+    let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| e.to_string())?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::clone(CONFIG.get_or_init(|| Arc::new(config))))
+}
+
+/// URLのホストに接続し、通信路とソケットの複製を返します
+fn open_connection(url: &Url) -> Result<(Connection, TcpStream), String> {
+    let addrs = url.socket_addrs(|| None).map_err(|e| e.to_string())?;
+    let mut last_error = format!("could not resolve {}", url.host_str().unwrap_or_default());
+    for addr in addrs {
+        let stream = match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => stream,
+            Err(e) => {
+                last_error = e.to_string();
+                continue;
+            }
+        };
+        stream.set_read_timeout(Some(IDLE_TIMEOUT)).map_err(|e| e.to_string())?;
+        stream.set_write_timeout(Some(CONNECT_TIMEOUT)).map_err(|e| e.to_string())?;
+        let socket = stream.try_clone().map_err(|e| e.to_string())?;
+        if url.scheme() == "http" {
+            return Ok((Connection::Plain(stream), socket));
+        }
+        let server_name = match url.host() {
+            Some(Host::Domain(domain)) => ServerName::try_from(domain.to_string())
+                .map_err(|_| format!("invalid host name: {}", domain))?,
+            Some(Host::Ipv4(ip)) => ServerName::from(IpAddr::V4(ip)),
+            Some(Host::Ipv6(ip)) => ServerName::from(IpAddr::V6(ip)),
+            None => return Err("URL has no host".to_string()),
+        };
+        let connection =
+            ClientConnection::new(tls_config()?, server_name).map_err(|e| e.to_string())?;
+        let tls = StreamOwned::new(connection, stream);
+        return Ok((Connection::Tls(Box::new(tls)), socket));
+    }
+    Err(last_error)
+}
+
+/// レスポンスのステータスとヘッダー（名前は小文字）を読みます
+fn read_head(reader: &mut impl BufRead) -> io::Result<(u16, HashMap<String, String>)> {
+    let mut remaining = MAX_HEAD_LEN;
+    let mut next_line = |reader: &mut _| -> io::Result<String> {
+        let line = read_line(reader, remaining)?;
+        remaining = remaining.saturating_sub(line.len() + 2);
+        Ok(line)
+    };
+    let status_line = next_line(reader)?;
+    let status = status_line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| io::Error::new(ErrorKind::InvalidData, "invalid status line"))?;
+    let mut headers = HashMap::new();
+    loop {
+        let line = next_line(reader)?;
+        if line.is_empty() {
+            return Ok((status, headers));
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+        }
+    }
+}
+
+/// 接続スレッドと共有する状態
+struct Shared {
+    listener: Option<Arc<dyn EventSourceListener>>,
+    retry_policy: RetryPolicy,
+    state: Mutex<EventSourceState>,
+    /// 接続ごとに増える世代（`close`・`connect`で古い接続スレッドを無効にする）
+    generation: AtomicU64,
+    /// 現在の接続のソケット（`close`で閉じて、ブロックしている読み込みを中断する）
+    socket: Mutex<Option<TcpStream>>,
+    last_event_id: Mutex<String>,
+    events: Mutex<VecDeque<ServerSentEvent>>,
+    events_changed: Condvar,
+}
+
+impl Shared {
+    /// 指定した世代の接続が現在の接続かを返します
+    fn is_current(&self, generation: u64) -> bool {
+        self.generation.load(Ordering::SeqCst) == generation
+    }
+
+    /// 現在の接続であれば状態を更新し、リスナーに通知します
+    fn set_state(&self, generation: u64, state: EventSourceState) -> bool {
+        {
+            let Ok(mut current) = self.state.lock() else { return false };
+            if !self.is_current(generation) {
+                return false;
+            }
+            *current = state.clone();
+        }
+        self.notify_state(state);
+        true
+    }
+
+    /// 状態の変化を`next_event`の待機とリスナーに通知します
+    fn notify_state(&self, state: EventSourceState) {
+        if let Some(listener) = &self.listener {
+            listener.on_state_changed(state);
+        }
+        // 待機側は状態の確認から待機までキューのロックを保持しているため、
+        // ロックを取得してから通知すれば通知を取りこぼさない
+        drop(self.events.lock());
+        self.events_changed.notify_all();
+    }
+
+    /// 現在の接続であればイベントを保持し、リスナーに通知します
+    fn deliver(&self, generation: u64, event: ServerSentEvent) {
+        if !self.is_current(generation) {
+            return;
+        }
+        if let Ok(mut last_event_id) = self.last_event_id.lock() {
+            last_event_id.clone_from(&event.last_event_id);
+        }
+        if let Ok(mut events) = self.events.lock() {
+            if events.len() >= MAX_BUFFERED_EVENTS {
+                events.pop_front();
+            }
+            events.push_back(event.clone());
+        }
+        self.events_changed.notify_all();
+        if let Some(listener) = &self.listener {
+            listener.on_event(event);
+        }
+    }
+
+    /// 現在の接続であればソケットを保持し、そうでなければ閉じます
+    fn set_socket(&self, generation: u64, socket: TcpStream) -> bool {
+        let Ok(mut current) = self.socket.lock() else { return false };
+        // `close`は世代を進めてからソケットを取り出すため、ロック中に世代を確認すれば
+        // 閉じ忘れは起きない
+        if !self.is_current(generation) {
+            let _ = socket.shutdown(Shutdown::Both);
+            return false;
+        }
+        *current = Some(socket);
+        true
+    }
+
+    /// 保持しているソケットを閉じます
+    fn shutdown_socket(&self) {
+        if let Some(socket) = self.socket.lock().ok().and_then(|mut socket| socket.take()) {
+            let _ = socket.shutdown(Shutdown::Both);
+        }
+    }
+
+    /// 指定した時間だけ待ちます（接続が無効になった場合は`false`を返して中断）
+    fn sleep_while_current(&self, generation: u64, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if !self.is_current(generation) {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - Instant::now()));
+        }
+        self.is_current(generation)
+    }
+
+    /// 接続・再接続を繰り返し、再接続しない状態になったら終了します
+    fn run(
+        &self,
+        generation: u64,
+        url: Url,
+        headers: HashMap<String, String>,
+        last_event_id: String,
+    ) {
+        let mut parser = EventParser { last_event_id, ..Default::default() };
+        let mut attempt = 0u32;
+        loop {
+            let reason = match self.stream(generation, &url, &headers, &mut parser, &mut attempt) {
+                Disconnect::Retry(reason) => reason,
+                Disconnect::Fatal(reason) => {
+                    self.set_state(generation, EventSourceState::Closed { reason });
+                    return;
+                }
+            };
+            parser.reset_pending();
+            attempt += 1;
+            let mut policy = self.retry_policy.clone();
+            if let Some(retry_ms) = parser.retry_ms {
+                policy.base_delay_ms = retry_ms;
+            }
+            let Some(delay_ms) = policy.delay(attempt) else {
+                self.set_state(generation, EventSourceState::Closed { reason });
+                return;
+            };
+            let reconnecting = EventSourceState::Reconnecting { attempt, delay_ms, reason };
+            if !self.set_state(generation, reconnecting)
+                || !self.sleep_while_current(generation, Duration::from_millis(delay_ms))
+                || !self.set_state(generation, EventSourceState::Connecting)
+            {
+                return;
+            }
+        }
+    }
+
+    /// リクエストを送信し、レスポンスを返します
+    ///
+    /// 接続したソケットは、`close`で閉じられるように保持します。
+    fn request(
+        &self,
+        generation: u64,
+        url: &Url,
+        headers: &HashMap<String, String>,
+        last_event_id: &str,
+    ) -> Result<Response, String> {
+        let (mut connection, socket) = open_connection(url)?;
+        if !self.set_socket(generation, socket) {
+            return Err("closed".to_string());
+        }
+        let mut request = format!(
+            "GET {} HTTP/1.1\r\nAccept: text/event-stream\r\nCache-Control: no-cache\r\n",
+            &url[Position::BeforePath..Position::AfterQuery]
+        );
+        if !headers.keys().any(|name| name.eq_ignore_ascii_case("host")) {
+            request.push_str(&format!(
+                "Host: {}\r\n",
+                &url[Position::BeforeHost..Position::AfterPort]
+            ));
+        }
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        if !last_event_id.is_empty() {
+            request.push_str(&format!("Last-Event-ID: {}\r\n", last_event_id));
+        }
+        request.push_str("\r\n");
+        connection.write_all(request.as_bytes()).map_err(|e| e.to_string())?;
+        connection.flush().map_err(|e| e.to_string())?;
+
+        let mut reader = BufReader::with_capacity(READ_CHUNK_LEN, connection);
+        let (status, headers) = read_head(&mut reader).map_err(|e| e.to_string())?;
+        let chunked = headers
+            .get("transfer-encoding")
+            .is_some_and(|encoding| encoding.to_ascii_lowercase().contains("chunked"));
+        let content_length = headers.get("content-length").and_then(|len| len.parse().ok());
+        let body: Box<dyn Read + Send> = match content_length {
+            _ if chunked => Box::new(ChunkedReader { inner: reader, remaining: 0, done: false }),
+            Some(len) => Box::new(reader.take(len)),
+            None => Box::new(reader),
+        };
+        Ok(Response { status, headers, body })
+    }
+
+    /// 接続してイベントを受信し、切断された理由を返します
+    ///
+    /// 接続が開いた時点で再接続の試行回数をリセットします。
+    fn stream(
+        &self,
+        generation: u64,
+        url: &Url,
+        headers: &HashMap<String, String>,
+        parser: &mut EventParser,
+        attempt: &mut u32,
+    ) -> Disconnect {
+        let Response { status, headers, body: mut reader } =
+            match self.request(generation, url, headers, &parser.last_event_id) {
+                Ok(response) => response,
+                Err(reason) => return Disconnect::Retry(reason),
+            };
+        if status == 204 {
+            return Disconnect::Fatal("server requested to stop reconnecting".to_string());
+        }
+        if !(200..300).contains(&status) {
+            let reason = format!("unexpected HTTP status: {}", status);
+            if self.retry_policy.retries_status(status) {
+                return Disconnect::Retry(reason);
+            }
+            return Disconnect::Fatal(reason);
+        }
+        let content_type = headers.get("content-type").map_or("", String::as_str);
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+        if !mime_type.eq_ignore_ascii_case("text/event-stream") {
+            return Disconnect::Fatal(format!("unexpected content type: {}", content_type));
+        }
+        if !self.set_state(generation, EventSourceState::Open) {
+            return Disconnect::Fatal("closed".to_string());
+        }
+        *attempt = 0;
+
+        let mut buf = [0u8; READ_CHUNK_LEN];
+        let mut events = Vec::new();
+        loop {
+            let len = match reader.read(&mut buf) {
+                Ok(0) => return Disconnect::Retry("connection closed".to_string()),
+                Ok(len) => len,
+                Err(e) => return Disconnect::Retry(e.to_string()),
+            };
+            let fed = parser.feed(&buf[..len], &mut events);
+            for event in events.drain(..) {
+                self.deliver(generation, event);
+            }
+            if !self.is_current(generation) {
+                return Disconnect::Fatal("closed".to_string());
+            }
+            if let Err(reason) = fed {
+                return Disconnect::Fatal(reason);
+            }
+        }
+    }
+}
+
+/// ヘッダーの名前と値が送信できる形式かを確認します
+fn check_header(name: &str, value: &str) -> Result<(), EventSourceError> {
+    let valid_name = !name.is_empty()
+        && name.bytes().all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b));
+    if !valid_name || value.contains(['\r', '\n', '\0']) {
+        return Err(EventSourceError::InvalidHeader(name.to_string()));
+    }
+    Ok(())
+}
+
+/// Server-Sent Eventsクライアント
+///
+/// 1つのクライアントは同時に1つの接続を持ちます。`close`の後は再び`connect`できます。
+/// イベントはリスナーに通知されると同時に内部のキューに保持され、`next_event`で
+/// 取り出せます（キューは最大1024件で、超えた場合は古いものから破棄されます）。
+///
+/// # Example
+/// ```
+/// let source = EventSource::new(listener, None)?;
+/// source.connect("https://example.com/live".to_string(), headers, None)?;
+/// while let Some(event) = source.next_event().await {
+///     // event.eventType・event.dataを処理する
+/// }
+/// ```
+#[derive(uniffi::Object)]
+pub struct EventSource {
+    shared: Arc<Shared>,
+}
+
+impl EventSource {
+    /// イベントを受信するか、接続が閉じるまで現在のスレッドで待ちます
+    fn next_event_blocking(&self) -> Option<ServerSentEvent> {
+        let mut events = self.shared.events.lock().ok()?;
+        loop {
+            if let Some(event) = events.pop_front() {
+                return Some(event);
+            }
+            let closed = matches!(
+                self.shared.state.lock().ok().as_deref(),
+                None | Some(EventSourceState::Closed { .. })
+            );
+            if closed {
+                return None;
+            }
+            events = self.shared.events_changed.wait(events).ok()?;
+        }
+    }
+}
+
+#[uniffi::export]
+impl EventSource {
+    /// クライアントを作成します
+    ///
+    /// # Arguments
+    /// * `listener` - イベントと状態の変化を受け取るリスナー（`None`の場合は`next_event`のみ）
+    /// * `retry_policy` - 切断された場合と再試行対象のステータスの場合の再接続のポリシー
+    ///   （`None`の場合は既定値）。試行回数は接続が開くたびにリセットされます。
+    ///   サーバーが`retry`フィールドを送信した場合は、その値を最初の待ち時間として使います
+    #[uniffi::constructor(default(listener = None, retry_policy = None))]
+    pub fn new(
+        listener: Option<Arc<dyn EventSourceListener>>,
+        retry_policy: Option<RetryPolicy>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            shared: Arc::new(Shared {
+                listener,
+                retry_policy: retry_policy.unwrap_or_default(),
+                state: Mutex::new(EventSourceState::Closed { reason: String::new() }),
+                generation: AtomicU64::new(0),
+                socket: Mutex::new(None),
+                last_event_id: Mutex::new(String::new()),
+                events: Mutex::new(VecDeque::new()),
+                events_changed: Condvar::new(),
+            }),
+        })
+    }
+
+    /// エンドポイントへの接続をバックグラウンドで開始します
+    ///
+    /// 接続の結果は`on_state_changed`で通知されます。再試行対象以外の成功でないステータスや
+    /// `text/event-stream`以外のレスポンス、204 No Contentの場合、受信中のイベントが
+    /// 1 MiBを超えた場合は再接続しません。
+    ///
+    /// # Arguments
+    /// * `url` - 接続先の`http://`または`https://`のURL
+    /// * `headers` - リクエストに追加するヘッダー（認証など）
+    /// * `last_event_id` - 最初の接続で`Last-Event-ID`として送るID（前回の続きから受信する場合）
+    ///
+    /// # Errors
+    /// * `EventSourceError::InvalidUrl` - URLが不正な場合
+    /// * `EventSourceError::InvalidHeader` - ヘッダーが不正な場合
+    /// * `EventSourceError::AlreadyConnected` - 既に接続中・接続済みの場合
+    /// * `EventSourceError::MutexPoisoned` - 内部Mutexが破損している場合
+    #[uniffi::method(default(last_event_id = None))]
+    pub fn connect(
+        &self,
+        url: String,
+        headers: HashMap<String, String>,
+        last_event_id: Option<String>,
+    ) -> Result<(), EventSourceError> {
+        let parsed = Url::parse(&url).map_err(|_| EventSourceError::InvalidUrl(url.clone()))?;
+        if !matches!(parsed.scheme(), "http" | "https") || !parsed.has_host() {
+            return Err(EventSourceError::InvalidUrl(url));
+        }
+        for (name, value) in &headers {
+            check_header(name, value)?;
+        }
+        let last_event_id = last_event_id.unwrap_or_default();
+        if last_event_id.contains(['\r', '\n', '\0']) {
+            return Err(EventSourceError::InvalidHeader("Last-Event-ID".to_string()));
+        }
+
+        let generation = {
+            let mut state = self.shared.state.lock()
+                .map_err(|_| EventSourceError::MutexPoisoned)?;
+            if !matches!(*state, EventSourceState::Closed { .. }) {
+                return Err(EventSourceError::AlreadyConnected);
+            }
+            *state = EventSourceState::Connecting;
+            self.shared.generation.fetch_add(1, Ordering::SeqCst) + 1
+        };
+        {
+            let mut current = self.shared.last_event_id.lock()
+                .map_err(|_| EventSourceError::MutexPoisoned)?;
+            current.clone_from(&last_event_id);
+        }
+        if let Some(listener) = &self.shared.listener {
+            listener.on_state_changed(EventSourceState::Connecting);
+        }
+        let shared = Arc::clone(&self.shared);
+        let spawned = thread::Builder::new()
+            .name("mobile-event-source".to_string())
+            .spawn(move || shared.run(generation, parsed, headers, last_event_id));
+        if let Err(e) = spawned {
+            self.shared.set_state(generation, EventSourceState::Closed { reason: e.to_string() });
+        }
+        Ok(())
+    }
+
+    /// 接続を閉じ、自動再接続を停止します
+    ///
+    /// 状態はすぐに`EventSourceState::Closed`になり、以降のイベントは通知されません。
+    /// 受信を待っている接続はソケットを閉じて中断します。
+    /// `next_event`で待機中の呼び出しは、保持済みのイベントを返し終えると`None`を返します。
+    ///
+    /// # Errors
+    /// * `EventSourceError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn close(&self) -> Result<(), EventSourceError> {
+        let closed = EventSourceState::Closed { reason: "closed".to_string() };
+        {
+            let mut state = self.shared.state.lock()
+                .map_err(|_| EventSourceError::MutexPoisoned)?;
+            if matches!(*state, EventSourceState::Closed { .. }) {
+                return Ok(());
+            }
+            // 接続スレッドは世代が変わったことを確認して終了する
+            self.shared.generation.fetch_add(1, Ordering::SeqCst);
+            *state = closed.clone();
+        }
+        self.shared.shutdown_socket();
+        self.shared.notify_state(closed);
+        Ok(())
+    }
+
+    /// 現在の接続の状態を返します
+    ///
+    /// # Errors
+    /// * `EventSourceError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn state(&self) -> Result<EventSourceState, EventSourceError> {
+        let state = self.shared.state.lock()
+            .map_err(|_| EventSourceError::MutexPoisoned)?;
+        Ok(state.clone())
+    }
+
+    /// 最後に受信したイベントIDを返します（受信していない場合は空文字列）
+    ///
+    /// アプリの再起動後に`connect`の`last_event_id`に渡すと、続きから受信できます。
+    ///
+    /// # Errors
+    /// * `EventSourceError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn last_event_id(&self) -> Result<String, EventSourceError> {
+        let last_event_id = self.shared.last_event_id.lock()
+            .map_err(|_| EventSourceError::MutexPoisoned)?;
+        Ok(last_event_id.clone())
+    }
+
+    /// 次のイベントを待って返します
+    ///
+    /// # Returns
+    /// * 受信したイベント。接続が閉じていて保持済みのイベントもない場合は`None`
+    pub async fn next_event(self: Arc<Self>) -> Option<ServerSentEvent> {
+        blocking::unblock(move || self.next_event_blocking()).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[derive(Default)]
+    struct RecordingListener {
+        states: Mutex<Vec<EventSourceState>>,
+        events: Mutex<Vec<ServerSentEvent>>,
+    }
+
+    impl EventSourceListener for RecordingListener {
+        fn on_state_changed(&self, state: EventSourceState) {
+            self.states.lock().unwrap().push(state);
+        }
+
+        fn on_event(&self, event: ServerSentEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    fn event(event_type: &str, data: &str, last_event_id: &str) -> ServerSentEvent {
+        ServerSentEvent {
+            event_type: event_type.to_string(),
+            data: data.to_string(),
+            last_event_id: last_event_id.to_string(),
+        }
+    }
+
+    /// 接続ごとに`responses`の内容を返すサーバーを起動し、URLと受信したリクエストを返します
+    fn serve(responses: Vec<&'static str>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for response in responses {
+                let Ok((mut stream, _)) = listener.accept() else { return };
+                let mut request = Vec::new();
+                let mut byte = [0u8; 1];
+                while !request.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+                    request.push(byte[0]);
+                }
+                recorded.lock().unwrap().push(String::from_utf8_lossy(&request).to_lowercase());
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}/live", addr), requests)
+    }
+
+    #[test]
+    fn test_parser() {
+        let mut parser = EventParser::default();
+        let mut events = Vec::new();
+        let stream = b"\xEF\xBB\xBF: comment\r\ndata: first\r\ndata:second\r\n\r\n\
+                       event: update\nid: 7\ndata\ndata:  x\n\nretry: 1500\nid: 8\r\r\
+                       event: ignored\n\ndata: partial";
+        for chunk in stream.chunks(2) {
+            parser.feed(chunk, &mut events).unwrap();
+        }
+        assert_eq!(events, vec![
+            event("message", "first\nsecond", ""),
+            event("update", "\n x", "7")
+        ]);
+        assert_eq!(parser.retry_ms, Some(1500));
+        assert_eq!(parser.last_event_id, "8");
+
+        // 切断で途中のイベントは破棄され、IDは次のイベントに引き継がれる
+        parser.reset_pending();
+        parser.feed(b"data: next\n\n", &mut events).unwrap();
+        assert_eq!(events.last(), Some(&event("message", "next", "8")));
+    }
+
+    #[test]
+    fn test_parser_event_size_limit() {
+        let mut parser = EventParser::default();
+        let mut events = Vec::new();
+        let half = "x".repeat(MAX_EVENT_LEN / 2);
+        parser.feed(format!("data: {}\n", half).as_bytes(), &mut events).unwrap();
+        // 行とデータの合計で判定する
+        match parser.feed(format!("data: {}", half).as_bytes(), &mut events) {
+            Err(reason) => assert!(reason.contains("exceeds"), "{}", reason),
+            Ok(()) => panic!("Expected an error for an oversized event"),
+        }
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_chunked_reader() {
+        let body = b"5\r\nhello\r\n7;ext=1\r\n, world\r\n0\r\n\r\n";
+        let mut reader = ChunkedReader { inner: &body[..], remaining: 0, done: false };
+        let mut text = String::new();
+        reader.read_to_string(&mut text).unwrap();
+        assert_eq!(text, "hello, world");
+
+        let mut truncated = ChunkedReader { inner: &b"5\r\nhel"[..], remaining: 0, done: false };
+        assert!(truncated.read_to_end(&mut Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_event_source_reconnects_with_last_event_id() {
+        let (url, requests) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nConnection: close\r\n\r\n\
+             retry: 10\nid: 1\ndata: hello\n\nevent: ping\ndata: {\"n\":2}\nid: 2\n\ndata: lost",
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream; charset=utf-8\r\n\
+             Connection: close\r\n\r\nid: 3\ndata: again\n\n",
+            "HTTP/1.1 204 No Content\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let recorder = Arc::new(RecordingListener::default());
+        let policy = RetryPolicy { jitter: 0.0, ..Default::default() };
+        let source = EventSource::new(Some(recorder.clone()), Some(policy));
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer token".to_string())]);
+        source.connect(url, headers, Some("0".to_string())).unwrap();
+
+        let mut received = Vec::new();
+        while let Some(event) = pollster::block_on(source.clone().next_event()) {
+            received.push(event);
+        }
+        let expected = vec![
+            event("message", "hello", "1"),
+            event("ping", "{\"n\":2}", "2"),
+            event("message", "again", "3"),
+        ];
+        assert_eq!(received, expected);
+        assert_eq!(*recorder.events.lock().unwrap(), expected);
+        assert_eq!(source.last_event_id().unwrap(), "3");
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("accept: text/event-stream"));
+        assert!(requests[0].contains("authorization: bearer token"));
+        assert!(requests[0].contains("last-event-id: 0"));
+        assert!(requests[1].contains("last-event-id: 2"));
+        assert!(requests[2].contains("last-event-id: 3"));
+        let states = recorder.states.lock().unwrap();
+        assert!(states.contains(&EventSourceState::Reconnecting {
+            attempt: 1,
+            delay_ms: 10,
+            reason: "connection closed".to_string(),
+        }));
+        assert!(matches!(states.last(), Some(EventSourceState::Closed { .. })));
+    }
+
+    #[test]
+    fn test_event_source_stops_on_http_error() {
+        let (url, requests) = serve(vec![
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        ]);
+        let policy = RetryPolicy { base_delay_ms: 10, ..Default::default() };
+        let source = EventSource::new(None, Some(policy));
+        source.connect(url, HashMap::new(), None).unwrap();
+        assert_eq!(pollster::block_on(source.clone().next_event()), None);
+        match source.state().unwrap() {
+            EventSourceState::Closed { reason } => assert!(reason.contains("404")),
+            other => panic!("Expected Closed state, got {:?}", other),
+        }
+        // 503は再試行の対象のため再接続する
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_event_source_close() {
+        let (url, _) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\r\ndata: first\n\n",
+        ]);
+        let source = EventSource::new(None, None);
+        source.connect(url.clone(), HashMap::new(), None).unwrap();
+        let first = pollster::block_on(source.clone().next_event()).unwrap();
+        assert_eq!(first.data, "first");
+        match source.connect(url, HashMap::new(), None) {
+            Err(EventSourceError::AlreadyConnected) => (),
+            other => panic!("Expected AlreadyConnected error, got {:?}", other),
+        }
+        source.close().unwrap();
+        assert_eq!(
+            source.state().unwrap(),
+            EventSourceState::Closed { reason: "closed".to_string() }
+        );
+        assert_eq!(pollster::block_on(source.clone().next_event()), None);
+    }
+
+    #[test]
+    fn test_event_source_close_interrupts_blocked_read() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/live", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = stream.read(&mut buf).unwrap();
+            // チャンク形式で1件送った後は、クライアントが切断するまで何も送らない
+            stream
+                .write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\n\
+                      Transfer-Encoding: chunked\r\n\r\nd\r\ndata: first\n\n\r\n",
+                )
+                .unwrap();
+            while stream.read(&mut buf).is_ok_and(|len| len > 0) {}
+        });
+
+        let source = EventSource::new(None, None);
+        source.connect(url, HashMap::new(), None).unwrap();
+        let first = pollster::block_on(source.clone().next_event()).unwrap();
+        assert_eq!(first.data, "first");
+        let started = Instant::now();
+        source.close().unwrap();
+        server.join().unwrap();
+        assert!(started.elapsed() < IDLE_TIMEOUT);
+    }
+
+    #[test]
+    fn test_event_source_errors() {
+        let source = EventSource::new(None, None);
+        for url in ["ws://example.com/live", "not a url"] {
+            match source.connect(url.to_string(), HashMap::new(), None) {
+                Err(EventSourceError::InvalidUrl(_)) => (),
+                other => panic!("Expected InvalidUrl error, got {:?}", other),
+            }
+        }
+        let headers = HashMap::from([("X-Token".to_string(), "a\r\nInjected: 1".to_string())]);
+        match source.connect("https://example.com/live".to_string(), headers, None) {
+            Err(EventSourceError::InvalidHeader(_)) => (),
+            other => panic!("Expected InvalidHeader error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! 安全なファイル入出力モジュール
+//!
+//! このモジュールは、クラッシュや電源断が起きても中途半端な内容が残らない
+//! ファイルの書き込み関数と、サイズ上限付きの読み込み関数をエクスポートします。
+//! 書き込みは一時ファイル（`<path>.tmp`）への書き出し・fsync・リネームの順で行うため、
+//! ファイルは常に書き込み前か書き込み後のどちらかの内容になります。
+//! クレート内の保存機能もこのモジュールの関数を使用します。
+
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use thiserror::Error;
+
+/// ファイルの入出力で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum FileIoError {
+    /// ファイルが存在しない場合
+    #[error("File not found: {0}")]
+    FileNotFound(String),
+    /// ファイルが上限を超えている場合
+    #[error("File exceeds the limit of {max_bytes} bytes")]
+    FileTooLarge { max_bytes: u64 },
+    /// ファイルの読み書きに失敗した場合
+    #[error("File IO error: {0}")]
+    IoError(String),
+}
+
+impl From<io::Error> for FileIoError {
+    fn from(error: io::Error) -> Self {
+        FileIoError::IoError(error.to_string())
+    }
+}
+
+/// アトミックな書き込みに使用する一時ファイルのパス（`<path>.tmp`）
+pub(crate) fn temp_path_for(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".tmp");
+    PathBuf::from(name)
+}
+
+/// ファイルを含むディレクトリを同期し、作成やリネームを永続化します
+///
+/// ディレクトリを開けないプラットフォームもあるため、失敗は無視します。
+pub(crate) fn sync_parent_directory(path: &Path) {
+    if let Some(dir) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        let _ = File::open(dir).and_then(|d| d.sync_all());
+    }
+}
+
+/// `write`で一時ファイルに書き出した内容でファイルをアトミックに置き換えます
+///
+/// 失敗した場合は一時ファイルを削除し、元のファイルは変更されません。
+pub(crate) fn write_atomic_with<E: From<io::Error>>(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> Result<(), E>,
+) -> Result<(), E> {
+    let temp_path = temp_path_for(path);
+    let result = File::create(&temp_path).map_err(E::from).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, path)?;
+        Ok(())
+    });
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
+    sync_parent_directory(path);
+    Ok(())
+}
+
+/// 複数のバッファを連結した内容でファイルをアトミックに置き換えます
+///
+/// 失敗した場合は一時ファイルを削除し、元のファイルは変更されません。
+pub(crate) fn write_atomic(path: &Path, parts: &[&[u8]]) -> io::Result<()> {
+    write_atomic_with(path, |file| parts.iter().try_for_each(|part| file.write_all(part)))
+}
+
+/// ファイルをアトミックに書き込みます
+///
+/// 同じディレクトリの一時ファイル（`<path>.tmp`）に書き出してfsyncした後、
+/// リネームで置き換えます。途中でクラッシュしても、ファイルは書き込み前か
+/// 書き込み後のどちらかの内容になります。同じパスへの書き込みは
+/// 呼び出し側で直列化してください。
+///
+/// # Arguments
+/// * `path` - 書き込むファイルのパス（親ディレクトリは存在している必要があります）
+/// * `data` - 書き込む内容
+///
+/// # Errors
+/// * `FileIoError::IoError` - 書き込みまたはリネームに失敗した場合
+///
+/// # Example
+/// ```
+/// write_file_atomic(settingsPath, jsonBytes)?;
+/// ```
+#[uniffi::export]
+pub fn write_file_atomic(path: String, data: Vec<u8>) -> Result<(), FileIoError> {
+    Ok(write_atomic(Path::new(&path), &[&data])?)
+}
+
+/// ファイルをサイズ上限付きで読み込みます
+///
+/// 読み込み中にファイルが大きくなった場合も、上限を超えた時点でエラーになります。
+///
+/// # Arguments
+/// * `path` - 読み込むファイルのパス
+/// * `max_bytes` - 読み込むバイト数の上限
+///
+/// # Errors
+/// * `FileIoError::FileNotFound` - ファイルが存在しない場合
+/// * `FileIoError::FileTooLarge` - ファイルが上限を超えている場合
+/// * `FileIoError::IoError` - 読み込みに失敗した場合
+#[uniffi::export]
+pub fn read_file_with_limit(path: String, max_bytes: u64) -> Result<Vec<u8>, FileIoError> {
+    let file = match File::open(&path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(FileIoError::FileNotFound(path));
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let size = file.metadata()?.len();
+    if size > max_bytes {
+        return Err(FileIoError::FileTooLarge { max_bytes });
+    }
+    let mut data = Vec::with_capacity(size as usize);
+    file.take(max_bytes.saturating_add(1)).read_to_end(&mut data)?;
+    if data.len() as u64 > max_bytes {
+        return Err(FileIoError::FileTooLarge { max_bytes });
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("mobile_file_io_{}_{}.bin", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_write_file_atomic() {
+        let path = temp_file("write");
+        write_file_atomic(path.clone(), b"first".to_vec()).unwrap();
+        write_file_atomic(path.clone(), b"second".to_vec()).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), b"second");
+        assert!(!temp_path_for(Path::new(&path)).exists());
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_write_file_atomic_failure_keeps_original() {
+        let dir = temp_file("dir");
+        fs::create_dir_all(&dir).unwrap();
+        // ディレクトリへのリネームは失敗する
+        match write_file_atomic(dir.clone(), b"data".to_vec()) {
+            Err(FileIoError::IoError(_)) => (),
+            other => panic!("Expected IoError error, got {:?}", other),
+        }
+        assert!(Path::new(&dir).is_dir());
+        assert!(!temp_path_for(Path::new(&dir)).exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_read_file_with_limit() {
+        let path = temp_file("read");
+        fs::write(&path, b"0123456789").unwrap();
+        assert_eq!(read_file_with_limit(path.clone(), 10).unwrap(), b"0123456789");
+        match read_file_with_limit(path.clone(), 9) {
+            Err(FileIoError::FileTooLarge { max_bytes: 9 }) => (),
+            other => panic!("Expected FileTooLarge error, got {:?}", other),
+        }
+        let _ = fs::remove_file(&path);
+        match read_file_with_limit(path, 10) {
+            Err(FileIoError::FileNotFound(_)) => (),
+            other => panic!("Expected FileNotFound error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! あいまい文字列一致モジュール
+//!
+//! このモジュールは、2つの文字列の編集距離を返す`levenshtein`、類似度を返す
+//! `jaro_winkler`、クエリに一致する候補を関連度の順に並べる`fuzzy_rank`をエクスポートします。
+//! アプリのクイックオープンや検索候補の表示に使用します。
+//!
+//! `fuzzy_rank`はfzfと同じ方式で、クエリの文字が候補に順に含まれるか（`abc`は
+//! `a_b_c`に一致）を判定し、連続した一致や単語の先頭での一致を高く評価します。
+
+use nucleo_matcher::pattern::{AtomKind, CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Config, Matcher, Utf32Str};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// 一致した範囲
+///
+/// オフセットはUTF-16のコード単位で表すため、Swiftの`NSRange`にそのまま変換できます。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct MatchRange {
+    /// 開始位置（UTF-16のコード単位）
+    pub start: u32,
+    /// 終了位置（UTF-16のコード単位、この位置を含まない）
+    pub end: u32,
+}
+
+/// `fuzzy_rank`の結果の1件
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct FuzzyMatch {
+    /// 候補の配列内の位置
+    pub index: u32,
+    /// 候補の文字列
+    pub candidate: String,
+    /// 関連度（0.0〜1.0、クエリと同じ文字列は1.0）
+    pub score: f64,
+    /// クエリの文字に一致した範囲（開始位置の順、隣接する範囲は結合済み）
+    pub highlights: Vec<MatchRange>,
+}
+
+/// 2つの文字列のレーベンシュタイン距離（編集距離）を返します
+///
+/// 1文字（Unicodeのスカラー値）の挿入・削除・置換を1回として数えます。
+/// 大文字と小文字は区別します。
+///
+/// # Arguments
+/// * `a` - 比較する文字列
+/// * `b` - 比較する文字列
+///
+/// # Example
+/// ```
+/// assert_eq!(levenshtein("kitten".to_string(), "sitting".to_string()), 3);
+/// ```
+#[uniffi::export]
+pub fn levenshtein(a: String, b: String) -> u32 {
+    strsim::levenshtein(&a, &b) as u32
+}
+
+/// 2つの文字列のJaro-Winkler類似度を返します
+///
+/// 先頭が一致する文字列ほど高くなる類似度で、人名や短い語の表記ゆれの比較に適しています。
+/// 大文字と小文字は区別します。
+///
+/// # Arguments
+/// * `a` - 比較する文字列
+/// * `b` - 比較する文字列
+///
+/// # Returns
+/// 0.0（まったく異なる）〜1.0（同じ）の類似度
+///
+/// # Example
+/// ```
+/// let similarity = jaro_winkler("martha".to_string(), "marhta".to_string());
+/// // 0.961
+/// ```
+#[uniffi::export]
+pub fn jaro_winkler(a: String, b: String) -> f64 {
+    strsim::jaro_winkler(&a, &b)
+}
+
+/// 書記素クラスタの位置の一覧をUTF-16の範囲に変換します
+fn highlight_ranges(text: &str, mut indices: Vec<u32>) -> Vec<MatchRange> {
+    indices.sort_unstable();
+    indices.dedup();
+    let mut ranges: Vec<MatchRange> = Vec::new();
+    let mut pending = indices.into_iter().peekable();
+    let mut offset = 0u32;
+    for (index, grapheme) in text.graphemes(true).enumerate() {
+        let length = grapheme.encode_utf16().count() as u32;
+        if pending.next_if_eq(&(index as u32)).is_some() {
+            match ranges.last_mut() {
+                Some(last) if last.end == offset => last.end += length,
+                _ => ranges.push(MatchRange { start: offset, end: offset + length }),
+            }
+        }
+        offset += length;
+    }
+    ranges
+}
+
+/// クエリに一致する候補を関連度の高い順に返します
+///
+/// クエリを空白で区切った場合は、すべての語に一致する候補を返します。
+/// クエリに大文字を含まない場合は大文字と小文字を区別せず、アクセント記号の有無も
+/// 区別しません（`cafe`は`Café`に一致）。関連度が同じ場合は候補の配列の順になります。
+///
+/// # Arguments
+/// * `query` - 入力されたクエリ（空の場合は空の配列を返します）
+/// * `candidates` - 候補の文字列
+/// * `limit` - 返す最大件数
+///
+/// # Example
+/// ```
+/// let files = vec!["src/main.rs".to_string(), "src/model/mod.rs".to_string()];
+/// let matches = fuzzy_rank("mmod".to_string(), files, 10);
+/// assert_eq!(matches[0].candidate, "src/model/mod.rs");
+/// ```
+#[uniffi::export]
+pub fn fuzzy_rank(query: String, candidates: Vec<String>, limit: u32) -> Vec<FuzzyMatch> {
+    let pattern = Pattern::new(&query, CaseMatching::Smart, Normalization::Smart, AtomKind::Fuzzy);
+    if pattern.atoms.is_empty() {
+        return Vec::new();
+    }
+    let mut config = Config::DEFAULT;
+    config.prefer_prefix = true;
+    let mut matcher = Matcher::new(config);
+    let mut buffer = Vec::new();
+    // クエリと同じ文字列の関連度を1.0とする
+    let perfect =
+        pattern.score(Utf32Str::new(&query, &mut buffer), &mut matcher).unwrap_or(1).max(1);
+
+    let mut matches: Vec<FuzzyMatch> = Vec::new();
+    let mut indices = Vec::new();
+    for (index, candidate) in candidates.into_iter().enumerate() {
+        indices.clear();
+        let haystack = Utf32Str::new(&candidate, &mut buffer);
+        let Some(score) = pattern.indices(haystack, &mut matcher, &mut indices) else {
+            continue;
+        };
+        matches.push(FuzzyMatch {
+            index: index as u32,
+            score: (f64::from(score) / f64::from(perfect)).min(1.0),
+            highlights: highlight_ranges(&candidate, indices.clone()),
+            candidate,
+        });
+    }
+    matches.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.index.cmp(&b.index)));
+    matches.truncate(limit as usize);
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rank(query: &str, candidates: &[&str], limit: u32) -> Vec<FuzzyMatch> {
+        let candidates = candidates.iter().map(|candidate| candidate.to_string()).collect();
+        fuzzy_rank(query.to_string(), candidates, limit)
+    }
+
+    fn names(matches: &[FuzzyMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.candidate.as_str()).collect()
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        let distance = |a: &str, b: &str| levenshtein(a.to_string(), b.to_string());
+        assert_eq!(distance("kitten", "sitting"), 3);
+        assert_eq!(distance("", "abc"), 3);
+        assert_eq!(distance("same", "same"), 0);
+        assert_eq!(distance("Same", "same"), 1);
+        assert_eq!(distance("東京都", "京都府"), 2);
+    }
+
+    #[test]
+    fn test_jaro_winkler() {
+        let similarity = |a: &str, b: &str| jaro_winkler(a.to_string(), b.to_string());
+        assert!((similarity("martha", "marhta") - 0.961).abs() < 0.001);
+        assert!((similarity("dixon", "dicksonx") - 0.813).abs() < 0.001);
+        assert_eq!(similarity("abc", "abc"), 1.0);
+        assert_eq!(similarity("abc", "xyz"), 0.0);
+        assert!(similarity("prefix_a", "prefix_b") > similarity("a_suffix", "b_suffix"));
+    }
+
+    #[test]
+    fn test_fuzzy_rank() {
+        let candidates = ["README.md", "src/main.rs", "src/model/mod.rs", "Cargo.toml", "main"];
+        let matches = rank("main", &candidates, 10);
+        assert_eq!(names(&matches), vec!["main", "src/main.rs"]);
+        assert_eq!(matches[0].score, 1.0);
+        assert!(matches[1].score < 1.0);
+        assert_eq!(matches[1].index, 1);
+        assert_eq!(matches[1].highlights, vec![MatchRange { start: 4, end: 8 }]);
+
+        let matches = rank("smr", &candidates, 10);
+        assert_eq!(names(&matches), vec!["src/main.rs", "src/model/mod.rs"]);
+        assert_eq!(
+            matches[0].highlights,
+            vec![
+                MatchRange { start: 0, end: 1 },
+                MatchRange { start: 4, end: 5 },
+                MatchRange { start: 9, end: 10 }
+            ]
+        );
+
+        assert_eq!(names(&rank("rs src", &candidates, 1)), vec!["src/main.rs"]);
+        assert!(rank("xyz", &candidates, 10).is_empty());
+        assert!(rank("  ", &candidates, 10).is_empty());
+        assert!(rank("main", &candidates, 0).is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_rank_case_and_unicode() {
+        let candidates = ["Café Latte", "cafeteria", "👍 Cafe"];
+        // 小文字のクエリは大文字・小文字とアクセント記号を区別しない
+        assert_eq!(rank("cafe", &candidates, 10).len(), 3);
+        assert_eq!(names(&rank("Cafe", &candidates, 10)), vec!["Café Latte", "👍 Cafe"]);
+
+        // ハイライトはUTF-16のオフセット（絵文字は2単位）
+        let matches = rank("cafe", &["👍 Cafe"], 10);
+        assert_eq!(matches[0].highlights, vec![MatchRange { start: 3, end: 7 }]);
+    }
+}
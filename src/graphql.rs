@@ -0,0 +1,357 @@
+//! GraphQLリクエスト補助モジュール
+//!
+//! このモジュールは、GraphQL over HTTPのリクエストボディを組み立てる
+//! `build_graphql_request`と、レスポンスをデータとエラーに分ける
+//! `parse_graphql_response`をエクスポートします。
+//! リクエストの形式とエラーの分類をプラットフォーム間で統一するために使用します。
+//!
+//! エラーの種類は`extensions.code`（Apollo Serverなどで使われる慣例）から判定します。
+
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// GraphQLのリクエスト・レスポンスの処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum GraphQlError {
+    /// クエリが空の場合
+    #[error("GraphQL query must not be empty")]
+    EmptyQuery,
+    /// 変数がJSONオブジェクトでない場合
+    #[error("Invalid GraphQL variables: {0}")]
+    InvalidVariables(String),
+    /// レスポンスがJSONとして不正、またはGraphQLのレスポンスの形式でない場合
+    #[error("Invalid GraphQL response: {0}")]
+    InvalidResponse(String),
+}
+
+/// サーバーが返したエラーの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum GraphQlErrorKind {
+    /// 認証されていない（`UNAUTHENTICATED`）
+    Unauthenticated,
+    /// 権限がない（`FORBIDDEN`）
+    Forbidden,
+    /// クエリの構文・検証エラー（`GRAPHQL_PARSE_FAILED`・`GRAPHQL_VALIDATION_FAILED`）
+    InvalidQuery,
+    /// 入力値が不正（`BAD_USER_INPUT`）
+    BadUserInput,
+    /// 対象が見つからない（`NOT_FOUND`）
+    NotFound,
+    /// サーバー内部のエラー（`INTERNAL_SERVER_ERROR`）
+    Internal,
+    /// コードがない、または上記以外のコード
+    Other,
+}
+
+impl GraphQlErrorKind {
+    /// `extensions.code`から種類を判定します
+    fn from_code(code: Option<&str>) -> Self {
+        match code {
+            Some("UNAUTHENTICATED") => Self::Unauthenticated,
+            Some("FORBIDDEN") => Self::Forbidden,
+            Some("GRAPHQL_PARSE_FAILED") | Some("GRAPHQL_VALIDATION_FAILED") => Self::InvalidQuery,
+            Some("BAD_USER_INPUT") => Self::BadUserInput,
+            Some("NOT_FOUND") => Self::NotFound,
+            Some("INTERNAL_SERVER_ERROR") => Self::Internal,
+            _ => Self::Other,
+        }
+    }
+}
+
+/// エラーが発生したクエリ内の位置
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct GraphQlLocation {
+    /// 行（1始まり）
+    pub line: u32,
+    /// 列（1始まり）
+    pub column: u32,
+}
+
+/// サーバーが返したエラー
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct GraphQlServerError {
+    /// エラーメッセージ
+    pub message: String,
+    /// エラーの種類
+    pub kind: GraphQlErrorKind,
+    /// `extensions.code`の値
+    pub code: Option<String>,
+    /// エラーが発生したフィールドのパス（配列のインデックスは10進数の文字列）
+    pub path: Vec<String>,
+    /// エラーが発生したクエリ内の位置
+    pub locations: Vec<GraphQlLocation>,
+    /// `extensions`のJSON文字列
+    pub extensions_json: Option<String>,
+}
+
+/// GraphQLのレスポンスを分解した結果
+///
+/// `data`と`errors`の両方がある場合は、一部のフィールドだけが失敗した部分的な成功です。
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct GraphQlResult {
+    /// `data`のJSON文字列（`null`または存在しない場合は`None`）
+    pub data_json: Option<String>,
+    /// サーバーが返したエラー
+    pub errors: Vec<GraphQlServerError>,
+    /// レスポンスの`extensions`のJSON文字列
+    pub extensions_json: Option<String>,
+}
+
+/// エラーのオブジェクトを解析します
+fn parse_server_error(value: &Value) -> Result<GraphQlServerError, GraphQlError> {
+    let invalid = || GraphQlError::InvalidResponse(format!("invalid error entry: {}", value));
+    let object = value.as_object().ok_or_else(invalid)?;
+    let message = object.get("message").and_then(Value::as_str).ok_or_else(invalid)?;
+    let extensions = object.get("extensions").filter(|v| !v.is_null());
+    let code = extensions
+        .and_then(|extensions| extensions.get("code"))
+        .and_then(Value::as_str);
+    let path = match object.get("path") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(segments)) => segments
+            .iter()
+            .map(|segment| match segment {
+                Value::String(name) => Some(name.clone()),
+                Value::Number(index) if index.is_u64() => Some(index.to_string()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?,
+        Some(_) => return Err(invalid()),
+    };
+    let locations = match object.get("locations") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(locations)) => locations
+            .iter()
+            .map(|location| {
+                let field = |name| location.get(name)?.as_u64()?.try_into().ok();
+                Some(GraphQlLocation { line: field("line")?, column: field("column")? })
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(invalid)?,
+        Some(_) => return Err(invalid()),
+    };
+    Ok(GraphQlServerError {
+        message: message.to_string(),
+        kind: GraphQlErrorKind::from_code(code),
+        code: code.map(str::to_string),
+        path,
+        locations,
+        extensions_json: extensions.map(Value::to_string),
+    })
+}
+
+/// GraphQL over HTTPのリクエストボディ（JSON）を組み立てます
+///
+/// # Arguments
+/// * `query` - クエリ文字列
+/// * `variables_json` - 変数のJSONオブジェクト（`None`の場合は`variables`を含めません）
+/// * `operation_name` - 実行する操作の名前（クエリに複数の操作がある場合）
+///
+/// # Errors
+/// * `GraphQlError::EmptyQuery` - クエリが空白のみの場合
+/// * `GraphQlError::InvalidVariables` - 変数がJSONオブジェクトでない場合
+///
+/// # Example
+/// ```
+/// let body = build_graphql_request(
+///     "query User($id: ID!) { user(id: $id) { name } }".to_string(),
+///     Some(r#"{"id":"42"}"#.to_string()),
+///     Some("User".to_string()),
+/// )?;
+/// // {"query":"query User($id: ID!) { user(id: $id) { name } }","variables":{"id":"42"},
+/// //  "operationName":"User"}
+/// ```
+#[uniffi::export(default(variables_json = None, operation_name = None))]
+pub fn build_graphql_request(
+    query: String,
+    variables_json: Option<String>,
+    operation_name: Option<String>,
+) -> Result<String, GraphQlError> {
+    if query.trim().is_empty() {
+        return Err(GraphQlError::EmptyQuery);
+    }
+    let mut body = Map::new();
+    body.insert("query".to_string(), Value::String(query));
+    if let Some(variables_json) = variables_json {
+        let variables: Value = serde_json::from_str(&variables_json)
+            .map_err(|e| GraphQlError::InvalidVariables(e.to_string()))?;
+        if !variables.is_object() {
+            return Err(GraphQlError::InvalidVariables(
+                "variables must be a JSON object".to_string(),
+            ));
+        }
+        body.insert("variables".to_string(), variables);
+    }
+    if let Some(operation_name) = operation_name {
+        body.insert("operationName".to_string(), Value::String(operation_name));
+    }
+    Ok(Value::Object(body).to_string())
+}
+
+/// GraphQLのレスポンスボディをデータとエラーに分けます
+///
+/// # Arguments
+/// * `body` - レスポンスボディ（JSON）
+///
+/// # Errors
+/// * `GraphQlError::InvalidResponse` - JSONとして不正、`data`と`errors`のどちらもない、
+///   またはエラーの形式が不正な場合
+///
+/// # Example
+/// ```
+/// let result = parse_graphql_response(body)?;
+/// if result.errors.iter().any(|e| e.kind == GraphQlErrorKind::Unauthenticated) {
+///     // トークンを更新して再送する
+/// }
+/// ```
+#[uniffi::export]
+pub fn parse_graphql_response(body: String) -> Result<GraphQlResult, GraphQlError> {
+    let value: Value =
+        serde_json::from_str(&body).map_err(|e| GraphQlError::InvalidResponse(e.to_string()))?;
+    let object = value.as_object().ok_or_else(|| {
+        GraphQlError::InvalidResponse("response must be a JSON object".to_string())
+    })?;
+    if !object.contains_key("data") && !object.contains_key("errors") {
+        return Err(GraphQlError::InvalidResponse(
+            "response has neither data nor errors".to_string(),
+        ));
+    }
+    let errors = match object.get("errors") {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(errors)) => {
+            errors.iter().map(parse_server_error).collect::<Result<_, _>>()?
+        }
+        Some(_) => {
+            return Err(GraphQlError::InvalidResponse("errors must be an array".to_string()));
+        }
+    };
+    let json_of = |name| object.get(name).filter(|v| !v.is_null()).map(Value::to_string);
+    Ok(GraphQlResult {
+        data_json: json_of("data"),
+        errors,
+        extensions_json: json_of("extensions"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_graphql_request() {
+        let body = build_graphql_request(
+            "query User($id: ID!) { user(id: $id) { name } }".to_string(),
+            Some(r#"{"id": "42", "flags": [1, 2]}"#.to_string()),
+            Some("User".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            body,
+            concat!(
+                r#"{"operationName":"User","#,
+                r#""query":"query User($id: ID!) { user(id: $id) { name } }","#,
+                r#""variables":{"flags":[1,2],"id":"42"}}"#
+            )
+        );
+        let body = build_graphql_request("{ viewer { id } }".to_string(), None, None).unwrap();
+        assert_eq!(body, r#"{"query":"{ viewer { id } }"}"#);
+    }
+
+    #[test]
+    fn test_build_graphql_request_errors() {
+        match build_graphql_request("  ".to_string(), None, None) {
+            Err(GraphQlError::EmptyQuery) => (),
+            other => panic!("Expected EmptyQuery error, got {:?}", other),
+        }
+        for variables in ["[1, 2]", "{invalid"] {
+            match build_graphql_request("{ a }".to_string(), Some(variables.to_string()), None) {
+                Err(GraphQlError::InvalidVariables(_)) => (),
+                other => panic!("Expected InvalidVariables error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_graphql_response_partial() {
+        let body = r#"{
+            "data": {"user": {"name": "Alice", "posts": [null]}},
+            "errors": [{
+                "message": "Post not found",
+                "locations": [{"line": 3, "column": 5}],
+                "path": ["user", "posts", 0],
+                "extensions": {"code": "NOT_FOUND", "id": "p1"}
+            }, {
+                "message": "Something went wrong"
+            }],
+            "extensions": {"cost": 3}
+        }"#;
+        let result = parse_graphql_response(body.to_string()).unwrap();
+        assert_eq!(
+            result.data_json.as_deref(),
+            Some(r#"{"user":{"name":"Alice","posts":[null]}}"#)
+        );
+        assert_eq!(result.extensions_json.as_deref(), Some(r#"{"cost":3}"#));
+        assert_eq!(result.errors, vec![
+            GraphQlServerError {
+                message: "Post not found".to_string(),
+                kind: GraphQlErrorKind::NotFound,
+                code: Some("NOT_FOUND".to_string()),
+                path: vec!["user".to_string(), "posts".to_string(), "0".to_string()],
+                locations: vec![GraphQlLocation { line: 3, column: 5 }],
+                extensions_json: Some(r#"{"code":"NOT_FOUND","id":"p1"}"#.to_string()),
+            },
+            GraphQlServerError {
+                message: "Something went wrong".to_string(),
+                kind: GraphQlErrorKind::Other,
+                code: None,
+                path: Vec::new(),
+                locations: Vec::new(),
+                extensions_json: None,
+            },
+        ]);
+    }
+
+    #[test]
+    fn test_parse_graphql_response_error_kinds() {
+        let body = r#"{"data": null, "errors": [
+            {"message": "a", "extensions": {"code": "UNAUTHENTICATED"}},
+            {"message": "b", "extensions": {"code": "FORBIDDEN"}},
+            {"message": "c", "extensions": {"code": "GRAPHQL_VALIDATION_FAILED"}},
+            {"message": "d", "extensions": {"code": "BAD_USER_INPUT"}},
+            {"message": "e", "extensions": {"code": "INTERNAL_SERVER_ERROR"}},
+            {"message": "f", "extensions": {"code": "RATE_LIMITED"}}
+        ]}"#;
+        let result = parse_graphql_response(body.to_string()).unwrap();
+        assert_eq!(result.data_json, None);
+        let kinds: Vec<GraphQlErrorKind> = result.errors.iter().map(|e| e.kind).collect();
+        assert_eq!(kinds, vec![
+            GraphQlErrorKind::Unauthenticated,
+            GraphQlErrorKind::Forbidden,
+            GraphQlErrorKind::InvalidQuery,
+            GraphQlErrorKind::BadUserInput,
+            GraphQlErrorKind::Internal,
+            GraphQlErrorKind::Other,
+        ]);
+        assert_eq!(result.errors[5].code.as_deref(), Some("RATE_LIMITED"));
+    }
+
+    #[test]
+    fn test_parse_graphql_response_invalid() {
+        for body in [
+            "not json",
+            "[1, 2]",
+            r#"{"extensions": {}}"#,
+            r#"{"errors": {"message": "x"}}"#,
+            r#"{"errors": [{"code": "NO_MESSAGE"}]}"#,
+            r#"{"errors": [{"message": "x", "path": [true]}]}"#,
+            r#"{"errors": [{"message": "x", "locations": [{"line": 1}]}]}"#,
+        ] {
+            match parse_graphql_response(body.to_string()) {
+                Err(GraphQlError::InvalidResponse(_)) => (),
+                other => panic!("Expected InvalidResponse error for {}, got {:?}", body, other),
+            }
+        }
+    }
+}
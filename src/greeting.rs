@@ -1,10 +1,315 @@
 //! 挨拶機能を提供するモジュール
 
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use thiserror::Error;
+
+use crate::sigv4::HttpRequestParts;
+use crate::transport::{send_request, TransportError};
+
+/// 名前が空の場合に使用される呼びかけ
+const DEFAULT_NAME: &str = "friend";
+
+/// グループ挨拶で省略せずに列挙する最大人数
+const MAX_LISTED_NAMES: usize = 3;
+
+/// リモートから取得する挨拶メッセージの最大文字数
+const MAX_REMOTE_GREETING_CHARS: usize = 280;
+
+/// リモートから読み込むレスポンスボディの最大バイト数
+const MAX_REMOTE_GREETING_BYTES: u64 = 4 * 1024;
+
+/// 言語ごとの挨拶テンプレート（`{name}`が名前に置換されます）
+struct LocaleTemplates {
+    /// 言語コード（ISO 639-1）
+    language: &'static str,
+    /// 時間帯によらない挨拶
+    hello: &'static str,
+    /// 朝の挨拶
+    morning: &'static str,
+    /// 昼の挨拶
+    afternoon: &'static str,
+    /// 夜の挨拶
+    evening: &'static str,
+    /// 名前を列挙する際の区切り
+    list_separator: &'static str,
+    /// 2名を列挙する際の接続詞
+    pair_conjunction: &'static str,
+    /// 3名以上を列挙する際の最後の接続詞
+    last_conjunction: &'static str,
+    /// 人数を省略する場合の挨拶（`{names}`と`{count}`が置換されます）
+    others: &'static str,
+}
+
+/// 対応言語の挨拶テンプレート（先頭がフォールバック言語）
+const LOCALIZED_GREETINGS: &[LocaleTemplates] = &[
+    LocaleTemplates {
+        language: "en",
+        hello: "Hello, {name}!",
+        morning: "Good morning, {name}!",
+        afternoon: "Good afternoon, {name}!",
+        evening: "Good evening, {name}!",
+        list_separator: ", ",
+        pair_conjunction: " and ",
+        last_conjunction: ", and ",
+        others: "Hello, {names}, and {count} others!",
+    },
+    LocaleTemplates {
+        language: "ja",
+        hello: "こんにちは、{name}さん！",
+        morning: "おはようございます、{name}さん！",
+        afternoon: "こんにちは、{name}さん！",
+        evening: "こんばんは、{name}さん！",
+        list_separator: "、",
+        pair_conjunction: "と",
+        last_conjunction: "と",
+        others: "こんにちは、{names}さんほか{count}名！",
+    },
+    LocaleTemplates {
+        language: "es",
+        hello: "¡Hola, {name}!",
+        morning: "¡Buenos días, {name}!",
+        afternoon: "¡Buenas tardes, {name}!",
+        evening: "¡Buenas noches, {name}!",
+        list_separator: ", ",
+        pair_conjunction: " y ",
+        last_conjunction: " y ",
+        others: "¡Hola, {names} y {count} más!",
+    },
+    LocaleTemplates {
+        language: "fr",
+        hello: "Bonjour, {name} !",
+        morning: "Bonjour, {name} !",
+        afternoon: "Bon après-midi, {name} !",
+        evening: "Bonsoir, {name} !",
+        list_separator: ", ",
+        pair_conjunction: " et ",
+        last_conjunction: " et ",
+        others: "Bonjour, {names} et {count} autres !",
+    },
+    LocaleTemplates {
+        language: "de",
+        hello: "Hallo, {name}!",
+        morning: "Guten Morgen, {name}!",
+        afternoon: "Guten Tag, {name}!",
+        evening: "Guten Abend, {name}!",
+        list_separator: ", ",
+        pair_conjunction: " und ",
+        last_conjunction: " und ",
+        others: "Hallo, {names} und {count} weitere!",
+    },
+    LocaleTemplates {
+        language: "zh",
+        hello: "你好，{name}！",
+        morning: "早上好，{name}！",
+        afternoon: "下午好，{name}！",
+        evening: "晚上好，{name}！",
+        list_separator: "、",
+        pair_conjunction: "和",
+        last_conjunction: "和",
+        others: "你好，{names}等{count}人！",
+    },
+];
+
+/// 挨拶機能で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum GreetingError {
+    /// 時刻が0〜23の範囲外の場合
+    #[error("Invalid hour: {0} (expected 0-23)")]
+    InvalidHour(u8),
+    /// URLが不正、またはHTTP(S)以外のスキームの場合
+    #[error("Invalid greeting URL: {0}")]
+    InvalidUrl(String),
+    /// 通信に失敗した場合
+    #[error("Network error: {0}")]
+    Network(String),
+    /// サーバーが成功以外のステータスを返した場合
+    #[error("Unexpected HTTP status: {0}")]
+    HttpStatus(u16),
+    /// 取得した内容が挨拶メッセージとして不正な場合
+    #[error("Invalid greeting content: {0}")]
+    InvalidContent(String),
+}
+
+/// 現在時刻（時）を提供するクロック
+///
+/// テストやSwift側からの差し替えのためにコールバックインターフェースとして公開されます。
+#[uniffi::export(with_foreign)]
+pub trait Clock: Send + Sync {
+    /// 現在のローカル時刻の「時」（0〜23）を返します
+    fn current_hour(&self) -> u8;
+}
+
+/// ホスト側から挨拶文を差し替えるためのプロバイダー
+///
+/// Swift側でリモート設定などから挨拶文を決定したい場合に実装します。
+/// `None`を返した場合は組み込みの挨拶文が使用されます。
+#[uniffi::export(with_foreign)]
+pub trait GreetingProvider: Send + Sync {
+    /// 指定された名前とロケールに対する挨拶文を返します
+    ///
+    /// # Arguments
+    /// * `name` - 制御文字を除去済みの名前（`greet_group`ではロケールに応じて連結した名前の一覧）
+    /// * `locale` - 呼び出し元が指定したロケール識別子（`greet`では`"en"`）
+    fn greeting(&self, name: String, locale: String) -> Option<String>;
+}
+
+/// 登録されている挨拶プロバイダー
+static GREETING_PROVIDER: RwLock<Option<Arc<dyn GreetingProvider>>> = RwLock::new(None);
+
+/// 挨拶プロバイダーを登録します
+///
+/// 登録後は`greet`・`greet_localized`・`greet_for_time`・`greet_now`・`greet_group`が
+/// まずプロバイダーに問い合わせ、`None`が返された場合のみ組み込みの挨拶文を使用します。
+/// 表示オプションはどちらの挨拶文にも適用されます。
+///
+/// # Arguments
+/// * `provider` - 登録するプロバイダー（既存の登録は置き換えられます）
+#[uniffi::export]
+pub fn set_greeting_provider(provider: Arc<dyn GreetingProvider>) {
+    if let Ok(mut current) = GREETING_PROVIDER.write() {
+        *current = Some(provider);
+    }
+}
+
+/// 登録されている挨拶プロバイダーを解除し、組み込みの挨拶文に戻します
+#[uniffi::export]
+pub fn clear_greeting_provider() {
+    if let Ok(mut current) = GREETING_PROVIDER.write() {
+        *current = None;
+    }
+}
+
+/// 登録されているプロバイダーに挨拶文を問い合わせます
+fn provided_greeting(name: &str, locale: &str) -> Option<String> {
+    let provider = GREETING_PROVIDER.read().ok()?.clone()?;
+    provider.greeting(name.to_string(), locale.to_string())
+}
+
+/// プロバイダーの挨拶文、または組み込みのテンプレートに名前を埋め込んだ挨拶文を返します
+fn provided_or_template(name: &str, locale: &str, template: &str) -> String {
+    provided_greeting(name, locale).unwrap_or_else(|| template.replace("{name}", name))
+}
+
+/// システム時計に基づくクロック
+///
+/// 標準ライブラリはローカルタイムゾーンを扱えないため、UTCからの
+/// オフセット（分）をコンストラクタで指定します。
+#[derive(uniffi::Object)]
+pub struct SystemClock {
+    utc_offset_minutes: i32,
+}
+
+#[uniffi::export]
+impl SystemClock {
+    /// 指定されたUTCオフセットでシステムクロックを作成します
+    ///
+    /// # Arguments
+    /// * `utc_offset_minutes` - UTCからのオフセット（分）。日本時間なら`540`
+    #[uniffi::constructor]
+    pub fn new(utc_offset_minutes: i32) -> Arc<Self> {
+        Arc::new(Self { utc_offset_minutes })
+    }
+}
+
+#[uniffi::export]
+impl Clock for SystemClock {
+    fn current_hour(&self) -> u8 {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let local_minutes = secs / 60 + self.utc_offset_minutes as i64;
+        (local_minutes.rem_euclid(24 * 60) / 60) as u8
+    }
+}
+
+/// 挨拶の文体
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum GreetingStyle {
+    /// くだけた挨拶
+    Casual,
+    /// 丁寧な挨拶
+    Formal,
+    /// 元気な挨拶
+    Enthusiastic,
+}
+
+/// 挨拶メッセージの表示オプション
+///
+/// UIのバリエーションごとに関数を増やさず、各挨拶関数にこのレコードを
+/// 渡して出力を調整します。Swift側では全フィールドが省略可能です。
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct GreetingOptions {
+    /// 末尾に絵文字（👋）を付けるかどうか
+    #[uniffi(default = false)]
+    pub include_emoji: bool,
+    /// 全体を大文字に変換するかどうか
+    #[uniffi(default = false)]
+    pub uppercase: bool,
+    /// 最大文字数（超過分は「…」で切り詰め）
+    #[uniffi(default = None)]
+    pub max_length: Option<u32>,
+    /// 名前の前に付ける敬称（例: `"Dr."`, `"Ms."`）
+    #[uniffi(default = None)]
+    pub honorific: Option<String>,
+}
+
+/// 敬称が指定されていれば名前の前に付けます
+fn apply_honorific(name: String, options: &GreetingOptions) -> String {
+    match options.honorific.as_deref().map(str::trim) {
+        Some(honorific) if !honorific.is_empty() => {
+            let honorific: String = honorific.chars().filter(|c| !c.is_control()).collect();
+            format!("{} {}", honorific, name)
+        }
+        _ => name,
+    }
+}
+
+/// 絵文字・大文字化・文字数制限のオプションをメッセージに適用します
+fn apply_options(message: String, options: &GreetingOptions) -> String {
+    let mut message = if options.uppercase {
+        message.to_uppercase()
+    } else {
+        message
+    };
+    if options.include_emoji {
+        message.push_str(" 👋");
+    }
+    match options.max_length.map(|max| max as usize) {
+        Some(max) if message.chars().count() > max => {
+            if max == 0 {
+                return String::new();
+            }
+            let mut truncated: String = message.chars().take(max - 1).collect();
+            truncated.push('…');
+            truncated
+        }
+        _ => message,
+    }
+}
+
+/// 名前から制御文字を取り除き、前後の空白を削除します
+///
+/// 結果が空になった場合は既定の呼びかけを返します。
+fn sanitize_name(name: &str) -> String {
+    let cleaned: String = name.chars().filter(|c| !c.is_control()).collect();
+    let trimmed = cleaned.trim();
+    if trimmed.is_empty() {
+        DEFAULT_NAME.to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
 /// 挨拶メッセージを返します
-/// 
+///
 /// # Returns
 /// * 固定の挨拶メッセージ文字列
-/// 
+///
 /// # Example
 /// ```
 /// let message = say_hi();
@@ -12,7 +317,286 @@
 /// ```
 #[uniffi::export]
 pub fn say_hi() -> String {
-    "Hello mh from Rust!".to_string()
+    greet("mh", GreetingStyle::Casual, None)
+}
+
+/// 名前と文体を指定して挨拶メッセージを返します
+///
+/// 名前に含まれる制御文字（改行やNULなど）は取り除かれます。
+/// プロバイダーが登録されている場合は、ロケール`"en"`で問い合わせた結果が優先されます。
+///
+/// # Arguments
+/// * `name` - 挨拶する相手の名前
+/// * `style` - 挨拶の文体
+/// * `options` - 表示オプション（`None`の場合は既定値）
+///
+/// # Example
+/// ```
+/// let message = greet("Alice", GreetingStyle::Formal, None);
+/// assert_eq!(message, "Good day, Alice. Greetings from Rust.");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn greet(name: &str, style: GreetingStyle, options: Option<GreetingOptions>) -> String {
+    let options = options.unwrap_or_default();
+    let name = apply_honorific(sanitize_name(name), &options);
+    let message = provided_greeting(&name, "en").unwrap_or_else(|| match style {
+        GreetingStyle::Casual => format!("Hello {} from Rust!", name),
+        GreetingStyle::Formal => format!("Good day, {}. Greetings from Rust.", name),
+        GreetingStyle::Enthusiastic => format!("Hey {}!!! Awesome to see you from Rust!", name),
+    });
+    apply_options(message, &options)
+}
+
+/// ロケール識別子を対応言語の挨拶テンプレートに解決します
+///
+/// `ja_JP`や`es-MX`のような地域付きの識別子は言語部分で照合し、
+/// 大文字小文字は区別しません。対応していない言語の場合は英語になります。
+fn resolve_templates(locale: &str) -> &'static LocaleTemplates {
+    let language = locale
+        .trim()
+        .split(['-', '_', '.', '@'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    LOCALIZED_GREETINGS
+        .iter()
+        .find(|templates| templates.language == language)
+        .unwrap_or(&LOCALIZED_GREETINGS[0])
+}
+
+/// 指定されたロケールの言語で挨拶メッセージを返します
+///
+/// 対応言語は英語・日本語・スペイン語・フランス語・ドイツ語・中国語です。
+/// ロケールは言語部分（`ja-JP`なら`ja`）で照合され、未対応の場合は
+/// 英語にフォールバックします。
+///
+/// `set_greeting_provider`でプロバイダーが登録されている場合は、
+/// その結果が組み込みの挨拶文より優先されます。
+///
+/// # Arguments
+/// * `name` - 挨拶する相手の名前
+/// * `locale` - ロケール識別子（例: `"ja"`, `"es-MX"`, `"fr_CA"`）
+/// * `options` - 表示オプション（`None`の場合は既定値）
+///
+/// # Example
+/// ```
+/// let message = greet_localized("太郎", "ja-JP", None);
+/// assert_eq!(message, "こんにちは、太郎さん！");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn greet_localized(name: &str, locale: &str, options: Option<GreetingOptions>) -> String {
+    let options = options.unwrap_or_default();
+    let name = apply_honorific(sanitize_name(name), &options);
+    let message = provided_or_template(&name, locale, resolve_templates(locale).hello);
+    apply_options(message, &options)
+}
+
+/// 時間帯に応じた挨拶メッセージを返します
+///
+/// 5〜11時は朝、12〜17時は昼、それ以外は夜の挨拶になります。
+/// プロバイダーが登録されている場合は、その結果が組み込みの挨拶文より優先されます。
+///
+/// # Arguments
+/// * `name` - 挨拶する相手の名前
+/// * `hour` - ローカル時刻の「時」（0〜23）
+/// * `locale` - ロケール識別子
+/// * `options` - 表示オプション（`None`の場合は既定値）
+///
+/// # Errors
+/// * `GreetingError::InvalidHour` - `hour`が23を超える場合
+///
+/// # Example
+/// ```
+/// let message = greet_for_time("Alice", 9, "en", None)?;
+/// assert_eq!(message, "Good morning, Alice!");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn greet_for_time(
+    name: &str,
+    hour: u8,
+    locale: &str,
+    options: Option<GreetingOptions>,
+) -> Result<String, GreetingError> {
+    let options = options.unwrap_or_default();
+    let templates = resolve_templates(locale);
+    let template = match hour {
+        5..=11 => templates.morning,
+        12..=17 => templates.afternoon,
+        0..=4 | 18..=23 => templates.evening,
+        _ => return Err(GreetingError::InvalidHour(hour)),
+    };
+    let name = apply_honorific(sanitize_name(name), &options);
+    Ok(apply_options(provided_or_template(&name, locale, template), &options))
+}
+
+/// クロックから現在時刻を取得し、時間帯に応じた挨拶メッセージを返します
+///
+/// プロバイダーの扱いは`greet_for_time`と同じです。
+///
+/// # Arguments
+/// * `name` - 挨拶する相手の名前
+/// * `locale` - ロケール識別子
+/// * `clock` - 現在時刻を提供するクロック（通常は`SystemClock`）
+/// * `options` - 表示オプション（`None`の場合は既定値）
+///
+/// # Errors
+/// * `GreetingError::InvalidHour` - クロックが23を超える値を返した場合
+#[uniffi::export(default(options = None))]
+pub fn greet_now(
+    name: &str,
+    locale: &str,
+    clock: Arc<dyn Clock>,
+    options: Option<GreetingOptions>,
+) -> Result<String, GreetingError> {
+    greet_for_time(name, clock.current_hour(), locale, options)
+}
+
+/// 名前の一覧をロケールに応じた区切りと接続詞で連結します
+fn join_names(names: &[String], templates: &LocaleTemplates) -> String {
+    match names {
+        [] => String::new(),
+        [only] => only.clone(),
+        [first, second] => format!("{}{}{}", first, templates.pair_conjunction, second),
+        _ => {
+            let (last, rest) = names.split_last().unwrap_or((&names[0], &[]));
+            format!(
+                "{}{}{}",
+                rest.join(templates.list_separator),
+                templates.last_conjunction,
+                last
+            )
+        }
+    }
+}
+
+/// 複数人に向けた挨拶メッセージを返します
+///
+/// 名前はロケールに応じた区切りと接続詞で連結され（英語なら
+/// "Alice, Bob, and Carol"）、4名以上の場合は"Alice, Bob, and 3 others"の
+/// ように省略されます。空の名前は無視されます。
+/// プロバイダーが登録されている場合は、省略せずに連結した名前の一覧で問い合わせた結果が
+/// 優先されます。
+///
+/// # Arguments
+/// * `names` - 挨拶する相手の名前一覧
+/// * `locale` - ロケール識別子
+/// * `options` - 表示オプション（`None`の場合は既定値）。敬称は各名前に付きます
+///
+/// # Example
+/// ```
+/// let names = vec!["Alice".to_string(), "Bob".to_string(), "Carol".to_string()];
+/// assert_eq!(greet_group(names, "en", None), "Hello, Alice, Bob, and Carol!");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn greet_group(names: Vec<String>, locale: &str, options: Option<GreetingOptions>) -> String {
+    let options = options.unwrap_or_default();
+    let names: Vec<String> = names
+        .iter()
+        .filter(|name| !name.chars().all(|c| c.is_control() || c.is_whitespace()))
+        .map(|name| apply_honorific(sanitize_name(name), &options))
+        .collect();
+    let templates = resolve_templates(locale);
+    let joined = if names.is_empty() {
+        DEFAULT_NAME.to_string()
+    } else {
+        join_names(&names, templates)
+    };
+    let message = if let Some(message) = provided_greeting(&joined, locale) {
+        message
+    } else if names.len() > MAX_LISTED_NAMES {
+        // 先頭の名前のみを列挙し、残りを「ほか○名」のように省略する
+        let listed = MAX_LISTED_NAMES - 1;
+        templates
+            .others
+            .replace("{count}", &(names.len() - listed).to_string())
+            .replace("{names}", &names[..listed].join(templates.list_separator))
+    } else {
+        templates.hello.replace("{name}", &joined)
+    };
+    apply_options(message, &options)
+}
+
+/// 挨拶メッセージを同期的にダウンロードして検証します
+fn fetch_greeting_blocking(url: &str) -> Result<String, GreetingError> {
+    let lower = url.to_ascii_lowercase();
+    if !lower.starts_with("https://") && !lower.starts_with("http://") {
+        return Err(GreetingError::InvalidUrl(url.to_string()));
+    }
+
+    let request = HttpRequestParts {
+        method: "GET".to_string(),
+        url: url.to_string(),
+        headers: HashMap::new(),
+        body: Vec::new(),
+        payload_hash: None,
+    };
+    let response = send_request(&request, MAX_REMOTE_GREETING_BYTES).map_err(|e| match e {
+        TransportError::InvalidUrl { .. } => GreetingError::InvalidUrl(url.to_string()),
+        TransportError::Network { message } => GreetingError::Network(message),
+    })?;
+    if !response.is_success() {
+        return Err(GreetingError::HttpStatus(response.status));
+    }
+
+    if let Some(content_type) = response.header("Content-Type") {
+        let content_type = content_type.to_ascii_lowercase();
+        if !content_type.trim_start().starts_with("text/") {
+            return Err(GreetingError::InvalidContent(format!(
+                "unsupported content type: {}",
+                content_type
+            )));
+        }
+    }
+
+    // 上限+1バイトまで読まれるため、超過していればエラーにする
+    let body = response.body;
+    if body.len() as u64 > MAX_REMOTE_GREETING_BYTES {
+        return Err(GreetingError::InvalidContent("response is too large".to_string()));
+    }
+
+    let text = String::from_utf8(body)
+        .map_err(|_| GreetingError::InvalidContent("response is not valid UTF-8".to_string()))?;
+    let cleaned: String = text
+        .chars()
+        .filter(|c| *c == '\n' || !c.is_control())
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        return Err(GreetingError::InvalidContent("greeting is empty".to_string()));
+    }
+    if cleaned.chars().count() > MAX_REMOTE_GREETING_CHARS {
+        return Err(GreetingError::InvalidContent(format!(
+            "greeting exceeds {} characters",
+            MAX_REMOTE_GREETING_CHARS
+        )));
+    }
+    Ok(cleaned.to_string())
+}
+
+/// リモートから挨拶（バナー）メッセージを非同期に取得します
+///
+/// 通信はRust側のスレッドプールで実行されるため、呼び出し元のスレッドを
+/// ブロックしません。取得した内容はUTF-8のテキストであること、空でないこと、
+/// 280文字以内であることが検証され、改行以外の制御文字は除去されます。
+///
+/// `set_http_transport`でトランスポートが登録されている場合は、それを経由して取得します。
+///
+/// # Arguments
+/// * `url` - 挨拶メッセージを取得するHTTP(S)のURL
+///
+/// # Errors
+/// * `GreetingError::InvalidUrl` - URLが不正な場合
+/// * `GreetingError::Network` - 通信に失敗した場合
+/// * `GreetingError::HttpStatus` - サーバーがエラーステータスを返した場合
+/// * `GreetingError::InvalidContent` - 内容が挨拶メッセージとして不正な場合
+///
+/// # Example
+/// ```
+/// let banner = fetch_greeting("https://example.com/greeting.txt".to_string()).await?;
+/// ```
+#[uniffi::export]
+pub async fn fetch_greeting(url: String) -> Result<String, GreetingError> {
+    blocking::unblock(move || fetch_greeting_blocking(&url)).await
 }
 
 #[cfg(test)]
@@ -30,4 +614,277 @@ mod tests {
         let message = say_hi();
         assert!(!message.is_empty());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_greet_styles() {
+        assert_eq!(greet("Alice", GreetingStyle::Casual, None), "Hello Alice from Rust!");
+        assert_eq!(greet("Alice", GreetingStyle::Formal, None), "Good day, Alice. Greetings from Rust.");
+        assert_eq!(
+            greet("Alice", GreetingStyle::Enthusiastic, None),
+            "Hey Alice!!! Awesome to see you from Rust!"
+        );
+    }
+
+    #[test]
+    fn test_greet_strips_control_characters() {
+        let message = greet("Ali\nce\u{0}\u{1b}", GreetingStyle::Casual, None);
+        assert_eq!(message, "Hello Alice from Rust!");
+    }
+
+    #[test]
+    fn test_greet_empty_name() {
+        assert_eq!(greet("  \t ", GreetingStyle::Casual, None), "Hello friend from Rust!");
+    }
+
+    #[test]
+    fn test_greet_localized_languages() {
+        assert_eq!(greet_localized("Alice", "en", None), "Hello, Alice!");
+        assert_eq!(greet_localized("太郎", "ja", None), "こんにちは、太郎さん！");
+        assert_eq!(greet_localized("Ana", "es", None), "¡Hola, Ana!");
+        assert_eq!(greet_localized("Marie", "fr", None), "Bonjour, Marie !");
+        assert_eq!(greet_localized("Hans", "de", None), "Hallo, Hans!");
+        assert_eq!(greet_localized("小明", "zh", None), "你好，小明！");
+    }
+
+    #[test]
+    fn test_greet_localized_region_fallback() {
+        assert_eq!(greet_localized("Ana", "es-MX", None), "¡Hola, Ana!");
+        assert_eq!(greet_localized("太郎", "ja_JP", None), "こんにちは、太郎さん！");
+        assert_eq!(greet_localized("小明", "zh-Hant-TW", None), "你好，小明！");
+        assert_eq!(greet_localized("Marie", "FR_ca", None), "Bonjour, Marie !");
+    }
+
+    #[test]
+    fn test_greet_localized_unknown_locale() {
+        assert_eq!(greet_localized("Alice", "xx-YY", None), "Hello, Alice!");
+        assert_eq!(greet_localized("Alice", "", None), "Hello, Alice!");
+    }
+
+    struct FixedClock(u8);
+
+    impl Clock for FixedClock {
+        fn current_hour(&self) -> u8 {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_greet_for_time_periods() {
+        assert_eq!(greet_for_time("Alice", 9, "en", None).unwrap(), "Good morning, Alice!");
+        assert_eq!(greet_for_time("Alice", 14, "en", None).unwrap(), "Good afternoon, Alice!");
+        assert_eq!(greet_for_time("Alice", 21, "en", None).unwrap(), "Good evening, Alice!");
+        assert_eq!(greet_for_time("Alice", 2, "en", None).unwrap(), "Good evening, Alice!");
+        assert_eq!(greet_for_time("太郎", 7, "ja", None).unwrap(), "おはようございます、太郎さん！");
+        assert_eq!(greet_for_time("Hans", 19, "de-AT", None).unwrap(), "Guten Abend, Hans!");
+    }
+
+    #[test]
+    fn test_greet_for_time_invalid_hour() {
+        match greet_for_time("Alice", 24, "en", None) {
+            Err(GreetingError::InvalidHour(24)) => (),
+            _ => panic!("Expected InvalidHour error"),
+        }
+    }
+
+    #[test]
+    fn test_greet_now_with_injected_clock() {
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock(13));
+        assert_eq!(greet_now("Ana", "es", clock, None).unwrap(), "¡Buenas tardes, Ana!");
+    }
+
+    #[test]
+    fn test_system_clock_hour_in_range() {
+        let clock = SystemClock::new(-13 * 60);
+        assert!(clock.current_hour() < 24);
+    }
+
+    /// 特定の名前を含む場合にのみ挨拶文を返すテスト用プロバイダー
+    struct RemoteConfigProvider;
+
+    impl GreetingProvider for RemoteConfigProvider {
+        fn greeting(&self, name: String, locale: String) -> Option<String> {
+            name.contains("ProviderTest").then(|| format!("Welcome back, {} ({})", name, locale))
+        }
+    }
+
+    #[test]
+    fn test_greeting_provider_override_and_fallback() {
+        set_greeting_provider(Arc::new(RemoteConfigProvider));
+        assert_eq!(
+            greet_localized("ProviderTest", "en-US", None),
+            "Welcome back, ProviderTest (en-US)"
+        );
+        let options = GreetingOptions { uppercase: true, ..Default::default() };
+        assert_eq!(
+            greet("ProviderTest", GreetingStyle::Formal, None),
+            "Welcome back, ProviderTest (en)"
+        );
+        assert_eq!(
+            greet("ProviderTest", GreetingStyle::Casual, Some(options.clone())),
+            "WELCOME BACK, PROVIDERTEST (EN)"
+        );
+        assert_eq!(
+            greet_for_time("ProviderTest", 9, "ja", None).unwrap(),
+            "Welcome back, ProviderTest (ja)"
+        );
+        assert!(matches!(
+            greet_for_time("ProviderTest", 24, "ja", None),
+            Err(GreetingError::InvalidHour(24))
+        ));
+        let clock: Arc<dyn Clock> = Arc::new(FixedClock(20));
+        assert_eq!(
+            greet_now("ProviderTest", "de", clock, None).unwrap(),
+            "Welcome back, ProviderTest (de)"
+        );
+        let names = vec!["Alice".to_string(), "ProviderTest".to_string()];
+        assert_eq!(greet_group(names, "en", None), "Welcome back, Alice and ProviderTest (en)");
+        let names: Vec<String> =
+            ["A", "B", "C", "D", "ProviderTest"].iter().map(|n| n.to_string()).collect();
+        assert_eq!(
+            greet_group(names, "en", None),
+            "Welcome back, A, B, C, D, and ProviderTest (en)"
+        );
+
+        // プロバイダーがNoneを返した場合は組み込みの挨拶文
+        assert_eq!(greet_localized("Alice", "en", None), "Hello, Alice!");
+        assert_eq!(greet_for_time("Alice", 9, "en", None).unwrap(), "Good morning, Alice!");
+        assert_eq!(greet("Alice", GreetingStyle::Casual, None), "Hello Alice from Rust!");
+        let names = vec!["Alice".to_string(), "Bob".to_string()];
+        assert_eq!(greet_group(names, "en", None), "Hello, Alice and Bob!");
+
+        clear_greeting_provider();
+        assert_eq!(greet_localized("ProviderTest", "en", None), "Hello, ProviderTest!");
+    }
+
+    /// 1回だけ指定されたレスポンスを返すHTTPサーバーを起動し、URLを返します
+    fn serve_once(response: &'static str) -> String {
+        use std::io::{Read, Write};
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        format!("http://{}/greeting", addr)
+    }
+
+    #[test]
+    fn test_fetch_greeting_success() {
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: 19\r\nConnection: close\r\n\r\n  Welcome\u{7}, guest!\n",
+        );
+        let greeting = pollster::block_on(fetch_greeting(url)).unwrap();
+        assert_eq!(greeting, "Welcome, guest!");
+    }
+
+    #[test]
+    fn test_fetch_greeting_http_error() {
+        let url = serve_once(
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        );
+        match pollster::block_on(fetch_greeting(url)) {
+            Err(GreetingError::HttpStatus(404)) => (),
+            other => panic!("Expected HttpStatus error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fetch_greeting_rejects_non_text() {
+        let url = serve_once(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/octet-stream\r\nContent-Length: 2\r\nConnection: close\r\n\r\nhi",
+        );
+        match pollster::block_on(fetch_greeting(url)) {
+            Err(GreetingError::InvalidContent(_)) => (),
+            other => panic!("Expected InvalidContent error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_fetch_greeting_invalid_url() {
+        match pollster::block_on(fetch_greeting("ftp://example.com/greeting".to_string())) {
+            Err(GreetingError::InvalidUrl(_)) => (),
+            other => panic!("Expected InvalidUrl error, got {:?}", other),
+        }
+    }
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_greet_group_english() {
+        assert_eq!(greet_group(names(&["Alice"]), "en", None), "Hello, Alice!");
+        assert_eq!(greet_group(names(&["Alice", "Bob"]), "en", None), "Hello, Alice and Bob!");
+        assert_eq!(
+            greet_group(names(&["Alice", "Bob", "Carol"]), "en", None),
+            "Hello, Alice, Bob, and Carol!"
+        );
+        assert_eq!(
+            greet_group(names(&["Alice", "Bob", "Carol", "Dave", "Eve"]), "en-GB", None),
+            "Hello, Alice, Bob, and 3 others!"
+        );
+    }
+
+    #[test]
+    fn test_greet_group_localized_conjunctions() {
+        assert_eq!(greet_group(names(&["Ana", "Luis", "Eva"]), "es", None), "¡Hola, Ana, Luis y Eva!");
+        assert_eq!(greet_group(names(&["太郎", "花子"]), "ja", None), "こんにちは、太郎と花子さん！");
+        assert_eq!(
+            greet_group(names(&["太郎", "花子", "次郎", "三郎"]), "ja", None),
+            "こんにちは、太郎、花子さんほか2名！"
+        );
+        assert_eq!(
+            greet_group(names(&["甲", "乙\u{7}", " 丙 ", "丁"]), "zh", None),
+            "你好，甲、乙等2人！"
+        );
+        assert_eq!(greet_group(names(&["Hans", "Eva", "Max"]), "de", None), "Hallo, Hans, Eva und Max!");
+    }
+
+    #[test]
+    fn test_greet_group_empty() {
+        assert_eq!(greet_group(Vec::new(), "en", None), "Hello, friend!");
+        assert_eq!(greet_group(names(&["", " \n "]), "en", None), "Hello, friend!");
+    }
+
+    #[test]
+    fn test_greeting_options_emoji_and_uppercase() {
+        let options = GreetingOptions {
+            include_emoji: true,
+            uppercase: true,
+            ..Default::default()
+        };
+        assert_eq!(greet_localized("Alice", "en", Some(options)), "HELLO, ALICE! 👋");
+    }
+
+    #[test]
+    fn test_greeting_options_honorific() {
+        let options = GreetingOptions {
+            honorific: Some("Dr.".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            greet("Smith", GreetingStyle::Formal, Some(options.clone())),
+            "Good day, Dr. Smith. Greetings from Rust."
+        );
+        assert_eq!(
+            greet_group(names(&["Lee", "Kim"]), "en", Some(options)),
+            "Hello, Dr. Lee and Dr. Kim!"
+        );
+    }
+
+    #[test]
+    fn test_greeting_options_max_length() {
+        let options = GreetingOptions {
+            max_length: Some(10),
+            ..Default::default()
+        };
+        let message = greet_for_time("Alexander", 9, "en", Some(options)).unwrap();
+        assert_eq!(message, "Good morn…");
+        assert_eq!(message.chars().count(), 10);
+    }
+}
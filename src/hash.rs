@@ -0,0 +1,326 @@
+//! ハッシュ関数モジュール
+//!
+//! このモジュールは、SHA-256・SHA-512・MD5・BLAKE3のダイジェストを計算する関数を
+//! エクスポートします。SHA-2とMD5は16進数文字列を返す版と生のバイト列を返す版があります。
+//! MD5は暗号学的に安全ではないため、既存システムとの互換性やチェックサム用途に限定してください。
+//!
+//! 大きなファイルをメモリに読み込まずにハッシュ化するため、チャンク単位で
+//! データを渡せるストリーミング版の`Hasher`オブジェクトも提供します。
+
+use std::sync::{Arc, Mutex};
+
+use md5::Md5;
+use sha2::{Digest, Sha256, Sha512};
+use thiserror::Error;
+
+/// ハッシュ計算で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum HashError {
+    /// 鍵付きハッシュの鍵長が不正な場合
+    #[error("Invalid key length: {0} bytes (expected 32)")]
+    InvalidKeyLength(u32),
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// `finalize`済みの`Hasher`を使用した場合
+    #[error("Hasher has already been finalized")]
+    AlreadyFinalized,
+}
+
+/// ストリーミングハッシュで使用するアルゴリズム
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum HashAlgorithm {
+    /// SHA-256（32バイト）
+    Sha256,
+    /// SHA-512（64バイト）
+    Sha512,
+    /// MD5（16バイト、互換性用途のみ）
+    Md5,
+    /// BLAKE3（32バイト）
+    Blake3,
+}
+
+/// 任意のダイジェストアルゴリズムでハッシュ値を計算します
+fn digest<D: Digest>(data: &[u8]) -> Vec<u8> {
+    D::digest(data).to_vec()
+}
+
+/// SHA-256ハッシュを計算し、小文字の16進数文字列で返します
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+///
+/// # Example
+/// ```
+/// let hash = sha256(b"abc".to_vec());
+/// assert_eq!(hash, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+/// ```
+#[uniffi::export]
+pub fn sha256(data: Vec<u8>) -> String {
+    hex::encode(digest::<Sha256>(&data))
+}
+
+/// SHA-256ハッシュを計算し、32バイトのバイト列で返します
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+#[uniffi::export]
+pub fn sha256_bytes(data: Vec<u8>) -> Vec<u8> {
+    digest::<Sha256>(&data)
+}
+
+/// SHA-512ハッシュを計算し、小文字の16進数文字列で返します
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+#[uniffi::export]
+pub fn sha512(data: Vec<u8>) -> String {
+    hex::encode(digest::<Sha512>(&data))
+}
+
+/// SHA-512ハッシュを計算し、64バイトのバイト列で返します
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+#[uniffi::export]
+pub fn sha512_bytes(data: Vec<u8>) -> Vec<u8> {
+    digest::<Sha512>(&data)
+}
+
+/// MD5ハッシュを計算し、小文字の16進数文字列で返します
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+#[uniffi::export]
+pub fn md5(data: Vec<u8>) -> String {
+    hex::encode(digest::<Md5>(&data))
+}
+
+/// MD5ハッシュを計算し、16バイトのバイト列で返します
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+#[uniffi::export]
+pub fn md5_bytes(data: Vec<u8>) -> Vec<u8> {
+    digest::<Md5>(&data)
+}
+
+/// BLAKE3ハッシュ（32バイト）を計算し、小文字の16進数文字列で返します
+///
+/// SHA-256より高速なため、大きなコンテンツのハッシュに適しています。
+///
+/// # Arguments
+/// * `data` - ハッシュ化するデータ
+#[uniffi::export]
+pub fn blake3_hash(data: Vec<u8>) -> String {
+    blake3::hash(&data).to_hex().to_string()
+}
+
+/// 鍵付きBLAKE3ハッシュ（MACとして使用可能）を計算し、16進数文字列で返します
+///
+/// # Arguments
+/// * `key` - 32バイトの鍵
+/// * `data` - ハッシュ化するデータ
+///
+/// # Errors
+/// * `HashError::InvalidKeyLength` - 鍵が32バイトでない場合
+#[uniffi::export]
+pub fn blake3_keyed_hash(key: Vec<u8>, data: Vec<u8>) -> Result<String, HashError> {
+    let key: [u8; 32] = key
+        .as_slice()
+        .try_into()
+        .map_err(|_| HashError::InvalidKeyLength(key.len() as u32))?;
+    Ok(blake3::keyed_hash(&key, &data).to_hex().to_string())
+}
+
+/// アルゴリズムごとの計算途中の状態
+enum HasherState {
+    Sha256(Sha256),
+    Sha512(Sha512),
+    Md5(Md5),
+    Blake3(Box<blake3::Hasher>),
+}
+
+/// チャンク単位でデータを受け取るストリーミングハッシュ
+///
+/// 数GBのファイルでも一定のメモリでハッシュ化できます。
+/// `finalize`を呼び出した後は再利用できません。
+///
+/// # Example
+/// ```
+/// let hasher = Hasher::new(HashAlgorithm::Sha256);
+/// for chunk in chunks {
+///     hasher.update(chunk)?;
+/// }
+/// let digest = hasher.finalize()?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct Hasher {
+    algorithm: HashAlgorithm,
+    state: Mutex<Option<HasherState>>,
+}
+
+#[uniffi::export]
+impl Hasher {
+    /// 指定したアルゴリズムで新しいハッシュ計算を開始します
+    ///
+    /// # Arguments
+    /// * `algorithm` - 使用するハッシュアルゴリズム
+    #[uniffi::constructor]
+    pub fn new(algorithm: HashAlgorithm) -> Arc<Self> {
+        let state = match algorithm {
+            HashAlgorithm::Sha256 => HasherState::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => HasherState::Sha512(Sha512::new()),
+            HashAlgorithm::Md5 => HasherState::Md5(Md5::new()),
+            HashAlgorithm::Blake3 => HasherState::Blake3(Box::new(blake3::Hasher::new())),
+        };
+        Arc::new(Self {
+            algorithm,
+            state: Mutex::new(Some(state)),
+        })
+    }
+
+    /// このハッシュ計算で使用しているアルゴリズムを返します
+    pub fn algorithm(&self) -> HashAlgorithm {
+        self.algorithm
+    }
+
+    /// データのチャンクを追加します
+    ///
+    /// # Arguments
+    /// * `chunk` - 追加するデータ
+    ///
+    /// # Errors
+    /// * `HashError::AlreadyFinalized` - `finalize`済みの場合
+    /// * `HashError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn update(&self, chunk: Vec<u8>) -> Result<(), HashError> {
+        let mut state = self.state.lock()
+            .map_err(|_| HashError::MutexPoisoned)?;
+        match state.as_mut().ok_or(HashError::AlreadyFinalized)? {
+            HasherState::Sha256(hasher) => hasher.update(&chunk),
+            HasherState::Sha512(hasher) => hasher.update(&chunk),
+            HasherState::Md5(hasher) => hasher.update(&chunk),
+            HasherState::Blake3(hasher) => {
+                hasher.update(&chunk);
+            }
+        }
+        Ok(())
+    }
+
+    /// ハッシュ計算を完了し、ダイジェストをバイト列で返します
+    ///
+    /// # Errors
+    /// * `HashError::AlreadyFinalized` - 既に`finalize`済みの場合
+    /// * `HashError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn finalize(&self) -> Result<Vec<u8>, HashError> {
+        let mut state = self.state.lock()
+            .map_err(|_| HashError::MutexPoisoned)?;
+        let digest = match state.take().ok_or(HashError::AlreadyFinalized)? {
+            HasherState::Sha256(hasher) => hasher.finalize().to_vec(),
+            HasherState::Sha512(hasher) => hasher.finalize().to_vec(),
+            HasherState::Md5(hasher) => hasher.finalize().to_vec(),
+            HasherState::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+        };
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            sha256(b"abc".to_vec()),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha256(Vec::new()),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_sha512() {
+        assert_eq!(
+            sha512(b"abc".to_vec()),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+    }
+
+    #[test]
+    fn test_md5() {
+        assert_eq!(md5(b"abc".to_vec()), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(md5(Vec::new()), "d41d8cd98f00b204e9800998ecf8427e");
+    }
+
+    #[test]
+    fn test_raw_bytes_match_hex() {
+        let data = b"The quick brown fox".to_vec();
+        assert_eq!(sha256_bytes(data.clone()).len(), 32);
+        assert_eq!(sha512_bytes(data.clone()).len(), 64);
+        assert_eq!(md5_bytes(data.clone()).len(), 16);
+        assert_eq!(hex::encode(sha256_bytes(data.clone())), sha256(data));
+    }
+
+    #[test]
+    fn test_blake3_hash() {
+        assert_eq!(
+            blake3_hash(Vec::new()),
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+        );
+    }
+
+    #[test]
+    fn test_blake3_keyed_hash() {
+        let key = vec![7u8; 32];
+        let a = blake3_keyed_hash(key.clone(), b"data".to_vec()).unwrap();
+        let b = blake3_keyed_hash(vec![8u8; 32], b"data".to_vec()).unwrap();
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, b);
+        assert_ne!(a, blake3_hash(b"data".to_vec()));
+    }
+
+    #[test]
+    fn test_blake3_keyed_hash_invalid_key() {
+        match blake3_keyed_hash(vec![0u8; 16], Vec::new()) {
+            Err(HashError::InvalidKeyLength(16)) => (),
+            _ => panic!("Expected InvalidKeyLength error"),
+        }
+    }
+
+    #[test]
+    fn test_hasher_matches_one_shot() {
+        let data = b"The quick brown fox jumps over the lazy dog".to_vec();
+        let cases = [
+            (HashAlgorithm::Sha256, sha256_bytes(data.clone())),
+            (HashAlgorithm::Sha512, sha512_bytes(data.clone())),
+            (HashAlgorithm::Md5, md5_bytes(data.clone())),
+            (HashAlgorithm::Blake3, hex::decode(blake3_hash(data.clone())).unwrap()),
+        ];
+        for (algorithm, expected) in cases {
+            let hasher = Hasher::new(algorithm);
+            for chunk in data.chunks(7) {
+                hasher.update(chunk.to_vec()).unwrap();
+            }
+            assert_eq!(hasher.finalize().unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_hasher_already_finalized() {
+        let hasher = Hasher::new(HashAlgorithm::Sha256);
+        hasher.finalize().unwrap();
+        match hasher.update(b"more".to_vec()) {
+            Err(HashError::AlreadyFinalized) => (),
+            _ => panic!("Expected AlreadyFinalized error"),
+        }
+        match hasher.finalize() {
+            Err(HashError::AlreadyFinalized) => (),
+            _ => panic!("Expected AlreadyFinalized error"),
+        }
+    }
+}
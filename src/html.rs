@@ -0,0 +1,93 @@
+//! HTMLテキスト処理モジュール
+//!
+//! このモジュールは、テキストをHTMLに埋め込むためのエスケープと、
+//! 文字参照（実体参照・数値文字参照）のデコードを行う関数をエクスポートします。
+//! 通知やメッセージの表示処理で、プラットフォーム間で同じ規則を共有するために使用します。
+
+use std::borrow::Cow;
+
+/// テキストをHTMLの要素内容・属性値として安全に埋め込めるようにエスケープします
+///
+/// `&`・`<`・`>`・`"`・`'`をそれぞれ`&amp;`・`&lt;`・`&gt;`・`&quot;`・`&#39;`に
+/// 置き換えます。それ以外の文字はそのまま出力されます。
+///
+/// # Arguments
+/// * `text` - エスケープするテキスト
+///
+/// # Example
+/// ```
+/// assert_eq!(html_escape("<b>\"Tom\" & 'Jerry'</b>".to_string()),
+///            "&lt;b&gt;&quot;Tom&quot; &amp; &#39;Jerry&#39;&lt;/b&gt;");
+/// ```
+#[uniffi::export]
+pub fn html_escape(text: String) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// HTMLの文字参照をデコードします
+///
+/// HTML Living Standardで定義された名前付き文字参照（`&amp;`、`&hearts;`など）と、
+/// 10進・16進の数値文字参照（`&#169;`、`&#x1F600;`）に対応します。
+/// `;`で終わらない参照や、不正なコードポイント・制御文字を指す数値文字参照は
+/// 変換せずにそのまま残します。
+///
+/// # Arguments
+/// * `text` - デコードするテキスト
+///
+/// # Example
+/// ```
+/// assert_eq!(html_unescape("&lt;p&gt; &copy; &#x1F600;".to_string()), "<p> © 😀");
+/// ```
+#[uniffi::export]
+pub fn html_unescape(text: String) -> String {
+    match html_escape::decode_html_entities(&text) {
+        Cow::Borrowed(_) => text,
+        Cow::Owned(decoded) => decoded,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_html_escape() {
+        assert_eq!(
+            html_escape("<a href=\"x\">Tom & 'Jerry'</a>".to_string()),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+        assert_eq!(html_escape("こんにちは".to_string()), "こんにちは");
+    }
+
+    #[test]
+    fn test_html_unescape_named_and_numeric() {
+        assert_eq!(
+            html_unescape("&lt;b&gt;&amp;&quot;&apos;&nbsp;&hearts;&eacute;&rarr;".to_string()),
+            "<b>&\"'\u{a0}♥é→"
+        );
+        assert_eq!(html_unescape("&#169;&#x1F600;&#X41;".to_string()), "©😀A");
+    }
+
+    #[test]
+    fn test_html_unescape_leaves_invalid_references() {
+        let text = "AT&T &amp &unknown; &#xD800; &#0;";
+        assert_eq!(html_unescape(text.to_string()), text);
+    }
+
+    #[test]
+    fn test_html_escape_roundtrip() {
+        let text = "if (a < b && c > \"d\") { return 'e'; }";
+        assert_eq!(html_unescape(html_escape(text.to_string())), text);
+    }
+}
@@ -0,0 +1,781 @@
+//! HTTPレスポンスキャッシュモジュール
+//!
+//! このモジュールは、GETリクエストのレスポンスをディスクにキャッシュする`HttpCache`と、
+//! キャッシュとネットワークの使い分けを指定する`CachePolicy`をエクスポートします。
+//! オフライン時の挙動をアプリ全体で統一するために使用します。
+//!
+//! レスポンスの`Cache-Control`（`max-age`・`no-cache`・`no-store`）と`Age`から鮮度を判定し、
+//! 期限切れのキャッシュは`ETag`（`If-None-Match`）・`Last-Modified`（`If-Modified-Since`）
+//! による条件付きリクエストで再検証します。`Expires`と`Vary`は考慮せず、
+//! キャッシュのキーはURLです。
+//!
+//! キーにリクエストヘッダーを含めないため、`Authorization`または`Cookie`ヘッダーを持つ
+//! リクエストはキャッシュを使わずに常にネットワークから取得し、レスポンスも保存しません。
+//! ユーザーごとに異なるレスポンスを別のユーザーのリクエストに返さないためです。
+//!
+//! # ディレクトリ構成
+//! ```text
+//! <directory>/http_cache.sqlite    レスポンスのヘッダー・ボディ・保存時刻
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, OptionalExtension};
+use thiserror::Error;
+
+use crate::sigv4::HttpRequestParts;
+use crate::transport::{send_request, TransportError};
+
+/// レスポンスを保存するテーブルの作成SQL
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS responses (
+    url TEXT PRIMARY KEY,
+    status INTEGER NOT NULL,
+    headers TEXT NOT NULL,
+    body BLOB NOT NULL,
+    stored_at INTEGER NOT NULL,
+    max_age INTEGER,
+    last_accessed INTEGER NOT NULL,
+    size INTEGER NOT NULL
+) WITHOUT ROWID";
+
+/// キャッシュの対象とするレスポンスボディの最大サイズ（64 MiB）
+const MAX_BODY_LEN: u64 = 64 * 1024 * 1024;
+
+/// HTTPキャッシュの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum HttpCacheError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ディレクトリの作成に失敗した場合
+    #[error("Failed to access cache directory: {0}")]
+    IoError(String),
+    /// キャッシュのデータベースの操作に失敗した場合
+    #[error("Cache storage error: {0}")]
+    StorageError(String),
+    /// GET以外のメソッド、またはHTTP(S)以外のURLの場合
+    #[error("Invalid cache request: {0}")]
+    InvalidRequest(String),
+    /// 通信に失敗し、利用できるキャッシュもない場合
+    #[error("Network error: {0}")]
+    Network(String),
+}
+
+impl From<std::io::Error> for HttpCacheError {
+    fn from(error: std::io::Error) -> Self {
+        HttpCacheError::IoError(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for HttpCacheError {
+    fn from(error: rusqlite::Error) -> Self {
+        HttpCacheError::StorageError(error.to_string())
+    }
+}
+
+/// キャッシュとネットワークの使い分け
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum CachePolicy {
+    /// 有効期限内のキャッシュがあれば使い、なければネットワークから取得します。
+    /// 通信に失敗した場合は期限切れのキャッシュを返します
+    CacheFirst,
+    /// 常にネットワークから取得（期限切れのキャッシュは再検証）し、
+    /// 通信に失敗した場合はキャッシュを返します
+    NetworkFirst,
+    /// キャッシュがあれば期限切れでもすぐに返し、期限切れの場合は
+    /// バックグラウンドで再検証してキャッシュを更新します
+    StaleWhileRevalidate,
+}
+
+/// レスポンスの取得元
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum CacheSource {
+    /// ネットワークから取得した
+    Network,
+    /// キャッシュから返した（通信していない、または通信に失敗した）
+    Cache,
+    /// 条件付きリクエストで変更がない（304）ことを確認したキャッシュ
+    Revalidated,
+}
+
+/// `fetch_cached`が返すレスポンス
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct CachedResponse {
+    /// ステータスコード
+    pub status: u16,
+    /// ヘッダー（名前は小文字）
+    pub headers: HashMap<String, String>,
+    /// ボディ
+    pub body: Vec<u8>,
+    /// 取得元
+    pub source: CacheSource,
+    /// 有効期限が切れたキャッシュを返した場合は`true`
+    pub stale: bool,
+}
+
+/// 保存されたレスポンス
+#[derive(Debug, Clone)]
+struct Entry {
+    status: u16,
+    headers: HashMap<String, String>,
+    body: Vec<u8>,
+    stored_at: i64,
+    max_age: Option<i64>,
+}
+
+impl Entry {
+    /// 有効期限内かを返します
+    fn is_fresh(&self, now: i64) -> bool {
+        self.max_age.is_some_and(|max_age| now - self.stored_at < max_age)
+    }
+
+    fn into_response(self, source: CacheSource, stale: bool) -> CachedResponse {
+        CachedResponse {
+            status: self.status,
+            headers: self.headers,
+            body: self.body,
+            source,
+            stale,
+        }
+    }
+}
+
+/// ネットワークからの取得結果
+enum Fetched {
+    /// 変更がない（304）。新しいヘッダーを含みます
+    NotModified(HashMap<String, String>),
+    /// レスポンス
+    Response {
+        status: u16,
+        headers: HashMap<String, String>,
+        body: Vec<u8>,
+    },
+}
+
+/// 認証情報（`Authorization`・`Cookie`）を含むリクエストかを返します
+fn has_credentials(request: &HttpRequestParts) -> bool {
+    request.headers.keys().any(|name| {
+        name.eq_ignore_ascii_case("authorization") || name.eq_ignore_ascii_case("cookie")
+    })
+}
+
+/// 現在のUNIX時刻（秒）
+fn now_secs() -> i64 {
+    now_millis() / 1000
+}
+
+/// 現在のUNIX時刻（ミリ秒）。最後に使用した時刻の記録に使用します
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// `Cache-Control`から鮮度の有効期間を返します
+///
+/// 保存しない場合（`no-store`）は`Err(())`、有効期間の指定がない場合は`Ok(None)`を返します。
+fn freshness(headers: &HashMap<String, String>) -> Result<Option<i64>, ()> {
+    let mut max_age = None;
+    let Some(cache_control) = headers.get("cache-control") else { return Ok(None) };
+    for directive in cache_control.split(',') {
+        let (name, value) = directive.split_once('=').unwrap_or((directive, ""));
+        match name.trim().to_ascii_lowercase().as_str() {
+            "no-store" => return Err(()),
+            "no-cache" => max_age = Some(0),
+            "max-age" if max_age != Some(0) => {
+                max_age = value.trim().trim_matches('"').parse::<i64>().ok();
+            }
+            _ => {}
+        }
+    }
+    Ok(max_age)
+}
+
+/// `Age`ヘッダーの秒数を返します
+fn age(headers: &HashMap<String, String>) -> i64 {
+    headers
+        .get("age")
+        .and_then(|age| age.trim().parse::<i64>().ok())
+        .unwrap_or(0)
+}
+
+/// ディスク上のHTTPレスポンスキャッシュ
+///
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let cache = HttpCache::open(caches_dir, Some(50 * 1024 * 1024))?;
+/// let response = cache.fetch_cached(request, CachePolicy::StaleWhileRevalidate).await?;
+/// if response.stale {
+///     // 古いデータであることを表示する
+/// }
+/// ```
+#[derive(uniffi::Object)]
+pub struct HttpCache {
+    connection: Mutex<Connection>,
+    /// キャッシュの合計サイズの上限（超えた場合は最後に使用した時刻が古いものから削除）
+    max_bytes: Option<u64>,
+}
+
+impl HttpCache {
+    /// 保存されたレスポンスを読み出し、最後に使用した時刻を更新します
+    fn load(&self, url: &str) -> Result<Option<Entry>, HttpCacheError> {
+        let connection = self.connection.lock()
+            .map_err(|_| HttpCacheError::MutexPoisoned)?;
+        let row = connection
+            .query_row(
+                "SELECT status, headers, body, stored_at, max_age FROM responses WHERE url = ?1",
+                params![url],
+                |row| {
+                    Ok((
+                        row.get::<_, u16>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, Vec<u8>>(2)?,
+                        row.get::<_, i64>(3)?,
+                        row.get::<_, Option<i64>>(4)?,
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((status, headers, body, stored_at, max_age)) = row else { return Ok(None) };
+        connection.execute(
+            "UPDATE responses SET last_accessed = ?1 WHERE url = ?2",
+            params![now_millis(), url],
+        )?;
+        Ok(Some(Entry {
+            status,
+            headers: serde_json::from_str(&headers).unwrap_or_default(),
+            body,
+            stored_at,
+            max_age,
+        }))
+    }
+
+    /// レスポンスを保存し、上限を超えた分を削除します
+    fn store(&self, url: &str, entry: &Entry) -> Result<(), HttpCacheError> {
+        let headers = serde_json::to_string(&entry.headers)
+            .map_err(|e| HttpCacheError::StorageError(e.to_string()))?;
+        let mut connection = self.connection.lock()
+            .map_err(|_| HttpCacheError::MutexPoisoned)?;
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO responses
+                (url, status, headers, body, stored_at, max_age, last_accessed, size)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                url,
+                entry.status,
+                headers,
+                entry.body,
+                entry.stored_at,
+                entry.max_age,
+                now_millis(),
+                (entry.body.len() + headers.len()) as i64
+            ],
+        )?;
+        if let Some(max_bytes) = self.max_bytes {
+            let max_bytes = i64::try_from(max_bytes).unwrap_or(i64::MAX);
+            let mut total: i64 =
+                transaction.query_row("SELECT COALESCE(SUM(size), 0) FROM responses", [], |row| {
+                    row.get(0)
+                })?;
+            while total > max_bytes {
+                let (oldest, size): (String, i64) = transaction.query_row(
+                    "SELECT url, size FROM responses ORDER BY last_accessed, url LIMIT 1",
+                    [],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )?;
+                transaction.execute("DELETE FROM responses WHERE url = ?1", params![oldest])?;
+                total -= size;
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// ネットワークから取得します（キャッシュがあれば条件付きリクエストにします）
+    fn fetch_network(
+        &self,
+        request: &HttpRequestParts,
+        cached: Option<&Entry>,
+    ) -> Result<Fetched, HttpCacheError> {
+        let mut request = HttpRequestParts {
+            method: "GET".to_string(),
+            body: Vec::new(),
+            ..request.clone()
+        };
+        if let Some(cached) = cached {
+            if let Some(etag) = cached.headers.get("etag") {
+                request.headers.insert("If-None-Match".to_string(), etag.clone());
+            }
+            if let Some(last_modified) = cached.headers.get("last-modified") {
+                request.headers.insert("If-Modified-Since".to_string(), last_modified.clone());
+            }
+        }
+        let response = send_request(&request, MAX_BODY_LEN).map_err(|e| match e {
+            TransportError::InvalidUrl { url } => HttpCacheError::InvalidRequest(url),
+            TransportError::Network { message } => HttpCacheError::Network(message),
+        })?;
+        // ホスト側のトランスポートは大文字のヘッダー名を返す場合がある
+        let headers: HashMap<String, String> = response
+            .headers
+            .into_iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value))
+            .collect();
+        if response.status == 304 && cached.is_some() {
+            return Ok(Fetched::NotModified(headers));
+        }
+        Ok(Fetched::Response {
+            status: response.status,
+            headers,
+            body: response.body,
+        })
+    }
+
+    /// ネットワークから取得し、結果に応じてキャッシュを更新します
+    fn fetch_and_update(
+        &self,
+        request: &HttpRequestParts,
+        cached: Option<Entry>,
+    ) -> Result<CachedResponse, HttpCacheError> {
+        let now = now_secs();
+        match self.fetch_network(request, cached.as_ref())? {
+            Fetched::NotModified(headers) => {
+                let mut entry = cached.expect("304 is only returned for cached entries");
+                entry.headers.extend(headers);
+                entry.stored_at = now - age(&entry.headers);
+                entry.max_age = freshness(&entry.headers).unwrap_or(None);
+                self.store(&request.url, &entry)?;
+                Ok(entry.into_response(CacheSource::Revalidated, false))
+            }
+            Fetched::Response { status, headers, body } => {
+                let max_age = freshness(&headers);
+                let entry = Entry {
+                    status,
+                    stored_at: now - age(&headers),
+                    max_age: max_age.unwrap_or(None),
+                    headers,
+                    body,
+                };
+                let cacheable = status == 200 && entry.body.len() as u64 <= MAX_BODY_LEN;
+                match max_age {
+                    Ok(_) if cacheable => self.store(&request.url, &entry)?,
+                    // no-storeの場合は古いキャッシュも残さない
+                    Err(()) => {
+                        self.remove_entry(&request.url)?;
+                    }
+                    Ok(_) => {}
+                }
+                Ok(entry.into_response(CacheSource::Network, false))
+            }
+        }
+    }
+
+    /// 保存されたレスポンスを削除します
+    fn remove_entry(&self, url: &str) -> Result<bool, HttpCacheError> {
+        let connection = self.connection.lock()
+            .map_err(|_| HttpCacheError::MutexPoisoned)?;
+        Ok(connection.execute("DELETE FROM responses WHERE url = ?1", params![url])? > 0)
+    }
+
+    /// ポリシーに従ってレスポンスを返します（同期版）
+    fn fetch_cached_blocking(
+        self: Arc<Self>,
+        request: HttpRequestParts,
+        policy: CachePolicy,
+    ) -> Result<CachedResponse, HttpCacheError> {
+        if !request.method.eq_ignore_ascii_case("GET") {
+            return Err(HttpCacheError::InvalidRequest(format!(
+                "only GET requests can be cached: {}",
+                request.method
+            )));
+        }
+        let lower = request.url.to_ascii_lowercase();
+        if !lower.starts_with("https://") && !lower.starts_with("http://") {
+            return Err(HttpCacheError::InvalidRequest(request.url));
+        }
+        if has_credentials(&request) {
+            let Fetched::Response { status, headers, body } = self.fetch_network(&request, None)?
+            else {
+                unreachable!("304 is only returned for cached entries")
+            };
+            let entry = Entry { status, headers, body, stored_at: 0, max_age: None };
+            return Ok(entry.into_response(CacheSource::Network, false));
+        }
+        let cached = self.load(&request.url)?;
+        let fresh = cached.as_ref().is_some_and(|entry| entry.is_fresh(now_secs()));
+        match (policy, cached) {
+            (CachePolicy::CacheFirst, Some(entry)) if fresh => {
+                Ok(entry.into_response(CacheSource::Cache, false))
+            }
+            (CachePolicy::StaleWhileRevalidate, Some(entry)) => {
+                if !fresh {
+                    let cache = Arc::clone(&self);
+                    let stale = entry.clone();
+                    let spawned = thread::Builder::new()
+                        .name("mobile-http-cache".to_string())
+                        .spawn(move || {
+                            let _ = cache.fetch_and_update(&request, Some(stale));
+                        });
+                    drop(spawned);
+                }
+                Ok(entry.into_response(CacheSource::Cache, !fresh))
+            }
+            (_, cached) => match self.fetch_and_update(&request, cached.clone()) {
+                Err(HttpCacheError::Network(e)) => match cached {
+                    Some(entry) => Ok(entry.into_response(CacheSource::Cache, !fresh)),
+                    None => Err(HttpCacheError::Network(e)),
+                },
+                result => result,
+            },
+        }
+    }
+}
+
+#[uniffi::export]
+impl HttpCache {
+    /// ディレクトリを指定してキャッシュを開きます（存在しない場合は作成）
+    ///
+    /// # Arguments
+    /// * `directory` - キャッシュを保存するディレクトリ（アプリのCachesディレクトリなど）
+    /// * `max_bytes` - キャッシュの合計サイズの上限（`None`の場合は無制限）
+    ///
+    /// # Errors
+    /// * `HttpCacheError::IoError` - ディレクトリを作成できない場合
+    /// * `HttpCacheError::StorageError` - データベースを開けない場合
+    #[uniffi::constructor(default(max_bytes = None))]
+    pub fn open(directory: String, max_bytes: Option<u64>) -> Result<Arc<Self>, HttpCacheError> {
+        let directory = PathBuf::from(directory);
+        fs::create_dir_all(&directory)?;
+        let connection = Connection::open(directory.join("http_cache.sqlite"))?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(CREATE_TABLE)?;
+        Ok(Arc::new(Self {
+            connection: Mutex::new(connection),
+            max_bytes,
+        }))
+    }
+
+    /// ポリシーに従って、キャッシュまたはネットワークからレスポンスを返します
+    ///
+    /// ステータスが200で`no-store`が指定されていないレスポンスを保存します。
+    /// `Authorization`・`Cookie`ヘッダーを持つリクエストはポリシーによらずキャッシュを使わず、
+    /// レスポンスも保存しません。
+    /// 200以外のステータス（404など）もエラーではなくレスポンスとして返します。
+    /// `set_http_transport`で登録されたトランスポートがあれば、それを経由して通信します。
+    ///
+    /// # Arguments
+    /// * `request` - GETリクエスト（`body`と`payload_hash`は使用しません）
+    /// * `policy` - キャッシュとネットワークの使い分け
+    ///
+    /// # Errors
+    /// * `HttpCacheError::InvalidRequest` - GET以外のメソッド、またはHTTP(S)以外のURLの場合
+    /// * `HttpCacheError::Network` - 通信に失敗し、利用できるキャッシュもない場合
+    /// * `HttpCacheError::StorageError` - キャッシュの読み書きに失敗した場合
+    /// * `HttpCacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub async fn fetch_cached(
+        self: Arc<Self>,
+        request: HttpRequestParts,
+        policy: CachePolicy,
+    ) -> Result<CachedResponse, HttpCacheError> {
+        blocking::unblock(move || self.fetch_cached_blocking(request, policy)).await
+    }
+
+    /// 指定したURLのキャッシュを削除し、削除した場合は`true`を返します
+    ///
+    /// # Errors
+    /// * `HttpCacheError::StorageError` - 削除に失敗した場合
+    /// * `HttpCacheError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn remove(&self, url: String) -> Result<bool, HttpCacheError> {
+        self.remove_entry(&url)
+    }
+
+    /// すべてのキャッシュを削除します
+    ///
+    /// # Errors
+    /// * `remove`と同じエラー
+    pub fn clear(&self) -> Result<(), HttpCacheError> {
+        let connection = self.connection.lock()
+            .map_err(|_| HttpCacheError::MutexPoisoned)?;
+        connection.execute("DELETE FROM responses", [])?;
+        Ok(())
+    }
+
+    /// キャッシュの合計サイズ（ヘッダーとボディのバイト数）を返します
+    ///
+    /// # Errors
+    /// * `remove`と同じエラー
+    pub fn size_bytes(&self) -> Result<u64, HttpCacheError> {
+        let connection = self.connection.lock()
+            .map_err(|_| HttpCacheError::MutexPoisoned)?;
+        let total: i64 =
+            connection.query_row("SELECT COALESCE(SUM(size), 0) FROM responses", [], |row| {
+                row.get(0)
+            })?;
+        Ok(total as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Instant;
+
+    fn temp_dir(name: &str) -> String {
+        let dir = std::env::temp_dir()
+            .join(format!("mobile_http_cache_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.to_string_lossy().to_string()
+    }
+
+    fn get(url: &str) -> HttpRequestParts {
+        HttpRequestParts {
+            method: "GET".to_string(),
+            url: url.to_string(),
+            headers: HashMap::new(),
+            body: Vec::new(),
+            payload_hash: None,
+        }
+    }
+
+    /// リクエスト数を数えるサーバーを起動します
+    ///
+    /// `If-None-Match: "v1"`を受け取ると304を、それ以外は`cache_control`と`ETag: "v1"`を
+    /// 付けた200を返します。ボディにはリクエストの通し番号を入れます。
+    fn serve(cache_control: &'static str) -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { continue };
+                let mut data = Vec::new();
+                let mut buf = [0u8; 1024];
+                while let Ok(len) = stream.read(&mut buf) {
+                    if len == 0 {
+                        break;
+                    }
+                    data.extend_from_slice(&buf[..len]);
+                    if data.windows(4).any(|w| w == b"\r\n\r\n") {
+                        break;
+                    }
+                }
+                let request = String::from_utf8_lossy(&data).to_lowercase();
+                let number = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    format!(
+                        "HTTP/1.1 304 Not Modified\r\nCache-Control: {}\r\nETag: \"v1\"\r\n\
+                         Connection: close\r\n\r\n",
+                        cache_control
+                    )
+                } else {
+                    let body = format!("body {}", number);
+                    format!(
+                        "HTTP/1.1 200 OK\r\nCache-Control: {}\r\nETag: \"v1\"\r\n\
+                         Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        cache_control,
+                        body.len(),
+                        body
+                    )
+                };
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+        (format!("http://{}/resource", addr), count)
+    }
+
+    fn wait_until(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "condition was not met in time");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_cache_first_uses_fresh_entry() {
+        let (url, count) = serve("max-age=60");
+        let cache = HttpCache::open(temp_dir("cache_first"), None).unwrap();
+
+        let first = pollster::block_on(
+            Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::CacheFirst),
+        )
+        .unwrap();
+        assert_eq!(first.source, CacheSource::Network);
+        assert_eq!(first.body, b"body 1");
+        assert_eq!(first.headers.get("etag").map(String::as_str), Some("\"v1\""));
+
+        let second = pollster::block_on(
+            Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::CacheFirst),
+        )
+        .unwrap();
+        assert_eq!(second.source, CacheSource::Cache);
+        assert!(!second.stale);
+        assert_eq!(second.body, b"body 1");
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_network_first_revalidates_with_etag() {
+        let (url, count) = serve("no-cache");
+        let cache = HttpCache::open(temp_dir("network_first"), None).unwrap();
+
+        pollster::block_on(Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::NetworkFirst))
+            .unwrap();
+        let second = pollster::block_on(
+            Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::NetworkFirst),
+        )
+        .unwrap();
+        assert_eq!(second.source, CacheSource::Revalidated);
+        assert_eq!(second.body, b"body 1");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_credentialed_requests_bypass_cache() {
+        let (url, count) = serve("max-age=60");
+        let cache = HttpCache::open(temp_dir("credentials"), None).unwrap();
+
+        for (name, value) in [("Authorization", "Bearer alice"), ("cookie", "session=alice")] {
+            let mut request = get(&url);
+            request.headers.insert(name.to_string(), value.to_string());
+            let response = pollster::block_on(
+                Arc::clone(&cache).fetch_cached(request, CachePolicy::CacheFirst),
+            )
+            .unwrap();
+            assert_eq!(response.source, CacheSource::Network);
+        }
+        assert_eq!(cache.size_bytes().unwrap(), 0);
+
+        // 認証情報のないリクエストに認証付きのレスポンスを返さない
+        let anonymous = pollster::block_on(
+            Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::CacheFirst),
+        )
+        .unwrap();
+        assert_eq!(anonymous.source, CacheSource::Network);
+        assert_eq!(anonymous.body, b"body 3");
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_falls_back_to_stale_entry_when_offline() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/resource", listener.local_addr().unwrap());
+        drop(listener);
+        let cache = HttpCache::open(temp_dir("offline"), None).unwrap();
+        cache
+            .store(
+                &url,
+                &Entry {
+                    status: 200,
+                    headers: HashMap::new(),
+                    body: b"offline".to_vec(),
+                    stored_at: 0,
+                    max_age: Some(60),
+                },
+            )
+            .unwrap();
+
+        let response = pollster::block_on(
+            Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::NetworkFirst),
+        )
+        .unwrap();
+        assert_eq!(response.source, CacheSource::Cache);
+        assert!(response.stale);
+        assert_eq!(response.body, b"offline");
+
+        cache.clear().unwrap();
+        match pollster::block_on(cache.fetch_cached(get(&url), CachePolicy::CacheFirst)) {
+            Err(HttpCacheError::Network(_)) => (),
+            other => panic!("Expected Network error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_stale_while_revalidate_updates_in_background() {
+        let (url, count) = serve("max-age=0");
+        let cache = HttpCache::open(temp_dir("swr"), None).unwrap();
+        cache
+            .store(
+                &url,
+                &Entry {
+                    status: 200,
+                    headers: HashMap::new(),
+                    body: b"old".to_vec(),
+                    stored_at: 0,
+                    max_age: Some(60),
+                },
+            )
+            .unwrap();
+
+        let response = pollster::block_on(
+            Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::StaleWhileRevalidate),
+        )
+        .unwrap();
+        assert_eq!(response.source, CacheSource::Cache);
+        assert!(response.stale);
+        assert_eq!(response.body, b"old");
+
+        wait_until(|| cache.load(&url).unwrap().is_some_and(|entry| entry.body == b"body 1"));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_no_store_and_errors_are_not_cached() {
+        let (url, count) = serve("no-store");
+        let cache = HttpCache::open(temp_dir("no_store"), None).unwrap();
+        for _ in 0..2 {
+            let response = pollster::block_on(
+                Arc::clone(&cache).fetch_cached(get(&url), CachePolicy::CacheFirst),
+            )
+            .unwrap();
+            assert_eq!(response.source, CacheSource::Network);
+        }
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(cache.size_bytes().unwrap(), 0);
+
+        let mut post = get(&url);
+        post.method = "POST".to_string();
+        match pollster::block_on(cache.fetch_cached(post, CachePolicy::NetworkFirst)) {
+            Err(HttpCacheError::InvalidRequest(_)) => (),
+            other => panic!("Expected InvalidRequest error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = HttpCache::open(temp_dir("evict"), Some(40)).unwrap();
+        let entry = Entry {
+            status: 200,
+            headers: HashMap::new(),
+            body: vec![0u8; 15],
+            stored_at: now_secs(),
+            max_age: Some(60),
+        };
+        cache.store("http://example.com/a", &entry).unwrap();
+        cache.store("http://example.com/b", &entry).unwrap();
+        cache.store("http://example.com/c", &entry).unwrap();
+        assert!(cache.load("http://example.com/a").unwrap().is_none());
+        assert!(cache.load("http://example.com/c").unwrap().is_some());
+        assert!(cache.size_bytes().unwrap() <= 40);
+        assert!(cache.remove("http://example.com/c".to_string()).unwrap());
+        assert!(!cache.remove("http://example.com/c".to_string()).unwrap());
+
+        // i64に収まらない上限は無制限として扱う
+        let cache = HttpCache::open(temp_dir("evict_unbounded"), Some(u64::MAX)).unwrap();
+        cache.store("http://example.com/a", &entry).unwrap();
+        assert!(cache.load("http://example.com/a").unwrap().is_some());
+    }
+}
@@ -0,0 +1,957 @@
+//! iCalendar（ICS）解析モジュール
+//!
+//! このモジュールは、招待メールの添付ファイルなどのiCalendar（RFC 5545）テキストから
+//! 予定（VEVENT）を取り出す`parse_ics`をエクスポートします。繰り返しルール（RRULE）は
+//! 指定した期間内の各回に展開し、除外日（EXDATE）と個別に変更された回（RECURRENCE-ID）を
+//! 反映します。
+//!
+//! 対応する繰り返しルールは`FREQ`（`DAILY`・`WEEKLY`・`MONTHLY`・`YEARLY`）、`INTERVAL`、
+//! `COUNT`、`UNTIL`、`BYDAY`、`BYMONTHDAY`、`BYMONTH`、`BYSETPOS`、`WKST`です。
+//! `TZID`はIANAタイムゾーン名として解決し、VTIMEZONEコンポーネントは使用しません。
+
+use std::collections::HashSet;
+
+use chrono::{Datelike, Months, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+use thiserror::Error;
+
+use crate::datetime::{DateTimeError, Timestamp};
+use crate::duration::parse_duration;
+use crate::scan::unescape_vcard;
+use crate::timezone::Zone;
+
+/// 1つの予定から展開する回数の上限
+const MAX_OCCURRENCES: usize = 1000;
+
+/// 繰り返しルールを展開する周期（日・週・月・年）の数の上限
+const MAX_PERIODS: i64 = 100_000;
+
+/// iCalendarの解析で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum IcsError {
+    /// コンポーネントの構造が正しくない、または必須のプロパティがない場合
+    #[error("Invalid calendar: {0}")]
+    InvalidCalendar(String),
+    /// 日時・期間の値が正しくない場合
+    #[error("Invalid date-time: {0}")]
+    InvalidDateTime(String),
+    /// `TZID`がIANAタイムゾーン名として解決できない場合
+    #[error("Invalid time zone: {0}")]
+    InvalidTimeZone(String),
+    /// 繰り返しルールが正しくない、またはサポートしない指定を含む場合
+    #[error("Invalid recurrence rule: {0}")]
+    InvalidRecurrence(String),
+}
+
+/// 予定（繰り返しの予定は1回分）
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct CalendarEvent {
+    /// 予定の識別子（UID）
+    pub uid: Option<String>,
+    /// 件名（SUMMARY）
+    pub summary: Option<String>,
+    /// 説明（DESCRIPTION）
+    pub description: Option<String>,
+    /// 場所（LOCATION）
+    pub location: Option<String>,
+    /// 主催者のアドレス（ORGANIZER、`mailto:`は除きます）
+    pub organizer: Option<String>,
+    /// 出席者のアドレス（ATTENDEE、`mailto:`は除きます）
+    pub attendees: Vec<String>,
+    /// 開始日時。終日の予定とタイムゾーンのない日時はUTCの同じ時刻（オフセット0）で表します
+    pub start: Timestamp,
+    /// 終了日時（この時刻を含みません）
+    pub end: Timestamp,
+    /// 終日の予定の場合は`true`
+    pub all_day: bool,
+    /// 繰り返しの予定の1回の場合は`true`
+    pub recurring: bool,
+}
+
+/// コンテンツ行（`NAME;PARAM=VALUE:value`）
+struct Property {
+    /// プロパティ名（大文字）
+    name: String,
+    /// パラメータ（名前は大文字、値の引用符は除く）
+    params: Vec<(String, String)>,
+    /// 値
+    value: String,
+}
+
+impl Property {
+    /// パラメータの値を返します
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+}
+
+/// 引用符の外にある区切り文字で文字列を分割します
+fn split_unquoted(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut quoted = false;
+    for (i, c) in text.char_indices() {
+        if c == '"' {
+            quoted = !quoted;
+        } else if c == separator && !quoted {
+            parts.push(&text[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+/// コンテンツ行を名前・パラメータ・値に分解します
+fn parse_property(line: &str) -> Option<Property> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(i, c)| {
+        if c == '"' {
+            quoted = !quoted;
+        }
+        (c == ':' && !quoted).then_some(i)
+    })?;
+    let mut head = split_unquoted(&line[..colon], ';').into_iter();
+    let name = head.next()?.trim().to_ascii_uppercase();
+    let params = head
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim().to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        .collect();
+    Some(Property { name, params, value: line[colon + 1..].to_string() })
+}
+
+/// VEVENTごとのプロパティを取り出します
+fn parse_components(text: &str) -> Result<Vec<Vec<Property>>, IcsError> {
+    // 空白またはタブで始まる行は前の行の続き（折り返し）
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut stack: Vec<String> = Vec::new();
+    let mut current = Vec::new();
+    let mut found_calendar = false;
+    for property in lines.iter().filter_map(|line| parse_property(line)) {
+        let in_event = stack.len() == 2 && stack[0] == "VCALENDAR" && stack[1] == "VEVENT";
+        match property.name.as_str() {
+            "BEGIN" => {
+                let component = property.value.trim().to_ascii_uppercase();
+                found_calendar |= stack.is_empty() && component == "VCALENDAR";
+                stack.push(component);
+            }
+            "END" => {
+                let component = property.value.trim().to_ascii_uppercase();
+                if stack.pop().as_deref() != Some(component.as_str()) {
+                    return Err(IcsError::InvalidCalendar(format!("unexpected END:{}", component)));
+                }
+                if in_event {
+                    events.push(std::mem::take(&mut current));
+                }
+            }
+            _ if in_event => current.push(property),
+            _ => {}
+        }
+    }
+    if let Some(component) = stack.last() {
+        return Err(IcsError::InvalidCalendar(format!("missing END:{}", component)));
+    }
+    if !found_calendar {
+        return Err(IcsError::InvalidCalendar("missing BEGIN:VCALENDAR".to_string()));
+    }
+    Ok(events)
+}
+
+/// 日時プロパティの値と、その時刻を解釈するタイムゾーン
+#[derive(Clone, Copy)]
+struct EventTime {
+    /// 壁時計の日時（終日の場合は0時）
+    local: NaiveDateTime,
+    /// タイムゾーン（終日・タイムゾーンなしの場合はUTC）
+    zone: Zone,
+    /// 日付のみの値の場合は`true`
+    all_day: bool,
+}
+
+impl EventTime {
+    /// 壁時計の日時をUNIX時刻に変換します
+    fn to_unix(self, local: &NaiveDateTime) -> Result<i64, IcsError> {
+        self.zone.to_unix(local).map_err(invalid_date_time)
+    }
+
+    /// UNIX時刻をこのタイムゾーンのオフセット付きの時点に変換します
+    fn timestamp(self, unix_seconds: i64) -> Result<Timestamp, IcsError> {
+        let utc_offset_seconds = self.zone.offset_at(unix_seconds).map_err(invalid_date_time)?;
+        Ok(Timestamp { unix_seconds, nanoseconds: 0, utc_offset_seconds })
+    }
+}
+
+/// 日時の処理のエラーを変換します
+fn invalid_date_time(error: DateTimeError) -> IcsError {
+    IcsError::InvalidDateTime(error.to_string())
+}
+
+/// 固定桁の数字を解析します
+fn digits(text: &str, range: std::ops::Range<usize>) -> Option<u32> {
+    let part = text.get(range)?;
+    part.bytes().all(|b| b.is_ascii_digit()).then(|| part.parse().ok())?
+}
+
+/// `YYYYMMDD`形式の日付を解析します
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    if text.len() != 8 {
+        return None;
+    }
+    let year = i32::try_from(digits(text, 0..4)?).ok()?;
+    NaiveDate::from_ymd_opt(year, digits(text, 4..6)?, digits(text, 6..8)?)
+}
+
+/// `YYYYMMDDTHHMMSS`形式の日時を解析します
+fn parse_local_date_time(text: &str) -> Option<NaiveDateTime> {
+    let (date, time) = text.split_once(['T', 't'])?;
+    if time.len() != 6 {
+        return None;
+    }
+    parse_date(date)?.and_hms_opt(digits(time, 0..2)?, digits(time, 2..4)?, digits(time, 4..6)?)
+}
+
+/// 日時プロパティの値（`DTSTART`・`EXDATE`などの1つの値）を解析します
+fn parse_time_value(value: &str, property: &Property) -> Result<EventTime, IcsError> {
+    let value = value.trim();
+    let invalid = || IcsError::InvalidDateTime(format!("{}:{}", property.name, value));
+    let date_only = property.param("VALUE").is_some_and(|kind| kind.eq_ignore_ascii_case("DATE"));
+    if date_only || value.len() == 8 {
+        let date = parse_date(value).ok_or_else(invalid)?;
+        let local = date.and_time(NaiveTime::MIN);
+        return Ok(EventTime { local, zone: Zone::Fixed(0), all_day: true });
+    }
+    let (value, utc) = match value.strip_suffix(['Z', 'z']) {
+        Some(value) => (value, true),
+        None => (value, false),
+    };
+    let local = parse_local_date_time(value).ok_or_else(invalid)?;
+    let zone = match property.param("TZID") {
+        Some(tzid) if !utc => {
+            Zone::parse(tzid).map_err(|_| IcsError::InvalidTimeZone(tzid.to_string()))?
+        }
+        _ => Zone::Fixed(0),
+    };
+    Ok(EventTime { local, zone, all_day: false })
+}
+
+/// 繰り返しの周期
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// `BYDAY`の1つの値（`MO`・`1MO`・`-1FR`）
+#[derive(Debug, Clone, Copy)]
+struct ByDay {
+    /// 月・年の中での順番（負の値は末尾から）
+    ordinal: Option<i32>,
+    /// 曜日
+    weekday: Weekday,
+}
+
+/// 繰り返しの終了日時
+#[derive(Debug, Clone, Copy)]
+enum Until {
+    /// UTCで指定された時点（UNIX時刻）
+    Instant(i64),
+    /// 予定のタイムゾーンでの壁時計の日時
+    Local(NaiveDateTime),
+}
+
+/// 繰り返しルール（RRULE）
+#[derive(Debug, Clone)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<Until>,
+    by_day: Vec<ByDay>,
+    by_month_day: Vec<i32>,
+    by_month: Vec<u32>,
+    by_set_pos: Vec<i32>,
+    week_start: Weekday,
+}
+
+/// 2文字の曜日を解析します
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    match text.to_ascii_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// `week_start`から`weekday`までの日数（0〜6）を返します
+fn days_since(week_start: Weekday, weekday: Weekday) -> u32 {
+    (weekday.num_days_from_monday() + 7 - week_start.num_days_from_monday()) % 7
+}
+
+/// 0以外の整数の一覧を解析します（`BYMONTHDAY`・`BYSETPOS`）
+fn parse_ordinals(rule: &str, value: &str, limit: u32) -> Result<Vec<i32>, IcsError> {
+    value
+        .split(',')
+        .map(|item| {
+            item.trim()
+                .parse::<i32>()
+                .ok()
+                .filter(|n| *n != 0 && n.unsigned_abs() <= limit)
+                .ok_or_else(|| IcsError::InvalidRecurrence(rule.to_string()))
+        })
+        .collect()
+}
+
+/// RRULEの値を解析します
+fn parse_rrule(value: &str) -> Result<RecurrenceRule, IcsError> {
+    let invalid = || IcsError::InvalidRecurrence(value.to_string());
+    let mut frequency = None;
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+        by_month_day: Vec::new(),
+        by_month: Vec::new(),
+        by_set_pos: Vec::new(),
+        week_start: Weekday::Mon,
+    };
+    for part in value.trim().split(';').filter(|part| !part.is_empty()) {
+        let (key, item) = part.split_once('=').ok_or_else(invalid)?;
+        match key.trim().to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = Some(match item.trim().to_ascii_uppercase().as_str() {
+                    "DAILY" => Frequency::Daily,
+                    "WEEKLY" => Frequency::Weekly,
+                    "MONTHLY" => Frequency::Monthly,
+                    "YEARLY" => Frequency::Yearly,
+                    _ => return Err(IcsError::InvalidRecurrence(format!("FREQ={}", item))),
+                })
+            }
+            "INTERVAL" => {
+                rule.interval = item.trim().parse().ok().filter(|n| *n > 0).ok_or_else(invalid)?
+            }
+            "COUNT" => rule.count = Some(item.trim().parse().map_err(|_| invalid())?),
+            "UNTIL" => {
+                let item = item.trim();
+                rule.until = Some(if let Some(date) = parse_date(item) {
+                    Until::Local(date.and_hms_opt(23, 59, 59).ok_or_else(invalid)?)
+                } else if let Some(utc) = item.strip_suffix(['Z', 'z']) {
+                    let utc = parse_local_date_time(utc).ok_or_else(invalid)?;
+                    Until::Instant(utc.and_utc().timestamp())
+                } else {
+                    Until::Local(parse_local_date_time(item).ok_or_else(invalid)?)
+                })
+            }
+            "BYDAY" => {
+                for day in item.split(',') {
+                    let day = day.trim();
+                    let split = day.len().checked_sub(2).ok_or_else(invalid)?;
+                    let (ordinal, weekday) = day.split_at_checked(split).ok_or_else(invalid)?;
+                    let ordinal = match ordinal.trim_start_matches('+') {
+                        "" => None,
+                        ordinal => Some(parse_ordinals(part, ordinal, 53)?[0]),
+                    };
+                    let weekday = parse_weekday(weekday).ok_or_else(invalid)?;
+                    rule.by_day.push(ByDay { ordinal, weekday });
+                }
+            }
+            "BYMONTHDAY" => rule.by_month_day = parse_ordinals(part, item, 31)?,
+            "BYMONTH" => {
+                rule.by_month = parse_ordinals(part, item, 12)?
+                    .into_iter()
+                    .map(|month| u32::try_from(month).map_err(|_| invalid()))
+                    .collect::<Result<_, _>>()?
+            }
+            "BYSETPOS" => rule.by_set_pos = parse_ordinals(part, item, 366)?,
+            "WKST" => rule.week_start = parse_weekday(item.trim()).ok_or_else(invalid)?,
+            // 拡張（X-で始まる名前）は無視する
+            key if key.starts_with("X-") => {}
+            _ => {
+                return Err(IcsError::InvalidRecurrence(format!("unsupported rule part: {}", part)))
+            }
+        }
+    }
+    rule.frequency = frequency.ok_or_else(|| IcsError::InvalidRecurrence("missing FREQ".into()))?;
+    if rule.count.is_some() && rule.until.is_some() {
+        return Err(IcsError::InvalidRecurrence("COUNT and UNTIL are exclusive".to_string()));
+    }
+    Ok(rule)
+}
+
+/// 期間内で`BYDAY`に一致する日付を返します（順番の指定は期間内で数えます）
+fn weekdays_between(first: NaiveDate, last: NaiveDate, by_day: &[ByDay]) -> Vec<NaiveDate> {
+    let mut dates = Vec::new();
+    for day in by_day {
+        let matching: Vec<NaiveDate> = first
+            .iter_days()
+            .take_while(|date| *date <= last)
+            .filter(|date| date.weekday() == day.weekday)
+            .collect();
+        match day.ordinal {
+            None => dates.extend(matching),
+            Some(ordinal) => {
+                let index = if ordinal > 0 { ordinal - 1 } else { matching.len() as i32 + ordinal };
+                dates.extend(usize::try_from(index).ok().and_then(|i| matching.get(i)));
+            }
+        }
+    }
+    dates
+}
+
+impl RecurrenceRule {
+    /// 月の中で繰り返しに一致する日付を返します
+    fn month_dates(&self, first: NaiveDate, start_day: u32) -> Option<Vec<NaiveDate>> {
+        let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+        let dates = if !self.by_month_day.is_empty() {
+            let last_day = last.day() as i32;
+            self.by_month_day
+                .iter()
+                .map(|&day| if day > 0 { day } else { last_day + 1 + day })
+                .filter_map(|day| u32::try_from(day).ok().and_then(|day| first.with_day(day)))
+                .filter(|date| {
+                    self.by_day.is_empty()
+                        || self.by_day.iter().any(|d| d.weekday == date.weekday())
+                })
+                .collect()
+        } else if !self.by_day.is_empty() {
+            weekdays_between(first, last, &self.by_day)
+        } else {
+            first.with_day(start_day).into_iter().collect()
+        };
+        Some(dates)
+    }
+
+    /// `period`番目の周期に含まれる日付を返します（範囲外の場合は`None`）
+    fn period_dates(&self, start: NaiveDate, period: i64) -> Option<Vec<NaiveDate>> {
+        let step = period * i64::from(self.interval);
+        let in_months =
+            |date: &NaiveDate| self.by_month.is_empty() || self.by_month.contains(&date.month());
+        let mut dates = match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(TimeDelta::try_days(step)?)?;
+                let last_day = date.checked_add_months(Months::new(1))?.with_day(1)?.pred_opt()?;
+                let from_end = date.day() as i32 - last_day.day() as i32 - 1;
+                let day_matches = self.by_month_day.is_empty()
+                    || self.by_month_day.contains(&(date.day() as i32))
+                    || self.by_month_day.contains(&from_end);
+                let weekday_matches = self.by_day.is_empty()
+                    || self.by_day.iter().any(|day| day.weekday == date.weekday());
+                (day_matches && weekday_matches).then_some(date).into_iter().collect()
+            }
+            Frequency::Weekly => {
+                let elapsed = days_since(self.week_start, start.weekday());
+                let week = start
+                    .checked_sub_signed(TimeDelta::days(elapsed.into()))?
+                    .checked_add_signed(TimeDelta::try_weeks(step)?)?;
+                let weekdays: Vec<Weekday> = if self.by_day.is_empty() {
+                    vec![start.weekday()]
+                } else {
+                    self.by_day.iter().map(|day| day.weekday).collect()
+                };
+                weekdays
+                    .into_iter()
+                    .filter_map(|weekday| {
+                        let offset = days_since(self.week_start, weekday);
+                        week.checked_add_signed(TimeDelta::days(offset.into()))
+                    })
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = Months::new(u32::try_from(step).ok()?);
+                let first = start.with_day(1)?.checked_add_months(months)?;
+                self.month_dates(first, start.day())?
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(i64::from(start.year()) + step).ok()?;
+                if self.by_month.is_empty()
+                    && self.by_month_day.is_empty()
+                    && !self.by_day.is_empty()
+                {
+                    let first = NaiveDate::from_ymd_opt(year, 1, 1)?;
+                    let last = NaiveDate::from_ymd_opt(year, 12, 31)?;
+                    weekdays_between(first, last, &self.by_day)
+                } else {
+                    let months: Vec<u32> = if !self.by_month.is_empty() {
+                        self.by_month.clone()
+                    } else if !self.by_month_day.is_empty() {
+                        (1..=12).collect()
+                    } else {
+                        vec![start.month()]
+                    };
+                    let mut dates = Vec::new();
+                    for month in months {
+                        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+                        dates.extend(self.month_dates(first, start.day())?);
+                    }
+                    dates
+                }
+            }
+        };
+        dates.retain(in_months);
+        dates.sort();
+        dates.dedup();
+        if self.by_set_pos.is_empty() {
+            return Some(dates);
+        }
+        let mut selected: Vec<NaiveDate> = self
+            .by_set_pos
+            .iter()
+            .filter_map(|&position| {
+                let index = if position > 0 { position - 1 } else { dates.len() as i32 + position };
+                usize::try_from(index).ok().and_then(|i| dates.get(i).copied())
+            })
+            .collect();
+        selected.sort();
+        selected.dedup();
+        Some(selected)
+    }
+
+    /// 開始日時から順に各回の壁時計の日時を`visit`に渡します
+    ///
+    /// 開始日時は常に最初の回になります。`visit`が`false`を返すと展開を終了します。
+    fn expand(
+        &self,
+        start: NaiveDateTime,
+        mut visit: impl FnMut(NaiveDateTime) -> Result<bool, IcsError>,
+    ) -> Result<(), IcsError> {
+        if !visit(start)? {
+            return Ok(());
+        }
+        for period in 0..MAX_PERIODS {
+            let Some(dates) = self.period_dates(start.date(), period) else {
+                break;
+            };
+            for date in dates {
+                let local = date.and_time(start.time());
+                if local > start && !visit(local)? {
+                    return Ok(());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 解析済みの予定
+struct RawEvent {
+    uid: Option<String>,
+    summary: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    organizer: Option<String>,
+    attendees: Vec<String>,
+    start: EventTime,
+    /// 長さ（秒）
+    duration: i64,
+    rule: Option<RecurrenceRule>,
+    /// 除外する回の開始時刻（UNIX時刻）
+    exception_dates: HashSet<i64>,
+    /// 個別に変更された回の場合、元の開始時刻（UNIX時刻）
+    recurrence_id: Option<i64>,
+}
+
+/// `mailto:`を除いたアドレスを返します
+fn calendar_address(value: &str) -> String {
+    let value = value.trim();
+    match value.get(..7) {
+        Some(scheme) if scheme.eq_ignore_ascii_case("mailto:") => value[7..].to_string(),
+        _ => value.to_string(),
+    }
+}
+
+/// VEVENTのプロパティから予定を作成します
+fn parse_event(properties: &[Property]) -> Result<RawEvent, IcsError> {
+    let find = |name: &str| properties.iter().find(|property| property.name == name);
+    let text = |name: &str| {
+        find(name).map(|property| unescape_vcard(property.value.trim())).filter(|s| !s.is_empty())
+    };
+    let dtstart = find("DTSTART")
+        .ok_or_else(|| IcsError::InvalidCalendar("VEVENT without DTSTART".to_string()))?;
+    let start = parse_time_value(&dtstart.value, dtstart)?;
+    let start_unix = start.to_unix(&start.local)?;
+
+    let duration = if let Some(dtend) = find("DTEND") {
+        let end = parse_time_value(&dtend.value, dtend)?;
+        end.to_unix(&end.local)? - start_unix
+    } else if let Some(duration) = find("DURATION") {
+        let value = duration.value.trim();
+        let seconds = parse_duration(value.trim_start_matches('+').to_string())
+            .map_err(|error| IcsError::InvalidDateTime(format!("DURATION:{}: {}", value, error)))?;
+        seconds.round() as i64
+    } else if start.all_day {
+        86_400
+    } else {
+        0
+    };
+    if duration < 0 {
+        return Err(IcsError::InvalidDateTime("DTEND is before DTSTART".to_string()));
+    }
+
+    let mut exception_dates = HashSet::new();
+    for property in properties.iter().filter(|property| property.name == "EXDATE") {
+        for value in property.value.split(',').filter(|value| !value.trim().is_empty()) {
+            let time = parse_time_value(value, property)?;
+            exception_dates.insert(time.to_unix(&time.local)?);
+        }
+    }
+    let recurrence_id = match find("RECURRENCE-ID") {
+        Some(property) => {
+            let time = parse_time_value(&property.value, property)?;
+            Some(time.to_unix(&time.local)?)
+        }
+        None => None,
+    };
+
+    Ok(RawEvent {
+        uid: text("UID"),
+        summary: text("SUMMARY"),
+        description: text("DESCRIPTION"),
+        location: text("LOCATION"),
+        organizer: find("ORGANIZER").map(|property| calendar_address(&property.value)),
+        attendees: properties
+            .iter()
+            .filter(|property| property.name == "ATTENDEE")
+            .map(|property| calendar_address(&property.value))
+            .collect(),
+        start,
+        duration,
+        rule: find("RRULE").map(|property| parse_rrule(&property.value)).transpose()?,
+        exception_dates,
+        recurrence_id,
+    })
+}
+
+/// 予定の1回分を作成します
+fn occurrence(
+    event: &RawEvent,
+    start_unix: i64,
+    recurring: bool,
+) -> Result<CalendarEvent, IcsError> {
+    Ok(CalendarEvent {
+        uid: event.uid.clone(),
+        summary: event.summary.clone(),
+        description: event.description.clone(),
+        location: event.location.clone(),
+        organizer: event.organizer.clone(),
+        attendees: event.attendees.clone(),
+        start: event.start.timestamp(start_unix)?,
+        end: event.start.timestamp(start_unix + event.duration)?,
+        all_day: event.start.all_day,
+        recurring,
+    })
+}
+
+/// iCalendarテキストから予定を取り出します
+///
+/// 繰り返しの予定は`range_start`から`range_end`までの期間に重なる各回に展開し、
+/// 除外日（EXDATE）と、同じUIDで個別に変更された回（RECURRENCE-ID）の元の回は除きます。
+/// 期間を省略した場合は制限なしとみなしますが、1つの予定から展開する回数は
+/// 最大1000回です。結果は開始日時の順に並びます。
+///
+/// # Arguments
+/// * `text` - iCalendarテキスト（`BEGIN:VCALENDAR`から`END:VCALENDAR`まで）
+/// * `range_start` - 期間の開始（省略時は制限なし）
+/// * `range_end` - 期間の終了（この時刻を含みません。省略時は制限なし）
+///
+/// # Errors
+/// * `IcsError::InvalidCalendar` - コンポーネントの構造が正しくない、またはDTSTARTがない場合
+/// * `IcsError::InvalidDateTime` - 日時・期間の値が正しくない場合
+/// * `IcsError::InvalidTimeZone` - `TZID`がIANAタイムゾーン名でない場合
+/// * `IcsError::InvalidRecurrence` - 繰り返しルールが正しくない、またはサポートしない指定を含む場合
+///
+/// # Example
+/// ```
+/// let events = parse_ics(invite_text, Some(month_start), Some(month_end))?;
+/// for event in events {
+///     println!("{:?} {:?}", event.start, event.summary);
+/// }
+/// ```
+#[uniffi::export(default(range_start = None, range_end = None))]
+pub fn parse_ics(
+    text: String,
+    range_start: Option<Timestamp>,
+    range_end: Option<Timestamp>,
+) -> Result<Vec<CalendarEvent>, IcsError> {
+    let range_start = range_start.map(|timestamp| timestamp.unix_seconds);
+    let range_end = range_end.map(|timestamp| timestamp.unix_seconds);
+    let overlaps = |start: i64, end: i64| {
+        range_end.is_none_or(|range_end| start < range_end)
+            && range_start.is_none_or(|range_start| end > range_start || start >= range_start)
+    };
+
+    let events = parse_components(&text)?
+        .iter()
+        .map(|properties| parse_event(properties))
+        .collect::<Result<Vec<_>, _>>()?;
+    let overridden: HashSet<(&str, i64)> = events
+        .iter()
+        .filter_map(|event| Some((event.uid.as_deref()?, event.recurrence_id?)))
+        .collect();
+
+    let mut occurrences = Vec::new();
+    for event in &events {
+        let Some(rule) = &event.rule else {
+            let start = event.start.to_unix(&event.start.local)?;
+            if overlaps(start, start + event.duration) {
+                occurrences.push(occurrence(event, start, event.recurrence_id.is_some())?);
+            }
+            continue;
+        };
+        let mut generated = 0;
+        let mut collected = 0;
+        rule.expand(event.start.local, |local| {
+            generated += 1;
+            let start = event.start.to_unix(&local)?;
+            let past_until = match rule.until {
+                Some(Until::Instant(until)) => start > until,
+                Some(Until::Local(until)) => local > until,
+                None => false,
+            };
+            if rule.count.is_some_and(|count| generated > count)
+                || past_until
+                || range_end.is_some_and(|range_end| start >= range_end)
+            {
+                return Ok(false);
+            }
+            let is_overridden =
+                event.uid.as_deref().is_some_and(|uid| overridden.contains(&(uid, start)));
+            if event.exception_dates.contains(&start)
+                || is_overridden
+                || !overlaps(start, start + event.duration)
+            {
+                return Ok(true);
+            }
+            occurrences.push(occurrence(event, start, true)?);
+            collected += 1;
+            Ok(collected < MAX_OCCURRENCES)
+        })?;
+    }
+    occurrences.sort_by_key(|event| event.start.unix_seconds);
+    Ok(occurrences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::parse_datetime;
+
+    fn at(text: &str) -> Timestamp {
+        parse_datetime(text.to_string(), None).unwrap()
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    /// 各回の開始日時をタイムゾーンの壁時計の時刻で返します
+    fn starts(events: &[CalendarEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| event.start.local_datetime().unwrap().format("%Y-%m-%d %H:%M").to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_ics_single_event() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:abc@example.com\r\n\
+             SUMMARY:Design review\\, round 2\r\n\
+             DESCRIPTION:Agenda:\\n1. Mockups\\n2. Q&A that continues on\r\n  a folded line\r\n\
+             LOCATION:Room 4\r\n\
+             ORGANIZER;CN=\"Doe, Jane\":mailto:jane@example.com\r\n\
+             ATTENDEE;ROLE=REQ-PARTICIPANT:MAILTO:bob@example.com\r\n\
+             ATTENDEE:mailto:carol@example.com\r\n\
+             DTSTART;TZID=Asia/Tokyo:20240115T100000\r\n\
+             DTEND;TZID=Asia/Tokyo:20240115T113000\r\n\
+             BEGIN:VALARM\r\n\
+             ACTION:DISPLAY\r\n\
+             DESCRIPTION:Reminder\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n",
+        );
+        let events = parse_ics(text, None, None).unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.uid.as_deref(), Some("abc@example.com"));
+        assert_eq!(event.summary.as_deref(), Some("Design review, round 2"));
+        assert_eq!(
+            event.description.as_deref(),
+            Some("Agenda:\n1. Mockups\n2. Q&A that continues on a folded line")
+        );
+        assert_eq!(event.location.as_deref(), Some("Room 4"));
+        assert_eq!(event.organizer.as_deref(), Some("jane@example.com"));
+        assert_eq!(event.attendees, vec!["bob@example.com", "carol@example.com"]);
+        assert_eq!(event.start, at("2024-01-15T10:00:00+09:00"));
+        assert_eq!(event.end.unix_seconds - event.start.unix_seconds, 5400);
+        assert!(!event.all_day);
+        assert!(!event.recurring);
+    }
+
+    #[test]
+    fn test_parse_ics_all_day_and_duration() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\nSUMMARY:Holiday\r\nDTSTART;VALUE=DATE:20240101\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nSUMMARY:Call\r\nDTSTART:20231231T150000Z\r\nDURATION:PT45M\r\n\
+             END:VEVENT\r\n",
+        );
+        let events = parse_ics(text, None, None).unwrap();
+        assert_eq!(events[0].summary.as_deref(), Some("Call"));
+        assert_eq!(events[0].end, at("2023-12-31T15:45:00Z"));
+        assert!(events[1].all_day);
+        assert_eq!(events[1].start, at("2024-01-01T00:00:00Z"));
+        assert_eq!(events[1].end, at("2024-01-02T00:00:00Z"));
+    }
+
+    #[test]
+    fn test_parse_ics_weekly_rule_with_exceptions() {
+        let text = calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:standup\r\n\
+             SUMMARY:Standup\r\n\
+             DTSTART;TZID=America/New_York:20240304T093000\r\n\
+             DURATION:PT15M\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20240320T000000Z\r\n\
+             EXDATE;TZID=America/New_York:20240311T093000\r\n\
+             END:VEVENT\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:standup\r\n\
+             SUMMARY:Standup (moved)\r\n\
+             RECURRENCE-ID;TZID=America/New_York:20240313T093000\r\n\
+             DTSTART;TZID=America/New_York:20240313T110000\r\n\
+             DURATION:PT15M\r\n\
+             END:VEVENT\r\n",
+        );
+        let events = parse_ics(text, None, None).unwrap();
+        // 3月10日に夏時間が始まっても壁時計の9:30を保つ
+        assert_eq!(
+            starts(&events),
+            vec!["2024-03-04 09:30", "2024-03-06 09:30", "2024-03-13 11:00", "2024-03-18 09:30"]
+        );
+        assert_eq!(events[0].start.utc_offset_seconds, -5 * 3600);
+        assert_eq!(events[3].start.utc_offset_seconds, -4 * 3600);
+        assert_eq!(events[2].summary.as_deref(), Some("Standup (moved)"));
+        assert!(events.iter().all(|event| event.recurring));
+    }
+
+    #[test]
+    fn test_parse_ics_range_and_monthly_rules() {
+        let event = |rule: &str| {
+            calendar(&format!(
+                "BEGIN:VEVENT\r\nDTSTART:20240131T120000Z\r\nRRULE:{}\r\nEND:VEVENT\r\n",
+                rule
+            ))
+        };
+        let range = |rule: &str, from: &str, to: &str| {
+            starts(&parse_ics(event(rule), Some(at(from)), Some(at(to))).unwrap())
+        };
+        // 31日のない月は飛ばす
+        assert_eq!(
+            range("FREQ=MONTHLY", "2024-01-01T00:00:00Z", "2024-06-01T00:00:00Z"),
+            vec!["2024-01-31 12:00", "2024-03-31 12:00", "2024-05-31 12:00"]
+        );
+        // 月の最終日
+        assert_eq!(
+            range("FREQ=MONTHLY;BYMONTHDAY=-1", "2024-02-01T00:00:00Z", "2024-05-01T00:00:00Z"),
+            vec!["2024-02-29 12:00", "2024-03-31 12:00", "2024-04-30 12:00"]
+        );
+        // 第2火曜日（終了のないルールも期間で打ち切る）
+        assert_eq!(
+            range("FREQ=MONTHLY;BYDAY=2TU", "2024-03-01T00:00:00Z", "2024-05-01T00:00:00Z"),
+            vec!["2024-03-12 12:00", "2024-04-09 12:00"]
+        );
+        // 月の最後の平日
+        assert_eq!(
+            range(
+                "FREQ=MONTHLY;BYDAY=MO,TU,WE,TH,FR;BYSETPOS=-1",
+                "2024-03-01T00:00:00Z",
+                "2024-07-01T00:00:00Z"
+            ),
+            vec!["2024-03-29 12:00", "2024-04-30 12:00", "2024-05-31 12:00", "2024-06-28 12:00"]
+        );
+        assert_eq!(
+            range("FREQ=DAILY;INTERVAL=10;COUNT=3", "2024-01-01T00:00:00Z", "2025-01-01T00:00:00Z"),
+            vec!["2024-01-31 12:00", "2024-02-10 12:00", "2024-02-20 12:00"]
+        );
+        // 11月の第4木曜日
+        assert_eq!(
+            range(
+                "FREQ=YEARLY;BYMONTH=11;BYDAY=4TH",
+                "2024-01-01T00:00:00Z",
+                "2026-01-01T00:00:00Z"
+            ),
+            vec!["2024-01-31 12:00", "2024-11-28 12:00", "2025-11-27 12:00"]
+        );
+        // 期間の開始より前に始まって期間に重なる回を含む
+        let text = calendar(
+            "BEGIN:VEVENT\r\nDTSTART;VALUE=DATE:20240101\r\nRRULE:FREQ=DAILY\r\nEND:VEVENT\r\n",
+        );
+        let events =
+            parse_ics(text, Some(at("2024-01-10T12:00:00Z")), Some(at("2024-01-11T00:00:00Z")));
+        assert_eq!(starts(&events.unwrap()), vec!["2024-01-10 00:00"]);
+    }
+
+    #[test]
+    fn test_parse_ics_errors() {
+        match parse_ics("BEGIN:VEVENT\r\nEND:VEVENT\r\n".to_string(), None, None) {
+            Err(IcsError::InvalidCalendar(_)) => (),
+            other => panic!("Expected InvalidCalendar error, got {:?}", other),
+        }
+        match parse_ics(calendar("BEGIN:VEVENT\r\nDTSTART:20240101T000000Z\r\n"), None, None) {
+            Err(IcsError::InvalidCalendar(_)) => (),
+            other => panic!("Expected InvalidCalendar error, got {:?}", other),
+        }
+        match parse_ics(calendar("BEGIN:VEVENT\r\nSUMMARY:x\r\nEND:VEVENT\r\n"), None, None) {
+            Err(IcsError::InvalidCalendar(_)) => (),
+            other => panic!("Expected InvalidCalendar error, got {:?}", other),
+        }
+        match parse_ics(
+            calendar("BEGIN:VEVENT\r\nDTSTART:2024-01-01\r\nEND:VEVENT\r\n"),
+            None,
+            None,
+        ) {
+            Err(IcsError::InvalidDateTime(_)) => (),
+            other => panic!("Expected InvalidDateTime error, got {:?}", other),
+        }
+        let text = calendar(
+            "BEGIN:VEVENT\r\nDTSTART;TZID=Tokyo Standard Time:20240101T090000\r\nEND:VEVENT\r\n",
+        );
+        match parse_ics(text, None, None) {
+            Err(IcsError::InvalidTimeZone(_)) => (),
+            other => panic!("Expected InvalidTimeZone error, got {:?}", other),
+        }
+        for rule in [
+            "FREQ=HOURLY",
+            "FREQ=DAILY;BYHOUR=9",
+            "INTERVAL=2",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=MONTHLY;BYMONTHDAY=-2147483648",
+            "FREQ=MONTHLY;BYDAY=-2147483648MO",
+        ] {
+            let text = calendar(&format!(
+                "BEGIN:VEVENT\r\nDTSTART:20240101T000000Z\r\nRRULE:{}\r\nEND:VEVENT\r\n",
+                rule
+            ));
+            match parse_ics(text, None, None) {
+                Err(IcsError::InvalidRecurrence(_)) => (),
+                other => panic!("Expected InvalidRecurrence error for {}, got {:?}", rule, other),
+            }
+        }
+    }
+}
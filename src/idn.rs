@@ -0,0 +1,119 @@
+//! 国際化ドメイン名（IDN）変換モジュール
+//!
+//! このモジュールは、国際化ドメイン名をUTS #46の処理規則に従って
+//! ASCII形式（Punycode、`xn--`ラベル）とUnicode形式の間で変換する関数を
+//! エクスポートします。リンクプレビューでのドメインの表示と検証に使用します。
+//!
+//! 変換時には大文字・小文字や全角・半角などの正規化が行われます
+//! （例: `ＥＸＡＭＰＬＥ．ｃｏｍ` → `example.com`）。ラベル中の位置による
+//! ハイフンの制限は適用せず、URLのホスト名に使用できない文字を含む場合はエラーになります。
+
+use idna::uts46::{AsciiDenyList, DnsLength, Hyphens, Uts46};
+use thiserror::Error;
+
+/// IDN変換で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum IdnError {
+    /// ドメイン名がUTS #46の規則に従っていない場合（空、使用できない文字、長すぎるラベルなど）
+    #[error("Invalid domain name: {0}")]
+    InvalidDomain(String),
+}
+
+/// ドメイン名をASCII形式（Punycode）に変換します
+///
+/// DNSの長さの制限（ラベルは63バイト、全体は253バイトまで）も検証します。
+/// 末尾のルートを表す`.`は許可されます。
+///
+/// # Arguments
+/// * `domain` - 変換するドメイン名
+///
+/// # Errors
+/// * `IdnError::InvalidDomain` - ドメイン名として不正な場合
+///
+/// # Example
+/// ```
+/// assert_eq!(to_ascii_idn("例え.テスト".to_string())?, "xn--r8jz45g.xn--zckzah");
+/// ```
+#[uniffi::export]
+pub fn to_ascii_idn(domain: String) -> Result<String, IdnError> {
+    Uts46::new()
+        .to_ascii(
+            domain.as_bytes(),
+            AsciiDenyList::URL,
+            Hyphens::Allow,
+            DnsLength::VerifyAllowRootDot,
+        )
+        .map(|ascii| ascii.into_owned())
+        .map_err(|_| IdnError::InvalidDomain(domain))
+}
+
+/// ドメイン名をUnicode形式に変換します
+///
+/// `xn--`で始まるラベルをデコードし、表示用の形式にします。
+/// 不正なPunycodeを含む場合は、置換文字を含む文字列を返さずにエラーにします。
+///
+/// # Arguments
+/// * `domain` - 変換するドメイン名（ASCII形式・Unicode形式のどちらでも可）
+///
+/// # Errors
+/// * `IdnError::InvalidDomain` - ドメイン名として不正な場合
+///
+/// # Example
+/// ```
+/// assert_eq!(to_unicode_idn("xn--r8jz45g.xn--zckzah".to_string())?, "例え.テスト");
+/// ```
+#[uniffi::export]
+pub fn to_unicode_idn(domain: String) -> Result<String, IdnError> {
+    if domain.is_empty() {
+        return Err(IdnError::InvalidDomain(domain));
+    }
+    let (unicode, result) =
+        Uts46::new().to_unicode(domain.as_bytes(), AsciiDenyList::URL, Hyphens::Allow);
+    match result {
+        Ok(()) => Ok(unicode.into_owned()),
+        Err(_) => Err(IdnError::InvalidDomain(domain)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_ascii_idn() {
+        assert_eq!(to_ascii_idn("例え.テスト".to_string()).unwrap(), "xn--r8jz45g.xn--zckzah");
+        assert_eq!(to_ascii_idn("Bücher.Example".to_string()).unwrap(), "xn--bcher-kva.example");
+        assert_eq!(to_ascii_idn("ＥＸＡＭＰＬＥ．ｃｏｍ".to_string()).unwrap(), "example.com");
+        assert_eq!(to_ascii_idn("example.com.".to_string()).unwrap(), "example.com.");
+    }
+
+    #[test]
+    fn test_to_unicode_idn() {
+        assert_eq!(to_unicode_idn("xn--r8jz45g.xn--zckzah".to_string()).unwrap(), "例え.テスト");
+        assert_eq!(to_unicode_idn("XN--BCHER-KVA.example".to_string()).unwrap(), "bücher.example");
+        assert_eq!(to_unicode_idn("Straße.de".to_string()).unwrap(), "straße.de");
+    }
+
+    #[test]
+    fn test_idn_roundtrip() {
+        let ascii = to_ascii_idn("日本語.jp".to_string()).unwrap();
+        assert!(ascii.starts_with("xn--"));
+        assert_eq!(to_unicode_idn(ascii).unwrap(), "日本語.jp");
+    }
+
+    #[test]
+    fn test_idn_errors() {
+        let long_label = format!("{}.com", "a".repeat(64));
+        for domain in ["", "exa mple.com", "a/b.com", long_label.as_str()] {
+            match to_ascii_idn(domain.to_string()) {
+                Err(IdnError::InvalidDomain(_)) => (),
+                other => panic!("Expected InvalidDomain error for {:?}, got {:?}", domain, other),
+            }
+        }
+        match to_unicode_idn("xn--a.com".to_string()) {
+            Err(IdnError::InvalidDomain(_)) => (),
+            other => panic!("Expected InvalidDomain error, got {:?}", other),
+        }
+    }
+}
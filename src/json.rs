@@ -0,0 +1,546 @@
+//! JSONユーティリティモジュール
+//!
+//! このモジュールは、JSON文字列の検証・圧縮（空白の除去）・整形を行う関数を
+//! エクスポートします。アプリ内のAPIデバッグコンソールでの利用を想定しています。
+//! 圧縮・整形ではオブジェクトのキーの順序（重複したキーを含む）は入力のまま保持されます。
+//!
+//! また、JSONポインタ（RFC 6901）とJSONPathのサブセットで値を取り出す
+//! `json_query`を提供します。対応するJSONPathの構文は次のとおりです。
+//!
+//! | 構文 | 意味 |
+//! |------|------|
+//! | `$` | ルート |
+//! | `.name` / `['name']` | オブジェクトのメンバー |
+//! | `[0]` / `[-1]` | 配列の要素（負の値は末尾から） |
+//! | `[1:3]` | 配列のスライス（終端を含まない） |
+//! | `.*` / `[*]` | すべての子要素 |
+//! | `..name` / `..*` | すべての子孫に対する再帰的な選択 |
+
+use std::fmt;
+
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+use serde_json::ser::PrettyFormatter;
+use serde_json::{Number, Value};
+use thiserror::Error;
+
+/// 整形時に指定できる最大のインデント幅
+const MAX_INDENT: u32 = 16;
+
+/// JSON処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum JsonError {
+    /// JSONとして不正な文字列の場合（位置は1始まりの行・列）
+    #[error("Invalid JSON at line {line}, column {column}: {message}")]
+    InvalidJson { line: u64, column: u64, message: String },
+    /// インデント幅が範囲外の場合
+    #[error("Invalid indent: {0} (expected 0 to 16)")]
+    InvalidIndent(u32),
+    /// クエリのパスが不正な場合
+    #[error("Invalid query path at byte {position}: {message}")]
+    InvalidPath { position: u64, message: String },
+}
+
+/// JSONPathの選択子
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    /// オブジェクトのメンバー
+    Member(String),
+    /// 配列の要素（負の値は末尾から）
+    Index(i64),
+    /// 配列のスライス
+    Slice(Option<i64>, Option<i64>),
+    /// すべての子要素
+    Wildcard,
+}
+
+/// JSONPathの1ステップ（`recursive`は`..`による子孫の探索）
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    recursive: bool,
+    selector: Selector,
+}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(error: serde_json::Error) -> Self {
+        JsonError::InvalidJson {
+            line: error.line() as u64,
+            column: error.column() as u64,
+            message: error.to_string(),
+        }
+    }
+}
+
+/// オブジェクトのキーを入力の順序のまま保持するJSONの値
+///
+/// `serde_json::Value`のオブジェクトはキーを名前順に並べ替えるため、
+/// 入力の見た目を保つ必要がある圧縮・整形ではこちらを使用します。
+enum OrderedValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<OrderedValue>),
+    Object(Vec<(String, OrderedValue)>),
+}
+
+impl<'de> Deserialize<'de> for OrderedValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(OrderedValueVisitor)
+    }
+}
+
+struct OrderedValueVisitor;
+
+impl<'de> Visitor<'de> for OrderedValueVisitor {
+    type Value = OrderedValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any JSON value")
+    }
+
+    fn visit_unit<E>(self) -> Result<OrderedValue, E> {
+        Ok(OrderedValue::Null)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<OrderedValue, E> {
+        Ok(OrderedValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<OrderedValue, E> {
+        Ok(OrderedValue::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<OrderedValue, E> {
+        Ok(OrderedValue::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<OrderedValue, E> {
+        Ok(Number::from_f64(value).map_or(OrderedValue::Null, OrderedValue::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<OrderedValue, E> {
+        Ok(OrderedValue::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<OrderedValue, E> {
+        Ok(OrderedValue::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<OrderedValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element()? {
+            items.push(item);
+        }
+        Ok(OrderedValue::Array(items))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<OrderedValue, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = map.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(OrderedValue::Object(entries))
+    }
+}
+
+impl Serialize for OrderedValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            OrderedValue::Null => serializer.serialize_unit(),
+            OrderedValue::Bool(value) => serializer.serialize_bool(*value),
+            OrderedValue::Number(value) => value.serialize(serializer),
+            OrderedValue::String(value) => serializer.serialize_str(value),
+            OrderedValue::Array(items) => {
+                let mut seq = serializer.serialize_seq(Some(items.len()))?;
+                for item in items {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            OrderedValue::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// JSON文字列を解析します
+fn parse_json(text: &str) -> Result<Value, JsonError> {
+    Ok(serde_json::from_str(text)?)
+}
+
+/// 文字列がJSONとして正しいか検証します
+///
+/// # Arguments
+/// * `text` - 検証する文字列
+///
+/// # Errors
+/// * `JsonError::InvalidJson` - JSONとして不正な場合（エラー位置を含む）
+#[uniffi::export]
+pub fn json_validate(text: String) -> Result<(), JsonError> {
+    serde_json::from_str::<serde::de::IgnoredAny>(&text)?;
+    Ok(())
+}
+
+/// JSON文字列から不要な空白を取り除きます
+///
+/// オブジェクトのキーの順序は入力のまま保持されます。
+///
+/// # Arguments
+/// * `text` - 圧縮するJSON文字列
+///
+/// # Errors
+/// * `JsonError::InvalidJson` - JSONとして不正な場合
+///
+/// # Example
+/// ```
+/// let minified = json_minify("{ \"a\": [1, 2] }".to_string())?;
+/// assert_eq!(minified, "{\"a\":[1,2]}");
+/// ```
+#[uniffi::export]
+pub fn json_minify(text: String) -> Result<String, JsonError> {
+    let value: OrderedValue = serde_json::from_str(&text)?;
+    Ok(serde_json::to_string(&value)?)
+}
+
+/// JSON文字列を指定した幅のインデントで整形します
+///
+/// オブジェクトのキーの順序は入力のまま保持されます。
+///
+/// # Arguments
+/// * `text` - 整形するJSON文字列
+/// * `indent` - インデントの空白数（0〜16、既定値は2）
+///
+/// # Errors
+/// * `JsonError::InvalidJson` - JSONとして不正な場合
+/// * `JsonError::InvalidIndent` - インデント幅が範囲外の場合
+#[uniffi::export(default(indent = 2))]
+pub fn json_pretty(text: String, indent: u32) -> Result<String, JsonError> {
+    if indent > MAX_INDENT {
+        return Err(JsonError::InvalidIndent(indent));
+    }
+    let value: OrderedValue = serde_json::from_str(&text)?;
+    let indent = " ".repeat(indent as usize);
+    let mut output = Vec::with_capacity(text.len() * 2);
+    let mut serializer = serde_json::Serializer::with_formatter(
+        &mut output,
+        PrettyFormatter::with_indent(indent.as_bytes()),
+    );
+    value.serialize(&mut serializer)?;
+    Ok(String::from_utf8(output).expect("serde_json produces valid UTF-8"))
+}
+
+/// パス解析中のエラーを作成します
+fn path_error(position: usize, message: &str) -> JsonError {
+    JsonError::InvalidPath {
+        position: position as u64,
+        message: message.to_string(),
+    }
+}
+
+/// 整数を解析します（空文字列は`None`）
+fn parse_optional_int(text: &str, position: usize) -> Result<Option<i64>, JsonError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    text.parse()
+        .map(Some)
+        .map_err(|_| path_error(position, "expected an integer"))
+}
+
+/// `[...]`の中身を選択子に変換します
+fn parse_bracket(content: &str, position: usize) -> Result<Selector, JsonError> {
+    let content = content.trim();
+    if content == "*" {
+        return Ok(Selector::Wildcard);
+    }
+    if let Some(quote) = content.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        let inner = content
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+            .ok_or_else(|| path_error(position, "unterminated string"))?;
+        let mut name = String::with_capacity(inner.len());
+        let mut chars = inner.chars();
+        while let Some(c) = chars.next() {
+            if c == '\\' {
+                name.push(chars.next().ok_or_else(|| path_error(position, "dangling escape"))?);
+            } else {
+                name.push(c);
+            }
+        }
+        return Ok(Selector::Member(name));
+    }
+    if let Some((start, end)) = content.split_once(':') {
+        return Ok(Selector::Slice(
+            parse_optional_int(start, position)?,
+            parse_optional_int(end, position)?,
+        ));
+    }
+    parse_optional_int(content, position)?
+        .map(Selector::Index)
+        .ok_or_else(|| path_error(position, "empty brackets"))
+}
+
+/// JSONPathを解析してステップの列に変換します
+fn parse_json_path(path: &str) -> Result<Vec<Step>, JsonError> {
+    if !path.starts_with('$') {
+        return Err(path_error(0, "path must start with '$' or '/'"));
+    }
+    let mut steps = Vec::new();
+    let mut pos = 1;
+    let bytes = path.as_bytes();
+
+    while pos < bytes.len() {
+        let recursive = path[pos..].starts_with("..");
+        if recursive {
+            pos += 2;
+        } else if bytes[pos] == b'.' {
+            pos += 1;
+        } else if bytes[pos] != b'[' {
+            return Err(path_error(pos, "expected '.' or '['"));
+        }
+
+        let selector = if bytes.get(pos) == Some(&b'[') {
+            let close = find_closing_bracket(path, pos)?;
+            let selector = parse_bracket(&path[pos + 1..close], pos)?;
+            pos = close + 1;
+            selector
+        } else {
+            let end = path[pos..]
+                .find(['.', '['])
+                .map_or(path.len(), |offset| pos + offset);
+            let name = &path[pos..end];
+            if name.is_empty() {
+                return Err(path_error(pos, "expected a member name"));
+            }
+            pos = end;
+            if name == "*" {
+                Selector::Wildcard
+            } else {
+                Selector::Member(name.to_string())
+            }
+        };
+        steps.push(Step { recursive, selector });
+    }
+    Ok(steps)
+}
+
+/// `[`に対応する`]`の位置を探します（引用符内の`]`は無視します）
+fn find_closing_bracket(path: &str, open: usize) -> Result<usize, JsonError> {
+    let mut quote = None;
+    let mut escaped = false;
+    for (offset, c) in path[open + 1..].char_indices() {
+        match (quote, c) {
+            (Some(_), _) if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"') => quote = Some(c),
+            (None, ']') => return Ok(open + 1 + offset),
+            _ => {}
+        }
+    }
+    Err(path_error(open, "unclosed '['"))
+}
+
+/// 値とそのすべての子孫を深さ優先の順で集めます
+fn collect_descendants<'a>(value: &'a Value, output: &mut Vec<&'a Value>) {
+    output.push(value);
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_descendants(item, output)),
+        Value::Object(map) => map.values().for_each(|item| collect_descendants(item, output)),
+        _ => {}
+    }
+}
+
+/// 負の値を末尾からの位置として配列の添字に変換します
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let resolved = if index < 0 { len as i64 + index } else { index };
+    (0..len as i64).contains(&resolved).then_some(resolved as usize)
+}
+
+/// 選択子を1つの値に適用します
+fn apply_selector<'a>(selector: &Selector, value: &'a Value, output: &mut Vec<&'a Value>) {
+    match (selector, value) {
+        (Selector::Member(name), Value::Object(map)) => output.extend(map.get(name)),
+        (Selector::Index(index), Value::Array(items)) => {
+            output.extend(resolve_index(*index, items.len()).map(|i| &items[i]));
+        }
+        (Selector::Slice(start, end), Value::Array(items)) => {
+            let len = items.len() as i64;
+            let clamp = |bound: i64| (if bound < 0 { len + bound } else { bound }).clamp(0, len);
+            let start = clamp(start.unwrap_or(0)) as usize;
+            let end = clamp(end.unwrap_or(len)) as usize;
+            if start < end {
+                output.extend(&items[start..end]);
+            }
+        }
+        (Selector::Wildcard, Value::Array(items)) => output.extend(items),
+        (Selector::Wildcard, Value::Object(map)) => output.extend(map.values()),
+        _ => {}
+    }
+}
+
+/// JSONドキュメントからパスに一致する値を取り出します
+///
+/// `path`が`/`で始まるか空文字列の場合はJSONポインタ（RFC 6901）として、
+/// `$`で始まる場合はJSONPathとして解釈します。一致した値はそれぞれ
+/// 圧縮したJSON文字列として返されるため、文字列の値は引用符付きになります。
+///
+/// # Arguments
+/// * `document` - 検索するJSONドキュメント
+/// * `path` - JSONポインタ（例: `/items/0/name`）またはJSONPath（例: `$.items[*].name`）
+///
+/// # Returns
+/// * 一致した値のJSON文字列（一致しない場合は空）。ワイルドカードと再帰的な選択では、
+///   オブジェクトのメンバーはキーの名前順に並びます
+///
+/// # Errors
+/// * `JsonError::InvalidJson` - ドキュメントがJSONとして不正な場合
+/// * `JsonError::InvalidPath` - パスの構文が不正な場合
+///
+/// # Example
+/// ```
+/// let doc = r#"{"items":[{"name":"a"},{"name":"b"}]}"#;
+/// assert_eq!(json_query(doc, "$.items[*].name")?, vec!["\"a\"", "\"b\""]);
+/// assert_eq!(json_query(doc, "/items/1/name")?, vec!["\"b\""]);
+/// ```
+#[uniffi::export]
+pub fn json_query(document: &str, path: &str) -> Result<Vec<String>, JsonError> {
+    let root = parse_json(document)?;
+    let matches: Vec<&Value> = if path.is_empty() || path.starts_with('/') {
+        root.pointer(path).into_iter().collect()
+    } else {
+        let steps = parse_json_path(path)?;
+        let mut current = vec![&root];
+        for step in &steps {
+            let candidates = if step.recursive {
+                let mut all = Vec::new();
+                current.iter().for_each(|value| collect_descendants(value, &mut all));
+                all
+            } else {
+                current
+            };
+            let mut next = Vec::new();
+            for value in candidates {
+                apply_selector(&step.selector, value, &mut next);
+            }
+            current = next;
+        }
+        current
+    };
+    matches
+        .into_iter()
+        .map(|value| Ok(serde_json::to_string(value)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_validate() {
+        assert!(json_validate(r#"{"a": [1, true, null, "x"]}"#.to_string()).is_ok());
+        match json_validate("{\n  \"a\": 1,\n}".to_string()) {
+            Err(JsonError::InvalidJson { line: 3, column: 1, .. }) => (),
+            other => panic!("Expected InvalidJson error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_json_minify_preserves_key_order() {
+        let text = "{ \"z\": 1,\n \"a\": { \"y\": [1, 2.5e0, -3], \"b\": \"s p\" } }";
+        let minified = json_minify(text.to_string());
+        assert_eq!(minified.unwrap(), r#"{"z":1,"a":{"y":[1,2.5,-3],"b":"s p"}}"#);
+        let duplicated = json_minify(r#"{"b": 1, "a": null, "b": 2}"#.to_string());
+        assert_eq!(duplicated.unwrap(), r#"{"b":1,"a":null,"b":2}"#);
+        assert_eq!(
+            json_pretty(r#"{"z":{},"a":[]}"#.to_string(), 1).unwrap(),
+            "{\n \"z\": {},\n \"a\": []\n}"
+        );
+    }
+
+    #[test]
+    fn test_json_pretty() {
+        let text = r#"{"a":[1,2],"b":{}}"#.to_string();
+        assert_eq!(
+            json_pretty(text.clone(), 2).unwrap(),
+            "{\n  \"a\": [\n    1,\n    2\n  ],\n  \"b\": {}\n}"
+        );
+        assert_eq!(json_pretty(r#"{"a":1}"#.to_string(), 4).unwrap(), "{\n    \"a\": 1\n}");
+        match json_pretty(text, 17) {
+            Err(JsonError::InvalidIndent(17)) => (),
+            other => panic!("Expected InvalidIndent error, got {:?}", other),
+        }
+    }
+
+    const STORE: &str = r#"{
+        "store": {
+            "book": [
+                {"title": "A", "price": 8.95, "tags": ["x"]},
+                {"title": "B", "price": 12.99},
+                {"title": "C.d", "price": 22}
+            ],
+            "bicycle": {"color": "red", "price": 19.95},
+            "a/b": {"m~n": 1}
+        }
+    }"#;
+
+    #[test]
+    fn test_json_query_pointer() {
+        assert_eq!(json_query(STORE, "/store/book/1/title").unwrap(), vec!["\"B\""]);
+        assert_eq!(json_query(STORE, "/store/a~1b/m~0n").unwrap(), vec!["1"]);
+        assert!(json_query(STORE, "/store/missing").unwrap().is_empty());
+        assert_eq!(json_query("[1]", "").unwrap(), vec!["[1]"]);
+    }
+
+    #[test]
+    fn test_json_query_path_members_and_indexes() {
+        assert_eq!(json_query(STORE, "$.store.bicycle.color").unwrap(), vec!["\"red\""]);
+        assert_eq!(json_query(STORE, "$['store']['book'][0]['title']").unwrap(), vec!["\"A\""]);
+        assert_eq!(json_query(STORE, "$.store.book[-1].title").unwrap(), vec!["\"C.d\""]);
+        assert_eq!(json_query(STORE, "$.store['a/b']").unwrap(), vec![r#"{"m~n":1}"#]);
+        assert_eq!(json_query(STORE, "$").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_json_query_path_wildcards_and_slices() {
+        assert_eq!(
+            json_query(STORE, "$.store.book[*].title").unwrap(),
+            vec!["\"A\"", "\"B\"", "\"C.d\""]
+        );
+        assert_eq!(json_query(STORE, "$.store.book[1:].price").unwrap(), vec!["12.99", "22"]);
+        assert_eq!(json_query(STORE, "$.store.book[:-2].title").unwrap(), vec!["\"A\""]);
+        assert_eq!(json_query(STORE, "$.store.bicycle.*").unwrap(), vec!["\"red\"", "19.95"]);
+    }
+
+    #[test]
+    fn test_json_query_recursive_descent() {
+        assert_eq!(
+            json_query(STORE, "$..price").unwrap(),
+            vec!["19.95", "8.95", "12.99", "22"]
+        );
+        assert_eq!(json_query(STORE, "$..tags[0]").unwrap(), vec!["\"x\""]);
+    }
+
+    #[test]
+    fn test_json_query_errors() {
+        for path in ["store.book", "$.store[", "$.store[abc]", "$.", "$x"] {
+            match json_query(STORE, path) {
+                Err(JsonError::InvalidPath { .. }) => (),
+                other => panic!("Expected InvalidPath error for {}, got {:?}", path, other),
+            }
+        }
+        match json_query("{", "$") {
+            Err(JsonError::InvalidJson { .. }) => (),
+            other => panic!("Expected InvalidJson error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! 鍵導出関数（KDF）モジュール
+//!
+//! このモジュールは、パスフレーズ（PBKDF2）やマスターシークレット（HKDF）から
+//! 暗号鍵を導出する関数をエクスポートします。
+//! 同じ入力からは全プラットフォームで同一の鍵が得られます。
+
+use hkdf::Hkdf;
+use sha2::Sha256;
+use thiserror::Error;
+
+/// PBKDF2で導出できる鍵の最大長（バイト）
+const MAX_DERIVED_KEY_LENGTH: u32 = 1024;
+
+/// HKDF-SHA256で導出できる鍵の最大長（255 × ハッシュ長）
+const MAX_HKDF_LENGTH: u32 = 255 * 32;
+
+/// 鍵導出で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum KdfError {
+    /// 反復回数が0の場合
+    #[error("Iteration count must be greater than zero")]
+    InvalidIterations,
+    /// 導出する鍵の長さが不正な場合
+    #[error("Invalid derived key length: {0}")]
+    InvalidLength(u32),
+}
+
+/// PBKDF2-HMAC-SHA256でパスワードから鍵を導出します
+///
+/// # Arguments
+/// * `password` - パスワード（UTF-8としてバイト列に変換されます）
+/// * `salt` - ソルト（16バイト以上のランダム値を推奨）
+/// * `iterations` - 反復回数（600,000回以上を推奨）
+/// * `length` - 導出する鍵の長さ（バイト、1〜1024）
+///
+/// # Errors
+/// * `KdfError::InvalidIterations` - 反復回数が0の場合
+/// * `KdfError::InvalidLength` - 鍵の長さが範囲外の場合
+///
+/// # Example
+/// ```
+/// let key = derive_key_pbkdf2("passphrase".to_string(), salt, 600_000, 32)?;
+/// assert_eq!(key.len(), 32);
+/// ```
+#[uniffi::export]
+pub fn derive_key_pbkdf2(
+    password: String,
+    salt: Vec<u8>,
+    iterations: u32,
+    length: u32,
+) -> Result<Vec<u8>, KdfError> {
+    if iterations == 0 {
+        return Err(KdfError::InvalidIterations);
+    }
+    if length == 0 || length > MAX_DERIVED_KEY_LENGTH {
+        return Err(KdfError::InvalidLength(length));
+    }
+    let mut key = vec![0u8; length as usize];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), &salt, iterations, &mut key);
+    Ok(key)
+}
+
+/// HKDF-SHA256（RFC 5869）でマスターシークレットから鍵を導出します
+///
+/// extractとexpandの両方を行います。`info`に用途を表す文字列を指定することで、
+/// 同じマスターシークレットから用途ごとに独立したサブキーを導出できます。
+///
+/// # Arguments
+/// * `ikm` - 入力鍵マテリアル（マスターシークレット）
+/// * `salt` - ソルト（空の場合はRFC 5869に従いゼロ埋めのソルトを使用）
+/// * `info` - 用途を区別するためのコンテキスト情報
+/// * `length` - 導出する鍵の長さ（バイト、1〜8160）
+///
+/// # Errors
+/// * `KdfError::InvalidLength` - 鍵の長さが範囲外の場合
+///
+/// # Example
+/// ```
+/// let enc_key = hkdf_sha256(master.clone(), salt.clone(), b"encryption".to_vec(), 32)?;
+/// let mac_key = hkdf_sha256(master, salt, b"authentication".to_vec(), 32)?;
+/// ```
+#[uniffi::export]
+pub fn hkdf_sha256(
+    ikm: Vec<u8>,
+    salt: Vec<u8>,
+    info: Vec<u8>,
+    length: u32,
+) -> Result<Vec<u8>, KdfError> {
+    if length == 0 || length > MAX_HKDF_LENGTH {
+        return Err(KdfError::InvalidLength(length));
+    }
+    let salt = (!salt.is_empty()).then_some(salt.as_slice());
+    let hkdf = Hkdf::<Sha256>::new(salt, &ikm);
+    let mut okm = vec![0u8; length as usize];
+    hkdf.expand(&info, &mut okm)
+        .map_err(|_| KdfError::InvalidLength(length))?;
+    Ok(okm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_key_pbkdf2_known_vector() {
+        // RFC 7914 Section 11 のPBKDF2-HMAC-SHA256テストベクタ
+        let key = derive_key_pbkdf2("passwd".to_string(), b"salt".to_vec(), 1, 64).unwrap();
+        assert_eq!(
+            hex::encode(key),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+             49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+        );
+    }
+
+    #[test]
+    fn test_derive_key_pbkdf2_deterministic() {
+        let a = derive_key_pbkdf2("pw".to_string(), b"salt1234".to_vec(), 1000, 32).unwrap();
+        let b = derive_key_pbkdf2("pw".to_string(), b"salt1234".to_vec(), 1000, 32).unwrap();
+        let c = derive_key_pbkdf2("pw".to_string(), b"salt5678".to_vec(), 1000, 32).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn test_derive_key_pbkdf2_invalid_arguments() {
+        match derive_key_pbkdf2("pw".to_string(), Vec::new(), 0, 32) {
+            Err(KdfError::InvalidIterations) => (),
+            _ => panic!("Expected InvalidIterations error"),
+        }
+        match derive_key_pbkdf2("pw".to_string(), Vec::new(), 1, 0) {
+            Err(KdfError::InvalidLength(0)) => (),
+            _ => panic!("Expected InvalidLength error"),
+        }
+    }
+
+    #[test]
+    fn test_hkdf_sha256_rfc5869_case1() {
+        // RFC 5869 Appendix A.1
+        let ikm = vec![0x0b; 22];
+        let salt = hex::decode("000102030405060708090a0b0c").unwrap();
+        let info = hex::decode("f0f1f2f3f4f5f6f7f8f9").unwrap();
+        let okm = hkdf_sha256(ikm, salt, info, 42).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_sha256_rfc5869_case3_empty_salt() {
+        // RFC 5869 Appendix A.3（ソルト・infoなし）
+        let okm = hkdf_sha256(vec![0x0b; 22], Vec::new(), Vec::new(), 42).unwrap();
+        assert_eq!(
+            hex::encode(okm),
+            "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"
+        );
+    }
+
+    #[test]
+    fn test_hkdf_sha256_invalid_length() {
+        match hkdf_sha256(vec![1; 32], Vec::new(), Vec::new(), 255 * 32 + 1) {
+            Err(KdfError::InvalidLength(_)) => (),
+            _ => panic!("Expected InvalidLength error"),
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! 鍵共有モジュール
+//!
+//! このモジュールは、X25519による鍵ペア生成と共有秘密の計算、および
+//! 共有秘密からHKDF-SHA256で用途別の鍵を導出する関数をエクスポートします。
+
+use rand_core::OsRng;
+use thiserror::Error;
+use x25519_dalek::{PublicKey, StaticSecret};
+
+use crate::kdf::hkdf_sha256;
+
+/// 鍵共有で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum KeyAgreementError {
+    /// 鍵の長さが不正な場合
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+    /// 相手の公開鍵が低位数の点で、共有秘密がゼロになった場合
+    #[error("Shared secret is all zeros (low-order peer public key)")]
+    WeakSharedSecret,
+    /// 共有秘密からの鍵導出に失敗した場合
+    #[error("Key derivation failed: {0}")]
+    DerivationFailed(String),
+}
+
+/// X25519の鍵ペア
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct X25519Keypair {
+    /// 秘密鍵（32バイト）
+    pub private_key: Vec<u8>,
+    /// 公開鍵（32バイト）
+    pub public_key: Vec<u8>,
+}
+
+/// バイト列を32バイトの配列に変換します
+fn to_key_bytes(bytes: &[u8], label: &str) -> Result<[u8; 32], KeyAgreementError> {
+    bytes.try_into().map_err(|_| {
+        KeyAgreementError::InvalidKey(format!(
+            "expected 32-byte {}, got {} bytes",
+            label,
+            bytes.len()
+        ))
+    })
+}
+
+/// X25519の鍵ペアを生成します
+///
+/// 秘密鍵はOSの暗号論的乱数生成器から生成されます。
+#[uniffi::export]
+pub fn x25519_generate_keypair() -> X25519Keypair {
+    let secret = StaticSecret::random_from_rng(OsRng);
+    let public = PublicKey::from(&secret);
+    X25519Keypair {
+        private_key: secret.to_bytes().to_vec(),
+        public_key: public.to_bytes().to_vec(),
+    }
+}
+
+/// 自分の秘密鍵と相手の公開鍵からX25519の共有秘密を計算します
+///
+/// 共有秘密はそのまま鍵として使わず、`x25519_derive_key`などで
+/// 鍵導出を行ってから使用してください。
+///
+/// # Arguments
+/// * `private_key` - 自分の32バイトの秘密鍵
+/// * `peer_public_key` - 相手の32バイトの公開鍵
+///
+/// # Errors
+/// * `KeyAgreementError::InvalidKey` - 鍵の長さが不正な場合
+/// * `KeyAgreementError::WeakSharedSecret` - 相手の公開鍵が不正な点の場合
+#[uniffi::export]
+pub fn x25519_shared_secret(
+    private_key: Vec<u8>,
+    peer_public_key: Vec<u8>,
+) -> Result<Vec<u8>, KeyAgreementError> {
+    let secret = StaticSecret::from(to_key_bytes(&private_key, "private key")?);
+    let peer = PublicKey::from(to_key_bytes(&peer_public_key, "public key")?);
+    let shared = secret.diffie_hellman(&peer);
+    if !shared.was_contributory() {
+        return Err(KeyAgreementError::WeakSharedSecret);
+    }
+    Ok(shared.as_bytes().to_vec())
+}
+
+/// X25519の共有秘密を計算し、HKDF-SHA256で鍵を導出します
+///
+/// # Arguments
+/// * `private_key` - 自分の32バイトの秘密鍵
+/// * `peer_public_key` - 相手の32バイトの公開鍵
+/// * `salt` - HKDFのソルト（空でも可）
+/// * `info` - 用途を区別するためのコンテキスト情報
+/// * `length` - 導出する鍵の長さ（バイト）
+///
+/// # Errors
+/// * `KeyAgreementError::InvalidKey` - 鍵の長さが不正な場合
+/// * `KeyAgreementError::WeakSharedSecret` - 相手の公開鍵が不正な点の場合
+/// * `KeyAgreementError::DerivationFailed` - 鍵の長さが範囲外の場合
+///
+/// # Example
+/// ```
+/// let key = x25519_derive_key(my_private, peer_public, Vec::new(), b"chat v1".to_vec(), 32)?;
+/// ```
+#[uniffi::export]
+pub fn x25519_derive_key(
+    private_key: Vec<u8>,
+    peer_public_key: Vec<u8>,
+    salt: Vec<u8>,
+    info: Vec<u8>,
+    length: u32,
+) -> Result<Vec<u8>, KeyAgreementError> {
+    let shared = x25519_shared_secret(private_key, peer_public_key)?;
+    hkdf_sha256(shared, salt, info, length)
+        .map_err(|e| KeyAgreementError::DerivationFailed(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_x25519_shared_secret_matches() {
+        let alice = x25519_generate_keypair();
+        let bob = x25519_generate_keypair();
+        let a = x25519_shared_secret(alice.private_key.clone(), bob.public_key.clone()).unwrap();
+        let b = x25519_shared_secret(bob.private_key, alice.public_key).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 32);
+    }
+
+    #[test]
+    fn test_x25519_rfc7748_vector() {
+        // RFC 7748 Section 6.1
+        let alice_private =
+            hex::decode("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a")
+                .unwrap();
+        let bob_public =
+            hex::decode("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+                .unwrap();
+        let shared = x25519_shared_secret(alice_private, bob_public).unwrap();
+        assert_eq!(
+            hex::encode(shared),
+            "4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742"
+        );
+    }
+
+    #[test]
+    fn test_x25519_derive_key() {
+        let alice = x25519_generate_keypair();
+        let bob = x25519_generate_keypair();
+        let a = x25519_derive_key(
+            alice.private_key.clone(),
+            bob.public_key.clone(),
+            b"salt".to_vec(),
+            b"chat v1".to_vec(),
+            32,
+        )
+        .unwrap();
+        let b = x25519_derive_key(
+            bob.private_key.clone(),
+            alice.public_key.clone(),
+            b"salt".to_vec(),
+            b"chat v1".to_vec(),
+            32,
+        )
+        .unwrap();
+        let other_purpose = x25519_derive_key(
+            bob.private_key,
+            alice.public_key,
+            b"salt".to_vec(),
+            b"file v1".to_vec(),
+            32,
+        )
+        .unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, other_purpose);
+    }
+
+    #[test]
+    fn test_x25519_rejects_low_order_point() {
+        let alice = x25519_generate_keypair();
+        match x25519_shared_secret(alice.private_key, vec![0u8; 32]) {
+            Err(KeyAgreementError::WeakSharedSecret) => (),
+            _ => panic!("Expected WeakSharedSecret error"),
+        }
+    }
+
+    #[test]
+    fn test_x25519_invalid_key_length() {
+        match x25519_shared_secret(vec![1u8; 31], vec![9u8; 32]) {
+            Err(KeyAgreementError::InvalidKey(_)) => (),
+            _ => panic!("Expected InvalidKey error"),
+        }
+    }
+}
@@ -0,0 +1,144 @@
+//! 鍵ラッピング（AES Key Wrap）モジュール
+//!
+//! このモジュールは、RFC 3394のAES Key Wrap（AES-KW）でデータ暗号化鍵を
+//! 鍵暗号化鍵（KEK）によって包む・解く関数をエクスポートします。
+//! iOSのKeychainへ保存する前に鍵をラップする用途を想定しています。
+
+use aes_kw::{KekAes128, KekAes192, KekAes256};
+use thiserror::Error;
+
+/// 鍵ラッピングで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum KeyWrapError {
+    /// KEKの長さが16・24・32バイトのいずれでもない場合
+    #[error("Invalid KEK length: {0} bytes (expected 16, 24 or 32)")]
+    InvalidKekLength(u32),
+    /// ラップ対象の鍵（またはラップ済みデータ）の長さが不正な場合
+    #[error("Invalid key data length: {0} bytes (must be a multiple of 8 and at least 16)")]
+    InvalidDataLength(u32),
+    /// 完全性チェックに失敗した場合（KEKの誤りまたはデータの改ざん）
+    #[error("Integrity check failed while unwrapping key")]
+    IntegrityCheckFailed,
+}
+
+/// aes-kwのエラーを`KeyWrapError`に変換します
+fn map_error(error: aes_kw::Error, data_len: usize) -> KeyWrapError {
+    match error {
+        aes_kw::Error::IntegrityCheckFailed => KeyWrapError::IntegrityCheckFailed,
+        aes_kw::Error::InvalidKekSize { size } => KeyWrapError::InvalidKekLength(size as u32),
+        _ => KeyWrapError::InvalidDataLength(data_len as u32),
+    }
+}
+
+/// RFC 3394の入力長の要件（8バイトの倍数かつ16バイト以上）を確認します
+fn check_data_length(data: &[u8], min: usize) -> Result<(), KeyWrapError> {
+    if data.len() < min || !data.len().is_multiple_of(8) {
+        return Err(KeyWrapError::InvalidDataLength(data.len() as u32));
+    }
+    Ok(())
+}
+
+/// KEKで鍵をラップします（AES-KW）
+///
+/// KEKの長さに応じてAES-128/192/256が選択されます。
+///
+/// # Arguments
+/// * `kek` - 鍵暗号化鍵（16・24・32バイト）
+/// * `key` - ラップする鍵（8バイトの倍数かつ16バイト以上）
+///
+/// # Returns
+/// * ラップ済みの鍵（入力より8バイト長い）
+///
+/// # Errors
+/// * `KeyWrapError::InvalidKekLength` - KEKの長さが不正な場合
+/// * `KeyWrapError::InvalidDataLength` - 鍵の長さが不正な場合
+///
+/// # Example
+/// ```
+/// let wrapped = wrap_key(kek.clone(), data_key)?;
+/// let unwrapped = unwrap_key(kek, wrapped)?;
+/// ```
+#[uniffi::export]
+pub fn wrap_key(kek: Vec<u8>, key: Vec<u8>) -> Result<Vec<u8>, KeyWrapError> {
+    check_data_length(&key, 16)?;
+    let result = match kek.len() {
+        16 => KekAes128::try_from(kek.as_slice()).and_then(|k| k.wrap_vec(&key)),
+        24 => KekAes192::try_from(kek.as_slice()).and_then(|k| k.wrap_vec(&key)),
+        32 => KekAes256::try_from(kek.as_slice()).and_then(|k| k.wrap_vec(&key)),
+        len => return Err(KeyWrapError::InvalidKekLength(len as u32)),
+    };
+    result.map_err(|e| map_error(e, key.len()))
+}
+
+/// KEKでラップされた鍵を元に戻します（AES-KW）
+///
+/// # Arguments
+/// * `kek` - ラップ時に使用した鍵暗号化鍵
+/// * `wrapped` - `wrap_key`が返したラップ済みの鍵
+///
+/// # Errors
+/// * `KeyWrapError::InvalidKekLength` - KEKの長さが不正な場合
+/// * `KeyWrapError::InvalidDataLength` - ラップ済みデータの長さが不正な場合
+/// * `KeyWrapError::IntegrityCheckFailed` - KEKが異なる、またはデータが改ざんされている場合
+#[uniffi::export]
+pub fn unwrap_key(kek: Vec<u8>, wrapped: Vec<u8>) -> Result<Vec<u8>, KeyWrapError> {
+    check_data_length(&wrapped, 24)?;
+    let result = match kek.len() {
+        16 => KekAes128::try_from(kek.as_slice()).and_then(|k| k.unwrap_vec(&wrapped)),
+        24 => KekAes192::try_from(kek.as_slice()).and_then(|k| k.unwrap_vec(&wrapped)),
+        32 => KekAes256::try_from(kek.as_slice()).and_then(|k| k.unwrap_vec(&wrapped)),
+        len => return Err(KeyWrapError::InvalidKekLength(len as u32)),
+    };
+    result.map_err(|e| map_error(e, wrapped.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_key_rfc3394_vector() {
+        // RFC 3394 Section 4.6: 256ビットKEKで256ビットの鍵をラップ
+        let kek = hex::decode("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F")
+            .unwrap();
+        let key = hex::decode("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F")
+            .unwrap();
+        let wrapped = wrap_key(kek.clone(), key.clone()).unwrap();
+        assert_eq!(
+            hex::encode_upper(&wrapped),
+            "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21"
+        );
+        assert_eq!(unwrap_key(kek, wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn test_wrap_key_aes128_roundtrip() {
+        let kek = vec![7u8; 16];
+        let key = vec![42u8; 32];
+        let wrapped = wrap_key(kek.clone(), key.clone()).unwrap();
+        assert_eq!(wrapped.len(), 40);
+        assert_eq!(unwrap_key(kek, wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn test_unwrap_key_wrong_kek() {
+        let wrapped = wrap_key(vec![1u8; 32], vec![2u8; 32]).unwrap();
+        match unwrap_key(vec![3u8; 32], wrapped) {
+            Err(KeyWrapError::IntegrityCheckFailed) => (),
+            _ => panic!("Expected IntegrityCheckFailed error"),
+        }
+    }
+
+    #[test]
+    fn test_wrap_key_invalid_lengths() {
+        match wrap_key(vec![0u8; 20], vec![0u8; 16]) {
+            Err(KeyWrapError::InvalidKekLength(20)) => (),
+            _ => panic!("Expected InvalidKekLength error"),
+        }
+        match wrap_key(vec![0u8; 32], vec![0u8; 15]) {
+            Err(KeyWrapError::InvalidDataLength(15)) => (),
+            _ => panic!("Expected InvalidDataLength error"),
+        }
+    }
+}
@@ -1,9 +1,208 @@
+mod archive;
+mod blob_store;
+mod cache;
 mod calculator;
+mod calendar;
+mod card;
+mod cassette;
+mod cbor;
+mod checksum;
+mod compression;
+mod config;
+mod cron;
+mod csv;
+mod database;
+mod datetime;
+mod deny_list;
+mod document_store;
+mod download;
+mod duration;
+mod encoding;
+mod event_source;
+mod envelope;
+mod file_io;
+mod fuzzy;
+mod graphql;
 mod greeting;
+mod hash;
+mod html;
+mod http_cache;
+mod ics;
+mod idn;
+mod json;
 mod jwt;
+mod kdf;
+mod key_agreement;
+mod key_wrap;
+mod mac;
+mod markdown;
+mod message_format;
+mod migration;
+mod mime_type;
+mod msgpack;
+mod multipart;
+mod oauth;
+mod otp;
+mod password;
+mod password_policy;
+mod password_strength;
+mod pattern;
+mod pinning;
+mod protobuf;
+mod queue;
+mod random;
+mod recovery;
+mod relative_time;
+mod retry;
+mod router;
+mod sanitize;
+mod scan;
+mod search;
+mod secure_store;
+mod shamir;
+mod signing;
+mod sigv4;
+mod slug;
+mod snapshot;
+mod template;
+mod timezone;
+mod transport;
+mod ulid;
+mod unicode;
+mod upload;
+mod url_parser;
+mod url_validation;
+mod user_agent;
+mod vault;
+mod version;
+mod websocket;
+mod xml;
 
+pub use archive::{ArchiveError, ZipArchive, ZipEntry, ZipInputFile};
+pub use blob_store::{BlobGcReport, BlobStore, BlobStoreError};
+pub use cache::{CacheError, CacheStats, LruCache};
 pub use calculator::{Calculator, CalculatorError};
-pub use greeting::say_hi;
-pub use jwt::{decode_jwt, JwtError, JwtParts};
+pub use calendar::{
+    add_business_days, add_months, days_between, start_of_week, CalendarDate, CalendarError,
+    MonthEndPolicy,
+};
+pub use card::{format_card_display, validate_card_number, CardBrand, CardError, CardValidation};
+pub use cassette::{CassetteError, CassetteMode, HttpCassette, RecordedResponse};
+pub use cbor::{cbor_to_json, json_to_cbor, CborError};
+pub use checksum::{adler32, crc32, crc32c};
+pub use compression::{
+    deflate_compress, deflate_decompress, gzip_compress, gzip_decompress, train_dictionary,
+    zstd_compress, zstd_decompress, CompressionError,
+};
+pub use config::{parse_toml_to_json, parse_yaml_to_json, ConfigError};
+pub use cron::{next_occurrences, parse_cron, CronError, CronSchedule};
+pub use csv::{write_csv, CsvError, CsvOptions, CsvReader, CsvRow};
+pub use database::{Database, DatabaseError, SqlRow, SqlValue};
+pub use datetime::{format_datetime, parse_datetime, DateParseMode, DateTimeError, Timestamp};
+pub use deny_list::{DenyListError, TokenDenyList};
+pub use document_store::{DocumentStore, DocumentStoreError, StoredDocument};
+pub use download::{DownloadError, DownloadObserver, DownloadTask, Downloader};
+pub use duration::{humanize_duration, parse_duration, DurationError};
+pub use encoding::{
+    base32_decode, base32_encode, base58_decode, base58_encode, base64_decode, base64_encode,
+    build_query_string, hex_decode, hex_encode, url_decode_component, url_encode_component,
+    Base64Variant, EncodingError, QueryParam,
+};
+pub use envelope::{open, seal, EnvelopeError};
+pub use event_source::{
+    EventSource, EventSourceError, EventSourceListener, EventSourceState, ServerSentEvent,
+};
+pub use file_io::{read_file_with_limit, write_file_atomic, FileIoError};
+pub use fuzzy::{fuzzy_rank, jaro_winkler, levenshtein, FuzzyMatch, MatchRange};
+pub use graphql::{
+    build_graphql_request, parse_graphql_response, GraphQlError, GraphQlErrorKind, GraphQlLocation,
+    GraphQlResult, GraphQlServerError,
+};
+pub use greeting::{
+    clear_greeting_provider, fetch_greeting, greet, greet_for_time, greet_group, greet_localized,
+    greet_now, say_hi, set_greeting_provider, Clock, GreetingError, GreetingOptions,
+    GreetingProvider, GreetingStyle, SystemClock,
+};
+pub use hash::{
+    blake3_hash, blake3_keyed_hash, md5, md5_bytes, sha256, sha256_bytes, sha512, sha512_bytes,
+    HashAlgorithm, HashError, Hasher,
+};
+pub use html::{html_escape, html_unescape};
+pub use http_cache::{CachePolicy, CacheSource, CachedResponse, HttpCache, HttpCacheError};
+pub use ics::{parse_ics, CalendarEvent, IcsError};
+pub use idn::{to_ascii_idn, to_unicode_idn, IdnError};
+pub use json::{json_minify, json_pretty, json_query, json_validate, JsonError};
+pub use jwt::{
+    decode_jws_raw, decode_jwt, decode_jwt_bytes, JwsRawParts, JwtBytes, JwtError, JwtParts,
+};
+pub use kdf::{derive_key_pbkdf2, hkdf_sha256, KdfError};
+pub use key_agreement::{
+    x25519_derive_key, x25519_generate_keypair, x25519_shared_secret, KeyAgreementError,
+    X25519Keypair,
+};
+pub use key_wrap::{unwrap_key, wrap_key, KeyWrapError};
+pub use mac::{constant_time_eq, hmac_sha256, hmac_verify};
+pub use markdown::{
+    parse_markdown, render_markdown, MarkdownNode, MarkdownNodeKind, MarkdownOptions,
+    TableAlignment,
+};
+pub use message_format::{format_message, MessageArgument, MessageFormatError};
+pub use migration::{Migration, MigrationError, MigrationReport, MigrationRunner};
+pub use mime_type::{detect_mime_type, MimeCategory, MimeInfo, MimeSource};
+pub use msgpack::{json_to_msgpack, msgpack_to_json, MsgpackError};
+pub use multipart::{
+    MultipartBody, MultipartBuilder, MultipartError, MultipartFile, MultipartFileSource,
+};
+pub use oauth::{OAuthConfig, OAuthError, OAuthTokens, PkceSession};
+pub use otp::{Hotp, HotpConfig, OtpAlgorithm, OtpError, Totp, TotpConfig};
+pub use password::{
+    bcrypt_hash, bcrypt_verify, hash_password, verify_password, Argon2Params, PasswordError,
+};
+pub use password_policy::{check_password, PasswordPolicy, PolicyViolation};
+pub use password_strength::{estimate_password_strength, StrengthResult};
+pub use pattern::{Regex, RegexError, RegexGroup, RegexMatch, RegexOptions, TextRange};
+pub use pinning::{compute_spki_pin, match_pins, PinningError};
+pub use protobuf::{decode_protobuf, ProtobufError};
+pub use queue::{QueueError, QueueItem, TaskQueue};
+pub use random::{random_alphanumeric, random_bytes, random_u64_in_range, RandomError};
+pub use recovery::{
+    generate_recovery_codes, hash_recovery_code, verify_recovery_code, RecoveryCodeError,
+    RecoveryCodeFormat,
+};
+pub use relative_time::format_relative;
+pub use retry::{default_retry_policy, is_retryable_status, next_delay, RetryPolicy};
+pub use router::{RouteMatch, RouteValue, Router, RouterError};
+pub use sanitize::{sanitize_html, HtmlPolicy};
+pub use scan::{
+    parse_scanned_payload, ContactCard, EpcPayment, ScanError, ScannedPayload, WifiConfig,
+    WifiSecurity,
+};
+pub use search::{SearchError, SearchHighlight, SearchHit, SearchIndex};
+pub use secure_store::{IntegrityReport, SecureStore, SecureStoreError, StoreObserver};
+pub use shamir::{combine_shares, split_secret, SecretSharingError};
+pub use signing::{
+    ed25519_sign, ed25519_verify, generate_ed25519_keypair, generate_rsa_keypair, rsa_sign,
+    rsa_verify, Ed25519Keypair, RsaKeypair, RsaPadding, SigningError,
+};
+pub use sigv4::{sign_request, AwsCredentials, HttpRequestParts, SigV4Error};
+pub use slug::{slugify, SlugOptions};
+pub use snapshot::{MergeStrategy, SnapshotError, SnapshotImportReport};
+pub use template::{render_template, TemplateError};
+pub use timezone::{convert_timezone, list_timezones, timezone_offset_at};
+pub use transport::{
+    clear_http_transport, set_http_transport, HttpResponseParts, HttpTransport, TransportError,
+};
+pub use ulid::{generate_ulid, ulid_timestamp, UlidError};
+pub use unicode::{grapheme_count, normalize, truncate_graphemes, NormalizationForm};
+pub use upload::{upload_file, UploadError, UploadObserver, UploadResult};
+pub use url_parser::{parse_url, UrlBuilder, UrlError, UrlParts};
+pub use url_validation::{validate_url, UrlPolicy, UrlRejection, UrlValidation};
+pub use user_agent::{parse_user_agent, ClientInfo, DeviceType};
+pub use vault::{EncryptedVault, VaultError};
+pub use version::{compare_versions, parse_semver, satisfies, Semver, VersionError};
+pub use websocket::{
+    ReconnectOptions, WebSocketClient, WebSocketError, WebSocketListener, WebSocketState,
+};
+pub use xml::{parse_xml, XmlAttribute, XmlError, XmlNode};
 
 uniffi::setup_scaffolding!();
\ No newline at end of file
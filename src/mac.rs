@@ -0,0 +1,110 @@
+//! メッセージ認証コード（HMAC）モジュール
+//!
+//! このモジュールは、HMAC-SHA256によるメッセージ認証コードの生成と検証、
+//! および定数時間でのバイト列比較を行う関数をエクスポートします。
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// HMAC-SHA256を計算します
+///
+/// # Arguments
+/// * `key` - 秘密鍵（任意の長さ）
+/// * `data` - 認証するデータ
+///
+/// # Returns
+/// * 32バイトのメッセージ認証コード
+///
+/// # Example
+/// ```
+/// let mac = hmac_sha256(b"secret".to_vec(), b"message".to_vec());
+/// assert_eq!(mac.len(), 32);
+/// ```
+#[uniffi::export]
+pub fn hmac_sha256(key: Vec<u8>, data: Vec<u8>) -> Vec<u8> {
+    // HMACは任意長の鍵を受け付けるため失敗しない
+    let mut mac = HmacSha256::new_from_slice(&key)
+        .expect("HMAC accepts keys of any length");
+    mac.update(&data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// HMAC-SHA256のメッセージ認証コードを検証します
+///
+/// 比較は定数時間で行われるため、タイミング攻撃による推測を防ぎます。
+///
+/// # Arguments
+/// * `key` - 秘密鍵
+/// * `data` - 認証するデータ
+/// * `mac` - 検証するメッセージ認証コード
+///
+/// # Returns
+/// * `true` - 認証コードが一致した場合
+/// * `false` - 一致しない場合（長さが異なる場合を含む）
+#[uniffi::export]
+pub fn hmac_verify(key: Vec<u8>, data: Vec<u8>, mac: Vec<u8>) -> bool {
+    let mut expected = HmacSha256::new_from_slice(&key)
+        .expect("HMAC accepts keys of any length");
+    expected.update(&data);
+    expected.verify_slice(&mac).is_ok()
+}
+
+/// 2つのバイト列を定数時間で比較します
+///
+/// MAC・トークン・PINハッシュなどの秘密値の比較に使用します。
+/// 先頭から一致しなくなった時点で打ち切る通常の比較と異なり、
+/// 処理時間から一致した長さを推測されません。
+/// 長さが異なる場合は即座に`false`を返します（長さ自体は秘密として扱いません）。
+///
+/// # Arguments
+/// * `a` - 比較するバイト列
+/// * `b` - 比較するバイト列
+///
+/// # Example
+/// ```
+/// assert!(constant_time_eq(b"token".to_vec(), b"token".to_vec()));
+/// ```
+#[uniffi::export]
+pub fn constant_time_eq(a: Vec<u8>, b: Vec<u8>) -> bool {
+    a.len() == b.len() && bool::from(a.ct_eq(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hmac_sha256_rfc4231_case2() {
+        // RFC 4231 Test Case 2
+        let mac = hmac_sha256(b"Jefe".to_vec(), b"what do ya want for nothing?".to_vec());
+        assert_eq!(
+            hex::encode(mac),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_hmac_verify() {
+        let key = b"shared-secret".to_vec();
+        let data = b"GET /api/items".to_vec();
+        let mac = hmac_sha256(key.clone(), data.clone());
+        assert!(hmac_verify(key.clone(), data.clone(), mac.clone()));
+
+        let mut tampered = mac.clone();
+        tampered[0] ^= 0x01;
+        assert!(!hmac_verify(key.clone(), data.clone(), tampered));
+        assert!(!hmac_verify(key.clone(), b"GET /api/other".to_vec(), mac.clone()));
+        assert!(!hmac_verify(key, data, mac[..16].to_vec()));
+    }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"123456".to_vec(), b"123456".to_vec()));
+        assert!(!constant_time_eq(b"123456".to_vec(), b"123457".to_vec()));
+        assert!(!constant_time_eq(b"123456".to_vec(), b"12345".to_vec()));
+        assert!(constant_time_eq(Vec::new(), Vec::new()));
+    }
+}
@@ -0,0 +1,454 @@
+//! Markdownレンダリングモジュール
+//!
+//! このモジュールは、CommonMark（表・取り消し線・タスクリストの拡張を含む）を
+//! HTMLに変換する`render_markdown`と、型付きのノードの木に変換する`parse_markdown`を
+//! エクスポートします。チャットのメッセージをiOSとAndroidで同じ解釈で表示するために使用します。
+//! `parse_markdown`の結果はNSAttributedStringなどのネイティブの表示に変換できます。
+//!
+//! 生のHTMLは既定で文字列としてエスケープし、`javascript:`などの危険なスキームのリンクは
+//! 無効化するため、ユーザーが入力したメッセージもそのまま表示できます。
+
+use pulldown_cmark::{html, Alignment, CodeBlockKind, CowStr, Event, Options, Parser, Tag};
+
+/// リンク・画像で許可するスキーム（相対URLは常に許可します）
+const SAFE_SCHEMES: [&str; 5] = ["http", "https", "mailto", "tel", "sms"];
+
+/// `parse_markdown`が返すノードの入れ子の最大の深さ
+///
+/// ノードの木の解放やUniFFIでの受け渡しは再帰で行うため、深い入れ子はスタックを溢れさせます。
+const MAX_DEPTH: usize = 64;
+
+/// Markdownの解釈のオプション
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MarkdownOptions {
+    /// 表（GitHub Flavored Markdown）を有効にする
+    #[uniffi(default = true)]
+    pub tables: bool,
+    /// 取り消し線（`~~text~~`）を有効にする
+    #[uniffi(default = true)]
+    pub strikethrough: bool,
+    /// タスクリスト（`- [x] done`）を有効にする
+    #[uniffi(default = true)]
+    pub task_lists: bool,
+    /// 生のHTMLをそのまま出力する（無効の場合は文字列としてエスケープします）
+    #[uniffi(default = false)]
+    pub allow_html: bool,
+    /// 段落内の改行を改行（`<br />`）として扱う（チャット向け）
+    #[uniffi(default = false)]
+    pub hard_breaks: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            tables: true,
+            strikethrough: true,
+            task_lists: true,
+            allow_html: false,
+            hard_breaks: false,
+        }
+    }
+}
+
+/// 表の列の揃え
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum TableAlignment {
+    /// 指定なし
+    None,
+    /// 左揃え
+    Left,
+    /// 中央揃え
+    Center,
+    /// 右揃え
+    Right,
+}
+
+/// Markdownのノードの種類
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum MarkdownNodeKind {
+    /// 段落
+    Paragraph,
+    /// 見出し（レベル1〜6）
+    Heading { level: u8 },
+    /// 引用
+    BlockQuote,
+    /// コードブロック（子ノードを持ちません）
+    CodeBlock { language: Option<String>, code: String },
+    /// リスト（順序付きリストの場合は開始番号を持ちます）
+    List { ordered: bool, start: Option<u64> },
+    /// リストの項目（タスクリストの場合はチェックの状態を持ちます）
+    ListItem { checked: Option<bool> },
+    /// 表
+    Table { alignments: Vec<TableAlignment> },
+    /// 表の見出し行（子ノードはセル）
+    TableHead,
+    /// 表の行
+    TableRow,
+    /// 表のセル
+    TableCell,
+    /// 区切り線
+    ThematicBreak,
+    /// 文字列
+    Text { text: String },
+    /// インラインのコード
+    Code { code: String },
+    /// 強調
+    Emphasis,
+    /// 強い強調
+    Strong,
+    /// 取り消し線
+    Strikethrough,
+    /// リンク（子ノードはリンクの文字列）。危険なスキームのURLは空になります
+    Link { url: String, title: Option<String> },
+    /// 画像（子ノードは代替テキスト）。危険なスキームのURLは空になります
+    Image { url: String, title: Option<String> },
+    /// 段落内の改行（表示では空白として扱います）
+    SoftBreak,
+    /// 強制改行
+    HardBreak,
+    /// 生のHTML（`allow_html`が有効な場合のみ）
+    Html { html: String },
+}
+
+/// Markdownのノード
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MarkdownNode {
+    /// ノードの種類
+    pub kind: MarkdownNodeKind,
+    /// 子ノード
+    pub children: Vec<MarkdownNode>,
+}
+
+/// URLのスキームが安全かどうかを返します（スキームのない相対URLは安全とみなします）
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim();
+    let scheme_end =
+        url.find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.')));
+    match scheme_end {
+        Some(end) if end > 0 && url[end..].starts_with(':') => {
+            SAFE_SCHEMES.iter().any(|scheme| scheme.eq_ignore_ascii_case(&url[..end]))
+        }
+        _ => true,
+    }
+}
+
+/// オプションに従ってMarkdownを解析し、イベントを調整したパーサーを返します
+fn parse_events<'a>(text: &'a str, options: &MarkdownOptions) -> impl Iterator<Item = Event<'a>> {
+    let mut parser_options = Options::empty();
+    if options.tables {
+        parser_options.insert(Options::ENABLE_TABLES);
+    }
+    if options.strikethrough {
+        parser_options.insert(Options::ENABLE_STRIKETHROUGH);
+    }
+    if options.task_lists {
+        parser_options.insert(Options::ENABLE_TASKLISTS);
+    }
+    let allow_html = options.allow_html;
+    let hard_breaks = options.hard_breaks;
+    Parser::new_ext(text, parser_options).map(move |event| match event {
+        Event::Html(html) | Event::InlineHtml(html) if !allow_html => Event::Text(html),
+        Event::SoftBreak if hard_breaks => Event::HardBreak,
+        Event::Start(Tag::Link { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Link { link_type, dest_url: CowStr::from(""), title, id })
+        }
+        Event::Start(Tag::Image { link_type, dest_url, title, id }) if !is_safe_url(&dest_url) => {
+            Event::Start(Tag::Image { link_type, dest_url: CowStr::from(""), title, id })
+        }
+        event => event,
+    })
+}
+
+/// MarkdownをHTMLに変換します
+///
+/// CommonMarkに加えて、オプションで表・取り消し線・タスクリストに対応します。
+/// 生のHTMLは既定でエスケープされ、`http`・`https`・`mailto`・`tel`・`sms`以外の
+/// スキームのリンクと画像のURLは空になります。
+///
+/// # Arguments
+/// * `text` - Markdownのテキスト
+/// * `options` - オプション（省略時は表・取り消し線・タスクリストが有効）
+///
+/// # Example
+/// ```
+/// let html = render_markdown("**Hello** ~~world~~".to_string(), None);
+/// assert_eq!(html, "<p><strong>Hello</strong> <del>world</del></p>\n");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn render_markdown(text: String, options: Option<MarkdownOptions>) -> String {
+    let options = options.unwrap_or_default();
+    let mut output = String::with_capacity(text.len() * 3 / 2);
+    html::push_html(&mut output, parse_events(&text, &options));
+    output
+}
+
+/// 表の列の揃えを変換します
+fn table_alignment(alignment: Alignment) -> TableAlignment {
+    match alignment {
+        Alignment::None => TableAlignment::None,
+        Alignment::Left => TableAlignment::Left,
+        Alignment::Center => TableAlignment::Center,
+        Alignment::Right => TableAlignment::Right,
+    }
+}
+
+/// 開始タグに対応するノードの種類を返します（ノードにしないタグは`None`）
+fn node_kind(tag: Tag) -> Option<MarkdownNodeKind> {
+    Some(match tag {
+        Tag::Paragraph => MarkdownNodeKind::Paragraph,
+        Tag::Heading { level, .. } => MarkdownNodeKind::Heading { level: level as u8 },
+        Tag::BlockQuote(_) => MarkdownNodeKind::BlockQuote,
+        Tag::CodeBlock(kind) => MarkdownNodeKind::CodeBlock {
+            language: match kind {
+                CodeBlockKind::Fenced(info) => info.split_whitespace().next().map(str::to_string),
+                CodeBlockKind::Indented => None,
+            },
+            code: String::new(),
+        },
+        Tag::List(start) => MarkdownNodeKind::List { ordered: start.is_some(), start },
+        Tag::Item => MarkdownNodeKind::ListItem { checked: None },
+        Tag::Table(alignments) => MarkdownNodeKind::Table {
+            alignments: alignments.into_iter().map(table_alignment).collect(),
+        },
+        Tag::TableHead => MarkdownNodeKind::TableHead,
+        Tag::TableRow => MarkdownNodeKind::TableRow,
+        Tag::TableCell => MarkdownNodeKind::TableCell,
+        Tag::Emphasis => MarkdownNodeKind::Emphasis,
+        Tag::Strong => MarkdownNodeKind::Strong,
+        Tag::Strikethrough => MarkdownNodeKind::Strikethrough,
+        Tag::Link { dest_url, title, .. } => MarkdownNodeKind::Link {
+            url: dest_url.into_string(),
+            title: (!title.is_empty()).then(|| title.into_string()),
+        },
+        Tag::Image { dest_url, title, .. } => MarkdownNodeKind::Image {
+            url: dest_url.into_string(),
+            title: (!title.is_empty()).then(|| title.into_string()),
+        },
+        _ => return None,
+    })
+}
+
+/// 葉ノードを作成します
+fn leaf(kind: MarkdownNodeKind) -> MarkdownNode {
+    MarkdownNode { kind, children: Vec::new() }
+}
+
+/// Markdownを型付きのノードの木に変換します
+///
+/// 解釈の規則とURLの無効化は`render_markdown`と同じです。
+/// 生のHTMLは`allow_html`が有効な場合は`Html`、無効な場合は`Text`のノードになります。
+/// 64段より深く入れ子になった引用やリストなどはノードにせず、内容を祖先のノードに含めます。
+///
+/// # Arguments
+/// * `text` - Markdownのテキスト
+/// * `options` - オプション（省略時は表・取り消し線・タスクリストが有効）
+///
+/// # Returns
+/// 最上位のブロックのノード
+///
+/// # Example
+/// ```
+/// let nodes = parse_markdown("# Title".to_string(), None);
+/// assert_eq!(nodes[0].kind, MarkdownNodeKind::Heading { level: 1 });
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn parse_markdown(text: String, options: Option<MarkdownOptions>) -> Vec<MarkdownNode> {
+    let options = options.unwrap_or_default();
+    // ノードにしないタグ（HTMLブロックなど）は`None`の枠にして、終了時に子を親に移す
+    let mut stack: Vec<(Option<MarkdownNodeKind>, Vec<MarkdownNode>)> = vec![(None, Vec::new())];
+    let mut depth = 0;
+    for event in parse_events(&text, &options) {
+        let (kind, children) = stack.last_mut().expect("the root frame is never popped");
+        match event {
+            Event::Start(tag) => {
+                // 深すぎる入れ子のノードは作らず、内容を祖先のノードに含める
+                let kind = node_kind(tag).filter(|_| depth < MAX_DEPTH);
+                depth += usize::from(kind.is_some());
+                stack.push((kind, Vec::new()));
+            }
+            Event::End(_) => {
+                let (kind, children) = stack.pop().expect("End always follows a matching Start");
+                let parent = &mut stack.last_mut().expect("the root frame is never popped").1;
+                depth -= usize::from(kind.is_some());
+                match kind {
+                    Some(kind) => parent.push(MarkdownNode { kind, children }),
+                    None => parent.extend(children),
+                }
+            }
+            Event::Text(text) => match kind {
+                Some(MarkdownNodeKind::CodeBlock { code, .. }) => code.push_str(&text),
+                _ => children.push(leaf(MarkdownNodeKind::Text { text: text.into_string() })),
+            },
+            Event::Code(code) => {
+                children.push(leaf(MarkdownNodeKind::Code { code: code.into_string() }))
+            }
+            Event::Html(html) | Event::InlineHtml(html) => {
+                children.push(leaf(MarkdownNodeKind::Html { html: html.into_string() }))
+            }
+            Event::SoftBreak => children.push(leaf(MarkdownNodeKind::SoftBreak)),
+            Event::HardBreak => children.push(leaf(MarkdownNodeKind::HardBreak)),
+            Event::Rule => children.push(leaf(MarkdownNodeKind::ThematicBreak)),
+            Event::TaskListMarker(checked) => {
+                if let Some(MarkdownNodeKind::ListItem { checked: state }) = kind {
+                    *state = Some(checked);
+                }
+            }
+            // 数式・脚注は有効にしていないため現れない
+            _ => {}
+        }
+    }
+    stack.pop().map(|(_, children)| children).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> MarkdownNode {
+        leaf(MarkdownNodeKind::Text { text: value.to_string() })
+    }
+
+    fn node(kind: MarkdownNodeKind, children: Vec<MarkdownNode>) -> MarkdownNode {
+        MarkdownNode { kind, children }
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let html = render_markdown("# Title\n\n**bold** *em* ~~gone~~ `code`".to_string(), None);
+        assert_eq!(
+            html,
+            concat!(
+                "<h1>Title</h1>\n",
+                "<p><strong>bold</strong> <em>em</em> <del>gone</del> <code>code</code></p>\n"
+            )
+        );
+
+        let html = render_markdown("| a | b |\n|:--|--:|\n| 1 | 2 |".to_string(), None);
+        assert!(html.contains("<table>"));
+        assert!(html.contains("<td style=\"text-align: right\">2</td>"));
+
+        let html = render_markdown("- [x] done\n- [ ] todo".to_string(), None);
+        assert!(html.contains("<input disabled=\"\" type=\"checkbox\" checked=\"\"/>"));
+
+        let options = MarkdownOptions { tables: false, strikethrough: false, ..Default::default() };
+        let html = render_markdown("~~a~~".to_string(), Some(options));
+        assert_eq!(html, "<p>~~a~~</p>\n");
+    }
+
+    #[test]
+    fn test_render_markdown_is_safe_by_default() {
+        let html = render_markdown("<script>alert(1)</script>\n\nhi <b>x</b>".to_string(), None);
+        assert_eq!(html, "&lt;script&gt;alert(1)&lt;/script&gt;\n<p>hi &lt;b&gt;x&lt;/b&gt;</p>\n");
+
+        let html = render_markdown(
+            "[a](javascript:alert(1)) [b](https://example.com) [c](/path)".to_string(),
+            None,
+        );
+        assert_eq!(
+            html,
+            concat!(
+                "<p><a href=\"\">a</a> <a href=\"https://example.com\">b</a> ",
+                "<a href=\"/path\">c</a></p>\n"
+            )
+        );
+
+        let options = MarkdownOptions { allow_html: true, hard_breaks: true, ..Default::default() };
+        let html = render_markdown("line1\nline2 <b>x</b>".to_string(), Some(options));
+        assert_eq!(html, "<p>line1<br />\nline2 <b>x</b></p>\n");
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let nodes = parse_markdown(
+            concat!(
+                "## Hi *there*\n\n```rust\nfn main() {}\n```\n\n",
+                "1. [x] one\n2. [link](https://a.example \"T\")\n"
+            )
+            .to_string(),
+            None,
+        );
+        assert_eq!(
+            nodes,
+            vec![
+                node(
+                    MarkdownNodeKind::Heading { level: 2 },
+                    vec![text("Hi "), node(MarkdownNodeKind::Emphasis, vec![text("there")])]
+                ),
+                leaf(MarkdownNodeKind::CodeBlock {
+                    language: Some("rust".to_string()),
+                    code: "fn main() {}\n".to_string(),
+                }),
+                node(
+                    MarkdownNodeKind::List { ordered: true, start: Some(1) },
+                    vec![
+                        node(MarkdownNodeKind::ListItem { checked: Some(true) }, vec![text("one")]),
+                        node(
+                            MarkdownNodeKind::ListItem { checked: None },
+                            vec![node(
+                                MarkdownNodeKind::Link {
+                                    url: "https://a.example".to_string(),
+                                    title: Some("T".to_string()),
+                                },
+                                vec![text("link")]
+                            )]
+                        ),
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_markdown_table_and_html() {
+        let nodes = parse_markdown("| a |\n|:-:|\n| `1` |".to_string(), None);
+        assert_eq!(
+            nodes,
+            vec![node(
+                MarkdownNodeKind::Table { alignments: vec![TableAlignment::Center] },
+                vec![
+                    node(
+                        MarkdownNodeKind::TableHead,
+                        vec![node(MarkdownNodeKind::TableCell, vec![text("a")])]
+                    ),
+                    node(
+                        MarkdownNodeKind::TableRow,
+                        vec![node(
+                            MarkdownNodeKind::TableCell,
+                            vec![leaf(MarkdownNodeKind::Code { code: "1".to_string() })]
+                        )]
+                    ),
+                ]
+            )]
+        );
+
+        let nodes = parse_markdown("a <b>x</b>\nb".to_string(), None);
+        assert_eq!(nodes[0].children[1], text("<b>"));
+        assert_eq!(nodes[0].children[4], leaf(MarkdownNodeKind::SoftBreak));
+
+        let options = MarkdownOptions { allow_html: true, ..Default::default() };
+        let nodes = parse_markdown("<div>raw</div>".to_string(), Some(options));
+        assert_eq!(
+            nodes,
+            vec![leaf(MarkdownNodeKind::Html { html: "<div>raw</div>".to_string() })]
+        );
+    }
+
+    #[test]
+    fn test_parse_markdown_depth_limit() {
+        let text = format!("{} x", ">".repeat(10_000));
+        let nodes = parse_markdown(text.clone(), None);
+        let mut depth = 0;
+        let mut level = nodes.as_slice();
+        while let Some(node) = level.first() {
+            depth += 1;
+            level = &node.children;
+        }
+        assert_eq!(depth, MAX_DEPTH + 1);
+        let mut node = &nodes[0];
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        assert_eq!(node.kind, MarkdownNodeKind::Text { text: "x".to_string() });
+        assert!(render_markdown(text, None).contains("<blockquote>"));
+    }
+}
@@ -0,0 +1,880 @@
+//! メッセージ書式化モジュール
+//!
+//! このモジュールは、ICUのMessageFormatの構文のサブセットでメッセージを書式化する
+//! `format_message`をエクスポートします。翻訳ファイルの`{count, plural, one {# item} other {# items}}`
+//! のような文字列を、CLDRの複数形規則と数値の書式で展開します。規則と書式のデータは
+//! ライブラリに組み込まれているため、OSのバージョンやプラットフォームによらず同じ結果になります。
+//!
+//! 対応する構文は次のとおりです。
+//! * `{name}` - 値をそのまま（数値と日時は既定の書式で）出力
+//! * `{name, number}`・`{name, number, integer|percent}` - 数値
+//! * `{name, date}`・`{name, date, short|medium|long}` - 日付（既定は`medium`）
+//! * `{name, time}`・`{name, time, short|medium}` - 時刻（既定は`short`）
+//! * `{name, plural, [offset:n] =0 {…} one {…} other {…}}` - 基数の複数形（`#`は数値）
+//! * `{name, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}` - 序数の複数形
+//! * `{name, select, male {…} female {…} other {…}}` - 文字列による選択
+//!
+//! `'`は`{`・`}`（複数形の中では`#`も）の前に置くと、次の`'`までを文字どおりに出力します。
+//! `''`は`'`を出力します。
+
+use std::collections::HashMap;
+
+use chrono::Datelike;
+use fixed_decimal::{Decimal, SignedRoundingMode, UnsignedRoundingMode};
+use icu_decimal::DecimalFormatter;
+use icu_locale_core::Locale;
+use icu_plurals::{PluralCategory, PluralRules};
+use thiserror::Error;
+
+use crate::datetime::{format_local, Timestamp};
+
+/// 引数の入れ子の上限（解析と書式化の再帰がスタックを溢れさせないようにする）
+const MAX_DEPTH: usize = 32;
+
+/// メッセージの書式化で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum MessageFormatError {
+    /// パターンの構文が正しくない場合
+    #[error("Invalid message pattern: {0}")]
+    InvalidPattern(String),
+    /// 引数が指定されていない場合
+    #[error("Missing value for argument: {0}")]
+    MissingArgument(String),
+    /// 引数の種類が書式に合わない、または値が扱える範囲外の場合
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+    /// ロケール識別子が正しくない場合
+    #[error("Invalid locale: {0}")]
+    InvalidLocale(String),
+}
+
+/// メッセージの引数の値
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum MessageArgument {
+    /// 文字列（`select`の選択に使用します）
+    Text { value: String },
+    /// 数値（`number`・`plural`・`selectordinal`に使用します）
+    Number { value: f64 },
+    /// 日時（`date`・`time`に使用します。`utc_offset_seconds`の時刻で表示します）
+    Date { value: Timestamp },
+}
+
+/// 言語ごとの日時とパーセントの表記
+///
+/// パターンは`format_datetime`と同じ形式で、`{MMMM}`・`{MMM}`は月の名前に置換されます。
+struct LocaleFormats {
+    /// 言語コード（ISO 639-1）
+    language: &'static str,
+    /// 日付（short・medium・long）
+    date: [&'static str; 3],
+    /// 時刻（short・medium）
+    time: [&'static str; 2],
+    /// 月の名前
+    months: &'static [&'static str],
+    /// 月の名前の省略形
+    short_months: &'static [&'static str],
+    /// パーセントの表記（`{n}`が数値に置換されます）
+    percent: &'static str,
+}
+
+/// 対応言語の表記（CLDRに基づく、先頭がフォールバック言語）
+const LOCALE_FORMATS: &[LocaleFormats] = &[
+    LocaleFormats {
+        language: "en",
+        date: ["M/d/yy", "{MMM} d, y", "{MMMM} d, y"],
+        time: ["h:mm a", "h:mm:ss a"],
+        months: &[
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+        short_months: &[
+            "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+        ],
+        percent: "{n}%",
+    },
+    LocaleFormats {
+        language: "ja",
+        date: ["y/MM/dd", "y/MM/dd", "y年M月d日"],
+        time: ["H:mm", "H:mm:ss"],
+        months: &[],
+        short_months: &[],
+        percent: "{n}%",
+    },
+    LocaleFormats {
+        language: "es",
+        date: ["d/M/yy", "d {MMM} y", "d 'de' {MMMM} 'de' y"],
+        time: ["H:mm", "H:mm:ss"],
+        months: &[
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+        short_months: &[
+            "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sept", "oct", "nov", "dic",
+        ],
+        percent: "{n}\u{a0}%",
+    },
+    LocaleFormats {
+        language: "fr",
+        date: ["dd/MM/y", "d {MMM} y", "d {MMMM} y"],
+        time: ["HH:mm", "HH:mm:ss"],
+        months: &[
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+        short_months: &[
+            "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.",
+            "nov.", "déc.",
+        ],
+        percent: "{n}\u{202f}%",
+    },
+    LocaleFormats {
+        language: "de",
+        date: ["dd.MM.yy", "dd.MM.y", "d. {MMMM} y"],
+        time: ["HH:mm", "HH:mm:ss"],
+        months: &[
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+        short_months: &[],
+        percent: "{n}\u{a0}%",
+    },
+    LocaleFormats {
+        language: "zh",
+        date: ["y/M/d", "y年M月d日", "y年M月d日"],
+        time: ["HH:mm", "HH:mm:ss"],
+        months: &[],
+        short_months: &[],
+        percent: "{n}%",
+    },
+];
+
+/// 数値の書式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NumberStyle {
+    /// 小数点以下3桁まで
+    Decimal,
+    /// 整数に丸める
+    Integer,
+    /// 100倍して整数に丸め、`%`を付ける
+    Percent,
+}
+
+/// 複数形の選択肢のキー
+#[derive(Debug, Clone, PartialEq)]
+enum PluralKey {
+    /// `=n`（値が一致する場合）
+    Exact(f64),
+    /// `zero`・`one`・`two`・`few`・`many`・`other`
+    Category(String),
+}
+
+/// 引数の書式
+#[derive(Debug, Clone, PartialEq)]
+enum ArgumentFormat {
+    /// `{name}`
+    Simple,
+    /// `{name, number}`
+    Number(NumberStyle),
+    /// `{name, date}`（short・medium・longの位置）
+    Date(usize),
+    /// `{name, time}`（short・mediumの位置）
+    Time(usize),
+    /// `{name, plural}`・`{name, selectordinal}`
+    Plural { ordinal: bool, offset: f64, cases: Vec<(PluralKey, Vec<Part>)> },
+    /// `{name, select}`
+    Select { cases: Vec<(String, Vec<Part>)> },
+}
+
+/// メッセージを構成する要素
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    /// 文字列
+    Text(String),
+    /// 複数形の中の`#`
+    Number,
+    /// 引数
+    Argument { name: String, format: ArgumentFormat },
+}
+
+/// パターンを先頭から読み進める解析器
+struct Parser<'a> {
+    pattern: &'a str,
+    position: usize,
+    /// 現在解析中の引数の入れ子の深さ
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.pattern[self.position..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> MessageFormatError {
+        MessageFormatError::InvalidPattern(format!("{} at byte {}", message, self.position))
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    /// 空白を読み飛ばしてから`expected`を読みます
+    fn expect(&mut self, expected: char) -> Result<(), MessageFormatError> {
+        self.skip_whitespace();
+        if self.peek() != Some(expected) {
+            return Err(self.error(&format!("expected '{}'", expected)));
+        }
+        self.bump();
+        Ok(())
+    }
+
+    /// 空白を読み飛ばしてから引数名やキーワードを読みます
+    fn identifier(&mut self) -> Result<&'a str, MessageFormatError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+            self.bump();
+        }
+        if start == self.position {
+            return Err(self.error("expected an identifier"));
+        }
+        Ok(&self.pattern[start..self.position])
+    }
+
+    /// 空白を読み飛ばしてから数値を読みます
+    fn number(&mut self) -> Result<f64, MessageFormatError> {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+')) {
+            self.bump();
+        }
+        self.pattern[start..self.position].parse().map_err(|_| self.error("expected a number"))
+    }
+
+    /// 閉じていない`{`の位置まで、または末尾までのメッセージを解析します
+    fn message(&mut self, nested: bool, in_plural: bool) -> Result<Vec<Part>, MessageFormatError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            match c {
+                '}' if nested => break,
+                '}' => return Err(self.error("unexpected '}'")),
+                '{' => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    self.bump();
+                    if self.depth == MAX_DEPTH {
+                        return Err(
+                            self.error(&format!("arguments are nested deeper than {}", MAX_DEPTH))
+                        );
+                    }
+                    self.depth += 1;
+                    parts.push(self.argument(in_plural)?);
+                    self.depth -= 1;
+                }
+                '#' if in_plural => {
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    self.bump();
+                    parts.push(Part::Number);
+                }
+                '\'' => {
+                    self.bump();
+                    match self.peek() {
+                        Some('\'') => {
+                            self.bump();
+                            text.push('\'');
+                        }
+                        Some('{' | '}') => self.quoted(&mut text),
+                        Some('#') if in_plural => self.quoted(&mut text),
+                        _ => text.push('\''),
+                    }
+                }
+                c => {
+                    self.bump();
+                    text.push(c);
+                }
+            }
+        }
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+        Ok(parts)
+    }
+
+    /// `'`で囲まれた部分を読みます（閉じていない場合は末尾まで）
+    fn quoted(&mut self, text: &mut String) {
+        while let Some(c) = self.bump() {
+            if c != '\'' {
+                text.push(c);
+            } else if self.peek() == Some('\'') {
+                self.bump();
+                text.push('\'');
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// `{`の後の引数を解析します
+    fn argument(&mut self, in_plural: bool) -> Result<Part, MessageFormatError> {
+        let name = self.identifier()?.to_string();
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.bump();
+            return Ok(Part::Argument { name, format: ArgumentFormat::Simple });
+        }
+        self.expect(',')?;
+        let kind = self.identifier()?;
+        let format = match kind {
+            "number" => {
+                let style = match self.style()? {
+                    None => NumberStyle::Decimal,
+                    Some("integer") => NumberStyle::Integer,
+                    Some("percent") => NumberStyle::Percent,
+                    Some(style) => {
+                        return Err(self.error(&format!("unknown number style {}", style)))
+                    }
+                };
+                ArgumentFormat::Number(style)
+            }
+            "date" => match self.style()? {
+                None | Some("medium") => ArgumentFormat::Date(1),
+                Some("short") => ArgumentFormat::Date(0),
+                Some("long") => ArgumentFormat::Date(2),
+                Some(style) => return Err(self.error(&format!("unknown date style {}", style))),
+            },
+            "time" => match self.style()? {
+                None | Some("short") => ArgumentFormat::Time(0),
+                Some("medium") => ArgumentFormat::Time(1),
+                Some(style) => return Err(self.error(&format!("unknown time style {}", style))),
+            },
+            "plural" | "selectordinal" => self.plural(kind == "selectordinal")?,
+            "select" => {
+                self.expect(',')?;
+                let mut cases = Vec::new();
+                for (key, message) in self.cases(in_plural)? {
+                    cases.push((key.to_string(), message));
+                }
+                ArgumentFormat::Select { cases }
+            }
+            _ => return Err(self.error(&format!("unknown argument type {}", kind))),
+        };
+        self.expect('}')?;
+        Ok(Part::Argument { name, format })
+    }
+
+    /// 省略可能な`, style`を読みます
+    fn style(&mut self) -> Result<Option<&'a str>, MessageFormatError> {
+        self.skip_whitespace();
+        if self.peek() != Some(',') {
+            return Ok(None);
+        }
+        self.bump();
+        self.identifier().map(Some)
+    }
+
+    /// `plural`・`selectordinal`の`offset:`と選択肢を解析します
+    fn plural(&mut self, ordinal: bool) -> Result<ArgumentFormat, MessageFormatError> {
+        self.expect(',')?;
+        self.skip_whitespace();
+        let mut offset = 0.0;
+        if self.pattern[self.position..].starts_with("offset:") {
+            self.position += "offset:".len();
+            offset = self.number()?;
+        }
+        let mut cases = Vec::new();
+        for (key, message) in self.cases(true)? {
+            let key = match key.strip_prefix('=') {
+                Some(value) => PluralKey::Exact(
+                    value.parse().map_err(|_| self.error(&format!("invalid key {}", key)))?,
+                ),
+                None => PluralKey::Category(key.to_string()),
+            };
+            cases.push((key, message));
+        }
+        Ok(ArgumentFormat::Plural { ordinal, offset, cases })
+    }
+
+    /// `key {message}`の並びを解析します（`other`は必須）
+    fn cases(&mut self, in_plural: bool) -> Result<Vec<(&'a str, Vec<Part>)>, MessageFormatError> {
+        let mut cases = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if matches!(self.peek(), Some('}') | None) {
+                break;
+            }
+            let start = self.position;
+            if self.peek() == Some('=') {
+                self.bump();
+                self.number()?;
+            } else {
+                self.identifier()?;
+            }
+            let key = &self.pattern[start..self.position];
+            self.expect('{')?;
+            let message = self.message(true, in_plural)?;
+            self.expect('}')?;
+            cases.push((key, message));
+        }
+        if !cases.iter().any(|(key, _)| *key == "other") {
+            return Err(self.error("missing 'other' case"));
+        }
+        Ok(cases)
+    }
+}
+
+/// 解析したメッセージを引数で展開する書式化器
+struct Formatter<'a> {
+    arguments: &'a HashMap<String, MessageArgument>,
+    formats: &'static LocaleFormats,
+    decimal: DecimalFormatter,
+    cardinal: PluralRules,
+    ordinal: PluralRules,
+}
+
+impl Formatter<'_> {
+    fn argument(&self, name: &str) -> Result<&MessageArgument, MessageFormatError> {
+        self.arguments
+            .get(name)
+            .ok_or_else(|| MessageFormatError::MissingArgument(name.to_string()))
+    }
+
+    fn number_argument(&self, name: &str) -> Result<f64, MessageFormatError> {
+        match self.argument(name)? {
+            MessageArgument::Number { value } => Ok(*value),
+            _ => Err(MessageFormatError::InvalidArgument(format!("{} must be a number", name))),
+        }
+    }
+
+    fn format(
+        &self,
+        parts: &[Part],
+        number: Option<&Decimal>,
+    ) -> Result<String, MessageFormatError> {
+        let mut output = String::new();
+        for part in parts {
+            match part {
+                Part::Text(text) => output.push_str(text),
+                Part::Number => match number {
+                    Some(number) => output.push_str(&self.decimal.format_to_string(number)),
+                    None => output.push('#'),
+                },
+                Part::Argument { name, format } => {
+                    output.push_str(&self.format_argument(name, format, number)?)
+                }
+            }
+        }
+        Ok(output)
+    }
+
+    fn format_argument(
+        &self,
+        name: &str,
+        format: &ArgumentFormat,
+        number: Option<&Decimal>,
+    ) -> Result<String, MessageFormatError> {
+        match format {
+            ArgumentFormat::Simple => match self.argument(name)? {
+                MessageArgument::Text { value } => Ok(value.clone()),
+                MessageArgument::Number { value } => {
+                    self.format_number(*value, NumberStyle::Decimal)
+                }
+                MessageArgument::Date { value } => Ok(format!(
+                    "{} {}",
+                    self.format_date(value, self.formats.date[0])?,
+                    self.format_date(value, self.formats.time[0])?
+                )),
+            },
+            ArgumentFormat::Number(style) => {
+                self.format_number(self.number_argument(name)?, *style)
+            }
+            ArgumentFormat::Date(style) | ArgumentFormat::Time(style) => {
+                let MessageArgument::Date { value } = self.argument(name)? else {
+                    return Err(MessageFormatError::InvalidArgument(format!(
+                        "{} must be a date",
+                        name
+                    )));
+                };
+                let patterns: &[&str] = match format {
+                    ArgumentFormat::Date(_) => &self.formats.date,
+                    _ => &self.formats.time,
+                };
+                self.format_date(value, patterns[*style])
+            }
+            ArgumentFormat::Plural { ordinal, offset, cases } => {
+                let value = self.number_argument(name)?;
+                let number = to_decimal(value - offset, NumberStyle::Decimal)?;
+                let rules = if *ordinal { &self.ordinal } else { &self.cardinal };
+                let category = category_name(rules.category_for(&number));
+                let message = cases
+                    .iter()
+                    .find(|(key, _)| *key == PluralKey::Exact(value))
+                    .or_else(|| cases.iter().find(|(key, _)| is_category(key, category)))
+                    .or_else(|| cases.iter().find(|(key, _)| is_category(key, "other")))
+                    .map(|(_, message)| message);
+                self.format(message.map(Vec::as_slice).unwrap_or_default(), Some(&number))
+            }
+            ArgumentFormat::Select { cases } => {
+                let MessageArgument::Text { value } = self.argument(name)? else {
+                    return Err(MessageFormatError::InvalidArgument(format!(
+                        "{} must be a text",
+                        name
+                    )));
+                };
+                let message = cases
+                    .iter()
+                    .find(|(key, _)| key == value)
+                    .or_else(|| cases.iter().find(|(key, _)| key == "other"))
+                    .map(|(_, message)| message);
+                self.format(message.map(Vec::as_slice).unwrap_or_default(), number)
+            }
+        }
+    }
+
+    fn format_number(&self, value: f64, style: NumberStyle) -> Result<String, MessageFormatError> {
+        let formatted = self.decimal.format_to_string(&to_decimal(value, style)?);
+        Ok(match style {
+            NumberStyle::Percent => self.formats.percent.replace("{n}", &formatted),
+            _ => formatted,
+        })
+    }
+
+    fn format_date(
+        &self,
+        timestamp: &Timestamp,
+        pattern: &str,
+    ) -> Result<String, MessageFormatError> {
+        let local = timestamp
+            .local_datetime()
+            .map_err(|error| MessageFormatError::InvalidArgument(error.to_string()))?;
+        let month = local.month0() as usize;
+        let quote = |names: &[&str]| {
+            format!("'{}'", names.get(month).copied().unwrap_or_default().replace('\'', "''"))
+        };
+        let pattern = pattern
+            .replace("{MMMM}", &quote(self.formats.months))
+            .replace("{MMM}", &quote(self.formats.short_months));
+        format_local(&local, timestamp.utc_offset_seconds, &pattern)
+            .map_err(|error| MessageFormatError::InvalidArgument(error.to_string()))
+    }
+}
+
+/// 数値を書式に合わせて丸めた10進数に変換します
+fn to_decimal(value: f64, style: NumberStyle) -> Result<Decimal, MessageFormatError> {
+    if !value.is_finite() {
+        return Err(MessageFormatError::InvalidArgument(format!("{} is not finite", value)));
+    }
+    // f64のDisplayは指数表記を使わない最短の表記
+    let mut decimal = Decimal::try_from_str(&value.to_string())
+        .map_err(|_| MessageFormatError::InvalidArgument(value.to_string()))?;
+    if style == NumberStyle::Percent {
+        decimal.absolute.multiply_pow10(2);
+    }
+    let position = if style == NumberStyle::Decimal { -3 } else { 0 };
+    decimal.round_with_mode(position, SignedRoundingMode::Unsigned(UnsignedRoundingMode::HalfEven));
+    decimal.absolute.trim_start();
+    decimal.absolute.trim_end();
+    Ok(decimal)
+}
+
+/// 複数形のカテゴリのキーワードを返します
+fn category_name(category: PluralCategory) -> &'static str {
+    match category {
+        PluralCategory::Zero => "zero",
+        PluralCategory::One => "one",
+        PluralCategory::Two => "two",
+        PluralCategory::Few => "few",
+        PluralCategory::Many => "many",
+        PluralCategory::Other => "other",
+    }
+}
+
+fn is_category(key: &PluralKey, category: &str) -> bool {
+    matches!(key, PluralKey::Category(name) if name == category)
+}
+
+/// ロケール識別子を解析します（`ja_JP.UTF-8`のようなPOSIX形式も受け付けます）
+fn parse_locale(locale: &str) -> Result<Locale, MessageFormatError> {
+    let identifier = locale.trim().split(['.', '@']).next().unwrap_or_default().replace('_', "-");
+    Locale::try_from_str(&identifier)
+        .map_err(|_| MessageFormatError::InvalidLocale(locale.to_string()))
+}
+
+/// ICUのMessageFormat形式のパターンを引数で展開します
+///
+/// 複数形のカテゴリ（`one`・`few`など）と数値の書式（桁区切り・小数点）はCLDRの
+/// ロケールのデータに従います。`plural`は`=n`の完全一致を優先し、次にカテゴリ、
+/// 最後に`other`を選択します。`#`は`offset`を引いた数値に置換されます。
+/// 日時の表記は英語・日本語・スペイン語・フランス語・ドイツ語・中国語に対応し、
+/// それ以外の言語は英語の表記になります。
+///
+/// # Arguments
+/// * `pattern` - MessageFormat形式のパターン
+/// * `args` - 引数名と値の対応
+/// * `locale` - ロケール識別子（例: `"ja"`, `"en-US"`, `"fr_CA"`）
+///
+/// # Errors
+/// * `MessageFormatError::InvalidPattern` - パターンの構文が正しくない場合
+/// * `MessageFormatError::MissingArgument` - 値が指定されていない引数がある場合
+/// * `MessageFormatError::InvalidArgument` - 引数の種類が書式に合わない場合
+/// * `MessageFormatError::InvalidLocale` - ロケール識別子が正しくない場合
+///
+/// # Example
+/// ```
+/// let pattern = "{count, plural, =0 {No messages} one {# message} other {# messages}}";
+/// let mut args = HashMap::new();
+/// args.insert("count".to_string(), MessageArgument::Number { value: 1234.0 });
+/// let text = format_message(pattern.to_string(), args, "en-US".to_string())?;
+/// assert_eq!(text, "1,234 messages");
+/// ```
+#[uniffi::export]
+pub fn format_message(
+    pattern: String,
+    args: HashMap<String, MessageArgument>,
+    locale: String,
+) -> Result<String, MessageFormatError> {
+    let parsed_locale = parse_locale(&locale)?;
+    let data_error = |_| MessageFormatError::InvalidLocale(locale.clone());
+    let language = parsed_locale.id.language.as_str();
+    let formats = LOCALE_FORMATS
+        .iter()
+        .find(|formats| formats.language == language)
+        .unwrap_or(&LOCALE_FORMATS[0]);
+    let formatter = Formatter {
+        arguments: &args,
+        formats,
+        decimal: DecimalFormatter::try_new((&parsed_locale).into(), Default::default())
+            .map_err(data_error)?,
+        cardinal: PluralRules::try_new_cardinal((&parsed_locale).into()).map_err(data_error)?,
+        ordinal: PluralRules::try_new_ordinal((&parsed_locale).into()).map_err(data_error)?,
+    };
+    let mut parser = Parser { pattern: &pattern, position: 0, depth: 0 };
+    let parts = parser.message(false, false)?;
+    formatter.format(&parts, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn format(
+        pattern: &str,
+        args: &[(&str, MessageArgument)],
+        locale: &str,
+    ) -> Result<String, MessageFormatError> {
+        let args = args.iter().map(|(name, value)| (name.to_string(), value.clone())).collect();
+        format_message(pattern.to_string(), args, locale.to_string())
+    }
+
+    fn number(value: f64) -> MessageArgument {
+        MessageArgument::Number { value }
+    }
+
+    fn text(value: &str) -> MessageArgument {
+        MessageArgument::Text { value: value.to_string() }
+    }
+
+    #[test]
+    fn test_format_message_plural() {
+        let pattern = "{count, plural, =0 {No items} one {# item} other {# items}}";
+        let plural =
+            |value: f64, locale: &str| format(pattern, &[("count", number(value))], locale);
+        assert_eq!(plural(0.0, "en").unwrap(), "No items");
+        assert_eq!(plural(1.0, "en").unwrap(), "1 item");
+        assert_eq!(plural(1.5, "en").unwrap(), "1.5 items");
+        assert_eq!(plural(1234.0, "en-US").unwrap(), "1,234 items");
+        assert_eq!(plural(1234.5, "de").unwrap(), "1.234,5 items");
+        // 日本語は常にother
+        assert_eq!(plural(1.0, "ja_JP.UTF-8").unwrap(), "1 items");
+
+        // ロシア語のone・few・many
+        let russian = "{n, plural, one {# файл} few {# файла} many {# файлов} other {# файла}}";
+        let files = |value: f64| format(russian, &[("n", number(value))], "ru").unwrap();
+        assert_eq!(files(21.0), "21 файл");
+        assert_eq!(files(3.0), "3 файла");
+        assert_eq!(files(11.0), "11 файлов");
+        assert_eq!(files(2.5), "2,5 файла");
+
+        let offset = "{n, plural, offset:1 =0 {nobody} =1 {{name}} one {{name} and # other} \
+                      other {{name} and # others}}";
+        let guests = |value: f64| {
+            format(offset, &[("n", number(value)), ("name", text("Alice"))], "en").unwrap()
+        };
+        assert_eq!(guests(0.0), "nobody");
+        assert_eq!(guests(1.0), "Alice");
+        assert_eq!(guests(2.0), "Alice and 1 other");
+        assert_eq!(guests(3.0), "Alice and 2 others");
+    }
+
+    #[test]
+    fn test_format_message_select_and_ordinal() {
+        let pattern = concat!(
+            "{gender, select, female {She} male {He} other {They}} finished ",
+            "{place, selectordinal, one {#st} two {#nd} few {#rd} other {#th}}."
+        );
+        let finish = |gender: &str, place: f64| {
+            format(pattern, &[("gender", text(gender)), ("place", number(place))], "en").unwrap()
+        };
+        assert_eq!(finish("female", 1.0), "She finished 1st.");
+        assert_eq!(finish("male", 22.0), "He finished 22nd.");
+        assert_eq!(finish("unknown", 103.0), "They finished 103rd.");
+        assert_eq!(finish("female", 11.0), "She finished 11th.");
+
+        // selectの中の`#`は外側のpluralの数値
+        let nested = "{n, plural, other {{g, select, other {# guests}}}}";
+        let args = [("n", number(5.0)), ("g", text("x"))];
+        assert_eq!(format(nested, &args, "en").unwrap(), "5 guests");
+    }
+
+    #[test]
+    fn test_format_message_number_and_date() {
+        let args = [("n", number(0.4567)), ("big", number(-1234567.891))];
+        assert_eq!(format("{n, number, percent}", &args, "en").unwrap(), "46%");
+        assert_eq!(format("{n, number, percent}", &args, "fr").unwrap(), "46\u{202f}%");
+        assert_eq!(format("{big, number, integer}", &args, "en").unwrap(), "-1,234,568");
+        assert_eq!(format("{big}", &args, "fr").unwrap(), "-1\u{202f}234\u{202f}567,891");
+        assert_eq!(format("{n, number}", &args, "en").unwrap(), "0.457");
+
+        // 2024-03-05T14:07:09+09:00
+        let date = MessageArgument::Date {
+            value: Timestamp {
+                unix_seconds: 1709615229,
+                nanoseconds: 0,
+                utc_offset_seconds: 32400,
+            },
+        };
+        let args = [("d", date)];
+        let date = |pattern: &str, locale: &str| format(pattern, &args, locale).unwrap();
+        assert_eq!(date("{d, date}", "en"), "Mar 5, 2024");
+        assert_eq!(date("{d, date, short}", "en"), "3/5/24");
+        assert_eq!(date("{d, date, long}", "ja"), "2024年3月5日");
+        assert_eq!(date("{d, date, long}", "es"), "5 de marzo de 2024");
+        assert_eq!(date("{d, date, medium}", "fr"), "5 mars 2024");
+        assert_eq!(date("{d, date, long}", "de"), "5. März 2024");
+        assert_eq!(date("{d, time}", "en"), "2:07 PM");
+        assert_eq!(date("{d, time, medium}", "de"), "14:07:09");
+        assert_eq!(date("{d}", "ja"), "2024/03/05 14:07");
+    }
+
+    #[test]
+    fn test_format_message_quoting() {
+        let args = [("n", number(2.0)), ("name", text("Bob"))];
+        assert_eq!(format("It''s {name}", &args, "en").unwrap(), "It's Bob");
+        assert_eq!(format("'{name}' is {name}", &args, "en").unwrap(), "{name} is Bob");
+        assert_eq!(format("Don't", &args, "en").unwrap(), "Don't");
+        assert_eq!(format("{n, plural, other {'#' #}}", &args, "en").unwrap(), "# 2");
+        assert_eq!(format("# {n}", &args, "en").unwrap(), "# 2");
+    }
+
+    #[test]
+    fn test_format_message_depth_limit() {
+        let args = [("g", text("x"))];
+        let nested = |depth: usize| {
+            format!("{}x{}", "{g, select, other {".repeat(depth), "}}".repeat(depth))
+        };
+        assert_eq!(format(&nested(MAX_DEPTH), &args, "en").unwrap(), "x");
+        match format(&nested(MAX_DEPTH + 1), &args, "en") {
+            Err(MessageFormatError::InvalidPattern(message)) => {
+                assert!(message.contains("nested deeper"), "{}", message)
+            }
+            other => panic!("Expected InvalidPattern error, got {:?}", other),
+        }
+        // 閉じていない深い入れ子もスタックを溢れさせずにエラーになる
+        let unclosed = "{g, select, other {".repeat(100_000);
+        assert!(matches!(
+            format(&unclosed, &args, "en"),
+            Err(MessageFormatError::InvalidPattern(_))
+        ));
+    }
+
+    #[test]
+    fn test_format_message_errors() {
+        let args = [("n", number(1.0)), ("name", text("Bob"))];
+        for pattern in [
+            "{name",
+            "name}",
+            "{}",
+            "{n, plural, one {x}}",
+            "{n, plural, other {x}",
+            "{n, unknown}",
+            "{n, number, currency}",
+            "{n, plural, =x {a} other {b}}",
+        ] {
+            match format(pattern, &args, "en") {
+                Err(MessageFormatError::InvalidPattern(_)) => (),
+                other => panic!("Expected InvalidPattern error for {}, got {:?}", pattern, other),
+            }
+        }
+        assert!(matches!(
+            format("{missing}", &args, "en"),
+            Err(MessageFormatError::MissingArgument(name)) if name == "missing"
+        ));
+        assert!(matches!(
+            format("{name, plural, other {#}}", &args, "en"),
+            Err(MessageFormatError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            format("{n, select, other {x}}", &args, "en"),
+            Err(MessageFormatError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            format("{n, date}", &args, "en"),
+            Err(MessageFormatError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            format("x", &args, "not a locale"),
+            Err(MessageFormatError::InvalidLocale(_))
+        ));
+    }
+}
@@ -0,0 +1,395 @@
+//! スキーママイグレーションモジュール
+//!
+//! このモジュールは、`Database`に対して番号付きのSQLマイグレーションを順に適用する
+//! `MigrationRunner`をエクスポートします。適用済みのマイグレーションは
+//! `schema_migrations`テーブルにSQLのチェックサムと共に記録され、
+//! 適用後にSQLが書き換えられた場合はエラーとして検出されます。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection, TransactionBehavior};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::database::{Database, DatabaseError};
+
+/// 適用済みのマイグレーションを記録するテーブルの作成SQL
+const CREATE_VERSION_TABLE: &str = "CREATE TABLE IF NOT EXISTS schema_migrations (
+    version INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    checksum TEXT NOT NULL,
+    applied_at INTEGER NOT NULL
+)";
+
+/// マイグレーションの適用で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum MigrationError {
+    /// マイグレーションの一覧が不正な場合（バージョンが0、昇順でない、重複している）
+    #[error("Invalid migrations: {0}")]
+    InvalidMigrations(String),
+    /// 適用済みのマイグレーションのSQLが変更されている場合
+    #[error("Checksum mismatch for migration {version}: expected {expected}, found {actual}")]
+    ChecksumMismatch {
+        version: u32,
+        expected: String,
+        actual: String,
+    },
+    /// データベースに一覧にないバージョンが適用されている場合（新しいアプリで更新された場合など）
+    #[error("Database has unknown migration version {0} applied")]
+    UnknownVersion(u32),
+    /// マイグレーションのSQLの実行に失敗した場合
+    #[error("Migration {version} failed: {message}")]
+    MigrationFailed { version: u32, message: String },
+    /// データベースの操作に失敗した場合
+    #[error("Database error: {0}")]
+    DatabaseError(String),
+}
+
+impl From<DatabaseError> for MigrationError {
+    fn from(error: DatabaseError) -> Self {
+        MigrationError::DatabaseError(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for MigrationError {
+    fn from(error: rusqlite::Error) -> Self {
+        DatabaseError::from(error).into()
+    }
+}
+
+/// 1つのマイグレーション
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct Migration {
+    /// バージョン（1以上、一覧内で昇順）
+    pub version: u32,
+    /// 説明用の名前（例: `create_notes`）
+    pub name: String,
+    /// 実行するSQL（セミコロン区切りで複数の文を記述できます）
+    pub sql: String,
+}
+
+/// マイグレーションの実行結果
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct MigrationReport {
+    /// 適用した（ドライランの場合は適用される）マイグレーションのバージョン
+    pub applied: Vec<u32>,
+    /// 実行後のスキーマのバージョン（ドライランの場合は実行前のバージョン）
+    pub current_version: u32,
+    /// ドライランだったかどうか
+    pub dry_run: bool,
+}
+
+/// マイグレーションのSQLのチェックサム（SHA-256の16進数）を計算します
+fn checksum(sql: &str) -> String {
+    hex::encode(Sha256::digest(sql.as_bytes()))
+}
+
+/// 適用済みのバージョンとチェックサムを読み込みます
+///
+/// 記録用のテーブルがまだない場合は、適用済みのバージョンがないものとして扱います
+/// （ドライランなどの読み取りだけの操作でテーブルを作成しないため）。
+fn applied_versions(connection: &Connection) -> Result<HashMap<u32, String>, MigrationError> {
+    let exists: bool = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master \
+         WHERE type = 'table' AND name = 'schema_migrations')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(HashMap::new());
+    }
+    let mut statement = connection.prepare("SELECT version, checksum FROM schema_migrations")?;
+    let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
+/// スキーママイグレーションの実行器
+///
+/// 各マイグレーションは個別のトランザクションで適用されるため、
+/// 途中のマイグレーションが失敗した場合もそれ以前のマイグレーションは適用されたままです。
+///
+/// # Example
+/// ```
+/// let create_notes = Migration {
+///     version: 1,
+///     name: "create_notes".to_string(),
+///     sql: "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);".to_string(),
+/// };
+/// let runner = MigrationRunner::new(db, vec![create_notes])?;
+/// let plan = runner.run(true)?;   // ドライラン
+/// let report = runner.run(false)?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct MigrationRunner {
+    database: Arc<Database>,
+    migrations: Vec<Migration>,
+}
+
+impl MigrationRunner {
+    /// 適用済みのマイグレーションを検証し、未適用のマイグレーションを返します
+    fn pending_for(
+        &self,
+        applied: &HashMap<u32, String>,
+    ) -> Result<Vec<&Migration>, MigrationError> {
+        let mut unknown: Vec<u32> = applied
+            .keys()
+            .filter(|v| !self.migrations.iter().any(|m| m.version == **v))
+            .copied()
+            .collect();
+        unknown.sort_unstable();
+        if let Some(&version) = unknown.first() {
+            return Err(MigrationError::UnknownVersion(version));
+        }
+        let mut pending = Vec::new();
+        for migration in &self.migrations {
+            match applied.get(&migration.version) {
+                Some(expected) => {
+                    let actual = checksum(&migration.sql);
+                    if *expected != actual {
+                        return Err(MigrationError::ChecksumMismatch {
+                            version: migration.version,
+                            expected: expected.clone(),
+                            actual,
+                        });
+                    }
+                }
+                None => pending.push(migration),
+            }
+        }
+        Ok(pending)
+    }
+}
+
+#[uniffi::export]
+impl MigrationRunner {
+    /// マイグレーションの実行器を作成します
+    ///
+    /// # Arguments
+    /// * `database` - 対象のデータベース
+    /// * `migrations` - マイグレーションの一覧（バージョンの昇順）
+    ///
+    /// # Errors
+    /// * `MigrationError::InvalidMigrations` - バージョンが0、昇順でない、または重複している場合
+    #[uniffi::constructor]
+    pub fn new(
+        database: Arc<Database>,
+        migrations: Vec<Migration>,
+    ) -> Result<Arc<Self>, MigrationError> {
+        let mut previous = 0;
+        for migration in &migrations {
+            if migration.version <= previous {
+                return Err(MigrationError::InvalidMigrations(format!(
+                    "version {} must be greater than {}",
+                    migration.version, previous
+                )));
+            }
+            previous = migration.version;
+        }
+        Ok(Arc::new(Self {
+            database,
+            migrations,
+        }))
+    }
+
+    /// 適用済みの最新のバージョンを返します（未適用の場合は0）
+    ///
+    /// # Errors
+    /// * `MigrationError::DatabaseError` - データベースの操作に失敗した場合
+    pub fn current_version(&self) -> Result<u32, MigrationError> {
+        let connection = self.database.lock_connection()?;
+        Ok(applied_versions(&connection)?.into_keys().max().unwrap_or(0))
+    }
+
+    /// 未適用のマイグレーションを返します
+    ///
+    /// # Errors
+    /// * `MigrationError::ChecksumMismatch` - 適用済みのマイグレーションのSQLが変更されている場合
+    /// * `MigrationError::UnknownVersion` - 一覧にないバージョンが適用されている場合
+    /// * `MigrationError::DatabaseError` - データベースの操作に失敗した場合
+    pub fn pending(&self) -> Result<Vec<Migration>, MigrationError> {
+        let connection = self.database.lock_connection()?;
+        let applied = applied_versions(&connection)?;
+        Ok(self.pending_for(&applied)?.into_iter().cloned().collect())
+    }
+
+    /// 未適用のマイグレーションをバージョン順に適用します
+    ///
+    /// ドライランの場合は、未適用のマイグレーションを1つのトランザクション内で実行した後に
+    /// 取り消すため、SQLの誤りを検出しつつデータベースは変更されません。
+    ///
+    /// # Arguments
+    /// * `dry_run` - `true`の場合は変更を確定しない
+    ///
+    /// # Errors
+    /// * `MigrationError::ChecksumMismatch` - 適用済みのマイグレーションのSQLが変更されている場合
+    /// * `MigrationError::UnknownVersion` - 一覧にないバージョンが適用されている場合
+    /// * `MigrationError::MigrationFailed` - マイグレーションのSQLの実行に失敗した場合
+    /// * `MigrationError::DatabaseError` - トランザクションが開始されている、
+    ///   またはデータベースの操作に失敗した場合
+    #[uniffi::method(default(dry_run = false))]
+    pub fn run(&self, dry_run: bool) -> Result<MigrationReport, MigrationError> {
+        let mut connection = self.database.lock_connection()?;
+        if !connection.is_autocommit() {
+            return Err(MigrationError::DatabaseError(
+                "a transaction is already active".to_string(),
+            ));
+        }
+        let applied = applied_versions(&connection)?;
+        let pending = self.pending_for(&applied)?;
+        let before = applied.keys().copied().max().unwrap_or(0);
+        let versions: Vec<u32> = pending.iter().map(|m| m.version).collect();
+
+        if dry_run {
+            let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            for migration in &pending {
+                transaction.execute_batch(&migration.sql).map_err(|e| {
+                    MigrationError::MigrationFailed {
+                        version: migration.version,
+                        message: e.to_string(),
+                    }
+                })?;
+            }
+            transaction.rollback()?;
+            return Ok(MigrationReport {
+                applied: versions,
+                current_version: before,
+                dry_run: true,
+            });
+        }
+
+        if !pending.is_empty() {
+            connection.execute_batch(CREATE_VERSION_TABLE)?;
+        }
+        for migration in &pending {
+            let transaction = connection.transaction_with_behavior(TransactionBehavior::Immediate)?;
+            transaction.execute_batch(&migration.sql).map_err(|e| {
+                MigrationError::MigrationFailed {
+                    version: migration.version,
+                    message: e.to_string(),
+                }
+            })?;
+            let applied_at = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs() as i64)
+                .unwrap_or(0);
+            transaction.execute(
+                "INSERT INTO schema_migrations (version, name, checksum, applied_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![migration.version, migration.name, checksum(&migration.sql), applied_at],
+            )?;
+            transaction.commit()?;
+        }
+        Ok(MigrationReport {
+            current_version: versions.last().copied().unwrap_or(before),
+            applied: versions,
+            dry_run: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::SqlValue;
+
+    fn migration(version: u32, sql: &str) -> Migration {
+        Migration {
+            version,
+            name: format!("m{}", version),
+            sql: sql.to_string(),
+        }
+    }
+
+    fn migrations() -> Vec<Migration> {
+        vec![
+            migration(1, "CREATE TABLE notes (id INTEGER PRIMARY KEY, body TEXT);"),
+            migration(2, "ALTER TABLE notes ADD COLUMN tag TEXT;"),
+        ]
+    }
+
+    fn table_names(db: &Database) -> Vec<SqlValue> {
+        db.query(
+            "SELECT name FROM sqlite_master WHERE type = 'table' ORDER BY name".to_string(),
+            Vec::new(),
+        )
+        .unwrap()
+        .into_iter()
+        .map(|row| row.values["name"].clone())
+        .collect()
+    }
+
+    #[test]
+    fn test_migration_run() {
+        let db = Database::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(db.clone(), migrations()).unwrap();
+        assert_eq!(runner.current_version().unwrap(), 0);
+        assert_eq!(runner.pending().unwrap().len(), 2);
+
+        let report = runner.run(false).unwrap();
+        assert_eq!(report.applied, vec![1, 2]);
+        assert_eq!(report.current_version, 2);
+        db.execute("INSERT INTO notes (body, tag) VALUES ('a', 'b')".to_string(), Vec::new())
+            .unwrap();
+
+        let report = runner.run(false).unwrap();
+        assert!(report.applied.is_empty());
+        assert_eq!(report.current_version, 2);
+
+        let mut extended = migrations();
+        extended.push(migration(5, "CREATE TABLE tags (name TEXT);"));
+        let report = MigrationRunner::new(db, extended).unwrap().run(false).unwrap();
+        assert_eq!(report.applied, vec![5]);
+        assert_eq!(report.current_version, 5);
+    }
+
+    #[test]
+    fn test_migration_dry_run() {
+        let db = Database::open_in_memory().unwrap();
+        let runner = MigrationRunner::new(db.clone(), migrations()).unwrap();
+        let report = runner.run(true).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.applied, vec![1, 2]);
+        assert_eq!(report.current_version, 0);
+        assert_eq!(runner.current_version().unwrap(), 0);
+        assert_eq!(runner.pending().unwrap().len(), 2);
+        assert!(table_names(&db).is_empty());
+    }
+
+    #[test]
+    fn test_migration_failure_keeps_earlier_versions() {
+        let db = Database::open_in_memory().unwrap();
+        let mut list = migrations();
+        list.push(migration(3, "CREATE TABLE ok (x); CREATE TABLE broken (;"));
+        let runner = MigrationRunner::new(db.clone(), list).unwrap();
+        match runner.run(false) {
+            Err(MigrationError::MigrationFailed { version: 3, .. }) => (),
+            other => panic!("Expected MigrationFailed error, got {:?}", other),
+        }
+        assert_eq!(runner.current_version().unwrap(), 2);
+        assert!(!table_names(&db).contains(&SqlValue::Text { value: "ok".to_string() }));
+    }
+
+    #[test]
+    fn test_migration_checksum_and_version_errors() {
+        let db = Database::open_in_memory().unwrap();
+        MigrationRunner::new(db.clone(), migrations()).unwrap().run(false).unwrap();
+
+        let mut changed = migrations();
+        changed[0].sql = "CREATE TABLE notes (id INTEGER PRIMARY KEY);".to_string();
+        match MigrationRunner::new(db.clone(), changed).unwrap().run(false) {
+            Err(MigrationError::ChecksumMismatch { version: 1, .. }) => (),
+            other => panic!("Expected ChecksumMismatch error, got {:?}", other),
+        }
+        match MigrationRunner::new(db.clone(), migrations()[..1].to_vec()).unwrap().pending() {
+            Err(MigrationError::UnknownVersion(2)) => (),
+            other => panic!("Expected UnknownVersion error, got {:?}", other),
+        }
+        match MigrationRunner::new(db, vec![migration(2, ""), migration(2, "")]) {
+            Err(MigrationError::InvalidMigrations(_)) => (),
+            other => panic!("Expected InvalidMigrations error, got {:?}", other.map(|_| ())),
+        }
+    }
+}
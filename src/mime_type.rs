@@ -0,0 +1,301 @@
+//! MIMEタイプ判別モジュール
+//!
+//! このモジュールは、ファイルの先頭のバイト列のマジックナンバーからMIMEタイプを判別し、
+//! 判別できない場合はファイル名の拡張子で補う`detect_mime_type`をエクスポートします。
+//! アップロードの検証で、ユーザーが指定したContent-Typeや拡張子を信用せずに
+//! 実際の形式を確認するために使用します。
+
+use std::path::Path;
+
+/// 判別できない場合のMIMEタイプ
+const OCTET_STREAM: &str = "application/octet-stream";
+
+/// ZIP形式をコンテナーとして使う形式の拡張子（マジックナンバーではZIPと区別できない場合がある）
+const ZIP_CONTAINERS: [&str; 10] =
+    ["docx", "xlsx", "pptx", "odt", "ods", "odp", "epub", "jar", "apk", "ipa"];
+
+/// 文書として扱うMIMEタイプの接頭辞
+const DOCUMENT_PREFIXES: [&str; 8] = [
+    "application/pdf",
+    "application/msword",
+    "application/rtf",
+    "application/epub+zip",
+    "application/vnd.ms-",
+    "application/vnd.openxmlformats-officedocument.",
+    "application/vnd.oasis.opendocument.",
+    "application/x-mobipocket-ebook",
+];
+
+/// アーカイブとして扱うMIMEタイプ
+const ARCHIVE_TYPES: [&str; 10] = [
+    "application/zip",
+    "application/gzip",
+    "application/x-tar",
+    "application/x-7z-compressed",
+    "application/vnd.rar",
+    "application/x-rar-compressed",
+    "application/x-bzip2",
+    "application/x-xz",
+    "application/zstd",
+    "application/java-archive",
+];
+
+/// MIMEタイプの判別方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum MimeSource {
+    /// 先頭のバイト列のマジックナンバー
+    Magic,
+    /// ファイル名の拡張子（マジックナンバーで判別できない、またはZIPのコンテナー形式の場合）
+    Extension,
+    /// 内容がUTF-8のテキストであること
+    Text,
+    /// 判別できなかった場合（`application/octet-stream`）
+    Unknown,
+}
+
+/// MIMEタイプの大まかな種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum MimeCategory {
+    /// 画像
+    Image,
+    /// 動画
+    Video,
+    /// 音声
+    Audio,
+    /// 文書（PDF・Office・電子書籍など）
+    Document,
+    /// アーカイブ・圧縮ファイル
+    Archive,
+    /// フォント
+    Font,
+    /// テキスト
+    Text,
+    /// その他
+    Other,
+}
+
+/// MIMEタイプの判別結果
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct MimeInfo {
+    /// MIMEタイプ（`image/png`など）
+    pub mime_type: String,
+    /// MIMEタイプに対応する拡張子（`.`を含みません。判別できない場合は`None`）
+    pub extension: Option<String>,
+    /// 大まかな種類
+    pub category: MimeCategory,
+    /// 判別方法
+    pub source: MimeSource,
+    /// ファイル名の拡張子が判別したMIMEタイプと一致する場合は`true`
+    /// （ファイル名や拡張子がない場合も`true`）
+    pub extension_matches: bool,
+}
+
+/// MIMEタイプの大まかな種類を返します
+fn category_of(mime_type: &str) -> MimeCategory {
+    let (top_level, _) = mime_type.split_once('/').unwrap_or((mime_type, ""));
+    match top_level {
+        "image" => MimeCategory::Image,
+        "video" => MimeCategory::Video,
+        "audio" => MimeCategory::Audio,
+        "font" => MimeCategory::Font,
+        "text" => MimeCategory::Text,
+        _ if DOCUMENT_PREFIXES.iter().any(|prefix| mime_type.starts_with(prefix)) => {
+            MimeCategory::Document
+        }
+        _ if ARCHIVE_TYPES.contains(&mime_type) => MimeCategory::Archive,
+        _ if mime_type.starts_with("application/font-") => MimeCategory::Font,
+        _ => MimeCategory::Other,
+    }
+}
+
+/// 内容がテキストかどうかを返します
+///
+/// UTF-8として正しく（末尾で途中まで切れた文字は許容）、空白以外の制御文字を含まない場合に
+/// テキストとみなします。
+fn is_text(bytes: &[u8]) -> bool {
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(error) if error.error_len().is_none() => {
+            // 先頭の一部だけが渡された場合は末尾の文字が途中で切れている
+            std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default()
+        }
+        Err(_) => return false,
+    };
+    !text.is_empty()
+        && !text.chars().any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r' | '\x0c'))
+}
+
+/// ファイルの先頭のバイト列からMIMEタイプを判別します
+///
+/// マジックナンバーで判別できた形式を優先し、ファイル名の拡張子は判別できない場合にだけ
+/// 使用します。ただしZIP形式と判別した場合は、拡張子がDOCX・XLSX・EPUB・JARなどの
+/// ZIPをコンテナーとして使う形式であればその形式とみなします。
+/// 拡張子とマジックナンバーが食い違う場合は`extension_matches`が`false`になるため、
+/// アップロードを拒否するかどうかの判定に使用できます。
+///
+/// # Arguments
+/// * `bytes_prefix` - ファイルの先頭のバイト列（Officeの形式を判別するには数KB以上が必要）
+/// * `filename_hint` - ファイル名（拡張子による補完と一致の確認に使用します）
+///
+/// # Example
+/// ```
+/// let info = detect_mime_type(first_bytes, Some("photo.jpg".to_string()));
+/// if info.category != MimeCategory::Image || !info.extension_matches {
+///     // アップロードを拒否
+/// }
+/// ```
+#[uniffi::export(default(filename_hint = None))]
+pub fn detect_mime_type(bytes_prefix: Vec<u8>, filename_hint: Option<String>) -> MimeInfo {
+    let hint_extension = filename_hint
+        .as_deref()
+        .and_then(|name| Path::new(name.trim()).extension())
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase)
+        .filter(|extension| !extension.is_empty());
+    let hint_types: Vec<String> = hint_extension
+        .as_deref()
+        .map(|extension| {
+            mime_guess::from_ext(extension)
+                .iter()
+                .map(|mime| mime.essence_str().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let from_extension = |extension: &str, mime_type: &str| MimeInfo {
+        mime_type: mime_type.to_string(),
+        extension: Some(extension.to_string()),
+        category: category_of(mime_type),
+        source: MimeSource::Extension,
+        extension_matches: true,
+    };
+
+    if let Some(kind) = infer::get(&bytes_prefix) {
+        let mime_type = kind.mime_type();
+        if let (Some(extension), Some(hint_type)) = (&hint_extension, hint_types.first()) {
+            if mime_type == "application/zip" && ZIP_CONTAINERS.contains(&extension.as_str()) {
+                return from_extension(extension, hint_type);
+            }
+        }
+        let extension_matches = hint_extension.as_deref().is_none_or(|extension| {
+            extension == kind.extension() || hint_types.iter().any(|hint| hint == mime_type)
+        });
+        return MimeInfo {
+            mime_type: mime_type.to_string(),
+            extension: Some(kind.extension().to_string()),
+            category: category_of(mime_type),
+            source: MimeSource::Magic,
+            extension_matches,
+        };
+    }
+    if let (Some(extension), Some(hint_type)) = (&hint_extension, hint_types.first()) {
+        return from_extension(extension, hint_type);
+    }
+    if is_text(&bytes_prefix) {
+        return MimeInfo {
+            mime_type: "text/plain".to_string(),
+            extension: Some("txt".to_string()),
+            category: MimeCategory::Text,
+            source: MimeSource::Text,
+            extension_matches: hint_extension.is_none(),
+        };
+    }
+    MimeInfo {
+        mime_type: OCTET_STREAM.to_string(),
+        extension: None,
+        category: MimeCategory::Other,
+        source: MimeSource::Unknown,
+        extension_matches: hint_extension.is_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+    const PDF: &[u8] = b"%PDF-1.7\n%\xe2\xe3\xcf\xd3\n";
+    const ZIP: &[u8] = b"PK\x03\x04\x14\0\0\0\x08\0";
+
+    fn detect(bytes: &[u8], filename: Option<&str>) -> MimeInfo {
+        detect_mime_type(bytes.to_vec(), filename.map(str::to_string))
+    }
+
+    #[test]
+    fn test_detect_mime_type_magic() {
+        assert_eq!(
+            detect(PNG, Some("image.PNG")),
+            MimeInfo {
+                mime_type: "image/png".to_string(),
+                extension: Some("png".to_string()),
+                category: MimeCategory::Image,
+                source: MimeSource::Magic,
+                extension_matches: true,
+            }
+        );
+        let jpeg = detect(b"\xff\xd8\xff\xe0\0\x10JFIF\0", Some("photo.jpeg"));
+        assert_eq!((jpeg.mime_type.as_str(), jpeg.extension_matches), ("image/jpeg", true));
+        let pdf = detect(PDF, None);
+        assert_eq!(
+            (pdf.mime_type.as_str(), pdf.category),
+            ("application/pdf", MimeCategory::Document)
+        );
+        let gzip = detect(b"\x1f\x8b\x08\0\0\0\0\0", None);
+        assert_eq!(
+            (gzip.mime_type.as_str(), gzip.category),
+            ("application/gzip", MimeCategory::Archive)
+        );
+    }
+
+    #[test]
+    fn test_detect_mime_type_disguised_file() {
+        // 拡張子を偽装したファイルはマジックナンバーの形式になる
+        let disguised = detect(PDF, Some("invoice.jpg"));
+        assert_eq!(disguised.mime_type, "application/pdf");
+        assert_eq!(disguised.source, MimeSource::Magic);
+        assert!(!disguised.extension_matches);
+
+        let executable = detect(b"MZ\x90\0\x03\0\0\0\x04\0", Some("cat.png"));
+        assert_eq!(executable.category, MimeCategory::Other);
+        assert!(!executable.extension_matches);
+
+        // ZIPのコンテナー形式は拡張子で補うが、それ以外の拡張子は補わない
+        let docx = detect(ZIP, Some("report.docx"));
+        assert_eq!(
+            docx.mime_type,
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+        );
+        assert_eq!((docx.source, docx.category), (MimeSource::Extension, MimeCategory::Document));
+        let zip_as_pdf = detect(ZIP, Some("report.pdf"));
+        assert_eq!(zip_as_pdf.mime_type, "application/zip");
+        assert!(!zip_as_pdf.extension_matches);
+    }
+
+    #[test]
+    fn test_detect_mime_type_fallbacks() {
+        let csv = detect(b"name,age\nAlice,30\n", Some("people.csv"));
+        assert_eq!(
+            (csv.mime_type.as_str(), csv.source, csv.category),
+            ("text/csv", MimeSource::Extension, MimeCategory::Text)
+        );
+        let text = detect("こんにちは".as_bytes(), None);
+        assert_eq!((text.mime_type.as_str(), text.source), ("text/plain", MimeSource::Text));
+        // 先頭だけを渡した場合に末尾で切れた文字は無視する
+        let truncated = detect(&"日本語".as_bytes()[..7], None);
+        assert_eq!(truncated.source, MimeSource::Text);
+
+        let unknown = detect(b"\0\x01\x02\x03", None);
+        assert_eq!(
+            unknown,
+            MimeInfo {
+                mime_type: "application/octet-stream".to_string(),
+                extension: None,
+                category: MimeCategory::Other,
+                source: MimeSource::Unknown,
+                extension_matches: true,
+            }
+        );
+        assert_eq!(detect(b"", None).source, MimeSource::Unknown);
+        assert!(!detect(b"\0\x01\x02\x03", Some("data.unknownext")).extension_matches);
+    }
+}
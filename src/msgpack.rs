@@ -0,0 +1,211 @@
+//! MessagePack変換モジュール
+//!
+//! このモジュールは、MessagePackとJSONを相互に変換する関数をエクスポートします。
+//! リアルタイム通信のバックエンドが送受信するMessagePackを、
+//! プラットフォームごとのライブラリを使わずに共通コアで扱うために使用します。
+//! APIは`cbor`モジュールと対称になっています。
+//!
+//! MessagePackにはJSONにない型があるため、JSONへの変換時は次のように対応付けます。
+//!
+//! | MessagePack | JSON |
+//! |-------------|------|
+//! | バイナリ | Base64URL（パディングなし）の文字列 |
+//! | 拡張型 | データ部分のBase64URL（パディングなし）の文字列 |
+//! | 文字列以外のマップのキー | キーをJSONで表した文字列（例: `1`、`true`） |
+//! | NaN・無限大 | `null` |
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rmpv::Value as MsgpackValue;
+use serde_json::{Map, Number, Value as JsonValue};
+use thiserror::Error;
+
+/// MessagePack変換で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum MsgpackError {
+    /// MessagePackとして不正なバイト列の場合
+    #[error("Invalid MessagePack: {0}")]
+    InvalidMsgpack(String),
+    /// JSONとして不正な文字列の場合
+    #[error("Invalid JSON: {0}")]
+    InvalidJson(String),
+    /// JSONで表現できない値を含む場合（UTF-8として不正な文字列など）
+    #[error("Unsupported value: {0}")]
+    UnsupportedValue(String),
+}
+
+/// マップのキーをJSONオブジェクトのキーに変換します
+fn key_to_string(key: MsgpackValue) -> Result<String, MsgpackError> {
+    match key {
+        MsgpackValue::String(text) if text.is_str() => Ok(text.into_str().unwrap_or_default()),
+        other => Ok(msgpack_value_to_json(other)?.to_string()),
+    }
+}
+
+/// MessagePackの値をJSONの値に変換します
+fn msgpack_value_to_json(value: MsgpackValue) -> Result<JsonValue, MsgpackError> {
+    Ok(match value {
+        MsgpackValue::Nil => JsonValue::Null,
+        MsgpackValue::Boolean(b) => JsonValue::Bool(b),
+        MsgpackValue::Integer(integer) => {
+            if let Some(n) = integer.as_u64() {
+                JsonValue::from(n)
+            } else if let Some(n) = integer.as_i64() {
+                JsonValue::from(n)
+            } else {
+                return Err(MsgpackError::UnsupportedValue(integer.to_string()));
+            }
+        }
+        MsgpackValue::F32(f) => {
+            Number::from_f64(f64::from(f)).map_or(JsonValue::Null, JsonValue::Number)
+        }
+        MsgpackValue::F64(f) => Number::from_f64(f).map_or(JsonValue::Null, JsonValue::Number),
+        MsgpackValue::String(text) => JsonValue::String(text.into_str().ok_or_else(|| {
+            MsgpackError::UnsupportedValue("string is not valid UTF-8".to_string())
+        })?),
+        MsgpackValue::Binary(bytes) => JsonValue::String(URL_SAFE_NO_PAD.encode(bytes)),
+        MsgpackValue::Array(items) => JsonValue::Array(
+            items
+                .into_iter()
+                .map(msgpack_value_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        MsgpackValue::Map(entries) => {
+            let mut map = Map::with_capacity(entries.len());
+            for (key, value) in entries {
+                map.insert(key_to_string(key)?, msgpack_value_to_json(value)?);
+            }
+            JsonValue::Object(map)
+        }
+        MsgpackValue::Ext(_, data) => JsonValue::String(URL_SAFE_NO_PAD.encode(data)),
+    })
+}
+
+/// JSONの値をMessagePackの値に変換します
+fn json_value_to_msgpack(value: JsonValue) -> MsgpackValue {
+    match value {
+        JsonValue::Null => MsgpackValue::Nil,
+        JsonValue::Bool(b) => MsgpackValue::Boolean(b),
+        JsonValue::Number(n) => {
+            if let Some(u) = n.as_u64() {
+                MsgpackValue::from(u)
+            } else if let Some(i) = n.as_i64() {
+                MsgpackValue::from(i)
+            } else {
+                MsgpackValue::F64(n.as_f64().unwrap_or(f64::NAN))
+            }
+        }
+        JsonValue::String(text) => MsgpackValue::from(text),
+        JsonValue::Array(items) => {
+            MsgpackValue::Array(items.into_iter().map(json_value_to_msgpack).collect())
+        }
+        JsonValue::Object(map) => MsgpackValue::Map(
+            map.into_iter()
+                .map(|(key, value)| (MsgpackValue::from(key), json_value_to_msgpack(value)))
+                .collect(),
+        ),
+    }
+}
+
+/// MessagePackのバイト列をJSON文字列に変換します
+///
+/// 変換規則はモジュールのドキュメントを参照してください。
+/// 入力は1つの値である必要があり、後続のデータは許可されません。
+///
+/// # Arguments
+/// * `bytes` - MessagePackでエンコードされたデータ
+///
+/// # Errors
+/// * `MsgpackError::InvalidMsgpack` - MessagePackとして不正な場合、または後続のデータがある場合
+/// * `MsgpackError::UnsupportedValue` - UTF-8として不正な文字列を含む場合
+///
+/// # Example
+/// ```
+/// // {"a": 1}
+/// let json = msgpack_to_json(vec![0x81, 0xa1, 0x61, 0x01])?;
+/// assert_eq!(json, r#"{"a":1}"#);
+/// ```
+#[uniffi::export]
+pub fn msgpack_to_json(bytes: Vec<u8>) -> Result<String, MsgpackError> {
+    let mut reader = bytes.as_slice();
+    let value = rmpv::decode::read_value(&mut reader)
+        .map_err(|e| MsgpackError::InvalidMsgpack(e.to_string()))?;
+    if !reader.is_empty() {
+        return Err(MsgpackError::InvalidMsgpack(format!(
+            "{} trailing bytes after value",
+            reader.len()
+        )));
+    }
+    Ok(msgpack_value_to_json(value)?.to_string())
+}
+
+/// JSON文字列をMessagePackのバイト列に変換します
+///
+/// 整数は最短の整数表現に、小数は64ビット浮動小数点数に変換されます。
+/// JSONの文字列はすべてMessagePackの文字列になります（バイナリには戻りません）。
+///
+/// # Arguments
+/// * `text` - 変換するJSON文字列
+///
+/// # Errors
+/// * `MsgpackError::InvalidJson` - JSONとして不正な場合
+#[uniffi::export]
+pub fn json_to_msgpack(text: String) -> Result<Vec<u8>, MsgpackError> {
+    let value: JsonValue =
+        serde_json::from_str(&text).map_err(|e| MsgpackError::InvalidJson(e.to_string()))?;
+    let mut output = Vec::new();
+    rmpv::encode::write_value(&mut output, &json_value_to_msgpack(value))
+        .map_err(|e| MsgpackError::UnsupportedValue(e.to_string()))?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_to_msgpack_encoding() {
+        assert_eq!(json_to_msgpack("1".to_string()).unwrap(), vec![0x01]);
+        assert_eq!(json_to_msgpack("-1".to_string()).unwrap(), vec![0xff]);
+        assert_eq!(json_to_msgpack("300".to_string()).unwrap(), vec![0xcd, 0x01, 0x2c]);
+        assert_eq!(json_to_msgpack("null".to_string()).unwrap(), vec![0xc0]);
+        assert_eq!(
+            json_to_msgpack(r#"{"a":[true]}"#.to_string()).unwrap(),
+            vec![0x81, 0xa1, 0x61, 0x91, 0xc3]
+        );
+    }
+
+    #[test]
+    fn test_msgpack_json_roundtrip() {
+        let json = r#"{"body":"こんにちは","score":-0.5,"seq":12345678901,"type":"chat"}"#;
+        let packed = json_to_msgpack(json.to_string()).unwrap();
+        assert_eq!(msgpack_to_json(packed).unwrap(), json);
+    }
+
+    #[test]
+    fn test_msgpack_to_json_binary_and_int_keys() {
+        // {1: bin8(0x01 0x02), "e": ext(5, 0xff)}
+        let packed = vec![0x82, 0x01, 0xc4, 0x02, 0x01, 0x02, 0xa1, 0x65, 0xd4, 0x05, 0xff];
+        assert_eq!(msgpack_to_json(packed).unwrap(), r#"{"1":"AQI","e":"_w"}"#);
+    }
+
+    #[test]
+    fn test_msgpack_errors() {
+        match msgpack_to_json(vec![0x92, 0x01]) {
+            Err(MsgpackError::InvalidMsgpack(_)) => (),
+            other => panic!("Expected InvalidMsgpack error, got {:?}", other),
+        }
+        match msgpack_to_json(vec![0x01, 0x02]) {
+            Err(MsgpackError::InvalidMsgpack(_)) => (),
+            other => panic!("Expected InvalidMsgpack error, got {:?}", other),
+        }
+        match msgpack_to_json(vec![0xa2, 0xff, 0xfe]) {
+            Err(MsgpackError::UnsupportedValue(_)) => (),
+            other => panic!("Expected UnsupportedValue error, got {:?}", other),
+        }
+        match json_to_msgpack("[1,".to_string()) {
+            Err(MsgpackError::InvalidJson(_)) => (),
+            other => panic!("Expected InvalidJson error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,464 @@
+//! multipart/form-data生成モジュール
+//!
+//! このモジュールは、ファイルアップロード用の`multipart/form-data`（RFC 7578）の
+//! ボディを組み立てる`MultipartBuilder`をエクスポートします。
+//! 境界文字列の生成やフィールド名のエスケープをプラットフォーム間で統一するために使用します。
+//!
+//! 小さなボディは`finish`でメモリ上に生成し、大きなファイルを含む場合は
+//! `finish_to_file`で一時ファイルに書き出して、URLSessionの
+//! `uploadTask(with:fromFile:)`などからストリーミング送信できます。
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+
+/// 境界文字列の最大長（RFC 2046）
+const MAX_BOUNDARY_LEN: usize = 70;
+
+/// 境界文字列に使用できる記号（RFC 2046の`bcharsnospace`から英数字を除いたもの）
+const BOUNDARY_SYMBOLS: &str = "'()+_,-./:=?";
+
+/// multipartボディの生成で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum MultipartError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// `finish`・`finish_to_file`の後にパートを追加・生成しようとした場合
+    #[error("Multipart body has already been finished")]
+    AlreadyFinished,
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access file: {0}")]
+    IoError(String),
+    /// 境界文字列・フィールド名・Content-Typeが不正な場合
+    #[error("Invalid multipart field: {0}")]
+    InvalidField(String),
+}
+
+impl From<io::Error> for MultipartError {
+    fn from(error: io::Error) -> Self {
+        MultipartError::IoError(error.to_string())
+    }
+}
+
+/// ファイルパートの内容
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum MultipartFileSource {
+    /// メモリ上のデータ
+    Bytes { data: Vec<u8> },
+    /// ファイルのパス（ボディの生成時に読み込まれます）
+    Path { path: String },
+}
+
+/// メモリ上に生成したmultipartボディ
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct MultipartBody {
+    /// 境界文字列
+    pub boundary: String,
+    /// リクエストの`Content-Type`ヘッダーの値
+    pub content_type: String,
+    /// ボディのバイト列
+    pub body: Vec<u8>,
+}
+
+/// ファイルに書き出したmultipartボディの情報
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct MultipartFile {
+    /// 境界文字列
+    pub boundary: String,
+    /// リクエストの`Content-Type`ヘッダーの値
+    pub content_type: String,
+    /// 書き出したボディのバイト数（`Content-Length`ヘッダーの値）
+    pub content_length: u64,
+}
+
+/// 追加されたパート
+enum Part {
+    Text {
+        name: String,
+        value: String,
+    },
+    File {
+        name: String,
+        filename: String,
+        content_type: String,
+        source: MultipartFileSource,
+    },
+}
+
+/// `Content-Disposition`の引用符付き文字列に使えるよう、`"`と改行をエスケープします
+///
+/// HTML Living Standardのフォーム送信と同じく、パーセントエンコードで置き換えます。
+fn escape_quoted(value: &str) -> String {
+    value
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+        .replace('"', "%22")
+}
+
+/// 境界文字列がRFC 2046の規則に従っているか確認します
+fn check_boundary(boundary: &str) -> Result<(), MultipartError> {
+    let valid_chars = boundary
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || BOUNDARY_SYMBOLS.contains(c));
+    if boundary.is_empty() || boundary.len() > MAX_BOUNDARY_LEN || !valid_chars {
+        return Err(MultipartError::InvalidField(format!("invalid boundary: {}", boundary)));
+    }
+    Ok(())
+}
+
+/// ヘッダーの値に改行が含まれていないか確認します
+fn check_header_value(kind: &str, value: &str) -> Result<(), MultipartError> {
+    if value.is_empty() || value.contains(['\r', '\n']) {
+        return Err(MultipartError::InvalidField(format!(
+            "{} must be non-empty and must not contain line breaks",
+            kind
+        )));
+    }
+    Ok(())
+}
+
+/// multipart/form-dataのボディを組み立てるビルダー
+///
+/// パートは追加した順に出力されます。`finish`または`finish_to_file`を呼ぶと
+/// ビルダーは使用済みになり、以降の操作は`MultipartError::AlreadyFinished`になります。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let builder = MultipartBuilder::new();
+/// builder.add_text("caption".to_string(), "夕焼け".to_string())?;
+/// builder.add_file(
+///     "photo".to_string(),
+///     "sunset.jpg".to_string(),
+///     "image/jpeg".to_string(),
+///     MultipartFileSource::Path { path: photo_path },
+/// )?;
+/// let body = builder.finish()?;
+/// // request.setValue(body.contentType, forHTTPHeaderField: "Content-Type")
+/// ```
+#[derive(uniffi::Object)]
+pub struct MultipartBuilder {
+    boundary: String,
+    parts: Mutex<Option<Vec<Part>>>,
+}
+
+impl MultipartBuilder {
+    /// `Content-Type`ヘッダーの値を返します
+    ///
+    /// 境界文字列にtspecials（`(),/:=?`）が含まれる場合は、パラメータの値を引用符で囲みます。
+    fn content_type(&self) -> String {
+        if self.boundary.contains(['(', ')', ',', '/', ':', '=', '?']) {
+            format!("multipart/form-data; boundary=\"{}\"", self.boundary)
+        } else {
+            format!("multipart/form-data; boundary={}", self.boundary)
+        }
+    }
+
+    /// 未完了のパートを取り出し、ビルダーを使用済みにします
+    fn take_parts(&self) -> Result<Vec<Part>, MultipartError> {
+        let mut parts = self.parts.lock()
+            .map_err(|_| MultipartError::MutexPoisoned)?;
+        parts.take().ok_or(MultipartError::AlreadyFinished)
+    }
+
+    /// パートを追加します
+    fn push_part(&self, part: Part) -> Result<(), MultipartError> {
+        let mut parts = self.parts.lock()
+            .map_err(|_| MultipartError::MutexPoisoned)?;
+        parts.as_mut().ok_or(MultipartError::AlreadyFinished)?.push(part);
+        Ok(())
+    }
+
+    /// すべてのパートと終端の境界を書き出します
+    fn write_body<W: Write>(&self, parts: &[Part], writer: &mut W) -> Result<(), MultipartError> {
+        for part in parts {
+            write!(writer, "--{}\r\n", self.boundary)?;
+            match part {
+                Part::Text { name, value } => {
+                    write!(
+                        writer,
+                        "Content-Disposition: form-data; name=\"{}\"\r\n\r\n",
+                        escape_quoted(name)
+                    )?;
+                    writer.write_all(value.as_bytes())?;
+                }
+                Part::File {
+                    name,
+                    filename,
+                    content_type,
+                    source,
+                } => {
+                    write!(
+                        writer,
+                        "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+                         Content-Type: {}\r\n\r\n",
+                        escape_quoted(name),
+                        escape_quoted(filename),
+                        content_type
+                    )?;
+                    match source {
+                        MultipartFileSource::Bytes { data } => writer.write_all(data)?,
+                        MultipartFileSource::Path { path } => {
+                            io::copy(&mut File::open(path)?, writer)?;
+                        }
+                    }
+                }
+            }
+            writer.write_all(b"\r\n")?;
+        }
+        write!(writer, "--{}--\r\n", self.boundary)?;
+        Ok(())
+    }
+}
+
+#[uniffi::export]
+impl MultipartBuilder {
+    /// ランダムな境界文字列を持つビルダーを作成します
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        let mut random = [0u8; 16];
+        OsRng.fill_bytes(&mut random);
+        Arc::new(Self {
+            boundary: format!("----MobileFormBoundary{}", hex::encode(random)),
+            parts: Mutex::new(Some(Vec::new())),
+        })
+    }
+
+    /// 境界文字列を指定してビルダーを作成します
+    ///
+    /// 通常は`new`を使用してください。サーバーとの互換性の確認やテストで
+    /// 出力を固定したい場合に使用します。
+    ///
+    /// # Arguments
+    /// * `boundary` - 境界文字列（1〜70文字の英数字と`'()+_,-./:=?`）
+    ///
+    /// # Errors
+    /// * `MultipartError::InvalidField` - 境界文字列が不正な場合
+    #[uniffi::constructor]
+    pub fn with_boundary(boundary: String) -> Result<Arc<Self>, MultipartError> {
+        check_boundary(&boundary)?;
+        Ok(Arc::new(Self {
+            boundary,
+            parts: Mutex::new(Some(Vec::new())),
+        }))
+    }
+
+    /// 境界文字列を返します
+    pub fn boundary(&self) -> String {
+        self.boundary.clone()
+    }
+
+    /// テキストのフィールドを追加します
+    ///
+    /// # Arguments
+    /// * `name` - フィールド名
+    /// * `value` - 値（UTF-8で出力されます）
+    ///
+    /// # Errors
+    /// * `MultipartError::InvalidField` - フィールド名が空の場合
+    /// * `MultipartError::AlreadyFinished` - 既にボディを生成済みの場合
+    /// * `MultipartError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn add_text(&self, name: String, value: String) -> Result<(), MultipartError> {
+        if name.is_empty() {
+            return Err(MultipartError::InvalidField("name must not be empty".to_string()));
+        }
+        self.push_part(Part::Text { name, value })
+    }
+
+    /// ファイルのフィールドを追加します
+    ///
+    /// パスを指定した場合、ファイルの内容はボディの生成時に読み込まれます。
+    ///
+    /// # Arguments
+    /// * `name` - フィールド名
+    /// * `filename` - サーバーに伝えるファイル名
+    /// * `content_type` - ファイルのMIMEタイプ（例: `image/jpeg`）
+    /// * `source` - ファイルの内容（バイト列またはファイルのパス）
+    ///
+    /// # Errors
+    /// * `MultipartError::InvalidField` - フィールド名が空、またはContent-Typeが不正な場合
+    /// * `MultipartError::IoError` - 指定したパスのファイルが存在しない場合
+    /// * `MultipartError::AlreadyFinished` - 既にボディを生成済みの場合
+    /// * `MultipartError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn add_file(
+        &self,
+        name: String,
+        filename: String,
+        content_type: String,
+        source: MultipartFileSource,
+    ) -> Result<(), MultipartError> {
+        if name.is_empty() {
+            return Err(MultipartError::InvalidField("name must not be empty".to_string()));
+        }
+        check_header_value("content type", &content_type)?;
+        if let MultipartFileSource::Path { path } = &source {
+            if !Path::new(path).is_file() {
+                return Err(MultipartError::IoError(format!("not a file: {}", path)));
+            }
+        }
+        self.push_part(Part::File {
+            name,
+            filename,
+            content_type,
+            source,
+        })
+    }
+
+    /// ボディをメモリ上に生成します
+    ///
+    /// # Errors
+    /// * `MultipartError::IoError` - パスで指定したファイルの読み込みに失敗した場合
+    /// * `MultipartError::AlreadyFinished` - 既にボディを生成済みの場合
+    /// * `MultipartError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn finish(&self) -> Result<MultipartBody, MultipartError> {
+        let parts = self.take_parts()?;
+        let mut body = Vec::new();
+        self.write_body(&parts, &mut body)?;
+        Ok(MultipartBody {
+            boundary: self.boundary.clone(),
+            content_type: self.content_type(),
+            body,
+        })
+    }
+
+    /// ボディをファイルに書き出します
+    ///
+    /// ファイルの内容はメモリに展開せずにコピーされるため、大きなファイルの
+    /// アップロードに適しています。書き出しに失敗した場合、出力ファイルは削除されます。
+    ///
+    /// # Arguments
+    /// * `output_path` - 書き出し先のパス（既存のファイルは上書きされます）
+    ///
+    /// # Errors
+    /// * `MultipartError::IoError` - ファイルの読み書きに失敗した場合
+    /// * `MultipartError::AlreadyFinished` - 既にボディを生成済みの場合
+    /// * `MultipartError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn finish_to_file(&self, output_path: String) -> Result<MultipartFile, MultipartError> {
+        let parts = self.take_parts()?;
+        let result = File::create(&output_path)
+            .map_err(MultipartError::from)
+            .and_then(|file| {
+                let mut writer = BufWriter::new(file);
+                self.write_body(&parts, &mut writer)?;
+                writer.flush()?;
+                Ok(writer.get_ref().metadata()?.len())
+            });
+        match result {
+            Ok(content_length) => Ok(MultipartFile {
+                boundary: self.boundary.clone(),
+                content_type: self.content_type(),
+                content_length,
+            }),
+            Err(e) => {
+                let _ = fs::remove_file(&output_path);
+                Err(e)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("mobile_multipart_{}_{}.bin", name, std::process::id()))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let builder = MultipartBuilder::with_boundary("XyZ".to_string()).unwrap();
+        builder.add_text("caption".to_string(), "夕焼け".to_string()).unwrap();
+        builder
+            .add_file(
+                "photo".to_string(),
+                "a\"b.txt".to_string(),
+                "text/plain".to_string(),
+                MultipartFileSource::Bytes {
+                    data: b"hello".to_vec(),
+                },
+            )
+            .unwrap();
+        let body = builder.finish().unwrap();
+        assert_eq!(body.content_type, "multipart/form-data; boundary=XyZ");
+        let expected = "--XyZ\r\n\
+                        Content-Disposition: form-data; name=\"caption\"\r\n\r\n\
+                        夕焼け\r\n\
+                        --XyZ\r\n\
+                        Content-Disposition: form-data; name=\"photo\"; filename=\"a%22b.txt\"\r\n\
+                        Content-Type: text/plain\r\n\r\n\
+                        hello\r\n\
+                        --XyZ--\r\n";
+        assert_eq!(String::from_utf8(body.body).unwrap(), expected);
+
+        // tspecialsを含む境界文字列は引用符で囲む
+        let builder = MultipartBuilder::with_boundary("a=b/c".to_string()).unwrap();
+        builder.add_text("k".to_string(), "v".to_string()).unwrap();
+        let body = builder.finish().unwrap();
+        assert_eq!(body.content_type, "multipart/form-data; boundary=\"a=b/c\"");
+        assert!(body.body.starts_with(b"--a=b/c\r\n"));
+    }
+
+    #[test]
+    fn test_multipart_finish_to_file() {
+        let input = temp_path("input");
+        let output = temp_path("output");
+        std::fs::write(&input, vec![0xabu8; 10_000]).unwrap();
+
+        let builder = MultipartBuilder::new();
+        assert!(builder.boundary().starts_with("----MobileFormBoundary"));
+        builder
+            .add_file(
+                "file".to_string(),
+                "data.bin".to_string(),
+                "application/octet-stream".to_string(),
+                MultipartFileSource::Path {
+                    path: input.clone(),
+                },
+            )
+            .unwrap();
+        let file = builder.finish_to_file(output.clone()).unwrap();
+        let written = std::fs::read(&output).unwrap();
+        assert_eq!(file.content_length, written.len() as u64);
+        assert!(written.windows(10_000).any(|w| w.iter().all(|&b| b == 0xab)));
+        assert!(written.ends_with(format!("--{}--\r\n", file.boundary).as_bytes()));
+
+        std::fs::remove_file(&input).unwrap();
+        std::fs::remove_file(&output).unwrap();
+    }
+
+    #[test]
+    fn test_multipart_errors() {
+        match MultipartBuilder::with_boundary("bad boundary!".to_string()) {
+            Err(MultipartError::InvalidField(_)) => (),
+            other => panic!("Expected InvalidField error, got {:?}", other.map(|_| ())),
+        }
+        let builder = MultipartBuilder::new();
+        let source = MultipartFileSource::Bytes { data: Vec::new() };
+        match builder.add_file("f".to_string(), "x".to_string(), "a\r\nb".to_string(), source) {
+            Err(MultipartError::InvalidField(_)) => (),
+            other => panic!("Expected InvalidField error, got {:?}", other),
+        }
+        let source = MultipartFileSource::Path {
+            path: "/nonexistent/mobile.bin".to_string(),
+        };
+        match builder.add_file("f".to_string(), "x".to_string(), "a/b".to_string(), source) {
+            Err(MultipartError::IoError(_)) => (),
+            other => panic!("Expected IoError error, got {:?}", other),
+        }
+        builder.finish().unwrap();
+        match builder.add_text("a".to_string(), "b".to_string()) {
+            Err(MultipartError::AlreadyFinished) => (),
+            other => panic!("Expected AlreadyFinished error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,519 @@
+//! OAuth 2.0認可コードフロー（PKCE）モジュール
+//!
+//! このモジュールは、PKCE（RFC 7636）付きの認可コードフローを1回分管理する
+//! `PkceSession`をエクスポートします。`code_verifier`・`code_challenge`・`state`の生成、
+//! 認可URLの組み立て、リダイレクトURLの検証、認可コードとトークンの交換を行います。
+//!
+//! トークンレスポンスの`id_token`は、そのまま`decode_jwt`などに渡せます。
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand_core::{OsRng, RngCore};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+use url::Url;
+
+use crate::sigv4::HttpRequestParts;
+use crate::transport::{send_request, TransportError};
+
+/// トークンレスポンスの最大バイト数
+const MAX_TOKEN_RESPONSE_BYTES: u64 = 1024 * 1024;
+
+/// `code_verifier`の元になる乱数のバイト数（Base64URLで43文字）
+const VERIFIER_BYTES: usize = 32;
+
+/// `state`の元になる乱数のバイト数
+const STATE_BYTES: usize = 16;
+
+/// OAuthの処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum OAuthError {
+    /// エンドポイントのURLやクライアントIDが不正な場合
+    #[error("Invalid OAuth configuration: {0}")]
+    InvalidConfig(String),
+    /// リダイレクトURLの`state`がセッションのものと一致しない場合
+    #[error("State parameter does not match")]
+    StateMismatch,
+    /// リダイレクトURLに認可コードが含まれていない場合
+    #[error("Authorization code is missing from the redirect URL")]
+    MissingCode,
+    /// 認可サーバーがエラーを返した場合（ユーザーによる拒否など）
+    #[error("Authorization failed: {error} ({description})")]
+    AuthorizationDenied { error: String, description: String },
+    /// トークンエンドポイントがエラーを返した場合
+    #[error("Token request failed: {error} ({description})")]
+    TokenError { error: String, description: String },
+    /// 通信に失敗した場合
+    #[error("Network error: {0}")]
+    Network(String),
+    /// トークンレスポンスの形式が不正な場合
+    #[error("Invalid token response: {0}")]
+    InvalidResponse(String),
+    /// 認可コードを既に交換済みの場合
+    #[error("Authorization code has already been exchanged")]
+    AlreadyExchanged,
+}
+
+/// 認可サーバーとクライアントの設定
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct OAuthConfig {
+    /// 認可エンドポイントのURL
+    pub authorization_endpoint: String,
+    /// トークンエンドポイントのURL
+    pub token_endpoint: String,
+    /// クライアントID
+    pub client_id: String,
+    /// リダイレクトURI（カスタムスキームやユニバーサルリンク）
+    pub redirect_uri: String,
+    /// 要求するスコープ
+    pub scopes: Vec<String>,
+}
+
+/// トークンエンドポイントが返したトークン
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct OAuthTokens {
+    /// アクセストークン
+    pub access_token: String,
+    /// トークンの種類（通常は`Bearer`）
+    pub token_type: String,
+    /// アクセストークンの有効期間（秒）
+    pub expires_in: Option<u64>,
+    /// リフレッシュトークン
+    pub refresh_token: Option<String>,
+    /// IDトークン（OpenID Connectの場合、JWT）
+    pub id_token: Option<String>,
+    /// 許可されたスコープ（空白区切り）
+    pub scope: Option<String>,
+}
+
+/// ランダムな値をBase64URL（パディングなし）で返します
+fn random_token(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+/// HTTP(S)のURLとして解析します
+fn parse_endpoint(name: &str, url: &str) -> Result<Url, OAuthError> {
+    let parsed =
+        Url::parse(url).map_err(|e| OAuthError::InvalidConfig(format!("{}: {}", name, e)))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(OAuthError::InvalidConfig(format!("{} must be an HTTP(S) URL", name)));
+    }
+    Ok(parsed)
+}
+
+/// トークンレスポンスのJSONを解析します
+fn parse_tokens(body: &str) -> Result<OAuthTokens, OAuthError> {
+    let value: Value =
+        serde_json::from_str(body).map_err(|e| OAuthError::InvalidResponse(e.to_string()))?;
+    let text = |name: &str| value.get(name).and_then(Value::as_str).map(str::to_string);
+    let access_token = text("access_token")
+        .ok_or_else(|| OAuthError::InvalidResponse("access_token is missing".to_string()))?;
+    Ok(OAuthTokens {
+        access_token,
+        token_type: text("token_type").unwrap_or_else(|| "Bearer".to_string()),
+        // 一部のサーバーは有効期間を文字列で返す
+        expires_in: match value.get("expires_in") {
+            Some(Value::Number(n)) => n.as_u64(),
+            Some(Value::String(s)) => s.parse().ok(),
+            _ => None,
+        },
+        refresh_token: text("refresh_token"),
+        id_token: text("id_token"),
+        scope: text("scope"),
+    })
+}
+
+/// エラーレスポンス（RFC 6749 5.2）から(エラーコード, 説明)を取り出します
+fn parse_error_body(body: &str) -> Option<(String, String)> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let error = value.get("error")?.as_str()?.to_string();
+    let description = value
+        .get("error_description")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    Some((error, description))
+}
+
+/// PKCE付きの認可コードフロー1回分のセッション
+///
+/// 認可を開始するたびに新しいセッションを作成してください。
+/// 認可コードの交換は1回だけ行えます。
+///
+/// # Example
+/// ```
+/// let session = PkceSession::new(config)?;
+/// // ASWebAuthenticationSessionでsession.authorizationUrl()を開く
+/// let code = session.parse_redirect(callback_url)?;
+/// let tokens = session.exchange_code(code).await?;
+/// if let Some(id_token) = tokens.id_token {
+///     let claims = decode_jwt(&id_token)?;
+/// }
+/// ```
+#[derive(uniffi::Object)]
+pub struct PkceSession {
+    config: OAuthConfig,
+    authorization_endpoint: Url,
+    code_verifier: String,
+    state: String,
+    exchanged: AtomicBool,
+}
+
+impl PkceSession {
+    /// `code_verifier`と`state`を指定してセッションを作成します
+    fn with_values(
+        config: OAuthConfig,
+        code_verifier: String,
+        state: String,
+    ) -> Result<Self, OAuthError> {
+        let authorization_endpoint =
+            parse_endpoint("authorization endpoint", &config.authorization_endpoint)?;
+        parse_endpoint("token endpoint", &config.token_endpoint)?;
+        if config.client_id.is_empty() {
+            return Err(OAuthError::InvalidConfig("client ID must not be empty".to_string()));
+        }
+        Url::parse(&config.redirect_uri)
+            .map_err(|e| OAuthError::InvalidConfig(format!("redirect URI: {}", e)))?;
+        Ok(Self {
+            config,
+            authorization_endpoint,
+            code_verifier,
+            state,
+            exchanged: AtomicBool::new(false),
+        })
+    }
+
+    /// 認可コードをトークンと交換します（同期版）
+    fn exchange_code_blocking(&self, code: &str) -> Result<OAuthTokens, OAuthError> {
+        if self.exchanged.swap(true, Ordering::SeqCst) {
+            return Err(OAuthError::AlreadyExchanged);
+        }
+        let body = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("grant_type", "authorization_code")
+            .append_pair("code", code)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("code_verifier", &self.code_verifier)
+            .finish();
+        let request = HttpRequestParts {
+            method: "POST".to_string(),
+            url: self.config.token_endpoint.clone(),
+            headers: HashMap::from([
+                ("Accept".to_string(), "application/json".to_string()),
+                (
+                    "Content-Type".to_string(),
+                    "application/x-www-form-urlencoded".to_string(),
+                ),
+            ]),
+            body: body.into_bytes(),
+            payload_hash: None,
+        };
+        let response = send_request(&request, MAX_TOKEN_RESPONSE_BYTES).map_err(|e| match e {
+            TransportError::InvalidUrl { url } => {
+                OAuthError::Network(format!("invalid token endpoint: {}", url))
+            }
+            TransportError::Network { message } => OAuthError::Network(message),
+        })?;
+        if response.body.len() as u64 > MAX_TOKEN_RESPONSE_BYTES {
+            return Err(OAuthError::InvalidResponse("response is too large".to_string()));
+        }
+        let body = String::from_utf8_lossy(&response.body);
+        if response.is_success() {
+            parse_tokens(&body)
+        } else {
+            let (error, description) = parse_error_body(&body)
+                .unwrap_or_else(|| (format!("http_{}", response.status), body.into_owned()));
+            Err(OAuthError::TokenError { error, description })
+        }
+    }
+}
+
+#[uniffi::export]
+impl PkceSession {
+    /// 新しい`code_verifier`と`state`を生成してセッションを作成します
+    ///
+    /// # Arguments
+    /// * `config` - 認可サーバーとクライアントの設定
+    ///
+    /// # Errors
+    /// * `OAuthError::InvalidConfig` - エンドポイントのURL・リダイレクトURIが不正、
+    ///   またはクライアントIDが空の場合
+    #[uniffi::constructor]
+    pub fn new(config: OAuthConfig) -> Result<Arc<Self>, OAuthError> {
+        let session =
+            Self::with_values(config, random_token(VERIFIER_BYTES), random_token(STATE_BYTES))?;
+        Ok(Arc::new(session))
+    }
+
+    /// `code_verifier`を返します
+    pub fn code_verifier(&self) -> String {
+        self.code_verifier.clone()
+    }
+
+    /// `code_challenge`（`code_verifier`のSHA-256のBase64URL）を返します
+    pub fn code_challenge(&self) -> String {
+        URL_SAFE_NO_PAD.encode(Sha256::digest(self.code_verifier.as_bytes()))
+    }
+
+    /// `state`を返します
+    pub fn state(&self) -> String {
+        self.state.clone()
+    }
+
+    /// 認可エンドポイントのURLを返します
+    ///
+    /// 認可エンドポイントのURLに含まれていたクエリは残したまま、
+    /// `response_type`・`client_id`・`redirect_uri`・`scope`・`state`・
+    /// `code_challenge`・`code_challenge_method`を追加します。
+    pub fn authorization_url(&self) -> String {
+        let mut url = self.authorization_endpoint.clone();
+        {
+            let mut query = url.query_pairs_mut();
+            query
+                .append_pair("response_type", "code")
+                .append_pair("client_id", &self.config.client_id)
+                .append_pair("redirect_uri", &self.config.redirect_uri);
+            if !self.config.scopes.is_empty() {
+                query.append_pair("scope", &self.config.scopes.join(" "));
+            }
+            query
+                .append_pair("state", &self.state)
+                .append_pair("code_challenge", &self.code_challenge())
+                .append_pair("code_challenge_method", "S256");
+        }
+        url.to_string()
+    }
+
+    /// 認可サーバーからのリダイレクトURLを検証し、認可コードを返します
+    ///
+    /// # Arguments
+    /// * `redirect_url` - リダイレクトされたURL（クエリに`code`と`state`を含む）
+    ///
+    /// # Errors
+    /// * `OAuthError::AuthorizationDenied` - `error`パラメータが含まれる場合
+    /// * `OAuthError::StateMismatch` - `state`が一致しない場合
+    /// * `OAuthError::MissingCode` - 認可コードが含まれない場合
+    /// * `OAuthError::InvalidResponse` - URLとして解析できない場合
+    pub fn parse_redirect(&self, redirect_url: String) -> Result<String, OAuthError> {
+        let url = Url::parse(&redirect_url)
+            .map_err(|e| OAuthError::InvalidResponse(format!("redirect URL: {}", e)))?;
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.into_owned())
+        };
+        let state_matches = param("state").is_some_and(|state| {
+            subtle::ConstantTimeEq::ct_eq(state.as_bytes(), self.state.as_bytes()).into()
+        });
+        if let Some(error) = param("error") {
+            // stateが一致しないエラーは第三者が送り込んだ可能性があるため区別する
+            if !state_matches {
+                return Err(OAuthError::StateMismatch);
+            }
+            let description = param("error_description").unwrap_or_default();
+            return Err(OAuthError::AuthorizationDenied { error, description });
+        }
+        if !state_matches {
+            return Err(OAuthError::StateMismatch);
+        }
+        param("code")
+            .filter(|code| !code.is_empty())
+            .ok_or(OAuthError::MissingCode)
+    }
+
+    /// 認可コードをトークンと交換します
+///
+/// `set_http_transport`で登録されたトランスポートがあれば、それを経由して送信します。
+    ///
+    /// # Arguments
+    /// * `code` - `parse_redirect`で取得した認可コード
+    ///
+    /// # Errors
+    /// * `OAuthError::TokenError` - トークンエンドポイントがエラーを返した場合
+    /// * `OAuthError::Network` - 通信に失敗した場合
+    /// * `OAuthError::InvalidResponse` - レスポンスに`access_token`が含まれない、または大きすぎる場合
+    /// * `OAuthError::AlreadyExchanged` - 既に交換を試みた場合
+    pub async fn exchange_code(self: Arc<Self>, code: String) -> Result<OAuthTokens, OAuthError> {
+        blocking::unblock(move || self.exchange_code_blocking(&code)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+    use std::thread;
+
+    fn config(token_endpoint: &str) -> OAuthConfig {
+        OAuthConfig {
+            authorization_endpoint: "https://auth.example.com/authorize?prompt=login".to_string(),
+            token_endpoint: token_endpoint.to_string(),
+            client_id: "mobile-app".to_string(),
+            redirect_uri: "com.example.app:/callback".to_string(),
+            scopes: vec!["openid".to_string(), "profile".to_string()],
+        }
+    }
+
+    /// 1回だけレスポンスを返すトークンエンドポイントを起動し、URLとリクエストを返します
+    fn serve(response: &'static str) -> (String, Arc<Mutex<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let request = Arc::new(Mutex::new(String::new()));
+        let recorded = Arc::clone(&request);
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut data = Vec::new();
+            let mut buf = [0u8; 1024];
+            while let Ok(len) = stream.read(&mut buf) {
+                data.extend_from_slice(&buf[..len]);
+                let text = String::from_utf8_lossy(&data).to_string();
+                if let Some((head, body)) = text.split_once("\r\n\r\n") {
+                    let length = head
+                        .to_lowercase()
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length: ")?.parse().ok())
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        *recorded.lock().unwrap() = text;
+                        break;
+                    }
+                }
+            }
+            let _ = stream.write_all(response.as_bytes());
+        });
+        (format!("http://{}/token", addr), request)
+    }
+
+    #[test]
+    fn test_code_challenge_rfc7636() {
+        let session = PkceSession::with_values(
+            config("https://auth.example.com/token"),
+            "dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk".to_string(),
+            "xyz".to_string(),
+        )
+        .unwrap();
+        assert_eq!(session.code_challenge(), "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM");
+        assert_eq!(
+            session.authorization_url(),
+            "https://auth.example.com/authorize?prompt=login&response_type=code\
+             &client_id=mobile-app&redirect_uri=com.example.app%3A%2Fcallback\
+             &scope=openid+profile&state=xyz\
+             &code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM\
+             &code_challenge_method=S256"
+        );
+    }
+
+    #[test]
+    fn test_new_generates_random_values() {
+        let a = PkceSession::new(config("https://auth.example.com/token")).unwrap();
+        let b = PkceSession::new(config("https://auth.example.com/token")).unwrap();
+        assert_eq!(a.code_verifier().len(), 43);
+        assert_ne!(a.code_verifier(), b.code_verifier());
+        assert_ne!(a.state(), b.state());
+    }
+
+    #[test]
+    fn test_parse_redirect() {
+        let session = PkceSession::new(config("https://auth.example.com/token")).unwrap();
+        let state = session.state();
+        let url = format!("com.example.app:/callback?code=abc%2F1&state={}", state);
+        assert_eq!(session.parse_redirect(url).unwrap(), "abc/1");
+
+        let url = format!(
+            "com.example.app:/callback?error=access_denied&error_description=User+cancelled\
+             &state={}",
+            state
+        );
+        match session.parse_redirect(url) {
+            Err(OAuthError::AuthorizationDenied { error, description }) => {
+                assert_eq!(error, "access_denied");
+                assert_eq!(description, "User cancelled");
+            }
+            other => panic!("Expected AuthorizationDenied error, got {:?}", other),
+        }
+        for url in [
+            "com.example.app:/callback?code=abc&state=forged".to_string(),
+            "com.example.app:/callback?error=access_denied".to_string(),
+        ] {
+            match session.parse_redirect(url) {
+                Err(OAuthError::StateMismatch) => (),
+                other => panic!("Expected StateMismatch error, got {:?}", other),
+            }
+        }
+        match session.parse_redirect(format!("com.example.app:/callback?state={}", state)) {
+            Err(OAuthError::MissingCode) => (),
+            other => panic!("Expected MissingCode error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exchange_code() {
+        let (url, request) = serve(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+             Connection: close\r\n\r\n\
+             {\"access_token\":\"at\",\"token_type\":\"Bearer\",\"expires_in\":3600,\
+             \"refresh_token\":\"rt\",\"id_token\":\"a.b.c\",\"scope\":\"openid\"}",
+        );
+        let session = PkceSession::new(config(&url)).unwrap();
+        let tokens = pollster::block_on(session.clone().exchange_code("abc".to_string())).unwrap();
+        assert_eq!(tokens, OAuthTokens {
+            access_token: "at".to_string(),
+            token_type: "Bearer".to_string(),
+            expires_in: Some(3600),
+            refresh_token: Some("rt".to_string()),
+            id_token: Some("a.b.c".to_string()),
+            scope: Some("openid".to_string()),
+        });
+        let request = request.lock().unwrap().clone();
+        assert!(request.starts_with("POST /token "));
+        assert!(request.contains("grant_type=authorization_code"));
+        assert!(request.contains(&format!("code_verifier={}", session.code_verifier())));
+        assert!(request.contains("redirect_uri=com.example.app%3A%2Fcallback"));
+
+        match pollster::block_on(session.exchange_code("abc".to_string())) {
+            Err(OAuthError::AlreadyExchanged) => (),
+            other => panic!("Expected AlreadyExchanged error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_exchange_code_error_response() {
+        let (url, _) = serve(
+            "HTTP/1.1 400 Bad Request\r\nContent-Type: application/json\r\n\
+             Connection: close\r\n\r\n\
+             {\"error\":\"invalid_grant\",\"error_description\":\"Code has expired\"}",
+        );
+        let session = PkceSession::new(config(&url)).unwrap();
+        match pollster::block_on(session.exchange_code("abc".to_string())) {
+            Err(OAuthError::TokenError { error, description }) => {
+                assert_eq!(error, "invalid_grant");
+                assert_eq!(description, "Code has expired");
+            }
+            other => panic!("Expected TokenError error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut invalid = config("https://auth.example.com/token");
+        invalid.authorization_endpoint = "auth.example.com/authorize".to_string();
+        let mut empty_client = config("ftp://auth.example.com/token");
+        empty_client.token_endpoint = "https://auth.example.com/token".to_string();
+        empty_client.client_id = String::new();
+        for config in [invalid, config("ftp://auth.example.com/token"), empty_client] {
+            match PkceSession::new(config) {
+                Err(OAuthError::InvalidConfig(_)) => (),
+                other => panic!("Expected InvalidConfig error, got {:?}", other.map(|_| ())),
+            }
+        }
+    }
+}
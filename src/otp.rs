@@ -0,0 +1,684 @@
+//! ワンタイムパスワード（TOTP/HOTP）モジュール
+//!
+//! このモジュールは、RFC 6238のTOTPコードを生成・検証する`Totp`オブジェクトと、
+//! RFC 4226のカウンタベースのHOTPコードを扱う`Hotp`オブジェクトをエクスポートします。
+//! 設定は`otpauth://`形式のURIまたはBase32の共有秘密から作成でき、
+//! アプリ内の認証コード表示機能での利用を想定しています。
+
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use data_encoding::BASE32_NOPAD;
+use hmac::{Hmac, Mac};
+use percent_encoding::percent_decode_str;
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use url::Url;
+
+/// ワンタイムパスワード処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum OtpError {
+    /// 共有秘密が空、または不正なBase32文字列の場合
+    #[error("Invalid secret: {0}")]
+    InvalidSecret(String),
+    /// `otpauth://`URIの形式が不正な場合
+    #[error("Invalid otpauth URI: {0}")]
+    InvalidUri(String),
+    /// 桁数が6〜8の範囲外の場合
+    #[error("Invalid number of digits: {0} (expected 6 to 8)")]
+    InvalidDigits(u32),
+    /// 時間ステップが0の場合
+    #[error("Invalid period: must be greater than 0")]
+    InvalidPeriod,
+    /// HOTPの先読み数が上限を超えている場合
+    #[error("Invalid look-ahead: {0} (expected at most 100)")]
+    InvalidLookAhead(u32),
+    /// サポートされていないハッシュアルゴリズムが指定された場合
+    #[error("Unsupported algorithm: {0}")]
+    UnsupportedAlgorithm(String),
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+}
+
+/// HMACに使用するハッシュアルゴリズム
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, uniffi::Enum)]
+pub enum OtpAlgorithm {
+    /// HMAC-SHA1（多くの認証アプリの既定値）
+    #[default]
+    Sha1,
+    /// HMAC-SHA256
+    Sha256,
+    /// HMAC-SHA512
+    Sha512,
+}
+
+impl OtpAlgorithm {
+    /// `otpauth://`URIの`algorithm`パラメータを解釈します
+    fn parse(value: &str) -> Result<Self, OtpError> {
+        match value.to_ascii_uppercase().as_str() {
+            "SHA1" => Ok(Self::Sha1),
+            "SHA256" => Ok(Self::Sha256),
+            "SHA512" => Ok(Self::Sha512),
+            _ => Err(OtpError::UnsupportedAlgorithm(value.to_string())),
+        }
+    }
+}
+
+/// TOTPの設定
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct TotpConfig {
+    /// Base32でエンコードされた共有秘密（大文字・小文字、空白、パディングは無視されます）
+    pub secret: String,
+    /// HMACに使用するハッシュアルゴリズム
+    pub algorithm: OtpAlgorithm,
+    /// サービス提供者の名前
+    #[uniffi(default = None)]
+    pub issuer: Option<String>,
+    /// アカウント名（メールアドレスなど）
+    #[uniffi(default = None)]
+    pub account_name: Option<String>,
+    /// コードの桁数（6〜8）
+    #[uniffi(default = 6)]
+    pub digits: u32,
+    /// 時間ステップ（秒）
+    #[uniffi(default = 30)]
+    pub period: u64,
+}
+
+/// HOTPの設定
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct HotpConfig {
+    /// Base32でエンコードされた共有秘密（大文字・小文字、空白、パディングは無視されます）
+    pub secret: String,
+    /// HMACに使用するハッシュアルゴリズム
+    pub algorithm: OtpAlgorithm,
+    /// サービス提供者の名前
+    #[uniffi(default = None)]
+    pub issuer: Option<String>,
+    /// アカウント名（メールアドレスなど）
+    #[uniffi(default = None)]
+    pub account_name: Option<String>,
+    /// コードの桁数（6〜8）
+    #[uniffi(default = 6)]
+    pub digits: u32,
+    /// 次に使用するカウンタ値
+    #[uniffi(default = 0)]
+    pub counter: u64,
+}
+
+/// TOTPの検証で許容する前後の時間ステップ数の上限
+const MAX_WINDOW: u32 = 10;
+
+/// HOTPの再同期で先読みするカウンタ数の上限
+const MAX_LOOK_AHEAD: u32 = 100;
+
+/// 現在のUNIX時刻（秒）を返します
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Base32の共有秘密をデコードします
+fn decode_secret(secret: &str) -> Result<Vec<u8>, OtpError> {
+    let normalized: String = secret
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '=' && *c != '-')
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if normalized.is_empty() {
+        return Err(OtpError::InvalidSecret("secret is empty".to_string()));
+    }
+    BASE32_NOPAD
+        .decode(normalized.as_bytes())
+        .map_err(|e| OtpError::InvalidSecret(e.to_string()))
+}
+
+/// 桁数が有効範囲内かを確認します
+fn check_digits(digits: u32) -> Result<(), OtpError> {
+    if !(6..=8).contains(&digits) {
+        return Err(OtpError::InvalidDigits(digits));
+    }
+    Ok(())
+}
+
+/// 指定したHMAC実装でダイジェストを計算します
+fn hmac_digest<M: Mac + hmac::digest::KeyInit>(key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut mac = <M as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(message);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// RFC 4226のHOTP値を計算し、ゼロ埋めした文字列で返します
+fn hotp_code(algorithm: OtpAlgorithm, key: &[u8], counter: u64, digits: u32) -> String {
+    let message = counter.to_be_bytes();
+    let digest = match algorithm {
+        OtpAlgorithm::Sha1 => hmac_digest::<Hmac<Sha1>>(key, &message),
+        OtpAlgorithm::Sha256 => hmac_digest::<Hmac<Sha256>>(key, &message),
+        OtpAlgorithm::Sha512 => hmac_digest::<Hmac<Sha512>>(key, &message),
+    };
+    // 動的切り捨て（dynamic truncation）
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset],
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]) & 0x7fff_ffff;
+    format!("{:0width$}", binary % 10u32.pow(digits), width = digits as usize)
+}
+
+/// 2つのコードを定数時間で比較します
+fn codes_match(expected: &str, actual: &str) -> bool {
+    expected.as_bytes().ct_eq(actual.as_bytes()).into()
+}
+
+/// `otpauth://`URIを解析した結果
+struct OtpauthUri {
+    secret: String,
+    algorithm: OtpAlgorithm,
+    issuer: Option<String>,
+    account_name: Option<String>,
+    digits: u32,
+    period: u64,
+    counter: u64,
+}
+
+/// `otpauth://{kind}/{label}?secret=...`形式のURIを解析します
+fn parse_otpauth_uri(uri: &str, kind: &str) -> Result<OtpauthUri, OtpError> {
+    let url = Url::parse(uri).map_err(|e| OtpError::InvalidUri(e.to_string()))?;
+    if url.scheme() != "otpauth" {
+        return Err(OtpError::InvalidUri(format!("unexpected scheme: {}", url.scheme())));
+    }
+    if !url.host_str().is_some_and(|host| host.eq_ignore_ascii_case(kind)) {
+        return Err(OtpError::InvalidUri(format!("expected otpauth://{}/", kind)));
+    }
+
+    // ラベルは「発行者:アカウント名」または「アカウント名」
+    let label = percent_decode_str(url.path().trim_start_matches('/'))
+        .decode_utf8()
+        .map_err(|e| OtpError::InvalidUri(e.to_string()))?;
+    let (label_issuer, account_name) = match label.split_once(':') {
+        Some((issuer, account)) => (Some(issuer.trim().to_string()), account.trim().to_string()),
+        None => (None, label.trim().to_string()),
+    };
+
+    let mut parsed = OtpauthUri {
+        secret: String::new(),
+        algorithm: OtpAlgorithm::default(),
+        issuer: label_issuer,
+        account_name: (!account_name.is_empty()).then_some(account_name),
+        digits: 6,
+        period: 30,
+        counter: 0,
+    };
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "secret" => parsed.secret = value.into_owned(),
+            "issuer" => parsed.issuer = Some(value.into_owned()),
+            "algorithm" => parsed.algorithm = OtpAlgorithm::parse(&value)?,
+            "digits" => {
+                parsed.digits = value
+                    .parse()
+                    .map_err(|_| OtpError::InvalidUri(format!("invalid digits: {}", value)))?;
+            }
+            "period" => {
+                parsed.period = value
+                    .parse()
+                    .map_err(|_| OtpError::InvalidUri(format!("invalid period: {}", value)))?;
+            }
+            "counter" => {
+                parsed.counter = value
+                    .parse()
+                    .map_err(|_| OtpError::InvalidUri(format!("invalid counter: {}", value)))?;
+            }
+            _ => {}
+        }
+    }
+    if parsed.secret.is_empty() {
+        return Err(OtpError::InvalidUri("missing secret parameter".to_string()));
+    }
+    Ok(parsed)
+}
+
+/// 時間ベースのワンタイムパスワード（TOTP, RFC 6238）
+///
+/// # Example
+/// ```
+/// let totp = Totp::from_uri(
+///     "otpauth://totp/Example:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=Example"
+///         .to_string(),
+/// )?;
+/// let code = totp.current_code();
+/// assert!(totp.verify(code, 1));
+/// ```
+#[derive(uniffi::Object)]
+pub struct Totp {
+    config: TotpConfig,
+    key: Vec<u8>,
+}
+
+impl Totp {
+    /// 指定時刻における時間ステップのカウンタ値を返します
+    fn counter_at(&self, timestamp: u64) -> u64 {
+        timestamp / self.config.period
+    }
+
+    /// 指定時刻を基準にコードを検証します
+    fn verify_at(&self, code: &str, window: u32, timestamp: u64) -> bool {
+        let code = code.trim();
+        if code.len() != self.config.digits as usize {
+            return false;
+        }
+        let counter = self.counter_at(timestamp);
+        let window = u64::from(window.min(MAX_WINDOW));
+        let start = counter.saturating_sub(window);
+        let end = counter.saturating_add(window);
+        // 一致した時点で打ち切らず、すべての候補と比較します
+        (start..=end).fold(false, |matched, step| {
+            let expected = hotp_code(self.config.algorithm, &self.key, step, self.config.digits);
+            codes_match(&expected, code) | matched
+        })
+    }
+}
+
+#[uniffi::export]
+impl Totp {
+    /// 設定からTOTPを作成します
+    ///
+    /// # Arguments
+    /// * `config` - 共有秘密・アルゴリズム・桁数・時間ステップなどの設定
+    ///
+    /// # Errors
+    /// * `OtpError::InvalidSecret` - 共有秘密が空または不正なBase32の場合
+    /// * `OtpError::InvalidDigits` - 桁数が6〜8の範囲外の場合
+    /// * `OtpError::InvalidPeriod` - 時間ステップが0の場合
+    #[uniffi::constructor]
+    pub fn new(config: TotpConfig) -> Result<Arc<Self>, OtpError> {
+        check_digits(config.digits)?;
+        if config.period == 0 {
+            return Err(OtpError::InvalidPeriod);
+        }
+        let key = decode_secret(&config.secret)?;
+        Ok(Arc::new(Self { config, key }))
+    }
+
+    /// `otpauth://totp/...`形式のURI（QRコードの内容）からTOTPを作成します
+    ///
+    /// # Arguments
+    /// * `uri` - `otpauth://totp/Issuer:account?secret=...&issuer=...`形式のURI
+    ///
+    /// # Errors
+    /// * `OtpError::InvalidUri` - URIの形式が不正、または`secret`がない場合
+    /// * `OtpError::UnsupportedAlgorithm` - `algorithm`が未対応の値の場合
+    /// * その他`Totp::new`と同じエラー
+    #[uniffi::constructor]
+    pub fn from_uri(uri: String) -> Result<Arc<Self>, OtpError> {
+        let parsed = parse_otpauth_uri(&uri, "totp")?;
+        Self::new(TotpConfig {
+            secret: parsed.secret,
+            algorithm: parsed.algorithm,
+            issuer: parsed.issuer,
+            account_name: parsed.account_name,
+            digits: parsed.digits,
+            period: parsed.period,
+        })
+    }
+
+    /// このTOTPの設定を返します
+    pub fn config(&self) -> TotpConfig {
+        self.config.clone()
+    }
+
+    /// 現在時刻のコードを返します
+    pub fn current_code(&self) -> String {
+        self.code_at(now_unix())
+    }
+
+    /// 指定時刻（UNIX秒）のコードを返します
+    pub fn code_at(&self, timestamp: u64) -> String {
+        hotp_code(
+            self.config.algorithm,
+            &self.key,
+            self.counter_at(timestamp),
+            self.config.digits,
+        )
+    }
+
+    /// コードを検証します
+    ///
+    /// 時計のずれを考慮し、現在の時間ステップの前後`window`ステップ分の
+    /// コードも有効として扱います。比較は定数時間で行われます。
+    ///
+    /// # Arguments
+    /// * `code` - 利用者が入力したコード
+    /// * `window` - 許容する前後の時間ステップ数（通常は1、10を超える値は10として扱います）
+    pub fn verify(&self, code: String, window: u32) -> bool {
+        self.verify_at(&code, window, now_unix())
+    }
+
+    /// 現在のコードが切り替わるまでの残り秒数を返します
+    pub fn seconds_remaining(&self) -> u64 {
+        self.config.period - now_unix() % self.config.period
+    }
+}
+
+/// カウンタベースのワンタイムパスワード（HOTP, RFC 4226）
+///
+/// 内部でカウンタを保持し、複数のスレッドから安全にアクセスできます。
+/// 認証アプリ側では`next_code`でコードを発行し、検証側では
+/// `verify_and_resync`でトークンとのカウンタのずれを吸収します。
+///
+/// # Example
+/// ```
+/// let hotp = Hotp::from_uri(
+///     "otpauth://hotp/Example:alice?secret=JBSWY3DPEHPK3PXP&counter=0".to_string(),
+/// )?;
+/// let code = hotp.next_code()?;
+/// assert_eq!(hotp.counter()?, 1);
+/// ```
+#[derive(uniffi::Object)]
+pub struct Hotp {
+    config: HotpConfig,
+    key: Vec<u8>,
+    counter: Mutex<u64>,
+}
+
+#[uniffi::export]
+impl Hotp {
+    /// 設定からHOTPを作成します
+    ///
+    /// # Arguments
+    /// * `config` - 共有秘密・アルゴリズム・桁数・初期カウンタなどの設定
+    ///
+    /// # Errors
+    /// * `OtpError::InvalidSecret` - 共有秘密が空または不正なBase32の場合
+    /// * `OtpError::InvalidDigits` - 桁数が6〜8の範囲外の場合
+    #[uniffi::constructor]
+    pub fn new(config: HotpConfig) -> Result<Arc<Self>, OtpError> {
+        check_digits(config.digits)?;
+        let key = decode_secret(&config.secret)?;
+        Ok(Arc::new(Self {
+            counter: Mutex::new(config.counter),
+            config,
+            key,
+        }))
+    }
+
+    /// `otpauth://hotp/...`形式のURI（QRコードの内容）からHOTPを作成します
+    ///
+    /// # Arguments
+    /// * `uri` - `otpauth://hotp/Issuer:account?secret=...&counter=...`形式のURI
+    ///
+    /// # Errors
+    /// * `OtpError::InvalidUri` - URIの形式が不正、または`secret`がない場合
+    /// * `OtpError::UnsupportedAlgorithm` - `algorithm`が未対応の値の場合
+    /// * その他`Hotp::new`と同じエラー
+    #[uniffi::constructor]
+    pub fn from_uri(uri: String) -> Result<Arc<Self>, OtpError> {
+        let parsed = parse_otpauth_uri(&uri, "hotp")?;
+        Self::new(HotpConfig {
+            secret: parsed.secret,
+            algorithm: parsed.algorithm,
+            issuer: parsed.issuer,
+            account_name: parsed.account_name,
+            digits: parsed.digits,
+            counter: parsed.counter,
+        })
+    }
+
+    /// このHOTPの設定を返します（`counter`は現在のカウンタ値）
+    ///
+    /// # Errors
+    /// * `OtpError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn config(&self) -> Result<HotpConfig, OtpError> {
+        Ok(HotpConfig {
+            counter: self.counter()?,
+            ..self.config.clone()
+        })
+    }
+
+    /// 次に使用するカウンタ値を返します
+    ///
+    /// # Errors
+    /// * `OtpError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn counter(&self) -> Result<u64, OtpError> {
+        let counter = self.counter.lock()
+            .map_err(|_| OtpError::MutexPoisoned)?;
+        Ok(*counter)
+    }
+
+    /// 指定したカウンタ値のコードを返します（カウンタは変更しません）
+    pub fn code_at(&self, counter: u64) -> String {
+        hotp_code(self.config.algorithm, &self.key, counter, self.config.digits)
+    }
+
+    /// 現在のカウンタ値のコードを返し、カウンタを1進めます
+    ///
+    /// # Errors
+    /// * `OtpError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn next_code(&self) -> Result<String, OtpError> {
+        let mut counter = self.counter.lock()
+            .map_err(|_| OtpError::MutexPoisoned)?;
+        let code = self.code_at(*counter);
+        *counter = counter.wrapping_add(1);
+        Ok(code)
+    }
+
+    /// コードを検証し、一致した場合はカウンタを再同期します
+    ///
+    /// 現在のカウンタ値から`look_ahead`個先までのコードと比較し、
+    /// 一致した場合はカウンタをその次の値に進めます。
+    /// 一致しなかった場合、カウンタは変更されません。
+    ///
+    /// # Arguments
+    /// * `code` - 利用者が入力したコード
+    /// * `look_ahead` - 先読みするカウンタ数（トークン側で未使用のまま進んだ回数の許容量）
+    ///
+    /// # Errors
+    /// * `OtpError::InvalidLookAhead` - `look_ahead`が100を超える場合
+    /// * `OtpError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn verify_and_resync(&self, code: String, look_ahead: u32) -> Result<bool, OtpError> {
+        if look_ahead > MAX_LOOK_AHEAD {
+            return Err(OtpError::InvalidLookAhead(look_ahead));
+        }
+        let code = code.trim();
+        let mut counter = self.counter.lock()
+            .map_err(|_| OtpError::MutexPoisoned)?;
+        if code.len() != self.config.digits as usize {
+            return Ok(false);
+        }
+        let start = *counter;
+        let end = start.saturating_add(u64::from(look_ahead));
+        // 一致した時点で打ち切らず、すべての候補と比較します
+        let matched = (start..=end).fold(None, |matched, step| {
+            let expected = self.code_at(step);
+            match matched {
+                None if codes_match(&expected, code) => Some(step),
+                _ => matched,
+            }
+        });
+        match matched {
+            Some(step) => {
+                *counter = step.wrapping_add(1);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use data_encoding::BASE32;
+
+    fn totp(secret: &[u8], algorithm: OtpAlgorithm) -> Arc<Totp> {
+        Totp::new(TotpConfig {
+            secret: BASE32.encode(secret),
+            algorithm,
+            issuer: None,
+            account_name: None,
+            digits: 8,
+            period: 30,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let sha1 = totp(b"12345678901234567890", OtpAlgorithm::Sha1);
+        let sha256 = totp(b"12345678901234567890123456789012", OtpAlgorithm::Sha256);
+        let sha512 = totp(
+            b"1234567890123456789012345678901234567890123456789012345678901234",
+            OtpAlgorithm::Sha512,
+        );
+        assert_eq!(sha1.code_at(59), "94287082");
+        assert_eq!(sha256.code_at(59), "46119246");
+        assert_eq!(sha512.code_at(59), "90693936");
+        assert_eq!(sha1.code_at(1111111109), "07081804");
+        assert_eq!(sha1.code_at(20000000000), "65353130");
+    }
+
+    #[test]
+    fn test_totp_verify_window() {
+        let totp = totp(b"12345678901234567890", OtpAlgorithm::Sha1);
+        let code = totp.code_at(59);
+        assert!(totp.verify_at(&code, 0, 59));
+        assert!(totp.verify_at(&code, 1, 89));
+        assert!(!totp.verify_at(&code, 0, 89));
+        assert!(!totp.verify_at("123", 1, 59));
+        // 大きなwindowは上限に丸められます
+        assert!(totp.verify_at(&code, u32::MAX, 59 + 30 * 10));
+        assert!(!totp.verify_at(&code, u32::MAX, 59 + 30 * 11));
+    }
+
+    #[test]
+    fn test_totp_from_uri() {
+        let totp = Totp::from_uri(
+            "otpauth://totp/ACME%20Co:john@example.com?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&algorithm=SHA1&digits=8&period=30"
+                .to_string(),
+        )
+        .unwrap();
+        let config = totp.config();
+        assert_eq!(config.issuer.as_deref(), Some("ACME Co"));
+        assert_eq!(config.account_name.as_deref(), Some("john@example.com"));
+        assert_eq!(config.digits, 8);
+        assert_eq!(totp.code_at(59), "94287082");
+    }
+
+    #[test]
+    fn test_totp_issuer_parameter_overrides_label() {
+        let totp = Totp::from_uri(
+            "otpauth://totp/Old:alice?secret=jbswy3dpehpk3pxp&issuer=New".to_string(),
+        )
+        .unwrap();
+        assert_eq!(totp.config().issuer.as_deref(), Some("New"));
+        assert_eq!(totp.config().digits, 6);
+    }
+
+    #[test]
+    fn test_totp_invalid_uri() {
+        match Totp::from_uri("otpauth://hotp/x?secret=JBSWY3DPEHPK3PXP".to_string()) {
+            Err(OtpError::InvalidUri(_)) => (),
+            _ => panic!("Expected InvalidUri error"),
+        }
+        match Totp::from_uri("otpauth://totp/x?issuer=Example".to_string()) {
+            Err(OtpError::InvalidUri(_)) => (),
+            _ => panic!("Expected InvalidUri error"),
+        }
+    }
+
+    #[test]
+    fn test_totp_invalid_config() {
+        let config = TotpConfig {
+            secret: "JBSWY3DPEHPK3PXP".to_string(),
+            algorithm: OtpAlgorithm::Sha1,
+            issuer: None,
+            account_name: None,
+            digits: 6,
+            period: 30,
+        };
+        match Totp::new(TotpConfig { digits: 4, ..config.clone() }) {
+            Err(OtpError::InvalidDigits(4)) => (),
+            _ => panic!("Expected InvalidDigits error"),
+        }
+        match Totp::new(TotpConfig { period: 0, ..config.clone() }) {
+            Err(OtpError::InvalidPeriod) => (),
+            _ => panic!("Expected InvalidPeriod error"),
+        }
+        match Totp::new(TotpConfig { secret: "not base32!".to_string(), ..config }) {
+            Err(OtpError::InvalidSecret(_)) => (),
+            _ => panic!("Expected InvalidSecret error"),
+        }
+    }
+
+    #[test]
+    fn test_totp_seconds_remaining() {
+        let totp = totp(b"12345678901234567890", OtpAlgorithm::Sha1);
+        let remaining = totp.seconds_remaining();
+        assert!((1..=30).contains(&remaining));
+    }
+
+    fn hotp(counter: u64) -> Arc<Hotp> {
+        Hotp::new(HotpConfig {
+            secret: BASE32.encode(b"12345678901234567890"),
+            algorithm: OtpAlgorithm::Sha1,
+            issuer: None,
+            account_name: None,
+            digits: 6,
+            counter,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_hotp_rfc4226_vectors() {
+        let hotp = hotp(0);
+        let expected = [
+            "755224", "287082", "359152", "969429", "338314",
+            "254676", "287922", "162583", "399871", "520489",
+        ];
+        for code in expected {
+            assert_eq!(hotp.next_code().unwrap(), code);
+        }
+        assert_eq!(hotp.counter().unwrap(), 10);
+    }
+
+    #[test]
+    fn test_hotp_verify_and_resync() {
+        let hotp = hotp(0);
+        // トークン側で3回分コードが消費された後のコード
+        assert!(!hotp.verify_and_resync("969429".to_string(), 2).unwrap());
+        assert_eq!(hotp.counter().unwrap(), 0);
+        assert!(hotp.verify_and_resync("969429".to_string(), 3).unwrap());
+        assert_eq!(hotp.counter().unwrap(), 4);
+        // 同じコードは再利用できない
+        assert!(!hotp.verify_and_resync("969429".to_string(), 3).unwrap());
+        // 先読み数の上限
+        assert!(!hotp.verify_and_resync("969429".to_string(), 100).unwrap());
+        assert!(matches!(
+            hotp.verify_and_resync("969429".to_string(), 101),
+            Err(OtpError::InvalidLookAhead(101))
+        ));
+        assert_eq!(hotp.counter().unwrap(), 4);
+    }
+
+    #[test]
+    fn test_hotp_from_uri() {
+        let hotp = Hotp::from_uri(
+            "otpauth://hotp/Corp:bob?secret=GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ&counter=5".to_string(),
+        )
+        .unwrap();
+        assert_eq!(hotp.counter().unwrap(), 5);
+        assert_eq!(hotp.next_code().unwrap(), "254676");
+        let config = hotp.config().unwrap();
+        assert_eq!(config.issuer.as_deref(), Some("Corp"));
+        assert_eq!(config.counter, 6);
+    }
+}
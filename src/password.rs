@@ -0,0 +1,260 @@
+//! パスワードハッシュモジュール
+//!
+//! このモジュールは、Argon2idによるパスワードのハッシュ化と検証を行う関数を
+//! エクスポートします。ハッシュはPHC文字列形式（`$argon2id$v=19$...`）で返され、
+//! パラメータとソルトを含むためそのまま保存できます。
+//!
+//! 既存バックエンドとの互換性のため、bcryptのハッシュ化と検証にも対応しています。
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::{Algorithm, Argon2, Params, Version};
+use thiserror::Error;
+
+/// パスワードハッシュ処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum PasswordError {
+    /// ハッシュパラメータが不正な場合
+    #[error("Invalid hashing parameters: {0}")]
+    InvalidParams(String),
+    /// ハッシュ計算に失敗した場合
+    #[error("Password hashing failed: {0}")]
+    HashingFailed(String),
+    /// 保存されたハッシュ文字列の形式が不正な場合
+    #[error("Invalid password hash: {0}")]
+    InvalidHash(String),
+}
+
+/// Argon2idのコストパラメータ
+///
+/// 既定値はOWASPの推奨値（19 MiB、2回、並列度1）です。
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct Argon2Params {
+    /// 使用メモリ量（KiB）
+    #[uniffi(default = 19456)]
+    pub memory_kib: u32,
+    /// 反復回数
+    #[uniffi(default = 2)]
+    pub iterations: u32,
+    /// 並列度
+    #[uniffi(default = 1)]
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    fn default() -> Self {
+        Self {
+            memory_kib: 19456,
+            iterations: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// ファイルに保存して読み戻すパラメータの上限（既定値の4倍）を超えないかを返します
+    ///
+    /// 細工されたファイルのヘッダーで巨大なメモリ確保や長時間の計算を
+    /// 引き起こされないように、ファイルから読んだパラメータは鍵導出の前に確認します。
+    pub(crate) fn is_within_file_limits(&self) -> bool {
+        let default = Self::default();
+        self.memory_kib <= default.memory_kib * 4
+            && self.iterations <= default.iterations * 4
+            && self.parallelism <= default.parallelism * 4
+    }
+}
+
+/// パスワードをArgon2idでハッシュ化します
+///
+/// ソルトはOSの暗号論的乱数生成器から生成されます。
+///
+/// # Arguments
+/// * `password` - ハッシュ化するパスワード
+/// * `params` - コストパラメータ（`None`の場合は既定値）
+///
+/// # Returns
+/// * PHC文字列形式のハッシュ
+///
+/// # Errors
+/// * `PasswordError::InvalidParams` - パラメータが許容範囲外の場合
+/// * `PasswordError::HashingFailed` - ハッシュ計算に失敗した場合
+///
+/// # Example
+/// ```
+/// let hash = hash_password("correct horse".to_string(), None)?;
+/// assert!(verify_password("correct horse".to_string(), hash)?);
+/// ```
+#[uniffi::export(default(params = None))]
+pub fn hash_password(
+    password: String,
+    params: Option<Argon2Params>,
+) -> Result<String, PasswordError> {
+    let params = params.unwrap_or_default();
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, None)
+        .map_err(|e| PasswordError::InvalidParams(e.to_string()))?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+    let salt = SaltString::generate(&mut OsRng);
+    argon2
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| PasswordError::HashingFailed(e.to_string()))
+}
+
+/// パスワードがPHC文字列形式のArgon2ハッシュと一致するか検証します
+///
+/// ハッシュ化時のパラメータはPHC文字列から読み取られます。
+///
+/// # Arguments
+/// * `password` - 検証するパスワード
+/// * `phc_string` - `hash_password`が返したハッシュ
+///
+/// # Returns
+/// * `true` - パスワードが一致した場合
+/// * `false` - 一致しない場合
+///
+/// # Errors
+/// * `PasswordError::InvalidHash` - ハッシュ文字列の形式が不正な場合
+#[uniffi::export]
+pub fn verify_password(password: String, phc_string: String) -> Result<bool, PasswordError> {
+    let hash = PasswordHash::new(&phc_string)
+        .map_err(|e| PasswordError::InvalidHash(e.to_string()))?;
+    match Argon2::default().verify_password(password.as_bytes(), &hash) {
+        Ok(()) => Ok(true),
+        Err(argon2::password_hash::Error::Password) => Ok(false),
+        Err(e) => Err(PasswordError::InvalidHash(e.to_string())),
+    }
+}
+
+/// bcryptのエラーを`PasswordError`に変換します
+fn map_bcrypt_error(error: bcrypt::BcryptError) -> PasswordError {
+    match error {
+        bcrypt::BcryptError::CostNotAllowed(_) => PasswordError::InvalidParams(error.to_string()),
+        bcrypt::BcryptError::InvalidCost(_)
+        | bcrypt::BcryptError::InvalidPrefix(_)
+        | bcrypt::BcryptError::InvalidHash(_)
+        | bcrypt::BcryptError::InvalidSaltLen(_)
+        | bcrypt::BcryptError::InvalidBase64(_) => PasswordError::InvalidHash(error.to_string()),
+        _ => PasswordError::HashingFailed(error.to_string()),
+    }
+}
+
+/// パスワードをbcryptでハッシュ化します
+///
+/// 既存システムとの互換性のため、72バイトを超えるパスワードは
+/// bcryptの仕様どおり切り詰められます。新規の用途では`hash_password`を使用してください。
+///
+/// # Arguments
+/// * `password` - ハッシュ化するパスワード
+/// * `cost` - コスト（4〜31、既定値は12）
+///
+/// # Returns
+/// * `$2b$`形式のbcryptハッシュ
+///
+/// # Errors
+/// * `PasswordError::InvalidParams` - コストが範囲外の場合
+/// * `PasswordError::HashingFailed` - ハッシュ計算に失敗した場合
+#[uniffi::export(default(cost = 12))]
+pub fn bcrypt_hash(password: String, cost: u32) -> Result<String, PasswordError> {
+    bcrypt::hash(password.as_bytes(), cost).map_err(map_bcrypt_error)
+}
+
+/// パスワードがbcryptハッシュと一致するか検証します
+///
+/// `$2a$`・`$2b$`・`$2x$`・`$2y$`の各形式に対応しています。
+///
+/// # Arguments
+/// * `password` - 検証するパスワード
+/// * `hash` - bcryptハッシュ
+///
+/// # Returns
+/// * `true` - パスワードが一致した場合
+/// * `false` - 一致しない場合
+///
+/// # Errors
+/// * `PasswordError::InvalidHash` - ハッシュの形式が不正な場合
+#[uniffi::export]
+pub fn bcrypt_verify(password: String, hash: String) -> Result<bool, PasswordError> {
+    bcrypt::verify(password.as_bytes(), &hash).map_err(map_bcrypt_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テストを高速化するための軽量パラメータ
+    fn fast_params() -> Option<Argon2Params> {
+        Some(Argon2Params {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        })
+    }
+
+    #[test]
+    fn test_hash_and_verify_password() {
+        let hash = hash_password("s3cret!".to_string(), fast_params()).unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert!(verify_password("s3cret!".to_string(), hash.clone()).unwrap());
+        assert!(!verify_password("wrong".to_string(), hash).unwrap());
+    }
+
+    #[test]
+    fn test_hash_password_uses_random_salt() {
+        let a = hash_password("same".to_string(), fast_params()).unwrap();
+        let b = hash_password("same".to_string(), fast_params()).unwrap();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_hash_password_invalid_params() {
+        let params = Argon2Params {
+            memory_kib: 1,
+            iterations: 1,
+            parallelism: 1,
+        };
+        match hash_password("x".to_string(), Some(params)) {
+            Err(PasswordError::InvalidParams(_)) => (),
+            _ => panic!("Expected InvalidParams error"),
+        }
+    }
+
+    #[test]
+    fn test_verify_password_invalid_hash() {
+        match verify_password("x".to_string(), "not-a-phc-string".to_string()) {
+            Err(PasswordError::InvalidHash(_)) => (),
+            _ => panic!("Expected InvalidHash error"),
+        }
+    }
+
+    #[test]
+    fn test_bcrypt_hash_and_verify() {
+        let hash = bcrypt_hash("legacy-pass".to_string(), 4).unwrap();
+        assert!(hash.starts_with("$2b$04$"));
+        assert!(bcrypt_verify("legacy-pass".to_string(), hash.clone()).unwrap());
+        assert!(!bcrypt_verify("wrong".to_string(), hash).unwrap());
+    }
+
+    #[test]
+    fn test_bcrypt_verify_known_hash() {
+        // OpenBSDのbcryptで生成された$2a$形式のハッシュ
+        let hash = "$2a$05$CCCCCCCCCCCCCCCCCCCCC.E5YPO9kmyuRGyh0XouQYb4YMJKvyOeW";
+        assert!(bcrypt_verify("U*U".to_string(), hash.to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_bcrypt_invalid_cost() {
+        match bcrypt_hash("x".to_string(), 3) {
+            Err(PasswordError::InvalidParams(_)) => (),
+            _ => panic!("Expected InvalidParams error"),
+        }
+    }
+
+    #[test]
+    fn test_bcrypt_invalid_hash() {
+        match bcrypt_verify("x".to_string(), "$2b$bogus".to_string()) {
+            Err(PasswordError::InvalidHash(_)) => (),
+            other => panic!("Expected InvalidHash error, got {:?}", other),
+        }
+    }
+}
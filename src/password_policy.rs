@@ -0,0 +1,249 @@
+//! パスワードポリシーモジュール
+//!
+//! このモジュールは、最小文字数・文字種・禁止語・同じ文字の連続などの規則を
+//! `PasswordPolicy`で表し、パスワードが違反している規則を返す`check_password`を
+//! エクスポートします。ポリシーはサーバーから配信した値をそのまま渡せるため、
+//! 規則の変更をアプリのリリースなしに反映できます。
+
+use crate::password_strength::unleet;
+
+/// パスワードポリシー
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct PasswordPolicy {
+    /// 最小文字数（Unicodeのスカラー値の数）
+    #[uniffi(default = 8)]
+    pub min_length: u32,
+    /// 最大文字数（`None`の場合は制限なし）
+    #[uniffi(default = None)]
+    pub max_length: Option<u32>,
+    /// 英小文字などの小文字を必須にするかどうか
+    #[uniffi(default = false)]
+    pub require_lowercase: bool,
+    /// 英大文字などの大文字を必須にするかどうか
+    #[uniffi(default = false)]
+    pub require_uppercase: bool,
+    /// 数字を必須にするかどうか
+    #[uniffi(default = false)]
+    pub require_digit: bool,
+    /// 記号（英数字以外の文字）を必須にするかどうか
+    #[uniffi(default = false)]
+    pub require_symbol: bool,
+    /// 小文字・大文字・数字・記号のうち、含む必要がある種類の数（0〜4）
+    #[uniffi(default = 0)]
+    pub min_character_classes: u32,
+    /// 含めてはいけない語（大文字・小文字とleet表記の違いは無視します）
+    #[uniffi(default = [])]
+    pub banned_words: Vec<String>,
+    /// 同じ文字を連続して使える最大数（`None`の場合は制限なし）
+    #[uniffi(default = None)]
+    pub max_repeated_characters: Option<u32>,
+}
+
+impl Default for PasswordPolicy {
+    fn default() -> Self {
+        Self {
+            min_length: 8,
+            max_length: None,
+            require_lowercase: false,
+            require_uppercase: false,
+            require_digit: false,
+            require_symbol: false,
+            min_character_classes: 0,
+            banned_words: Vec::new(),
+            max_repeated_characters: None,
+        }
+    }
+}
+
+/// パスワードポリシーへの違反
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum PolicyViolation {
+    /// 最小文字数に満たない場合
+    TooShort { length: u32, min_length: u32 },
+    /// 最大文字数を超える場合
+    TooLong { length: u32, max_length: u32 },
+    /// 小文字を含まない場合
+    MissingLowercase,
+    /// 大文字を含まない場合
+    MissingUppercase,
+    /// 数字を含まない場合
+    MissingDigit,
+    /// 記号を含まない場合
+    MissingSymbol,
+    /// 文字種の数が足りない場合
+    TooFewCharacterClasses { found: u32, required: u32 },
+    /// 禁止語を含む場合（`word`はポリシーに指定された表記）
+    ContainsBannedWord { word: String },
+    /// 同じ文字が連続する数が多すぎる場合
+    TooManyRepeatedCharacters { character: String, count: u32, max_repeats: u32 },
+}
+
+/// 同じ文字が最も長く連続する箇所の文字と長さを返します
+fn longest_run(chars: &[char]) -> Option<(char, usize)> {
+    let mut longest: Option<(char, usize)> = None;
+    let mut start = 0;
+    while start < chars.len() {
+        let length = chars[start..].iter().take_while(|c| **c == chars[start]).count();
+        if longest.is_none_or(|(_, best)| length > best) {
+            longest = Some((chars[start], length));
+        }
+        start += length;
+    }
+    longest
+}
+
+/// パスワードがポリシーに違反している規則を返します
+///
+/// 違反がない場合は空の配列を返します。違反はポリシーのフィールドの順に並びます。
+///
+/// # Arguments
+/// * `password` - 検査するパスワード
+/// * `policy` - パスワードポリシー
+///
+/// # Example
+/// ```
+/// let policy = PasswordPolicy {
+///     min_length: 10,
+///     require_digit: true,
+///     banned_words: vec!["acme".to_string()],
+///     ..Default::default()
+/// };
+/// let violations = check_password("Acme2024".to_string(), policy);
+/// // [TooShort { length: 8, min_length: 10 }, ContainsBannedWord { word: "acme" }]
+/// ```
+#[uniffi::export]
+pub fn check_password(password: String, policy: PasswordPolicy) -> Vec<PolicyViolation> {
+    let chars: Vec<char> = password.chars().collect();
+    let length = chars.len() as u32;
+    let mut violations = Vec::new();
+    if length < policy.min_length {
+        violations.push(PolicyViolation::TooShort { length, min_length: policy.min_length });
+    }
+    if let Some(max_length) = policy.max_length.filter(|max_length| length > *max_length) {
+        violations.push(PolicyViolation::TooLong { length, max_length });
+    }
+
+    let classes = [
+        (
+            chars.iter().any(|c| c.is_lowercase()),
+            policy.require_lowercase,
+            PolicyViolation::MissingLowercase,
+        ),
+        (
+            chars.iter().any(|c| c.is_uppercase()),
+            policy.require_uppercase,
+            PolicyViolation::MissingUppercase,
+        ),
+        (chars.iter().any(|c| c.is_numeric()), policy.require_digit, PolicyViolation::MissingDigit),
+        (
+            chars.iter().any(|c| !c.is_alphanumeric()),
+            policy.require_symbol,
+            PolicyViolation::MissingSymbol,
+        ),
+    ];
+    let found = classes.iter().filter(|(present, _, _)| *present).count() as u32;
+    for (present, required, violation) in classes {
+        if required && !present {
+            violations.push(violation);
+        }
+    }
+    let required = policy.min_character_classes.min(4);
+    if found < required {
+        violations.push(PolicyViolation::TooFewCharacterClasses { found, required });
+    }
+
+    let lower = password.to_lowercase();
+    let unleeted: String = lower.chars().map(unleet).collect();
+    for word in &policy.banned_words {
+        let needle = word.trim().to_lowercase();
+        if !needle.is_empty() && (lower.contains(&needle) || unleeted.contains(&needle)) {
+            violations.push(PolicyViolation::ContainsBannedWord { word: word.trim().to_string() });
+        }
+    }
+
+    if let (Some(max_repeats), Some((character, count))) =
+        (policy.max_repeated_characters, longest_run(&chars))
+    {
+        if count as u32 > max_repeats {
+            violations.push(PolicyViolation::TooManyRepeatedCharacters {
+                character: character.to_string(),
+                count: count as u32,
+                max_repeats,
+            });
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_password_valid() {
+        let policy = PasswordPolicy {
+            min_length: 12,
+            max_length: Some(64),
+            require_lowercase: true,
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            min_character_classes: 4,
+            banned_words: vec!["acme".to_string()],
+            max_repeated_characters: Some(2),
+        };
+        assert!(check_password("Correct-Horse-42".to_string(), policy).is_empty());
+        assert!(check_password("パスワードは十分に長い".to_string(), PasswordPolicy::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_check_password_violations() {
+        let policy = PasswordPolicy {
+            min_length: 10,
+            max_length: Some(12),
+            require_uppercase: true,
+            require_symbol: true,
+            min_character_classes: 3,
+            ..Default::default()
+        };
+        assert_eq!(
+            check_password("abc123".to_string(), policy.clone()),
+            vec![
+                PolicyViolation::TooShort { length: 6, min_length: 10 },
+                PolicyViolation::MissingUppercase,
+                PolicyViolation::MissingSymbol,
+                PolicyViolation::TooFewCharacterClasses { found: 2, required: 3 },
+            ]
+        );
+        assert_eq!(
+            check_password("Abcdefgh!12345".to_string(), policy),
+            vec![PolicyViolation::TooLong { length: 14, max_length: 12 }]
+        );
+    }
+
+    #[test]
+    fn test_check_password_banned_words_and_repeats() {
+        let policy = PasswordPolicy {
+            banned_words: vec![" Acme ".to_string(), "password".to_string(), String::new()],
+            max_repeated_characters: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_password("my-ACME-p@ssw0rd".to_string(), policy.clone()),
+            vec![
+                PolicyViolation::ContainsBannedWord { word: "Acme".to_string() },
+                PolicyViolation::ContainsBannedWord { word: "password".to_string() },
+            ]
+        );
+        assert_eq!(
+            check_password("zzzz-abcd-1111".to_string(), policy.clone()),
+            vec![PolicyViolation::TooManyRepeatedCharacters {
+                character: "z".to_string(),
+                count: 4,
+                max_repeats: 3,
+            }]
+        );
+        assert!(check_password("zzz-abcd-111".to_string(), policy).is_empty());
+    }
+}
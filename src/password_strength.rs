@@ -0,0 +1,382 @@
+//! パスワード強度推定モジュール
+//!
+//! このモジュールは、パスワードを推測に必要な試行回数から評価する
+//! `estimate_password_strength`をエクスポートします。
+//! よく使われるパスワード・利用者の個人情報・繰り返し・連続した文字列を
+//! パターンとして検出し、総当たりよりも少ない試行回数で見積もります。
+//! アプリのサインアップ画面とバックエンドで同じ評価結果を得るために使用します。
+
+/// オフライン攻撃（低速なハッシュ）で想定する1秒あたりの試行回数
+const GUESSES_PER_SECOND: f64 = 1.0e4;
+
+/// スコアの閾値（log10(試行回数)）。zxcvbnと同じ区切りを使用します
+const SCORE_THRESHOLDS: [f64; 4] = [3.0, 6.0, 8.0, 10.0];
+
+/// 個人情報として扱う入力の最小文字数
+const MIN_USER_INPUT_LENGTH: usize = 3;
+
+/// 連続した文字列として扱う最小文字数
+const MIN_SEQUENCE_LENGTH: usize = 3;
+
+/// 推奨する最小文字数（これ未満の場合は長くするよう提案します）
+const RECOMMENDED_LENGTH: usize = 12;
+
+/// よく使われるパスワード（出現頻度順）
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456", "password", "123456789", "12345678", "12345", "qwerty", "1234567", "111111",
+    "123123", "abc123", "1234567890", "password1", "iloveyou", "000000", "qwerty123",
+    "1q2w3e4r", "admin", "letmein", "welcome", "monkey", "dragon", "football", "baseball",
+    "sunshine", "princess", "master", "shadow", "superman", "michael", "trustno1", "login",
+    "passw0rd", "starwars", "hello", "freedom", "whatever", "qazwsx", "ninja", "azerty",
+    "secret", "charlie", "jordan", "hunter", "killer", "soccer", "batman", "flower",
+];
+
+/// 連続した文字列として検出する並び（キーボード配列を含む）
+const SEQUENCES: &[&str] = &[
+    "abcdefghijklmnopqrstuvwxyz",
+    "01234567890",
+    "qwertyuiop",
+    "asdfghjkl",
+    "zxcvbnm",
+];
+
+/// パスワード強度の評価結果
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct StrengthResult {
+    /// 0（非常に弱い）〜4（非常に強い）のスコア
+    pub score: u8,
+    /// 推測に必要な試行回数の推定値
+    pub guesses: f64,
+    /// 試行回数の常用対数
+    pub guesses_log10: f64,
+    /// オフライン攻撃（低速なハッシュ）で解読されるまでの時間の目安（例: "3 hours"）
+    pub crack_time_display: String,
+    /// 最も大きな弱点についての警告
+    pub warning: Option<String>,
+    /// パスワードを強くするための提案
+    pub suggestions: Vec<String>,
+}
+
+/// 検出したパターンの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pattern {
+    Common,
+    UserInput,
+    Repeat,
+    Sequence,
+}
+
+/// パスワードの文字種から総当たりの文字集合の大きさを求めます
+fn bruteforce_cardinality(chars: &[char]) -> f64 {
+    let mut cardinality = 0.0;
+    if chars.iter().any(|c| c.is_ascii_lowercase()) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_uppercase()) {
+        cardinality += 26.0;
+    }
+    if chars.iter().any(|c| c.is_ascii_digit()) {
+        cardinality += 10.0;
+    }
+    if chars.iter().any(|c| c.is_ascii() && !c.is_ascii_alphanumeric()) {
+        cardinality += 33.0;
+    }
+    if chars.iter().any(|c| !c.is_ascii()) {
+        cardinality += 100.0;
+    }
+    cardinality
+}
+
+/// leet表記（`p@ssw0rd`など）を通常の英字に戻します
+pub(crate) fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        c => c,
+    }
+}
+
+/// `chars[start..]`の先頭に一致する最長の単語を探し、(文字数, 順位)を返します
+fn longest_word_match(chars: &[char], start: usize, words: &[String]) -> Option<(usize, usize)> {
+    words
+        .iter()
+        .enumerate()
+        .filter_map(|(rank, word)| {
+            let len = word.chars().count();
+            let candidate = chars.get(start..start + len)?;
+            word.chars().eq(candidate.iter().copied()).then_some((len, rank))
+        })
+        .max_by_key(|(len, _)| *len)
+}
+
+/// `chars[start..]`の先頭から同じ文字が続く長さを返します
+fn repeat_length(chars: &[char], start: usize) -> usize {
+    chars[start..]
+        .iter()
+        .take_while(|c| **c == chars[start])
+        .count()
+}
+
+/// `chars[start..]`の先頭から既知の並び（順方向・逆方向）が続く長さを返します
+fn sequence_length(chars: &[char], start: usize) -> usize {
+    let mut best = 0;
+    for sequence in SEQUENCES {
+        let forward: Vec<char> = sequence.chars().collect();
+        let backward: Vec<char> = sequence.chars().rev().collect();
+        for seq in [forward, backward] {
+            let offsets = seq.iter().enumerate().filter(|(_, c)| **c == chars[start]);
+            for (offset, _) in offsets {
+                let len = seq[offset..]
+                    .iter()
+                    .zip(&chars[start..])
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                best = best.max(len);
+            }
+        }
+    }
+    best
+}
+
+/// 解読時間（秒）を読みやすい文字列に変換します
+fn display_time(seconds: f64) -> String {
+    const MINUTE: f64 = 60.0;
+    const HOUR: f64 = MINUTE * 60.0;
+    const DAY: f64 = HOUR * 24.0;
+    const MONTH: f64 = DAY * 31.0;
+    const YEAR: f64 = MONTH * 12.0;
+    const CENTURY: f64 = YEAR * 100.0;
+
+    let (value, unit) = if seconds < 1.0 {
+        return "less than a second".to_string();
+    } else if seconds < MINUTE {
+        (seconds, "second")
+    } else if seconds < HOUR {
+        (seconds / MINUTE, "minute")
+    } else if seconds < DAY {
+        (seconds / HOUR, "hour")
+    } else if seconds < MONTH {
+        (seconds / DAY, "day")
+    } else if seconds < YEAR {
+        (seconds / MONTH, "month")
+    } else if seconds < CENTURY {
+        (seconds / YEAR, "year")
+    } else {
+        return "centuries".to_string();
+    };
+    let value = value.round() as u64;
+    if value == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", value, unit)
+    }
+}
+
+/// パスワードの強度を推定します
+///
+/// パスワードを先頭から順に、よく使われるパスワード・個人情報・同じ文字の繰り返し・
+/// 連続した文字列（`abc`、`654`、`qwerty`など）のパターンに分解し、
+/// どのパターンにも当てはまらない文字は総当たりとして試行回数を見積もります。
+/// 評価は決定的なため、アプリとバックエンドで同じ結果になります。
+///
+/// # Arguments
+/// * `password` - 評価するパスワード
+/// * `user_inputs` - 名前・メールアドレスなど、パスワードに含めるべきでない利用者の情報
+///
+/// # Example
+/// ```
+/// let result = estimate_password_strength("alice123".to_string(), vec!["alice".to_string()]);
+/// assert!(result.score <= 1);
+/// assert!(result.warning.is_some());
+/// ```
+#[uniffi::export(default(user_inputs = []))]
+pub fn estimate_password_strength(password: String, user_inputs: Vec<String>) -> StrengthResult {
+    let chars: Vec<char> = password.chars().collect();
+    let lower: Vec<char> = chars.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    let unleeted: Vec<char> = lower.iter().map(|c| unleet(*c)).collect();
+    let cardinality = bruteforce_cardinality(&chars);
+
+    let common: Vec<String> = COMMON_PASSWORDS.iter().map(|w| w.to_string()).collect();
+    let inputs: Vec<String> = user_inputs
+        .iter()
+        .flat_map(|input| {
+            // メールアドレスはローカル部も個別に扱う
+            let input = input.trim().to_lowercase();
+            let local = input.split_once('@').map(|(local, _)| local.to_string());
+            std::iter::once(input).chain(local)
+        })
+        .filter(|input| input.chars().count() >= MIN_USER_INPUT_LENGTH)
+        .collect();
+
+    let mut guesses_log10 = 0.0;
+    let mut patterns = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let has_upper = |len: usize| chars[i..i + len].iter().any(|c| c.is_uppercase());
+        let user_match = longest_word_match(&lower, i, &inputs);
+        let common_match = longest_word_match(&lower, i, &common)
+            .map(|(len, rank)| (len, rank, false))
+            .or_else(|| {
+                longest_word_match(&unleeted, i, &common).map(|(len, rank)| (len, rank, true))
+            })
+            .filter(|(len, _, _)| *len >= MIN_SEQUENCE_LENGTH);
+        let repeat = repeat_length(&chars, i);
+        let sequence = sequence_length(&lower, i);
+
+        let (len, log10, pattern) = if let Some((len, _)) = user_match {
+            let variations = if has_upper(len) { 2.0 } else { 1.0 };
+            (len, (inputs.len() as f64 * variations).log10(), Some(Pattern::UserInput))
+        } else if let Some((len, rank, leet)) = common_match {
+            let mut variations = if has_upper(len) { 2.0 } else { 1.0 };
+            if leet {
+                variations *= 2.0;
+            }
+            (len, ((rank + 1) as f64 * variations).log10(), Some(Pattern::Common))
+        } else if repeat >= MIN_SEQUENCE_LENGTH {
+            (repeat, (cardinality * repeat as f64).log10(), Some(Pattern::Repeat))
+        } else if sequence >= MIN_SEQUENCE_LENGTH {
+            // 開始文字（約26通り）・方向（2通り）・長さで見積もる
+            (sequence, (26.0 * 2.0 * sequence as f64).log10(), Some(Pattern::Sequence))
+        } else {
+            (1, cardinality.log10(), None)
+        };
+        guesses_log10 += log10;
+        if let Some(pattern) = pattern {
+            patterns.push(pattern);
+        }
+        i += len;
+    }
+
+    let guesses = 10f64.powf(guesses_log10).max(1.0);
+    let guesses_log10 = guesses.log10();
+    let score = SCORE_THRESHOLDS
+        .iter()
+        .filter(|threshold| guesses_log10 >= **threshold)
+        .count() as u8;
+    let crack_time_display = display_time(guesses / GUESSES_PER_SECOND);
+
+    let is_whole_common = patterns == [Pattern::Common];
+    let warning = if chars.is_empty() {
+        None
+    } else if is_whole_common {
+        Some("This is a very common password".to_string())
+    } else if patterns.contains(&Pattern::UserInput) {
+        Some("Passwords containing your personal information are easy to guess".to_string())
+    } else if patterns.contains(&Pattern::Common) {
+        Some("Passwords based on common passwords are easy to guess".to_string())
+    } else if patterns.contains(&Pattern::Repeat) {
+        Some("Repeated characters like \"aaa\" are easy to guess".to_string())
+    } else if patterns.contains(&Pattern::Sequence) {
+        Some("Sequences like \"abc\" or \"6543\" are easy to guess".to_string())
+    } else {
+        None
+    };
+
+    let mut suggestions = Vec::new();
+    if score < 3 {
+        suggestions.push("Add another word or two. Uncommon words are better".to_string());
+    }
+    if chars.len() < RECOMMENDED_LENGTH {
+        suggestions.push(format!("Use at least {} characters", RECOMMENDED_LENGTH));
+    }
+    if patterns.contains(&Pattern::UserInput) {
+        suggestions
+            .push("Avoid your name, email address or other personal information".to_string());
+    }
+    if patterns.contains(&Pattern::Repeat) {
+        suggestions.push("Avoid repeated characters".to_string());
+    }
+    if patterns.contains(&Pattern::Sequence) {
+        suggestions.push("Avoid sequences and keyboard patterns".to_string());
+    }
+    if score < 3 && cardinality <= 26.0 {
+        suggestions.push("Mix uppercase letters, digits and symbols".to_string());
+    }
+
+    StrengthResult {
+        score,
+        guesses,
+        guesses_log10,
+        crack_time_display,
+        warning,
+        suggestions,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_password_is_weak() {
+        let result = estimate_password_strength("password".to_string(), Vec::new());
+        assert_eq!(result.score, 0);
+        assert_eq!(result.guesses, 2.0);
+        assert_eq!(result.warning.as_deref(), Some("This is a very common password"));
+        assert_eq!(result.crack_time_display, "less than a second");
+    }
+
+    #[test]
+    fn test_leet_common_password_is_weak() {
+        let result = estimate_password_strength("P@ssw0rd".to_string(), Vec::new());
+        assert_eq!(result.score, 0);
+        assert!(result.warning.is_some());
+    }
+
+    #[test]
+    fn test_user_input_is_detected() {
+        let result = estimate_password_strength(
+            "alice123".to_string(),
+            vec!["alice@example.com".to_string()],
+        );
+        assert!(result.score <= 1);
+        assert_eq!(
+            result.warning.as_deref(),
+            Some("Passwords containing your personal information are easy to guess")
+        );
+    }
+
+    #[test]
+    fn test_repeats_and_sequences_are_weak() {
+        let repeat = estimate_password_strength("aaaaaaaaaa".to_string(), Vec::new());
+        assert_eq!(repeat.score, 0);
+        assert!(repeat.suggestions.contains(&"Avoid repeated characters".to_string()));
+
+        let sequence = estimate_password_strength("abcdef987654".to_string(), Vec::new());
+        assert!(sequence.score <= 1);
+        assert!(sequence.warning.unwrap().starts_with("Sequences"));
+    }
+
+    #[test]
+    fn test_long_random_password_is_strong() {
+        let result =
+            estimate_password_strength("correct horse battery staple".to_string(), Vec::new());
+        assert_eq!(result.score, 4);
+        assert_eq!(result.crack_time_display, "centuries");
+        assert!(result.warning.is_none());
+        assert!(result.suggestions.is_empty());
+
+        let mixed = estimate_password_strength("vT9#qLm2!xR".to_string(), Vec::new());
+        assert_eq!(mixed.score, 4);
+    }
+
+    #[test]
+    fn test_empty_password() {
+        let result = estimate_password_strength(String::new(), Vec::new());
+        assert_eq!(result.score, 0);
+        assert_eq!(result.guesses, 1.0);
+        assert!(result.warning.is_none());
+    }
+
+    #[test]
+    fn test_display_time() {
+        assert_eq!(display_time(0.5), "less than a second");
+        assert_eq!(display_time(1.0), "1 second");
+        assert_eq!(display_time(7200.0), "2 hours");
+        assert_eq!(display_time(1.0e12), "centuries");
+    }
+}
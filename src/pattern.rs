@@ -0,0 +1,358 @@
+//! 正規表現モジュール
+//!
+//! このモジュールは、Rustの`regex`クレートの構文で正規表現をコンパイルし、一致の判定・
+//! 検索・置換・名前付きグループの取り出しを行う`Regex`オブジェクトをエクスポートします。
+//! NSRegularExpression（ICU）とバックエンドの正規表現は構文と動作が異なるため、
+//! バックエンドと同じパターンをそのまま使うために使用します。
+//!
+//! 後方参照と先読み・後読みには対応しませんが、入力の長さに対して線形時間で
+//! 照合するため、信頼できないパターンや入力でも処理時間が爆発しません。
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use regex::{Captures, RegexBuilder};
+use thiserror::Error;
+
+/// コンパイル後のプログラムの最大サイズ（バイト）
+const SIZE_LIMIT: usize = 10 * 1024 * 1024;
+
+/// 正規表現の処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum RegexError {
+    /// パターンの構文が正しくない、またはコンパイル後のサイズが上限を超える場合
+    #[error("Invalid pattern: {0}")]
+    InvalidPattern(String),
+}
+
+/// 正規表現のオプション
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct RegexOptions {
+    /// 大文字・小文字を区別しない（`(?i)`）
+    #[uniffi(default = false)]
+    pub case_insensitive: bool,
+    /// `^`と`$`を各行の先頭と末尾に一致させる（`(?m)`）
+    #[uniffi(default = false)]
+    pub multi_line: bool,
+    /// `.`を改行にも一致させる（`(?s)`）
+    #[uniffi(default = false)]
+    pub dot_matches_new_line: bool,
+    /// パターン中の空白と`#`以降のコメントを無視する（`(?x)`）
+    #[uniffi(default = false)]
+    pub ignore_whitespace: bool,
+}
+
+/// 入力文字列の中の範囲
+///
+/// バイト位置はUTF-8での位置、文字位置はUnicodeのスカラー値（Swiftの`unicodeScalars`）の
+/// 数で数えた位置です。いずれも終了位置を含みません。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Record)]
+pub struct TextRange {
+    /// 開始位置（バイト）
+    pub byte_start: u64,
+    /// 終了位置（バイト）
+    pub byte_end: u64,
+    /// 開始位置（文字）
+    pub char_start: u64,
+    /// 終了位置（文字）
+    pub char_end: u64,
+}
+
+/// キャプチャグループ
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct RegexGroup {
+    /// グループの番号（1から始まります）
+    pub index: u32,
+    /// 名前付きグループの名前
+    pub name: Option<String>,
+    /// 一致した文字列（グループが一致に参加しなかった場合は`None`）
+    pub text: Option<String>,
+    /// 一致した範囲（グループが一致に参加しなかった場合は`None`）
+    pub range: Option<TextRange>,
+}
+
+/// 一致
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct RegexMatch {
+    /// 一致した文字列
+    pub text: String,
+    /// 一致した範囲
+    pub range: TextRange,
+    /// キャプチャグループ（番号順、一致全体は含みません）
+    pub groups: Vec<RegexGroup>,
+}
+
+/// バイト位置を文字位置に変換します
+///
+/// 前回の位置から先へ数え進めるため、昇順に問い合わせると全体で線形時間になります。
+struct CharCounter<'a> {
+    text: &'a str,
+    byte: usize,
+    chars: usize,
+}
+
+impl<'a> CharCounter<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, byte: 0, chars: 0 }
+    }
+
+    /// バイト位置`byte`までの文字数を返します
+    fn chars_before(&mut self, byte: usize) -> usize {
+        if byte < self.byte {
+            return self.text[..byte].chars().count();
+        }
+        self.chars += self.text[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.chars
+    }
+
+    /// バイト範囲を`TextRange`に変換します
+    fn range(&mut self, start: usize, end: usize) -> TextRange {
+        let char_start = self.chars_before(start);
+        let char_end = char_start + self.text[start..end].chars().count();
+        TextRange {
+            byte_start: start as u64,
+            byte_end: end as u64,
+            char_start: char_start as u64,
+            char_end: char_end as u64,
+        }
+    }
+}
+
+/// コンパイル済みの正規表現
+///
+/// パターンはRustの`regex`クレートの構文（名前付きグループは`(?P<name>...)`または
+/// `(?<name>...)`）です。一度コンパイルしたオブジェクトは複数のスレッドから
+/// 同時に使用できます。
+///
+/// # Example
+/// ```
+/// let regex = Regex::new(r"(?<user>[\w.]+)@(?<domain>[\w.]+)".to_string(), None)?;
+/// let groups = regex.named_groups("contact: alice@example.com".to_string());
+/// assert_eq!(groups.unwrap()["domain"], "example.com");
+/// ```
+#[derive(uniffi::Object)]
+pub struct Regex {
+    regex: regex::Regex,
+}
+
+impl Regex {
+    /// 一致をレコードに変換します
+    fn to_match(&self, captures: &Captures, counter: &mut CharCounter) -> RegexMatch {
+        let whole = captures.get(0).expect("capture group 0 always participates in a match");
+        let range = counter.range(whole.start(), whole.end());
+        let groups = self
+            .regex
+            .capture_names()
+            .enumerate()
+            .skip(1)
+            .map(|(index, name)| {
+                let group = captures.get(index);
+                RegexGroup {
+                    index: index as u32,
+                    name: name.map(str::to_string),
+                    text: group.map(|group| group.as_str().to_string()),
+                    // グループは一致の内側にあるため、一致の開始位置から数える
+                    range: group.map(|group| {
+                        let mut inner = CharCounter {
+                            text: counter.text,
+                            byte: whole.start(),
+                            chars: range.char_start as usize,
+                        };
+                        inner.range(group.start(), group.end())
+                    }),
+                }
+            })
+            .collect();
+        RegexMatch { text: whole.as_str().to_string(), range, groups }
+    }
+}
+
+#[uniffi::export]
+impl Regex {
+    /// パターンをコンパイルします
+    ///
+    /// # Arguments
+    /// * `pattern` - 正規表現のパターン
+    /// * `options` - オプション（省略時はすべて無効）
+    ///
+    /// # Errors
+    /// * `RegexError::InvalidPattern` - 構文が正しくない、またはコンパイル後のサイズが大きすぎる場合
+    #[uniffi::constructor(default(options = None))]
+    pub fn new(pattern: String, options: Option<RegexOptions>) -> Result<Arc<Self>, RegexError> {
+        let options = options.unwrap_or_default();
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.case_insensitive)
+            .multi_line(options.multi_line)
+            .dot_matches_new_line(options.dot_matches_new_line)
+            .ignore_whitespace(options.ignore_whitespace)
+            .size_limit(SIZE_LIMIT)
+            .build()
+            .map_err(|e| RegexError::InvalidPattern(e.to_string()))?;
+        Ok(Arc::new(Self { regex }))
+    }
+
+    /// コンパイルしたパターンを返します
+    pub fn pattern(&self) -> String {
+        self.regex.as_str().to_string()
+    }
+
+    /// 文字列のどこかに一致する部分があるかどうかを返します
+    ///
+    /// 文字列全体との一致を判定する場合は、パターンを`^...$`で囲みます。
+    pub fn is_match(&self, text: String) -> bool {
+        self.regex.is_match(&text)
+    }
+
+    /// 最初の一致を返します（一致しない場合は`None`）
+    pub fn find(&self, text: String) -> Option<RegexMatch> {
+        let captures = self.regex.captures(&text)?;
+        Some(self.to_match(&captures, &mut CharCounter::new(&text)))
+    }
+
+    /// 重ならないすべての一致を先頭から順に返します
+    ///
+    /// # Arguments
+    /// * `text` - 検索する文字列
+    ///
+    /// # Example
+    /// ```
+    /// let regex = Regex::new(r"\d+".to_string(), None)?;
+    /// let matches = regex.find_all("価格: 120円、80円".to_string());
+    /// assert_eq!(matches[1].range.char_start, 9);
+    /// assert_eq!(matches[1].range.byte_start, 17);
+    /// ```
+    pub fn find_all(&self, text: String) -> Vec<RegexMatch> {
+        let mut counter = CharCounter::new(&text);
+        self.regex
+            .captures_iter(&text)
+            .map(|captures| self.to_match(&captures, &mut counter))
+            .collect()
+    }
+
+    /// 最初の一致の名前付きグループを名前と文字列の組で返します
+    ///
+    /// 一致に参加しなかったグループは含みません。一致しない場合は`None`を返します。
+    pub fn named_groups(&self, text: String) -> Option<HashMap<String, String>> {
+        let captures = self.regex.captures(&text)?;
+        Some(
+            self.regex
+                .capture_names()
+                .flatten()
+                .filter_map(|name| {
+                    Some((name.to_string(), captures.name(name)?.as_str().to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    /// すべての一致を置換した文字列を返します
+    ///
+    /// 置換文字列の`$1`・`${name}`はグループの文字列に置き換わります（`$$`は`$`）。
+    /// グループ名の直後に英数字が続く場合は`${1}x`のように`{}`で囲みます。
+    ///
+    /// # Arguments
+    /// * `text` - 対象の文字列
+    /// * `replacement` - 置換文字列
+    ///
+    /// # Example
+    /// ```
+    /// let regex = Regex::new(r"(?<y>\d{4})-(?<m>\d{2})-(?<d>\d{2})".to_string(), None)?;
+    /// let text = regex.replace_all("2024-03-15".to_string(), "${d}/${m}/${y}".to_string());
+    /// assert_eq!(text, "15/03/2024");
+    /// ```
+    pub fn replace_all(&self, text: String, replacement: String) -> String {
+        self.regex.replace_all(&text, replacement.as_str()).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(byte_start: u64, byte_end: u64, char_start: u64, char_end: u64) -> TextRange {
+        TextRange { byte_start, byte_end, char_start, char_end }
+    }
+
+    #[test]
+    fn test_regex_is_match_and_options() {
+        let regex = Regex::new(r"^hello$".to_string(), None).unwrap();
+        assert!(regex.is_match("hello".to_string()));
+        assert!(!regex.is_match("Hello".to_string()));
+        assert!(!regex.is_match("say\nhello".to_string()));
+        assert_eq!(regex.pattern(), "^hello$");
+
+        let options =
+            RegexOptions { case_insensitive: true, multi_line: true, ..Default::default() };
+        let regex = Regex::new(r"^hello$".to_string(), Some(options)).unwrap();
+        assert!(regex.is_match("say\nHELLO".to_string()));
+
+        let options = RegexOptions { ignore_whitespace: true, ..Default::default() };
+        let regex = Regex::new("a b # comment\n c".to_string(), Some(options)).unwrap();
+        assert!(regex.is_match("abc".to_string()));
+
+        let options = RegexOptions { dot_matches_new_line: true, ..Default::default() };
+        let regex = Regex::new("a.b".to_string(), Some(options)).unwrap();
+        assert!(regex.is_match("a\nb".to_string()));
+    }
+
+    #[test]
+    fn test_regex_find_all_ranges() {
+        let regex = Regex::new(r"\d+".to_string(), None).unwrap();
+        let matches = regex.find_all("価格: 120円、80円".to_string());
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].text, "120");
+        assert_eq!(matches[0].range, range(8, 11, 4, 7));
+        assert_eq!(matches[1].text, "80");
+        assert_eq!(matches[1].range, range(17, 19, 9, 11));
+        assert!(regex.find_all("なし".to_string()).is_empty());
+        assert_eq!(regex.find("x9".to_string()).unwrap().range, range(1, 2, 1, 2));
+    }
+
+    #[test]
+    fn test_regex_groups() {
+        let regex =
+            Regex::new(r"(?<user>\w+)@(?<domain>[\w.]+)(:(\d+))?".to_string(), None).unwrap();
+        let found = regex.find("連絡先 alice@example.com".to_string()).unwrap();
+        assert_eq!(found.range, range(10, 27, 4, 21));
+        assert_eq!(found.groups.len(), 4);
+        assert_eq!(found.groups[0].index, 1);
+        assert_eq!(found.groups[0].name.as_deref(), Some("user"));
+        assert_eq!(found.groups[0].text.as_deref(), Some("alice"));
+        assert_eq!(found.groups[0].range, Some(range(10, 15, 4, 9)));
+        assert_eq!(found.groups[1].range, Some(range(16, 27, 10, 21)));
+        assert_eq!(found.groups[3].name, None);
+        assert_eq!(found.groups[3].text, None);
+        assert_eq!(found.groups[3].range, None);
+
+        let groups = regex.named_groups("bob@example.org".to_string()).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups["user"], "bob");
+        assert_eq!(groups["domain"], "example.org");
+        assert_eq!(regex.named_groups("no address".to_string()), None);
+    }
+
+    #[test]
+    fn test_regex_replace_all() {
+        let regex = Regex::new(r"(?<y>\d{4})-(?<m>\d{2})-(?<d>\d{2})".to_string(), None).unwrap();
+        assert_eq!(
+            regex.replace_all("2024-03-15 と 2025-01-02".to_string(), "${d}/${m}/${y}".to_string()),
+            "15/03/2024 と 02/01/2025"
+        );
+        let regex = Regex::new(r"\s+".to_string(), None).unwrap();
+        assert_eq!(regex.replace_all("a  b\t c".to_string(), "$$".to_string()), "a$b$c");
+    }
+
+    #[test]
+    fn test_regex_invalid_pattern() {
+        for pattern in ["(unclosed", r"(?<=look)behind", r"(a)\1", "a{5000}{5000}"] {
+            match Regex::new(pattern.to_string(), None) {
+                Err(RegexError::InvalidPattern(_)) => (),
+                other => {
+                    panic!("Expected InvalidPattern error for {}, got {:?}", pattern, other.err())
+                }
+            }
+        }
+    }
+}
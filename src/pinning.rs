@@ -0,0 +1,140 @@
+//! 証明書ピンニングモジュール
+//!
+//! このモジュールは、X.509証明書のSubjectPublicKeyInfo（SPKI）から
+//! ピン（SHA-256ハッシュのBase64）を計算し、証明書チェーンが
+//! 期待するピンのいずれかに一致するかを判定する関数をエクスポートします。
+//! URLSessionのデリゲートからピン評価を共通のRustコアに委ねる用途を想定しています。
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use x509_cert::der::{Decode, Encode};
+use x509_cert::Certificate;
+
+/// ピンに付与されることがあるプレフィックス（OkHttpなどの形式）
+const PIN_PREFIX: &str = "sha256/";
+
+/// 証明書ピンニングで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum PinningError {
+    /// 証明書のDERデコードに失敗した場合
+    #[error("Invalid certificate: {0}")]
+    InvalidCertificate(String),
+    /// 証明書チェーンが空の場合
+    #[error("Certificate chain is empty")]
+    EmptyChain,
+}
+
+/// 証明書からSPKIのSHA-256ハッシュを計算します
+fn spki_digest(cert_der: &[u8]) -> Result<[u8; 32], PinningError> {
+    let cert = Certificate::from_der(cert_der)
+        .map_err(|e| PinningError::InvalidCertificate(e.to_string()))?;
+    let spki = cert
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(|e| PinningError::InvalidCertificate(e.to_string()))?;
+    Ok(Sha256::digest(&spki).into())
+}
+
+/// ピン文字列をSHA-256ハッシュに戻します（不正なピンは`None`）
+fn decode_pin(pin: &str) -> Option<Vec<u8>> {
+    let pin = pin.trim();
+    let pin = pin.strip_prefix(PIN_PREFIX).unwrap_or(pin);
+    STANDARD.decode(pin).ok().filter(|digest| digest.len() == 32)
+}
+
+/// 証明書のSPKIピンを計算します
+///
+/// ピンはSubjectPublicKeyInfo（DER）のSHA-256ハッシュをBase64でエンコードしたもので、
+/// 公開鍵が同じであれば証明書を更新しても変わりません。
+///
+/// # Arguments
+/// * `cert_der` - DER形式のX.509証明書
+///
+/// # Errors
+/// * `PinningError::InvalidCertificate` - 証明書のデコードに失敗した場合
+///
+/// # Example
+/// ```
+/// let pin = compute_spki_pin(cert_der)?;
+/// // 例: "QWIpy/pzbvrWPUdJdbTmyjs+0IfEWT9hZGGyzOMW88Q="
+/// ```
+#[uniffi::export]
+pub fn compute_spki_pin(cert_der: Vec<u8>) -> Result<String, PinningError> {
+    Ok(STANDARD.encode(spki_digest(&cert_der)?))
+}
+
+/// 証明書チェーンのいずれかの証明書が期待するピンに一致するかを判定します
+///
+/// ピンは`compute_spki_pin`の形式のほか、`sha256/`プレフィックス付きの形式も
+/// 受け付けます。デコードできないピンは無視されます。
+/// チェーン内のすべての証明書を検査するため、リーフ・中間・ルートの
+/// どの証明書にピン留めしても構いません。
+///
+/// # Arguments
+/// * `cert_chain` - DER形式の証明書チェーン（リーフから順）
+/// * `pins` - 期待するピンの一覧（バックアップ用のピンを含めることを推奨）
+///
+/// # Errors
+/// * `PinningError::EmptyChain` - 証明書チェーンが空の場合
+/// * `PinningError::InvalidCertificate` - チェーン内の証明書のデコードに失敗した場合
+#[uniffi::export]
+pub fn match_pins(cert_chain: Vec<Vec<u8>>, pins: Vec<String>) -> Result<bool, PinningError> {
+    if cert_chain.is_empty() {
+        return Err(PinningError::EmptyChain);
+    }
+    let expected: Vec<Vec<u8>> = pins.iter().filter_map(|pin| decode_pin(pin)).collect();
+    let mut matched = false;
+    for cert in &cert_chain {
+        let digest = spki_digest(cert)?;
+        for pin in &expected {
+            matched |= bool::from(digest.ct_eq(pin.as_slice()));
+        }
+    }
+    Ok(matched)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// P-256鍵による自己署名証明書（CN=pin.example）
+    const TEST_CERT_BASE64: &str = "MIIBgzCCASmgAwIBAgIUMwlLbmZiYWAj1sciGoCCNW/ty1cwCgYIKoZIzj0EAwIwFjEUMBIGA1UEAwwLcGluLmV4YW1wbGUwIBcNMjYxMDE2MTQ1MDQwWhgPMjEyNjA5MjIxNDUwNDBaMBYxFDASBgNVBAMMC3Bpbi5leGFtcGxlMFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAEGZCQVSSbbjO4ZSGuOEAZkukf0yxIjAYP/EGwAHfTrdi3QL8ATXr7Ku4eeLaebsqVEdBerCVjElp8NUWdampdjqNTMFEwHQYDVR0OBBYEFLWP8MW8uQNpSc9MMM3IroZ2nvv6MB8GA1UdIwQYMBaAFLWP8MW8uQNpSc9MMM3IroZ2nvv6MA8GA1UdEwEB/wQFMAMBAf8wCgYIKoZIzj0EAwIDSAAwRQIgYWp+a7+25q80qF5dllN38JhClNJNXeibLQntw3yZunsCIQD43ZhSr3kVmzdbhNi4vm8JLPAlbk0DBdmYs4NHiJFH5g==";
+
+    /// `openssl x509 -pubkey | openssl pkey -pubin -outform der | openssl dgst -sha256 -binary | base64`
+    const TEST_CERT_PIN: &str = "QWIpy/pzbvrWPUdJdbTmyjs+0IfEWT9hZGGyzOMW88Q=";
+
+    fn test_cert() -> Vec<u8> {
+        STANDARD.decode(TEST_CERT_BASE64).unwrap()
+    }
+
+    #[test]
+    fn test_compute_spki_pin() {
+        assert_eq!(compute_spki_pin(test_cert()).unwrap(), TEST_CERT_PIN);
+    }
+
+    #[test]
+    fn test_match_pins() {
+        let other = STANDARD.encode([0u8; 32]);
+        assert!(match_pins(vec![test_cert()], vec![other.clone(), TEST_CERT_PIN.to_string()])
+            .unwrap());
+        assert!(match_pins(vec![test_cert()], vec![format!("sha256/{}", TEST_CERT_PIN)])
+            .unwrap());
+        assert!(!match_pins(vec![test_cert()], vec![other, "not base64".to_string()]).unwrap());
+    }
+
+    #[test]
+    fn test_match_pins_errors() {
+        match match_pins(Vec::new(), vec![TEST_CERT_PIN.to_string()]) {
+            Err(PinningError::EmptyChain) => (),
+            _ => panic!("Expected EmptyChain error"),
+        }
+        match compute_spki_pin(vec![0x30, 0x03, 0x02, 0x01]) {
+            Err(PinningError::InvalidCertificate(_)) => (),
+            _ => panic!("Expected InvalidCertificate error"),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Protocol Buffers動的デコードモジュール
+//!
+//! このモジュールは、コード生成なしでProtocol Buffersのメッセージを
+//! JSONに変換する関数をエクスポートします。
+//! ネットワークインスペクターでprotobufのボディを表示する用途を想定しています。
+//!
+//! メッセージの型情報は`FileDescriptorSet`（`protoc --descriptor_set_out`の出力）から
+//! 読み込みます。依存する`.proto`ファイルもすべて含める必要があるため、
+//! `protoc`には`--include_imports`を指定してください。
+//! JSONへの変換は[proto3のJSONマッピング](https://protobuf.dev/programming-guides/proto3/#json)に
+//! 従います（64ビット整数は文字列、`bytes`はBase64、既定値のフィールドは省略）。
+
+use prost_reflect::{DescriptorPool, DynamicMessage};
+use thiserror::Error;
+
+/// Protocol Buffersのデコードで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum ProtobufError {
+    /// `FileDescriptorSet`として不正なデータの場合
+    #[error("Invalid descriptor set: {0}")]
+    InvalidDescriptor(String),
+    /// 指定したメッセージ型が`FileDescriptorSet`に含まれていない場合
+    #[error("Unknown message type: {0}")]
+    UnknownMessageType(String),
+    /// ペイロードが指定したメッセージ型としてデコードできない場合
+    #[error("Invalid payload: {0}")]
+    InvalidPayload(String),
+}
+
+/// Protocol BuffersのメッセージをJSON文字列にデコードします
+///
+/// 型情報に含まれないフィールド番号のデータは無視されます。
+///
+/// # Arguments
+/// * `descriptor_set` - シリアライズされた`FileDescriptorSet`
+/// * `message_type` - パッケージ名を含むメッセージの完全名（例: `chat.v1.Message`）
+/// * `payload` - デコードするメッセージのバイナリ
+///
+/// # Errors
+/// * `ProtobufError::InvalidDescriptor` - `FileDescriptorSet`として不正な場合
+/// * `ProtobufError::UnknownMessageType` - メッセージ型が見つからない場合
+/// * `ProtobufError::InvalidPayload` - ペイロードのデコードまたはJSON化に失敗した場合
+///
+/// # Example
+/// ```
+/// let json = decode_protobuf(descriptor_set, "chat.v1.Message", body)?;
+/// // 例: {"id":"42","text":"hello"}
+/// ```
+#[uniffi::export]
+pub fn decode_protobuf(
+    descriptor_set: Vec<u8>,
+    message_type: &str,
+    payload: Vec<u8>,
+) -> Result<String, ProtobufError> {
+    let pool = DescriptorPool::decode(descriptor_set.as_slice())
+        .map_err(|e| ProtobufError::InvalidDescriptor(e.to_string()))?;
+    let name = message_type.trim().trim_start_matches('.');
+    let descriptor = pool
+        .get_message_by_name(name)
+        .ok_or_else(|| ProtobufError::UnknownMessageType(name.to_string()))?;
+    let message = DynamicMessage::decode(descriptor, payload.as_slice())
+        .map_err(|e| ProtobufError::InvalidPayload(e.to_string()))?;
+    serde_json::to_string(&message).map_err(|e| ProtobufError::InvalidPayload(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use prost_reflect::prost::Message;
+    use prost_reflect::prost_types::field_descriptor_proto::{Label, Type};
+    use prost_reflect::prost_types::{
+        DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet,
+    };
+
+    fn field(name: &str, number: i32, label: Label, r#type: Type) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(label as i32),
+            r#type: Some(r#type as i32),
+            ..Default::default()
+        }
+    }
+
+    /// `chat.v1.Message { int64 id = 1; string text = 2; repeated uint32 tags = 3; }`
+    fn test_descriptor_set() -> Vec<u8> {
+        let message = DescriptorProto {
+            name: Some("Message".to_string()),
+            field: vec![
+                field("id", 1, Label::Optional, Type::Int64),
+                field("text", 2, Label::Optional, Type::String),
+                field("tags", 3, Label::Repeated, Type::Uint32),
+            ],
+            ..Default::default()
+        };
+        let file = FileDescriptorProto {
+            name: Some("chat.proto".to_string()),
+            package: Some("chat.v1".to_string()),
+            message_type: vec![message],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        };
+        FileDescriptorSet { file: vec![file] }.encode_to_vec()
+    }
+
+    #[test]
+    fn test_decode_protobuf() {
+        // id = 42, text = "hello", tags = [1, 300]（packed）
+        let payload = vec![
+            0x08, 0x2a, 0x12, 0x05, b'h', b'e', b'l', b'l', b'o', 0x1a, 0x03, 0x01, 0xac, 0x02,
+        ];
+        let json = decode_protobuf(test_descriptor_set(), "chat.v1.Message", payload).unwrap();
+        assert_eq!(json, r#"{"id":"42","text":"hello","tags":[1,300]}"#);
+    }
+
+    #[test]
+    fn test_decode_protobuf_defaults_and_leading_dot() {
+        let json = decode_protobuf(test_descriptor_set(), ".chat.v1.Message", Vec::new()).unwrap();
+        assert_eq!(json, "{}");
+    }
+
+    #[test]
+    fn test_decode_protobuf_errors() {
+        match decode_protobuf(vec![0xff], "chat.v1.Message", Vec::new()) {
+            Err(ProtobufError::InvalidDescriptor(_)) => (),
+            other => panic!("Expected InvalidDescriptor error, got {:?}", other),
+        }
+        match decode_protobuf(test_descriptor_set(), "chat.v1.Missing", Vec::new()) {
+            Err(ProtobufError::UnknownMessageType(name)) => assert_eq!(name, "chat.v1.Missing"),
+            other => panic!("Expected UnknownMessageType error, got {:?}", other),
+        }
+        match decode_protobuf(test_descriptor_set(), "chat.v1.Message", vec![0x12, 0x05, b'h']) {
+            Err(ProtobufError::InvalidPayload(_)) => (),
+            other => panic!("Expected InvalidPayload error, got {:?}", other),
+        }
+    }
+}
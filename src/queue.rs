@@ -0,0 +1,339 @@
+//! 永続タスクキューモジュール
+//!
+//! このモジュールは、SQLiteファイルに保存されるFIFOキュー`TaskQueue`をエクスポートします。
+//! オフライン中に作成された操作を保存しておき、アプリの再起動後も
+//! 作成順にサーバーへ再送するために使用します。
+//!
+//! 取り出した項目は`ack`で完了を通知するまでキューに残り、
+//! `nack`または可視性タイムアウトの経過によって再び取り出せるようになります。
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, params_from_iter, Connection};
+use thiserror::Error;
+
+/// 一度に取り出せる最大件数
+const MAX_BATCH_SIZE: u32 = 1000;
+
+/// キューを保存するテーブルの作成SQL
+const CREATE_TABLE: &str = "CREATE TABLE IF NOT EXISTS task_queue (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    payload BLOB NOT NULL,
+    available_at INTEGER NOT NULL,
+    leased_until INTEGER,
+    attempts INTEGER NOT NULL DEFAULT 0
+)";
+
+/// タスクキューの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum QueueError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// 取り出す件数が1〜1000の範囲外の場合
+    #[error("Invalid batch size: {0} (expected 1 to 1000)")]
+    InvalidBatchSize(u32),
+    /// データベースの操作に失敗した場合
+    #[error("Queue storage error: {0}")]
+    StorageError(String),
+}
+
+impl From<rusqlite::Error> for QueueError {
+    fn from(error: rusqlite::Error) -> Self {
+        QueueError::StorageError(error.to_string())
+    }
+}
+
+/// キューから取り出した項目
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct QueueItem {
+    /// 項目のID（`ack`・`nack`に使用、作成順に増加）
+    pub id: i64,
+    /// 保存されたデータ
+    pub payload: Vec<u8>,
+    /// 今回を含めた取り出し回数
+    pub attempts: u32,
+}
+
+/// 現在時刻（UNIXエポックからのミリ秒）を返します
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// `?, ?, ...`形式のプレースホルダーを作成します
+fn placeholders(count: usize) -> String {
+    vec!["?"; count].join(", ")
+}
+
+/// SQLiteに保存される永続FIFOキュー
+///
+/// 項目はIDの昇順（作成順）に取り出されます。`nack`で遅延を指定した項目は、
+/// 遅延が経過するまで後続の項目より後に回されます。
+/// 開いた時点で取り出し中だった項目は、前回のプロセスが処理を終えられなかったものとして
+/// すぐに再び取り出せる状態に戻されます。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let queue = TaskQueue::open(path)?;
+/// queue.enqueue(request_json)?;
+/// for item in queue.dequeue_batch(10, 30_000)? {
+///     match send(item.payload) {
+///         Ok(_) => queue.ack(vec![item.id])?,
+///         Err(_) => queue.nack(vec![item.id], 60_000)?,
+///     };
+/// }
+/// ```
+#[derive(uniffi::Object)]
+pub struct TaskQueue {
+    connection: Mutex<Connection>,
+}
+
+impl TaskQueue {
+    /// 指定したIDの項目を更新し、更新された件数を返します
+    fn update_ids(&self, sql: &str, first: i64, ids: &[i64]) -> Result<u64, QueueError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let connection = self.connection.lock()
+            .map_err(|_| QueueError::MutexPoisoned)?;
+        let sql = format!("{} WHERE id IN ({})", sql, placeholders(ids.len()));
+        let params = std::iter::once(first).chain(ids.iter().copied());
+        Ok(connection.execute(&sql, params_from_iter(params))? as u64)
+    }
+}
+
+#[uniffi::export]
+impl TaskQueue {
+    /// キューのファイルを開きます（存在しない場合は作成されます）
+    ///
+    /// # Arguments
+    /// * `path` - SQLiteファイルのパス
+    ///
+    /// # Errors
+    /// * `QueueError::StorageError` - ファイルを開けない、またはテーブルを作成できない場合
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, QueueError> {
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(CREATE_TABLE)?;
+        connection.execute("UPDATE task_queue SET leased_until = NULL", [])?;
+        Ok(Arc::new(Self {
+            connection: Mutex::new(connection),
+        }))
+    }
+
+    /// 項目を末尾に追加します
+    ///
+    /// # Returns
+    /// 追加した項目のID
+    ///
+    /// # Errors
+    /// * `QueueError::StorageError` - 書き込みに失敗した場合
+    /// * `QueueError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn enqueue(&self, payload: Vec<u8>) -> Result<i64, QueueError> {
+        let connection = self.connection.lock()
+            .map_err(|_| QueueError::MutexPoisoned)?;
+        connection.execute(
+            "INSERT INTO task_queue (payload, available_at) VALUES (?1, ?2)",
+            params![payload, now_ms()],
+        )?;
+        Ok(connection.last_insert_rowid())
+    }
+
+    /// 取り出せる項目を先頭から最大`max_items`件取り出します
+    ///
+    /// 取り出した項目は可視性タイムアウトの間は他の呼び出しで取り出されず、
+    /// その間に`ack`されなければ再び取り出せるようになります。
+    ///
+    /// # Arguments
+    /// * `max_items` - 取り出す最大件数（1〜1000）
+    /// * `visibility_timeout_ms` - 取り出した項目を非表示にする時間（ミリ秒、既定値は30000）
+    ///
+    /// # Errors
+    /// * `QueueError::InvalidBatchSize` - 件数が範囲外の場合
+    /// * `QueueError::StorageError` - 読み書きに失敗した場合
+    /// * `QueueError::MutexPoisoned` - 内部Mutexが破損している場合
+    #[uniffi::method(default(visibility_timeout_ms = 30000))]
+    pub fn dequeue_batch(
+        &self,
+        max_items: u32,
+        visibility_timeout_ms: u64,
+    ) -> Result<Vec<QueueItem>, QueueError> {
+        if !(1..=MAX_BATCH_SIZE).contains(&max_items) {
+            return Err(QueueError::InvalidBatchSize(max_items));
+        }
+        let mut connection = self.connection.lock()
+            .map_err(|_| QueueError::MutexPoisoned)?;
+        let now = now_ms();
+        let transaction = connection.transaction()?;
+        let items = {
+            let mut statement = transaction.prepare(
+                "SELECT id, payload, attempts FROM task_queue
+                 WHERE available_at <= ?1 AND (leased_until IS NULL OR leased_until <= ?1)
+                 ORDER BY id LIMIT ?2",
+            )?;
+            let rows = statement.query_map(params![now, max_items], |row| {
+                Ok(QueueItem {
+                    id: row.get(0)?,
+                    payload: row.get(1)?,
+                    attempts: row.get::<_, u32>(2)? + 1,
+                })
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+        let leased_until = now.saturating_add(visibility_timeout_ms.min(i64::MAX as u64) as i64);
+        for item in &items {
+            transaction.execute(
+                "UPDATE task_queue SET leased_until = ?1, attempts = ?2 WHERE id = ?3",
+                params![leased_until, item.attempts, item.id],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(items)
+    }
+
+    /// 処理が完了した項目をキューから削除します
+    ///
+    /// # Returns
+    /// 削除された件数（存在しないIDは無視されます）
+    ///
+    /// # Errors
+    /// * `QueueError::StorageError` - 書き込みに失敗した場合
+    /// * `QueueError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn ack(&self, ids: Vec<i64>) -> Result<u64, QueueError> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        let connection = self.connection.lock()
+            .map_err(|_| QueueError::MutexPoisoned)?;
+        let sql = format!("DELETE FROM task_queue WHERE id IN ({})", placeholders(ids.len()));
+        Ok(connection.execute(&sql, params_from_iter(ids))? as u64)
+    }
+
+    /// 処理に失敗した項目を、指定した遅延の後に再び取り出せるようにします
+    ///
+    /// # Arguments
+    /// * `ids` - 項目のID
+    /// * `delay_ms` - 再び取り出せるようになるまでの時間（ミリ秒、0の場合はすぐ）
+    ///
+    /// # Returns
+    /// 更新された件数（存在しないIDは無視されます）
+    ///
+    /// # Errors
+    /// * `QueueError::StorageError` - 書き込みに失敗した場合
+    /// * `QueueError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn nack(&self, ids: Vec<i64>, delay_ms: u64) -> Result<u64, QueueError> {
+        let available_at = now_ms().saturating_add(delay_ms.min(i64::MAX as u64) as i64);
+        self.update_ids(
+            "UPDATE task_queue SET leased_until = NULL, available_at = ?",
+            available_at,
+            &ids,
+        )
+    }
+
+    /// キューに残っている項目数（取り出し中・遅延中を含む）を返します
+    ///
+    /// # Errors
+    /// * `QueueError::StorageError` - 読み込みに失敗した場合
+    /// * `QueueError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn count(&self) -> Result<u64, QueueError> {
+        let connection = self.connection.lock()
+            .map_err(|_| QueueError::MutexPoisoned)?;
+        Ok(connection.query_row("SELECT COUNT(*) FROM task_queue", [], |row| row.get(0))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テストで使用する可視性タイムアウト（ミリ秒）
+    const VISIBILITY_TIMEOUT_MS: u64 = 30_000;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_queue_{}_{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn payloads(items: &[QueueItem]) -> Vec<Vec<u8>> {
+        items.iter().map(|item| item.payload.clone()).collect()
+    }
+
+    #[test]
+    fn test_queue_fifo_and_ack() {
+        let path = temp_path("fifo");
+        let queue = TaskQueue::open(path.clone()).unwrap();
+        for payload in [b"a", b"b", b"c"] {
+            queue.enqueue(payload.to_vec()).unwrap();
+        }
+        let batch = queue.dequeue_batch(2, VISIBILITY_TIMEOUT_MS).unwrap();
+        assert_eq!(payloads(&batch), vec![b"a".to_vec(), b"b".to_vec()]);
+        assert_eq!(batch[0].attempts, 1);
+
+        let rest = queue.dequeue_batch(10, VISIBILITY_TIMEOUT_MS).unwrap();
+        assert_eq!(payloads(&rest), vec![b"c".to_vec()]);
+        assert!(queue.dequeue_batch(10, VISIBILITY_TIMEOUT_MS).unwrap().is_empty());
+
+        let ids: Vec<i64> = batch.iter().chain(&rest).map(|item| item.id).collect();
+        assert_eq!(queue.ack(ids).unwrap(), 3);
+        assert_eq!(queue.count().unwrap(), 0);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_queue_nack_and_visibility_timeout() {
+        let path = temp_path("nack");
+        let queue = TaskQueue::open(path.clone()).unwrap();
+        queue.enqueue(b"a".to_vec()).unwrap();
+        queue.enqueue(b"b".to_vec()).unwrap();
+
+        let batch = queue.dequeue_batch(2, VISIBILITY_TIMEOUT_MS).unwrap();
+        assert_eq!(queue.nack(vec![batch[0].id], 0).unwrap(), 1);
+        assert_eq!(queue.nack(vec![batch[1].id], 60_000).unwrap(), 1);
+        let retried = queue.dequeue_batch(10, 0).unwrap();
+        assert_eq!(payloads(&retried), vec![b"a".to_vec()]);
+        assert_eq!(retried[0].attempts, 2);
+
+        // 可視性タイムアウトが0のため、ackしなければすぐに再び取り出される
+        let again = queue.dequeue_batch(10, VISIBILITY_TIMEOUT_MS).unwrap();
+        assert_eq!(again[0].attempts, 3);
+        assert_eq!(queue.count().unwrap(), 2);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_queue_survives_reopen() {
+        let path = temp_path("reopen");
+        {
+            let queue = TaskQueue::open(path.clone()).unwrap();
+            queue.enqueue(b"first".to_vec()).unwrap();
+            queue.enqueue(b"second".to_vec()).unwrap();
+            queue.dequeue_batch(1, VISIBILITY_TIMEOUT_MS).unwrap();
+        }
+        let queue = TaskQueue::open(path.clone()).unwrap();
+        let batch = queue.dequeue_batch(10, VISIBILITY_TIMEOUT_MS).unwrap();
+        assert_eq!(payloads(&batch), vec![b"first".to_vec(), b"second".to_vec()]);
+        assert_eq!(batch[0].attempts, 2);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_queue_invalid_batch_size() {
+        let path = temp_path("invalid");
+        let queue = TaskQueue::open(path.clone()).unwrap();
+        match queue.dequeue_batch(0, VISIBILITY_TIMEOUT_MS) {
+            Err(QueueError::InvalidBatchSize(0)) => (),
+            other => panic!("Expected InvalidBatchSize error, got {:?}", other),
+        }
+        assert_eq!(queue.ack(Vec::new()).unwrap(), 0);
+        let _ = std::fs::remove_file(path);
+    }
+}
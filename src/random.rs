@@ -0,0 +1,157 @@
+//! 暗号論的に安全な乱数ユーティリティモジュール
+//!
+//! このモジュールは、OSの暗号論的乱数生成器（CSPRNG）を使用して
+//! ランダムなバイト列・整数・文字列を生成する関数をエクスポートします。
+//! トークンやノンスの生成に使用できます。
+
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+
+/// 一度に生成できる最大の長さ（1 MiB）
+const MAX_RANDOM_LENGTH: u32 = 1024 * 1024;
+
+/// 英数字（62文字）
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+
+/// 乱数生成で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum RandomError {
+    /// 範囲の下限が上限より大きい場合
+    #[error("Invalid range: min ({min}) is greater than max ({max})")]
+    InvalidRange { min: u64, max: u64 },
+    /// 要求された長さが上限を超える場合
+    #[error("Requested length {0} exceeds the maximum of 1048576")]
+    LengthTooLarge(u32),
+}
+
+/// 要求された長さが上限以内か確認します
+fn check_length(len: u32) -> Result<(), RandomError> {
+    if len > MAX_RANDOM_LENGTH {
+        return Err(RandomError::LengthTooLarge(len));
+    }
+    Ok(())
+}
+
+/// 指定された長さのランダムなバイト列を生成します
+///
+/// # Arguments
+/// * `len` - 生成するバイト数（最大1 MiB）
+///
+/// # Errors
+/// * `RandomError::LengthTooLarge` - 長さが上限を超える場合
+///
+/// # Example
+/// ```
+/// let nonce = random_bytes(12)?;
+/// assert_eq!(nonce.len(), 12);
+/// ```
+#[uniffi::export]
+pub fn random_bytes(len: u32) -> Result<Vec<u8>, RandomError> {
+    check_length(len)?;
+    let mut bytes = vec![0u8; len as usize];
+    OsRng.fill_bytes(&mut bytes);
+    Ok(bytes)
+}
+
+/// `min`以上`max`以下の一様な乱数を生成します
+///
+/// 剰余による偏りが生じないよう棄却サンプリングを行います。
+///
+/// # Arguments
+/// * `min` - 下限（含む）
+/// * `max` - 上限（含む）
+///
+/// # Errors
+/// * `RandomError::InvalidRange` - `min`が`max`より大きい場合
+#[uniffi::export]
+pub fn random_u64_in_range(min: u64, max: u64) -> Result<u64, RandomError> {
+    if min > max {
+        return Err(RandomError::InvalidRange { min, max });
+    }
+    let span = max - min;
+    if span == u64::MAX {
+        return Ok(OsRng.next_u64());
+    }
+    let range = span + 1;
+    // rangeの倍数に収まる最大値を超えた値は棄却する
+    let zone = u64::MAX - (u64::MAX - range + 1) % range;
+    loop {
+        let value = OsRng.next_u64();
+        if value <= zone {
+            return Ok(min + value % range);
+        }
+    }
+}
+
+/// 英数字（A-Z, a-z, 0-9）からなるランダムな文字列を生成します
+///
+/// # Arguments
+/// * `len` - 生成する文字数（最大1 MiB）
+///
+/// # Errors
+/// * `RandomError::LengthTooLarge` - 長さが上限を超える場合
+#[uniffi::export]
+pub fn random_alphanumeric(len: u32) -> Result<String, RandomError> {
+    check_length(len)?;
+    let mut output = String::with_capacity(len as usize);
+    let mut buf = [0u8; 64];
+    while output.len() < len as usize {
+        OsRng.fill_bytes(&mut buf);
+        // 62 * 4 = 248 未満のバイトのみを使用して偏りをなくす
+        for byte in buf.iter().filter(|b| **b < 248) {
+            if output.len() == len as usize {
+                break;
+            }
+            output.push(ALPHANUMERIC[(*byte % 62) as usize] as char);
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_bytes() {
+        let a = random_bytes(32).unwrap();
+        let b = random_bytes(32).unwrap();
+        assert_eq!(a.len(), 32);
+        assert_ne!(a, b);
+        assert!(random_bytes(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_random_bytes_too_large() {
+        match random_bytes(MAX_RANDOM_LENGTH + 1) {
+            Err(RandomError::LengthTooLarge(_)) => (),
+            _ => panic!("Expected LengthTooLarge error"),
+        }
+    }
+
+    #[test]
+    fn test_random_u64_in_range() {
+        for _ in 0..1000 {
+            let value = random_u64_in_range(10, 20).unwrap();
+            assert!((10..=20).contains(&value));
+        }
+        assert_eq!(random_u64_in_range(7, 7).unwrap(), 7);
+        let _ = random_u64_in_range(0, u64::MAX).unwrap();
+    }
+
+    #[test]
+    fn test_random_u64_invalid_range() {
+        match random_u64_in_range(5, 1) {
+            Err(RandomError::InvalidRange { min: 5, max: 1 }) => (),
+            _ => panic!("Expected InvalidRange error"),
+        }
+    }
+
+    #[test]
+    fn test_random_alphanumeric() {
+        let token = random_alphanumeric(100).unwrap();
+        assert_eq!(token.len(), 100);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
@@ -0,0 +1,267 @@
+//! リカバリーコード（バックアップコード）モジュール
+//!
+//! このモジュールは、二要素認証の予備手段として使うリカバリーコードを生成し、
+//! 保存用のハッシュ化と検証を行う関数をエクスポートします。
+//! アプリとサーバーで同じ正規化・ハッシュ化の規則を共有するために使用します。
+
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+
+/// 一度に生成できるコードの最大数
+const MAX_RECOVERY_CODES: u32 = 100;
+
+/// 1グループの最大文字数
+const MAX_GROUP_LENGTH: u32 = 16;
+
+/// 1コードの最大グループ数
+const MAX_GROUPS: u32 = 8;
+
+/// コードに使用する文字（Crockford Base32：紛らわしいI・L・O・Uを除く32文字）
+const CODE_ALPHABET: &[u8] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// リカバリーコード処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum RecoveryCodeError {
+    /// 生成するコード数が1〜100の範囲外の場合
+    #[error("Invalid recovery code count: {0} (expected 1 to 100)")]
+    InvalidCount(u32),
+    /// コードの書式指定が不正な場合
+    #[error("Invalid recovery code format: {0}")]
+    InvalidFormat(String),
+    /// コードが空、または使用できない文字を含む場合
+    #[error("Invalid recovery code")]
+    InvalidCode,
+}
+
+/// リカバリーコードの書式
+///
+/// 既定値は`XXXXX-XXXXX`形式（5文字×2グループ、約50ビットのエントロピー）です。
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct RecoveryCodeFormat {
+    /// グループ数（1〜8）
+    #[uniffi(default = 2)]
+    pub groups: u32,
+    /// 1グループの文字数（1〜16）
+    #[uniffi(default = 5)]
+    pub group_length: u32,
+    /// グループ間の区切り文字（空白またはASCII記号）
+    #[uniffi(default = "-")]
+    pub separator: String,
+}
+
+impl Default for RecoveryCodeFormat {
+    fn default() -> Self {
+        Self {
+            groups: 2,
+            group_length: 5,
+            separator: "-".to_string(),
+        }
+    }
+}
+
+/// 書式指定が有効範囲内かを確認します
+fn check_format(format: &RecoveryCodeFormat) -> Result<(), RecoveryCodeError> {
+    if !(1..=MAX_GROUPS).contains(&format.groups) {
+        return Err(RecoveryCodeError::InvalidFormat(format!(
+            "groups must be between 1 and {}",
+            MAX_GROUPS
+        )));
+    }
+    if !(1..=MAX_GROUP_LENGTH).contains(&format.group_length) {
+        return Err(RecoveryCodeError::InvalidFormat(format!(
+            "group_length must be between 1 and {}",
+            MAX_GROUP_LENGTH
+        )));
+    }
+    // 検証時に読み飛ばせるよう、区切り文字は空白とASCII記号に限定する
+    if !format.separator.chars().all(|c| c == ' ' || c.is_ascii_punctuation()) {
+        return Err(RecoveryCodeError::InvalidFormat(
+            "separator must consist of spaces or ASCII punctuation".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// コードを比較用の正規形に変換します
+///
+/// 区切り文字と空白を取り除いて大文字にし、読み間違えやすい
+/// `O`→`0`、`I`・`L`→`1`を置き換えます。
+fn normalize_code(code: &str) -> Result<String, RecoveryCodeError> {
+    let mut normalized = String::with_capacity(code.len());
+    for c in code.chars() {
+        if c.is_whitespace() || c.is_ascii_punctuation() {
+            continue;
+        }
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        if !c.is_ascii() || !CODE_ALPHABET.contains(&(c as u8)) {
+            return Err(RecoveryCodeError::InvalidCode);
+        }
+        normalized.push(c);
+    }
+    if normalized.is_empty() {
+        return Err(RecoveryCodeError::InvalidCode);
+    }
+    Ok(normalized)
+}
+
+/// リカバリーコードを生成します
+///
+/// 各文字は32文字のアルファベットから一様に選ばれるため、
+/// 1文字あたり5ビットのエントロピーを持ちます。
+///
+/// # Arguments
+/// * `count` - 生成するコード数（1〜100）
+/// * `format` - コードの書式（`None`の場合は`XXXXX-XXXXX`形式）
+///
+/// # Errors
+/// * `RecoveryCodeError::InvalidCount` - コード数が範囲外の場合
+/// * `RecoveryCodeError::InvalidFormat` - 書式指定が不正な場合
+///
+/// # Example
+/// ```
+/// let codes = generate_recovery_codes(10, None)?;
+/// // 例: ["7KQ2M-X9D4T", ...]
+/// ```
+#[uniffi::export(default(format = None))]
+pub fn generate_recovery_codes(
+    count: u32,
+    format: Option<RecoveryCodeFormat>,
+) -> Result<Vec<String>, RecoveryCodeError> {
+    if !(1..=MAX_RECOVERY_CODES).contains(&count) {
+        return Err(RecoveryCodeError::InvalidCount(count));
+    }
+    let format = format.unwrap_or_default();
+    check_format(&format)?;
+
+    let group_length = format.group_length as usize;
+    let mut buf = vec![0u8; group_length];
+    let codes = (0..count)
+        .map(|_| {
+            let groups: Vec<String> = (0..format.groups)
+                .map(|_| {
+                    OsRng.fill_bytes(&mut buf);
+                    // アルファベットは32文字なので下位5ビットで偏りなく選べる
+                    buf.iter()
+                        .map(|b| CODE_ALPHABET[(b & 0x1f) as usize] as char)
+                        .collect()
+                })
+                .collect();
+            groups.join(&format.separator)
+        })
+        .collect();
+    Ok(codes)
+}
+
+/// 保存用にリカバリーコードをハッシュ化します
+///
+/// コードを正規化（区切り文字・大文字小文字・紛らわしい文字の違いを吸収）した上で
+/// SHA-256を計算し、小文字の16進数文字列で返します。
+/// リカバリーコードは十分なエントロピーを持つランダム値であるため、
+/// パスワード用の低速なハッシュではなく決定的なハッシュを使用します。
+///
+/// # Arguments
+/// * `code` - ハッシュ化するリカバリーコード
+///
+/// # Errors
+/// * `RecoveryCodeError::InvalidCode` - コードが空、または使用できない文字を含む場合
+#[uniffi::export]
+pub fn hash_recovery_code(code: String) -> Result<String, RecoveryCodeError> {
+    let normalized = normalize_code(&code)?;
+    Ok(hex::encode(Sha256::digest(normalized.as_bytes())))
+}
+
+/// 入力されたリカバリーコードを保存済みのハッシュと照合します
+///
+/// 比較は定数時間で行われます。不正な文字を含むコードは`false`になります。
+///
+/// # Arguments
+/// * `code` - 利用者が入力したリカバリーコード
+/// * `hash` - `hash_recovery_code`が返したハッシュ
+#[uniffi::export]
+pub fn verify_recovery_code(code: String, hash: String) -> bool {
+    match hash_recovery_code(code) {
+        Ok(computed) => computed
+            .as_bytes()
+            .ct_eq(hash.trim().to_ascii_lowercase().as_bytes())
+            .into(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_recovery_codes_default_format() {
+        let codes = generate_recovery_codes(10, None).unwrap();
+        assert_eq!(codes.len(), 10);
+        for code in &codes {
+            assert_eq!(code.len(), 11);
+            assert_eq!(&code[5..6], "-");
+            assert!(code.replace('-', "").bytes().all(|b| CODE_ALPHABET.contains(&b)));
+        }
+    }
+
+    #[test]
+    fn test_generate_recovery_codes_custom_format() {
+        let format = RecoveryCodeFormat {
+            groups: 3,
+            group_length: 4,
+            separator: " ".to_string(),
+        };
+        let codes = generate_recovery_codes(2, Some(format)).unwrap();
+        assert_eq!(codes[0].split(' ').count(), 3);
+        assert_eq!(codes[0].len(), 14);
+        assert_ne!(codes[0], codes[1]);
+    }
+
+    #[test]
+    fn test_generate_recovery_codes_invalid() {
+        match generate_recovery_codes(0, None) {
+            Err(RecoveryCodeError::InvalidCount(0)) => (),
+            _ => panic!("Expected InvalidCount error"),
+        }
+        let format = RecoveryCodeFormat {
+            separator: "x".to_string(),
+            ..RecoveryCodeFormat::default()
+        };
+        match generate_recovery_codes(1, Some(format)) {
+            Err(RecoveryCodeError::InvalidFormat(_)) => (),
+            _ => panic!("Expected InvalidFormat error"),
+        }
+    }
+
+    #[test]
+    fn test_hash_and_verify_recovery_code() {
+        let code = generate_recovery_codes(1, None).unwrap().remove(0);
+        let hash = hash_recovery_code(code.clone()).unwrap();
+        assert_eq!(hash.len(), 64);
+        assert!(verify_recovery_code(code.clone(), hash.clone()));
+        assert!(verify_recovery_code(code.to_lowercase().replace('-', " "), hash.clone()));
+        assert!(!verify_recovery_code("00000-00000".to_string(), hash));
+    }
+
+    #[test]
+    fn test_recovery_code_normalizes_ambiguous_characters() {
+        let hash = hash_recovery_code("10ABC-DEF12".to_string()).unwrap();
+        assert!(verify_recovery_code("lOabc-def12".to_string(), hash.clone()));
+        assert!(verify_recovery_code("IoABCDEFI2".to_string(), hash));
+    }
+
+    #[test]
+    fn test_hash_recovery_code_invalid() {
+        match hash_recovery_code(" - ".to_string()) {
+            Err(RecoveryCodeError::InvalidCode) => (),
+            _ => panic!("Expected InvalidCode error"),
+        }
+        assert!(!verify_recovery_code("ÄBC".to_string(), "00".to_string()));
+    }
+}
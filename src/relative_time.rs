@@ -0,0 +1,230 @@
+//! 相対時刻の書式化モジュール
+//!
+//! このモジュールは、2つの時点の差を「3分前」「in 2 days」のような
+//! 相対的な表現に変換する`format_relative`をエクスポートします。
+//! フィードの投稿時刻などの表示をプラットフォーム間で揃えるために使用します。
+
+use crate::datetime::Timestamp;
+
+/// 1日の秒数
+const DAY: f64 = 86_400.0;
+
+/// 言語ごとの相対時刻テンプレート
+///
+/// 単位の表記は`[単数, 複数]`の順で、`{n}`が数値に置換されます。
+struct RelativeTimeTemplates {
+    /// 言語コード（ISO 639-1）
+    language: &'static str,
+    /// 差が45秒未満の場合の表記
+    now: &'static str,
+    /// 過去の表記（`{0}`が単位付きの数値に置換されます）
+    past: &'static str,
+    /// 未来の表記（`{0}`が単位付きの数値に置換されます）
+    future: &'static str,
+    /// 分
+    minute: [&'static str; 2],
+    /// 時間
+    hour: [&'static str; 2],
+    /// 日
+    day: [&'static str; 2],
+    /// 週
+    week: [&'static str; 2],
+    /// 月
+    month: [&'static str; 2],
+    /// 年
+    year: [&'static str; 2],
+}
+
+/// 対応言語の相対時刻テンプレート（先頭がフォールバック言語）
+const RELATIVE_TIME_TEMPLATES: &[RelativeTimeTemplates] = &[
+    RelativeTimeTemplates {
+        language: "en",
+        now: "now",
+        past: "{0} ago",
+        future: "in {0}",
+        minute: ["{n} minute", "{n} minutes"],
+        hour: ["{n} hour", "{n} hours"],
+        day: ["{n} day", "{n} days"],
+        week: ["{n} week", "{n} weeks"],
+        month: ["{n} month", "{n} months"],
+        year: ["{n} year", "{n} years"],
+    },
+    RelativeTimeTemplates {
+        language: "ja",
+        now: "今",
+        past: "{0}前",
+        future: "{0}後",
+        minute: ["{n}分", "{n}分"],
+        hour: ["{n}時間", "{n}時間"],
+        day: ["{n}日", "{n}日"],
+        week: ["{n}週間", "{n}週間"],
+        month: ["{n}か月", "{n}か月"],
+        year: ["{n}年", "{n}年"],
+    },
+    RelativeTimeTemplates {
+        language: "es",
+        now: "ahora",
+        past: "hace {0}",
+        future: "dentro de {0}",
+        minute: ["{n} minuto", "{n} minutos"],
+        hour: ["{n} hora", "{n} horas"],
+        day: ["{n} día", "{n} días"],
+        week: ["{n} semana", "{n} semanas"],
+        month: ["{n} mes", "{n} meses"],
+        year: ["{n} año", "{n} años"],
+    },
+    RelativeTimeTemplates {
+        language: "fr",
+        now: "maintenant",
+        past: "il y a {0}",
+        future: "dans {0}",
+        minute: ["{n} minute", "{n} minutes"],
+        hour: ["{n} heure", "{n} heures"],
+        day: ["{n} jour", "{n} jours"],
+        week: ["{n} semaine", "{n} semaines"],
+        month: ["{n} mois", "{n} mois"],
+        year: ["{n} an", "{n} ans"],
+    },
+    RelativeTimeTemplates {
+        language: "de",
+        now: "jetzt",
+        past: "vor {0}",
+        future: "in {0}",
+        minute: ["{n} Minute", "{n} Minuten"],
+        hour: ["{n} Stunde", "{n} Stunden"],
+        day: ["{n} Tag", "{n} Tagen"],
+        week: ["{n} Woche", "{n} Wochen"],
+        month: ["{n} Monat", "{n} Monaten"],
+        year: ["{n} Jahr", "{n} Jahren"],
+    },
+    RelativeTimeTemplates {
+        language: "zh",
+        now: "现在",
+        past: "{0}前",
+        future: "{0}后",
+        minute: ["{n}分钟", "{n}分钟"],
+        hour: ["{n}小时", "{n}小时"],
+        day: ["{n}天", "{n}天"],
+        week: ["{n}周", "{n}周"],
+        month: ["{n}个月", "{n}个月"],
+        year: ["{n}年", "{n}年"],
+    },
+];
+
+/// ロケール識別子から相対時刻テンプレートを選択します（未対応の場合は英語）
+fn resolve_templates(locale: &str) -> &'static RelativeTimeTemplates {
+    let language =
+        locale.trim().split(['-', '_', '.', '@']).next().unwrap_or_default().to_ascii_lowercase();
+    RELATIVE_TIME_TEMPLATES
+        .iter()
+        .find(|templates| templates.language == language)
+        .unwrap_or(&RELATIVE_TIME_TEMPLATES[0])
+}
+
+/// 差（秒）を単位に丸めた数値（1以上）を返します
+fn rounded(seconds: f64, unit: f64) -> u64 {
+    ((seconds / unit).round() as u64).max(1)
+}
+
+/// 時点を基準時刻からの相対的な表現で返します
+///
+/// 差の大きさに応じて単位を選び、四捨五入した数値で表記します。
+///
+/// | 差 | 単位 |
+/// |----|------|
+/// | 45秒未満 | 「今」 |
+/// | 45分未満 | 分 |
+/// | 22時間未満 | 時間 |
+/// | 6.5日未満 | 日 |
+/// | 26日未満 | 週 |
+/// | 320日未満 | 月（30.44日） |
+/// | それ以上 | 年（365.2425日） |
+///
+/// 対応言語は英語・日本語・スペイン語・フランス語・ドイツ語・中国語です。
+/// ロケールは言語部分（`ja-JP`なら`ja`）で照合され、未対応の場合は英語にフォールバックします。
+///
+/// # Arguments
+/// * `timestamp` - 表示する時点
+/// * `now` - 基準となる現在時刻
+/// * `locale` - ロケール識別子（例: `"ja"`, `"en-US"`）
+///
+/// # Example
+/// ```
+/// let text = format_relative(posted_at, now, "ja".to_string());
+/// assert_eq!(text, "3分前");
+/// ```
+#[uniffi::export]
+pub fn format_relative(timestamp: Timestamp, now: Timestamp, locale: String) -> String {
+    let templates = resolve_templates(&locale);
+    // i64の端の値どうしの差はi64に収まらないため、i128で計算する
+    let difference = (i128::from(timestamp.unix_seconds) - i128::from(now.unix_seconds)) as f64
+        + (f64::from(timestamp.nanoseconds) - f64::from(now.nanoseconds)) / 1e9;
+    let seconds = difference.abs();
+    if seconds < 45.0 {
+        return templates.now.to_string();
+    }
+    let (unit, count) = if seconds < 45.0 * 60.0 {
+        (templates.minute, rounded(seconds, 60.0))
+    } else if seconds < 22.0 * 3600.0 {
+        (templates.hour, rounded(seconds, 3600.0))
+    } else if seconds < 6.5 * DAY {
+        (templates.day, rounded(seconds, DAY))
+    } else if seconds < 26.0 * DAY {
+        (templates.week, rounded(seconds, 7.0 * DAY))
+    } else if seconds < 320.0 * DAY {
+        (templates.month, rounded(seconds, 30.436875 * DAY))
+    } else {
+        (templates.year, rounded(seconds, 365.2425 * DAY))
+    };
+    let amount = unit[usize::from(count != 1)].replace("{n}", &count.to_string());
+    let pattern = if difference < 0.0 { templates.past } else { templates.future };
+    pattern.replace("{0}", &amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: i64 = 1_704_164_645;
+
+    fn relative(offset_seconds: i64, locale: &str) -> String {
+        let at = |unix_seconds| Timestamp { unix_seconds, nanoseconds: 0, utc_offset_seconds: 0 };
+        format_relative(at(NOW + offset_seconds), at(NOW), locale.to_string())
+    }
+
+    #[test]
+    fn test_format_relative_english() {
+        assert_eq!(relative(0, "en"), "now");
+        assert_eq!(relative(-44, "en"), "now");
+        assert_eq!(relative(-45, "en"), "1 minute ago");
+        assert_eq!(relative(-3 * 60, "en-US"), "3 minutes ago");
+        assert_eq!(relative(90 * 60, "en"), "in 2 hours");
+        assert_eq!(relative(-22 * 3600, "en"), "1 day ago");
+        assert_eq!(relative(2 * 86400, "en"), "in 2 days");
+        assert_eq!(relative(-7 * 86400, "en"), "1 week ago");
+        assert_eq!(relative(-60 * 86400, "en"), "2 months ago");
+        assert_eq!(relative(400 * 86400, "en"), "in 1 year");
+        assert_eq!(relative(-3 * 365 * 86400, "xx"), "3 years ago");
+    }
+
+    #[test]
+    fn test_format_relative_localized() {
+        assert_eq!(relative(-3 * 60, "ja-JP"), "3分前");
+        assert_eq!(relative(2 * 86400, "ja"), "2日後");
+        assert_eq!(relative(10, "ja"), "今");
+        assert_eq!(relative(-14 * 86400, "ja"), "2週間前");
+        assert_eq!(relative(-86400, "es"), "hace 1 día");
+        assert_eq!(relative(3 * 3600, "fr_CA"), "dans 3 heures");
+        assert_eq!(relative(-2 * 86400, "de"), "vor 2 Tagen");
+        assert_eq!(relative(-60 * 86400, "zh-Hans"), "2个月前");
+    }
+
+    #[test]
+    fn test_format_relative_extreme_timestamps() {
+        let at = |unix_seconds| Timestamp { unix_seconds, nanoseconds: 0, utc_offset_seconds: 0 };
+        let text = format_relative(at(i64::MAX), at(-10), "en".to_string());
+        assert!(text.starts_with("in ") && text.ends_with(" years"), "{}", text);
+        let text = format_relative(at(i64::MIN), at(i64::MAX), "en".to_string());
+        assert!(text.ends_with(" years ago"), "{}", text);
+    }
+}
@@ -0,0 +1,161 @@
+//! 再試行ポリシーモジュール
+//!
+//! このモジュールは、通信の再試行回数と待ち時間（指数バックオフとジッター）を表す
+//! `RetryPolicy`と、次の再試行までの待ち時間を計算する`next_delay`をエクスポートします。
+//! `Downloader`・`upload_file`などのRust側の通信と、Swift側のURLSessionのコードで
+//! 同じ再試行のスケジュールを共有するために使用します。
+
+use rand_core::{OsRng, RngCore};
+
+/// 再試行ポリシー
+///
+/// `attempt`回目の失敗の後の待ち時間は`base_delay_ms × multiplier^(attempt - 1)`を
+/// `max_delay_ms`で頭打ちにし、`jitter`の割合だけ前後にランダムにずらした値です。
+/// 範囲外の`multiplier`（1未満）と`jitter`（0〜1の範囲外）は範囲内に丸めて扱います。
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct RetryPolicy {
+    /// 最初の試行を含む最大試行回数（1の場合は再試行しない）
+    pub max_attempts: u32,
+    /// 最初の再試行までの待ち時間（ミリ秒）
+    pub base_delay_ms: u64,
+    /// 再試行までの最大の待ち時間（ミリ秒）
+    pub max_delay_ms: u64,
+    /// 待ち時間の倍率
+    pub multiplier: f64,
+    /// 待ち時間をずらす割合（0.2の場合は±20%）
+    pub jitter: f64,
+    /// 再試行するHTTPステータスコード
+    pub retryable_status_codes: Vec<u16>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay_ms: 500,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+            jitter: 0.2,
+            retryable_status_codes: vec![408, 429, 500, 502, 503, 504],
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// 0以上1未満の乱数`random`を使って待ち時間を計算します
+    pub(crate) fn delay_with(&self, attempt: u32, random: f64) -> Option<u64> {
+        if attempt == 0 || attempt >= self.max_attempts {
+            return None;
+        }
+        let multiplier = if self.multiplier.is_finite() { self.multiplier.max(1.0) } else { 1.0 };
+        let jitter = if self.jitter.is_finite() { self.jitter.clamp(0.0, 1.0) } else { 0.0 };
+        let exponent = (attempt - 1).min(1024) as i32;
+        let delay = (self.base_delay_ms as f64 * multiplier.powi(exponent))
+            .min(self.max_delay_ms as f64);
+        let factor = 1.0 - jitter + 2.0 * jitter * random;
+        Some((delay * factor).round() as u64)
+    }
+
+    /// OSの乱数を使って待ち時間を計算します
+    pub(crate) fn delay(&self, attempt: u32) -> Option<u64> {
+        let random = (OsRng.next_u32() as f64) / (u32::MAX as f64 + 1.0);
+        self.delay_with(attempt, random)
+    }
+
+    /// ステータスコードが再試行の対象かを判定します
+    pub(crate) fn retries_status(&self, status: u16) -> bool {
+        self.retryable_status_codes.contains(&status)
+    }
+}
+
+/// 既定の再試行ポリシーを返します
+///
+/// 最大3回（再試行2回）、500ミリ秒から2倍ずつ最大30秒、ジッター±20%で、
+/// 408・429・500・502・503・504を再試行します。
+#[uniffi::export]
+pub fn default_retry_policy() -> RetryPolicy {
+    RetryPolicy::default()
+}
+
+/// `attempt`回目の試行が失敗した後、次の試行までの待ち時間を返します
+///
+/// # Arguments
+/// * `policy` - 再試行ポリシー
+/// * `attempt` - 失敗した試行の番号（最初の試行は1）
+///
+/// # Returns
+/// * 待ち時間（ミリ秒）、最大試行回数に達した場合は`None`
+///
+/// # Example
+/// ```
+/// let policy = default_retry_policy();
+/// if let Some(delay_ms) = next_delay(policy, attempt) {
+///     // delay_ms待ってから再試行する
+/// }
+/// ```
+#[uniffi::export]
+pub fn next_delay(policy: RetryPolicy, attempt: u32) -> Option<u64> {
+    policy.delay(attempt)
+}
+
+/// ステータスコードがポリシーで再試行の対象かを判定します
+///
+/// # Arguments
+/// * `policy` - 再試行ポリシー
+/// * `status` - HTTPステータスコード
+#[uniffi::export]
+pub fn is_retryable_status(policy: RetryPolicy, status: u16) -> bool {
+    policy.retries_status(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(jitter: f64) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            base_delay_ms: 100,
+            max_delay_ms: 500,
+            jitter,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_delay_exponential_backoff() {
+        let policy = policy(0.0);
+        let delays: Vec<Option<u64>> = (1..=5).map(|a| policy.delay_with(a, 0.5)).collect();
+        assert_eq!(delays, vec![Some(100), Some(200), Some(400), Some(500), None]);
+        assert_eq!(policy.delay_with(0, 0.5), None);
+    }
+
+    #[test]
+    fn test_delay_jitter_bounds() {
+        let policy = policy(0.2);
+        assert_eq!(policy.delay_with(2, 0.0), Some(160));
+        assert_eq!(policy.delay_with(2, 0.5), Some(200));
+        for _ in 0..100 {
+            let delay = next_delay(policy.clone(), 2).unwrap();
+            assert!((160..=240).contains(&delay), "delay out of range: {}", delay);
+        }
+    }
+
+    #[test]
+    fn test_delay_clamps_invalid_values() {
+        let policy = RetryPolicy { multiplier: 0.1, jitter: 5.0, ..policy(0.0) };
+        assert_eq!(policy.delay_with(3, 0.0), Some(0));
+        assert_eq!(policy.delay_with(3, 0.75), Some(150));
+        let single = RetryPolicy { max_attempts: 1, ..Default::default() };
+        assert_eq!(next_delay(single, 1), None);
+    }
+
+    #[test]
+    fn test_is_retryable_status() {
+        let policy = default_retry_policy();
+        assert!(is_retryable_status(policy.clone(), 503));
+        assert!(is_retryable_status(policy.clone(), 429));
+        assert!(!is_retryable_status(policy.clone(), 404));
+        assert!(!is_retryable_status(policy, 200));
+    }
+}
@@ -0,0 +1,446 @@
+//! ディープリンクのルーティングモジュール
+//!
+//! このモジュールは、`/items/{id}/comments`のようなパターンでルートを登録し、
+//! URLに一致するルートの名前と型付きのパラメータを返す`Router`をエクスポートします。
+//! ユニバーサルリンク（App Links）とカスタムスキームのルーティングをiOSとAndroidで
+//! 共有するために使用します。
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use percent_encoding::percent_decode_str;
+use thiserror::Error;
+use url::Url;
+
+use crate::encoding::QueryParam;
+
+/// パスだけのURL（`/items/1`）を解析するための基準URL
+const PATH_ONLY_BASE: &str = "https://localhost";
+
+/// ルーティングで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum RouterError {
+    /// パターンの形式が正しくない場合
+    #[error("Invalid route pattern: {0}")]
+    InvalidPattern(String),
+    /// 同じ名前のルートが既に登録されている場合
+    #[error("Route already registered: {0}")]
+    DuplicateRoute(String),
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+}
+
+/// ルートのパラメータの値
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum RouteValue {
+    /// 文字列（`{name}`、`{name:path}`は`/`区切りの残りのパス）
+    Text { value: String },
+    /// 整数（`{name:int}`）
+    Integer { value: i64 },
+    /// UUID（`{name:uuid}`、小文字のハイフン区切り）
+    Uuid { value: String },
+}
+
+/// URLに一致したルート
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct RouteMatch {
+    /// ルートの名前
+    pub name: String,
+    /// パスのパラメータ（パーセントデコード済み）
+    pub parameters: HashMap<String, RouteValue>,
+    /// クエリのパラメータ（出現順）
+    pub query: Vec<QueryParam>,
+    /// フラグメント（`#`を含みません）
+    pub fragment: Option<String>,
+}
+
+/// パラメータの型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParameterKind {
+    Text,
+    Integer,
+    Uuid,
+    /// 1つ以上の残りのセグメント（パターンの末尾のみ）
+    Path,
+}
+
+/// パターンのセグメント
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Parameter { name: String, kind: ParameterKind },
+}
+
+impl Segment {
+    /// 一致したルートが複数ある場合の優先度（固定の文字列 > 整数・UUID > 文字列 > 残りのパス）
+    fn specificity(&self) -> u8 {
+        match self {
+            Segment::Literal(_) => 3,
+            Segment::Parameter { kind: ParameterKind::Integer | ParameterKind::Uuid, .. } => 2,
+            Segment::Parameter { kind: ParameterKind::Text, .. } => 1,
+            Segment::Parameter { kind: ParameterKind::Path, .. } => 0,
+        }
+    }
+}
+
+/// 登録済みのルート
+#[derive(Debug, Clone)]
+struct Route {
+    name: String,
+    segments: Vec<Segment>,
+}
+
+/// UUIDの形式（8-4-4-4-12桁の16進数）かどうかを返します
+fn is_uuid(text: &str) -> bool {
+    text.len() == 36
+        && text.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        })
+}
+
+/// パラメータの名前として使用できるかどうかを返します
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// パスを空でないセグメントに分けます（先頭・末尾・連続する`/`は無視します）
+fn split_path(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// パターンを解析します
+fn parse_pattern(pattern: &str) -> Result<Vec<Segment>, RouterError> {
+    let invalid = |reason: &str| RouterError::InvalidPattern(format!("{}: {}", pattern, reason));
+    if !pattern.starts_with('/') {
+        return Err(invalid("pattern must start with '/'"));
+    }
+    let raw: Vec<&str> = split_path(pattern).collect();
+    let mut segments = Vec::with_capacity(raw.len());
+    for (i, segment) in raw.iter().enumerate() {
+        let Some(inner) = segment.strip_prefix('{').and_then(|rest| rest.strip_suffix('}')) else {
+            if segment.contains(['{', '}']) {
+                return Err(invalid("a parameter must be a whole segment"));
+            }
+            segments.push(Segment::Literal(segment.to_string()));
+            continue;
+        };
+        let (name, kind) = inner.split_once(':').unwrap_or((inner, "string"));
+        let kind = match kind {
+            "string" => ParameterKind::Text,
+            "int" => ParameterKind::Integer,
+            "uuid" => ParameterKind::Uuid,
+            "path" if i == raw.len() - 1 => ParameterKind::Path,
+            "path" => return Err(invalid("a path parameter must be the last segment")),
+            other => return Err(invalid(&format!("unknown parameter type '{}'", other))),
+        };
+        if !is_identifier(name) {
+            return Err(invalid(&format!("invalid parameter name '{}'", name)));
+        }
+        if segments.iter().any(|s| matches!(s, Segment::Parameter { name: n, .. } if n == name)) {
+            return Err(invalid(&format!("duplicate parameter '{}'", name)));
+        }
+        segments.push(Segment::Parameter { name: name.to_string(), kind });
+    }
+    Ok(segments)
+}
+
+/// デコード済みのセグメントがルートに一致する場合はパラメータを返します
+fn match_segments(route: &Route, path: &[String]) -> Option<HashMap<String, RouteValue>> {
+    let mut parameters = HashMap::new();
+    for (i, segment) in route.segments.iter().enumerate() {
+        let (name, kind) = match segment {
+            Segment::Literal(literal) => {
+                if path.get(i) != Some(literal) {
+                    return None;
+                }
+                continue;
+            }
+            Segment::Parameter { name, kind } => (name, *kind),
+        };
+        let value = match kind {
+            ParameterKind::Path if path.len() > i => {
+                RouteValue::Text { value: path[i..].join("/") }
+            }
+            ParameterKind::Path => return None,
+            ParameterKind::Text => RouteValue::Text { value: path.get(i)?.clone() },
+            ParameterKind::Integer => RouteValue::Integer { value: path.get(i)?.parse().ok()? },
+            ParameterKind::Uuid => {
+                let value = path.get(i)?;
+                if !is_uuid(value) {
+                    return None;
+                }
+                RouteValue::Uuid { value: value.to_ascii_lowercase() }
+            }
+        };
+        parameters.insert(name.clone(), value);
+    }
+    let consumes_rest =
+        matches!(route.segments.last(), Some(Segment::Parameter { kind: ParameterKind::Path, .. }));
+    (consumes_rest || route.segments.len() == path.len()).then_some(parameters)
+}
+
+/// ディープリンクのルーター
+///
+/// パターンは`/`区切りのセグメントで、`{name}`（任意の文字列）・`{name:int}`（整数）・
+/// `{name:uuid}`（UUID）・`{name:path}`（末尾の1つ以上のセグメント）のパラメータを
+/// 含められます。URLに一致するルートが複数ある場合は、先頭のセグメントから順に
+/// より具体的なセグメント（固定の文字列 > 整数・UUID > 文字列 > 残りのパス）を持つルートを、
+/// 同じ場合は先に登録したルートを選びます。複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let router = Router::new(vec!["example.com".to_string()]);
+/// router.register("comments".to_string(), "/items/{id:int}/comments".to_string())?;
+/// router.register("new_item".to_string(), "/items/new".to_string())?;
+///
+/// let matched = router.match_url("https://example.com/items/42/comments?sort=new".to_string())?;
+/// // Some(RouteMatch { name: "comments", parameters: {"id": Integer { value: 42 }}, .. })
+/// ```
+#[derive(uniffi::Object)]
+pub struct Router {
+    hosts: Vec<String>,
+    routes: Mutex<Vec<Route>>,
+}
+
+#[uniffi::export]
+impl Router {
+    /// ルーターを作成します
+    ///
+    /// # Arguments
+    /// * `hosts` - `http`・`https`のURLで受け付けるホスト（空の場合はすべてのホスト）。
+    ///   カスタムスキーム（`myapp://items/1`）のURLではホストをパスの先頭として扱います
+    #[uniffi::constructor(default(hosts = []))]
+    pub fn new(hosts: Vec<String>) -> Arc<Self> {
+        let hosts = hosts.iter().map(|host| host.trim().to_ascii_lowercase()).collect();
+        Arc::new(Self { hosts, routes: Mutex::new(Vec::new()) })
+    }
+
+    /// ルートを登録します
+    ///
+    /// # Arguments
+    /// * `name` - ルートの名前（一致したときに`RouteMatch`で返されます）
+    /// * `pattern` - パスのパターン（`/items/{id:int}/comments`）
+    ///
+    /// # Errors
+    /// * `RouterError::InvalidPattern` - パターンの形式が正しくない場合
+    /// * `RouterError::DuplicateRoute` - 同じ名前のルートが既に登録されている場合
+    /// * `RouterError::MutexPoisoned` - Mutexがポイズン状態の場合
+    pub fn register(&self, name: String, pattern: String) -> Result<(), RouterError> {
+        let segments = parse_pattern(pattern.trim())?;
+        let mut routes = self.routes.lock()
+            .map_err(|_| RouterError::MutexPoisoned)?;
+        if routes.iter().any(|route| route.name == name) {
+            return Err(RouterError::DuplicateRoute(name));
+        }
+        routes.push(Route { name, segments });
+        Ok(())
+    }
+
+    /// 登録済みのルートの名前を登録順に返します
+    ///
+    /// # Errors
+    /// * `RouterError::MutexPoisoned` - Mutexがポイズン状態の場合
+    pub fn route_names(&self) -> Result<Vec<String>, RouterError> {
+        let routes = self.routes.lock()
+            .map_err(|_| RouterError::MutexPoisoned)?;
+        Ok(routes.iter().map(|route| route.name.clone()).collect())
+    }
+
+    /// URLに一致するルートを返します
+    ///
+    /// 絶対URLのほか、パスだけのURL（`/items/1?ref=push`）も受け付けます。
+    /// パスの末尾の`/`は無視します。
+    ///
+    /// # Arguments
+    /// * `url` - ユニバーサルリンク・カスタムスキームのURL、またはパス
+    ///
+    /// # Returns
+    /// 一致するルートがない、URLとして解析できない、ホストが許可されていない場合は`None`
+    ///
+    /// # Errors
+    /// * `RouterError::MutexPoisoned` - Mutexがポイズン状態の場合
+    pub fn match_url(&self, url: String) -> Result<Option<RouteMatch>, RouterError> {
+        let url = url.trim();
+        let url = match url.strip_prefix('/') {
+            Some(_) => Url::parse(PATH_ONLY_BASE).and_then(|base| base.join(url)),
+            None => Url::parse(url),
+        };
+        let Ok(url) = url else {
+            return Ok(None);
+        };
+        let mut path: Vec<String> = Vec::new();
+        if matches!(url.scheme(), "http" | "https") {
+            let host = url.host_str().unwrap_or_default();
+            if !self.hosts.is_empty() && !self.hosts.iter().any(|allowed| allowed == host) {
+                return Ok(None);
+            }
+        } else if let Some(host) = url.host_str().filter(|host| !host.is_empty()) {
+            path.push(percent_decode_str(host).decode_utf8_lossy().into_owned());
+        }
+        path.extend(
+            split_path(url.path()).map(|s| percent_decode_str(s).decode_utf8_lossy().into_owned()),
+        );
+
+        let routes = self.routes.lock()
+            .map_err(|_| RouterError::MutexPoisoned)?;
+        let mut best: Option<(Vec<u8>, &Route, HashMap<String, RouteValue>)> = None;
+        for route in routes.iter() {
+            let Some(parameters) = match_segments(route, &path) else {
+                continue;
+            };
+            let specificity: Vec<u8> = route.segments.iter().map(Segment::specificity).collect();
+            if best.as_ref().is_none_or(|(current, _, _)| specificity > *current) {
+                best = Some((specificity, route, parameters));
+            }
+        }
+        let Some((_, route, parameters)) = best else {
+            return Ok(None);
+        };
+        Ok(Some(RouteMatch {
+            name: route.name.clone(),
+            parameters,
+            query: url
+                .query_pairs()
+                .map(|(name, value)| QueryParam {
+                    name: name.into_owned(),
+                    value: value.into_owned(),
+                })
+                .collect(),
+            fragment: url
+                .fragment()
+                .map(|f| percent_decode_str(f).decode_utf8_lossy().into_owned()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router(routes: &[(&str, &str)]) -> Arc<Router> {
+        let router = Router::new(Vec::new());
+        for (name, pattern) in routes {
+            router.register(name.to_string(), pattern.to_string()).unwrap();
+        }
+        router
+    }
+
+    fn matched(router: &Router, url: &str) -> RouteMatch {
+        router.match_url(url.to_string()).unwrap().unwrap()
+    }
+
+    fn matched_name(router: &Router, url: &str) -> Option<String> {
+        router.match_url(url.to_string()).unwrap().map(|matched| matched.name)
+    }
+
+    fn text(value: &str) -> RouteValue {
+        RouteValue::Text { value: value.to_string() }
+    }
+
+    #[test]
+    fn test_match_url_parameters() {
+        let router = router(&[
+            ("comments", "/items/{id:int}/comments"),
+            ("user", "/users/{name}"),
+            ("order", "/orders/{order_id:uuid}"),
+            ("docs", "/docs/{rest:path}"),
+        ]);
+        let comments =
+            matched(&router, "https://example.com/items/42/comments/?sort=new&page=2#c10");
+        assert_eq!(
+            comments,
+            RouteMatch {
+                name: "comments".to_string(),
+                parameters: HashMap::from([("id".to_string(), RouteValue::Integer { value: 42 })]),
+                query: vec![
+                    QueryParam { name: "sort".to_string(), value: "new".to_string() },
+                    QueryParam { name: "page".to_string(), value: "2".to_string() },
+                ],
+                fragment: Some("c10".to_string()),
+            }
+        );
+
+        let user = matched(&router, "https://example.com/users/%E5%A4%AA%E9%83%8E");
+        assert_eq!(user.parameters["name"], text("太郎"));
+        let order = matched(&router, "/orders/6F9619FF-8B86-D011-B42D-00C04FC964FF");
+        assert_eq!(
+            order.parameters["order_id"],
+            RouteValue::Uuid { value: "6f9619ff-8b86-d011-b42d-00c04fc964ff".to_string() }
+        );
+        let docs = matched(&router, "https://example.com/docs/guide/setup");
+        assert_eq!(docs.parameters["rest"], text("guide/setup"));
+
+        assert_eq!(matched_name(&router, "https://example.com/items/abc/comments"), None);
+        assert_eq!(matched_name(&router, "https://example.com/items/42"), None);
+        assert_eq!(matched_name(&router, "https://example.com/orders/not-a-uuid"), None);
+        assert_eq!(matched_name(&router, "https://example.com/docs"), None);
+        assert_eq!(matched_name(&router, "not a url"), None);
+    }
+
+    #[test]
+    fn test_match_url_precedence() {
+        let router = router(&[
+            ("catch_all", "/{rest:path}"),
+            ("item", "/items/{slug}"),
+            ("item_by_id", "/items/{id:int}"),
+            ("new_item", "/items/new"),
+            ("home", "/"),
+        ]);
+        assert_eq!(
+            matched_name(&router, "https://example.com/items/new").as_deref(),
+            Some("new_item")
+        );
+        assert_eq!(
+            matched_name(&router, "https://example.com/items/7").as_deref(),
+            Some("item_by_id")
+        );
+        assert_eq!(matched_name(&router, "https://example.com/items/red").as_deref(), Some("item"));
+        assert_eq!(
+            matched_name(&router, "https://example.com/about").as_deref(),
+            Some("catch_all")
+        );
+        assert_eq!(matched_name(&router, "https://example.com").as_deref(), Some("home"));
+        assert_eq!(matched_name(&router, "/").as_deref(), Some("home"));
+    }
+
+    #[test]
+    fn test_match_url_hosts_and_schemes() {
+        let router = Router::new(vec!["Example.com".to_string()]);
+        router.register("item".to_string(), "/items/{id:int}".to_string()).unwrap();
+        assert_eq!(matched_name(&router, "https://example.com/items/1").as_deref(), Some("item"));
+        assert_eq!(matched_name(&router, "https://EXAMPLE.com/items/1").as_deref(), Some("item"));
+        assert_eq!(matched_name(&router, "https://evil.example/items/1"), None);
+        // カスタムスキームではホストがパスの先頭になる
+        assert_eq!(matched_name(&router, "myapp://items/1").as_deref(), Some("item"));
+        assert_eq!(matched_name(&router, "myapp:///items/1").as_deref(), Some("item"));
+        assert_eq!(router.route_names().unwrap(), vec!["item"]);
+    }
+
+    #[test]
+    fn test_register_errors() {
+        let router = router(&[("item", "/items/{id}")]);
+        match router.register("item".to_string(), "/other".to_string()) {
+            Err(RouterError::DuplicateRoute(_)) => (),
+            other => panic!("Expected DuplicateRoute error, got {:?}", other),
+        }
+        for pattern in [
+            "items/{id}",
+            "/items/{id",
+            "/items/item-{id}",
+            "/items/{id:float}",
+            "/items/{1id}",
+            "/items/{}",
+            "/{id}/{id}",
+            "/{rest:path}/edit",
+        ] {
+            match router.register("other".to_string(), pattern.to_string()) {
+                Err(RouterError::InvalidPattern(_)) => (),
+                other => panic!("Expected InvalidPattern error for {}, got {:?}", pattern, other),
+            }
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! HTMLサニタイズモジュール
+//!
+//! このモジュールは、サーバーから配信されたリッチテキストをWebViewで安全に表示するために、
+//! スクリプトやイベントハンドラーを取り除き、タグと属性を許可リストに制限する
+//! `sanitize_html`をエクスポートします。HTMLはブラウザーと同じ規則（html5ever）で解析するため、
+//! 閉じ忘れや不正な入れ子のHTMLも正しく処理されます。
+
+use std::collections::HashSet;
+
+use ammonia::Builder;
+
+/// 常に中身ごと取り除くタグ
+const CLEAN_CONTENT_TAGS: [&str; 2] = ["script", "style"];
+
+/// 既定で許可するURLのスキーム
+const DEFAULT_URL_SCHEMES: [&str; 4] = ["http", "https", "mailto", "tel"];
+
+/// リンクに付けるrel属性
+const LINK_REL: &str = "noopener noreferrer";
+
+/// HTMLサニタイズのポリシー
+#[derive(Debug, Clone, Default, PartialEq, Eq, uniffi::Record)]
+pub struct HtmlPolicy {
+    /// 許可するタグ（`None`の場合は`p`・`a`・`img`・`table`などの一般的な書式のタグ）
+    #[uniffi(default = None)]
+    pub allowed_tags: Option<Vec<String>>,
+    /// 許可するタグのすべてで許可する属性
+    /// （`None`の場合は`a`の`href`・`img`の`src`と`alt`などのタグごとの既定の属性）
+    #[uniffi(default = None)]
+    pub allowed_attributes: Option<Vec<String>>,
+    /// `href`・`src`などのURLで許可するスキーム（`None`の場合は`http`・`https`・`mailto`・`tel`）
+    #[uniffi(default = None)]
+    pub allowed_url_schemes: Option<Vec<String>>,
+}
+
+/// 名前を小文字にして重複を除いた一覧を返します
+fn normalized(names: &[String]) -> Vec<String> {
+    let mut names: Vec<String> =
+        names.iter().map(|name| name.trim().to_ascii_lowercase()).collect();
+    names.sort();
+    names.dedup();
+    names.retain(|name| !name.is_empty());
+    names
+}
+
+/// HTMLをポリシーに従ってサニタイズします
+///
+/// 許可リストにないタグは取り除いて中身のテキストだけを残し、`script`と`style`は
+/// 中身ごと取り除きます。イベントハンドラー（`onclick`などの`on`で始まる属性）と
+/// コメントは常に取り除き、リンクには`rel="noopener noreferrer"`を付けます。
+/// 許可されないスキームのURLを持つ属性は取り除きます（相対URLは残します）。
+///
+/// # Arguments
+/// * `html` - サニタイズするHTMLの断片
+/// * `policy` - ポリシー（省略時は一般的な書式のタグと既定の属性を許可）
+///
+/// # Example
+/// ```
+/// let html = sanitize_html(
+///     "<p onclick=\"steal()\">Hi <script>alert(1)</script><b>there</b></p>".to_string(),
+///     None,
+/// );
+/// assert_eq!(html, "<p>Hi <b>there</b></p>");
+/// ```
+#[uniffi::export(default(policy = None))]
+pub fn sanitize_html(html: String, policy: Option<HtmlPolicy>) -> String {
+    let policy = policy.unwrap_or_default();
+    let tags = policy.allowed_tags.as_deref().map(normalized);
+    let attributes = policy.allowed_attributes.as_deref().map(normalized);
+    let schemes = policy.allowed_url_schemes.as_deref().map(normalized);
+
+    let mut builder = Builder::default();
+    builder.link_rel(Some(LINK_REL)).strip_comments(true);
+    builder.clean_content_tags(CLEAN_CONTENT_TAGS.into_iter().collect());
+    if let Some(tags) = &tags {
+        // 中身ごと取り除くタグは許可できない
+        builder.tags(
+            tags.iter()
+                .map(String::as_str)
+                .filter(|tag| !CLEAN_CONTENT_TAGS.contains(tag))
+                .collect(),
+        );
+    }
+    if let Some(attributes) = &attributes {
+        // イベントハンドラーは許可せず、relはリンクに付ける値で上書きする
+        let attributes: HashSet<&str> = attributes
+            .iter()
+            .map(String::as_str)
+            .filter(|attribute| !attribute.starts_with("on") && *attribute != "rel")
+            .collect();
+        builder.generic_attributes(attributes).tag_attributes(Default::default());
+    }
+    match &schemes {
+        Some(schemes) => builder.url_schemes(schemes.iter().map(String::as_str).collect()),
+        None => builder.url_schemes(DEFAULT_URL_SCHEMES.into_iter().collect()),
+    };
+    builder.clean(&html).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sanitize(html: &str) -> String {
+        sanitize_html(html.to_string(), None)
+    }
+
+    fn strings(values: &[&str]) -> Option<Vec<String>> {
+        Some(values.iter().map(|value| value.to_string()).collect())
+    }
+
+    #[test]
+    fn test_sanitize_html_default_policy() {
+        assert_eq!(
+            sanitize("<p onclick=\"steal()\">Hi <script>alert(1)</script><b>there</b></p>"),
+            "<p>Hi <b>there</b></p>"
+        );
+        assert_eq!(sanitize("<style>p { color: red }</style><!-- note --><i>x</i>"), "<i>x</i>");
+        assert_eq!(
+            sanitize("<a href=\"https://example.com\" target=\"_top\">link</a>"),
+            "<a href=\"https://example.com\" rel=\"noopener noreferrer\">link</a>"
+        );
+        assert_eq!(
+            sanitize("<a href=\"javascript:alert(1)\">x</a><img src=\"data:image/png;base64,AA\">"),
+            "<a rel=\"noopener noreferrer\">x</a><img>"
+        );
+        assert_eq!(
+            sanitize("<iframe src=\"https://evil.example\"></iframe><div><p>unclosed"),
+            "<div><p>unclosed</p></div>"
+        );
+        assert_eq!(sanitize("a < b &amp; c"), "a &lt; b &amp; c");
+    }
+
+    #[test]
+    fn test_sanitize_html_custom_policy() {
+        let policy = HtmlPolicy {
+            allowed_tags: strings(&["P", "a", "span", "script"]),
+            allowed_attributes: strings(&["href", "class", "onmouseover", "rel"]),
+            allowed_url_schemes: strings(&["https"]),
+        };
+        let html = sanitize_html(
+            concat!(
+                "<p class=\"lead\" onmouseover=\"x()\"><b>bold</b> <span title=\"t\">s</span>",
+                "<a href=\"http://a.example\" rel=\"opener\">a</a>",
+                "<a href=\"https://b.example\">b</a><script>x()</script></p>"
+            )
+            .to_string(),
+            Some(policy),
+        );
+        assert_eq!(
+            html,
+            concat!(
+                "<p class=\"lead\">bold <span>s</span>",
+                "<a rel=\"noopener noreferrer\">a</a>",
+                "<a href=\"https://b.example\" rel=\"noopener noreferrer\">b</a></p>"
+            )
+        );
+
+        let text_only = HtmlPolicy { allowed_tags: Some(Vec::new()), ..Default::default() };
+        assert_eq!(
+            sanitize_html("<h1>Title</h1><p>Body</p>".to_string(), Some(text_only)),
+            "TitleBody"
+        );
+    }
+}
@@ -0,0 +1,589 @@
+//! スキャン結果解析モジュール
+//!
+//! このモジュールは、QRコードやバーコードから読み取った文字列を分類し、
+//! 種類ごとの型付きレコードに解析する`parse_scanned_payload`をエクスポートします。
+//! スキャン後の処理（Wi-Fiへの接続、連絡先の追加、送金画面の表示など）の
+//! 判定ロジックをプラットフォームごとに重複させないために使用します。
+//!
+//! | 形式 | 判定条件 | 結果 |
+//! |------|----------|------|
+//! | Wi-Fi設定 | `WIFI:`で始まる | `ScannedPayload::Wifi` |
+//! | vCard | `BEGIN:VCARD`で始まる | `ScannedPayload::Contact` |
+//! | URL | `http://`・`https://`で始まる | `ScannedPayload::Url` |
+//! | ワンタイムパスワード | `otpauth://totp/`・`otpauth://hotp/`で始まる | `ScannedPayload::Totp`・`Hotp` |
+//! | EPC QR（SEPA送金） | 1行目が`BCD` | `ScannedPayload::Payment` |
+//! | その他 | - | `ScannedPayload::Text` |
+
+use thiserror::Error;
+use url::Url;
+
+use crate::otp::{Hotp, HotpConfig, Totp, TotpConfig};
+
+/// EPC QRの金額の上限（999,999,999.99ユーロ）
+const MAX_EPC_AMOUNT_CENTS: u64 = 99_999_999_999;
+
+/// スキャン結果の解析で発生する可能性のあるエラー
+///
+/// 形式の判定条件には一致したものの、内容が不正な場合に返されます。
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum ScanError {
+    /// Wi-Fi設定の内容が不正な場合
+    #[error("Invalid Wi-Fi payload: {0}")]
+    InvalidWifi(String),
+    /// vCardの内容が不正な場合
+    #[error("Invalid vCard payload: {0}")]
+    InvalidContact(String),
+    /// URLとして不正な場合
+    #[error("Invalid URL payload: {0}")]
+    InvalidUrl(String),
+    /// `otpauth://`URIの内容が不正な場合
+    #[error("Invalid otpauth payload: {0}")]
+    InvalidOtp(String),
+    /// EPC QRの内容が不正な場合
+    #[error("Invalid EPC payment payload: {0}")]
+    InvalidPayment(String),
+}
+
+/// Wi-Fiの認証方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum WifiSecurity {
+    /// 認証なし
+    Open,
+    /// WEP
+    Wep,
+    /// WPA・WPA2（エンタープライズを含む）
+    Wpa,
+    /// WPA3（SAE）
+    Wpa3,
+}
+
+/// Wi-Fiの接続設定
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct WifiConfig {
+    /// ネットワーク名
+    pub ssid: String,
+    /// パスワード（認証なしの場合は`None`）
+    pub password: Option<String>,
+    /// 認証方式
+    pub security: WifiSecurity,
+    /// SSIDをブロードキャストしないネットワークかどうか
+    pub hidden: bool,
+}
+
+/// vCardから取り出した連絡先
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ContactCard {
+    /// 表示名（`FN`、なければ`N`から組み立てた名前）
+    pub full_name: Option<String>,
+    /// 組織名
+    pub organization: Option<String>,
+    /// 役職
+    pub title: Option<String>,
+    /// 電話番号の一覧
+    pub phones: Vec<String>,
+    /// メールアドレスの一覧
+    pub emails: Vec<String>,
+    /// URLの一覧
+    pub urls: Vec<String>,
+    /// 住所の一覧（各要素を`, `で連結したもの）
+    pub addresses: Vec<String>,
+    /// メモ
+    pub note: Option<String>,
+}
+
+/// EPC QR（欧州送金協議会のSEPA送金用QRコード）の送金情報
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct EpcPayment {
+    /// 受取人の銀行のBIC（バージョン002では省略可能）
+    pub bic: Option<String>,
+    /// 受取人名
+    pub name: String,
+    /// 受取人のIBAN（空白を除いた大文字）
+    pub iban: String,
+    /// 通貨コード（EPC QRでは常に`EUR`、金額がない場合は`None`）
+    pub currency: Option<String>,
+    /// 金額（セント単位、指定がない場合は`None`）
+    pub amount_cents: Option<u64>,
+    /// 送金目的コード（例: `CHAR`）
+    pub purpose: Option<String>,
+    /// 構造化された送金参照（RF参照など）
+    pub reference: Option<String>,
+    /// 受取人向けの非構造化メッセージ
+    pub remittance_text: Option<String>,
+    /// 送金人向けの情報
+    pub information: Option<String>,
+}
+
+/// スキャン結果の種類と内容
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum ScannedPayload {
+    /// Wi-Fiの接続設定
+    Wifi { config: WifiConfig },
+    /// 連絡先（vCard）
+    Contact { card: ContactCard },
+    /// Webページ（正規化済みのURL）
+    Url { url: String },
+    /// 時間ベースのワンタイムパスワードの登録情報
+    Totp { config: TotpConfig },
+    /// カウンタベースのワンタイムパスワードの登録情報
+    Hotp { config: HotpConfig },
+    /// SEPA送金の情報
+    Payment { payment: EpcPayment },
+    /// 上記以外の文字列
+    Text { text: String },
+}
+
+/// 文字列が大文字・小文字を区別せずに接頭辞で始まるかを判定します
+fn has_prefix_ignore_case(text: &str, prefix: &str) -> bool {
+    text.get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+}
+
+/// `WIFI:T:WPA;S:ssid;P:password;H:true;;`形式のWi-Fi設定を解析します
+fn parse_wifi(text: &str) -> Result<WifiConfig, ScanError> {
+    let body = &text["WIFI:".len()..];
+    let mut fields = Vec::new();
+    let mut key = String::new();
+    let mut value = String::new();
+    let mut in_value = false;
+    let mut chars = body.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = chars.next().ok_or_else(|| {
+                    ScanError::InvalidWifi("dangling escape character".to_string())
+                })?;
+                if in_value {
+                    value.push(escaped);
+                } else {
+                    key.push(escaped);
+                }
+            }
+            ':' if !in_value => in_value = true,
+            ';' => {
+                if key.is_empty() && !in_value {
+                    break;
+                }
+                fields.push((key.trim().to_ascii_uppercase(), std::mem::take(&mut value)));
+                key.clear();
+                in_value = false;
+            }
+            c if in_value => value.push(c),
+            c => key.push(c),
+        }
+    }
+    if in_value || !key.trim().is_empty() {
+        fields.push((key.trim().to_ascii_uppercase(), value));
+    }
+
+    let mut ssid = None;
+    let mut password = None;
+    let mut security_type = None;
+    let mut hidden = false;
+    for (key, value) in fields {
+        match key.as_str() {
+            "S" => ssid = Some(value),
+            "P" => password = Some(value).filter(|p| !p.is_empty()),
+            "T" => security_type = Some(value),
+            "H" => hidden = value.eq_ignore_ascii_case("true"),
+            _ => {}
+        }
+    }
+    let ssid = ssid
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| ScanError::InvalidWifi("missing SSID".to_string()))?;
+    let security = match security_type.as_deref().map(str::to_ascii_uppercase).as_deref() {
+        None | Some("") | Some("NOPASS") => WifiSecurity::Open,
+        Some("WEP") => WifiSecurity::Wep,
+        Some("SAE") | Some("WPA3") => WifiSecurity::Wpa3,
+        Some(t) if t.starts_with("WPA") => WifiSecurity::Wpa,
+        Some(t) => return Err(ScanError::InvalidWifi(format!("unknown security type: {}", t))),
+    };
+    if security == WifiSecurity::Open {
+        password = None;
+    } else if password.is_none() {
+        return Err(ScanError::InvalidWifi("missing password".to_string()));
+    }
+    Ok(WifiConfig {
+        ssid,
+        password,
+        security,
+        hidden,
+    })
+}
+
+/// vCardのエスケープ（`\n`、`\,`、`\;`、`\\`）を解除します
+pub(crate) fn unescape_vcard(value: &str) -> String {
+    let mut output = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => output.push('\n'),
+            Some(escaped) => output.push(escaped),
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
+/// エスケープされていない`;`で値を分割します
+fn split_structured(value: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (i, c) in value.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            ';' if !escaped => {
+                parts.push(unescape_vcard(&value[start..i]).trim().to_string());
+                start = i + 1;
+            }
+            _ => escaped = false,
+        }
+    }
+    parts.push(unescape_vcard(&value[start..]).trim().to_string());
+    parts
+}
+
+/// vCard（2.1・3.0・4.0）から連絡先を取り出します
+fn parse_vcard(text: &str) -> Result<ContactCard, ScanError> {
+    // 空白またはタブで始まる行は前の行の続き（折り返し）
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.lines() {
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ => lines.push(line.to_string()),
+        }
+    }
+    if !lines.iter().any(|line| line.trim().eq_ignore_ascii_case("END:VCARD")) {
+        return Err(ScanError::InvalidContact("missing END:VCARD".to_string()));
+    }
+
+    let mut card = ContactCard {
+        full_name: None,
+        organization: None,
+        title: None,
+        phones: Vec::new(),
+        emails: Vec::new(),
+        urls: Vec::new(),
+        addresses: Vec::new(),
+        note: None,
+    };
+    let mut structured_name = None;
+    for line in &lines {
+        let Some((property, value)) = line.split_once(':') else {
+            continue;
+        };
+        // `item1.TEL;TYPE=CELL`のようなグループ名とパラメータを除く
+        let name = property.split(';').next().unwrap_or_default();
+        let name = name.rsplit('.').next().unwrap_or_default().to_ascii_uppercase();
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match name.as_str() {
+            "FN" => card.full_name = Some(unescape_vcard(value)),
+            "N" => structured_name = Some(split_structured(value)),
+            "ORG" => {
+                let units: Vec<String> =
+                    split_structured(value).into_iter().filter(|s| !s.is_empty()).collect();
+                card.organization = Some(units.join(", "));
+            }
+            "TITLE" => card.title = Some(unescape_vcard(value)),
+            "TEL" => card.phones.push(unescape_vcard(value)),
+            "EMAIL" => card.emails.push(unescape_vcard(value)),
+            "URL" => card.urls.push(unescape_vcard(value)),
+            "ADR" => {
+                let parts: Vec<String> =
+                    split_structured(value).into_iter().filter(|s| !s.is_empty()).collect();
+                if !parts.is_empty() {
+                    card.addresses.push(parts.join(", "));
+                }
+            }
+            "NOTE" => card.note = Some(unescape_vcard(value)),
+            _ => {}
+        }
+    }
+    if card.full_name.is_none() {
+        // N: 姓;名;ミドルネーム;敬称;接尾辞
+        card.full_name = structured_name.and_then(|parts| {
+            let order = [3, 1, 2, 0, 4];
+            let name: Vec<&str> = order
+                .iter()
+                .filter_map(|&i| parts.get(i).map(String::as_str))
+                .filter(|s| !s.is_empty())
+                .collect();
+            (!name.is_empty()).then(|| name.join(" "))
+        });
+    }
+    Ok(card)
+}
+
+/// IBANのチェックディジット（ISO 13616、mod 97）を検証します
+fn is_valid_iban(iban: &str) -> bool {
+    if !(15..=34).contains(&iban.len()) || !iban.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return false;
+    }
+    let (head, tail) = iban.split_at(4);
+    let remainder = tail.chars().chain(head.chars()).try_fold(0u32, |acc, c| {
+        let digit = c.to_digit(36)?;
+        let scale = if digit < 10 { 10 } else { 100 };
+        Some((acc * scale + digit) % 97)
+    });
+    remainder == Some(1)
+}
+
+/// `EUR12.50`形式の金額をセント単位に変換します
+fn parse_epc_amount(value: &str) -> Result<u64, ScanError> {
+    let invalid = || ScanError::InvalidPayment(format!("invalid amount: {}", value));
+    let number = value.strip_prefix("EUR").ok_or_else(invalid)?;
+    let (euros, cents) = number.split_once('.').unwrap_or((number, ""));
+    if euros.is_empty()
+        || cents.len() > 2
+        || !euros.chars().chain(cents.chars()).all(|c| c.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let euros: u64 = euros.parse().map_err(|_| invalid())?;
+    let cents: u64 = format!("{:0<2}", cents).parse().map_err(|_| invalid())?;
+    let amount = euros.checked_mul(100).and_then(|e| e.checked_add(cents)).ok_or_else(invalid)?;
+    if amount == 0 || amount > MAX_EPC_AMOUNT_CENTS {
+        return Err(invalid());
+    }
+    Ok(amount)
+}
+
+/// EPC QR（EPC069-12）の送金情報を解析します
+fn parse_epc(text: &str) -> Result<EpcPayment, ScanError> {
+    let lines: Vec<&str> = text.lines().map(str::trim).collect();
+    let field = |index: usize| {
+        lines
+            .get(index)
+            .copied()
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    let version = lines.get(1).copied().unwrap_or_default();
+    if version != "001" && version != "002" {
+        return Err(ScanError::InvalidPayment(format!("unsupported version: {}", version)));
+    }
+    if lines.get(3).copied() != Some("SCT") {
+        return Err(ScanError::InvalidPayment("identification must be SCT".to_string()));
+    }
+    let bic = field(4);
+    if version == "001" && bic.is_none() {
+        return Err(ScanError::InvalidPayment("BIC is required in version 001".to_string()));
+    }
+    let name = field(5).ok_or_else(|| ScanError::InvalidPayment("missing name".to_string()))?;
+    let iban: String = field(6)
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if !is_valid_iban(&iban) {
+        return Err(ScanError::InvalidPayment(format!("invalid IBAN: {}", iban)));
+    }
+    let amount_cents = field(7).map(|amount| parse_epc_amount(&amount)).transpose()?;
+    Ok(EpcPayment {
+        bic,
+        name,
+        iban,
+        currency: amount_cents.map(|_| "EUR".to_string()),
+        amount_cents,
+        purpose: field(8),
+        reference: field(9),
+        remittance_text: field(10),
+        information: field(11),
+    })
+}
+
+/// QRコード・バーコードから読み取った文字列を分類して解析します
+///
+/// 形式の判定条件はモジュールのドキュメントを参照してください。
+/// どの形式にも当てはまらない文字列は`ScannedPayload::Text`になります。
+///
+/// # Arguments
+/// * `text` - スキャンで読み取った文字列
+///
+/// # Errors
+/// 形式の判定条件に一致したものの内容が不正な場合、その形式に対応するエラーを返します。
+/// * `ScanError::InvalidWifi` - SSIDやパスワードがない、認証方式が不明な場合
+/// * `ScanError::InvalidContact` - vCardが終端していない場合
+/// * `ScanError::InvalidUrl` - URLとして解析できない場合
+/// * `ScanError::InvalidOtp` - 共有秘密・パラメータが不正な場合
+/// * `ScanError::InvalidPayment` - 必須項目がない、IBANや金額が不正な場合
+///
+/// # Example
+/// ```
+/// match parse_scanned_payload("WIFI:T:WPA;S:Cafe;P:secret;;".to_string())? {
+///     ScannedPayload::Wifi { config } => { /* config.ssid == "Cafe" */ }
+///     _ => {}
+/// }
+/// ```
+#[uniffi::export]
+pub fn parse_scanned_payload(text: String) -> Result<ScannedPayload, ScanError> {
+    let trimmed = text.trim();
+    if has_prefix_ignore_case(trimmed, "WIFI:") {
+        return Ok(ScannedPayload::Wifi {
+            config: parse_wifi(trimmed)?,
+        });
+    }
+    if has_prefix_ignore_case(trimmed, "BEGIN:VCARD") {
+        return Ok(ScannedPayload::Contact {
+            card: parse_vcard(trimmed)?,
+        });
+    }
+    if has_prefix_ignore_case(trimmed, "http://") || has_prefix_ignore_case(trimmed, "https://")
+    {
+        let url = Url::parse(trimmed).map_err(|e| ScanError::InvalidUrl(e.to_string()))?;
+        return Ok(ScannedPayload::Url {
+            url: url.to_string(),
+        });
+    }
+    if has_prefix_ignore_case(trimmed, "otpauth://totp/") {
+        let totp = Totp::from_uri(trimmed.to_string())
+            .map_err(|e| ScanError::InvalidOtp(e.to_string()))?;
+        return Ok(ScannedPayload::Totp {
+            config: totp.config(),
+        });
+    }
+    if has_prefix_ignore_case(trimmed, "otpauth://hotp/") {
+        let config = Hotp::from_uri(trimmed.to_string())
+            .and_then(|hotp| hotp.config())
+            .map_err(|e| ScanError::InvalidOtp(e.to_string()))?;
+        return Ok(ScannedPayload::Hotp { config });
+    }
+    if trimmed.lines().next().map(str::trim) == Some("BCD") {
+        return Ok(ScannedPayload::Payment {
+            payment: parse_epc(trimmed)?,
+        });
+    }
+    Ok(ScannedPayload::Text { text })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_wifi_payload() {
+        let payload = r#"WIFI:T:WPA;S:My\;Cafe;P:p\:ss\\word;H:true;;"#;
+        match parse_scanned_payload(payload.to_string()).unwrap() {
+            ScannedPayload::Wifi { config } => {
+                assert_eq!(config.ssid, "My;Cafe");
+                assert_eq!(config.password.as_deref(), Some(r"p:ss\word"));
+                assert_eq!(config.security, WifiSecurity::Wpa);
+                assert!(config.hidden);
+            }
+            other => panic!("Expected Wifi payload, got {:?}", other),
+        }
+        match parse_scanned_payload("WIFI:S:Guest;T:nopass;P:;;".to_string()).unwrap() {
+            ScannedPayload::Wifi { config } => {
+                assert_eq!(config.security, WifiSecurity::Open);
+                assert_eq!(config.password, None);
+            }
+            other => panic!("Expected Wifi payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_vcard_payload() {
+        let payload = "BEGIN:VCARD\r\nVERSION:3.0\r\nN:Yamada;Taro;;;\r\n\
+                       ORG:Example\\, Inc.;Mobile\r\nTEL;TYPE=CELL:+81-90-1234-5678\r\n\
+                       item1.EMAIL;TYPE=INTERNET:taro@example.com\r\n\
+                       ADR;TYPE=WORK:;;1-2-3 Chiyoda;Tokyo;;100-0001;Japan\r\n\
+                       NOTE:line1\\nline2 is a long\r\n  folded note\r\nEND:VCARD";
+        match parse_scanned_payload(payload.to_string()).unwrap() {
+            ScannedPayload::Contact { card } => {
+                assert_eq!(card.full_name.as_deref(), Some("Taro Yamada"));
+                assert_eq!(card.organization.as_deref(), Some("Example, Inc., Mobile"));
+                assert_eq!(card.phones, vec!["+81-90-1234-5678"]);
+                assert_eq!(card.emails, vec!["taro@example.com"]);
+                assert_eq!(card.addresses, vec!["1-2-3 Chiyoda, Tokyo, 100-0001, Japan"]);
+                assert_eq!(card.note.as_deref(), Some("line1\nline2 is a long folded note"));
+            }
+            other => panic!("Expected Contact payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_url_and_text_payloads() {
+        assert_eq!(
+            parse_scanned_payload("  HTTPS://Example.com/a b ".to_string()).unwrap(),
+            ScannedPayload::Url {
+                url: "https://example.com/a%20b".to_string()
+            }
+        );
+        assert_eq!(
+            parse_scanned_payload("4901234567894".to_string()).unwrap(),
+            ScannedPayload::Text {
+                text: "4901234567894".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_otpauth_payloads() {
+        let totp = "otpauth://totp/ACME:alice@example.com?secret=JBSWY3DPEHPK3PXP&issuer=ACME";
+        match parse_scanned_payload(totp.to_string()).unwrap() {
+            ScannedPayload::Totp { config } => {
+                assert_eq!(config.issuer.as_deref(), Some("ACME"));
+                assert_eq!(config.account_name.as_deref(), Some("alice@example.com"));
+            }
+            other => panic!("Expected Totp payload, got {:?}", other),
+        }
+        let hotp = "otpauth://hotp/alice?secret=JBSWY3DPEHPK3PXP&counter=7";
+        match parse_scanned_payload(hotp.to_string()).unwrap() {
+            ScannedPayload::Hotp { config } => assert_eq!(config.counter, 7),
+            other => panic!("Expected Hotp payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_epc_payload() {
+        let payload = "BCD\n002\n1\nSCT\n\nRed Cross\nBE72 0000 0000 1616\nEUR12.5\nCHAR\n\n\
+                       Donation\n";
+        match parse_scanned_payload(payload.to_string()).unwrap() {
+            ScannedPayload::Payment { payment } => {
+                assert_eq!(payment.bic, None);
+                assert_eq!(payment.name, "Red Cross");
+                assert_eq!(payment.iban, "BE72000000001616");
+                assert_eq!(payment.currency.as_deref(), Some("EUR"));
+                assert_eq!(payment.amount_cents, Some(1250));
+                assert_eq!(payment.purpose.as_deref(), Some("CHAR"));
+                assert_eq!(payment.reference, None);
+                assert_eq!(payment.remittance_text.as_deref(), Some("Donation"));
+            }
+            other => panic!("Expected Payment payload, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_parse_scanned_payload_errors() {
+        match parse_scanned_payload("WIFI:T:WPA;P:secret;;".to_string()) {
+            Err(ScanError::InvalidWifi(_)) => (),
+            other => panic!("Expected InvalidWifi error, got {:?}", other),
+        }
+        match parse_scanned_payload("BEGIN:VCARD\nFN:Alice".to_string()) {
+            Err(ScanError::InvalidContact(_)) => (),
+            other => panic!("Expected InvalidContact error, got {:?}", other),
+        }
+        match parse_scanned_payload("https://exa mple.com".to_string()) {
+            Err(ScanError::InvalidUrl(_)) => (),
+            other => panic!("Expected InvalidUrl error, got {:?}", other),
+        }
+        match parse_scanned_payload("otpauth://totp/alice?secret=!!".to_string()) {
+            Err(ScanError::InvalidOtp(_)) => (),
+            other => panic!("Expected InvalidOtp error, got {:?}", other),
+        }
+        match parse_scanned_payload("BCD\n002\n1\nSCT\n\nShop\nBE72000000001617".to_string()) {
+            Err(ScanError::InvalidPayment(_)) => (),
+            other => panic!("Expected InvalidPayment error, got {:?}", other),
+        }
+    }
+}
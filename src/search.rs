@@ -0,0 +1,531 @@
+//! 全文検索インデックスモジュール
+//!
+//! このモジュールは、ユーザーのコンテンツをオフラインで検索するための
+//! `SearchIndex`をエクスポートします。転置インデックスはSQLiteファイルに保存されます。
+//!
+//! # トークン化
+//! 英数字の連続は小文字化して1語とし、漢字・かな・ハングルの連続は
+//! 2文字ずつ重ねて区切ったバイグラムとします（例: `東京都` → `東京`・`京都`）。
+//! 分かち書きの辞書を持たずに日本語・中国語・韓国語を検索できます。
+//!
+//! # 検索クエリ
+//! 空白で区切った語をすべて含むドキュメントを返します（AND検索）。
+//! 語の末尾に`*`を付けると前方一致になります（例: `swi*`）。
+//! 1文字の漢字・かな・ハングルは、その文字で始まるバイグラムに前方一致します。
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use rusqlite::{params, Connection};
+use thiserror::Error;
+
+/// インデックスを保存するテーブルの作成SQL
+const CREATE_TABLES: &str = "
+CREATE TABLE IF NOT EXISTS search_documents (
+    id TEXT PRIMARY KEY
+) WITHOUT ROWID;
+CREATE TABLE IF NOT EXISTS search_postings (
+    term TEXT NOT NULL,
+    doc_id TEXT NOT NULL,
+    field TEXT NOT NULL,
+    start INTEGER NOT NULL,
+    end INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS search_postings_term ON search_postings (term);
+CREATE INDEX IF NOT EXISTS search_postings_doc ON search_postings (doc_id);
+";
+
+/// 全文検索の操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum SearchError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// クエリに検索できる語が含まれていない場合
+    #[error("Query contains no searchable terms")]
+    EmptyQuery,
+    /// データベースの操作に失敗した場合
+    #[error("Search index storage error: {0}")]
+    StorageError(String),
+}
+
+impl From<rusqlite::Error> for SearchError {
+    fn from(error: rusqlite::Error) -> Self {
+        SearchError::StorageError(error.to_string())
+    }
+}
+
+/// 一致した箇所
+///
+/// オフセットはUTF-16のコード単位で表すため、Swiftの`NSRange`にそのまま変換できます。
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SearchHighlight {
+    /// フィールド名
+    pub field: String,
+    /// 開始位置（UTF-16のコード単位）
+    pub start: u32,
+    /// 終了位置（UTF-16のコード単位、この位置を含まない）
+    pub end: u32,
+}
+
+/// 検索結果の1件
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SearchHit {
+    /// ドキュメントのID
+    pub id: String,
+    /// 関連度（大きいほど関連が高い）
+    pub score: f64,
+    /// 一致した箇所（フィールド名・開始位置の順、重なる箇所は結合済み）
+    pub highlights: Vec<SearchHighlight>,
+}
+
+/// テキスト中の語とその位置
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    term: String,
+    start: u32,
+    end: u32,
+}
+
+/// 漢字・かな・ハングルなど、語を空白で区切らない文字かを判定します
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x1100..=0x11FF        // ハングル字母
+        | 0x3005               // 々
+        | 0x3040..=0x30FF      // ひらがな・カタカナ
+        | 0x3130..=0x318F      // ハングル互換字母
+        | 0x31F0..=0x31FF      // カタカナ拡張
+        | 0x3400..=0x4DBF      // CJK統合漢字拡張A
+        | 0x4E00..=0x9FFF      // CJK統合漢字
+        | 0xAC00..=0xD7AF      // ハングル音節
+        | 0xF900..=0xFAFF      // CJK互換漢字
+        | 0xFF66..=0xFF9F      // 半角カタカナ
+        | 0x20000..=0x3FFFF)   // CJK統合漢字拡張B以降
+}
+
+/// テキストを語に分割します
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    // 現在の英数字の語（小文字化済み）と開始位置
+    let mut word = String::new();
+    let mut word_start = 0;
+    // 現在のCJKの連続（文字と開始・終了位置）
+    let mut cjk: Vec<(char, u32, u32)> = Vec::new();
+    let mut offset = 0u32;
+
+    let flush_word = |word: &mut String, start: u32, end: u32, tokens: &mut Vec<Token>| {
+        if !word.is_empty() {
+            tokens.push(Token {
+                term: std::mem::take(word),
+                start,
+                end,
+            });
+        }
+    };
+    let flush_cjk = |cjk: &mut Vec<(char, u32, u32)>, tokens: &mut Vec<Token>| {
+        if cjk.len() == 1 {
+            tokens.push(Token {
+                term: cjk[0].0.to_string(),
+                start: cjk[0].1,
+                end: cjk[0].2,
+            });
+        }
+        for pair in cjk.windows(2) {
+            tokens.push(Token {
+                term: [pair[0].0, pair[1].0].iter().collect(),
+                start: pair[0].1,
+                end: pair[1].2,
+            });
+        }
+        cjk.clear();
+    };
+
+    for c in text.chars() {
+        let end = offset + c.len_utf16() as u32;
+        if is_cjk(c) {
+            flush_word(&mut word, word_start, offset, &mut tokens);
+            cjk.push((c, offset, end));
+        } else if c.is_alphanumeric() {
+            flush_cjk(&mut cjk, &mut tokens);
+            if word.is_empty() {
+                word_start = offset;
+            }
+            word.extend(c.to_lowercase());
+        } else {
+            flush_word(&mut word, word_start, offset, &mut tokens);
+            flush_cjk(&mut cjk, &mut tokens);
+        }
+        offset = end;
+    }
+    flush_word(&mut word, word_start, offset, &mut tokens);
+    flush_cjk(&mut cjk, &mut tokens);
+    tokens
+}
+
+/// クエリ中の1語
+#[derive(Debug, Clone, PartialEq)]
+struct QueryTerm {
+    term: String,
+    prefix: bool,
+}
+
+/// クエリを語に分割します
+fn parse_query(query: &str) -> Vec<QueryTerm> {
+    let mut terms: Vec<QueryTerm> = Vec::new();
+    for word in query.split_whitespace() {
+        let (word, prefix) = match word.strip_suffix('*') {
+            Some(stripped) => (stripped, true),
+            None => (word, false),
+        };
+        let tokens = tokenize(word);
+        let last = tokens.len().saturating_sub(1);
+        for (index, token) in tokens.into_iter().enumerate() {
+            let single_cjk = token.term.chars().count() == 1
+                && token.term.chars().all(is_cjk);
+            let term = QueryTerm {
+                prefix: (prefix && index == last) || single_cjk,
+                term: token.term,
+            };
+            if !terms.contains(&term) {
+                terms.push(term);
+            }
+        }
+    }
+    terms
+}
+
+/// 重なる・隣接する一致箇所を結合します
+fn merge_highlights(mut highlights: Vec<SearchHighlight>) -> Vec<SearchHighlight> {
+    highlights.sort_by(|a, b| (&a.field, a.start, a.end).cmp(&(&b.field, b.start, b.end)));
+    let mut merged: Vec<SearchHighlight> = Vec::with_capacity(highlights.len());
+    for highlight in highlights {
+        match merged.last_mut() {
+            Some(last) if last.field == highlight.field && highlight.start <= last.end => {
+                last.end = last.end.max(highlight.end);
+            }
+            _ => merged.push(highlight),
+        }
+    }
+    merged
+}
+
+/// 1つの語に一致した箇所（ドキュメントID → 一致箇所）
+type TermMatches = HashMap<String, Vec<SearchHighlight>>;
+
+/// オフライン用の全文検索インデックス
+///
+/// ドキュメントはIDと、フィールド名から本文への対応で登録します。
+/// 関連度は各語の出現回数と、その語を含むドキュメントの少なさ（IDF）から計算します。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let index = SearchIndex::open(path)?;
+/// index.add_document("note-1".to_string(), HashMap::from([
+///     ("title".to_string(), "東京の天気".to_string()),
+///     ("body".to_string(), "Sunny weather in Tokyo".to_string()),
+/// ]))?;
+/// let hits = index.search("天気 sun*".to_string(), 20)?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct SearchIndex {
+    connection: Mutex<Connection>,
+}
+
+impl SearchIndex {
+    /// 1つの語に一致する箇所をドキュメントごとに取得します
+    fn term_matches(
+        connection: &Connection,
+        query: &QueryTerm,
+    ) -> Result<TermMatches, SearchError> {
+        let mut statement = if query.prefix {
+            connection.prepare_cached(
+                "SELECT doc_id, field, start, end FROM search_postings
+                 WHERE term >= ?1 AND term < ?1 || char(1114111)",
+            )?
+        } else {
+            connection.prepare_cached(
+                "SELECT doc_id, field, start, end FROM search_postings WHERE term = ?1",
+            )?
+        };
+        let rows = statement.query_map(params![query.term], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                SearchHighlight {
+                    field: row.get(1)?,
+                    start: row.get(2)?,
+                    end: row.get(3)?,
+                },
+            ))
+        })?;
+        let mut matches = TermMatches::new();
+        for row in rows {
+            let (doc_id, highlight) = row?;
+            matches.entry(doc_id).or_default().push(highlight);
+        }
+        Ok(matches)
+    }
+}
+
+#[uniffi::export]
+impl SearchIndex {
+    /// インデックスのファイルを開きます（存在しない場合は作成されます）
+    ///
+    /// # Arguments
+    /// * `path` - SQLiteファイルのパス
+    ///
+    /// # Errors
+    /// * `SearchError::StorageError` - ファイルを開けない、またはテーブルを作成できない場合
+    #[uniffi::constructor]
+    pub fn open(path: String) -> Result<Arc<Self>, SearchError> {
+        let connection = Connection::open(path)?;
+        connection.busy_timeout(Duration::from_secs(5))?;
+        connection.execute_batch(CREATE_TABLES)?;
+        Ok(Arc::new(Self {
+            connection: Mutex::new(connection),
+        }))
+    }
+
+    /// ドキュメントを登録します（同じIDのドキュメントは置き換えられます）
+    ///
+    /// # Arguments
+    /// * `id` - ドキュメントのID
+    /// * `fields` - フィールド名から本文への対応
+    ///
+    /// # Errors
+    /// * `SearchError::StorageError` - 書き込みに失敗した場合
+    /// * `SearchError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn add_document(
+        &self,
+        id: String,
+        fields: HashMap<String, String>,
+    ) -> Result<(), SearchError> {
+        let mut connection = self.connection.lock()
+            .map_err(|_| SearchError::MutexPoisoned)?;
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM search_postings WHERE doc_id = ?1", params![id])?;
+        transaction.execute(
+            "INSERT OR IGNORE INTO search_documents (id) VALUES (?1)",
+            params![id],
+        )?;
+        {
+            let mut insert = transaction.prepare(
+                "INSERT INTO search_postings (term, doc_id, field, start, end)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (field, text) in &fields {
+                for token in tokenize(text) {
+                    insert.execute(params![token.term, id, field, token.start, token.end])?;
+                }
+            }
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// ドキュメントを削除します
+    ///
+    /// # Returns
+    /// * `true` - ドキュメントが存在し削除された場合
+    /// * `false` - ドキュメントが存在しなかった場合
+    ///
+    /// # Errors
+    /// * `SearchError::StorageError` - 書き込みに失敗した場合
+    /// * `SearchError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn remove(&self, id: String) -> Result<bool, SearchError> {
+        let mut connection = self.connection.lock()
+            .map_err(|_| SearchError::MutexPoisoned)?;
+        let transaction = connection.transaction()?;
+        transaction.execute("DELETE FROM search_postings WHERE doc_id = ?1", params![id])?;
+        let removed =
+            transaction.execute("DELETE FROM search_documents WHERE id = ?1", params![id])?;
+        transaction.commit()?;
+        Ok(removed > 0)
+    }
+
+    /// 登録されているドキュメント数を返します
+    ///
+    /// # Errors
+    /// * `SearchError::StorageError` - 読み込みに失敗した場合
+    /// * `SearchError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn document_count(&self) -> Result<u64, SearchError> {
+        let connection = self.connection.lock()
+            .map_err(|_| SearchError::MutexPoisoned)?;
+        Ok(connection.query_row("SELECT COUNT(*) FROM search_documents", [], |row| row.get(0))?)
+    }
+
+    /// クエリに一致するドキュメントを関連度の高い順に返します
+    ///
+    /// 関連度が同じ場合はIDの昇順になります。
+    ///
+    /// # Arguments
+    /// * `query` - 検索クエリ（空白区切りのAND検索、末尾の`*`で前方一致）
+    /// * `limit` - 返す最大件数
+    ///
+    /// # Errors
+    /// * `SearchError::EmptyQuery` - クエリに検索できる語が含まれていない場合
+    /// * `SearchError::StorageError` - 読み込みに失敗した場合
+    /// * `SearchError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn search(&self, query: String, limit: u32) -> Result<Vec<SearchHit>, SearchError> {
+        let terms = parse_query(&query);
+        if terms.is_empty() {
+            return Err(SearchError::EmptyQuery);
+        }
+        let connection = self.connection.lock()
+            .map_err(|_| SearchError::MutexPoisoned)?;
+        let total: u64 =
+            connection.query_row("SELECT COUNT(*) FROM search_documents", [], |row| row.get(0))?;
+
+        let mut candidates: Option<HashSet<String>> = None;
+        let mut per_term = Vec::with_capacity(terms.len());
+        for term in &terms {
+            let matches = Self::term_matches(&connection, term)?;
+            let ids: HashSet<String> = matches.keys().cloned().collect();
+            candidates = Some(match candidates {
+                Some(current) => current.intersection(&ids).cloned().collect(),
+                None => ids,
+            });
+            per_term.push(matches);
+        }
+        let candidates = candidates.unwrap_or_default();
+
+        let mut hits: BTreeMap<String, SearchHit> = BTreeMap::new();
+        for matches in &mut per_term {
+            let idf = (1.0 + total as f64 / matches.len().max(1) as f64).ln();
+            for id in &candidates {
+                let Some(highlights) = matches.remove(id) else {
+                    continue;
+                };
+                let hit = hits.entry(id.clone()).or_insert_with(|| SearchHit {
+                    id: id.clone(),
+                    score: 0.0,
+                    highlights: Vec::new(),
+                });
+                hit.score += (1.0 + (highlights.len() as f64).ln()) * idf;
+                hit.highlights.extend(highlights);
+            }
+        }
+        let mut hits: Vec<SearchHit> = hits.into_values().collect();
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.id.cmp(&b.id)));
+        hits.truncate(limit as usize);
+        for hit in &mut hits {
+            hit.highlights = merge_highlights(std::mem::take(&mut hit.highlights));
+        }
+        Ok(hits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_search_{}_{}.sqlite", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    fn fields(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn ids(hits: &[SearchHit]) -> Vec<&str> {
+        hits.iter().map(|hit| hit.id.as_str()).collect()
+    }
+
+    fn sample_index(name: &str) -> (Arc<SearchIndex>, String) {
+        let path = temp_path(name);
+        let index = SearchIndex::open(path.clone()).unwrap();
+        let documents = [
+            ("a", "Swift concurrency", "Actors and async functions in Swift"),
+            ("b", "東京の天気", "明日の東京都は晴れ"),
+            ("c", "Rust FFI", "Calling Rust from Swift with UniFFI"),
+            ("d", "京都旅行", "Kyoto travel notes"),
+        ];
+        for (id, title, body) in documents {
+            let fields = fields(&[("title", title), ("body", body)]);
+            index.add_document(id.to_string(), fields).unwrap();
+        }
+        (index, path)
+    }
+
+    #[test]
+    fn test_tokenize_mixed_text() {
+        let terms: Vec<String> = tokenize("Hello, 東京都ﾃｽﾄ x2").into_iter().map(|t| t.term).collect();
+        assert_eq!(terms, vec!["hello", "東京", "京都", "都ﾃ", "ﾃｽ", "ｽﾄ", "x2"]);
+        let tokens = tokenize("a😀b 日");
+        assert_eq!(tokens[0].term, "a");
+        assert_eq!((tokens[1].start, tokens[1].end), (3, 4));
+        assert_eq!(tokens[2].term, "日");
+    }
+
+    #[test]
+    fn test_search_words_and_prefix() {
+        let (index, path) = sample_index("words");
+        let hits = index.search("swift".to_string(), 10).unwrap();
+        assert_eq!(ids(&hits), vec!["a", "c"]);
+        assert!(hits[0].score > hits[1].score);
+        assert_eq!(ids(&index.search("swift rust".to_string(), 10).unwrap()), vec!["c"]);
+        assert_eq!(ids(&index.search("uni*".to_string(), 10).unwrap()), vec!["c"]);
+        assert!(index.search("uni".to_string(), 10).unwrap().is_empty());
+        assert_eq!(index.search("swift".to_string(), 1).unwrap().len(), 1);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_search_cjk() {
+        let (index, path) = sample_index("cjk");
+        assert_eq!(ids(&index.search("東京".to_string(), 10).unwrap()), vec!["b"]);
+        assert_eq!(ids(&index.search("京都".to_string(), 10).unwrap()), vec!["b", "d"]);
+        assert_eq!(ids(&index.search("天".to_string(), 10).unwrap()), vec!["b"]);
+
+        let hits = index.search("東京都".to_string(), 10).unwrap();
+        assert_eq!(ids(&hits), vec!["b"]);
+        let body: Vec<_> = hits[0].highlights.iter().filter(|h| h.field == "body").collect();
+        assert_eq!((body[0].start, body[0].end), (3, 6));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_search_highlights() {
+        let (index, path) = sample_index("highlights");
+        let hits = index.search("swift".to_string(), 10).unwrap();
+        assert_eq!(
+            hits[0].highlights,
+            vec![
+                SearchHighlight { field: "body".to_string(), start: 30, end: 35 },
+                SearchHighlight { field: "title".to_string(), start: 0, end: 5 },
+            ]
+        );
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_search_update_remove_and_reopen() {
+        let (index, path) = sample_index("update");
+        index.add_document("a".to_string(), fields(&[("title", "Kotlin")])).unwrap();
+        assert_eq!(ids(&index.search("swift".to_string(), 10).unwrap()), vec!["c"]);
+        assert!(index.remove("c".to_string()).unwrap());
+        assert!(!index.remove("c".to_string()).unwrap());
+        drop(index);
+
+        let index = SearchIndex::open(path.clone()).unwrap();
+        assert_eq!(index.document_count().unwrap(), 3);
+        assert!(index.search("swift".to_string(), 10).unwrap().is_empty());
+        assert_eq!(ids(&index.search("kot*".to_string(), 10).unwrap()), vec!["a"]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_search_empty_query() {
+        let (index, path) = sample_index("empty");
+        match index.search(" ,.* ".to_string(), 10) {
+            Err(SearchError::EmptyQuery) => (),
+            other => panic!("Expected EmptyQuery error, got {:?}", other),
+        }
+        let _ = std::fs::remove_file(path);
+    }
+}
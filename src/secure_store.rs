@@ -0,0 +1,1486 @@
+//! 暗号化キー・バリューストアモジュール
+//!
+//! このモジュールは、アプリの設定やトークンを保存する`SecureStore`をエクスポートします。
+//! 変更はAES-256-GCMで暗号化したレコードとして先行書き込みログ（ジャーナル）に追記し、
+//! 一定数たまるとエントリ全体のスナップショットを書き直してジャーナルを空にします。
+//! 鍵はKeychainやKeystoreで管理された32バイトの鍵を渡すことを想定しています。
+//! `StoreObserver`を登録すると、キーのプレフィックスごとに変更を受け取れます。
+//!
+//! # ファイル形式
+//! ```text
+//! スナップショット（<path>）:
+//!   "MSST" | バージョン(1) | 世代(u64) | ノンス(12) | 暗号文（タグを含む）
+//!   平文: エントリ数(u32) | (キー長(u32) | キー | 値の長さ(u32) | 値)...
+//! ジャーナル（<path>.wal）:
+//!   ヘッダー: "MSWL" | バージョン(1) | 世代(u64) | ノンス(12) | 認証タグ(16)
+//!   レコード: 長さ(u32) | ノンス(12) | 暗号文（タグを含む）
+//!   平文: 操作(1) | キー長(u32) | キー | 値
+//! ```
+//! 数値はすべてビッグエンディアンです。スナップショットはヘッダーを、ジャーナルの
+//! レコードはジャーナルヘッダーの先頭13バイトと通し番号(u64)を追加認証データとして使用するため、
+//! レコードの並べ替えや別の世代のジャーナルの混入は復号の失敗として検出されます。
+//!
+//! # 障害からの復旧
+//! 開く際はスナップショットを読み込んだ後、同じ世代のジャーナルのレコードを順に適用します。
+//! 書き込み途中で終了したために末尾のレコードが不完全な場合は、その位置でジャーナルを切り詰めます。
+//! スナップショットの書き直しは一時ファイルとリネームで行い、その後にジャーナルを新しい世代で
+//! 作り直すため、間で終了した場合に残る古い世代のジャーナルは無視されます。
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+
+use crate::file_io::{sync_parent_directory, temp_path_for, write_atomic};
+use crate::snapshot::{
+    read_snapshot, write_snapshot, MergeStrategy, SnapshotError, SnapshotImportReport,
+    SnapshotKind,
+};
+
+/// スナップショット先頭のマジックナンバー
+const MAGIC: &[u8; 4] = b"MSST";
+
+/// スナップショットの形式のバージョン
+const FORMAT_VERSION: u8 = 2;
+
+/// 世代を持たない旧形式（一時ファイルとリネームのみで更新していた形式）のバージョン
+const LEGACY_FORMAT_VERSION: u8 = 1;
+
+/// スナップショットのヘッダーの長さ（マジック4 + バージョン1 + 世代8）
+const HEADER_LEN: usize = 13;
+
+/// 旧形式のスナップショットのヘッダーの長さ（マジック4 + バージョン1）
+const LEGACY_HEADER_LEN: usize = 5;
+
+/// ジャーナル先頭のマジックナンバー
+const JOURNAL_MAGIC: &[u8; 4] = b"MSWL";
+
+/// ジャーナルの形式のバージョン
+const JOURNAL_VERSION: u8 = 1;
+
+/// ジャーナルヘッダーのうち追加認証データに使う部分の長さ（マジック4 + バージョン1 + 世代8）
+const JOURNAL_PREFIX_LEN: usize = 13;
+
+/// ジャーナルヘッダーの長さ（先頭部分13 + ノンス12 + 認証タグ16）
+const JOURNAL_HEADER_LEN: usize = JOURNAL_PREFIX_LEN + NONCE_LEN + TAG_LEN;
+
+/// AES-GCMのノンスの長さ
+const NONCE_LEN: usize = 12;
+
+/// AES-GCMの認証タグの長さ
+const TAG_LEN: usize = 16;
+
+/// 暗号鍵の長さ（AES-256）
+const KEY_LEN: usize = 32;
+
+/// ジャーナルの1レコードの最大長（暗号文、64 MiB）
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// スナップショットを書き直すまでにジャーナルに追記するレコード数
+const CHECKPOINT_RECORDS: u64 = 128;
+
+/// キーから値への対応
+type Entries = BTreeMap<String, Vec<u8>>;
+
+/// ジャーナルのレコードの操作種別
+const OP_SET: u8 = 1;
+const OP_REMOVE: u8 = 2;
+
+/// 暗号化キー・バリューストアの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum SecureStoreError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access store file: {0}")]
+    IoError(String),
+    /// 暗号鍵の長さが32バイトでない場合
+    #[error("Invalid key length: {0} (expected 32)")]
+    InvalidKeyLength(u64),
+    /// 鍵が誤っている、またはファイルが改ざんされている場合
+    #[error("Failed to decrypt store file")]
+    DecryptionFailed,
+    /// ファイルの形式が不正な場合
+    #[error("Store file is corrupted: {0}")]
+    CorruptedFile(String),
+    /// 値がジャーナルの1レコードの上限を超える場合
+    #[error("Value is too large: {0} bytes")]
+    ValueTooLarge(u64),
+}
+
+impl From<std::io::Error> for SecureStoreError {
+    fn from(error: std::io::Error) -> Self {
+        SecureStoreError::IoError(error.to_string())
+    }
+}
+
+/// 整合性検査の結果
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct IntegrityReport {
+    /// 問題が見つからなかったかどうか
+    pub ok: bool,
+    /// 見つかった問題の説明
+    pub problems: Vec<String>,
+    /// 問題を修復したかどうか
+    pub repaired: bool,
+    /// 検査時点でジャーナルに記録されていたレコード数
+    pub journal_records: u64,
+}
+
+/// ストアの変更を受け取るオブザーバー
+///
+/// Swift側でSwiftUIのビューを更新するなど、Rust側での書き込みに反応するために実装します。
+#[uniffi::export(with_foreign)]
+pub trait StoreObserver: Send + Sync {
+    /// 値が変更されたときに呼ばれます
+    ///
+    /// # Arguments
+    /// * `key` - 変更されたキー
+    /// * `old_value` - 変更前の値（新規追加の場合は`None`）
+    /// * `new_value` - 変更後の値（削除の場合は`None`）
+    fn on_changed(&self, key: String, old_value: Option<Vec<u8>>, new_value: Option<Vec<u8>>);
+}
+
+/// 登録されたオブザーバー
+struct Subscription {
+    id: u64,
+    prefix: String,
+    observer: Arc<dyn StoreObserver>,
+}
+
+/// オブザーバーの登録状況
+struct Subscriptions {
+    next_id: u64,
+    list: Vec<Subscription>,
+}
+
+/// 通知を待っている変更
+struct PendingChange {
+    key: String,
+    old_value: Option<Vec<u8>>,
+    new_value: Option<Vec<u8>>,
+}
+
+/// 通知の待ち行列
+///
+/// 変更は状態のロックを保持したまま書き込み順に追加され、`delivering`を立てた1つのスレッドが
+/// 先頭から順に通知するため、オブザーバーは書き込みと同じ順序で変更を受け取ります。
+struct Notifications {
+    pending: VecDeque<PendingChange>,
+    delivering: bool,
+}
+
+/// エントリを平文に符号化します
+fn encode_entries(entries: &BTreeMap<String, Vec<u8>>) -> Vec<u8> {
+    let size: usize = entries.iter().map(|(k, v)| 8 + k.len() + v.len()).sum();
+    let mut plaintext = Vec::with_capacity(4 + size);
+    plaintext.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+    for (key, value) in entries {
+        plaintext.extend_from_slice(&(key.len() as u32).to_be_bytes());
+        plaintext.extend_from_slice(key.as_bytes());
+        plaintext.extend_from_slice(&(value.len() as u32).to_be_bytes());
+        plaintext.extend_from_slice(value);
+    }
+    plaintext
+}
+
+/// 先頭から`len`バイトを切り出します
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Result<&'a [u8], SecureStoreError> {
+    if rest.len() < len {
+        return Err(SecureStoreError::CorruptedFile("malformed entries".to_string()));
+    }
+    let (head, tail) = rest.split_at(len);
+    *rest = tail;
+    Ok(head)
+}
+
+/// 先頭から長さフィールド（u32）を読み取ります
+fn take_len(rest: &mut &[u8]) -> Result<usize, SecureStoreError> {
+    let bytes = take(rest, 4)?;
+    Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// 平文をエントリに復元します
+fn decode_entries(plaintext: &[u8]) -> Result<BTreeMap<String, Vec<u8>>, SecureStoreError> {
+    let corrupted = || SecureStoreError::CorruptedFile("malformed entries".to_string());
+    let mut rest = plaintext;
+    let count = take_len(&mut rest)?;
+    let mut entries = BTreeMap::new();
+    for _ in 0..count {
+        let key_len = take_len(&mut rest)?;
+        let key = String::from_utf8(take(&mut rest, key_len)?.to_vec()).map_err(|_| corrupted())?;
+        let value_len = take_len(&mut rest)?;
+        entries.insert(key, take(&mut rest, value_len)?.to_vec());
+    }
+    if !rest.is_empty() {
+        return Err(corrupted());
+    }
+    Ok(entries)
+}
+
+/// ジャーナルのレコードの平文を組み立てます
+fn encode_operation(op: u8, key: &str, value: &[u8]) -> Vec<u8> {
+    let mut plaintext = Vec::with_capacity(5 + key.len() + value.len());
+    plaintext.push(op);
+    plaintext.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    plaintext.extend_from_slice(key.as_bytes());
+    plaintext.extend_from_slice(value);
+    plaintext
+}
+
+/// ジャーナルのレコードの平文をエントリに適用します
+fn apply_operation(
+    entries: &mut BTreeMap<String, Vec<u8>>,
+    plaintext: &[u8],
+) -> Result<(), SecureStoreError> {
+    let corrupted = || SecureStoreError::CorruptedFile("malformed journal record".to_string());
+    let mut rest = plaintext;
+    let op = take(&mut rest, 1)?[0];
+    let key_len = take_len(&mut rest)?;
+    let key = String::from_utf8(take(&mut rest, key_len)?.to_vec()).map_err(|_| corrupted())?;
+    match op {
+        OP_SET => {
+            entries.insert(key, rest.to_vec());
+        }
+        OP_REMOVE => {
+            entries.remove(&key);
+        }
+        _ => return Err(corrupted()),
+    }
+    Ok(())
+}
+
+/// ジャーナルヘッダーの先頭部分（追加認証データ）を組み立てます
+fn journal_prefix(generation: u64) -> [u8; JOURNAL_PREFIX_LEN] {
+    let mut prefix = [0u8; JOURNAL_PREFIX_LEN];
+    prefix[..4].copy_from_slice(JOURNAL_MAGIC);
+    prefix[4] = JOURNAL_VERSION;
+    prefix[5..].copy_from_slice(&generation.to_be_bytes());
+    prefix
+}
+
+/// ジャーナルのレコードの追加認証データ（ヘッダーの先頭部分 + 通し番号）を組み立てます
+fn record_aad(generation: u64, sequence: u64) -> Vec<u8> {
+    let mut aad = journal_prefix(generation).to_vec();
+    aad.extend_from_slice(&sequence.to_be_bytes());
+    aad
+}
+
+/// ジャーナルを読み込んだ結果
+struct JournalScan {
+    /// 有効なレコードの平文（記録順）
+    records: Vec<Vec<u8>>,
+    /// 有効な部分の長さ（バイト、ジャーナルを使用できない場合は0）
+    valid_len: u64,
+    /// 見つかった問題
+    problem: Option<String>,
+}
+
+/// スナップショットの書き直しとジャーナルの状態を含むストアの内部状態
+struct StoreState {
+    entries: BTreeMap<String, Vec<u8>>,
+    /// スナップショットの世代（スナップショットが存在しない場合は0）
+    generation: u64,
+    /// 追記用に開いているジャーナル（未作成の場合は`None`）
+    journal: Option<File>,
+    /// ジャーナルの有効な長さ（バイト）
+    journal_len: u64,
+    /// ジャーナルに記録されているレコード数
+    journal_records: u64,
+}
+
+/// 暗号化キー・バリューストア
+///
+/// 全エントリをメモリ上に保持し、`set`・`remove`のたびに変更をジャーナルに追記して
+/// ディスクに同期します。書き込み途中でアプリが終了しても、次に開いたときに
+/// 最後に完了した書き込みまでの状態に復旧されます。
+/// 設定やトークンなど小さなデータの保存に適しています。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// 変更の通知は、書き込みが成功した後に書き込みと同じ順序で行われます。通常は書き込みを
+/// 行ったスレッドで同期的に通知しますが、別のスレッドが通知中の場合はそのスレッドが続けて
+/// 通知するため、`set`から戻った時点で通知が済んでいるとは限りません。
+/// 通知中は内部のロックを保持しないため、オブザーバーからストアを操作できます
+/// （その変更は現在の通知が終わった後に通知されます）。
+///
+/// # Example
+/// ```
+/// let store = SecureStore::open(path, key_from_keychain)?;
+/// let subscription = store.subscribe("settings/".to_string(), observer)?;
+/// store.set("auth/access_token".to_string(), token_bytes)?;
+/// let token = store.get("auth/access_token".to_string())?;
+/// let auth_keys = store.keys("auth/".to_string())?;
+/// let report = store.verify_integrity(true)?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct SecureStore {
+    path: PathBuf,
+    cipher: Aes256Gcm,
+    state: Mutex<StoreState>,
+    subscriptions: Mutex<Subscriptions>,
+    notifications: Mutex<Notifications>,
+}
+
+impl SecureStore {
+    /// ストアのファイルのパスに接尾辞を付けたパスを返します
+    fn sibling_path(&self, suffix: &str) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(suffix);
+        PathBuf::from(name)
+    }
+
+    /// ジャーナルのパス
+    fn journal_path(&self) -> PathBuf {
+        self.sibling_path(".wal")
+    }
+
+    /// スナップショットの書き直しに使う一時ファイルのパス
+    fn temp_path(&self) -> PathBuf {
+        temp_path_for(&self.path)
+    }
+
+    /// 新しいノンスで暗号化し、(ノンス, 暗号文)を返します
+    fn encrypt(
+        &self,
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<([u8; NONCE_LEN], Vec<u8>), SecureStoreError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let payload = Payload {
+            msg: plaintext,
+            aad,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| SecureStoreError::IoError("encryption failed".to_string()))?;
+        Ok((nonce, ciphertext))
+    }
+
+    /// 復号します（認証に失敗した場合は`None`）
+    fn decrypt(&self, aad: &[u8], nonce: &[u8], ciphertext: &[u8]) -> Option<Vec<u8>> {
+        let payload = Payload {
+            msg: ciphertext,
+            aad,
+        };
+        self.cipher.decrypt(Nonce::from_slice(nonce), payload).ok()
+    }
+
+    /// スナップショットを読み込んで復号し、(世代, エントリ)を返します（存在しない場合は`None`）
+    fn read_snapshot(&self) -> Result<Option<(u64, Entries)>, SecureStoreError> {
+        let data = match fs::read(&self.path) {
+            Ok(data) => data,
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if data.len() < LEGACY_HEADER_LEN || &data[..4] != MAGIC {
+            return Err(SecureStoreError::CorruptedFile("not a store file".to_string()));
+        }
+        let (header_len, generation) = match data[4] {
+            FORMAT_VERSION if data.len() >= HEADER_LEN => {
+                let mut generation = [0u8; 8];
+                generation.copy_from_slice(&data[5..HEADER_LEN]);
+                (HEADER_LEN, u64::from_be_bytes(generation))
+            }
+            LEGACY_FORMAT_VERSION => (LEGACY_HEADER_LEN, 0),
+            version => {
+                return Err(SecureStoreError::CorruptedFile(format!(
+                    "unsupported format version: {}",
+                    version
+                )))
+            }
+        };
+        if data.len() < header_len + NONCE_LEN {
+            return Err(SecureStoreError::CorruptedFile("truncated store file".to_string()));
+        }
+        let (header, rest) = data.split_at(header_len);
+        let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+        let plaintext = self
+            .decrypt(header, nonce, ciphertext)
+            .ok_or(SecureStoreError::DecryptionFailed)?;
+        Ok(Some((generation, decode_entries(&plaintext)?)))
+    }
+
+    /// エントリを暗号化し、一時ファイルとリネームでスナップショットを置き換えます
+    fn write_snapshot(
+        &self,
+        generation: u64,
+        entries: &BTreeMap<String, Vec<u8>>,
+    ) -> Result<(), SecureStoreError> {
+        let mut header = [0u8; HEADER_LEN];
+        header[..4].copy_from_slice(MAGIC);
+        header[4] = FORMAT_VERSION;
+        header[5..].copy_from_slice(&generation.to_be_bytes());
+        let (nonce, ciphertext) = self.encrypt(&header, &encode_entries(entries))?;
+
+        write_atomic(&self.path, &[&header, &nonce, &ciphertext])?;
+        Ok(())
+    }
+
+    /// 指定した世代の空のジャーナルを作成します（既存のジャーナルは置き換えられます）
+    fn create_journal(&self, generation: u64) -> Result<File, SecureStoreError> {
+        let prefix = journal_prefix(generation);
+        let (nonce, tag) = self.encrypt(&prefix, &[])?;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(self.journal_path())?;
+        file.write_all(&prefix)?;
+        file.write_all(&nonce)?;
+        file.write_all(&tag)?;
+        file.sync_all()?;
+        sync_parent_directory(&self.path);
+        Ok(file)
+    }
+
+    /// ジャーナルを読み込み、有効なレコードを返します
+    ///
+    /// `key_verified`が`false`の場合（スナップショットで鍵を確認できていない場合）、
+    /// ヘッダーの認証に失敗すると鍵の誤りとして`DecryptionFailed`を返します。
+    fn scan_journal(
+        &self,
+        data: &[u8],
+        generation: u64,
+        key_verified: bool,
+    ) -> Result<JournalScan, SecureStoreError> {
+        let unusable = |problem: String| JournalScan {
+            records: Vec::new(),
+            valid_len: 0,
+            problem: Some(problem),
+        };
+        if data.len() < JOURNAL_HEADER_LEN || &data[..4] != JOURNAL_MAGIC {
+            return Ok(unusable("journal header is missing or incomplete".to_string()));
+        }
+        if data[4] != JOURNAL_VERSION {
+            return Ok(unusable(format!("unsupported journal version: {}", data[4])));
+        }
+        let (prefix, rest) = data.split_at(JOURNAL_PREFIX_LEN);
+        let (nonce, tag) = rest[..NONCE_LEN + TAG_LEN].split_at(NONCE_LEN);
+        if self.decrypt(prefix, nonce, tag).is_none() {
+            if !key_verified {
+                return Err(SecureStoreError::DecryptionFailed);
+            }
+            return Ok(unusable("journal header failed authentication".to_string()));
+        }
+        let mut journal_generation = [0u8; 8];
+        journal_generation.copy_from_slice(&prefix[5..]);
+        let journal_generation = u64::from_be_bytes(journal_generation);
+        if journal_generation != generation {
+            return Ok(unusable(format!(
+                "stale journal from generation {} (snapshot generation {})",
+                journal_generation, generation
+            )));
+        }
+
+        let mut records = Vec::new();
+        let mut offset = JOURNAL_HEADER_LEN;
+        let mut problem = None;
+        while offset < data.len() {
+            let record = data.get(offset..offset + 4).and_then(|len| {
+                let len = u32::from_be_bytes([len[0], len[1], len[2], len[3]]);
+                if len > MAX_RECORD_LEN {
+                    return None;
+                }
+                let end = offset + 4 + NONCE_LEN + len as usize;
+                let (nonce, ciphertext) = data.get(offset + 4..end)?.split_at(NONCE_LEN);
+                let aad = record_aad(generation, records.len() as u64);
+                self.decrypt(&aad, nonce, ciphertext).map(|plaintext| (plaintext, end))
+            });
+            let Some((plaintext, end)) = record else {
+                problem = Some(format!(
+                    "discarded {} bytes of incomplete or corrupted journal records at offset {}",
+                    data.len() - offset,
+                    offset
+                ));
+                break;
+            };
+            if apply_operation(&mut BTreeMap::new(), &plaintext).is_err() {
+                problem = Some(format!("malformed journal record at offset {}", offset));
+                break;
+            }
+            records.push(plaintext);
+            offset = end;
+        }
+        Ok(JournalScan {
+            records,
+            valid_len: offset as u64,
+            problem,
+        })
+    }
+
+    /// ジャーナルにレコードを追記してディスクに同期します
+    ///
+    /// 書き込みに失敗した場合は、不完全なレコードが残らないよう元の長さに切り詰めます。
+    fn append(&self, state: &mut StoreState, plaintext: &[u8]) -> Result<(), SecureStoreError> {
+        let aad = record_aad(state.generation, state.journal_records);
+        let (nonce, ciphertext) = self.encrypt(&aad, plaintext)?;
+        if ciphertext.len() > MAX_RECORD_LEN as usize {
+            return Err(SecureStoreError::ValueTooLarge(plaintext.len() as u64));
+        }
+        let mut record = Vec::with_capacity(4 + NONCE_LEN + ciphertext.len());
+        record.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+        record.extend_from_slice(&nonce);
+        record.extend_from_slice(&ciphertext);
+
+        if state.journal.is_none() {
+            state.journal = Some(self.create_journal(state.generation)?);
+            state.journal_len = JOURNAL_HEADER_LEN as u64;
+            state.journal_records = 0;
+        }
+        let journal_len = state.journal_len;
+        let Some(journal) = state.journal.as_mut() else {
+            return Err(SecureStoreError::IoError("journal is not open".to_string()));
+        };
+        let result = journal
+            .seek(SeekFrom::Start(journal_len))
+            .and_then(|_| journal.write_all(&record))
+            .and_then(|()| journal.sync_data());
+        if let Err(e) = result {
+            let _ = journal.set_len(journal_len);
+            return Err(e.into());
+        }
+        state.journal_len += record.len() as u64;
+        state.journal_records += 1;
+        Ok(())
+    }
+
+    /// スナップショットを次の世代で書き直し、ジャーナルを空にします
+    fn checkpoint(&self, state: &mut StoreState) -> Result<(), SecureStoreError> {
+        let generation = state.generation + 1;
+        self.write_snapshot(generation, &state.entries)?;
+        state.generation = generation;
+        state.journal_len = JOURNAL_HEADER_LEN as u64;
+        state.journal_records = 0;
+        // 作成に失敗しても古い世代のジャーナルは無視されるため、次の書き込みで作り直す
+        state.journal = None;
+        state.journal = Some(self.create_journal(generation)?);
+        Ok(())
+    }
+
+    /// 変更をジャーナルに記録してからメモリ上のエントリに反映し、変更前の値を返します
+    ///
+    /// 値が変わった場合は、状態のロックを保持したまま変更を通知の待ち行列に追加します。
+    fn write(
+        &self,
+        key: &str,
+        value: Option<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, SecureStoreError> {
+        let mut state = self.state.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)?;
+        let plaintext = match &value {
+            Some(value) => encode_operation(OP_SET, key, value),
+            None => encode_operation(OP_REMOVE, key, &[]),
+        };
+        self.append(&mut state, &plaintext)?;
+        let previous = match &value {
+            Some(value) => state.entries.insert(key.to_string(), value.clone()),
+            None => state.entries.remove(key),
+        };
+        if previous != value {
+            self.notifications.lock()
+                .map_err(|_| SecureStoreError::MutexPoisoned)?
+                .pending
+                .push_back(PendingChange {
+                    key: key.to_string(),
+                    old_value: previous.clone(),
+                    new_value: value,
+                });
+        }
+        if state.journal_records >= CHECKPOINT_RECORDS {
+            // 変更はジャーナルで永続化済みのため、失敗しても次の書き込みで再試行する
+            let _ = self.checkpoint(&mut state);
+        }
+        Ok(previous)
+    }
+
+    /// 待ち行列の変更を、キーに一致するオブザーバーに書き込み順に通知します
+    ///
+    /// 別のスレッド（またはオブザーバーからの再入）が通知中の場合は、そのスレッドに任せて戻ります。
+    fn notify(&self) -> Result<(), SecureStoreError> {
+        {
+            let mut notifications = self.notifications.lock()
+                .map_err(|_| SecureStoreError::MutexPoisoned)?;
+            if notifications.delivering {
+                return Ok(());
+            }
+            notifications.delivering = true;
+        }
+        loop {
+            let change = {
+                let mut notifications = self.notifications.lock()
+                    .map_err(|_| SecureStoreError::MutexPoisoned)?;
+                match notifications.pending.pop_front() {
+                    Some(change) => change,
+                    None => {
+                        notifications.delivering = false;
+                        return Ok(());
+                    }
+                }
+            };
+            let observers = self.subscriptions.lock().map(|subscriptions| {
+                subscriptions
+                    .list
+                    .iter()
+                    .filter(|s| change.key.starts_with(&s.prefix))
+                    .map(|s| s.observer.clone())
+                    .collect::<Vec<Arc<dyn StoreObserver>>>()
+            });
+            let Ok(observers) = observers else {
+                if let Ok(mut notifications) = self.notifications.lock() {
+                    notifications.delivering = false;
+                }
+                return Err(SecureStoreError::MutexPoisoned);
+            };
+            for observer in observers {
+                observer.on_changed(
+                    change.key.clone(),
+                    change.old_value.clone(),
+                    change.new_value.clone(),
+                );
+            }
+        }
+    }
+}
+
+#[uniffi::export]
+impl SecureStore {
+    /// ストアを開きます
+    ///
+    /// ファイルが存在しない場合は空のストアとして開き、最初の`set`でファイルを作成します。
+    /// 前回の書き込みが途中で中断されていた場合は、ジャーナルの不完全な末尾を切り詰めて
+    /// 最後に完了した書き込みまでの状態に復旧します。
+    ///
+    /// # Arguments
+    /// * `path` - ストアファイルのパス（ジャーナルは`<path>.wal`に作成されます）
+    /// * `key` - 32バイトの暗号鍵
+    ///
+    /// # Errors
+    /// * `SecureStoreError::InvalidKeyLength` - 鍵が32バイトでない場合
+    /// * `SecureStoreError::DecryptionFailed` - 鍵が誤っている、またはファイルが改ざんされている場合
+    /// * `SecureStoreError::CorruptedFile` - ファイルの形式が不正な場合
+    /// * `SecureStoreError::IoError` - ファイルの読み書きに失敗した場合
+    #[uniffi::constructor]
+    pub fn open(path: String, key: Vec<u8>) -> Result<Arc<Self>, SecureStoreError> {
+        if key.len() != KEY_LEN {
+            return Err(SecureStoreError::InvalidKeyLength(key.len() as u64));
+        }
+        let store = Self {
+            path: PathBuf::from(path),
+            cipher: Aes256Gcm::new_from_slice(&key)
+                .map_err(|_| SecureStoreError::InvalidKeyLength(key.len() as u64))?,
+            state: Mutex::new(StoreState {
+                entries: BTreeMap::new(),
+                generation: 0,
+                journal: None,
+                journal_len: 0,
+                journal_records: 0,
+            }),
+            subscriptions: Mutex::new(Subscriptions {
+                next_id: 1,
+                list: Vec::new(),
+            }),
+            notifications: Mutex::new(Notifications {
+                pending: VecDeque::new(),
+                delivering: false,
+            }),
+        };
+        let snapshot = store.read_snapshot()?;
+        let key_verified = snapshot.is_some();
+        let (generation, mut entries) = snapshot.unwrap_or_default();
+        let mut state = StoreState {
+            entries: BTreeMap::new(),
+            generation,
+            journal: None,
+            journal_len: 0,
+            journal_records: 0,
+        };
+
+        let journal_path = store.journal_path();
+        match fs::read(&journal_path) {
+            Ok(data) => {
+                let scan = store.scan_journal(&data, generation, key_verified)?;
+                for record in &scan.records {
+                    apply_operation(&mut entries, record)?;
+                }
+                // 有効なジャーナルは不完全な末尾を切り詰めて追記を続け、
+                // 使用できないジャーナルは次の書き込みで作り直す
+                if scan.valid_len > 0 {
+                    let journal = OpenOptions::new().read(true).write(true).open(&journal_path)?;
+                    if scan.valid_len < data.len() as u64 {
+                        journal.set_len(scan.valid_len)?;
+                        journal.sync_all()?;
+                    }
+                    state.journal = Some(journal);
+                    state.journal_len = scan.valid_len;
+                    state.journal_records = scan.records.len() as u64;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        state.entries = entries;
+        *store.state.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)? = state;
+        Ok(Arc::new(store))
+    }
+
+    /// 値を保存します（同じキーの値は上書きされます）
+    ///
+    /// ジャーナルへの書き込みに失敗した場合、メモリ上の内容は変更されません。
+    ///
+    /// # Errors
+    /// * `SecureStoreError::ValueTooLarge` - 値が上限（約64 MiB）を超える場合
+    /// * `SecureStoreError::IoError` - ファイルへの書き込みに失敗した場合
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn set(&self, key: String, value: Vec<u8>) -> Result<(), SecureStoreError> {
+        self.write(&key, Some(value))?;
+        self.notify()
+    }
+
+    /// 値を取得します（存在しない場合は`None`）
+    ///
+    /// # Errors
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn get(&self, key: String) -> Result<Option<Vec<u8>>, SecureStoreError> {
+        let state = self.state.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)?;
+        Ok(state.entries.get(&key).cloned())
+    }
+
+    /// 値を削除します
+    ///
+    /// # Returns
+    /// * `true` - エントリが存在し削除された場合
+    /// * `false` - エントリが存在しなかった場合
+    ///
+    /// # Errors
+    /// * `SecureStoreError::IoError` - ファイルへの書き込みに失敗した場合
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn remove(&self, key: String) -> Result<bool, SecureStoreError> {
+        {
+            let state = self.state.lock()
+                .map_err(|_| SecureStoreError::MutexPoisoned)?;
+            if !state.entries.contains_key(&key) {
+                return Ok(false);
+            }
+        }
+        let previous = self.write(&key, None)?;
+        self.notify()?;
+        Ok(previous.is_some())
+    }
+
+    /// 指定したプレフィックスで始まるキーを昇順で返します
+    ///
+    /// # Arguments
+    /// * `prefix` - キーのプレフィックス（空文字列の場合はすべてのキー）
+    ///
+    /// # Errors
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn keys(&self, prefix: String) -> Result<Vec<String>, SecureStoreError> {
+        let state = self.state.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)?;
+        Ok(state
+            .entries
+            .range(prefix.clone()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(&prefix))
+            .cloned()
+            .collect())
+    }
+
+    /// キーのプレフィックスに一致する変更を受け取るオブザーバーを登録します
+    ///
+    /// 値が実際に変わった場合のみ通知されます（同じ値の`set`は通知されません）。
+    ///
+    /// # Arguments
+    /// * `prefix` - 監視するキーのプレフィックス（空文字列の場合はすべてのキー）
+    /// * `observer` - 通知を受け取るオブザーバー
+    ///
+    /// # Returns
+    /// 登録の解除に使用するID
+    ///
+    /// # Errors
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn subscribe(
+        &self,
+        prefix: String,
+        observer: Arc<dyn StoreObserver>,
+    ) -> Result<u64, SecureStoreError> {
+        let mut subscriptions = self.subscriptions.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)?;
+        let id = subscriptions.next_id;
+        subscriptions.next_id += 1;
+        subscriptions.list.push(Subscription {
+            id,
+            prefix,
+            observer,
+        });
+        Ok(id)
+    }
+
+    /// オブザーバーの登録を解除します
+    ///
+    /// # Returns
+    /// * `true` - 登録が存在し解除された場合
+    /// * `false` - 登録が存在しなかった場合
+    ///
+    /// # Errors
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn unsubscribe(&self, subscription_id: u64) -> Result<bool, SecureStoreError> {
+        let mut subscriptions = self.subscriptions.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)?;
+        let before = subscriptions.list.len();
+        subscriptions.list.retain(|s| s.id != subscription_id);
+        Ok(subscriptions.list.len() != before)
+    }
+
+    /// ディスク上のファイルを検査し、必要に応じて修復します
+    ///
+    /// スナップショットとジャーナルを読み直し、復号できない・不完全なレコードがある・
+    /// メモリ上の内容と一致しない・一時ファイルが残っているといった問題を報告します。
+    /// 修復する場合は、メモリ上の内容から新しい世代のスナップショットを書き直し、
+    /// ジャーナルを空にします。
+    ///
+    /// # Arguments
+    /// * `repair` - 問題が見つかった場合に修復するかどうか（既定値は`true`）
+    ///
+    /// # Errors
+    /// * `SecureStoreError::IoError` - 修復のための書き込みに失敗した場合
+    /// * `SecureStoreError::MutexPoisoned` - 内部Mutexが破損している場合
+    #[uniffi::method(default(repair = true))]
+    pub fn verify_integrity(&self, repair: bool) -> Result<IntegrityReport, SecureStoreError> {
+        let mut state = self.state.lock()
+            .map_err(|_| SecureStoreError::MutexPoisoned)?;
+        let state = &mut *state;
+        let mut problems = Vec::new();
+
+        let temp_path = self.temp_path();
+        if temp_path.exists() {
+            problems.push("leftover temporary snapshot file".to_string());
+        }
+        let mut on_disk = match self.read_snapshot() {
+            Ok(Some((generation, entries))) => {
+                if generation != state.generation {
+                    problems.push(format!(
+                        "snapshot generation {} does not match {}",
+                        generation, state.generation
+                    ));
+                }
+                entries
+            }
+            Ok(None) => {
+                if state.generation > 0 {
+                    problems.push("snapshot file is missing".to_string());
+                }
+                BTreeMap::new()
+            }
+            Err(e) => {
+                problems.push(format!("snapshot is unreadable: {}", e));
+                BTreeMap::new()
+            }
+        };
+        let journal_records = state.journal_records;
+        match fs::read(self.journal_path()) {
+            Ok(data) => {
+                let scan = self.scan_journal(&data, state.generation, true)?;
+                problems.extend(scan.problem);
+                if scan.records.len() as u64 != state.journal_records {
+                    problems.push(format!(
+                        "journal has {} records but {} were written",
+                        scan.records.len(),
+                        state.journal_records
+                    ));
+                }
+                for record in &scan.records {
+                    apply_operation(&mut on_disk, record)?;
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                if state.journal_records > 0 {
+                    problems.push("journal file is missing".to_string());
+                }
+            }
+            Err(e) => problems.push(format!("journal is unreadable: {}", e)),
+        }
+        if problems.is_empty() && on_disk != state.entries {
+            problems.push("stored entries do not match the entries in memory".to_string());
+        }
+
+        let repaired = repair && !problems.is_empty();
+        if repaired {
+            let _ = fs::remove_file(&temp_path);
+            self.checkpoint(state)?;
+        }
+        Ok(IntegrityReport {
+            ok: problems.is_empty(),
+            problems,
+            repaired,
+            journal_records,
+        })
+    }
+
+    /// 内容をパスフレーズで暗号化したスナップショットファイルに書き出します
+    ///
+    /// ユーザーのバックアップや機種変更時の移行に使用します。スナップショットは
+    /// ストアの鍵ではなくパスフレーズで保護されるため、別の鍵のストアにも読み込めます。
+    ///
+    /// # Arguments
+    /// * `path` - 書き出すファイルのパス（既存のファイルはアトミックに置き換えられます）
+    /// * `passphrase` - スナップショットを保護するパスフレーズ
+    ///
+    /// # Errors
+    /// * `SnapshotError::IoError` - ファイルの書き込みに失敗した場合
+    /// * `SnapshotError::StoreError` - 内部Mutexが破損している場合
+    pub fn export_snapshot(&self, path: String, passphrase: String) -> Result<(), SnapshotError> {
+        let entries: serde_json::Map<String, serde_json::Value> = {
+            let state = self.state.lock()
+                .map_err(|_| SecureStoreError::MutexPoisoned)?;
+            state
+                .entries
+                .iter()
+                .map(|(key, value)| (key.clone(), STANDARD.encode(value).into()))
+                .collect()
+        };
+        let plaintext = serde_json::Value::Object(entries).to_string();
+        write_snapshot(&path, &passphrase, SnapshotKind::KeyValue, plaintext.as_bytes())
+    }
+
+    /// `export_snapshot`で書き出したスナップショットを読み込みます
+    ///
+    /// エントリは`set`・`remove`と同じ方法で1件ずつ書き込まれ、オブザーバーにも通知されます。
+    /// 途中で失敗した場合、それまでに書き込んだエントリは残ります。
+    ///
+    /// # Arguments
+    /// * `path` - スナップショットファイルのパス
+    /// * `passphrase` - 書き出し時に指定したパスフレーズ
+    /// * `merge_strategy` - 既存のエントリとの統合方法
+    ///
+    /// # Errors
+    /// * `SnapshotError::InvalidPassphrase` - パスフレーズが誤っている、または改ざんされている場合
+    /// * `SnapshotError::KindMismatch` - `DocumentStore`のスナップショットの場合
+    /// * `SnapshotError::CorruptedSnapshot` - ファイルの内容が不正な場合、
+    ///   または鍵導出パラメータが既定値の4倍を超える場合
+    /// * `SnapshotError::IoError` - ファイルの読み込みに失敗した場合
+    /// * `SnapshotError::StoreError` - ストアへの書き込みに失敗した場合
+    pub fn import_snapshot(
+        &self,
+        path: String,
+        passphrase: String,
+        merge_strategy: MergeStrategy,
+    ) -> Result<SnapshotImportReport, SnapshotError> {
+        let plaintext = read_snapshot(&path, &passphrase, SnapshotKind::KeyValue)?;
+        let corrupted = |message: String| SnapshotError::CorruptedSnapshot(message);
+        let serde_json::Value::Object(object) = serde_json::from_slice(&plaintext)
+            .map_err(|e| corrupted(e.to_string()))?
+        else {
+            return Err(corrupted("entries must be a JSON object".to_string()));
+        };
+        let mut entries = BTreeMap::new();
+        for (key, value) in object {
+            let value = value
+                .as_str()
+                .and_then(|value| STANDARD.decode(value).ok())
+                .ok_or_else(|| corrupted(format!("invalid value for key: {}", key)))?;
+            entries.insert(key, value);
+        }
+
+        let existing = self.keys(String::new())?;
+        let mut report = SnapshotImportReport {
+            imported: 0,
+            skipped: 0,
+            removed: 0,
+        };
+        if merge_strategy == MergeStrategy::Replace {
+            for key in existing.iter().filter(|key| !entries.contains_key(*key)) {
+                if self.remove(key.clone())? {
+                    report.removed += 1;
+                }
+            }
+        }
+        for (key, value) in entries {
+            let exists = existing.binary_search(&key).is_ok();
+            if merge_strategy == MergeStrategy::KeepExisting && exists {
+                report.skipped += 1;
+                continue;
+            }
+            self.set(key, value)?;
+            report.imported += 1;
+        }
+        Ok(report)
+    }
+}
+
+/// 非同期版のAPI
+///
+/// 各メソッドは同名の同期メソッドと同じ処理をRust側のスレッドプールで実行するため、
+/// 呼び出し元のスレッドをブロックしません。オブザーバーへの通知は
+/// スレッドプールのスレッドから行われます。
+#[uniffi::export]
+impl SecureStore {
+    /// `set`の非同期版です
+    ///
+    /// # Errors
+    /// * `set`と同じエラー
+    pub async fn set_async(
+        self: Arc<Self>,
+        key: String,
+        value: Vec<u8>,
+    ) -> Result<(), SecureStoreError> {
+        blocking::unblock(move || self.set(key, value)).await
+    }
+
+    /// `get`の非同期版です
+    ///
+    /// # Errors
+    /// * `get`と同じエラー
+    pub async fn get_async(
+        self: Arc<Self>,
+        key: String,
+    ) -> Result<Option<Vec<u8>>, SecureStoreError> {
+        blocking::unblock(move || self.get(key)).await
+    }
+
+    /// `remove`の非同期版です
+    ///
+    /// # Errors
+    /// * `remove`と同じエラー
+    pub async fn remove_async(self: Arc<Self>, key: String) -> Result<bool, SecureStoreError> {
+        blocking::unblock(move || self.remove(key)).await
+    }
+
+    /// `keys`の非同期版です
+    ///
+    /// # Errors
+    /// * `keys`と同じエラー
+    pub async fn keys_async(
+        self: Arc<Self>,
+        prefix: String,
+    ) -> Result<Vec<String>, SecureStoreError> {
+        blocking::unblock(move || self.keys(prefix)).await
+    }
+
+    /// `verify_integrity`の非同期版です
+    ///
+    /// # Errors
+    /// * `verify_integrity`と同じエラー
+    #[uniffi::method(default(repair = true))]
+    pub async fn verify_integrity_async(
+        self: Arc<Self>,
+        repair: bool,
+    ) -> Result<IntegrityReport, SecureStoreError> {
+        blocking::unblock(move || self.verify_integrity(repair)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_secure_store_{}_{}.bin", name, std::process::id()));
+        let path = path.to_string_lossy().into_owned();
+        cleanup(&path);
+        path
+    }
+
+    /// ストアのファイルとジャーナル・一時ファイルを削除します
+    fn cleanup(path: &str) {
+        for suffix in ["", ".wal", ".tmp"] {
+            let _ = fs::remove_file(format!("{}{}", path, suffix));
+        }
+    }
+
+    #[test]
+    fn test_secure_store_set_get_remove() {
+        let path = temp_path("basic");
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        assert_eq!(store.get("token".to_string()).unwrap(), None);
+        store.set("token".to_string(), b"abc".to_vec()).unwrap();
+        store.set("token".to_string(), b"def".to_vec()).unwrap();
+        assert_eq!(store.get("token".to_string()).unwrap(), Some(b"def".to_vec()));
+        assert!(store.remove("token".to_string()).unwrap());
+        assert!(!store.remove("token".to_string()).unwrap());
+        assert_eq!(store.get("token".to_string()).unwrap(), None);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_keys_with_prefix() {
+        let path = temp_path("keys");
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        for key in ["settings/theme", "auth/refresh", "auth/access", "authz"] {
+            store.set(key.to_string(), Vec::new()).unwrap();
+        }
+        assert_eq!(store.keys("auth/".to_string()).unwrap(), vec!["auth/access", "auth/refresh"]);
+        assert_eq!(store.keys(String::new()).unwrap().len(), 4);
+        assert!(store.keys("zzz".to_string()).unwrap().is_empty());
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_persists_encrypted() {
+        let path = temp_path("persist");
+        {
+            let store = SecureStore::open(path.clone(), vec![7u8; 32]).unwrap();
+            store.set("refresh-token".to_string(), b"very secret value".to_vec()).unwrap();
+            store.set("gone".to_string(), b"x".to_vec()).unwrap();
+            store.remove("gone".to_string()).unwrap();
+        }
+        let raw = fs::read(format!("{}.wal", path)).unwrap();
+        assert!(!raw.windows(13).any(|w| w == b"refresh-token"));
+        assert!(!raw.windows(11).any(|w| w == b"very secret"));
+        assert!(!Path::new(&format!("{}.tmp", path)).exists());
+
+        let store = SecureStore::open(path.clone(), vec![7u8; 32]).unwrap();
+        assert_eq!(store.keys(String::new()).unwrap(), vec!["refresh-token"]);
+        assert_eq!(
+            store.get("refresh-token".to_string()).unwrap(),
+            Some(b"very secret value".to_vec())
+        );
+        cleanup(&path);
+    }
+
+    /// 記録された変更（キー・変更前・変更後）
+    type ChangeEvent = (String, Option<Vec<u8>>, Option<Vec<u8>>);
+
+    /// 受け取った変更を記録するオブザーバー
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Mutex<Vec<ChangeEvent>>,
+    }
+
+    impl StoreObserver for RecordingObserver {
+        fn on_changed(&self, key: String, old_value: Option<Vec<u8>>, new_value: Option<Vec<u8>>) {
+            self.events.lock().unwrap().push((key, old_value, new_value));
+        }
+    }
+
+    #[test]
+    fn test_secure_store_observer() {
+        let path = temp_path("observer");
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        let settings = Arc::new(RecordingObserver::default());
+        let all = Arc::new(RecordingObserver::default());
+        let id = store.subscribe("settings/".to_string(), settings.clone()).unwrap();
+        store.subscribe(String::new(), all.clone()).unwrap();
+
+        store.set("settings/theme".to_string(), b"dark".to_vec()).unwrap();
+        store.set("settings/theme".to_string(), b"dark".to_vec()).unwrap();
+        store.set("settings/theme".to_string(), b"light".to_vec()).unwrap();
+        store.set("auth/token".to_string(), b"t".to_vec()).unwrap();
+        store.remove("settings/theme".to_string()).unwrap();
+        store.remove("missing".to_string()).unwrap();
+
+        let theme = "settings/theme".to_string();
+        assert_eq!(
+            *settings.events.lock().unwrap(),
+            vec![
+                (theme.clone(), None, Some(b"dark".to_vec())),
+                (theme.clone(), Some(b"dark".to_vec()), Some(b"light".to_vec())),
+                (theme.clone(), Some(b"light".to_vec()), None),
+            ]
+        );
+        assert_eq!(all.events.lock().unwrap().len(), 4);
+
+        assert!(store.unsubscribe(id).unwrap());
+        assert!(!store.unsubscribe(id).unwrap());
+        store.set("settings/font".to_string(), b"serif".to_vec()).unwrap();
+        assert_eq!(settings.events.lock().unwrap().len(), 3);
+        assert_eq!(all.events.lock().unwrap().len(), 5);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_observer_receives_changes_in_order() {
+        let path = temp_path("observer_order");
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        let observer = Arc::new(RecordingObserver::default());
+        store.subscribe(String::new(), observer.clone()).unwrap();
+
+        let writers: Vec<_> = (0..4u8)
+            .map(|writer| {
+                let store = store.clone();
+                std::thread::spawn(move || {
+                    for i in 0..25u8 {
+                        store.set("counter".to_string(), vec![writer, i]).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        // 各通知の変更前の値は、直前の通知の変更後の値と一致する
+        let events = observer.events.lock().unwrap();
+        assert_eq!(events.len(), 100);
+        let mut previous = None;
+        for (_, old_value, new_value) in events.iter() {
+            assert_eq!(*old_value, previous);
+            previous = new_value.clone();
+        }
+        assert_eq!(previous, store.get("counter".to_string()).unwrap());
+        cleanup(&path);
+    }
+
+    /// 通知を受けてストアを読み書きするオブザーバー
+    struct MirroringObserver {
+        store: Arc<SecureStore>,
+    }
+
+    impl StoreObserver for MirroringObserver {
+        fn on_changed(&self, key: String, _old: Option<Vec<u8>>, new_value: Option<Vec<u8>>) {
+            let current = self.store.get(key.clone()).unwrap();
+            assert_eq!(current, new_value);
+            if let Some(value) = new_value {
+                self.store.set(format!("mirror/{}", key), value).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_secure_store_observer_can_reenter_store() {
+        let path = temp_path("reenter");
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        let observer = Arc::new(MirroringObserver {
+            store: store.clone(),
+        });
+        let id = store.subscribe("data/".to_string(), observer).unwrap();
+        store.set("data/x".to_string(), b"1".to_vec()).unwrap();
+        assert_eq!(store.get("mirror/data/x".to_string()).unwrap(), Some(b"1".to_vec()));
+        // オブザーバーがストアへの参照を持つため、循環参照を解消する
+        store.unsubscribe(id).unwrap();
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_checkpoint() {
+        let path = temp_path("checkpoint");
+        {
+            let store = SecureStore::open(path.clone(), vec![3u8; 32]).unwrap();
+            for i in 0..CHECKPOINT_RECORDS + 5 {
+                store.set(format!("k{}", i % 10), i.to_be_bytes().to_vec()).unwrap();
+            }
+        }
+        let journal_len = fs::metadata(format!("{}.wal", path)).unwrap().len();
+        assert!(journal_len < 5 * 100);
+        assert!(Path::new(&path).exists());
+
+        let store = SecureStore::open(path.clone(), vec![3u8; 32]).unwrap();
+        assert_eq!(store.keys(String::new()).unwrap().len(), 10);
+        let last = CHECKPOINT_RECORDS + 4;
+        assert_eq!(
+            store.get(format!("k{}", last % 10)).unwrap(),
+            Some(last.to_be_bytes().to_vec())
+        );
+        let report = store.verify_integrity(true).unwrap();
+        assert!(report.ok, "{:?}", report.problems);
+        assert!(!report.repaired);
+        assert_eq!(report.journal_records, 5);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_recovers_from_torn_write() {
+        let path = temp_path("torn");
+        let journal = format!("{}.wal", path);
+        {
+            let store = SecureStore::open(path.clone(), vec![4u8; 32]).unwrap();
+            store.set("a".to_string(), b"1".to_vec()).unwrap();
+            store.set("b".to_string(), b"2".to_vec()).unwrap();
+        }
+        // 3件目のレコードの書き込み中に終了した状態を再現する
+        let complete = fs::read(&journal).unwrap();
+        {
+            let store = SecureStore::open(path.clone(), vec![4u8; 32]).unwrap();
+            store.set("c".to_string(), vec![9u8; 64]).unwrap();
+        }
+        let mut torn = fs::read(&journal).unwrap();
+        torn.truncate(complete.len() + 30);
+        fs::write(&journal, &torn).unwrap();
+
+        let store = SecureStore::open(path.clone(), vec![4u8; 32]).unwrap();
+        assert_eq!(store.keys(String::new()).unwrap(), vec!["a", "b"]);
+        assert_eq!(fs::metadata(&journal).unwrap().len(), complete.len() as u64);
+        store.set("d".to_string(), b"4".to_vec()).unwrap();
+        drop(store);
+
+        let store = SecureStore::open(path.clone(), vec![4u8; 32]).unwrap();
+        assert_eq!(store.keys(String::new()).unwrap(), vec!["a", "b", "d"]);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_ignores_stale_journal() {
+        let path = temp_path("stale");
+        let journal = format!("{}.wal", path);
+        let store = SecureStore::open(path.clone(), vec![5u8; 32]).unwrap();
+        store.set("x".to_string(), b"1".to_vec()).unwrap();
+        let stale = fs::read(&journal).unwrap();
+        store.remove("x".to_string()).unwrap();
+        {
+            let mut state = store.state.lock().unwrap();
+            store.checkpoint(&mut state).unwrap();
+        }
+        // スナップショットの書き直し後、ジャーナルを作り直す前に終了した状態を再現する
+        fs::write(&journal, &stale).unwrap();
+        drop(store);
+
+        let store = SecureStore::open(path.clone(), vec![5u8; 32]).unwrap();
+        assert_eq!(store.get("x".to_string()).unwrap(), None);
+        let report = store.verify_integrity(false).unwrap();
+        assert!(report.problems[0].contains("stale journal"), "{:?}", report.problems);
+        store.set("y".to_string(), b"2".to_vec()).unwrap();
+        drop(store);
+
+        let store = SecureStore::open(path.clone(), vec![5u8; 32]).unwrap();
+        assert_eq!(store.keys(String::new()).unwrap(), vec!["y"]);
+        assert!(store.verify_integrity(false).unwrap().ok);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_verify_integrity_repairs() {
+        let path = temp_path("verify");
+        let journal = format!("{}.wal", path);
+        let store = SecureStore::open(path.clone(), vec![6u8; 32]).unwrap();
+        store.set("a".to_string(), b"1".to_vec()).unwrap();
+        store.set("b".to_string(), b"2".to_vec()).unwrap();
+        let report = store.verify_integrity(true).unwrap();
+        assert!(report.ok);
+        assert_eq!(report.journal_records, 2);
+
+        let mut raw = fs::read(&journal).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0x01;
+        fs::write(&journal, raw).unwrap();
+        fs::write(format!("{}.tmp", path), b"partial").unwrap();
+
+        let report = store.verify_integrity(false).unwrap();
+        assert!(!report.ok);
+        assert!(!report.repaired);
+        assert_eq!(report.problems.len(), 3, "{:?}", report.problems);
+
+        let report = store.verify_integrity(true).unwrap();
+        assert!(report.repaired);
+        assert!(store.verify_integrity(true).unwrap().ok);
+        drop(store);
+
+        let store = SecureStore::open(path.clone(), vec![6u8; 32]).unwrap();
+        assert_eq!(store.get("b".to_string()).unwrap(), Some(b"2".to_vec()));
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_wrong_key_keeps_journal() {
+        let path = temp_path("wrong_key");
+        let journal = format!("{}.wal", path);
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        store.set("k".to_string(), b"v".to_vec()).unwrap();
+        drop(store);
+        let before = fs::read(&journal).unwrap();
+        match SecureStore::open(path.clone(), vec![2u8; 32]) {
+            Err(SecureStoreError::DecryptionFailed) => (),
+            other => panic!("Expected DecryptionFailed error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(fs::read(&journal).unwrap(), before);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_errors() {
+        let path = temp_path("errors");
+        match SecureStore::open(path.clone(), vec![0u8; 16]) {
+            Err(SecureStoreError::InvalidKeyLength(16)) => (),
+            other => panic!("Expected InvalidKeyLength error, got {:?}", other.map(|_| ())),
+        }
+        let store = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        store.set("k".to_string(), b"v".to_vec()).unwrap();
+        match SecureStore::open(path.clone(), vec![2u8; 32]) {
+            Err(SecureStoreError::DecryptionFailed) => (),
+            other => panic!("Expected DecryptionFailed error, got {:?}", other.map(|_| ())),
+        }
+        fs::write(&path, b"not a store").unwrap();
+        match SecureStore::open(path.clone(), vec![1u8; 32]) {
+            Err(SecureStoreError::CorruptedFile(_)) => (),
+            other => panic!("Expected CorruptedFile error, got {:?}", other.map(|_| ())),
+        }
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_async() {
+        let path = temp_path("async");
+        let store = SecureStore::open(path.clone(), vec![7u8; 32]).unwrap();
+        pollster::block_on(store.clone().set_async("user/a".to_string(), b"1".to_vec()))
+            .unwrap();
+        assert_eq!(
+            pollster::block_on(store.clone().get_async("user/a".to_string())).unwrap(),
+            Some(b"1".to_vec())
+        );
+        assert_eq!(
+            pollster::block_on(store.clone().keys_async("user/".to_string())).unwrap(),
+            vec!["user/a".to_string()]
+        );
+        assert!(pollster::block_on(store.clone().remove_async("user/a".to_string())).unwrap());
+        assert!(pollster::block_on(store.verify_integrity_async(false)).unwrap().ok);
+        cleanup(&path);
+    }
+
+    #[test]
+    fn test_secure_store_snapshot_export_import() {
+        let path = temp_path("snapshot_source");
+        let target_path = temp_path("snapshot_target");
+        let snapshot = temp_path("snapshot_file");
+        let source = SecureStore::open(path.clone(), vec![1u8; 32]).unwrap();
+        source.set("a".to_string(), b"new-a".to_vec()).unwrap();
+        source.set("b".to_string(), vec![0, 255]).unwrap();
+        source.export_snapshot(snapshot.clone(), "backup pass".to_string()).unwrap();
+
+        // 別の鍵のストアにも読み込める
+        let target = SecureStore::open(target_path.clone(), vec![2u8; 32]).unwrap();
+        target.set("a".to_string(), b"old-a".to_vec()).unwrap();
+        target.set("c".to_string(), b"c".to_vec()).unwrap();
+        let report = target
+            .import_snapshot(
+                snapshot.clone(),
+                "backup pass".to_string(),
+                MergeStrategy::KeepExisting,
+            )
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.removed), (1, 1, 0));
+        assert_eq!(target.get("a".to_string()).unwrap(), Some(b"old-a".to_vec()));
+        assert_eq!(target.get("b".to_string()).unwrap(), Some(vec![0, 255]));
+
+        let report = target
+            .import_snapshot(snapshot.clone(), "backup pass".to_string(), MergeStrategy::Replace)
+            .unwrap();
+        assert_eq!((report.imported, report.skipped, report.removed), (2, 0, 1));
+        assert_eq!(target.keys(String::new()).unwrap(), vec!["a", "b"]);
+        assert_eq!(target.get("a".to_string()).unwrap(), Some(b"new-a".to_vec()));
+
+        let result =
+            target.import_snapshot(snapshot.clone(), "wrong".to_string(), MergeStrategy::Overwrite);
+        match result {
+            Err(SnapshotError::InvalidPassphrase) => (),
+            other => panic!("Expected InvalidPassphrase error, got {:?}", other),
+        }
+        cleanup(&path);
+        cleanup(&target_path);
+        let _ = fs::remove_file(snapshot);
+    }
+}
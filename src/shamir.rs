@@ -0,0 +1,343 @@
+//! Shamirの秘密分散モジュール
+//!
+//! このモジュールは、秘密をGF(256)上のShamirの秘密分散で複数のシェアに分割する
+//! `split_secret`と、しきい値以上のシェアから秘密を復元する`combine_shares`を
+//! エクスポートします。ウォレット形式のバックアップ機能での利用を想定しています。
+//!
+//! # シェア形式
+//! ```text
+//! バージョン(1) | しきい値(1) | x座標(1) | y値（秘密長+4） | チェックサム(4)
+//! ```
+//! 秘密には分割前にSHA-256の先頭4バイトを付加しており、復元後に照合することで
+//! 異なる秘密のシェアの混在を検出します。各シェア末尾のチェックサムは
+//! シェア自体の破損（書き写し間違いなど）を検出するためのものです。
+
+use std::collections::HashSet;
+
+use rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+/// 現在のシェア形式のバージョン
+const SHARE_VERSION: u8 = 1;
+
+/// シェアのヘッダー長（バージョン・しきい値・x座標）
+const SHARE_HEADER_LEN: usize = 3;
+
+/// チェックサムの長さ
+const CHECKSUM_LEN: usize = 4;
+
+/// 分割できる秘密の最大長（64 KiB）
+const MAX_SECRET_LEN: usize = 64 * 1024;
+
+/// 秘密分散で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum SecretSharingError {
+    /// 秘密が空、または長すぎる場合
+    #[error("Invalid secret length: {0} bytes (expected 1 to 65536)")]
+    InvalidSecretLength(u64),
+    /// しきい値・シェア数の組み合わせが不正な場合
+    #[error("Invalid parameters: threshold {threshold}, shares {shares} (expected 2 <= threshold <= shares <= 255)")]
+    InvalidParameters { threshold: u32, shares: u32 },
+    /// シェアの数がしきい値に満たない場合
+    #[error("Insufficient shares: {required} required, {provided} provided")]
+    InsufficientShares { required: u32, provided: u32 },
+    /// シェアの形式が不正、またはチェックサムが一致しない場合
+    #[error("Share {0} is corrupted")]
+    CorruptedShare(u32),
+    /// しきい値や長さが異なる、x座標が重複するなど、シェアの組み合わせが不整合な場合
+    #[error("Inconsistent shares: {0}")]
+    InconsistentShares(String),
+    /// 復元した秘密の検証に失敗した場合（異なる秘密のシェアが混在している）
+    #[error("Recovered secret failed verification")]
+    VerificationFailed,
+}
+
+/// GF(256)の乗算（AESと同じ既約多項式 x^8 + x^4 + x^3 + x + 1）
+fn gf_mul(mut a: u8, mut b: u8) -> u8 {
+    let mut product = 0u8;
+    while b != 0 {
+        if b & 1 != 0 {
+            product ^= a;
+        }
+        let carry = a & 0x80;
+        a <<= 1;
+        if carry != 0 {
+            a ^= 0x1b;
+        }
+        b >>= 1;
+    }
+    product
+}
+
+/// GF(256)の逆元（a^254）
+fn gf_inv(a: u8) -> u8 {
+    let mut result = 1u8;
+    let mut base = a;
+    let mut exp = 254u8;
+    while exp != 0 {
+        if exp & 1 != 0 {
+            result = gf_mul(result, base);
+        }
+        base = gf_mul(base, base);
+        exp >>= 1;
+    }
+    result
+}
+
+/// シェアの内容（チェックサムを除く）からチェックサムを計算します
+fn share_checksum(body: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let digest = Sha256::digest(body);
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// 秘密の検証用ダイジェストを計算します
+fn secret_digest(secret: &[u8]) -> [u8; CHECKSUM_LEN] {
+    share_checksum(secret)
+}
+
+/// 秘密をしきい値付きのシェアに分割します
+///
+/// 任意の`threshold`個のシェアから秘密を復元でき、それ未満のシェアからは
+/// 秘密に関する情報は一切得られません。
+///
+/// # Arguments
+/// * `secret` - 分割する秘密（1〜65536バイト）
+/// * `threshold` - 復元に必要なシェア数（2以上）
+/// * `shares` - 生成するシェア数（`threshold`以上255以下）
+///
+/// # Errors
+/// * `SecretSharingError::InvalidSecretLength` - 秘密が空または長すぎる場合
+/// * `SecretSharingError::InvalidParameters` - しきい値・シェア数が不正な場合
+///
+/// # Example
+/// ```
+/// let shares = split_secret(seed, 2, 3)?;
+/// let recovered = combine_shares(vec![shares[0].clone(), shares[2].clone()])?;
+/// assert_eq!(recovered, seed);
+/// ```
+#[uniffi::export]
+pub fn split_secret(
+    secret: Vec<u8>,
+    threshold: u32,
+    shares: u32,
+) -> Result<Vec<Vec<u8>>, SecretSharingError> {
+    if secret.is_empty() || secret.len() > MAX_SECRET_LEN {
+        return Err(SecretSharingError::InvalidSecretLength(secret.len() as u64));
+    }
+    if threshold < 2 || threshold > shares || shares > 255 {
+        return Err(SecretSharingError::InvalidParameters { threshold, shares });
+    }
+
+    let mut payload = secret;
+    payload.extend_from_slice(&secret_digest(&payload));
+
+    // バイトごとに、定数項が秘密となる(threshold - 1)次のランダムな多項式を作る
+    let degree = threshold as usize - 1;
+    let mut coefficients = vec![0u8; payload.len() * degree];
+    OsRng.fill_bytes(&mut coefficients);
+
+    let result = (1..=shares as u8)
+        .map(|x| {
+            let mut share = Vec::with_capacity(SHARE_HEADER_LEN + payload.len() + CHECKSUM_LEN);
+            share.extend_from_slice(&[SHARE_VERSION, threshold as u8, x]);
+            for (i, secret_byte) in payload.iter().enumerate() {
+                let coeffs = &coefficients[i * degree..(i + 1) * degree];
+                // ホーナー法で多項式を評価する
+                let y = coeffs
+                    .iter()
+                    .rev()
+                    .fold(0u8, |acc, c| gf_mul(acc, x) ^ c);
+                share.push(gf_mul(y, x) ^ secret_byte);
+            }
+            let checksum = share_checksum(&share);
+            share.extend_from_slice(&checksum);
+            share
+        })
+        .collect();
+    Ok(result)
+}
+
+/// シェアから秘密を復元します
+///
+/// シェアの順序は問いません。しきい値より多くのシェアを渡した場合、
+/// 余分なシェアも整合性の検査に使用されます。
+///
+/// # Arguments
+/// * `shares` - `split_secret`が返したシェアの一部または全部
+///
+/// # Errors
+/// * `SecretSharingError::CorruptedShare` - シェアの形式やチェックサムが不正な場合
+/// * `SecretSharingError::InconsistentShares` - シェアの組み合わせが不整合な場合
+/// * `SecretSharingError::InsufficientShares` - シェアがしきい値に満たない場合
+/// * `SecretSharingError::VerificationFailed` - 復元した秘密の検証に失敗した場合
+#[uniffi::export]
+pub fn combine_shares(shares: Vec<Vec<u8>>) -> Result<Vec<u8>, SecretSharingError> {
+    let mut parsed = Vec::with_capacity(shares.len());
+    for (index, share) in shares.iter().enumerate() {
+        let corrupted = SecretSharingError::CorruptedShare(index as u32);
+        if share.len() < SHARE_HEADER_LEN + CHECKSUM_LEN + CHECKSUM_LEN + 1
+            || share[0] != SHARE_VERSION
+        {
+            return Err(corrupted);
+        }
+        let (body, checksum) = share.split_at(share.len() - CHECKSUM_LEN);
+        if share_checksum(body) != checksum {
+            return Err(corrupted);
+        }
+        parsed.push((body[1], body[2], &body[SHARE_HEADER_LEN..]));
+    }
+
+    let Some(&(threshold, _, first_ys)) = parsed.first() else {
+        return Err(SecretSharingError::InsufficientShares { required: 2, provided: 0 });
+    };
+    let mut seen = HashSet::new();
+    for (share_threshold, x, ys) in &parsed {
+        if *share_threshold != threshold {
+            return Err(SecretSharingError::InconsistentShares(
+                "shares have different thresholds".to_string(),
+            ));
+        }
+        if ys.len() != first_ys.len() {
+            return Err(SecretSharingError::InconsistentShares(
+                "shares have different lengths".to_string(),
+            ));
+        }
+        if *x == 0 || !seen.insert(*x) {
+            return Err(SecretSharingError::InconsistentShares(format!(
+                "duplicate or invalid share index: {}",
+                x
+            )));
+        }
+    }
+    if parsed.len() < threshold as usize {
+        return Err(SecretSharingError::InsufficientShares {
+            required: u32::from(threshold),
+            provided: parsed.len() as u32,
+        });
+    }
+
+    // ラグランジュ補間で x = 0 の値を求める（GF(256)では加減算はXOR）
+    let interpolate = |points: &[(u8, u8, &[u8])], at: u8| -> Vec<u8> {
+        let weights: Vec<u8> = points
+            .iter()
+            .map(|(_, xi, _)| {
+                points
+                    .iter()
+                    .filter(|(_, xj, _)| xj != xi)
+                    .fold(1u8, |acc, (_, xj, _)| {
+                        gf_mul(acc, gf_mul(at ^ xj, gf_inv(xi ^ xj)))
+                    })
+            })
+            .collect();
+        (0..first_ys.len())
+            .map(|i| {
+                points
+                    .iter()
+                    .zip(&weights)
+                    .fold(0u8, |acc, ((_, _, ys), w)| acc ^ gf_mul(ys[i], *w))
+            })
+            .collect()
+    };
+
+    let (basis, extra) = parsed.split_at(threshold as usize);
+    let payload = interpolate(basis, 0);
+    // しきい値を超えるシェアが同じ多項式上にあるかを確認する
+    for (_, x, ys) in extra {
+        if interpolate(basis, *x) != *ys {
+            return Err(SecretSharingError::VerificationFailed);
+        }
+    }
+
+    let (secret, digest) = payload.split_at(payload.len() - CHECKSUM_LEN);
+    if secret_digest(secret) != digest {
+        return Err(SecretSharingError::VerificationFailed);
+    }
+    Ok(secret.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gf_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(gf_mul(a, gf_inv(a)), 1);
+        }
+    }
+
+    #[test]
+    fn test_split_and_combine_any_subset() {
+        let secret = b"correct horse battery staple".to_vec();
+        let shares = split_secret(secret.clone(), 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert_eq!(shares[0].len(), secret.len() + 11);
+
+        for (a, b, c) in [(0, 1, 2), (0, 2, 4), (4, 3, 1)] {
+            let subset = vec![shares[a].clone(), shares[b].clone(), shares[c].clone()];
+            assert_eq!(combine_shares(subset).unwrap(), secret);
+        }
+        assert_eq!(combine_shares(shares).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_combine_insufficient_shares() {
+        let shares = split_secret(vec![1, 2, 3], 3, 5).unwrap();
+        match combine_shares(shares[..2].to_vec()) {
+            Err(SecretSharingError::InsufficientShares { required: 3, provided: 2 }) => (),
+            other => panic!("Expected InsufficientShares error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_combine_corrupted_share() {
+        let mut shares = split_secret(vec![9; 16], 2, 3).unwrap();
+        shares[1][5] ^= 0xff;
+        match combine_shares(shares) {
+            Err(SecretSharingError::CorruptedShare(1)) => (),
+            other => panic!("Expected CorruptedShare error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_combine_inconsistent_shares() {
+        let a = split_secret(vec![1; 16], 2, 3).unwrap();
+        let b = split_secret(vec![1; 16], 3, 3).unwrap();
+        match combine_shares(vec![a[0].clone(), b[1].clone()]) {
+            Err(SecretSharingError::InconsistentShares(_)) => (),
+            other => panic!("Expected InconsistentShares error, got {:?}", other),
+        }
+        match combine_shares(vec![a[0].clone(), a[0].clone()]) {
+            Err(SecretSharingError::InconsistentShares(_)) => (),
+            other => panic!("Expected InconsistentShares error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_combine_shares_from_different_secrets() {
+        let a = split_secret(vec![1; 16], 2, 3).unwrap();
+        let b = split_secret(vec![2; 16], 2, 3).unwrap();
+        match combine_shares(vec![a[0].clone(), b[1].clone()]) {
+            Err(SecretSharingError::VerificationFailed) => (),
+            other => panic!("Expected VerificationFailed error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_split_invalid_parameters() {
+        match split_secret(vec![1], 1, 3) {
+            Err(SecretSharingError::InvalidParameters { threshold: 1, shares: 3 }) => (),
+            other => panic!("Expected InvalidParameters error, got {:?}", other),
+        }
+        match split_secret(vec![1], 4, 3) {
+            Err(SecretSharingError::InvalidParameters { .. }) => (),
+            other => panic!("Expected InvalidParameters error, got {:?}", other),
+        }
+        match split_secret(Vec::new(), 2, 3) {
+            Err(SecretSharingError::InvalidSecretLength(0)) => (),
+            other => panic!("Expected InvalidSecretLength error, got {:?}", other),
+        }
+    }
+}
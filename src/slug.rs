@@ -0,0 +1,149 @@
+//! スラッグ生成モジュール
+//!
+//! このモジュールは、タイトルなどの文字列からURLに使えるスラッグを生成する`slugify`を
+//! エクスポートします。Unicodeの文字はASCIIに翻字し（`東京`は`dong-jing`）、英数字以外の
+//! 文字の並びを区切り文字に置き換えます。翻字にはPythonのUnidecodeと同じ対応表を使うため、
+//! バックエンドが生成するスラッグと同じ結果になります。
+
+use deunicode::deunicode_with_tofu;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// スラッグから取り除くアポストロフィ（`Don't`は`dont`）
+const APOSTROPHES: [char; 2] = ['\'', '\u{2019}'];
+
+/// スラッグ生成のオプション
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct SlugOptions {
+    /// 単語の区切り文字
+    #[uniffi(default = "-")]
+    pub separator: String,
+    /// 最大文字数（Unicodeのスカラー値の数、`None`の場合は制限なし）
+    #[uniffi(default = None)]
+    pub max_length: Option<u32>,
+    /// 小文字に変換するかどうか
+    #[uniffi(default = true)]
+    pub lowercase: bool,
+    /// ASCIIに翻字するかどうか（`false`の場合はUnicodeの英数字をそのまま残します）
+    #[uniffi(default = true)]
+    pub transliterate: bool,
+}
+
+impl Default for SlugOptions {
+    fn default() -> Self {
+        Self { separator: "-".to_string(), max_length: None, lowercase: true, transliterate: true }
+    }
+}
+
+/// 書記素クラスタが単語の一部（英数字で始まる）かどうかを返します
+fn is_word(grapheme: &str) -> bool {
+    grapheme.chars().next().is_some_and(char::is_alphanumeric)
+}
+
+/// 文字列からスラッグを生成します
+///
+/// 英数字以外の文字の並びは1つの区切り文字になり、先頭と末尾の区切り文字は取り除きます。
+/// アポストロフィは区切らずに取り除きます。`max_length`を超える場合は書記素クラスタの
+/// 境界で切り詰めるため、結合文字や絵文字の途中で切れることはありません。
+///
+/// # Arguments
+/// * `text` - 元の文字列
+/// * `options` - オプション（省略時は`-`区切り・小文字・ASCIIに翻字）
+///
+/// # Example
+/// ```
+/// let slug = slugify("Ünïcödé & Friends: 東京 2024!".to_string(), None);
+/// assert_eq!(slug, "unicode-friends-dong-jing-2024");
+/// ```
+#[uniffi::export(default(options = None))]
+pub fn slugify(text: String, options: Option<SlugOptions>) -> String {
+    let options = options.unwrap_or_default();
+    // 翻字できない文字は単語の区切りとして扱う
+    let text = if options.transliterate { deunicode_with_tofu(&text, " ") } else { text };
+    let text = text.replace(APOSTROPHES, "");
+    let text = if options.lowercase { text.to_lowercase() } else { text };
+
+    let max_length = options.max_length.map(|max_length| max_length as usize);
+    let separator = options.separator.as_str();
+    let separator_length = separator.chars().count();
+    let mut slug = String::with_capacity(text.len());
+    let mut length = 0;
+    let mut pending_separator = false;
+    for grapheme in text.graphemes(true) {
+        if !is_word(grapheme) {
+            pending_separator = !slug.is_empty();
+            continue;
+        }
+        let grapheme_length = grapheme.chars().count();
+        let added = grapheme_length + if pending_separator { separator_length } else { 0 };
+        if max_length.is_some_and(|max_length| length + added > max_length) {
+            break;
+        }
+        if pending_separator {
+            slug.push_str(separator);
+            pending_separator = false;
+        }
+        slug.push_str(grapheme);
+        length += added;
+    }
+    slug
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slug(text: &str) -> String {
+        slugify(text.to_string(), None)
+    }
+
+    fn slug_with(text: &str, options: SlugOptions) -> String {
+        slugify(text.to_string(), Some(options))
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slug("Hello, World!"), "hello-world");
+        assert_eq!(slug("  --Rust   is  __fast__--  "), "rust-is-fast");
+        assert_eq!(slug("Don't Stop Believin’"), "dont-stop-believin");
+        assert_eq!(slug("Ünïcödé & Friends: 東京 2024!"), "unicode-friends-dong-jing-2024");
+        assert_eq!(slug("Crème Brûlée à l'œuf"), "creme-brulee-a-loeuf");
+        assert_eq!(slug("Straße Ελληνικά Москва"), "strasse-ellenika-moskva");
+        assert_eq!(slug(""), "");
+        assert_eq!(slug("!!!"), "");
+    }
+
+    #[test]
+    fn test_slugify_options() {
+        let options =
+            SlugOptions { separator: "_".to_string(), lowercase: false, ..Default::default() };
+        assert_eq!(slug_with("Hello World 2024", options), "Hello_World_2024");
+
+        let unicode = SlugOptions { transliterate: false, ..Default::default() };
+        assert_eq!(slug_with("東京タワー の 夜景!", unicode.clone()), "東京タワー-の-夜景");
+        assert_eq!(slug_with("Straße Café", unicode), "straße-café");
+
+        let empty_separator = SlugOptions { separator: String::new(), ..Default::default() };
+        assert_eq!(slug_with("a b c", empty_separator), "abc");
+    }
+
+    #[test]
+    fn test_slugify_max_length() {
+        let limit =
+            |max_length: u32| SlugOptions { max_length: Some(max_length), ..Default::default() };
+        assert_eq!(slug_with("hello world foo", limit(10)), "hello-worl");
+        // 区切り文字で終わる場合は区切り文字を含めない
+        assert_eq!(slug_with("hello world", limit(6)), "hello");
+        assert_eq!(slug_with("hello world", limit(5)), "hello");
+        assert_eq!(slug_with("hello", limit(0)), "");
+
+        // 結合文字を含む書記素クラスタは途中で切らない（`e`と結合アクセントで2文字）
+        let unicode = |max_length: u32| SlugOptions {
+            max_length: Some(max_length),
+            transliterate: false,
+            ..Default::default()
+        };
+        assert_eq!(slug_with("cafe\u{301} noir", unicode(4)), "caf");
+        assert_eq!(slug_with("cafe\u{301} noir", unicode(5)), "cafe\u{301}");
+        assert_eq!(slug_with("東京タワー", unicode(2)), "東京");
+    }
+}
@@ -0,0 +1,248 @@
+//! ストアのスナップショット（暗号化バックアップ）モジュール
+//!
+//! このモジュールは、`SecureStore`と`DocumentStore`の内容をパスフレーズで暗号化した
+//! 1つのファイルに書き出し、別の端末などで読み込むためのファイル形式と、
+//! 読み込み時の統合方法を表す`MergeStrategy`をエクスポートします。
+//! 書き出し・読み込みのメソッドは各ストアに実装されています。
+//!
+//! # ファイル形式
+//! ```text
+//! "MSNP" | バージョン(1) | 種別(1) | memory_kib(u32) | iterations(u32) | parallelism(u32)
+//!        | ソルト(16) | ノンス(12) | 暗号文（タグを含む）
+//! ```
+//! 鍵はArgon2idでパスフレーズから導出し、内容はAES-256-GCMで暗号化します。
+//! ヘッダー全体を追加認証データとして使用します。平文はストアの種別ごとのJSONです。
+
+use std::path::Path;
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+
+use crate::document_store::DocumentStoreError;
+use crate::file_io::{read_file_with_limit, write_atomic, FileIoError};
+use crate::password::Argon2Params;
+use crate::secure_store::SecureStoreError;
+
+/// ファイル先頭のマジックナンバー
+const MAGIC: &[u8; 4] = b"MSNP";
+
+/// ファイル形式のバージョン
+const FORMAT_VERSION: u8 = 1;
+
+/// ヘッダーの長さ（マジック4 + バージョン1 + 種別1 + パラメータ12 + ソルト16）
+const HEADER_LEN: usize = 34;
+
+/// ソルトの長さ
+const SALT_LEN: usize = 16;
+
+/// AES-GCMのノンスの長さ
+const NONCE_LEN: usize = 12;
+
+/// 読み込むスナップショットの最大サイズ（1 GiB）
+const MAX_SNAPSHOT_LEN: u64 = 1024 * 1024 * 1024;
+
+/// スナップショットの書き出し・読み込みで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum SnapshotError {
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access snapshot file: {0}")]
+    IoError(String),
+    /// パスフレーズが誤っている、または内容が改ざんされている場合
+    #[error("Invalid passphrase or tampered snapshot")]
+    InvalidPassphrase,
+    /// ファイルの内容が不正な場合
+    #[error("Snapshot is corrupted: {0}")]
+    CorruptedSnapshot(String),
+    /// 別の種類のストアのスナップショットの場合
+    #[error("Snapshot was exported from a different kind of store")]
+    KindMismatch,
+    /// ストアの読み書きに失敗した場合
+    #[error("Store error: {0}")]
+    StoreError(String),
+}
+
+impl From<FileIoError> for SnapshotError {
+    fn from(error: FileIoError) -> Self {
+        SnapshotError::IoError(error.to_string())
+    }
+}
+
+impl From<SecureStoreError> for SnapshotError {
+    fn from(error: SecureStoreError) -> Self {
+        SnapshotError::StoreError(error.to_string())
+    }
+}
+
+impl From<DocumentStoreError> for SnapshotError {
+    fn from(error: DocumentStoreError) -> Self {
+        SnapshotError::StoreError(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for SnapshotError {
+    fn from(error: rusqlite::Error) -> Self {
+        SnapshotError::StoreError(error.to_string())
+    }
+}
+
+/// スナップショットの読み込み時に既存の内容と統合する方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum MergeStrategy {
+    /// 既存の内容をすべて削除し、スナップショットの内容に置き換える
+    Replace,
+    /// 同じキーが既にある場合はスナップショットの値で上書きする
+    Overwrite,
+    /// 同じキーが既にある場合は既存の値を残す
+    KeepExisting,
+}
+
+/// スナップショットの読み込み結果
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct SnapshotImportReport {
+    /// 書き込んだエントリ数
+    pub imported: u64,
+    /// 既存の値を残したため書き込まなかったエントリ数
+    pub skipped: u64,
+    /// `MergeStrategy::Replace`で削除した既存のエントリ数
+    pub removed: u64,
+}
+
+/// スナップショットを書き出したストアの種別
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SnapshotKind {
+    KeyValue = 1,
+    Documents = 2,
+}
+
+/// パスフレーズからAES-256-GCMの鍵を導出します
+fn derive_cipher(
+    passphrase: &str,
+    params: &Argon2Params,
+    salt: &[u8],
+) -> Result<Aes256Gcm, String> {
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+        .map_err(|e| e.to_string())?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| e.to_string())?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+/// 平文をパスフレーズで暗号化し、スナップショットファイルとしてアトミックに書き出します
+pub(crate) fn write_snapshot(
+    path: &str,
+    passphrase: &str,
+    kind: SnapshotKind,
+    plaintext: &[u8],
+) -> Result<(), SnapshotError> {
+    let params = Argon2Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    let mut header = [0u8; HEADER_LEN];
+    header[..4].copy_from_slice(MAGIC);
+    header[4] = FORMAT_VERSION;
+    header[5] = kind as u8;
+    header[6..10].copy_from_slice(&params.memory_kib.to_be_bytes());
+    header[10..14].copy_from_slice(&params.iterations.to_be_bytes());
+    header[14..18].copy_from_slice(&params.parallelism.to_be_bytes());
+    header[18..].copy_from_slice(&salt);
+
+    let cipher = derive_cipher(passphrase, &params, &salt).map_err(SnapshotError::StoreError)?;
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad: &header })
+        .map_err(|_| SnapshotError::StoreError("encryption failed".to_string()))?;
+    write_atomic(Path::new(path), &[&header, &nonce, &ciphertext])
+        .map_err(|e| SnapshotError::IoError(e.to_string()))
+}
+
+/// スナップショットファイルを読み込み、パスフレーズで復号した平文を返します
+pub(crate) fn read_snapshot(
+    path: &str,
+    passphrase: &str,
+    kind: SnapshotKind,
+) -> Result<Vec<u8>, SnapshotError> {
+    let data = read_file_with_limit(path.to_string(), MAX_SNAPSHOT_LEN)?;
+    if data.len() < HEADER_LEN + NONCE_LEN || &data[..4] != MAGIC {
+        return Err(SnapshotError::CorruptedSnapshot("not a snapshot file".to_string()));
+    }
+    if data[4] != FORMAT_VERSION {
+        return Err(SnapshotError::CorruptedSnapshot(format!(
+            "unsupported format version: {}",
+            data[4]
+        )));
+    }
+    if data[5] != kind as u8 {
+        return Err(SnapshotError::KindMismatch);
+    }
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([data[offset], data[offset + 1], data[offset + 2], data[offset + 3]])
+    };
+    let params = Argon2Params {
+        memory_kib: read_u32(6),
+        iterations: read_u32(10),
+        parallelism: read_u32(14),
+    };
+    // 別の端末から受け取ったファイルの値で巨大なメモリ確保をしないよう、鍵導出の前に確認する
+    if !params.is_within_file_limits() {
+        return Err(SnapshotError::CorruptedSnapshot(format!(
+            "key derivation parameters exceed the limit: {:?}",
+            params
+        )));
+    }
+    let (header, rest) = data.split_at(HEADER_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = derive_cipher(passphrase, &params, &header[18..])
+        .map_err(SnapshotError::CorruptedSnapshot)?;
+    cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: header })
+        .map_err(|_| SnapshotError::InvalidPassphrase)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!("mobile_snapshot_{}_{}.bin", name, std::process::id()))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn test_snapshot_roundtrip_and_errors() {
+        let path = temp_path("roundtrip");
+        write_snapshot(&path, "passphrase", SnapshotKind::KeyValue, b"{}").unwrap();
+        assert_eq!(read_snapshot(&path, "passphrase", SnapshotKind::KeyValue).unwrap(), b"{}");
+        match read_snapshot(&path, "wrong", SnapshotKind::KeyValue) {
+            Err(SnapshotError::InvalidPassphrase) => (),
+            other => panic!("Expected InvalidPassphrase error, got {:?}", other),
+        }
+        match read_snapshot(&path, "passphrase", SnapshotKind::Documents) {
+            Err(SnapshotError::KindMismatch) => (),
+            other => panic!("Expected KindMismatch error, got {:?}", other),
+        }
+
+        // ヘッダーのmemory_kibを改ざんした場合は、鍵導出の前にエラーになる
+        let mut tampered = std::fs::read(&path).unwrap();
+        tampered[6..10].copy_from_slice(&u32::MAX.to_be_bytes());
+        std::fs::write(&path, tampered).unwrap();
+        match read_snapshot(&path, "passphrase", SnapshotKind::KeyValue) {
+            Err(SnapshotError::CorruptedSnapshot(message)) => assert!(message.contains("limit")),
+            other => panic!("Expected CorruptedSnapshot error, got {:?}", other),
+        }
+        std::fs::write(&path, b"not a snapshot").unwrap();
+        match read_snapshot(&path, "passphrase", SnapshotKind::KeyValue) {
+            Err(SnapshotError::CorruptedSnapshot(_)) => (),
+            other => panic!("Expected CorruptedSnapshot error, got {:?}", other),
+        }
+        let _ = std::fs::remove_file(path);
+    }
+}
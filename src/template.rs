@@ -0,0 +1,170 @@
+//! テンプレート文字列の展開モジュール
+//!
+//! このモジュールは、`{placeholder}`形式のプレースホルダーを値で置換する
+//! 小さなテンプレートエンジンを提供します。`{{`と`}}`はそれぞれ
+//! リテラルの`{`と`}`として出力されます。
+
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+/// テンプレート展開時に発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum TemplateError {
+    /// プレースホルダーに対応する値が指定されていない場合
+    #[error("Missing value for placeholder: {0}")]
+    MissingKey(String),
+    /// `{`が閉じられていない場合
+    #[error("Unclosed placeholder starting at byte {0}")]
+    UnclosedPlaceholder(u64),
+    /// 対応する`{`のない`}`が現れた場合
+    #[error("Unexpected closing brace at byte {0}")]
+    UnexpectedClosingBrace(u64),
+    /// プレースホルダー名が空、または不正な文字を含む場合
+    #[error("Invalid placeholder name at byte {0}")]
+    InvalidPlaceholder(u64),
+}
+
+/// プレースホルダー名として使用できる文字かどうかを判定します
+fn is_placeholder_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-'
+}
+
+/// テンプレート文字列のプレースホルダーを値で置換します
+///
+/// # Arguments
+/// * `template` - `{name}`形式のプレースホルダーを含むテンプレート
+/// * `values` - プレースホルダー名と置換する値の対応
+///
+/// # Errors
+/// * `TemplateError::MissingKey` - 値が指定されていないプレースホルダーがある場合
+/// * `TemplateError::UnclosedPlaceholder` - `{`が閉じられていない場合
+/// * `TemplateError::UnexpectedClosingBrace` - 対応のない`}`がある場合
+/// * `TemplateError::InvalidPlaceholder` - プレースホルダー名が空または不正な場合
+///
+/// # Example
+/// ```
+/// let mut values = HashMap::new();
+/// values.insert("name".to_string(), "Alice".to_string());
+/// let text = render_template("Hello, {name}! {{literal}}", values)?;
+/// assert_eq!(text, "Hello, Alice! {literal}");
+/// ```
+#[uniffi::export]
+pub fn render_template(
+    template: &str,
+    values: HashMap<String, String>,
+) -> Result<String, TemplateError> {
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.char_indices().peekable();
+
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '{' => {
+                // `{{`はリテラルの`{`
+                if matches!(chars.peek(), Some((_, '{'))) {
+                    chars.next();
+                    output.push('{');
+                    continue;
+                }
+                let mut key = String::new();
+                let mut closed = false;
+                for (_, c) in chars.by_ref() {
+                    if c == '}' {
+                        closed = true;
+                        break;
+                    }
+                    key.push(c);
+                }
+                if !closed {
+                    return Err(TemplateError::UnclosedPlaceholder(pos as u64));
+                }
+                let key = key.trim();
+                if key.is_empty() || !key.chars().all(is_placeholder_char) {
+                    return Err(TemplateError::InvalidPlaceholder(pos as u64));
+                }
+                let value = values
+                    .get(key)
+                    .ok_or_else(|| TemplateError::MissingKey(key.to_string()))?;
+                output.push_str(value);
+            }
+            '}' => {
+                // `}}`はリテラルの`}`
+                if matches!(chars.peek(), Some((_, '}'))) {
+                    chars.next();
+                    output.push('}');
+                } else {
+                    return Err(TemplateError::UnexpectedClosingBrace(pos as u64));
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_template_basic() {
+        let result = render_template(
+            "Hello, {name}! You have {count} new messages.",
+            values(&[("name", "Alice"), ("count", "3")]),
+        );
+        assert_eq!(result.unwrap(), "Hello, Alice! You have 3 new messages.");
+    }
+
+    #[test]
+    fn test_render_template_escaping() {
+        let result = render_template("{{name}} is {name}}}", values(&[("name", "Bob")]));
+        assert_eq!(result.unwrap(), "{name} is Bob}");
+    }
+
+    #[test]
+    fn test_render_template_value_not_reexpanded() {
+        let result = render_template("{a}", values(&[("a", "{b}"), ("b", "x")]));
+        assert_eq!(result.unwrap(), "{b}");
+    }
+
+    #[test]
+    fn test_render_template_missing_key() {
+        match render_template("Hi {name}", HashMap::new()) {
+            Err(TemplateError::MissingKey(key)) => assert_eq!(key, "name"),
+            _ => panic!("Expected MissingKey error"),
+        }
+    }
+
+    #[test]
+    fn test_render_template_unclosed() {
+        match render_template("Hi {name", values(&[("name", "x")])) {
+            Err(TemplateError::UnclosedPlaceholder(3)) => (),
+            _ => panic!("Expected UnclosedPlaceholder error"),
+        }
+    }
+
+    #[test]
+    fn test_render_template_unexpected_closing() {
+        match render_template("Hi }", HashMap::new()) {
+            Err(TemplateError::UnexpectedClosingBrace(3)) => (),
+            _ => panic!("Expected UnexpectedClosingBrace error"),
+        }
+    }
+
+    #[test]
+    fn test_render_template_invalid_placeholder() {
+        match render_template("Hi {}", HashMap::new()) {
+            Err(TemplateError::InvalidPlaceholder(3)) => (),
+            _ => panic!("Expected InvalidPlaceholder error"),
+        }
+    }
+}
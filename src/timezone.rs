@@ -0,0 +1,229 @@
+//! タイムゾーン変換モジュール
+//!
+//! このモジュールは、組み込みのIANAタイムゾーンデータベース（tzdata）を使って
+//! タイムゾーン間の変換を行う`convert_timezone`・`timezone_offset_at`・`list_timezones`を
+//! エクスポートします。端末のtzdataのバージョンに依存しないため、夏時間の切り替えを含む
+//! スケジュール計算がすべてのプラットフォームで同じ結果になります。
+
+use std::str::FromStr;
+
+use chrono::{DateTime, LocalResult, NaiveDateTime, Offset, TimeDelta, TimeZone};
+use chrono_tz::{Tz, TZ_VARIANTS};
+
+use crate::datetime::{parse_fixed_offset, DateTimeError, Timestamp};
+
+/// IANAタイムゾーン名を解決します（大文字・小文字は区別しません）
+fn parse_tz(name: &str) -> Option<Tz> {
+    let name = name.trim();
+    Tz::from_str(name)
+        .ok()
+        .or_else(|| TZ_VARIANTS.iter().copied().find(|tz| tz.name().eq_ignore_ascii_case(name)))
+}
+
+/// UNIX時刻をUTC日時に変換します
+fn utc_datetime(unix_seconds: i64) -> Result<NaiveDateTime, DateTimeError> {
+    DateTime::from_timestamp(unix_seconds, 0).map(|utc| utc.naive_utc()).ok_or_else(|| {
+        DateTimeError::OutOfRange(format!("timestamp is out of range: {}", unix_seconds))
+    })
+}
+
+/// 解決済みのタイムゾーン
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Zone {
+    /// IANAタイムゾーン
+    Named(Tz),
+    /// 固定オフセット（秒）
+    Fixed(i32),
+}
+
+impl Zone {
+    /// IANAタイムゾーン名（`Asia/Tokyo`）、または`UTC`・`+09:00`などの固定オフセットを解決します
+    pub(crate) fn parse(timezone: &str) -> Result<Self, DateTimeError> {
+        match parse_tz(timezone) {
+            Some(tz) => Ok(Zone::Named(tz)),
+            None => parse_fixed_offset(timezone).map(Zone::Fixed),
+        }
+    }
+
+    /// 指定した時点でのUTCオフセット（秒）を返します
+    pub(crate) fn offset_at(self, unix_seconds: i64) -> Result<i32, DateTimeError> {
+        match self {
+            Zone::Named(tz) => {
+                let utc = utc_datetime(unix_seconds)?;
+                Ok(tz.offset_from_utc_datetime(&utc).fix().local_minus_utc())
+            }
+            Zone::Fixed(offset) => Ok(offset),
+        }
+    }
+
+    /// このタイムゾーンでのローカル日時をUNIX時刻に変換します
+    ///
+    /// 夏時間の開始で存在しない時刻は切り替え前のオフセットで解釈し
+    /// （結果は切り替え量だけ後ろにずれる）、夏時間の終了で2回現れる時刻は早い方を選びます。
+    pub(crate) fn to_unix(self, local: &NaiveDateTime) -> Result<i64, DateTimeError> {
+        let tz = match self {
+            Zone::Named(tz) => tz,
+            Zone::Fixed(offset) => return Ok(local.and_utc().timestamp() - i64::from(offset)),
+        };
+        match tz.from_local_datetime(local) {
+            LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => {
+                Ok(datetime.timestamp())
+            }
+            LocalResult::None => {
+                let before = local
+                    .checked_sub_signed(TimeDelta::days(1))
+                    .ok_or_else(|| DateTimeError::OutOfRange(local.to_string()))?;
+                let offset = tz.offset_from_utc_datetime(&before).fix().local_minus_utc();
+                Ok(local.and_utc().timestamp() - i64::from(offset))
+            }
+        }
+    }
+}
+
+/// タイムゾーンの指定を、指定した時点でのUTCオフセット（秒）に変換します
+///
+/// IANAタイムゾーン名（`Asia/Tokyo`）と、`UTC`・`+09:00`などの固定オフセットを受け付けます。
+pub(crate) fn resolve_offset(timezone: &str, unix_seconds: i64) -> Result<i32, DateTimeError> {
+    Zone::parse(timezone)?.offset_at(unix_seconds)
+}
+
+/// あるタイムゾーンでのローカル日時を、別のタイムゾーンで表した時点に変換します
+///
+/// `timestamp`が表すローカル日時（`unix_seconds`に`utc_offset_seconds`を加えた壁時計の時刻）を
+/// `from_tz`での時刻として解釈し、同じ瞬間を`to_tz`のオフセットで返します。
+/// `from_tz`で夏時間の開始により存在しない時刻は切り替え量だけ後ろにずらし、
+/// 夏時間の終了により2回現れる時刻は早い方を選びます（FoundationのCalendarと同じ）。
+///
+/// # Arguments
+/// * `timestamp` - 変換するローカル日時
+/// * `from_tz` - ローカル日時のタイムゾーン（`America/New_York`、`UTC`、`+09:00`など）
+/// * `to_tz` - 変換先のタイムゾーン
+///
+/// # Returns
+/// `unix_seconds`が同じ瞬間を、`utc_offset_seconds`が`to_tz`でのオフセットを表す時点
+///
+/// # Errors
+/// * `DateTimeError::InvalidTimeZone` - タイムゾーン名が正しくない場合
+/// * `DateTimeError::OutOfRange` - 時点が扱える範囲外の場合
+///
+/// # Example
+/// ```
+/// // 東京の9:00はニューヨークの前日19:00（冬時間）
+/// let tokyo_morning = parse_datetime("2024-01-15T09:00:00Z".to_string(), None)?;
+/// let converted =
+///     convert_timezone(tokyo_morning, "Asia/Tokyo".to_string(), "America/New_York".to_string())?;
+/// assert_eq!(converted.utc_offset_seconds, -5 * 3600);
+/// ```
+#[uniffi::export]
+pub fn convert_timezone(
+    timestamp: Timestamp,
+    from_tz: String,
+    to_tz: String,
+) -> Result<Timestamp, DateTimeError> {
+    let local = timestamp.local_datetime()?;
+    let unix_seconds = Zone::parse(&from_tz)?.to_unix(&local)?;
+    Ok(Timestamp {
+        unix_seconds,
+        nanoseconds: timestamp.nanoseconds,
+        utc_offset_seconds: resolve_offset(&to_tz, unix_seconds)?,
+    })
+}
+
+/// 指定した時点でのタイムゾーンのUTCオフセット（秒）を返します
+///
+/// 夏時間の期間中は夏時間のオフセットを返します。
+///
+/// # Arguments
+/// * `tz` - タイムゾーン（`Europe/London`、`UTC`、`+09:00`など）
+/// * `timestamp` - オフセットを求める時点（`utc_offset_seconds`は使用しません）
+///
+/// # Errors
+/// * `DateTimeError::InvalidTimeZone` - タイムゾーン名が正しくない場合
+/// * `DateTimeError::OutOfRange` - 時点が扱える範囲外の場合
+#[uniffi::export]
+pub fn timezone_offset_at(tz: String, timestamp: Timestamp) -> Result<i32, DateTimeError> {
+    resolve_offset(&tz, timestamp.unix_seconds)
+}
+
+/// 組み込みのtzdataに含まれるIANAタイムゾーン名を辞書順で返します
+///
+/// 互換用の別名（`US/Eastern`など）も含みます。
+#[uniffi::export]
+pub fn list_timezones() -> Vec<String> {
+    let mut names: Vec<String> = TZ_VARIANTS.iter().map(|tz| tz.name().to_string()).collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datetime::parse_datetime;
+
+    fn wall_clock(text: &str) -> Timestamp {
+        parse_datetime(format!("{}Z", text), None).unwrap()
+    }
+
+    #[test]
+    fn test_convert_timezone() {
+        let converted = convert_timezone(
+            wall_clock("2024-01-15T09:00:00"),
+            "Asia/Tokyo".to_string(),
+            "America/New_York".to_string(),
+        )
+        .unwrap();
+        assert_eq!(converted.unix_seconds, 1705276800);
+        assert_eq!(converted.utc_offset_seconds, -5 * 3600);
+        let local = converted.local_datetime().unwrap();
+        assert_eq!(local.to_string(), "2024-01-14 19:00:00");
+
+        // 固定オフセットとの相互変換
+        let converted = convert_timezone(
+            wall_clock("2024-07-01T12:00:00"),
+            "+09:00".to_string(),
+            "europe/london".to_string(),
+        )
+        .unwrap();
+        assert_eq!(converted.utc_offset_seconds, 3600);
+        assert_eq!(converted.local_datetime().unwrap().to_string(), "2024-07-01 04:00:00");
+    }
+
+    #[test]
+    fn test_convert_timezone_dst_transitions() {
+        let new_york = || "America/New_York".to_string();
+        // 2024-03-10 02:30は存在しないため、03:30 EDTになる
+        let gap = convert_timezone(wall_clock("2024-03-10T02:30:00"), new_york(), new_york());
+        let gap = gap.unwrap();
+        assert_eq!(gap.local_datetime().unwrap().to_string(), "2024-03-10 03:30:00");
+        assert_eq!(gap.utc_offset_seconds, -4 * 3600);
+
+        // 2024-11-03 01:30は2回現れるため、早い方（EDT）を選ぶ
+        let overlap = convert_timezone(wall_clock("2024-11-03T01:30:00"), new_york(), "UTC".into());
+        assert_eq!(overlap.unwrap().unix_seconds, 1730611800);
+    }
+
+    #[test]
+    fn test_timezone_offset_at() {
+        let winter = wall_clock("2024-01-15T12:00:00");
+        let summer = wall_clock("2024-07-15T12:00:00");
+        assert_eq!(timezone_offset_at("Europe/London".into(), winter).unwrap(), 0);
+        assert_eq!(timezone_offset_at("Europe/London".into(), summer).unwrap(), 3600);
+        assert_eq!(timezone_offset_at("Asia/Kolkata".into(), summer).unwrap(), 19800);
+        assert_eq!(timezone_offset_at("-03:00".into(), summer).unwrap(), -3 * 3600);
+        for timezone in ["Mars/Olympus_Mons", "UTC+1é1"] {
+            match timezone_offset_at(timezone.into(), summer) {
+                Err(DateTimeError::InvalidTimeZone(_)) => (),
+                other => panic!("Expected InvalidTimeZone error, got {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_list_timezones() {
+        let names = list_timezones();
+        assert!(names.len() > 400);
+        assert!(names.contains(&"Asia/Tokyo".to_string()));
+        assert!(names.contains(&"UTC".to_string()));
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
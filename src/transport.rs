@@ -0,0 +1,310 @@
+//! HTTPトランスポートモジュール
+//!
+//! このモジュールは、クレートが行うHTTP通信をホスト側（SwiftのURLSessionなど）に
+//! 委譲するための`HttpTransport`コールバックインターフェースをエクスポートします。
+//! プロトコルの処理（リクエストの組み立てやレスポンスの検証）はRust側で行い、
+//! 実際の送受信だけをホストに任せます。
+//!
+//! トランスポートが登録されていない場合は、組み込みのHTTPクライアントを使用します。
+//! 現在、`fetch_greeting`・`PkceSession::exchange_code`・`HttpCache::fetch_cached`・
+//! `upload_file`がこのモジュールを経由して通信します。
+//!
+//! `send`はリクエストとレスポンスのボディ全体をメモリ上で受け渡すため、次の通信は
+//! 引き続き組み込みのクライアントで行います。
+//!
+//! - `Downloader`: ボディを受信しながらファイルに書き込み、中断位置からの再開や帯域制限を行うため
+//! - `EventSource`: 接続を保ったままイベントを逐次受信するため
+//! - `WebSocketClient`: HTTPからプロトコルを切り替えて双方向に通信するため
+//! - `HttpCassette`の記録: カセット自身がトランスポートとして登録されている場合があるため
+
+use std::collections::HashMap;
+use std::io::Read;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::sigv4::HttpRequestParts;
+
+/// 組み込みクライアントの接続・読み込みのタイムアウト
+const NETWORK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// トランスポートで発生する可能性のあるエラー
+///
+/// ホスト側のトランスポートからも返せるように、フィールド付きのエラーとして公開されます。
+#[derive(Debug, Error, uniffi::Error)]
+pub enum TransportError {
+    /// URLが不正、またはサポートしないスキームの場合
+    #[error("Invalid URL: {url}")]
+    InvalidUrl { url: String },
+    /// 通信に失敗した場合（タイムアウト・接続失敗・TLSエラーなど）
+    #[error("Network error: {message}")]
+    Network { message: String },
+}
+
+impl From<uniffi::UnexpectedUniFFICallbackError> for TransportError {
+    fn from(error: uniffi::UnexpectedUniFFICallbackError) -> Self {
+        TransportError::Network { message: error.reason }
+    }
+}
+
+/// HTTPレスポンス
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct HttpResponseParts {
+    /// ステータスコード
+    pub status: u16,
+    /// ヘッダー（名前は小文字で返してください。同じ名前のヘッダーはカンマで連結）
+    pub headers: HashMap<String, String>,
+    /// ボディ
+    pub body: Vec<u8>,
+}
+
+impl HttpResponseParts {
+    /// ヘッダーの値を返します（名前の大文字・小文字は区別しません）
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// ステータスが2xxかを返します
+    pub(crate) fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// ホスト側でHTTP通信を行うトランスポート
+///
+/// Swift側でURLSessionを使って実装し、`set_http_transport`で登録します。
+/// `send`はRust側のバックグラウンドスレッドから呼び出されるため、
+/// 完了までブロックして構いません。
+#[uniffi::export(with_foreign)]
+pub trait HttpTransport: Send + Sync {
+    /// リクエストを送信し、レスポンスを返します
+    ///
+    /// リダイレクトは追跡してください。200以外のステータス（404など）は
+    /// エラーではなくレスポンスとして返してください。
+    ///
+    /// # Arguments
+    /// * `request` - 送信するリクエスト（`payload_hash`は使用しません）
+    ///
+    /// # Errors
+    /// * `TransportError::InvalidUrl` - URLを扱えない場合
+    /// * `TransportError::Network` - 通信に失敗した場合
+    fn send(&self, request: HttpRequestParts) -> Result<HttpResponseParts, TransportError>;
+}
+
+/// 登録されているトランスポート
+static HTTP_TRANSPORT: RwLock<Option<Arc<dyn HttpTransport>>> = RwLock::new(None);
+
+/// HTTPトランスポートを登録します
+///
+/// 登録後は、クレートのHTTP通信がすべてこのトランスポートを経由します。
+///
+/// # Arguments
+/// * `transport` - 登録するトランスポート（既存の登録は置き換えられます）
+#[uniffi::export]
+pub fn set_http_transport(transport: Arc<dyn HttpTransport>) {
+    if let Ok(mut current) = HTTP_TRANSPORT.write() {
+        *current = Some(transport);
+    }
+}
+
+/// 登録されているHTTPトランスポートを解除し、組み込みのクライアントに戻します
+#[uniffi::export]
+pub fn clear_http_transport() {
+    if let Ok(mut current) = HTTP_TRANSPORT.write() {
+        *current = None;
+    }
+}
+
+/// 組み込みクライアントのエージェント
+fn agent() -> &'static ureq::Agent {
+    static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+    AGENT.get_or_init(|| {
+        ureq::AgentBuilder::new()
+            .timeout_connect(NETWORK_TIMEOUT)
+            .timeout_read(NETWORK_TIMEOUT)
+            .timeout_write(NETWORK_TIMEOUT)
+            .build()
+    })
+}
+
+/// 組み込みのHTTPクライアントでリクエストを送信します
+///
+/// ボディは`max_body_len + 1`バイトまで読み込みます（呼び出し側で超過を判定できるように）。
+pub(crate) fn send_builtin(
+    request: &HttpRequestParts,
+    max_body_len: u64,
+) -> Result<HttpResponseParts, TransportError> {
+    let mut call = agent().request(&request.method, &request.url);
+    for (name, value) in &request.headers {
+        call = call.set(name, value);
+    }
+    let result = if request.body.is_empty() {
+        call.call()
+    } else {
+        call.send_bytes(&request.body)
+    };
+    let response = match result {
+        Ok(response) => response,
+        Err(ureq::Error::Status(_, response)) => response,
+        Err(ureq::Error::Transport(e)) => {
+            return Err(match e.kind() {
+                ureq::ErrorKind::InvalidUrl | ureq::ErrorKind::UnknownScheme => {
+                    TransportError::InvalidUrl { url: request.url.clone() }
+                }
+                _ => TransportError::Network { message: e.to_string() },
+            })
+        }
+    };
+    let status = response.status();
+    let mut headers = HashMap::new();
+    for name in response.headers_names() {
+        let value = response.all(&name).join(", ");
+        headers.entry(name).or_insert(value);
+    }
+    let mut body = Vec::new();
+    response
+        .into_reader()
+        .take(max_body_len.saturating_add(1))
+        .read_to_end(&mut body)
+        .map_err(|e| TransportError::Network { message: e.to_string() })?;
+    Ok(HttpResponseParts { status, headers, body })
+}
+
+/// リクエストを送信します
+///
+/// トランスポートが登録されていればそれを使い、なければ組み込みのクライアントを使います。
+/// 登録されたトランスポートは`max_body_len`を超えるボディを返す場合があるため、
+/// 呼び出し側でボディの長さを検証してください。
+///
+/// # Arguments
+/// * `request` - 送信するリクエスト
+/// * `max_body_len` - 組み込みクライアントが読み込むボディの上限の目安
+pub(crate) fn send_request(
+    request: &HttpRequestParts,
+    max_body_len: u64,
+) -> Result<HttpResponseParts, TransportError> {
+    let transport = HTTP_TRANSPORT.read().ok().and_then(|current| current.clone());
+    match transport {
+        Some(transport) => transport.send(request.clone()),
+        None => send_builtin(request, max_body_len),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    /// 特定のホストへのリクエストを記録して応答し、それ以外は組み込みクライアントに渡す
+    /// トランスポート（他のテストの通信に影響しないように）
+    struct MockTransport {
+        requests: Mutex<Vec<HttpRequestParts>>,
+    }
+
+    impl HttpTransport for MockTransport {
+        fn send(&self, request: HttpRequestParts) -> Result<HttpResponseParts, TransportError> {
+            if !request.url.starts_with("https://transport.invalid/") {
+                return send_builtin(&request, u64::MAX);
+            }
+            self.requests.lock().unwrap().push(request.clone());
+            if request.url.ends_with("/fail") {
+                return Err(TransportError::Network { message: "offline".to_string() });
+            }
+            Ok(HttpResponseParts {
+                status: 200,
+                headers: HashMap::from([("Content-Type".to_string(), "text/plain".to_string())]),
+                body: format!("{} {}", request.method, request.url).into_bytes(),
+            })
+        }
+    }
+
+    fn request(method: &str, url: &str) -> HttpRequestParts {
+        HttpRequestParts {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: HashMap::from([("Accept".to_string(), "text/plain".to_string())]),
+            body: Vec::new(),
+            payload_hash: None,
+        }
+    }
+
+    #[test]
+    fn test_registered_transport_is_used() {
+        let mock = Arc::new(MockTransport { requests: Mutex::new(Vec::new()) });
+        set_http_transport(mock.clone());
+
+        let response =
+            send_request(&request("GET", "https://transport.invalid/greeting"), 1024).unwrap();
+        assert_eq!(response.body, b"GET https://transport.invalid/greeting");
+        assert_eq!(response.header("content-type"), Some("text/plain"));
+        assert!(response.is_success());
+        match send_request(&request("GET", "https://transport.invalid/fail"), 1024) {
+            Err(TransportError::Network { message }) => assert_eq!(message, "offline"),
+            other => panic!("Expected Network error, got {:?}", other),
+        }
+        // 挨拶の取得もトランスポートを経由する
+        let greeting = pollster::block_on(crate::greeting::fetch_greeting(
+            "https://transport.invalid/banner".to_string(),
+        ))
+        .unwrap();
+        assert_eq!(greeting, "GET https://transport.invalid/banner");
+        // アップロードもトランスポートを経由する
+        let path = std::env::temp_dir()
+            .join(format!("mobile_transport_upload_{}.bin", std::process::id()));
+        std::fs::write(&path, b"data").unwrap();
+        let uploaded = pollster::block_on(crate::upload::upload_file(
+            "https://transport.invalid/upload".to_string(),
+            path.to_string_lossy().into_owned(),
+            HashMap::new(),
+            1024,
+            None,
+            None,
+        ))
+        .unwrap();
+        assert_eq!(uploaded.body, "PUT https://transport.invalid/upload");
+        let _ = std::fs::remove_file(&path);
+
+        let requests = mock.requests.lock().unwrap();
+        assert_eq!(requests.len(), 4);
+        assert_eq!(requests[0].headers.get("Accept").map(String::as_str), Some("text/plain"));
+        let content_range = requests[3].headers.get("Content-Range").map(String::as_str);
+        assert_eq!(content_range, Some("bytes 0-3/4"));
+        assert_eq!(requests[3].body, b"data");
+        drop(requests);
+        clear_http_transport();
+    }
+
+    #[test]
+    fn test_builtin_transport() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            if let Ok((mut stream, _)) = listener.accept() {
+                let mut buf = [0u8; 1024];
+                let _ = stream.read(&mut buf);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 404 Not Found\r\nX-Trace: a\r\nX-Trace: b\r\n\
+                      Content-Length: 9\r\nConnection: close\r\n\r\nnot found",
+                );
+            }
+        });
+
+        let url = format!("http://{}/missing", addr);
+        let response = send_builtin(&request("GET", &url), 3).unwrap();
+        assert_eq!(response.status, 404);
+        assert!(!response.is_success());
+        assert_eq!(response.header("X-Trace"), Some("a, b"));
+        assert_eq!(response.body, b"not ");
+
+        match send_builtin(&request("GET", "ftp://example.com/file"), 1024) {
+            Err(TransportError::InvalidUrl { .. }) => (),
+            other => panic!("Expected InvalidUrl error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+//! ULIDモジュール
+//!
+//! このモジュールは、ULID（Universally Unique Lexicographically Sortable Identifier）を
+//! 生成・解析する関数をエクスポートします。同期プロトコルでレコードの順序付けに使用します。
+//!
+//! ULIDは48ビットのUNIX時刻（ミリ秒）と80ビットの乱数からなる128ビットの値を、
+//! Crockford Base32で26文字に表したもので、文字列の辞書順が生成順と一致します。
+//! 同じミリ秒内に生成した場合は乱数部分を1ずつ増やすため、
+//! プロセス内で生成したULIDは常に厳密に増加します。
+
+use std::sync::{Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+
+/// Crockford Base32のアルファベット
+const ENCODING: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// ULIDの文字数
+const ULID_LEN: usize = 26;
+
+/// 乱数部分のビット数
+const RANDOM_BITS: u32 = 80;
+
+/// 時刻部分の最大値（48ビット）
+const MAX_TIMESTAMP: u64 = (1 << 48) - 1;
+
+/// 直前に生成したULIDの(時刻, 乱数部分)
+static LAST_ULID: Mutex<(u64, u128)> = Mutex::new((0, 0));
+
+/// ULIDの処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum UlidError {
+    /// 26文字のCrockford Base32でない、または128ビットを超える値の場合
+    #[error("Invalid ULID: {0}")]
+    InvalidUlid(String),
+}
+
+/// 現在のUNIX時刻（ミリ秒）を返します
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 80ビットの乱数を生成します
+fn random_bits() -> u128 {
+    let mut bytes = [0u8; 16];
+    OsRng.fill_bytes(&mut bytes[6..]);
+    u128::from_be_bytes(bytes)
+}
+
+/// 時刻と乱数部分をULID文字列にエンコードします
+fn encode_ulid(timestamp: u64, random: u128) -> String {
+    let value = (u128::from(timestamp) << RANDOM_BITS) | random;
+    (0..ULID_LEN)
+        .map(|i| {
+            let shift = 5 * (ULID_LEN - 1 - i);
+            ENCODING[((value >> shift) & 0x1f) as usize] as char
+        })
+        .collect()
+}
+
+/// ULID文字列を128ビットの値にデコードします
+///
+/// 大文字・小文字を区別せず、読み間違えやすい`O`は`0`、`I`・`L`は`1`として扱います。
+fn decode_ulid(ulid: &str) -> Result<u128, UlidError> {
+    let invalid = || UlidError::InvalidUlid(ulid.to_string());
+    if ulid.len() != ULID_LEN {
+        return Err(invalid());
+    }
+    let mut value: u128 = 0;
+    for (i, c) in ulid.chars().enumerate() {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let digit = ENCODING.iter().position(|&b| b as char == c).ok_or_else(invalid)?;
+        // 26文字×5ビット=130ビットのため、先頭の文字は0〜7に限られる
+        if i == 0 && digit > 7 {
+            return Err(invalid());
+        }
+        value = (value << 5) | digit as u128;
+    }
+    Ok(value)
+}
+
+/// 新しいULIDを生成します
+///
+/// 同じミリ秒内での生成や、システム時計が過去に戻った場合でも、
+/// プロセス内で直前に生成したULIDより必ず大きい値を返します。
+///
+/// # Returns
+/// 26文字の大文字のULID
+///
+/// # Example
+/// ```
+/// let id = generate_ulid();
+/// // 例: "01JA4ZV3K8Q6X2N7M5R9T1W0YB"
+/// ```
+#[uniffi::export]
+pub fn generate_ulid() -> String {
+    let mut last = LAST_ULID.lock().unwrap_or_else(PoisonError::into_inner);
+    let now = now_millis().min(MAX_TIMESTAMP);
+    let (timestamp, random) = if now > last.0 {
+        (now, random_bits())
+    } else {
+        // 同じミリ秒内（または時計の巻き戻り）では乱数部分を1増やし、
+        // 溢れた場合は時刻部分を1ミリ秒進める
+        let next = last.1 + 1;
+        if next >> RANDOM_BITS == 0 {
+            (last.0, next)
+        } else {
+            (last.0 + 1, 0)
+        }
+    };
+    *last = (timestamp, random);
+    encode_ulid(timestamp, random)
+}
+
+/// ULIDに含まれる生成時刻を返します
+///
+/// # Arguments
+/// * `ulid` - 26文字のULID（大文字・小文字は区別しません）
+///
+/// # Returns
+/// UNIX時刻（ミリ秒）
+///
+/// # Errors
+/// * `UlidError::InvalidUlid` - ULIDとして不正な場合
+///
+/// # Example
+/// ```
+/// assert_eq!(ulid_timestamp("01ARZ3NDEKTSV4RRFFQ69G5FAV".to_string())?, 1469922850259);
+/// ```
+#[uniffi::export]
+pub fn ulid_timestamp(ulid: String) -> Result<u64, UlidError> {
+    Ok((decode_ulid(&ulid)? >> RANDOM_BITS) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ulid_timestamp() {
+        let timestamp = |ulid: &str| ulid_timestamp(ulid.to_string()).unwrap();
+        assert_eq!(timestamp("01ARZ3NDEKTSV4RRFFQ69G5FAV"), 1469922850259);
+        assert_eq!(timestamp("01arz3ndektsv4rrffq69g5fav"), 1469922850259);
+        assert_eq!(timestamp("00000000000000000000000000"), 0);
+        assert_eq!(timestamp("7ZZZZZZZZZZZZZZZZZZZZZZZZZ"), MAX_TIMESTAMP);
+    }
+
+    #[test]
+    fn test_generate_ulid_format_and_timestamp() {
+        let before = now_millis();
+        let ulid = generate_ulid();
+        let after = now_millis();
+        assert_eq!(ulid.len(), ULID_LEN);
+        assert!(ulid.bytes().all(|b| ENCODING.contains(&b)));
+        let timestamp = ulid_timestamp(ulid).unwrap();
+        assert!(before <= timestamp && timestamp <= after);
+    }
+
+    #[test]
+    fn test_generate_ulid_is_monotonic() {
+        let ulids: Vec<String> = (0..1000).map(|_| generate_ulid()).collect();
+        for pair in ulids.windows(2) {
+            assert!(pair[0] < pair[1], "{} should sort before {}", pair[0], pair[1]);
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_ulid() {
+        assert_eq!(encode_ulid(1, 0), format!("0000000001{}", "0".repeat(16)));
+        assert_eq!(decode_ulid(&encode_ulid(42, 12345)).unwrap(), (42u128 << 80) | 12345);
+    }
+
+    #[test]
+    fn test_ulid_timestamp_errors() {
+        let invalid = [
+            "",
+            "01ARZ3NDEKTSV4RRFFQ69G5FA",
+            "01ARZ3NDEKTSV4RRFFQ69G5FAU",
+            "8ZZZZZZZZZZZZZZZZZZZZZZZZZ",
+        ];
+        for ulid in invalid {
+            match ulid_timestamp(ulid.to_string()) {
+                Err(UlidError::InvalidUlid(_)) => (),
+                other => panic!("Expected InvalidUlid error for {:?}, got {:?}", ulid, other),
+            }
+        }
+    }
+}
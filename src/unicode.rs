@@ -0,0 +1,142 @@
+//! Unicode正規化・書記素クラスタモジュール
+//!
+//! このモジュールは、Unicode正規化（NFC・NFD・NFKC・NFKD）を行う`normalize`と、
+//! 書記素クラスタ（ユーザーが1文字と認識する単位）を数える`grapheme_count`、
+//! 書記素クラスタの境界で切り詰める`truncate_graphemes`をエクスポートします。
+//! SwiftのStringの`count`と同じ拡張書記素クラスタ（UAX #29）で数えるため、
+//! 絵文字や結合文字を含む文字列でもアプリとサーバーの文字数が一致します。
+
+use unicode_normalization::UnicodeNormalization;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Unicode正規化の形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum NormalizationForm {
+    /// 正規分解の後に正規合成（`e` + `◌́` → `é`）
+    Nfc,
+    /// 正規分解（`é` → `e` + `◌́`）
+    Nfd,
+    /// 互換分解の後に正規合成（`ｶﾞ` → `ガ`、`①` → `1`）
+    Nfkc,
+    /// 互換分解
+    Nfkd,
+}
+
+/// 文字列をUnicode正規化します
+///
+/// # Arguments
+/// * `text` - 正規化する文字列
+/// * `form` - 正規化の形式
+///
+/// # Example
+/// ```
+/// assert_eq!(normalize("ﾃｽﾄ①".to_string(), NormalizationForm::Nfkc), "テスト1");
+/// ```
+#[uniffi::export]
+pub fn normalize(text: String, form: NormalizationForm) -> String {
+    match form {
+        NormalizationForm::Nfc => text.nfc().collect(),
+        NormalizationForm::Nfd => text.nfd().collect(),
+        NormalizationForm::Nfkc => text.nfkc().collect(),
+        NormalizationForm::Nfkd => text.nfkd().collect(),
+    }
+}
+
+/// 文字列の書記素クラスタの数を返します
+///
+/// SwiftのStringの`count`と同じ値になります（`"👨‍👩‍👧"`は1、`"🇯🇵"`は1）。
+///
+/// # Arguments
+/// * `text` - 数える文字列
+#[uniffi::export]
+pub fn grapheme_count(text: String) -> u64 {
+    text.graphemes(true).count() as u64
+}
+
+/// 文字列を書記素クラスタの数で切り詰めます
+///
+/// 文字列が`max_graphemes`以下の場合はそのまま返します。超える場合は省略記号を含めて
+/// `max_graphemes`以下になるように切り詰め、省略記号の前の空白を取り除きます。
+/// 絵文字や結合文字の途中で切れることはありません。
+///
+/// # Arguments
+/// * `text` - 切り詰める文字列
+/// * `max_graphemes` - 結果の最大の書記素クラスタ数（省略記号を含みます）
+/// * `ellipsis` - 切り詰めた場合に末尾に付ける文字列（空の場合は付けません）。
+///   `max_graphemes`より長い場合は付けません
+///
+/// # Example
+/// ```
+/// let text = "家族👨‍👩‍👧でお出かけ".to_string();
+/// assert_eq!(truncate_graphemes(text, 4, "…".to_string()), "家族👨‍👩‍👧…");
+/// ```
+#[uniffi::export(default(ellipsis = "…"))]
+pub fn truncate_graphemes(text: String, max_graphemes: u32, ellipsis: String) -> String {
+    let max_graphemes = max_graphemes as usize;
+    let graphemes: Vec<&str> = text.graphemes(true).collect();
+    if graphemes.len() <= max_graphemes {
+        return text;
+    }
+    let ellipsis_length = ellipsis.graphemes(true).count();
+    if ellipsis_length == 0 || ellipsis_length > max_graphemes {
+        return graphemes[..max_graphemes].concat();
+    }
+    let mut kept = &graphemes[..max_graphemes - ellipsis_length];
+    while let Some((last, rest)) = kept.split_last() {
+        if !last.trim().is_empty() {
+            break;
+        }
+        kept = rest;
+    }
+    let mut truncated = kept.concat();
+    truncated.push_str(&ellipsis);
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        let composed = "Caf\u{e9}";
+        let decomposed = "Cafe\u{301}";
+        assert_eq!(normalize(decomposed.to_string(), NormalizationForm::Nfc), composed);
+        assert_eq!(normalize(composed.to_string(), NormalizationForm::Nfd), decomposed);
+        assert_eq!(normalize("ﾃｽﾄ①ＡＢＣ".to_string(), NormalizationForm::Nfkc), "テスト1ABC");
+        assert_eq!(normalize("ﾊﾟ".to_string(), NormalizationForm::Nfkd), "\u{30cf}\u{309a}");
+        // 互換文字は正規化（NFC）では変換されない
+        assert_eq!(normalize("①".to_string(), NormalizationForm::Nfc), "①");
+    }
+
+    #[test]
+    fn test_grapheme_count() {
+        let count = |text: &str| grapheme_count(text.to_string());
+        assert_eq!(count(""), 0);
+        assert_eq!(count("hello"), 5);
+        assert_eq!(count("Cafe\u{301}"), 4);
+        assert_eq!(count("👨‍👩‍👧"), 1);
+        assert_eq!(count("🇯🇵🇺🇸"), 2);
+        assert_eq!(count("👍🏽!"), 2);
+        assert_eq!(count("\r\n"), 1);
+        assert_eq!(count("か\u{3099}"), 1);
+    }
+
+    #[test]
+    fn test_truncate_graphemes() {
+        let truncate = |text: &str, max_graphemes: u32, ellipsis: &str| {
+            truncate_graphemes(text.to_string(), max_graphemes, ellipsis.to_string())
+        };
+        assert_eq!(truncate("hello", 5, "…"), "hello");
+        assert_eq!(truncate("hello world", 8, "…"), "hello w…");
+        // 省略記号の前の空白は取り除く
+        assert_eq!(truncate("hello world", 7, "…"), "hello…");
+        assert_eq!(truncate("hello world", 8, "..."), "hello...");
+        assert_eq!(truncate("👨‍👩‍👧👨‍👩‍👧👨‍👩‍👧", 2, ""), "👨‍👩‍👧👨‍👩‍👧");
+        assert_eq!(truncate("🇯🇵🇺🇸🇫🇷", 2, "…"), "🇯🇵…");
+        assert_eq!(truncate("Cafe\u{301} au lait", 4, ""), "Cafe\u{301}");
+        // 省略記号が最大数より長い場合は付けない
+        assert_eq!(truncate("hello", 2, "..."), "he");
+        assert_eq!(truncate("hello", 0, "…"), "");
+    }
+}
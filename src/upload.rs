@@ -0,0 +1,397 @@
+//! 分割アップロードモジュール
+//!
+//! このモジュールは、大きなファイルを一定サイズのチャンクに分けてHTTP(S)で送信する
+//! `upload_file`と、進捗を受け取る`UploadObserver`をエクスポートします。
+//! 動画などの大きなファイルのアップロードを、プラットフォーム固有のコードなしで行うために使用します。
+//!
+//! # プロトコル
+//! 各チャンクを`PUT`で順に送信し、`Content-Range: bytes <開始>-<終了>/<全体>`で位置を示します。
+//! 途中のチャンクには2xxまたは`308 Resume Incomplete`、最後のチャンクには2xxの応答を期待します。
+//! 通信エラーと`RetryPolicy`で再試行対象のステータスの場合は、ポリシーの待ち時間を空けて
+//! そのチャンクだけを再送します。
+//!
+//! 各チャンクは`set_http_transport`で登録されたトランスポート（未登録の場合は組み込みの
+//! クライアント）で送信します。
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::retry::RetryPolicy;
+use crate::sigv4::HttpRequestParts;
+use crate::transport::{send_request, HttpResponseParts, TransportError};
+
+/// 読み込むレスポンスボディの最大バイト数
+const MAX_RESPONSE_BYTES: u64 = 1024 * 1024;
+
+/// アップロードで発生する可能性のあるエラー
+#[derive(Debug, Clone, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum UploadError {
+    /// URLが不正、またはHTTP(S)以外のスキームの場合
+    #[error("Invalid upload URL: {0}")]
+    InvalidUrl(String),
+    /// チャンクサイズが0の場合
+    #[error("Chunk size must be greater than zero")]
+    InvalidChunkSize,
+    /// ファイルの読み込みに失敗した場合
+    #[error("Failed to read upload file: {0}")]
+    IoError(String),
+    /// 再送しても通信に失敗した場合
+    #[error("Network error: {0}")]
+    Network(String),
+    /// サーバーが成功以外のステータスを返した場合
+    #[error("Unexpected HTTP status: {0}")]
+    HttpStatus(u16),
+}
+
+impl From<io::Error> for UploadError {
+    fn from(error: io::Error) -> Self {
+        UploadError::IoError(error.to_string())
+    }
+}
+
+/// アップロードの進捗を受け取るオブザーバー
+///
+/// メソッドはアップロードを実行しているRust側のスレッドから呼ばれます。
+#[uniffi::export(with_foreign)]
+pub trait UploadObserver: Send + Sync {
+    /// チャンクの送信が完了したときに呼ばれます
+    ///
+    /// # Arguments
+    /// * `uploaded_bytes` - 送信が完了したバイト数
+    /// * `total_bytes` - ファイル全体のバイト数
+    fn on_progress(&self, uploaded_bytes: u64, total_bytes: u64);
+
+    /// チャンクを再送する前に呼ばれます
+    ///
+    /// # Arguments
+    /// * `chunk_index` - 再送するチャンクの番号（0始まり）
+    /// * `attempt` - 再送の回数（1始まり）
+    /// * `error` - 直前の送信で発生したエラー
+    fn on_retry(&self, chunk_index: u64, attempt: u32, error: UploadError);
+}
+
+/// アップロードの結果
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct UploadResult {
+    /// 最後のチャンクに対するHTTPステータス
+    pub status: u16,
+    /// 最後のチャンクに対するレスポンスボディ（UTF-8として解釈、最大1 MiB）
+    pub body: String,
+    /// 送信したバイト数
+    pub uploaded_bytes: u64,
+    /// 全チャンクの再送回数の合計
+    pub retries: u32,
+}
+
+/// 1つのチャンクを送信するリクエストを組み立てます
+fn chunk_request(
+    url: &str,
+    headers: &HashMap<String, String>,
+    content_range: String,
+    chunk: Vec<u8>,
+) -> HttpRequestParts {
+    let mut headers: HashMap<String, String> = headers
+        .iter()
+        .filter(|(name, _)| {
+            !name.eq_ignore_ascii_case("content-type")
+                && !name.eq_ignore_ascii_case("content-range")
+        })
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    headers.insert("Content-Type".to_string(), "application/octet-stream".to_string());
+    headers.insert("Content-Range".to_string(), content_range);
+    HttpRequestParts {
+        method: "PUT".to_string(),
+        url: url.to_string(),
+        headers,
+        body: chunk,
+        payload_hash: None,
+    }
+}
+
+/// 1つのチャンクを送信し、レスポンスを返します
+fn send_chunk(request: &HttpRequestParts) -> Result<HttpResponseParts, UploadError> {
+    send_request(request, MAX_RESPONSE_BYTES).map_err(|e| match e {
+        TransportError::InvalidUrl { url } => UploadError::InvalidUrl(url),
+        TransportError::Network { message } => UploadError::Network(message),
+    })
+}
+
+/// ファイルを同期的にアップロードします
+fn upload_file_blocking(
+    url: &str,
+    path: &str,
+    headers: &HashMap<String, String>,
+    chunk_size: u64,
+    retry_policy: &RetryPolicy,
+    observer: Option<&dyn UploadObserver>,
+) -> Result<UploadResult, UploadError> {
+    let lower = url.to_ascii_lowercase();
+    if !lower.starts_with("https://") && !lower.starts_with("http://") {
+        return Err(UploadError::InvalidUrl(url.to_string()));
+    }
+    if chunk_size == 0 {
+        return Err(UploadError::InvalidChunkSize);
+    }
+    let mut file = File::open(path)?;
+    let total = file.metadata()?.len();
+
+    let mut uploaded = 0u64;
+    let mut retries = 0u32;
+    let mut chunk_index = 0u64;
+    loop {
+        let len = chunk_size.min(total - uploaded);
+        let mut buf = Vec::new();
+        (&mut file).take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(UploadError::IoError("file was truncated during upload".to_string()));
+        }
+        let content_range = if total == 0 {
+            "bytes */0".to_string()
+        } else {
+            format!("bytes {}-{}/{}", uploaded, uploaded + len - 1, total)
+        };
+        let is_last = uploaded + len == total;
+        let request = chunk_request(url, headers, content_range, buf);
+
+        let mut attempt = 0u32;
+        let response = loop {
+            // 308はリダイレクトではなく途中のチャンクの受理として扱う
+            let result = send_chunk(&request).and_then(|response| {
+                let status = response.status;
+                if response.is_success() || (status == 308 && !is_last) {
+                    Ok(response)
+                } else {
+                    Err(UploadError::HttpStatus(status))
+                }
+            });
+            let error = match result {
+                Ok(sent) => break sent,
+                Err(e) => e,
+            };
+            let retryable = match &error {
+                UploadError::Network(_) => true,
+                UploadError::HttpStatus(status) => retry_policy.retries_status(*status),
+                _ => false,
+            };
+            attempt += 1;
+            let delay = match retry_policy.delay(attempt) {
+                Some(delay) if retryable => Duration::from_millis(delay),
+                _ => return Err(error),
+            };
+            retries += 1;
+            if let Some(observer) = observer {
+                observer.on_retry(chunk_index, attempt, error);
+            }
+            thread::sleep(delay);
+        };
+
+        uploaded += len;
+        chunk_index += 1;
+        if let Some(observer) = observer {
+            observer.on_progress(uploaded, total);
+        }
+        if is_last {
+            // 登録されたトランスポートは上限を超えるボディを返す場合がある
+            let body = &response.body[..response.body.len().min(MAX_RESPONSE_BYTES as usize)];
+            return Ok(UploadResult {
+                status: response.status,
+                body: String::from_utf8_lossy(body).into_owned(),
+                uploaded_bytes: uploaded,
+                retries,
+            });
+        }
+    }
+}
+
+/// ファイルをチャンクに分けてアップロードします
+///
+/// 送信はRust側のスレッドプールで実行されるため、呼び出し元のスレッドをブロックしません。
+/// ファイルは1チャンクずつ読み込むため、大きなファイルでもメモリ使用量はチャンクサイズ程度です。
+///
+/// # Arguments
+/// * `url` - アップロード先のHTTP(S)のURL
+/// * `path` - アップロードするファイルのパス
+/// * `headers` - 各チャンクのリクエストに追加するヘッダー（認証など）
+/// * `chunk_size` - 1チャンクのバイト数
+/// * `observer` - 進捗と再送を受け取るオブザーバー（既定値は`None`）
+/// * `retry_policy` - 1チャンクあたりの再送のポリシー（`None`の場合は`default_retry_policy`）
+///
+/// # Errors
+/// * `UploadError::InvalidUrl` - URLが不正な場合
+/// * `UploadError::InvalidChunkSize` - チャンクサイズが0の場合
+/// * `UploadError::IoError` - ファイルの読み込みに失敗した場合
+/// * `UploadError::Network` - 再送しても通信に失敗した場合
+/// * `UploadError::HttpStatus` - サーバーがエラーステータスを返した場合
+///
+/// # Example
+/// ```
+/// let result = upload_file(url, path, headers, 8 * 1024 * 1024, Some(observer), None).await?;
+/// ```
+#[uniffi::export(default(observer = None, retry_policy = None))]
+pub async fn upload_file(
+    url: String,
+    path: String,
+    headers: HashMap<String, String>,
+    chunk_size: u64,
+    observer: Option<Arc<dyn UploadObserver>>,
+    retry_policy: Option<RetryPolicy>,
+) -> Result<UploadResult, UploadError> {
+    let retry_policy = retry_policy.unwrap_or_default();
+    blocking::unblock(move || {
+        upload_file_blocking(&url, &path, &headers, chunk_size, &retry_policy, observer.as_deref())
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use std::net::TcpListener;
+    use std::sync::Mutex;
+
+    /// 受信したリクエスト（Content-Range, 認証ヘッダー, ボディ）
+    type Received = Arc<Mutex<Vec<(String, String, Vec<u8>)>>>;
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        progress: Mutex<Vec<u64>>,
+        retries: Mutex<Vec<(u64, u32)>>,
+    }
+
+    impl UploadObserver for RecordingObserver {
+        fn on_progress(&self, uploaded_bytes: u64, _total_bytes: u64) {
+            self.progress.lock().unwrap().push(uploaded_bytes);
+        }
+
+        fn on_retry(&self, chunk_index: u64, attempt: u32, _error: UploadError) {
+            self.retries.lock().unwrap().push((chunk_index, attempt));
+        }
+    }
+
+    /// リクエストを受け付け、`statuses`の順にステータスを返すHTTPサーバーを起動します
+    fn serve(statuses: Vec<u16>) -> (String, Received) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&received);
+        thread::spawn(move || {
+            for (stream, status) in listener.incoming().zip(statuses) {
+                let Ok(mut stream) = stream else { continue };
+                let mut head = Vec::new();
+                let mut byte = [0u8; 1];
+                while !head.ends_with(b"\r\n\r\n") && stream.read(&mut byte).unwrap_or(0) == 1 {
+                    head.push(byte[0]);
+                }
+                let head = String::from_utf8_lossy(&head).to_lowercase();
+                let header = |name: &str| {
+                    head.lines()
+                        .find_map(|line| line.strip_prefix(name))
+                        .unwrap_or("")
+                        .trim()
+                        .to_string()
+                };
+                let length: usize = header("content-length:").parse().unwrap_or(0);
+                let mut body = vec![0u8; length];
+                let _ = stream.read_exact(&mut body);
+                if status < 300 || status == 308 {
+                    recorded.lock().unwrap().push((
+                        header("content-range:"),
+                        header("authorization:"),
+                        body,
+                    ));
+                }
+                let reply = if status == 200 { "uploaded" } else { "" };
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 {} Status\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    reply.len(),
+                    reply
+                );
+            }
+        });
+        (format!("http://{}/upload", addr), received)
+    }
+
+    fn temp_file(name: &str, data: &[u8]) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_upload_{}_{}.bin", name, std::process::id()));
+        std::fs::write(&path, data).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn headers() -> HashMap<String, String> {
+        HashMap::from([("Authorization".to_string(), "Bearer token".to_string())])
+    }
+
+    fn retry_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy { max_attempts, base_delay_ms: 10, jitter: 0.0, ..Default::default() }
+    }
+
+    #[test]
+    fn test_upload_file_in_chunks_with_retry() {
+        let data: Vec<u8> = (0..2_500u32).map(|i| (i % 251) as u8).collect();
+        let path = temp_file("chunks", &data);
+        let (url, received) = serve(vec![308, 503, 308, 200]);
+        let observer = Arc::new(RecordingObserver::default());
+        let result = pollster::block_on(upload_file(
+            url,
+            path.clone(),
+            headers(),
+            1_000,
+            Some(observer.clone()),
+            Some(retry_policy(3)),
+        ))
+        .unwrap();
+        assert_eq!(result.status, 200);
+        assert_eq!(result.body, "uploaded");
+        assert_eq!(result.uploaded_bytes, 2_500);
+        assert_eq!(result.retries, 1);
+
+        let received = received.lock().unwrap();
+        let ranges: Vec<&str> = received.iter().map(|(range, _, _)| range.as_str()).collect();
+        assert_eq!(ranges, ["bytes 0-999/2500", "bytes 1000-1999/2500", "bytes 2000-2499/2500"]);
+        assert!(received.iter().all(|(_, auth, _)| auth == "bearer token"));
+        let uploaded: Vec<u8> = received.iter().flat_map(|(_, _, body)| body.clone()).collect();
+        assert_eq!(uploaded, data);
+        assert_eq!(*observer.progress.lock().unwrap(), vec![1_000, 2_000, 2_500]);
+        assert_eq!(*observer.retries.lock().unwrap(), vec![(1, 1)]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_upload_file_errors() {
+        let path = temp_file("errors", b"data");
+        let (url, _) = serve(vec![403]);
+        match pollster::block_on(upload_file(url, path.clone(), headers(), 10, None, None)) {
+            Err(UploadError::HttpStatus(403)) => (),
+            other => panic!("Expected HttpStatus error, got {:?}", other),
+        }
+        let (url, _) = serve(vec![500, 500]);
+        let policy = Some(retry_policy(2));
+        match pollster::block_on(upload_file(url, path.clone(), headers(), 10, None, policy)) {
+            Err(UploadError::HttpStatus(500)) => (),
+            other => panic!("Expected HttpStatus error, got {:?}", other),
+        }
+        let url = "http://127.0.0.1:9/upload".to_string();
+        match pollster::block_on(upload_file(url, path.clone(), headers(), 0, None, None)) {
+            Err(UploadError::InvalidChunkSize) => (),
+            other => panic!("Expected InvalidChunkSize error, got {:?}", other),
+        }
+        let url = "ftp://example.com/upload".to_string();
+        match pollster::block_on(upload_file(url, path.clone(), headers(), 10, None, None)) {
+            Err(UploadError::InvalidUrl(_)) => (),
+            other => panic!("Expected InvalidUrl error, got {:?}", other),
+        }
+        let _ = std::fs::remove_file(path);
+    }
+}
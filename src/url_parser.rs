@@ -0,0 +1,413 @@
+//! URL解析・組み立てモジュール
+//!
+//! このモジュールは、URLを構成要素に分解する`parse_url`と、構成要素から
+//! URLを組み立てる`UrlBuilder`をエクスポートします。
+//! FoundationのURLとバックエンドのルーターではURLの解釈が異なる場合があるため、
+//! 解析と組み立てをRust側に統一して同じ結果を得るために使用します。
+//!
+//! 解析はWHATWG URL Standard（RFC 3986と互換）に従い、ホスト名の小文字化・IDNの
+//! Punycode変換・既定ポートの省略・`.`/`..`セグメントの解決などの正規化を行います。
+
+use std::sync::{Arc, Mutex};
+
+use percent_encoding::percent_decode_str;
+use thiserror::Error;
+use url::Url;
+
+use crate::encoding::{build_query_string, url_encode_component, QueryParam};
+
+/// ホスト名に含めることのできない文字
+const FORBIDDEN_HOST_CHARS: [char; 6] = ['/', '?', '#', '@', '\\', ' '];
+
+/// URLの解析・組み立てで発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum UrlError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// URLとして解析できない、または階層を持たないURL（`mailto:`など）の場合
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    /// スキーム・ホストなどの構成要素が不正な場合
+    #[error("Invalid URL component: {0}")]
+    InvalidComponent(String),
+}
+
+/// URLの構成要素
+///
+/// パスのセグメント・クエリ・フラグメントはパーセントデコード済みの値です。
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct UrlParts {
+    /// スキーム（小文字、例: `https`）
+    pub scheme: String,
+    /// ホスト（IDNはPunycode、IPv6アドレスは`[`と`]`で囲まれます）
+    pub host: Option<String>,
+    /// 明示的に指定されたポート（スキームの既定ポートの場合は`None`）
+    pub port: Option<u16>,
+    /// パスのセグメント（`/a/b/`は`["a", "b", ""]`、`/`は`[""]`）
+    pub path_segments: Vec<String>,
+    /// クエリのパラメータ（出現順、`+`は空白として扱います）
+    pub query: Vec<QueryParam>,
+    /// フラグメント（`#`を含みません）
+    pub fragment: Option<String>,
+}
+
+/// パーセントエンコードされた文字列をデコードします（不正なUTF-8は置換文字になります）
+fn decode(text: &str) -> String {
+    percent_decode_str(text).decode_utf8_lossy().into_owned()
+}
+
+/// 解析済みのURLを構成要素に分解します
+fn parts_from_url(url: &Url) -> Result<UrlParts, UrlError> {
+    let segments = url
+        .path_segments()
+        .ok_or_else(|| UrlError::InvalidUrl(format!("URL has no hierarchical path: {}", url)))?;
+    Ok(UrlParts {
+        scheme: url.scheme().to_string(),
+        host: url.host_str().filter(|host| !host.is_empty()).map(str::to_string),
+        port: url.port(),
+        path_segments: segments.map(decode).collect(),
+        query: url
+            .query_pairs()
+            .map(|(name, value)| QueryParam { name: name.into_owned(), value: value.into_owned() })
+            .collect(),
+        fragment: url.fragment().map(decode),
+    })
+}
+
+/// 構成要素からURLを組み立て、解析し直して正規化します
+fn url_from_parts(parts: &UrlParts) -> Result<Url, UrlError> {
+    let mut text = format!("{}:", parts.scheme);
+    if let Some(host) = &parts.host {
+        if host.is_empty() || host.contains(FORBIDDEN_HOST_CHARS) {
+            return Err(UrlError::InvalidComponent(format!("invalid host: {}", host)));
+        }
+        if host.contains(':') && !host.starts_with('[') {
+            text.push_str(&format!("//[{}]", host));
+        } else {
+            text.push_str(&format!("//{}", host));
+        }
+        if let Some(port) = parts.port {
+            text.push_str(&format!(":{}", port));
+        }
+    } else if parts.port.is_some() {
+        return Err(UrlError::InvalidComponent("port requires a host".to_string()));
+    }
+    for segment in &parts.path_segments {
+        // `.`と`..`は解析時に解決され、親のパスへの移動になるため受け付けない
+        if segment == "." || segment == ".." {
+            return Err(UrlError::InvalidComponent(format!("invalid path segment: {}", segment)));
+        }
+        text.push('/');
+        text.push_str(&url_encode_component(segment.clone()));
+    }
+    if parts.path_segments.is_empty() {
+        text.push('/');
+    }
+    if !parts.query.is_empty() {
+        text.push('?');
+        text.push_str(&build_query_string(parts.query.clone()));
+    }
+    if let Some(fragment) = &parts.fragment {
+        text.push('#');
+        text.push_str(&url_encode_component(fragment.clone()));
+    }
+    let url = Url::parse(&text).map_err(|e| UrlError::InvalidComponent(e.to_string()))?;
+    if url.scheme() != parts.scheme.to_ascii_lowercase() {
+        return Err(UrlError::InvalidComponent(format!("invalid scheme: {}", parts.scheme)));
+    }
+    // `https:/a`のようにホストを省略したURLは、最初のセグメントがホストとして解釈されます
+    if parts.host.is_none() && url.host_str().is_some_and(|host| !host.is_empty()) {
+        return Err(UrlError::InvalidComponent(format!(
+            "scheme {} requires a host",
+            parts.scheme
+        )));
+    }
+    Ok(url)
+}
+
+/// URLを構成要素に分解します
+///
+/// ユーザー情報（`user:password@`）は構成要素に含まれません。
+///
+/// # Arguments
+/// * `text` - 解析するURL（絶対URL）
+///
+/// # Errors
+/// * `UrlError::InvalidUrl` - URLとして解析できない、または階層を持たないURLの場合
+///
+/// # Example
+/// ```
+/// let parts = parse_url(
+///     "https://Example.com:8443/users/%E5%A4%AA%E9%83%8E?tab=posts#top".to_string(),
+/// )?;
+/// assert_eq!(parts.host, Some("example.com".to_string()));
+/// assert_eq!(parts.port, Some(8443));
+/// assert_eq!(parts.path_segments, vec!["users", "太郎"]);
+/// ```
+#[uniffi::export]
+pub fn parse_url(text: String) -> Result<UrlParts, UrlError> {
+    let url = Url::parse(text.trim()).map_err(|e| UrlError::InvalidUrl(e.to_string()))?;
+    parts_from_url(&url)
+}
+
+/// URLを組み立てるビルダー
+///
+/// 構成要素はデコード済みの値で指定し、`build`でエンコードして組み立てます。
+/// パスのセグメントに含まれる`/`は`%2F`にエンコードされます。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let builder = UrlBuilder::new("https://api.example.com/v1".to_string())?;
+/// builder.append_path_segment("users".to_string())?;
+/// builder.append_path_segment(user_id)?;
+/// builder.append_query("include".to_string(), "posts,comments".to_string())?;
+/// let url = builder.build()?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct UrlBuilder {
+    parts: Mutex<UrlParts>,
+}
+
+impl UrlBuilder {
+    /// 構成要素を変更します
+    fn update(&self, f: impl FnOnce(&mut UrlParts)) -> Result<(), UrlError> {
+        let mut parts = self.parts.lock()
+            .map_err(|_| UrlError::MutexPoisoned)?;
+        f(&mut parts);
+        Ok(())
+    }
+}
+
+#[uniffi::export]
+impl UrlBuilder {
+    /// 基準となるURLからビルダーを作成します
+    ///
+    /// # Arguments
+    /// * `base` - 基準となるURL
+    ///
+    /// # Errors
+    /// * `UrlError::InvalidUrl` - URLとして解析できない、または階層を持たないURLの場合
+    #[uniffi::constructor]
+    pub fn new(base: String) -> Result<Arc<Self>, UrlError> {
+        Ok(Self::from_parts(parse_url(base)?))
+    }
+
+    /// 構成要素からビルダーを作成します
+    ///
+    /// 構成要素は`build`の時点で検証されます。
+    #[uniffi::constructor]
+    pub fn from_parts(parts: UrlParts) -> Arc<Self> {
+        Arc::new(Self { parts: Mutex::new(parts) })
+    }
+
+    /// スキームを設定します
+    pub fn set_scheme(&self, scheme: String) -> Result<(), UrlError> {
+        self.update(|parts| parts.scheme = scheme)
+    }
+
+    /// ホストを設定します（`None`の場合はホストなし）
+    pub fn set_host(&self, host: Option<String>) -> Result<(), UrlError> {
+        self.update(|parts| parts.host = host)
+    }
+
+    /// ポートを設定します（`None`の場合はスキームの既定ポート）
+    pub fn set_port(&self, port: Option<u16>) -> Result<(), UrlError> {
+        self.update(|parts| parts.port = port)
+    }
+
+    /// パスの末尾にセグメントを追加します
+    ///
+    /// 末尾のセグメントが空（パスが`/`や`/a/`）の場合は、空のセグメントを置き換えます。
+    pub fn append_path_segment(&self, segment: String) -> Result<(), UrlError> {
+        self.update(|parts| {
+            if parts.path_segments.last().is_some_and(String::is_empty) {
+                parts.path_segments.pop();
+            }
+            parts.path_segments.push(segment);
+        })
+    }
+
+    /// パスのセグメントをすべて置き換えます
+    pub fn set_path_segments(&self, segments: Vec<String>) -> Result<(), UrlError> {
+        self.update(|parts| parts.path_segments = segments)
+    }
+
+    /// クエリのパラメータを末尾に追加します（同じ名前のパラメータは残します）
+    pub fn append_query(&self, name: String, value: String) -> Result<(), UrlError> {
+        self.update(|parts| parts.query.push(QueryParam { name, value }))
+    }
+
+    /// クエリのパラメータを設定します
+    ///
+    /// 同じ名前のパラメータがある場合は最初の位置の値を置き換え、残りを削除します。
+    pub fn set_query(&self, name: String, value: String) -> Result<(), UrlError> {
+        self.update(|parts| {
+            let mut found = false;
+            parts.query.retain_mut(|param| {
+                if param.name != name {
+                    return true;
+                }
+                if found {
+                    return false;
+                }
+                found = true;
+                param.value = value.clone();
+                true
+            });
+            if !found {
+                parts.query.push(QueryParam { name, value });
+            }
+        })
+    }
+
+    /// 指定した名前のクエリのパラメータをすべて削除します
+    pub fn remove_query(&self, name: String) -> Result<(), UrlError> {
+        self.update(|parts| parts.query.retain(|param| param.name != name))
+    }
+
+    /// フラグメントを設定します（`None`の場合はフラグメントなし）
+    pub fn set_fragment(&self, fragment: Option<String>) -> Result<(), UrlError> {
+        self.update(|parts| parts.fragment = fragment)
+    }
+
+    /// 現在の構成要素を返します
+    pub fn parts(&self) -> Result<UrlParts, UrlError> {
+        let parts = self.parts.lock()
+            .map_err(|_| UrlError::MutexPoisoned)?;
+        Ok(parts.clone())
+    }
+
+    /// URLを組み立てます
+    ///
+    /// # Errors
+    /// * `UrlError::InvalidComponent` - スキームやホストが不正な場合、パスのセグメントが
+    ///   `.`・`..`の場合、またはホストのない`https`のようにスキームの要件を満たさない場合
+    /// * `UrlError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn build(&self) -> Result<String, UrlError> {
+        let parts = self.parts.lock()
+            .map_err(|_| UrlError::MutexPoisoned)?;
+        Ok(url_from_parts(&parts)?.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(name: &str, value: &str) -> QueryParam {
+        QueryParam { name: name.to_string(), value: value.to_string() }
+    }
+
+    #[test]
+    fn test_parse_url() {
+        let parts = parse_url(
+            "HTTPS://Example.COM:8443/a/./b/../users/%E5%A4%AA%E9%83%8E?q=a+b&tag=x&tag=%26#sec%201"
+                .to_string(),
+        )
+        .unwrap();
+        assert_eq!(parts.scheme, "https");
+        assert_eq!(parts.host, Some("example.com".to_string()));
+        assert_eq!(parts.port, Some(8443));
+        assert_eq!(parts.path_segments, vec!["a", "users", "太郎"]);
+        assert_eq!(parts.query, vec![param("q", "a b"), param("tag", "x"), param("tag", "&")]);
+        assert_eq!(parts.fragment, Some("sec 1".to_string()));
+
+        let parts = parse_url("http://user:pw@[::1]:80/".to_string()).unwrap();
+        assert_eq!(parts.host, Some("[::1]".to_string()));
+        assert_eq!(parts.port, None);
+        assert_eq!(parts.path_segments, vec![""]);
+        assert!(parts.query.is_empty());
+
+        let parts = parse_url("https://日本.example/".to_string()).unwrap();
+        assert_eq!(parts.host, Some("xn--wgv71a.example".to_string()));
+    }
+
+    #[test]
+    fn test_parse_url_errors() {
+        for text in ["not a url", "/relative/path", "https://", "mailto:someone@example.com"] {
+            match parse_url(text.to_string()) {
+                Err(UrlError::InvalidUrl(_)) => (),
+                other => panic!("Expected InvalidUrl error for {}, got {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_builder() {
+        let builder = UrlBuilder::new("https://api.example.com/".to_string()).unwrap();
+        builder.append_path_segment("v1".to_string()).unwrap();
+        builder.append_path_segment("files".to_string()).unwrap();
+        builder.append_path_segment("a/b c".to_string()).unwrap();
+        builder.append_query("tag".to_string(), "x".to_string()).unwrap();
+        builder.append_query("q".to_string(), "rust & swift".to_string()).unwrap();
+        builder.append_query("tag".to_string(), "y".to_string()).unwrap();
+        builder.set_fragment(Some("top".to_string())).unwrap();
+        assert_eq!(
+            builder.build().unwrap(),
+            "https://api.example.com/v1/files/a%2Fb%20c?tag=x&q=rust%20%26%20swift&tag=y#top"
+        );
+
+        builder.set_query("tag".to_string(), "z".to_string()).unwrap();
+        builder.remove_query("q".to_string()).unwrap();
+        builder.set_port(Some(443)).unwrap();
+        builder.set_fragment(None).unwrap();
+        assert_eq!(builder.build().unwrap(), "https://api.example.com/v1/files/a%2Fb%20c?tag=z");
+
+        builder.set_scheme("http".to_string()).unwrap();
+        builder.set_host(Some("::1".to_string())).unwrap();
+        builder.set_port(Some(8080)).unwrap();
+        builder.set_path_segments(Vec::new()).unwrap();
+        assert_eq!(builder.build().unwrap(), "http://[::1]:8080/?tag=z");
+    }
+
+    #[test]
+    fn test_builder_roundtrip() {
+        let text = "https://example.com:8443/users/%E5%A4%AA%E9%83%8E/?q=a%20b&tag=%26#sec%201";
+        let parts = parse_url(text.to_string()).unwrap();
+        let rebuilt = UrlBuilder::from_parts(parts.clone()).build().unwrap();
+        assert_eq!(rebuilt, text);
+        assert_eq!(parse_url(rebuilt).unwrap(), parts);
+    }
+
+    #[test]
+    fn test_builder_errors() {
+        let builder = UrlBuilder::new("https://example.com/".to_string()).unwrap();
+        builder.set_path_segments(vec!["v1".to_string()]).unwrap();
+        for host in [Some("evil.com/path"), Some("a@b"), None] {
+            builder.set_host(host.map(str::to_string)).unwrap();
+            match builder.build() {
+                Err(UrlError::InvalidComponent(_)) => (),
+                other => panic!("Expected InvalidComponent error for {:?}, got {:?}", host, other),
+            }
+        }
+        builder.set_host(Some("example.com".to_string())).unwrap();
+        builder.set_scheme("ht tp".to_string()).unwrap();
+        match builder.build() {
+            Err(UrlError::InvalidComponent(_)) => (),
+            other => panic!("Expected InvalidComponent error, got {:?}", other),
+        }
+
+        // `.`・`..`のセグメントはパスの移動になるため組み立てない
+        let builder = UrlBuilder::new("https://api.example.com/users".to_string()).unwrap();
+        for segment in ["..", "."] {
+            builder.append_path_segment(segment.to_string()).unwrap();
+            builder.append_path_segment("admin".to_string()).unwrap();
+            match builder.build() {
+                Err(UrlError::InvalidComponent(_)) => (),
+                other => panic!("Expected InvalidComponent error for {}, got {:?}", segment, other),
+            }
+            builder.set_path_segments(vec!["users".to_string()]).unwrap();
+        }
+        builder.append_path_segment("..a".to_string()).unwrap();
+        assert_eq!(builder.build().unwrap(), "https://api.example.com/users/..a");
+
+        // ホストを持たないスキームではホストを省略できる
+        let builder = UrlBuilder::new("file:///tmp/a.txt".to_string()).unwrap();
+        assert_eq!(builder.parts().unwrap().host, None);
+        builder.append_path_segment("b.txt".to_string()).unwrap();
+        assert_eq!(builder.build().unwrap(), "file:///tmp/a.txt/b.txt");
+    }
+}
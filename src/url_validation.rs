@@ -0,0 +1,367 @@
+//! URL検証モジュール
+//!
+//! このモジュールは、ユーザーが貼り付けたリンクやディープリンクを開く前に、
+//! スキーム・ホスト・長さをポリシーに照らして検証する`validate_url`をエクスポートします。
+//! 拒否の理由は構造化された値で返すため、Swift側で理由ごとのメッセージを表示できます。
+//!
+//! ホスト名はDNSで解決しないため、プライベートアドレスに解決されるホスト名は検出できません。
+//! サーバー側でのSSRF対策の代わりにはなりません。
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use url::{Host, Url};
+
+/// `allowed_schemes`が空の場合に許可するスキーム
+const DEFAULT_SCHEMES: [&str; 2] = ["http", "https"];
+
+/// URLの検証ポリシー
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct UrlPolicy {
+    /// 許可するスキーム（大文字・小文字は区別しません）。空の場合は`http`と`https`
+    #[uniffi(default = [])]
+    pub allowed_schemes: Vec<String>,
+    /// 許可するホスト。空の場合はすべてのホストを許可します。
+    /// ドメインはそのサブドメインにも一致します（`example.com`は`api.example.com`に一致）
+    #[uniffi(default = [])]
+    pub allowed_hosts: Vec<String>,
+    /// 拒否するホスト（`allowed_hosts`より優先され、サブドメインにも一致します）
+    #[uniffi(default = [])]
+    pub denied_hosts: Vec<String>,
+    /// `localhost`と、ループバック・プライベート・リンクローカルなどのIPアドレスを拒否するかどうか
+    #[uniffi(default = true)]
+    pub block_private_addresses: bool,
+    /// ユーザー情報（`user:password@`）を含むURLを拒否するかどうか
+    #[uniffi(default = true)]
+    pub block_credentials: bool,
+    /// URLの最大長（バイト数）
+    #[uniffi(default = 2048)]
+    pub max_length: u32,
+}
+
+impl Default for UrlPolicy {
+    fn default() -> Self {
+        Self {
+            allowed_schemes: Vec::new(),
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            block_private_addresses: true,
+            block_credentials: true,
+            max_length: 2048,
+        }
+    }
+}
+
+/// URLを拒否する理由
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Enum)]
+pub enum UrlRejection {
+    /// URLとして解析できない場合
+    Malformed { message: String },
+    /// 最大長を超える場合
+    TooLong { length: u64, max_length: u32 },
+    /// スキームが許可されていない場合
+    SchemeNotAllowed { scheme: String },
+    /// `allowed_hosts`が指定されているのにホストがない場合
+    MissingHost,
+    /// ホストが`allowed_hosts`に含まれない場合
+    HostNotAllowed { host: String },
+    /// ホストが`denied_hosts`に含まれる場合
+    HostDenied { host: String },
+    /// ホストが`localhost`またはプライベートなIPアドレスの場合
+    PrivateAddress { host: String },
+    /// ユーザー情報を含む場合
+    CredentialsNotAllowed,
+}
+
+/// URLの検証結果
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct UrlValidation {
+    /// 拒否する理由がない場合は`true`
+    pub is_valid: bool,
+    /// 正規化したURL（解析できなかった場合は`None`）
+    pub normalized_url: Option<String>,
+    /// 拒否する理由（該当するものをすべて含みます）
+    pub reasons: Vec<UrlRejection>,
+}
+
+/// ホストを比較用に正規化します
+///
+/// 特別でないスキーム（`myapp://`など）のホストは小文字化やIPv4の解析をされないため、
+/// 末尾のドットを取り除いてから`http`と同じ規則で解析し直します。
+fn canonical_host(host: &Host<&str>) -> Host<String> {
+    match host {
+        Host::Domain(name) => {
+            let name = name.to_ascii_lowercase();
+            let name = name.trim_end_matches('.');
+            Host::parse(name).unwrap_or_else(|_| Host::Domain(name.to_string()))
+        }
+        Host::Ipv4(address) => Host::Ipv4(*address),
+        Host::Ipv6(address) => Host::Ipv6(*address),
+    }
+}
+
+/// ポリシーのホスト指定を正規化します（小文字化・末尾のドットの除去・IDNのPunycode変換）
+fn normalize_pattern(pattern: &str) -> Option<String> {
+    let pattern = pattern.trim().trim_start_matches("*.").trim_start_matches('.');
+    let pattern = pattern.to_ascii_lowercase();
+    Host::parse(pattern.trim_end_matches('.')).ok().map(|host| host.to_string())
+}
+
+/// ホストがポリシーのホスト指定の一覧に一致するかどうかを返します
+fn matches_any(host: &Host<String>, patterns: &[String]) -> bool {
+    let name = host.to_string();
+    patterns.iter().filter_map(|pattern| normalize_pattern(pattern)).any(|pattern| {
+        name == pattern
+            || (matches!(host, Host::Domain(_)) && name.ends_with(&format!(".{}", pattern)))
+    })
+}
+
+/// 公開されていないIPv4アドレスかどうかを返します
+fn is_private_ipv4(address: Ipv4Addr) -> bool {
+    let [first, second, ..] = address.octets();
+    address.is_unspecified()
+        || address.is_loopback()
+        || address.is_private()
+        || address.is_link_local()
+        || address.is_multicast()
+        || address.is_broadcast()
+        // 0.0.0.0/8・共有アドレス空間（100.64.0.0/10）・予約済み（240.0.0.0/4）
+        || first == 0
+        || (first == 100 && (second & 0xc0) == 64)
+        || first >= 240
+}
+
+/// 公開されていないIPv6アドレスかどうかを返します
+fn is_private_ipv6(address: Ipv6Addr) -> bool {
+    let segments = address.segments();
+    // IPv4射影アドレス（::ffff:0:0/96）・IPv4互換アドレス（::/96）・NAT64（64:ff9b::/96）は
+    // 埋め込まれたIPv4で判定する
+    if let Some(ipv4) = address.to_ipv4() {
+        return is_private_ipv4(ipv4);
+    }
+    if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+        let [.., high, low] = segments;
+        return is_private_ipv4(Ipv4Addr::from((u32::from(high) << 16) | u32::from(low)));
+    }
+    address.is_unspecified()
+        || address.is_loopback()
+        || address.is_multicast()
+        // ユニークローカル（fc00::/7）・リンクローカル（fe80::/10）・サイトローカル（fec0::/10）
+        || (segments[0] & 0xfe00) == 0xfc00
+        || (segments[0] & 0xffc0) == 0xfe80
+        || (segments[0] & 0xffc0) == 0xfec0
+}
+
+/// `localhost`またはプライベートなIPアドレスかどうかを返します（`canonical_host`で正規化済み）
+fn is_private_host(host: &Host<String>) -> bool {
+    match host {
+        Host::Domain(name) => name == "localhost" || name.ends_with(".localhost"),
+        Host::Ipv4(address) => is_private_ipv4(*address),
+        Host::Ipv6(address) => is_private_ipv6(*address),
+    }
+}
+
+/// URLをポリシーに照らして検証します
+///
+/// 該当する拒否理由をすべて返します。`block_private_addresses`はIPアドレスの表記
+/// （`http://2130706433/`・`http://[::ffff:127.0.0.1]/`など）を正規化してから判定しますが、
+/// ホスト名のDNS解決は行いません。
+///
+/// # Arguments
+/// * `text` - 検証するURL（前後の空白は無視します）
+/// * `policy` - 検証ポリシー
+///
+/// # Example
+/// ```
+/// let policy = UrlPolicy {
+///     allowed_schemes: vec!["https".to_string(), "myapp".to_string()],
+///     ..Default::default()
+/// };
+/// let result = validate_url("http://192.168.0.1/admin".to_string(), policy);
+/// assert!(!result.is_valid);
+/// // reasons: [SchemeNotAllowed { scheme: "http" }, PrivateAddress { host: "192.168.0.1" }]
+/// ```
+#[uniffi::export]
+pub fn validate_url(text: String, policy: UrlPolicy) -> UrlValidation {
+    let text = text.trim();
+    let mut reasons = Vec::new();
+    if text.len() > policy.max_length as usize {
+        reasons.push(UrlRejection::TooLong {
+            length: text.len() as u64,
+            max_length: policy.max_length,
+        });
+    }
+    let url = match Url::parse(text) {
+        Ok(url) => url,
+        Err(error) => {
+            reasons.push(UrlRejection::Malformed { message: error.to_string() });
+            return UrlValidation { is_valid: false, normalized_url: None, reasons };
+        }
+    };
+
+    let scheme_allowed = if policy.allowed_schemes.is_empty() {
+        DEFAULT_SCHEMES.contains(&url.scheme())
+    } else {
+        policy.allowed_schemes.iter().any(|scheme| scheme.trim().eq_ignore_ascii_case(url.scheme()))
+    };
+    if !scheme_allowed {
+        reasons.push(UrlRejection::SchemeNotAllowed { scheme: url.scheme().to_string() });
+    }
+    if policy.block_credentials && (!url.username().is_empty() || url.password().is_some()) {
+        reasons.push(UrlRejection::CredentialsNotAllowed);
+    }
+
+    match url.host().filter(|host| !matches!(host, Host::Domain(""))) {
+        Some(host) => {
+            let name = host.to_string();
+            let host = canonical_host(&host);
+            if matches_any(&host, &policy.denied_hosts) {
+                reasons.push(UrlRejection::HostDenied { host: name.clone() });
+            } else if !policy.allowed_hosts.is_empty() && !matches_any(&host, &policy.allowed_hosts)
+            {
+                reasons.push(UrlRejection::HostNotAllowed { host: name.clone() });
+            }
+            if policy.block_private_addresses && is_private_host(&host) {
+                reasons.push(UrlRejection::PrivateAddress { host: name });
+            }
+        }
+        None if !policy.allowed_hosts.is_empty() => reasons.push(UrlRejection::MissingHost),
+        None => {}
+    }
+
+    UrlValidation { is_valid: reasons.is_empty(), normalized_url: Some(url.to_string()), reasons }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reasons(text: &str, policy: UrlPolicy) -> Vec<UrlRejection> {
+        validate_url(text.to_string(), policy).reasons
+    }
+
+    #[test]
+    fn test_validate_url_valid() {
+        let result =
+            validate_url(" HTTPS://Example.com/a/../b?q=1 ".to_string(), UrlPolicy::default());
+        assert!(result.is_valid);
+        assert_eq!(result.normalized_url.as_deref(), Some("https://example.com/b?q=1"));
+        assert!(result.reasons.is_empty());
+
+        let policy = UrlPolicy {
+            allowed_schemes: vec!["myapp".to_string()],
+            allowed_hosts: vec!["open".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_url("myapp://open/item/42".to_string(), policy).is_valid);
+    }
+
+    #[test]
+    fn test_validate_url_schemes_and_length() {
+        assert_eq!(
+            reasons("javascript:alert(1)", UrlPolicy::default()),
+            vec![UrlRejection::SchemeNotAllowed { scheme: "javascript".to_string() }]
+        );
+        let policy = UrlPolicy { max_length: 20, ..Default::default() };
+        assert_eq!(
+            reasons("https://example.com/long/path", policy),
+            vec![UrlRejection::TooLong { length: 29, max_length: 20 }]
+        );
+        match reasons("not a url", UrlPolicy::default()).as_slice() {
+            [UrlRejection::Malformed { .. }] => (),
+            other => panic!("Expected Malformed reason, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_url_hosts() {
+        let policy = UrlPolicy {
+            allowed_hosts: vec!["example.com".to_string(), "*.例え.jp".to_string()],
+            denied_hosts: vec!["evil.example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_url("https://api.example.com/".to_string(), policy.clone()).is_valid);
+        assert!(validate_url("https://www.例え.jp/".to_string(), policy.clone()).is_valid);
+        assert_eq!(
+            reasons("https://cdn.evil.example.com/", policy.clone()),
+            vec![UrlRejection::HostDenied { host: "cdn.evil.example.com".to_string() }]
+        );
+        assert_eq!(
+            reasons("https://notexample.com/", policy.clone()),
+            vec![UrlRejection::HostNotAllowed { host: "notexample.com".to_string() }]
+        );
+        // 末尾のドットや大文字を含むホストも同じホストとして扱う
+        assert_eq!(
+            reasons("https://evil.example.com./", policy.clone()),
+            vec![UrlRejection::HostDenied { host: "evil.example.com.".to_string() }]
+        );
+        assert!(validate_url("https://api.example.com./".to_string(), policy.clone()).is_valid);
+        let custom = UrlPolicy { allowed_schemes: vec!["myapp".to_string()], ..policy.clone() };
+        assert_eq!(
+            reasons("myapp://EVIL.example.com/x", custom.clone()),
+            vec![UrlRejection::HostDenied { host: "EVIL.example.com".to_string() }]
+        );
+        assert!(validate_url("myapp://API.Example.com./x".to_string(), custom).is_valid);
+        let policy = UrlPolicy { allowed_schemes: vec!["mailto".to_string()], ..policy };
+        assert_eq!(reasons("mailto:a@example.com", policy), vec![UrlRejection::MissingHost]);
+    }
+
+    #[test]
+    fn test_validate_url_private_addresses() {
+        for text in [
+            "http://localhost:8080/",
+            "http://api.localhost/",
+            "http://127.0.0.1/",
+            "http://2130706433/",
+            "http://10.1.2.3/",
+            "http://172.16.0.1/",
+            "http://192.168.1.1/",
+            "http://169.254.169.254/latest/meta-data",
+            "http://100.64.0.1/",
+            "http://0.0.0.0/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[fe80::1]/",
+            "http://[fec0::1]/",
+            "http://[::ffff:127.0.0.1]/",
+            "http://[::127.0.0.1]/",
+            "http://[::10.0.0.1]/",
+            "http://[64:ff9b::10.0.0.1]/",
+        ] {
+            match reasons(text, UrlPolicy::default()).as_slice() {
+                [UrlRejection::PrivateAddress { .. }] => (),
+                other => panic!("Expected PrivateAddress reason for {}, got {:?}", text, other),
+            }
+        }
+        for text in ["http://8.8.8.8/", "http://[2001:4860:4860::8888]/", "http://172.32.0.1/"] {
+            assert!(validate_url(text.to_string(), UrlPolicy::default()).is_valid, "{}", text);
+        }
+        let policy = UrlPolicy { block_private_addresses: false, ..Default::default() };
+        assert!(validate_url("http://localhost/".to_string(), policy).is_valid);
+
+        // 特別でないスキームのホストも同じ規則で判定する
+        let policy = UrlPolicy { allowed_schemes: vec!["myapp".to_string()], ..Default::default() };
+        for text in
+            ["myapp://127.0.0.1/", "myapp://2130706433/", "myapp://LocalHost/", "myapp://[::1]/"]
+        {
+            match reasons(text, policy.clone()).as_slice() {
+                [UrlRejection::PrivateAddress { .. }] => (),
+                other => panic!("Expected PrivateAddress reason for {}, got {:?}", text, other),
+            }
+        }
+        assert!(validate_url("myapp://8.8.8.8/".to_string(), policy).is_valid);
+    }
+
+    #[test]
+    fn test_validate_url_multiple_reasons() {
+        let result = validate_url("ftp://user:pw@192.168.0.1/".to_string(), UrlPolicy::default());
+        assert!(!result.is_valid);
+        assert_eq!(
+            result.reasons,
+            vec![
+                UrlRejection::SchemeNotAllowed { scheme: "ftp".to_string() },
+                UrlRejection::CredentialsNotAllowed,
+                UrlRejection::PrivateAddress { host: "192.168.0.1".to_string() },
+            ]
+        );
+    }
+}
@@ -0,0 +1,444 @@
+//! User-Agent解析モジュール
+//!
+//! このモジュールは、User-Agent文字列からブラウザー・OS・デバイスの種類とバージョンを
+//! 判別する`parse_user_agent`をエクスポートします。APIが返すセッションのメタデータを
+//! サポートツールの画面で確認するために使用します。
+//!
+//! 主要なブラウザー（Chrome・Safari・Firefox・Edge・Opera・Samsung Internetなど）、
+//! アプリ内のWebView、クローラー、`MyApp/1.0 (iPhone; iOS 17.0)`形式のアプリのUser-Agentを
+//! 判別します。判別できない値は`Other`になります。
+
+/// 判別できない場合の名前
+const OTHER: &str = "Other";
+
+/// ブラウザーの判別規則（User-Agentに含まれるトークン、ブラウザー名）。先頭から順に照合します
+const BROWSERS: &[(&str, &str)] = &[
+    ("EdgiOS/", "Edge"),
+    ("EdgA/", "Edge"),
+    ("Edg/", "Edge"),
+    ("Edge/", "Edge"),
+    ("OPiOS/", "Opera"),
+    ("OPR/", "Opera"),
+    ("SamsungBrowser/", "Samsung Internet"),
+    ("YaBrowser/", "Yandex Browser"),
+    ("UCBrowser/", "UC Browser"),
+    ("FxiOS/", "Firefox"),
+    ("Firefox/", "Firefox"),
+    ("CriOS/", "Chrome"),
+];
+
+/// クローラーの名前に含まれる語（小文字）
+const BOT_KEYWORDS: [&str; 4] = ["bot", "crawler", "spider", "slurp"];
+
+/// デバイスの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, uniffi::Enum)]
+pub enum DeviceType {
+    /// デスクトップ・ノートPC
+    Desktop,
+    /// スマートフォン
+    Mobile,
+    /// タブレット
+    Tablet,
+    /// クローラー
+    Bot,
+    /// 判別できない場合
+    Unknown,
+}
+
+/// User-Agentから判別したクライアントの情報
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct ClientInfo {
+    /// ブラウザー・クローラー・アプリの名前（`Chrome`・`Googlebot`など）
+    pub browser: String,
+    /// ブラウザーのバージョン（`120.0.6099.109`など）
+    pub browser_version: Option<String>,
+    /// OSの名前（`Windows`・`macOS`・`iOS`・`Android`・`Chrome OS`・`Linux`など）
+    pub os: String,
+    /// OSのバージョン（`17.1.2`など。Windowsは`10`・`8.1`・`XP`などの製品名）
+    pub os_version: Option<String>,
+    /// デバイスの名前（`iPhone`・`iPad`・`Mac`、Androidの場合は`Pixel 8`などの機種名）
+    pub device_family: String,
+    /// デバイスの種類
+    pub device_type: DeviceType,
+}
+
+/// `marker`の直後のバージョン（数字・`.`・`_`の並び）を`.`区切りで返します
+fn version_after(text: &str, marker: &str) -> Option<String> {
+    text.match_indices(marker).find_map(|(index, _)| {
+        let rest = &text[index + marker.len()..];
+        let end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '_'))
+            .unwrap_or(rest.len());
+        let version = rest[..end].replace('_', ".");
+        let version = version.trim_end_matches('.');
+        (!version.is_empty() && version.starts_with(|c: char| c.is_ascii_digit()))
+            .then(|| version.to_string())
+    })
+}
+
+/// 製品トークン（`Name/1.2.3`）を名前とバージョンに分けます
+fn product_token(token: &str) -> (String, Option<String>) {
+    match token.split_once('/') {
+        Some((name, version)) => (name.to_string(), version_after(version, "")),
+        None => (token.to_string(), None),
+    }
+}
+
+/// クローラーの名前とバージョンを返します（クローラーでない場合は`None`）
+fn detect_bot(ua: &str) -> Option<(String, Option<String>)> {
+    ua.split([' ', ';', '(', ')', ','])
+        .filter(|token| !token.is_empty() && !token.contains(':'))
+        .find(|token| {
+            let lower = token.to_ascii_lowercase();
+            BOT_KEYWORDS.iter().any(|keyword| lower.contains(keyword))
+        })
+        .map(|token| product_token(token.trim_start_matches('+')))
+}
+
+/// ブラウザーの名前とバージョンを返します
+fn detect_browser(ua: &str) -> (String, Option<String>) {
+    if let Some((marker, name)) = BROWSERS.iter().find(|(marker, _)| ua.contains(marker)) {
+        return (name.to_string(), version_after(ua, marker));
+    }
+    if ua.contains("Chrome/") {
+        // Android WebViewは`; wv)`を含む
+        let name = if ua.contains("; wv)") { "Android WebView" } else { "Chrome" };
+        return (name.to_string(), version_after(ua, "Chrome/"));
+    }
+    if ua.contains("MSIE ") {
+        return ("Internet Explorer".to_string(), version_after(ua, "MSIE "));
+    }
+    if ua.contains("Trident/") {
+        return ("Internet Explorer".to_string(), version_after(ua, "rv:"));
+    }
+    if ua.contains("Safari/") && ua.contains("Version/") {
+        return ("Safari".to_string(), version_after(ua, "Version/"));
+    }
+    let is_apple_mobile = ["iPhone", "iPad", "iPod"].iter().any(|device| ua.contains(device));
+    if ua.starts_with("Mozilla/") && is_apple_mobile && ua.contains("AppleWebKit/") {
+        // アプリ内のWKWebViewはSafariのトークンを含まない
+        return ("WebView".to_string(), None);
+    }
+    // アプリ・HTTPクライアントは先頭の製品トークンを使用する
+    match ua.split_whitespace().next() {
+        Some(token) if !token.starts_with("Mozilla/") && !token.starts_with('(') => {
+            product_token(token)
+        }
+        _ => (OTHER.to_string(), None),
+    }
+}
+
+/// Windows NTのバージョンを製品名のバージョンに変換します
+fn windows_version(nt_version: &str) -> String {
+    match nt_version {
+        "10.0" => "10",
+        "6.3" => "8.1",
+        "6.2" => "8",
+        "6.1" => "7",
+        "6.0" => "Vista",
+        "5.1" | "5.2" => "XP",
+        other => other,
+    }
+    .to_string()
+}
+
+/// OSの名前とバージョンを返します
+fn detect_os(ua: &str) -> (String, Option<String>) {
+    if ua.contains("Windows Phone") {
+        return ("Windows Phone".to_string(), version_after(ua, "Windows Phone "));
+    }
+    if ua.contains("Windows") {
+        let version = version_after(ua, "Windows NT ").map(|version| windows_version(&version));
+        return ("Windows".to_string(), version);
+    }
+    if ["iPhone", "iPad", "iPod"].iter().any(|device| ua.contains(device)) {
+        let version = version_after(ua, " OS ").or_else(|| version_after(ua, "iOS "));
+        return ("iOS".to_string(), version);
+    }
+    if ua.contains("Android") {
+        return ("Android".to_string(), version_after(ua, "Android "));
+    }
+    if let Some((_, rest)) = ua.split_once("CrOS ") {
+        // `CrOS x86_64 15633.69.0`のアーキテクチャの次がプラットフォームのバージョン
+        let version = rest.split_once(' ').and_then(|(_, rest)| version_after(rest, ""));
+        return ("Chrome OS".to_string(), version);
+    }
+    if ua.contains("Mac OS X") || ua.contains("Macintosh") {
+        return ("macOS".to_string(), version_after(ua, "Mac OS X "));
+    }
+    if ua.contains("Linux") || ua.contains("X11") {
+        return ("Linux".to_string(), None);
+    }
+    (OTHER.to_string(), None)
+}
+
+/// AndroidのUser-Agentから機種名を返します
+fn android_model(ua: &str) -> Option<String> {
+    let (_, rest) = ua.split_once("Android")?;
+    let rest = &rest[..rest.find(')').unwrap_or(rest.len())];
+    // 先頭はAndroidのバージョン。`U`やロケール（`ja-jp`）、Chromeの簡略化したUser-Agentの`K`は除く
+    rest.split(';').skip(1).map(str::trim).find_map(|part| {
+        let model = part.split(" Build/").next().unwrap_or(part).trim();
+        let is_locale = model.len() == 5 && model.as_bytes()[2] == b'-';
+        let is_placeholder = matches!(model, "" | "U" | "K" | "wv" | "Mobile" | "Tablet");
+        (!is_locale && !is_placeholder && !model.starts_with("rv:")).then(|| model.to_string())
+    })
+}
+
+/// デバイスの名前と種類を返します
+fn detect_device(ua: &str) -> (String, DeviceType) {
+    for (device, device_type) in
+        [("iPad", DeviceType::Tablet), ("iPhone", DeviceType::Mobile), ("iPod", DeviceType::Mobile)]
+    {
+        if ua.contains(device) {
+            return (device.to_string(), device_type);
+        }
+    }
+    if ua.contains("Windows Phone") {
+        return (OTHER.to_string(), DeviceType::Mobile);
+    }
+    if ua.contains("Android") {
+        let device_type =
+            if ua.contains("Mobile") { DeviceType::Mobile } else { DeviceType::Tablet };
+        return (android_model(ua).unwrap_or_else(|| OTHER.to_string()), device_type);
+    }
+    if ua.contains("Macintosh") {
+        return ("Mac".to_string(), DeviceType::Desktop);
+    }
+    if ["Windows", "CrOS", "X11", "Linux"].iter().any(|platform| ua.contains(platform)) {
+        return (OTHER.to_string(), DeviceType::Desktop);
+    }
+    (OTHER.to_string(), DeviceType::Unknown)
+}
+
+/// User-Agent文字列からブラウザー・OS・デバイスを判別します
+///
+/// User-Agentは自己申告の値のため、表示や集計の参考にのみ使用してください。
+/// iPadOSのSafariは既定でmacOSと同じUser-Agentを送るため、`Mac`と判別されます。
+///
+/// # Arguments
+/// * `text` - User-Agent文字列
+///
+/// # Example
+/// ```
+/// let info = parse_user_agent(
+///     "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1_2 like Mac OS X) AppleWebKit/605.1.15 \
+///      (KHTML, like Gecko) Version/17.1.2 Mobile/15E148 Safari/604.1"
+///         .to_string(),
+/// );
+/// assert_eq!(info.browser, "Safari");
+/// assert_eq!(info.os_version, Some("17.1.2".to_string()));
+/// assert_eq!(info.device_type, DeviceType::Mobile);
+/// ```
+#[uniffi::export]
+pub fn parse_user_agent(text: String) -> ClientInfo {
+    let ua = text.trim();
+    let (os, os_version) = detect_os(ua);
+    if let Some((browser, browser_version)) = detect_bot(ua) {
+        return ClientInfo {
+            browser,
+            browser_version,
+            os,
+            os_version,
+            device_family: OTHER.to_string(),
+            device_type: DeviceType::Bot,
+        };
+    }
+    let (browser, browser_version) = detect_browser(ua);
+    let (device_family, device_type) = detect_device(ua);
+    ClientInfo { browser, browser_version, os, os_version, device_family, device_type }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(ua: &str) -> ClientInfo {
+        parse_user_agent(ua.to_string())
+    }
+
+    fn summary(ua: &str) -> (String, Option<String>, String, Option<String>) {
+        let info = parse(ua);
+        (info.browser, info.browser_version, info.os, info.os_version)
+    }
+
+    fn expected(
+        browser: &str,
+        browser_version: &str,
+        os: &str,
+        os_version: Option<&str>,
+    ) -> (String, Option<String>, String, Option<String>) {
+        (
+            browser.to_string(),
+            Some(browser_version.to_string()),
+            os.to_string(),
+            os_version.map(str::to_string),
+        )
+    }
+
+    #[test]
+    fn test_parse_user_agent_desktop() {
+        let chrome = parse(concat!(
+            "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 ",
+            "(KHTML, like Gecko) Chrome/120.0.6099.109 Safari/537.36"
+        ));
+        assert_eq!(
+            chrome,
+            ClientInfo {
+                browser: "Chrome".to_string(),
+                browser_version: Some("120.0.6099.109".to_string()),
+                os: "Windows".to_string(),
+                os_version: Some("10".to_string()),
+                device_family: "Other".to_string(),
+                device_type: DeviceType::Desktop,
+            }
+        );
+
+        let safari = parse(concat!(
+            "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 ",
+            "(KHTML, like Gecko) Version/17.2 Safari/605.1.15"
+        ));
+        assert_eq!(
+            (safari.browser.as_str(), safari.os_version.as_deref(), safari.device_family.as_str()),
+            ("Safari", Some("10.15.7"), "Mac")
+        );
+
+        assert_eq!(
+            summary(concat!(
+                "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) ",
+                "Chrome/120.0.0.0 Safari/537.36 Edg/120.0.2210.91"
+            )),
+            expected("Edge", "120.0.2210.91", "Windows", Some("10"))
+        );
+        assert_eq!(
+            summary(concat!(
+                "Mozilla/5.0 (Macintosh; Intel Mac OS X 14.2; rv:121.0) ",
+                "Gecko/20100101 Firefox/121.0"
+            )),
+            expected("Firefox", "121.0", "macOS", Some("14.2"))
+        );
+        assert_eq!(
+            summary("Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0"),
+            expected("Firefox", "121.0", "Linux", None)
+        );
+        assert_eq!(
+            summary(concat!(
+                "Mozilla/5.0 (X11; CrOS x86_64 15633.69.0) AppleWebKit/537.36 ",
+                "(KHTML, like Gecko) Chrome/119.0.6045.212 Safari/537.36"
+            )),
+            expected("Chrome", "119.0.6045.212", "Chrome OS", Some("15633.69.0"))
+        );
+        assert_eq!(
+            summary("Mozilla/5.0 (Windows NT 6.1; WOW64; Trident/7.0; rv:11.0) like Gecko"),
+            expected("Internet Explorer", "11.0", "Windows", Some("7"))
+        );
+    }
+
+    #[test]
+    fn test_parse_user_agent_mobile() {
+        let iphone = parse(concat!(
+            "Mozilla/5.0 (iPhone; CPU iPhone OS 17_1_2 like Mac OS X) AppleWebKit/605.1.15 ",
+            "(KHTML, like Gecko) CriOS/120.0.6099.119 Mobile/15E148 Safari/604.1"
+        ));
+        assert_eq!(
+            iphone,
+            ClientInfo {
+                browser: "Chrome".to_string(),
+                browser_version: Some("120.0.6099.119".to_string()),
+                os: "iOS".to_string(),
+                os_version: Some("17.1.2".to_string()),
+                device_family: "iPhone".to_string(),
+                device_type: DeviceType::Mobile,
+            }
+        );
+
+        let ipad = parse(concat!(
+            "Mozilla/5.0 (iPad; CPU OS 16_6 like Mac OS X) AppleWebKit/605.1.15 ",
+            "(KHTML, like Gecko) Mobile/15E148"
+        ));
+        assert_eq!((ipad.browser.as_str(), ipad.os_version.as_deref()), ("WebView", Some("16.6")));
+        assert_eq!((ipad.device_family.as_str(), ipad.device_type), ("iPad", DeviceType::Tablet));
+
+        let pixel = parse(concat!(
+            "Mozilla/5.0 (Linux; Android 14; Pixel 8 Build/UD1A.230803.041; wv) ",
+            "AppleWebKit/537.36 (KHTML, like Gecko) Version/4.0 Chrome/120.0.6099.144 ",
+            "Mobile Safari/537.36"
+        ));
+        assert_eq!(pixel.browser, "Android WebView");
+        assert_eq!((pixel.os.as_str(), pixel.os_version.as_deref()), ("Android", Some("14")));
+        assert_eq!(
+            (pixel.device_family.as_str(), pixel.device_type),
+            ("Pixel 8", DeviceType::Mobile)
+        );
+
+        let samsung = parse(concat!(
+            "Mozilla/5.0 (Linux; Android 13; SM-X710) AppleWebKit/537.36 (KHTML, like Gecko) ",
+            "SamsungBrowser/23.0 Chrome/115.0.0.0 Safari/537.36"
+        ));
+        assert_eq!(samsung.browser, "Samsung Internet");
+        assert_eq!(
+            (samsung.device_family.as_str(), samsung.device_type),
+            ("SM-X710", DeviceType::Tablet)
+        );
+
+        let reduced = parse(concat!(
+            "Mozilla/5.0 (Linux; Android 10; K) AppleWebKit/537.36 (KHTML, like Gecko) ",
+            "Chrome/120.0.0.0 Mobile Safari/537.36"
+        ));
+        assert_eq!(
+            (reduced.device_family.as_str(), reduced.device_type),
+            ("Other", DeviceType::Mobile)
+        );
+        let firefox = parse("Mozilla/5.0 (Android 14; Mobile; rv:121.0) Gecko/121.0 Firefox/121.0");
+        assert_eq!(
+            (firefox.browser.as_str(), firefox.device_family.as_str()),
+            ("Firefox", "Other")
+        );
+    }
+
+    #[test]
+    fn test_parse_user_agent_bots_and_apps() {
+        let googlebot =
+            parse("Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)");
+        assert_eq!(
+            (
+                googlebot.browser.as_str(),
+                googlebot.browser_version.as_deref(),
+                googlebot.device_type
+            ),
+            ("Googlebot", Some("2.1"), DeviceType::Bot)
+        );
+        let bingbot = parse(concat!(
+            "Mozilla/5.0 AppleWebKit/537.36 (KHTML, like Gecko; compatible; bingbot/2.0; ",
+            "+http://www.bing.com/bingbot.htm) Chrome/116.0.1938.76 Safari/537.36"
+        ));
+        assert_eq!((bingbot.browser.as_str(), bingbot.device_type), ("bingbot", DeviceType::Bot));
+
+        let app = parse("MyApp/3.2.1 (iPhone; iOS 17.0; Scale/3.00)");
+        assert_eq!(
+            app,
+            ClientInfo {
+                browser: "MyApp".to_string(),
+                browser_version: Some("3.2.1".to_string()),
+                os: "iOS".to_string(),
+                os_version: Some("17.0".to_string()),
+                device_family: "iPhone".to_string(),
+                device_type: DeviceType::Mobile,
+            }
+        );
+        let dalvik = parse("Dalvik/2.1.0 (Linux; U; Android 13; ja-jp; SO-51C Build/64.1.C.0.123)");
+        assert_eq!((dalvik.browser.as_str(), dalvik.device_family.as_str()), ("Dalvik", "SO-51C"));
+        assert_eq!(summary("okhttp/4.12.0"), expected("okhttp", "4.12.0", "Other", None));
+    }
+
+    #[test]
+    fn test_parse_user_agent_unknown() {
+        for ua in ["", "   ", "Mozilla/5.0", "(unknown)"] {
+            let info = parse(ua);
+            assert_eq!(info.browser, "Other", "{:?}", ua);
+            assert_eq!(info.os, "Other", "{:?}", ua);
+            assert_eq!(info.device_type, DeviceType::Unknown, "{:?}", ua);
+        }
+        assert_eq!(summary("curl"), ("curl".to_string(), None, "Other".to_string(), None));
+    }
+}
@@ -0,0 +1,856 @@
+//! 暗号化コンテナファイルモジュール
+//!
+//! このモジュールは、パスワードで保護されたキー・バリュー形式のファイルを扱う
+//! `EncryptedVault`をエクスポートします。鍵はArgon2idでパスワードから導出し、
+//! 各エントリはAES-256-GCMで個別に暗号化されたチャンクとして追記されます。
+//! オフラインで保持する機密文書の保存を想定しています。
+//!
+//! # ファイル形式
+//! ```text
+//! ヘッダー: "MVLT" | バージョン(1) | memory_kib(u32) | iterations(u32) | parallelism(u32) | ソルト(16)
+//! レコード: 長さ(u32) | ノンス(12) | 暗号文（タグを含む）
+//! ```
+//! 先頭のレコードはパスワード確認用で、以降のレコードは追加・削除の操作を
+//! 時系列順に記録します。数値はすべてビッグエンディアンです。
+//! ヘッダー全体とレコードの通し番号（確認用レコードを0とするu64）を各レコードの
+//! 追加認証データとして使用するため、パラメータやソルトの改ざん、レコードの並べ替えや
+//! 古いレコードの再挿入は復号の失敗として検出されます。
+//! ただし、レコードの境界でファイルを切り詰めて以前の状態に戻す操作は、
+//! ファイル単体では検出できません。
+
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use aes_gcm::aead::{Aead, Payload};
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand_core::{OsRng, RngCore};
+use thiserror::Error;
+
+use crate::password::Argon2Params;
+
+/// ファイル先頭のマジックナンバー
+const MAGIC: &[u8; 4] = b"MVLT";
+
+/// ファイル形式のバージョン
+const FORMAT_VERSION: u8 = 2;
+
+/// ヘッダーの長さ（マジック4 + バージョン1 + パラメータ12 + ソルト16）
+const HEADER_LEN: usize = 33;
+
+/// ソルトの長さ
+const SALT_LEN: usize = 16;
+
+/// AES-GCMのノンスの長さ
+const NONCE_LEN: usize = 12;
+
+/// 1レコードの最大長（暗号文、64 MiB）
+const MAX_RECORD_LEN: u32 = 64 * 1024 * 1024;
+
+/// パスワード確認用レコードの平文
+const CHECK_PLAINTEXT: &[u8] = b"mobile-vault-check";
+
+/// レコードの操作種別
+const OP_PUT: u8 = 1;
+const OP_DELETE: u8 = 2;
+
+/// 暗号化コンテナの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum VaultError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// ファイルの読み書きに失敗した場合
+    #[error("Failed to access vault file: {0}")]
+    IoError(String),
+    /// 作成しようとしたファイルが既に存在する場合
+    #[error("Vault file already exists")]
+    AlreadyExists,
+    /// 開こうとしたファイルが存在しない場合
+    #[error("Vault file not found")]
+    NotFound,
+    /// パスワードが誤っている場合
+    #[error("Invalid password")]
+    InvalidPassword,
+    /// ファイルの内容が不正、または改ざんされている場合
+    #[error("Vault file is corrupted: {0}")]
+    CorruptedFile(String),
+    /// 鍵導出パラメータが不正な場合
+    #[error("Invalid key derivation parameters: {0}")]
+    InvalidParams(String),
+    /// 値が1レコードの上限を超える場合
+    #[error("Value is too large: {0} bytes")]
+    ValueTooLarge(u64),
+}
+
+impl From<std::io::Error> for VaultError {
+    fn from(error: std::io::Error) -> Self {
+        VaultError::IoError(error.to_string())
+    }
+}
+
+/// キーから最新の追加レコードの(ファイル内オフセット, 通し番号)への対応
+type RecordIndex = HashMap<String, (u64, u64)>;
+
+/// 開いているファイルとエントリの索引
+struct VaultState {
+    file: File,
+    /// キーから最新の追加レコードへの対応
+    index: RecordIndex,
+    /// 次に追記するレコードの通し番号
+    next_sequence: u64,
+    /// 上書き・削除によって不要になったレコード数
+    stale_records: u64,
+}
+
+/// パスワードで保護された暗号化キー・バリューファイル
+///
+/// 値はメモリ上に保持せず、`get`のたびにファイルから読み出して復号します。
+/// 上書きや削除は追記で記録されるため、不要になったレコードは
+/// `compact`でファイルを書き直して取り除きます。
+/// 複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let vault = EncryptedVault::create(path, "passphrase".to_string(), None)?;
+/// vault.put("passport".to_string(), scan_bytes)?;
+/// let reopened = EncryptedVault::open(path, "passphrase".to_string())?;
+/// let scan = reopened.get("passport".to_string())?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct EncryptedVault {
+    path: PathBuf,
+    header: [u8; HEADER_LEN],
+    cipher: Aes256Gcm,
+    state: Mutex<VaultState>,
+}
+
+/// ヘッダーを組み立てます
+fn encode_header(params: &Argon2Params, salt: &[u8; SALT_LEN]) -> [u8; HEADER_LEN] {
+    let mut header = [0u8; HEADER_LEN];
+    header[..4].copy_from_slice(MAGIC);
+    header[4] = FORMAT_VERSION;
+    header[5..9].copy_from_slice(&params.memory_kib.to_be_bytes());
+    header[9..13].copy_from_slice(&params.iterations.to_be_bytes());
+    header[13..17].copy_from_slice(&params.parallelism.to_be_bytes());
+    header[17..].copy_from_slice(salt);
+    header
+}
+
+/// ヘッダーからパラメータとソルトを読み取ります
+fn decode_header(
+    header: &[u8; HEADER_LEN],
+) -> Result<(Argon2Params, [u8; SALT_LEN]), VaultError> {
+    if &header[..4] != MAGIC {
+        return Err(VaultError::CorruptedFile("not a vault file".to_string()));
+    }
+    if header[4] != FORMAT_VERSION {
+        return Err(VaultError::CorruptedFile(format!(
+            "unsupported format version: {}",
+            header[4]
+        )));
+    }
+    let read_u32 = |offset: usize| {
+        u32::from_be_bytes([
+            header[offset],
+            header[offset + 1],
+            header[offset + 2],
+            header[offset + 3],
+        ])
+    };
+    let params = Argon2Params {
+        memory_kib: read_u32(5),
+        iterations: read_u32(9),
+        parallelism: read_u32(13),
+    };
+    if !params.is_within_file_limits() {
+        return Err(VaultError::CorruptedFile(format!(
+            "key derivation parameters exceed the limit: {:?}",
+            params
+        )));
+    }
+    let mut salt = [0u8; SALT_LEN];
+    salt.copy_from_slice(&header[17..]);
+    Ok((params, salt))
+}
+
+/// パスワードからAES-256-GCMの鍵を導出します
+fn derive_cipher(
+    password: &str,
+    params: &Argon2Params,
+    salt: &[u8],
+) -> Result<Aes256Gcm, VaultError> {
+    let params = Params::new(params.memory_kib, params.iterations, params.parallelism, Some(32))
+        .map_err(|e| VaultError::InvalidParams(e.to_string()))?;
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| VaultError::InvalidParams(e.to_string()))?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+/// 追加・削除操作を平文のレコードに符号化します
+fn encode_entry(op: u8, key: &str, value: &[u8]) -> Vec<u8> {
+    let mut entry = Vec::with_capacity(5 + key.len() + value.len());
+    entry.push(op);
+    entry.extend_from_slice(&(key.len() as u32).to_be_bytes());
+    entry.extend_from_slice(key.as_bytes());
+    entry.extend_from_slice(value);
+    entry
+}
+
+/// 平文のレコードを(操作, キー, 値)に分解します
+fn decode_entry(entry: &[u8]) -> Result<(u8, String, &[u8]), VaultError> {
+    let corrupted = || VaultError::CorruptedFile("malformed entry".to_string());
+    let (&op, rest) = entry.split_first().ok_or_else(corrupted)?;
+    let key_len = rest
+        .get(..4)
+        .map(|b| u32::from_be_bytes([b[0], b[1], b[2], b[3]]) as usize)
+        .ok_or_else(corrupted)?;
+    let key = rest.get(4..4 + key_len).ok_or_else(corrupted)?;
+    let key = String::from_utf8(key.to_vec()).map_err(|_| corrupted())?;
+    Ok((op, key, &rest[4 + key_len..]))
+}
+
+impl EncryptedVault {
+    /// レコードの追加認証データ（ヘッダー + 通し番号）を組み立てます
+    fn record_aad(&self, sequence: u64) -> Vec<u8> {
+        let mut aad = self.header.to_vec();
+        aad.extend_from_slice(&sequence.to_be_bytes());
+        aad
+    }
+
+    /// 平文を通し番号`sequence`のレコードとして暗号化して書き込み、開始オフセットを返します
+    fn write_record(
+        &self,
+        file: &mut File,
+        sequence: u64,
+        plaintext: &[u8],
+    ) -> Result<u64, VaultError> {
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let aad = self.record_aad(sequence);
+        let payload = Payload {
+            msg: plaintext,
+            aad: &aad,
+        };
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), payload)
+            .map_err(|_| VaultError::IoError("encryption failed".to_string()))?;
+        if ciphertext.len() > MAX_RECORD_LEN as usize {
+            return Err(VaultError::ValueTooLarge(plaintext.len() as u64));
+        }
+
+        let offset = file.seek(SeekFrom::End(0))?;
+        let mut record = Vec::with_capacity(4 + NONCE_LEN + ciphertext.len());
+        record.extend_from_slice(&(ciphertext.len() as u32).to_be_bytes());
+        record.extend_from_slice(&nonce);
+        record.extend_from_slice(&ciphertext);
+        if let Err(e) = file.write_all(&record).and_then(|()| file.sync_data()) {
+            // 途中まで書かれたレコードを残さない
+            let _ = file.set_len(offset);
+            return Err(e.into());
+        }
+        Ok(offset)
+    }
+
+    /// 指定オフセットにある通し番号`sequence`のレコードを読み出して復号します
+    /// （ファイル末尾では`None`）
+    fn read_record(
+        &self,
+        file: &mut File,
+        offset: u64,
+        sequence: u64,
+    ) -> Result<Option<Vec<u8>>, VaultError> {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut len = [0u8; 4];
+        match file.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e.into()),
+        }
+        let len = u32::from_be_bytes(len);
+        if len > MAX_RECORD_LEN {
+            return Err(VaultError::CorruptedFile(format!("record too large at {}", offset)));
+        }
+        let mut record = vec![0u8; NONCE_LEN + len as usize];
+        file.read_exact(&mut record)
+            .map_err(|_| VaultError::CorruptedFile(format!("truncated record at {}", offset)))?;
+        let (nonce, ciphertext) = record.split_at(NONCE_LEN);
+        let aad = self.record_aad(sequence);
+        let payload = Payload {
+            msg: ciphertext,
+            aad: &aad,
+        };
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), payload)
+            .map(Some)
+            .map_err(|_| VaultError::CorruptedFile(format!("authentication failed at {}", offset)))
+    }
+
+    /// レコード全体の長さ（長さフィールド・ノンスを含む）を返します
+    fn record_len(file: &mut File, offset: u64) -> Result<u64, VaultError> {
+        file.seek(SeekFrom::Start(offset))?;
+        let mut len = [0u8; 4];
+        file.read_exact(&mut len)?;
+        Ok(4 + NONCE_LEN as u64 + u64::from(u32::from_be_bytes(len)))
+    }
+
+    /// 指定オフセットのレコードがファイルの末尾で途切れているかどうかを返します
+    fn is_torn(file: &mut File, offset: u64, file_len: u64) -> Result<bool, VaultError> {
+        if file_len - offset < 4 {
+            return Ok(true);
+        }
+        file.seek(SeekFrom::Start(offset))?;
+        let mut len = [0u8; 4];
+        file.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len);
+        // 上限を超える長さは途切れではなく破損として`read_record`で扱う
+        Ok(len <= MAX_RECORD_LEN && offset + 4 + NONCE_LEN as u64 + u64::from(len) > file_len)
+    }
+
+    /// ファイルを先頭から読み、パスワードを確認して索引を構築します
+    fn load_index(&self, state: &mut VaultState) -> Result<(), VaultError> {
+        let file = &mut state.file;
+        let check_offset = HEADER_LEN as u64;
+        match self.read_record(file, check_offset, 0) {
+            Ok(Some(check)) if check == CHECK_PLAINTEXT => {}
+            Ok(_) => return Err(VaultError::CorruptedFile("missing check record".to_string())),
+            Err(VaultError::CorruptedFile(_)) => return Err(VaultError::InvalidPassword),
+            Err(e) => return Err(e),
+        }
+
+        let mut index = HashMap::new();
+        let mut stale_records = 0;
+        let file_len = file.metadata()?.len();
+        let mut offset = check_offset + Self::record_len(file, check_offset)?;
+        let mut sequence = 1;
+        loop {
+            // 追記中のクラッシュで途中までしか書かれていない末尾のレコードは、
+            // ログの終わりとみなして切り詰める
+            if offset < file_len && Self::is_torn(file, offset, file_len)? {
+                file.set_len(offset)?;
+                file.sync_all()?;
+                break;
+            }
+            // 並べ替えや再挿入されたレコードは通し番号が一致せず、認証に失敗する
+            let Some(plaintext) = self.read_record(file, offset, sequence)? else {
+                break;
+            };
+            let (op, key, _) = decode_entry(&plaintext)?;
+            let replaced = match op {
+                OP_PUT => index.insert(key, (offset, sequence)).is_some(),
+                OP_DELETE => {
+                    // 削除レコード自体も不要なレコードとして数える
+                    stale_records += 1;
+                    index.remove(&key).is_some()
+                }
+                _ => return Err(VaultError::CorruptedFile(format!("unknown operation: {}", op))),
+            };
+            if replaced {
+                stale_records += 1;
+            }
+            offset += Self::record_len(file, offset)?;
+            sequence += 1;
+        }
+        state.index = index;
+        state.stale_records = stale_records;
+        state.next_sequence = sequence;
+        Ok(())
+    }
+
+    /// ヘッダーとパスワード確認用レコードを新しいファイルに書き込みます
+    fn write_preamble(&self, file: &mut File) -> Result<(), VaultError> {
+        file.write_all(&self.header)?;
+        self.write_record(file, 0, CHECK_PLAINTEXT)?;
+        Ok(())
+    }
+
+    /// 有効なエントリを新しいファイルに書き出し、新しい索引と次の通し番号を返します
+    fn write_compacted(
+        &self,
+        state: &mut VaultState,
+        temp: &mut File,
+    ) -> Result<(RecordIndex, u64), VaultError> {
+        self.write_preamble(temp)?;
+        let mut index = HashMap::with_capacity(state.index.len());
+        let mut next_sequence = 1;
+        for (key, &(offset, sequence)) in &state.index {
+            let plaintext =
+                self.read_record(&mut state.file, offset, sequence)?.ok_or_else(|| {
+                    VaultError::CorruptedFile(format!("missing record at {}", offset))
+                })?;
+            let new_offset = self.write_record(temp, next_sequence, &plaintext)?;
+            index.insert(key.clone(), (new_offset, next_sequence));
+            next_sequence += 1;
+        }
+        Ok((index, next_sequence))
+    }
+
+    /// 読み書き用にファイルを開きます
+    fn open_file(path: &Path) -> Result<File, VaultError> {
+        OpenOptions::new().read(true).write(true).open(path).map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                VaultError::NotFound
+            } else {
+                e.into()
+            }
+        })
+    }
+}
+
+#[uniffi::export]
+impl EncryptedVault {
+    /// 新しい暗号化コンテナファイルを作成します
+    ///
+    /// ソルトはOSの暗号論的乱数生成器から生成され、鍵導出パラメータと共に
+    /// ヘッダーへ保存されます。
+    ///
+    /// # Arguments
+    /// * `path` - 作成するファイルのパス
+    /// * `password` - コンテナを保護するパスワード
+    /// * `params` - Argon2idのコストパラメータ（`None`の場合は既定値）
+    ///
+    /// # Errors
+    /// * `VaultError::AlreadyExists` - ファイルが既に存在する場合
+    /// * `VaultError::InvalidParams` - パラメータが許容範囲外、または既定値の4倍を超える場合
+    /// * `VaultError::IoError` - ファイルの書き込みに失敗した場合
+    #[uniffi::constructor(default(params = None))]
+    pub fn create(
+        path: String,
+        password: String,
+        params: Option<Argon2Params>,
+    ) -> Result<Arc<Self>, VaultError> {
+        let path = PathBuf::from(path);
+        let params = params.unwrap_or_default();
+        if !params.is_within_file_limits() {
+            return Err(VaultError::InvalidParams(
+                "parameters must not exceed 4 times the defaults".to_string(),
+            ));
+        }
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let cipher = derive_cipher(&password, &params, &salt)?;
+        let header = encode_header(&params, &salt);
+
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::AlreadyExists {
+                    VaultError::AlreadyExists
+                } else {
+                    e.into()
+                }
+            })?;
+        let vault = Self {
+            path,
+            header,
+            cipher,
+            state: Mutex::new(VaultState {
+                file: file.try_clone()?,
+                index: HashMap::new(),
+                next_sequence: 1,
+                stale_records: 0,
+            }),
+        };
+        if let Err(e) = vault.write_preamble(&mut file) {
+            let _ = fs::remove_file(&vault.path);
+            return Err(e);
+        }
+        Ok(Arc::new(vault))
+    }
+
+    /// 既存の暗号化コンテナファイルを開きます
+    ///
+    /// 追記中のクラッシュで途中までしか書かれていない末尾のレコードは切り詰めます
+    /// （その操作は記録されなかったものとして扱います）。
+    ///
+    /// # Arguments
+    /// * `path` - コンテナファイルのパス
+    /// * `password` - 作成時に指定したパスワード
+    ///
+    /// # Errors
+    /// * `VaultError::NotFound` - ファイルが存在しない場合
+    /// * `VaultError::InvalidPassword` - パスワードが誤っている場合
+    /// * `VaultError::CorruptedFile` - ファイルの内容が不正な場合（レコードの並べ替えや
+    ///   再挿入を含む）、または鍵導出パラメータが既定値の4倍を超える場合
+    /// * `VaultError::IoError` - ファイルの読み込みに失敗した場合
+    #[uniffi::constructor]
+    pub fn open(path: String, password: String) -> Result<Arc<Self>, VaultError> {
+        let path = PathBuf::from(path);
+        let mut file = Self::open_file(&path)?;
+        let mut header = [0u8; HEADER_LEN];
+        file.read_exact(&mut header)
+            .map_err(|_| VaultError::CorruptedFile("truncated header".to_string()))?;
+        let (params, salt) = decode_header(&header)?;
+        let cipher = derive_cipher(&password, &params, &salt)?;
+
+        let vault = Self {
+            path,
+            header,
+            cipher,
+            state: Mutex::new(VaultState {
+                file,
+                index: HashMap::new(),
+                next_sequence: 1,
+                stale_records: 0,
+            }),
+        };
+        {
+            let mut state = vault.state.lock()
+                .map_err(|_| VaultError::MutexPoisoned)?;
+            vault.load_index(&mut state)?;
+        }
+        Ok(Arc::new(vault))
+    }
+
+    /// 値を保存します（同じキーの値は上書きされます）
+    ///
+    /// # Arguments
+    /// * `key` - エントリのキー
+    /// * `value` - 保存するデータ（最大64 MiB）
+    ///
+    /// # Errors
+    /// * `VaultError::ValueTooLarge` - 値が上限を超える場合
+    /// * `VaultError::IoError` - ファイルへの書き込みに失敗した場合
+    /// * `VaultError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn put(&self, key: String, value: Vec<u8>) -> Result<(), VaultError> {
+        let mut state = self.state.lock()
+            .map_err(|_| VaultError::MutexPoisoned)?;
+        let state = &mut *state;
+        let sequence = state.next_sequence;
+        let entry = encode_entry(OP_PUT, &key, &value);
+        let offset = self.write_record(&mut state.file, sequence, &entry)?;
+        state.next_sequence += 1;
+        if state.index.insert(key, (offset, sequence)).is_some() {
+            state.stale_records += 1;
+        }
+        Ok(())
+    }
+
+    /// 値を取得します（存在しない場合は`None`）
+    ///
+    /// # Errors
+    /// * `VaultError::CorruptedFile` - レコードの復号に失敗した場合
+    /// * `VaultError::IoError` - ファイルの読み込みに失敗した場合
+    /// * `VaultError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn get(&self, key: String) -> Result<Option<Vec<u8>>, VaultError> {
+        let mut state = self.state.lock()
+            .map_err(|_| VaultError::MutexPoisoned)?;
+        let Some(&(offset, sequence)) = state.index.get(&key) else {
+            return Ok(None);
+        };
+        let plaintext = self
+            .read_record(&mut state.file, offset, sequence)?
+            .ok_or_else(|| VaultError::CorruptedFile(format!("missing record at {}", offset)))?;
+        let (_, _, value) = decode_entry(&plaintext)?;
+        Ok(Some(value.to_vec()))
+    }
+
+    /// 値を削除します
+    ///
+    /// # Returns
+    /// * `true` - エントリが存在し削除された場合
+    /// * `false` - エントリが存在しなかった場合
+    ///
+    /// # Errors
+    /// * `VaultError::IoError` - ファイルへの書き込みに失敗した場合
+    /// * `VaultError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn delete(&self, key: String) -> Result<bool, VaultError> {
+        let mut state = self.state.lock()
+            .map_err(|_| VaultError::MutexPoisoned)?;
+        let state = &mut *state;
+        if !state.index.contains_key(&key) {
+            return Ok(false);
+        }
+        let sequence = state.next_sequence;
+        self.write_record(&mut state.file, sequence, &encode_entry(OP_DELETE, &key, &[]))?;
+        state.next_sequence += 1;
+        state.index.remove(&key);
+        state.stale_records += 2;
+        Ok(true)
+    }
+
+    /// 保存されているキーの一覧を返します（順序は不定）
+    ///
+    /// # Errors
+    /// * `VaultError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn keys(&self) -> Result<Vec<String>, VaultError> {
+        let state = self.state.lock()
+            .map_err(|_| VaultError::MutexPoisoned)?;
+        Ok(state.index.keys().cloned().collect())
+    }
+
+    /// 上書き・削除によって不要になったレコード数を返します
+    ///
+    /// # Errors
+    /// * `VaultError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn stale_records(&self) -> Result<u64, VaultError> {
+        let state = self.state.lock()
+            .map_err(|_| VaultError::MutexPoisoned)?;
+        Ok(state.stale_records)
+    }
+
+    /// 不要になったレコードを取り除き、ファイルを書き直します
+    ///
+    /// 有効なエントリを新しいノンスで一時ファイルへ書き出した後、
+    /// 元のファイルと置き換えます。途中で失敗した場合、元のファイルは変更されません。
+    ///
+    /// # Errors
+    /// * `VaultError::CorruptedFile` - レコードの復号に失敗した場合
+    /// * `VaultError::IoError` - ファイルの読み書きに失敗した場合
+    /// * `VaultError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn compact(&self) -> Result<(), VaultError> {
+        let mut state = self.state.lock()
+            .map_err(|_| VaultError::MutexPoisoned)?;
+        let state = &mut *state;
+
+        let mut temp_name = self.path.as_os_str().to_owned();
+        temp_name.push(".compact");
+        let temp_path = PathBuf::from(temp_name);
+        let mut temp = File::create(&temp_path)?;
+        let result = self.write_compacted(state, &mut temp).and_then(|index| {
+            fs::rename(&temp_path, &self.path)?;
+            Ok(index)
+        });
+        let (index, next_sequence) = match result {
+            Ok(compacted) => compacted,
+            Err(e) => {
+                let _ = fs::remove_file(&temp_path);
+                return Err(e);
+            }
+        };
+
+        state.file = Self::open_file(&self.path)?;
+        state.index = index;
+        state.next_sequence = next_sequence;
+        state.stale_records = 0;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// テストを高速化するための軽量パラメータ
+    fn fast_params() -> Option<Argon2Params> {
+        Some(Argon2Params {
+            memory_kib: 1024,
+            iterations: 1,
+            parallelism: 1,
+        })
+    }
+
+    fn temp_path(name: &str) -> String {
+        let path = std::env::temp_dir()
+            .join(format!("mobile_vault_{}_{}.bin", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_vault_put_get_delete() {
+        let path = temp_path("basic");
+        let vault = EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+        vault.put("a".to_string(), b"alpha".to_vec()).unwrap();
+        vault.put("b".to_string(), Vec::new()).unwrap();
+        assert_eq!(vault.get("a".to_string()).unwrap(), Some(b"alpha".to_vec()));
+        assert_eq!(vault.get("b".to_string()).unwrap(), Some(Vec::new()));
+        assert_eq!(vault.get("c".to_string()).unwrap(), None);
+
+        assert!(vault.delete("a".to_string()).unwrap());
+        assert!(!vault.delete("a".to_string()).unwrap());
+        assert_eq!(vault.get("a".to_string()).unwrap(), None);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_reopen() {
+        let path = temp_path("reopen");
+        {
+            let vault =
+                EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+            vault.put("doc".to_string(), b"v1".to_vec()).unwrap();
+            vault.put("doc".to_string(), b"v2".to_vec()).unwrap();
+            vault.put("gone".to_string(), b"x".to_vec()).unwrap();
+            vault.delete("gone".to_string()).unwrap();
+        }
+        let vault = EncryptedVault::open(path.clone(), "pw".to_string()).unwrap();
+        assert_eq!(vault.get("doc".to_string()).unwrap(), Some(b"v2".to_vec()));
+        assert_eq!(vault.keys().unwrap(), vec!["doc".to_string()]);
+        assert_eq!(vault.stale_records().unwrap(), 3);
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_does_not_store_plaintext() {
+        let path = temp_path("plaintext");
+        let vault = EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+        vault.put("secret-key".to_string(), b"top secret value".to_vec()).unwrap();
+        let raw = fs::read(&path).unwrap();
+        assert!(!raw.windows(10).any(|w| w == b"secret-key"));
+        assert!(!raw.windows(10).any(|w| w == b"top secret"));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_compact() {
+        let path = temp_path("compact");
+        let vault = EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+        for i in 0..10u8 {
+            vault.put("k".to_string(), vec![i; 1024]).unwrap();
+        }
+        vault.put("other".to_string(), b"o".to_vec()).unwrap();
+        let before = fs::metadata(&path).unwrap().len();
+        vault.compact().unwrap();
+        let after = fs::metadata(&path).unwrap().len();
+        assert!(after < before);
+        assert_eq!(vault.stale_records().unwrap(), 0);
+        assert_eq!(vault.get("k".to_string()).unwrap(), Some(vec![9u8; 1024]));
+
+        vault.put("new".to_string(), b"n".to_vec()).unwrap();
+        let reopened = EncryptedVault::open(path.clone(), "pw".to_string()).unwrap();
+        assert_eq!(reopened.get("other".to_string()).unwrap(), Some(b"o".to_vec()));
+        assert_eq!(reopened.get("new".to_string()).unwrap(), Some(b"n".to_vec()));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_wrong_password() {
+        let path = temp_path("wrong");
+        EncryptedVault::create(path.clone(), "right".to_string(), fast_params()).unwrap();
+        match EncryptedVault::open(path.clone(), "wrong".to_string()) {
+            Err(VaultError::InvalidPassword) => (),
+            _ => panic!("Expected InvalidPassword error"),
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_create_and_open_errors() {
+        let path = temp_path("errors");
+        match EncryptedVault::open(path.clone(), "pw".to_string()) {
+            Err(VaultError::NotFound) => (),
+            _ => panic!("Expected NotFound error"),
+        }
+        EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+        match EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()) {
+            Err(VaultError::AlreadyExists) => (),
+            _ => panic!("Expected AlreadyExists error"),
+        }
+        fs::write(&path, b"not a vault file at all, just some bytes").unwrap();
+        match EncryptedVault::open(path.clone(), "pw".to_string()) {
+            Err(VaultError::CorruptedFile(_)) => (),
+            _ => panic!("Expected CorruptedFile error"),
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_detects_tampering() {
+        let path = temp_path("tamper");
+        {
+            let vault =
+                EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+            vault.put("k".to_string(), b"value".to_vec()).unwrap();
+        }
+        let mut raw = fs::read(&path).unwrap();
+        let last = raw.len() - 1;
+        raw[last] ^= 0x01;
+        fs::write(&path, raw).unwrap();
+        match EncryptedVault::open(path.clone(), "pw".to_string()) {
+            Err(VaultError::CorruptedFile(_)) => (),
+            _ => panic!("Expected CorruptedFile error"),
+        }
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_detects_reordered_and_replayed_records() {
+        let path = temp_path("replay");
+        let vault = EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+        let start = fs::metadata(&path).unwrap().len() as usize;
+        vault.put("k".to_string(), b"old".to_vec()).unwrap();
+        let middle = fs::metadata(&path).unwrap().len() as usize;
+        vault.put("k".to_string(), b"new".to_vec()).unwrap();
+        drop(vault);
+        let original = fs::read(&path).unwrap();
+        let (first, second) = (&original[start..middle], &original[middle..]);
+        assert_eq!(first.len(), second.len());
+
+        // 古い`put`を末尾に再挿入する
+        let mut replayed = original.clone();
+        replayed.extend_from_slice(first);
+        // 2つのレコードを入れ替える
+        let mut reordered = original[..start].to_vec();
+        reordered.extend_from_slice(second);
+        reordered.extend_from_slice(first);
+        for raw in [replayed, reordered] {
+            fs::write(&path, raw).unwrap();
+            match EncryptedVault::open(path.clone(), "pw".to_string()) {
+                Err(VaultError::CorruptedFile(_)) => (),
+                _ => panic!("Expected CorruptedFile error"),
+            }
+        }
+        fs::write(&path, original).unwrap();
+        let vault = EncryptedVault::open(path.clone(), "pw".to_string()).unwrap();
+        assert_eq!(vault.get("k".to_string()).unwrap(), Some(b"new".to_vec()));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_vault_recovers_from_torn_record() {
+        // 追記の途中でクラッシュした状態（長さフィールドの途中・本体の途中）を再現する
+        for torn_bytes in [2, 40] {
+            let path = temp_path("torn");
+            let vault =
+                EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+            vault.put("kept".to_string(), b"v1".to_vec()).unwrap();
+            let complete_len = fs::metadata(&path).unwrap().len();
+            vault.put("torn".to_string(), vec![7u8; 100]).unwrap();
+            drop(vault);
+            let file = OpenOptions::new().write(true).open(&path).unwrap();
+            file.set_len(complete_len + torn_bytes).unwrap();
+            drop(file);
+
+            let vault = EncryptedVault::open(path.clone(), "pw".to_string()).unwrap();
+            assert_eq!(vault.get("kept".to_string()).unwrap(), Some(b"v1".to_vec()));
+            assert_eq!(vault.get("torn".to_string()).unwrap(), None);
+            assert_eq!(fs::metadata(&path).unwrap().len(), complete_len);
+            vault.put("next".to_string(), b"n".to_vec()).unwrap();
+            drop(vault);
+            let reopened = EncryptedVault::open(path.clone(), "pw".to_string()).unwrap();
+            assert_eq!(reopened.get("next".to_string()).unwrap(), Some(b"n".to_vec()));
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    #[test]
+    fn test_vault_rejects_excessive_params() {
+        let path = temp_path("params");
+        let excessive = Argon2Params { memory_kib: 19456 * 4 + 1, iterations: 1, parallelism: 1 };
+        match EncryptedVault::create(path.clone(), "pw".to_string(), Some(excessive)) {
+            Err(VaultError::InvalidParams(_)) => (),
+            _ => panic!("Expected InvalidParams error"),
+        }
+        EncryptedVault::create(path.clone(), "pw".to_string(), fast_params()).unwrap();
+        // ヘッダーのmemory_kibをu32::MAXに書き換えても、鍵導出の前にエラーになる
+        let mut raw = fs::read(&path).unwrap();
+        raw[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+        fs::write(&path, raw).unwrap();
+        match EncryptedVault::open(path.clone(), "pw".to_string()) {
+            Err(VaultError::CorruptedFile(_)) => (),
+            _ => panic!("Expected CorruptedFile error"),
+        }
+        let _ = fs::remove_file(path);
+    }
+}
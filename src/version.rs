@@ -0,0 +1,236 @@
+//! セマンティックバージョニングモジュール
+//!
+//! このモジュールは、Semantic Versioning 2.0.0のバージョン文字列を解析する`parse_semver`、
+//! 優先順位を比較する`compare_versions`、バージョン要件に一致するかを判定する`satisfies`を
+//! エクスポートします。強制アップデートや機能の出し分けの判定に、リリースツールと
+//! 同じ規則を使うために使用します。
+
+use std::cmp::Ordering;
+
+use semver::{BuildMetadata, Version, VersionReq};
+use thiserror::Error;
+
+/// バージョンの処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum VersionError {
+    /// バージョン文字列の形式が正しくない場合
+    #[error("Invalid version: {0}")]
+    InvalidVersion(String),
+    /// バージョン要件の形式が正しくない場合
+    #[error("Invalid version requirement: {0}")]
+    InvalidRequirement(String),
+}
+
+/// 解析済みのセマンティックバージョン
+#[derive(Debug, Clone, PartialEq, Eq, uniffi::Record)]
+pub struct Semver {
+    /// メジャーバージョン
+    pub major: u64,
+    /// マイナーバージョン
+    pub minor: u64,
+    /// パッチバージョン
+    pub patch: u64,
+    /// プレリリース識別子（`1.0.0-beta.2`の`beta.2`）
+    pub pre_release: Option<String>,
+    /// ビルドメタデータ（`1.0.0+build.5`の`build.5`）
+    pub build: Option<String>,
+}
+
+/// バージョン文字列を解析します（先頭の`v`と前後の空白は無視します）
+fn parse_version(text: &str) -> Result<Version, VersionError> {
+    let trimmed = text.trim();
+    let trimmed = trimmed.strip_prefix(['v', 'V']).unwrap_or(trimmed);
+    Version::parse(trimmed).map_err(|e| VersionError::InvalidVersion(format!("{}: {}", text, e)))
+}
+
+/// バージョン文字列を解析します
+///
+/// `MAJOR.MINOR.PATCH`の3つの数値が必要です（`1.2`のような省略形はエラー）。
+/// 先頭の`v`（`v1.2.3`）と前後の空白は無視します。
+///
+/// # Arguments
+/// * `text` - バージョン文字列
+///
+/// # Errors
+/// * `VersionError::InvalidVersion` - 形式が正しくない場合
+///
+/// # Example
+/// ```
+/// let version = parse_semver("v2.1.0-beta.3+build.7".to_string())?;
+/// assert_eq!(version.major, 2);
+/// assert_eq!(version.pre_release, Some("beta.3".to_string()));
+/// ```
+#[uniffi::export]
+pub fn parse_semver(text: String) -> Result<Semver, VersionError> {
+    let version = parse_version(&text)?;
+    Ok(Semver {
+        major: version.major,
+        minor: version.minor,
+        patch: version.patch,
+        pre_release: (!version.pre.is_empty()).then(|| version.pre.to_string()),
+        build: (!version.build.is_empty()).then(|| version.build.to_string()),
+    })
+}
+
+/// 2つのバージョンの優先順位を比較します
+///
+/// Semantic Versioningの優先順位に従い、プレリリースは正式リリースより前になります
+/// （`1.0.0-rc.1` < `1.0.0`）。ビルドメタデータは比較に使用しません。
+///
+/// # Arguments
+/// * `a` - 比較するバージョン
+/// * `b` - 比較するバージョン
+///
+/// # Returns
+/// `a`が`b`より前なら`-1`、同じなら`0`、後なら`1`
+///
+/// # Errors
+/// * `VersionError::InvalidVersion` - どちらかの形式が正しくない場合
+///
+/// # Example
+/// ```
+/// assert_eq!(compare_versions("1.10.0".to_string(), "1.9.3".to_string())?, 1);
+/// ```
+#[uniffi::export]
+pub fn compare_versions(a: String, b: String) -> Result<i32, VersionError> {
+    let ordering = parse_version(&a)?.cmp_precedence(&parse_version(&b)?);
+    Ok(match ordering {
+        Ordering::Less => -1,
+        Ordering::Equal => 0,
+        Ordering::Greater => 1,
+    })
+}
+
+/// バージョンがバージョン要件に一致するかどうかを判定します
+///
+/// 要件はCargoと同じ構文（`^1.2`・`~1.2.3`・`>=1.0, <2.0`・`1.*`・`=1.2.3`）で指定し、
+/// `||`で区切った要件はいずれかに一致すれば一致とみなします。演算子のない`1.2.3`は`^1.2.3`と
+/// 同じです。プレリリースのバージョンは、同じ`MAJOR.MINOR.PATCH`のプレリリースを含む比較が
+/// ある場合にだけ一致します（`>=1.0.0-beta`は`1.0.0-rc.1`に一致し、`1.1.0-beta`には一致しない）。
+///
+/// # Arguments
+/// * `version` - 判定するバージョン
+/// * `requirement` - バージョン要件
+///
+/// # Errors
+/// * `VersionError::InvalidVersion` - バージョンの形式が正しくない場合
+/// * `VersionError::InvalidRequirement` - 要件の形式が正しくない場合
+///
+/// # Example
+/// ```
+/// // 最低サポートバージョン未満なら強制アップデート
+/// let supported = satisfies(app_version, ">=3.2.0".to_string())?;
+/// ```
+#[uniffi::export]
+pub fn satisfies(version: String, requirement: String) -> Result<bool, VersionError> {
+    let version = parse_version(&version)?;
+    let alternatives = requirement
+        .split("||")
+        .map(|alternative| {
+            VersionReq::parse(alternative.trim()).map_err(|e| {
+                VersionError::InvalidRequirement(format!("{}: {}", requirement.trim(), e))
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // ビルドメタデータは要件の判定に使用しない
+    let version = Version { build: BuildMetadata::EMPTY, ..version };
+    Ok(alternatives.iter().any(|alternative| alternative.matches(&version)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(a: &str, b: &str) -> i32 {
+        compare_versions(a.to_string(), b.to_string()).unwrap()
+    }
+
+    fn check(version: &str, requirement: &str) -> bool {
+        satisfies(version.to_string(), requirement.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_parse_semver() {
+        let version = parse_semver(" v2.1.0-beta.3+build.7 ".to_string()).unwrap();
+        assert_eq!(
+            version,
+            Semver {
+                major: 2,
+                minor: 1,
+                patch: 0,
+                pre_release: Some("beta.3".to_string()),
+                build: Some("build.7".to_string()),
+            }
+        );
+        let version = parse_semver("10.20.30".to_string()).unwrap();
+        assert_eq!((version.major, version.minor, version.patch), (10, 20, 30));
+        assert_eq!(version.pre_release, None);
+        assert_eq!(version.build, None);
+
+        for text in ["1.2", "1.2.3.4", "01.2.3", "1.2.3-", "", "version"] {
+            match parse_semver(text.to_string()) {
+                Err(VersionError::InvalidVersion(_)) => (),
+                other => panic!("Expected InvalidVersion error for {:?}, got {:?}", text, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare("1.10.0", "1.9.3"), 1);
+        assert_eq!(compare("1.0.0", "1.0.0+build.1"), 0);
+        assert_eq!(compare("v1.0.0", "1.0.0"), 0);
+        assert_eq!(compare("1.0.0-rc.1", "1.0.0"), -1);
+        // 仕様の例: 1.0.0-alpha < 1.0.0-alpha.1 < 1.0.0-alpha.beta < 1.0.0-beta
+        // < 1.0.0-beta.2 < 1.0.0-beta.11 < 1.0.0-rc.1 < 1.0.0
+        let ordered = [
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0.0",
+        ];
+        for pair in ordered.windows(2) {
+            assert_eq!(compare(pair[0], pair[1]), -1, "{} < {}", pair[0], pair[1]);
+            assert_eq!(compare(pair[1], pair[0]), 1, "{} > {}", pair[1], pair[0]);
+        }
+    }
+
+    #[test]
+    fn test_satisfies() {
+        assert!(check("1.4.2", "^1.2"));
+        assert!(!check("2.0.0", "^1.2"));
+        assert!(check("1.2.9", "~1.2.3"));
+        assert!(!check("1.3.0", "~1.2.3"));
+        assert!(check("1.5.0", ">=1.0, <2.0"));
+        assert!(check("3.2.0+build.9", ">=3.2.0"));
+        assert!(!check("3.1.9", ">=3.2.0"));
+        assert!(check("1.7.0", "1.*"));
+        assert!(check("1.2.3", "1.2.3"));
+        assert!(check("2.5.0", "^1.2 || ^2.4"));
+        assert!(!check("3.0.0", "^1.2 || ^2.4"));
+        assert!(check("1.0.0-rc.1", ">=1.0.0-beta"));
+        assert!(!check("1.1.0-beta", ">=1.0.0-beta"));
+        assert!(!check("1.0.0-rc.1", ">=0.9.0"));
+    }
+
+    #[test]
+    fn test_satisfies_errors() {
+        match satisfies("1.0".to_string(), ">=1.0".to_string()) {
+            Err(VersionError::InvalidVersion(_)) => (),
+            other => panic!("Expected InvalidVersion error, got {:?}", other),
+        }
+        for requirement in [">>1.0", "^1.2 ||", "1.0 - 2.0"] {
+            match satisfies("1.0.0".to_string(), requirement.to_string()) {
+                Err(VersionError::InvalidRequirement(_)) => (),
+                other => {
+                    panic!("Expected InvalidRequirement error for {}, got {:?}", requirement, other)
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,710 @@
+//! WebSocketクライアントモジュール
+//!
+//! このモジュールは、WebSocket（RFC 6455）で接続・送受信する`WebSocketClient`と、
+//! 受信したメッセージや接続状態の変化を受け取る`WebSocketListener`をエクスポートします。
+//! 接続はRust側のスレッドで維持され、切断された場合は`ReconnectOptions`に従って
+//! 間隔を空けながら自動的に再接続します。`ws://`と`wss://`（rustls）に対応しています。
+
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+use tungstenite::client::IntoClientRequest;
+use tungstenite::http::{HeaderName, HeaderValue, Request};
+use tungstenite::protocol::frame::coding::CloseCode;
+use tungstenite::protocol::CloseFrame;
+use tungstenite::stream::MaybeTlsStream;
+use tungstenite::{Message, WebSocket};
+
+/// 接続・ハンドシェイクのタイムアウト
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 受信を待つ間に送信キューを確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// クローズフレームを送信してからサーバーの応答を待つ時間
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 正常終了を表すクローズコード
+const NORMAL_CLOSURE: u16 = 1000;
+
+/// WebSocketの操作で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum WebSocketError {
+    /// Mutexがポイズン状態になった場合
+    #[error("Mutex was poisoned")]
+    MutexPoisoned,
+    /// URLが不正、または`ws`・`wss`以外のスキームの場合
+    #[error("Invalid WebSocket URL: {0}")]
+    InvalidUrl(String),
+    /// ヘッダーの名前または値が不正な場合
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    /// 既に接続中、または接続済みの場合
+    #[error("WebSocket is already connected")]
+    AlreadyConnected,
+    /// 接続が開いていない場合
+    #[error("WebSocket is not connected")]
+    NotConnected,
+    /// 再接続の設定が不正な場合
+    #[error("Invalid reconnect options: {0}")]
+    InvalidOptions(String),
+}
+
+/// 接続の状態
+#[derive(Debug, Clone, PartialEq, uniffi::Enum)]
+pub enum WebSocketState {
+    /// 接続・ハンドシェイク中
+    Connecting,
+    /// 接続済みで送受信できる状態
+    Open,
+    /// 切断されたため、待機した後に再接続する状態
+    Reconnecting {
+        /// 再接続の試行回数（1始まり）
+        attempt: u32,
+        /// 再接続までの待ち時間（ミリ秒）
+        delay_ms: u64,
+        /// 切断の理由
+        reason: String,
+    },
+    /// 切断され、再接続しない状態
+    Closed {
+        /// クローズフレームのコード（受信していない場合は`None`）
+        code: Option<u16>,
+        /// 切断の理由
+        reason: String,
+    },
+}
+
+/// 自動再接続の設定
+///
+/// 待ち時間は`initial_delay_ms`から始まり、再接続のたびに`multiplier`倍され、
+/// `max_delay_ms`で頭打ちになります。接続が開くと試行回数はリセットされます。
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct ReconnectOptions {
+    /// 連続して再接続を試みる最大回数（0の場合は再接続しない）
+    #[uniffi(default = 5)]
+    pub max_attempts: u32,
+    /// 最初の再接続までの待ち時間（ミリ秒）
+    #[uniffi(default = 500)]
+    pub initial_delay_ms: u64,
+    /// 再接続までの最大の待ち時間（ミリ秒）
+    #[uniffi(default = 30000)]
+    pub max_delay_ms: u64,
+    /// 待ち時間の倍率
+    #[uniffi(default = 2.0)]
+    pub multiplier: f64,
+}
+
+impl Default for ReconnectOptions {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_delay_ms: 500,
+            max_delay_ms: 30_000,
+            multiplier: 2.0,
+        }
+    }
+}
+
+impl ReconnectOptions {
+    /// `attempt`回目（1始まり）の再接続までの待ち時間
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let factor = self.multiplier.powi(attempt.saturating_sub(1).min(64) as i32);
+        let delay_ms = (self.initial_delay_ms as f64 * factor).min(self.max_delay_ms as f64);
+        Duration::from_millis(delay_ms as u64)
+    }
+}
+
+/// 受信したメッセージと接続状態の変化を受け取るリスナー
+///
+/// メソッドは接続を維持しているRust側のスレッドから呼ばれます。
+/// UIを更新する場合はメインスレッドに切り替えてください。
+#[uniffi::export(with_foreign)]
+pub trait WebSocketListener: Send + Sync {
+    /// 接続の状態が変化したときに呼ばれます
+    fn on_state_changed(&self, state: WebSocketState);
+
+    /// テキストメッセージを受信したときに呼ばれます
+    fn on_text_message(&self, text: String);
+
+    /// バイナリメッセージを受信したときに呼ばれます
+    fn on_binary_message(&self, data: Vec<u8>);
+
+    /// Pingを受信したときに呼ばれます（Pongは自動的に返信されます）
+    fn on_ping(&self, payload: Vec<u8>);
+}
+
+/// 接続スレッドへの指示
+enum Command {
+    Send(Message),
+    Close(u16, String),
+}
+
+/// 接続スレッドと共有する状態
+struct Shared {
+    listener: Arc<dyn WebSocketListener>,
+    reconnect: ReconnectOptions,
+    state: Mutex<WebSocketState>,
+    /// 現在の接続への送信キュー（接続が開いていない場合は`None`）
+    commands: Mutex<Option<Sender<Command>>>,
+    /// `close`が呼ばれたかどうか
+    closing: AtomicBool,
+}
+
+impl Shared {
+    /// 状態を更新し、リスナーに通知します
+    fn set_state(&self, state: WebSocketState) {
+        if let Ok(mut current) = self.state.lock() {
+            *current = state.clone();
+        }
+        self.listener.on_state_changed(state);
+    }
+
+    /// 送信キューを差し替えます
+    fn set_commands(&self, commands: Option<Sender<Command>>) {
+        if let Ok(mut current) = self.commands.lock() {
+            *current = commands;
+        }
+    }
+
+    /// 指定した時間だけ待ちます（`close`が呼ばれた場合は`false`を返して中断）
+    fn sleep_unless_closing(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            if self.closing.load(Ordering::SeqCst) {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL.min(deadline - Instant::now()));
+        }
+        !self.closing.load(Ordering::SeqCst)
+    }
+
+    /// 接続・再接続を繰り返し、再接続しない状態になったら終了します
+    fn run(&self, request: Request<()>) {
+        let mut attempt = 0u32;
+        loop {
+            let result = match connect(&request) {
+                Ok(socket) => {
+                    attempt = 0;
+                    self.serve(socket)
+                }
+                Err(reason) => Err(reason),
+            };
+            self.set_commands(None);
+            let (code, reason) = match result {
+                Ok(closed) => closed,
+                Err(reason) => (None, reason),
+            };
+            let closing = self.closing.load(Ordering::SeqCst);
+            attempt += 1;
+            if closing || code == Some(NORMAL_CLOSURE) || attempt > self.reconnect.max_attempts {
+                self.set_state(WebSocketState::Closed { code, reason });
+                return;
+            }
+            let delay = self.reconnect.delay(attempt);
+            self.set_state(WebSocketState::Reconnecting {
+                attempt,
+                delay_ms: delay.as_millis() as u64,
+                reason,
+            });
+            if !self.sleep_unless_closing(delay) {
+                self.set_state(WebSocketState::Closed { code, reason: "closed".to_string() });
+                return;
+            }
+            self.set_state(WebSocketState::Connecting);
+        }
+    }
+
+    /// 開いた接続で送受信し、切断されたら(クローズコード, 理由)を返します
+    ///
+    /// 通信エラーで切断された場合は`Err`で理由を返します。クローズフレームを送信してから
+    /// `CLOSE_TIMEOUT`以内にサーバーが応答しない場合は、待たずに接続を破棄します。
+    fn serve(
+        &self,
+        mut socket: WebSocket<MaybeTlsStream<TcpStream>>,
+    ) -> Result<(Option<u16>, String), String> {
+        let (sender, receiver): (Sender<Command>, Receiver<Command>) = mpsc::channel();
+        self.set_commands(Some(sender));
+        let mut close_deadline = None;
+        if self.closing.load(Ordering::SeqCst) {
+            let _ = socket.close(None);
+            close_deadline = Some(Instant::now() + CLOSE_TIMEOUT);
+        } else {
+            self.set_state(WebSocketState::Open);
+        }
+        let mut close_frame: Option<(u16, String)> = None;
+        loop {
+            if close_deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                return Ok((None, "close handshake timed out".to_string()));
+            }
+            while let Ok(command) = receiver.try_recv() {
+                let result = match command {
+                    Command::Send(message) => socket.write(message),
+                    Command::Close(code, reason) => {
+                        close_deadline.get_or_insert(Instant::now() + CLOSE_TIMEOUT);
+                        socket.close(Some(CloseFrame {
+                            code: CloseCode::from(code),
+                            reason: reason.into(),
+                        }))
+                    }
+                };
+                match result {
+                    Ok(()) => {}
+                    Err(tungstenite::Error::Io(e)) if is_timeout(e.kind()) => {}
+                    Err(e) => return Err(e.to_string()),
+                }
+            }
+            match socket.flush() {
+                Ok(()) => {}
+                Err(tungstenite::Error::Io(e)) if is_timeout(e.kind()) => {}
+                Err(tungstenite::Error::ConnectionClosed) => break,
+                Err(e) => return Err(e.to_string()),
+            }
+            match socket.read() {
+                Ok(Message::Text(text)) => self.listener.on_text_message(text.to_string()),
+                Ok(Message::Binary(data)) => self.listener.on_binary_message(data.to_vec()),
+                Ok(Message::Ping(payload)) => self.listener.on_ping(payload.to_vec()),
+                Ok(Message::Close(frame)) => {
+                    let frame = frame.map(|f| (u16::from(f.code), f.reason.to_string()));
+                    close_frame = close_frame.or(frame);
+                }
+                Ok(Message::Pong(_)) | Ok(Message::Frame(_)) => {}
+                Err(tungstenite::Error::Io(e)) if is_timeout(e.kind()) => {}
+                Err(tungstenite::Error::ConnectionClosed) => break,
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+        Ok(match close_frame {
+            Some((code, reason)) => (Some(code), reason),
+            None => (None, "connection closed".to_string()),
+        })
+    }
+}
+
+/// 読み込みのタイムアウトを表すエラーかを判定します
+fn is_timeout(kind: ErrorKind) -> bool {
+    matches!(kind, ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
+
+/// サーバーに接続してハンドシェイクを行います
+fn connect(request: &Request<()>) -> Result<WebSocket<MaybeTlsStream<TcpStream>>, String> {
+    let uri = request.uri();
+    let host = uri.host().ok_or_else(|| "URL has no host".to_string())?;
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = uri.port_u16().unwrap_or(if uri.scheme_str() == Some("wss") { 443 } else { 80 });
+    let mut last_error = format!("could not resolve {}", host);
+    let addrs = (host, port).to_socket_addrs().map_err(|e| e.to_string())?;
+    for addr in addrs {
+        let stream = match TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT) {
+            Ok(stream) => stream,
+            Err(e) => {
+                last_error = e.to_string();
+                continue;
+            }
+        };
+        let poll = stream.try_clone().map_err(|e| e.to_string())?;
+        poll.set_read_timeout(Some(CONNECT_TIMEOUT)).map_err(|e| e.to_string())?;
+        poll.set_write_timeout(Some(CONNECT_TIMEOUT)).map_err(|e| e.to_string())?;
+        let _ = poll.set_nodelay(true);
+        let (socket, _) =
+            tungstenite::client_tls(request.clone(), stream).map_err(|e| e.to_string())?;
+        // ハンドシェイク後は短いタイムアウトで受信を待ち、その間に送信キューを処理する
+        poll.set_read_timeout(Some(POLL_INTERVAL)).map_err(|e| e.to_string())?;
+        return Ok(socket);
+    }
+    Err(last_error)
+}
+
+/// WebSocketクライアント
+///
+/// 1つのクライアントは同時に1つの接続を持ちます。`close`の後は再び`connect`できます。
+///
+/// # Example
+/// ```
+/// let client = WebSocketClient::new(listener, None)?;
+/// client.connect("wss://example.com/socket".to_string(), headers)?;
+/// // listenerのon_state_changedでOpenを受け取った後
+/// client.send_text("hello".to_string())?;
+/// client.close(1000, "bye".to_string())?;
+/// ```
+#[derive(uniffi::Object)]
+pub struct WebSocketClient {
+    shared: Arc<Shared>,
+}
+
+impl WebSocketClient {
+    /// 開いている接続の送信キューに指示を追加します
+    fn enqueue(&self, command: Command) -> Result<(), WebSocketError> {
+        let commands = self.shared.commands.lock()
+            .map_err(|_| WebSocketError::MutexPoisoned)?;
+        commands
+            .as_ref()
+            .and_then(|sender| sender.send(command).ok())
+            .ok_or(WebSocketError::NotConnected)
+    }
+}
+
+#[uniffi::export]
+impl WebSocketClient {
+    /// クライアントを作成します
+    ///
+    /// # Arguments
+    /// * `listener` - メッセージと状態の変化を受け取るリスナー
+    /// * `reconnect` - 自動再接続の設定（`None`の場合は既定値）
+    ///
+    /// # Errors
+    /// * `WebSocketError::InvalidOptions` - 待ち時間の倍率が1未満、または有限でない場合
+    #[uniffi::constructor(default(reconnect = None))]
+    pub fn new(
+        listener: Arc<dyn WebSocketListener>,
+        reconnect: Option<ReconnectOptions>,
+    ) -> Result<Arc<Self>, WebSocketError> {
+        let reconnect = reconnect.unwrap_or_default();
+        if !reconnect.multiplier.is_finite() || reconnect.multiplier < 1.0 {
+            return Err(WebSocketError::InvalidOptions(format!(
+                "multiplier must be at least 1.0: {}",
+                reconnect.multiplier
+            )));
+        }
+        Ok(Arc::new(Self {
+            shared: Arc::new(Shared {
+                listener,
+                reconnect,
+                state: Mutex::new(WebSocketState::Closed {
+                    code: None,
+                    reason: String::new(),
+                }),
+                commands: Mutex::new(None),
+                closing: AtomicBool::new(false),
+            }),
+        }))
+    }
+
+    /// サーバーへの接続をバックグラウンドで開始します
+    ///
+    /// 接続の結果は`on_state_changed`で通知されます。
+    ///
+    /// # Arguments
+    /// * `url` - 接続先の`ws://`または`wss://`のURL
+    /// * `headers` - ハンドシェイクに追加するヘッダー（認証やサブプロトコルなど）
+    ///
+    /// # Errors
+    /// * `WebSocketError::InvalidUrl` - URLが不正な場合
+    /// * `WebSocketError::InvalidHeader` - ヘッダーが不正な場合
+    /// * `WebSocketError::AlreadyConnected` - 既に接続中・接続済みの場合
+    /// * `WebSocketError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn connect(
+        &self,
+        url: String,
+        headers: HashMap<String, String>,
+    ) -> Result<(), WebSocketError> {
+        let lower = url.to_ascii_lowercase();
+        if !lower.starts_with("wss://") && !lower.starts_with("ws://") {
+            return Err(WebSocketError::InvalidUrl(url));
+        }
+        let mut request = url
+            .as_str()
+            .into_client_request()
+            .map_err(|e| WebSocketError::InvalidUrl(e.to_string()))?;
+        for (name, value) in headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| WebSocketError::InvalidHeader(name.clone()))?;
+            let header_value = HeaderValue::from_str(&value)
+                .map_err(|_| WebSocketError::InvalidHeader(name.clone()))?;
+            request.headers_mut().insert(header_name, header_value);
+        }
+
+        {
+            let mut state = self.shared.state.lock()
+                .map_err(|_| WebSocketError::MutexPoisoned)?;
+            if !matches!(*state, WebSocketState::Closed { .. }) {
+                return Err(WebSocketError::AlreadyConnected);
+            }
+            *state = WebSocketState::Connecting;
+        }
+        self.shared.closing.store(false, Ordering::SeqCst);
+        self.shared.listener.on_state_changed(WebSocketState::Connecting);
+        let shared = Arc::clone(&self.shared);
+        let spawned = thread::Builder::new()
+            .name("mobile-websocket".to_string())
+            .spawn(move || shared.run(request));
+        if let Err(e) = spawned {
+            self.shared.set_state(WebSocketState::Closed {
+                code: None,
+                reason: e.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// テキストメッセージを送信します
+    ///
+    /// # Errors
+    /// * `WebSocketError::NotConnected` - 接続が開いていない場合
+    /// * `WebSocketError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn send_text(&self, text: String) -> Result<(), WebSocketError> {
+        self.enqueue(Command::Send(Message::text(text)))
+    }
+
+    /// バイナリメッセージを送信します
+    ///
+    /// # Errors
+    /// * `send_text`と同じエラー
+    pub fn send_binary(&self, data: Vec<u8>) -> Result<(), WebSocketError> {
+        self.enqueue(Command::Send(Message::binary(data)))
+    }
+
+    /// Pingを送信します（最大125バイト）
+    ///
+    /// # Errors
+    /// * `send_text`と同じエラー
+    pub fn send_ping(&self, payload: Vec<u8>) -> Result<(), WebSocketError> {
+        self.enqueue(Command::Send(Message::Ping(payload.into())))
+    }
+
+    /// 接続を閉じ、自動再接続を停止します
+    ///
+    /// 接続が開いている場合はクローズフレームを送信し、サーバーの応答を待って切断します。
+    /// 3秒以内に応答がない場合は、応答を待たずに接続を破棄します。
+    /// 切断が完了すると`WebSocketState::Closed`が通知されます。
+    ///
+    /// # Arguments
+    /// * `code` - クローズコード（既定値は1000）
+    /// * `reason` - 切断の理由（既定値は空文字列）
+    ///
+    /// # Errors
+    /// * `WebSocketError::MutexPoisoned` - 内部Mutexが破損している場合
+    #[uniffi::method(default(code = 1000, reason = ""))]
+    pub fn close(&self, code: u16, reason: String) -> Result<(), WebSocketError> {
+        self.shared.closing.store(true, Ordering::SeqCst);
+        match self.enqueue(Command::Close(code, reason)) {
+            Ok(()) | Err(WebSocketError::NotConnected) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 現在の接続の状態を返します
+    ///
+    /// # Errors
+    /// * `WebSocketError::MutexPoisoned` - 内部Mutexが破損している場合
+    pub fn state(&self) -> Result<WebSocketState, WebSocketError> {
+        let state = self.shared.state.lock()
+            .map_err(|_| WebSocketError::MutexPoisoned)?;
+        Ok(state.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+
+    #[derive(Default)]
+    struct RecordingListener {
+        states: Mutex<Vec<WebSocketState>>,
+        texts: Mutex<Vec<String>>,
+        binaries: Mutex<Vec<Vec<u8>>>,
+        pings: Mutex<Vec<Vec<u8>>>,
+    }
+
+    impl WebSocketListener for RecordingListener {
+        fn on_state_changed(&self, state: WebSocketState) {
+            self.states.lock().unwrap().push(state);
+        }
+
+        fn on_text_message(&self, text: String) {
+            self.texts.lock().unwrap().push(text);
+        }
+
+        fn on_binary_message(&self, data: Vec<u8>) {
+            self.binaries.lock().unwrap().push(data);
+        }
+
+        fn on_ping(&self, payload: Vec<u8>) {
+            self.pings.lock().unwrap().push(payload);
+        }
+    }
+
+    /// 条件が満たされるまで最大5秒待ちます
+    fn wait_until(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "timed out");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn is_open(client: &WebSocketClient) -> bool {
+        client.state().unwrap() == WebSocketState::Open
+    }
+
+    #[test]
+    fn test_websocket_send_receive_and_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut head = [0u8; 1024];
+            let len = stream.peek(&mut head).unwrap();
+            let head = String::from_utf8_lossy(&head[..len]).to_lowercase();
+            assert!(head.contains("authorization: bearer token"));
+            let mut socket = tungstenite::accept(stream).unwrap();
+            socket.send(Message::Ping(b"hb".to_vec().into())).unwrap();
+            loop {
+                match socket.read() {
+                    Ok(Message::Text(text)) => socket.send(Message::text(text)).unwrap(),
+                    Ok(Message::Binary(data)) => socket.send(Message::binary(data)).unwrap(),
+                    Ok(_) => {}
+                    Err(_) => break,
+                }
+            }
+        });
+
+        let recorder = Arc::new(RecordingListener::default());
+        let client = WebSocketClient::new(recorder.clone(), None).unwrap();
+        let headers = HashMap::from([("Authorization".to_string(), "Bearer token".to_string())]);
+        client.connect(format!("ws://{}/socket", addr), headers).unwrap();
+        wait_until(|| is_open(&client));
+        match client.connect(format!("ws://{}/socket", addr), HashMap::new()) {
+            Err(WebSocketError::AlreadyConnected) => (),
+            other => panic!("Expected AlreadyConnected error, got {:?}", other),
+        }
+
+        client.send_text("こんにちは".to_string()).unwrap();
+        client.send_binary(vec![1, 2, 3]).unwrap();
+        wait_until(|| !recorder.binaries.lock().unwrap().is_empty());
+        assert_eq!(*recorder.texts.lock().unwrap(), vec!["こんにちは".to_string()]);
+        assert_eq!(*recorder.binaries.lock().unwrap(), vec![vec![1, 2, 3]]);
+        assert_eq!(*recorder.pings.lock().unwrap(), vec![b"hb".to_vec()]);
+
+        client.close(1000, "bye".to_string()).unwrap();
+        wait_until(|| matches!(client.state().unwrap(), WebSocketState::Closed { .. }));
+        assert_eq!(
+            client.state().unwrap(),
+            WebSocketState::Closed { code: Some(1000), reason: "bye".to_string() }
+        );
+        assert_eq!(recorder.states.lock().unwrap()[..2], [
+            WebSocketState::Connecting,
+            WebSocketState::Open
+        ]);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_websocket_close_times_out_without_reply() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            // クローズフレームに応答せず、クライアントが切断するまで読み捨てる
+            let mut buf = [0u8; 256];
+            while socket.get_mut().read(&mut buf).is_ok_and(|len| len > 0) {}
+        });
+
+        let recorder = Arc::new(RecordingListener::default());
+        let client = WebSocketClient::new(recorder, None).unwrap();
+        client.connect(format!("ws://{}/", addr), HashMap::new()).unwrap();
+        wait_until(|| is_open(&client));
+        let started = Instant::now();
+        client.close(1000, "bye".to_string()).unwrap();
+        server.join().unwrap();
+        assert!(started.elapsed() >= CLOSE_TIMEOUT);
+        wait_until(|| matches!(client.state().unwrap(), WebSocketState::Closed { .. }));
+        assert_eq!(
+            client.state().unwrap(),
+            WebSocketState::Closed { code: None, reason: "close handshake timed out".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_websocket_reconnects_after_disconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            // 最初の接続はハンドシェイク直後に切断する
+            let (stream, _) = listener.accept().unwrap();
+            drop(tungstenite::accept(stream).unwrap());
+            let (stream, _) = listener.accept().unwrap();
+            let mut socket = tungstenite::accept(stream).unwrap();
+            socket.send(Message::text("welcome back")).unwrap();
+            while socket.read().is_ok() {}
+        });
+
+        let recorder = Arc::new(RecordingListener::default());
+        let options = ReconnectOptions {
+            max_attempts: 3,
+            initial_delay_ms: 50,
+            max_delay_ms: 200,
+            multiplier: 2.0,
+        };
+        let client = WebSocketClient::new(recorder.clone(), Some(options)).unwrap();
+        client.connect(format!("ws://{}/", addr), HashMap::new()).unwrap();
+        wait_until(|| !recorder.texts.lock().unwrap().is_empty());
+        assert_eq!(recorder.texts.lock().unwrap()[0], "welcome back");
+        let states = recorder.states.lock().unwrap().clone();
+        assert!(states.iter().any(|state| matches!(
+            state,
+            WebSocketState::Reconnecting { attempt: 1, delay_ms: 50, .. }
+        )));
+        assert_eq!(states.last(), Some(&WebSocketState::Open));
+        client.close(1000, String::new()).unwrap();
+        wait_until(|| matches!(client.state().unwrap(), WebSocketState::Closed { .. }));
+    }
+
+    #[test]
+    fn test_websocket_gives_up_after_max_attempts() {
+        // 接続を受け付けないポート
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let recorder = Arc::new(RecordingListener::default());
+        let options = ReconnectOptions {
+            max_attempts: 2,
+            initial_delay_ms: 10,
+            max_delay_ms: 10,
+            multiplier: 1.0,
+        };
+        let client = WebSocketClient::new(recorder.clone(), Some(options)).unwrap();
+        client.connect(format!("ws://{}/", addr), HashMap::new()).unwrap();
+        wait_until(|| matches!(client.state().unwrap(), WebSocketState::Closed { .. }));
+        let states = recorder.states.lock().unwrap();
+        let reconnects =
+            states.iter().filter(|s| matches!(s, WebSocketState::Reconnecting { .. })).count();
+        assert_eq!(reconnects, 2);
+    }
+
+    #[test]
+    fn test_websocket_errors() {
+        let recorder = Arc::new(RecordingListener::default());
+        let client = WebSocketClient::new(recorder.clone(), None).unwrap();
+        match client.send_text("hi".to_string()) {
+            Err(WebSocketError::NotConnected) => (),
+            other => panic!("Expected NotConnected error, got {:?}", other),
+        }
+        match client.connect("https://example.com".to_string(), HashMap::new()) {
+            Err(WebSocketError::InvalidUrl(_)) => (),
+            other => panic!("Expected InvalidUrl error, got {:?}", other),
+        }
+        let headers = HashMap::from([("bad header".to_string(), "x".to_string())]);
+        match client.connect("ws://127.0.0.1:9/".to_string(), headers) {
+            Err(WebSocketError::InvalidHeader(_)) => (),
+            other => panic!("Expected InvalidHeader error, got {:?}", other),
+        }
+        let options = ReconnectOptions { multiplier: 0.5, ..Default::default() };
+        match WebSocketClient::new(recorder, Some(options)) {
+            Err(WebSocketError::InvalidOptions(_)) => (),
+            other => panic!("Expected InvalidOptions error, got {:?}", other.map(|_| ())),
+        }
+        assert_eq!(ReconnectOptions::default().delay(1), Duration::from_millis(500));
+        assert_eq!(ReconnectOptions::default().delay(3), Duration::from_millis(2000));
+        assert_eq!(ReconnectOptions::default().delay(20), Duration::from_millis(30_000));
+    }
+}
@@ -0,0 +1,554 @@
+//! XML解析モジュール
+//!
+//! このモジュールは、XML文字列を解析して走査可能なツリー（`XmlNode`）を返す
+//! `parse_xml`をエクスポートします。XMLを返す既存のパートナーAPIの
+//! レスポンスを共通コアで読み取るために使用します。
+//!
+//! 要素の名前は名前空間の接頭辞を除いたローカル名で扱い、名前空間URIは
+//! `namespace`で取得できます。外部エンティティの展開による攻撃を避けるため、
+//! DTDを含む文書はエラーになります。
+//!
+//! `XmlNode::query`は、XPathの次のサブセットに対応しています。
+//!
+//! | 構文 | 意味 |
+//! |------|------|
+//! | `/a/b` | この要素を文書のルートとみなした絶対パス |
+//! | `a/b` | この要素からの相対パス |
+//! | `//b` / `a//b` | すべての子孫要素からの選択 |
+//! | `*` | すべての子要素 |
+//! | `.` | この要素自身 |
+//! | `[1]` / `[last()]` | 同じ親を持つ候補の中での位置（1始まり） |
+//! | `[@id]` / `[@id='x']` | 属性の有無・値による絞り込み |
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use thiserror::Error;
+
+/// 要素の入れ子の最大の深さ
+///
+/// roxmltreeの解析とツリーの構築・走査は再帰で行うため、深い入れ子はスタックを溢れさせます。
+const MAX_DEPTH: usize = 256;
+
+/// XML処理で発生する可能性のあるエラー
+#[derive(Debug, Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum XmlError {
+    /// XMLとして不正な文字列の場合（位置は1始まりの行・列）
+    #[error("Invalid XML at line {line}, column {column}: {message}")]
+    InvalidXml { line: u64, column: u64, message: String },
+    /// クエリのパスが不正な場合
+    #[error("Invalid query path at byte {position}: {message}")]
+    InvalidPath { position: u64, message: String },
+}
+
+/// XML要素の属性
+#[derive(Debug, Clone, PartialEq, uniffi::Record)]
+pub struct XmlAttribute {
+    /// 接頭辞を除いた属性名
+    pub name: String,
+    /// 属性の名前空間URI
+    pub namespace: Option<String>,
+    /// 属性値（実体参照は展開済み）
+    pub value: String,
+}
+
+/// 要素の内容（子要素またはテキスト）
+#[derive(Debug)]
+enum XmlContent {
+    Element(Arc<XmlNode>),
+    Text(String),
+}
+
+/// XMLの要素
+///
+/// 解析後のツリーは変更できないため、複数のスレッドから安全にアクセスできます。
+///
+/// # Example
+/// ```
+/// let root = parse_xml(response)?;
+/// for book in root.query("/catalog/book[@lang='ja']".to_string())? {
+///     let title = book.child("title".to_string()).map(|t| t.text());
+/// }
+/// ```
+#[derive(Debug, uniffi::Object)]
+pub struct XmlNode {
+    name: String,
+    namespace: Option<String>,
+    attributes: Vec<XmlAttribute>,
+    content: Vec<XmlContent>,
+}
+
+impl XmlNode {
+    /// roxmltreeの要素から所有権を持つツリーを構築します
+    fn from_element(element: roxmltree::Node) -> Arc<Self> {
+        let attributes = element
+            .attributes()
+            .map(|attribute| XmlAttribute {
+                name: attribute.name().to_string(),
+                namespace: attribute.namespace().map(str::to_string),
+                value: attribute.value().to_string(),
+            })
+            .collect();
+        let content = element
+            .children()
+            .filter_map(|child| {
+                if child.is_element() {
+                    Some(XmlContent::Element(Self::from_element(child)))
+                } else {
+                    child.text().map(|text| XmlContent::Text(text.to_string()))
+                }
+            })
+            .collect();
+        Arc::new(Self {
+            name: element.tag_name().name().to_string(),
+            namespace: element.tag_name().namespace().map(str::to_string),
+            attributes,
+            content,
+        })
+    }
+
+    /// 子要素を順に返します
+    fn child_elements(&self) -> impl Iterator<Item = &Arc<XmlNode>> {
+        self.content.iter().filter_map(|content| match content {
+            XmlContent::Element(element) => Some(element),
+            XmlContent::Text(_) => None,
+        })
+    }
+
+    /// 子孫のテキストを連結します
+    fn collect_text(&self, output: &mut String) {
+        for content in &self.content {
+            match content {
+                XmlContent::Element(element) => element.collect_text(output),
+                XmlContent::Text(text) => output.push_str(text),
+            }
+        }
+    }
+}
+
+/// パスの1ステップで選択する要素
+#[derive(Debug, Clone, PartialEq)]
+enum NodeTest {
+    /// 指定した名前の子要素
+    Name(String),
+    /// すべての子要素
+    Any,
+    /// 要素自身
+    SelfNode,
+}
+
+/// パスの述語（`[...]`）
+#[derive(Debug, Clone, PartialEq)]
+enum Predicate {
+    /// 候補の中での位置（1始まり）
+    Position(usize),
+    /// 候補の中の最後の要素
+    Last,
+    /// 属性を持つ要素
+    HasAttribute(String),
+    /// 属性が指定した値の要素
+    AttributeEquals(String, String),
+}
+
+/// パスの1ステップ（`recursive`は`//`による子孫の探索）
+#[derive(Debug, Clone, PartialEq)]
+struct Step {
+    recursive: bool,
+    test: NodeTest,
+    predicates: Vec<Predicate>,
+}
+
+fn path_error(position: usize, message: &str) -> XmlError {
+    XmlError::InvalidPath { position: position as u64, message: message.to_string() }
+}
+
+/// 述語の中身を解析します
+fn parse_predicate(content: &str, position: usize) -> Result<Predicate, XmlError> {
+    let content = content.trim();
+    if content == "last()" {
+        return Ok(Predicate::Last);
+    }
+    if let Some(attribute) = content.strip_prefix('@') {
+        let Some((name, value)) = attribute.split_once('=') else {
+            return Ok(Predicate::HasAttribute(attribute.trim().to_string()));
+        };
+        let value = value.trim();
+        let unquoted = value
+            .strip_prefix('\'')
+            .and_then(|v| v.strip_suffix('\''))
+            .or_else(|| value.strip_prefix('"').and_then(|v| v.strip_suffix('"')))
+            .ok_or_else(|| path_error(position, "attribute value must be quoted"))?;
+        return Ok(Predicate::AttributeEquals(name.trim().to_string(), unquoted.to_string()));
+    }
+    match content.parse::<usize>() {
+        Ok(index) if index > 0 => Ok(Predicate::Position(index)),
+        _ => Err(path_error(position, "unsupported predicate")),
+    }
+}
+
+/// パスを(絶対パスかどうか, ステップ)に解析します
+fn parse_path(path: &str) -> Result<(bool, Vec<Step>), XmlError> {
+    let bytes = path.as_bytes();
+    let absolute = path.starts_with('/');
+    let mut steps = Vec::new();
+    let mut pos = 0;
+    loop {
+        let mut recursive = false;
+        if path[pos..].starts_with("//") {
+            recursive = true;
+            pos += 2;
+        } else if path[pos..].starts_with('/') {
+            pos += 1;
+        } else if pos > 0 {
+            return Err(path_error(pos, "expected '/'"));
+        }
+        let start = pos;
+        while pos < bytes.len() && bytes[pos] != b'/' && bytes[pos] != b'[' {
+            pos += 1;
+        }
+        let test = match path[start..pos].trim() {
+            "" => return Err(path_error(start, "empty step")),
+            "*" => NodeTest::Any,
+            "." => NodeTest::SelfNode,
+            ".." => return Err(path_error(start, "parent steps are not supported")),
+            name => NodeTest::Name(name.to_string()),
+        };
+        let mut predicates = Vec::new();
+        while pos < bytes.len() && bytes[pos] == b'[' {
+            let mut quote = None;
+            let mut end = pos + 1;
+            while end < bytes.len() && (quote.is_some() || bytes[end] != b']') {
+                match (quote, bytes[end]) {
+                    (None, b'\'' | b'"') => quote = Some(bytes[end]),
+                    (Some(q), b) if q == b => quote = None,
+                    _ => (),
+                }
+                end += 1;
+            }
+            if end == bytes.len() {
+                return Err(path_error(pos, "unclosed bracket"));
+            }
+            predicates.push(parse_predicate(&path[pos + 1..end], pos)?);
+            pos = end + 1;
+        }
+        steps.push(Step { recursive, test, predicates });
+        if pos == bytes.len() {
+            return Ok((absolute, steps));
+        }
+    }
+}
+
+/// 要素自身とすべての子孫要素を文書順に集めます
+fn collect_descendants_or_self(node: &Arc<XmlNode>, output: &mut Vec<Arc<XmlNode>>) {
+    output.push(Arc::clone(node));
+    for child in node.child_elements() {
+        collect_descendants_or_self(child, output);
+    }
+}
+
+/// 1つのコンテキストに対してステップを適用します
+fn apply_step(step: &Step, context: &Arc<XmlNode>) -> Vec<Arc<XmlNode>> {
+    let mut candidates: Vec<Arc<XmlNode>> = match &step.test {
+        NodeTest::SelfNode => vec![Arc::clone(context)],
+        NodeTest::Any => context.child_elements().cloned().collect(),
+        NodeTest::Name(name) => {
+            context.child_elements().filter(|child| &child.name == name).cloned().collect()
+        }
+    };
+    for predicate in &step.predicates {
+        candidates = match predicate {
+            Predicate::Position(index) => candidates.into_iter().skip(index - 1).take(1).collect(),
+            Predicate::Last => candidates.pop().into_iter().collect(),
+            Predicate::HasAttribute(name) => candidates
+                .into_iter()
+                .filter(|node| node.attributes.iter().any(|a| &a.name == name))
+                .collect(),
+            Predicate::AttributeEquals(name, value) => candidates
+                .into_iter()
+                .filter(|node| node.attributes.iter().any(|a| &a.name == name && &a.value == value))
+                .collect(),
+        };
+    }
+    candidates
+}
+
+#[uniffi::export]
+impl XmlNode {
+    /// 接頭辞を除いた要素名を返します
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// 要素の名前空間URIを返します
+    pub fn namespace(&self) -> Option<String> {
+        self.namespace.clone()
+    }
+
+    /// すべての属性を文書中の順序で返します
+    pub fn attributes(&self) -> Vec<XmlAttribute> {
+        self.attributes.clone()
+    }
+
+    /// 指定した名前（接頭辞を除く）の属性値を返します
+    ///
+    /// # Arguments
+    /// * `name` - 属性名
+    pub fn attribute(&self, name: String) -> Option<String> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value.clone())
+    }
+
+    /// 子要素を文書中の順序で返します
+    pub fn children(&self) -> Vec<Arc<XmlNode>> {
+        self.child_elements().cloned().collect()
+    }
+
+    /// 指定した名前の最初の子要素を返します
+    ///
+    /// # Arguments
+    /// * `name` - 要素名（接頭辞を除く）
+    pub fn child(&self, name: String) -> Option<Arc<XmlNode>> {
+        self.child_elements().find(|child| child.name == name).cloned()
+    }
+
+    /// 子孫のテキスト（CDATAを含む）を文書中の順序で連結して返します
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.collect_text(&mut text);
+        text
+    }
+
+    /// XPathのサブセットで要素を検索します
+    ///
+    /// 対応する構文はモジュールのドキュメントを参照してください。
+    /// 結果は重複を除いた要素の一覧です。
+    ///
+    /// # Arguments
+    /// * `path` - 検索するパス
+    ///
+    /// # Errors
+    /// * `XmlError::InvalidPath` - パスが不正、または未対応の構文を含む場合
+    pub fn query(self: Arc<Self>, path: String) -> Result<Vec<Arc<XmlNode>>, XmlError> {
+        let (absolute, steps) = parse_path(&path)?;
+        let mut contexts = if absolute {
+            // この要素だけを子に持つ仮の文書ノードから辿る
+            vec![Arc::new(XmlNode {
+                name: String::new(),
+                namespace: None,
+                attributes: Vec::new(),
+                content: vec![XmlContent::Element(Arc::clone(&self))],
+            })]
+        } else {
+            vec![self]
+        };
+        for step in &steps {
+            if step.recursive {
+                let mut expanded = Vec::new();
+                for context in &contexts {
+                    collect_descendants_or_self(context, &mut expanded);
+                }
+                contexts = expanded;
+            }
+            let mut seen = HashSet::new();
+            contexts = contexts
+                .iter()
+                .flat_map(|context| apply_step(step, context))
+                .filter(|node| seen.insert(Arc::as_ptr(node)))
+                .collect();
+        }
+        Ok(contexts)
+    }
+}
+
+/// 要素の入れ子が`MAX_DEPTH`を超えないことを解析の前に確認します
+///
+/// タグだけを読み飛ばす簡易的な走査のため、不正なXMLの検出はroxmltreeに任せます。
+fn check_depth(text: &str) -> Result<(), XmlError> {
+    let bytes = text.as_bytes();
+    let find = |from: usize, pattern: &str| {
+        text.get(from..).and_then(|rest| rest.find(pattern)).map(|index| from + index)
+    };
+    let mut depth = 0usize;
+    let mut position = 0;
+    while let Some(start) = find(position, "<") {
+        let rest = &text[start..];
+        let end = if rest.starts_with("<!--") {
+            find(start + 4, "-->").map(|end| end + 3)
+        } else if rest.starts_with("<![CDATA[") {
+            find(start + 9, "]]>").map(|end| end + 3)
+        } else if rest.starts_with("<?") {
+            find(start + 2, "?>").map(|end| end + 2)
+        } else if rest.starts_with("</") {
+            depth = depth.saturating_sub(1);
+            find(start + 2, ">").map(|end| end + 1)
+        } else if rest.starts_with("<!") {
+            find(start + 2, ">").map(|end| end + 1)
+        } else {
+            // 属性値の中の`>`は読み飛ばす
+            let mut quote = None;
+            let end = bytes[start + 1..].iter().position(|&b| match quote {
+                Some(q) => {
+                    if b == q {
+                        quote = None;
+                    }
+                    false
+                }
+                None if b == b'"' || b == b'\'' => {
+                    quote = Some(b);
+                    false
+                }
+                None => b == b'>',
+            });
+            end.map(|end| {
+                let end = start + 1 + end;
+                if bytes[end - 1] != b'/' {
+                    depth += 1;
+                }
+                end + 1
+            })
+        };
+        if depth > MAX_DEPTH {
+            let line_start = text[..start].rfind('\n').map_or(0, |index| index + 1);
+            return Err(XmlError::InvalidXml {
+                line: text[..start].matches('\n').count() as u64 + 1,
+                column: text[line_start..start].chars().count() as u64 + 1,
+                message: format!("elements are nested deeper than {}", MAX_DEPTH),
+            });
+        }
+        match end {
+            Some(end) => position = end,
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// XML文字列を解析してルート要素を返します
+///
+/// # Arguments
+/// * `text` - 解析するXML文字列
+///
+/// # Errors
+/// * `XmlError::InvalidXml` - XMLとして不正な場合、DTDを含む場合、
+///   または要素の入れ子が256段より深い場合
+///
+/// # Example
+/// ```
+/// let root = parse_xml("<user id=\"1\"><name>Alice</name></user>".to_string())?;
+/// assert_eq!(root.attribute("id".to_string()), Some("1".to_string()));
+/// assert_eq!(root.child("name".to_string()).unwrap().text(), "Alice");
+/// ```
+#[uniffi::export]
+pub fn parse_xml(text: String) -> Result<Arc<XmlNode>, XmlError> {
+    check_depth(&text)?;
+    let document = roxmltree::Document::parse(&text).map_err(|e| {
+        let position = e.pos();
+        XmlError::InvalidXml {
+            line: u64::from(position.row),
+            column: u64::from(position.col),
+            message: e.to_string(),
+        }
+    })?;
+    Ok(XmlNode::from_element(document.root_element()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CATALOG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<catalog xmlns:x="urn:example">
+  <book id="1" lang="ja"><title>吾輩は猫である</title><price>500</price></book>
+  <book id="2" lang="en"><title>Kokoro &amp; <![CDATA[<Other>]]></title></book>
+  <x:magazine id="3"><title>Monthly</title></x:magazine>
+</catalog>"#;
+
+    fn ids(nodes: &[Arc<XmlNode>]) -> Vec<String> {
+        nodes.iter().map(|node| node.attribute("id".to_string()).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_parse_xml_accessors() {
+        let root = parse_xml(CATALOG.to_string()).unwrap();
+        assert_eq!(root.name(), "catalog");
+        assert_eq!(root.children().len(), 3);
+
+        let book = root.child("book".to_string()).unwrap();
+        assert_eq!(book.attribute("lang".to_string()), Some("ja".to_string()));
+        assert_eq!(book.attributes().len(), 2);
+        assert_eq!(book.text(), "吾輩は猫である500");
+
+        let magazine = root.child("magazine".to_string()).unwrap();
+        assert_eq!(magazine.namespace(), Some("urn:example".to_string()));
+        assert!(root.child("missing".to_string()).is_none());
+    }
+
+    #[test]
+    fn test_parse_xml_text_with_entities_and_cdata() {
+        let root = parse_xml(CATALOG.to_string()).unwrap();
+        let titles = root.query("book[2]/title".to_string()).unwrap();
+        assert_eq!(titles[0].text(), "Kokoro & <Other>");
+    }
+
+    #[test]
+    fn test_xml_query_paths() {
+        let root = parse_xml(CATALOG.to_string()).unwrap();
+        assert_eq!(ids(&root.clone().query("/catalog/book".to_string()).unwrap()), ["1", "2"]);
+        assert_eq!(ids(&root.clone().query("*".to_string()).unwrap()), ["1", "2", "3"]);
+        assert_eq!(ids(&root.clone().query("book[last()]".to_string()).unwrap()), ["2"]);
+        assert_eq!(ids(&root.clone().query("//*[@lang='en']".to_string()).unwrap()), ["2"]);
+        assert_eq!(root.clone().query("//title".to_string()).unwrap().len(), 3);
+        assert_eq!(root.clone().query("./book/title".to_string()).unwrap().len(), 2);
+        assert!(root.query("/book".to_string()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_xml_query_recursive_without_duplicates() {
+        let root = parse_xml("<a><a><b/></a><b/></a>".to_string()).unwrap();
+        assert_eq!(root.clone().query("//a//b".to_string()).unwrap().len(), 2);
+        assert_eq!(root.query("//b[1]".to_string()).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_xml_errors() {
+        match parse_xml("<a>\n  <b></a>".to_string()) {
+            Err(XmlError::InvalidXml { line: 2, .. }) => (),
+            other => panic!("Expected InvalidXml error, got {:?}", other),
+        }
+        match parse_xml("<!DOCTYPE a [<!ENTITY x \"y\">]><a>&x;</a>".to_string()) {
+            Err(XmlError::InvalidXml { .. }) => (),
+            other => panic!("Expected InvalidXml error, got {:?}", other),
+        }
+        let root = parse_xml("<a/>".to_string()).unwrap();
+        for path in ["", "a//", "a[@id=x]", "a[0]", "../b", "a[1"] {
+            match root.clone().query(path.to_string()) {
+                Err(XmlError::InvalidPath { .. }) => (),
+                other => panic!("Expected InvalidPath error for {:?}, got {:?}", path, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_xml_depth_limit() {
+        // デバッグビルドは再帰1段あたりのスタックが大きいため、上限の深さの確認は大きなスタックで行う
+        let nested = |depth: usize| format!("{}{}", "<a>".repeat(depth), "</a>".repeat(depth));
+        let root = std::thread::Builder::new()
+            .stack_size(32 * 1024 * 1024)
+            .spawn(move || parse_xml(nested(MAX_DEPTH)).unwrap())
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(root.query("//a".to_string()).unwrap().len(), MAX_DEPTH);
+        match parse_xml(nested(MAX_DEPTH + 1)) {
+            Err(XmlError::InvalidXml { line: 1, column, .. }) => {
+                assert_eq!(column, 3 * MAX_DEPTH as u64 + 1)
+            }
+            other => panic!("Expected InvalidXml error, got {:?}", other),
+        }
+        // 閉じていない深い入れ子もスタックを溢れさせずにエラーになる
+        assert!(parse_xml("<a>".repeat(100_000)).is_err());
+    }
+}